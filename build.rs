@@ -1,4 +1,37 @@
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
+    // Build info shown by /version. Docker builds have no .git directory, so the
+    // commit can also be supplied through the GIT_COMMIT environment variable.
+    let git_commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.is_empty())
+        .map(|commit| commit.chars().take(10).collect())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short=10", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|commit| commit.trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    let build_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=PIXIVBOT_GIT_COMMIT={}", git_commit);
+    println!(
+        "cargo:rustc-env=PIXIVBOT_BUILD_TIMESTAMP={}",
+        build_timestamp
+    );
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
     // When statically linking FFmpeg on Windows (e.g. via vcpkg), the avcodec
     // library's Media Foundation encoder references COM interfaces that live in
     // Windows SDK system libraries. Link them explicitly so the linker can
@@ -4,6 +4,8 @@ use crate::archive_download::{
 use crate::error::{Error, Result};
 use crate::models::{EhCookies, EhGallery, EhGalleryRef, RawApiResponse, RawGalleryMetaEntry};
 use crate::parser;
+use crate::rate_limit::{RateLimiter, DEFAULT_REQUESTS_PER_MINUTE};
+use chrono::{DateTime, Utc};
 use reqwest::header::COOKIE;
 use std::path::Path;
 
@@ -30,6 +32,7 @@ pub struct EhClient {
     base_url: String,
     pub(crate) api_url: String,
     cookies: EhCookies,
+    limiter: RateLimiter,
 }
 
 #[derive(Debug, Clone)]
@@ -157,7 +160,15 @@ fn resolve_url(base_url: &str, url: &str) -> String {
 }
 
 impl EhClient {
-    pub fn new(base_url: &str, api_url: &str, cookies: EhCookies) -> Result<Self> {
+    /// `proxy` is an optional SOCKS5/HTTP proxy URL for all requests.
+    /// Site requests are paced at [`DEFAULT_REQUESTS_PER_MINUTE`]; see
+    /// [`EhClient::with_requests_per_minute`].
+    pub fn new(
+        base_url: &str,
+        api_url: &str,
+        cookies: EhCookies,
+        proxy: Option<&str>,
+    ) -> Result<Self> {
         let mut builder = reqwest::Client::builder()
             .user_agent(USER_AGENT_STR)
             .connect_timeout(std::time::Duration::from_secs(ARCHIVE_CONNECT_TIMEOUT_SECS))
@@ -167,6 +178,9 @@ impl EhClient {
         if base_url.contains("exhentai") {
             builder = builder.local_address(std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED));
         }
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
 
         let http = builder.build()?;
         Ok(Self {
@@ -174,9 +188,23 @@ impl EhClient {
             base_url: base_url.to_string(),
             api_url: api_url.to_string(),
             cookies,
+            limiter: RateLimiter::new(DEFAULT_REQUESTS_PER_MINUTE),
         })
     }
 
+    /// Limit requests to E-Hentai pages and the API. `0` disables pacing.
+    /// Image and archive transfers from H@H servers are not counted.
+    pub fn with_requests_per_minute(mut self, requests_per_minute: u32) -> Self {
+        self.limiter = RateLimiter::new(requests_per_minute);
+        self
+    }
+
+    /// When the current IP ban ends, or `None` if not banned.
+    /// While banned, site requests fail with `Error::TemporarilyBanned`.
+    pub fn banned_until(&self) -> Option<DateTime<Utc>> {
+        self.limiter.banned_until()
+    }
+
     /// Build a search URL from query, category bitmask, and page number.
     pub fn build_search_url(&self, query: &str, cats: u32, page: u32) -> String {
         format!(
@@ -198,6 +226,7 @@ impl EhClient {
 
     async fn fetch_archiver_page(&self, gid: u64, token: &str) -> Result<(u64, String, String)> {
         let gallery_url = format!("{}/g/{}/{}/", self.base_url, gid, token);
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .get(&gallery_url)
@@ -213,6 +242,7 @@ impl EhClient {
             });
         }
         let gallery_html = resp.text().await.map_err(archive_http_error)?;
+        self.limiter.detect_ban(&gallery_html)?;
 
         let (archiver_gid, archiver_token) = parser::parse_archiver_url(&gallery_html)
             .ok_or_else(|| Error::Parse("archiver URL not found in gallery page".into()))?;
@@ -221,6 +251,7 @@ impl EhClient {
             "{}/archiver.php?gid={}&token={}",
             self.base_url, archiver_gid, archiver_token
         );
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .get(&archiver_page_url)
@@ -236,6 +267,7 @@ impl EhClient {
             });
         }
         let archiver_html = resp.text().await.map_err(archive_http_error)?;
+        self.limiter.detect_ban(&archiver_html)?;
 
         Ok((archiver_gid, archiver_token, archiver_html))
     }
@@ -243,6 +275,7 @@ impl EhClient {
     /// Search for galleries. Returns gallery references parsed from HTML.
     pub async fn search(&self, query: &str, cats: u32, page: u32) -> Result<Vec<EhGalleryRef>> {
         let url = self.build_search_url(query, cats, page);
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .get(&url)
@@ -257,9 +290,25 @@ impl EhClient {
             });
         }
         let html = resp.text().await?;
+        self.limiter.detect_ban(&html)?;
         Ok(parser::parse_search_results(&html, &self.base_url))
     }
 
+    /// Find the token of a gallery known only by its gid.
+    ///
+    /// The API and gallery pages both need `(gid, token)`, so the gallery is
+    /// looked up with a `gid:` search and the token taken from the matching result.
+    pub async fn resolve_token(&self, gid: u64) -> Result<String> {
+        let refs = self.search(&format!("gid:{}", gid), 0, 0).await?;
+        refs.into_iter()
+            .find(|gallery| gallery.gid == gid)
+            .map(|gallery| gallery.token)
+            .ok_or_else(|| Error::Api {
+                message: format!("gallery {} not found", gid),
+                status: 404,
+            })
+    }
+
     /// Fetch gallery metadata via the api.php JSON endpoint.
     /// Max 25 galleries per request.
     pub async fn get_metadata(&self, gidlist: &[(u64, &str)]) -> Result<Vec<EhGallery>> {
@@ -283,6 +332,7 @@ impl EhClient {
             "namespace": 1
         });
 
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .post(&self.api_url)
@@ -300,7 +350,9 @@ impl EhClient {
             });
         }
 
-        let raw: RawApiResponse = resp.json().await?;
+        let body = resp.text().await?;
+        self.limiter.detect_ban(&body)?;
+        let raw: RawApiResponse = serde_json::from_str(&body)?;
         let galleries = raw
             .gmetadata
             .into_iter()
@@ -460,6 +512,7 @@ impl EhClient {
     ) -> Result<u64> {
         let options = options.validate()?;
         // Step 1: POST to archiver.php to initiate download
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .post(&request.action_url)
@@ -478,6 +531,7 @@ impl EhClient {
         }
 
         let html = resp.text().await.map_err(archive_http_error)?;
+        self.limiter.detect_ban(&html)?;
 
         // Step 2: Parse the JS redirect URL
         let download_url = parser::parse_archive_redirect(&html)
@@ -531,8 +585,30 @@ impl EhClient {
     /// Returns a list of direct image URLs (on H@H servers).
     /// Used for Telegraph page creation without downloading images.
     pub async fn get_gallery_image_urls(&self, gid: u64, token: &str) -> Result<Vec<String>> {
+        self.resolve_gallery_image_urls(gid, token, None).await
+    }
+
+    /// Resolve direct image URLs for only the first `limit` pages of a gallery.
+    /// Used for sample-page previews, so later gallery index pages are never fetched.
+    pub async fn get_gallery_sample_image_urls(
+        &self,
+        gid: u64,
+        token: &str,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        self.resolve_gallery_image_urls(gid, token, Some(limit.max(1)))
+            .await
+    }
+
+    async fn resolve_gallery_image_urls(
+        &self,
+        gid: u64,
+        token: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<String>> {
         // Step 1: Fetch gallery page to get image page URLs and page count
         let gallery_url = format!("{}/g/{}/{}/", self.base_url, gid, token);
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .get(&gallery_url)
@@ -547,6 +623,7 @@ impl EhClient {
             });
         }
         let gallery_html = resp.text().await?;
+        self.limiter.detect_ban(&gallery_html)?;
 
         let total_pages = parser::parse_page_count(&gallery_html).unwrap_or(1);
 
@@ -554,8 +631,12 @@ impl EhClient {
         let mut all_page_urls: Vec<String> = parser::parse_image_page_urls(&gallery_html);
 
         for page_num in 1..total_pages {
+            if limit.is_some_and(|limit| all_page_urls.len() >= limit) {
+                break;
+            }
             tokio::time::sleep(std::time::Duration::from_secs(3)).await;
             let page_url = format!("{}/g/{}/{}/?p={}", self.base_url, gid, token, page_num);
+            self.limiter.acquire().await?;
             let resp = self
                 .http
                 .get(&page_url)
@@ -566,6 +647,7 @@ impl EhClient {
                 break;
             }
             let html = resp.text().await?;
+            self.limiter.detect_ban(&html)?;
             let urls = parser::parse_image_page_urls(&html);
             if urls.is_empty() {
                 break;
@@ -576,6 +658,9 @@ impl EhClient {
         // Dedup preserving order
         let mut seen = std::collections::HashSet::new();
         all_page_urls.retain(|url| seen.insert(url.clone()));
+        if let Some(limit) = limit {
+            all_page_urls.truncate(limit);
+        }
 
         // Normalize relative URLs to absolute
         let all_page_urls: Vec<String> = all_page_urls
@@ -599,6 +684,7 @@ impl EhClient {
             if idx > 0 {
                 tokio::time::sleep(std::time::Duration::from_secs(2)).await;
             }
+            self.limiter.acquire().await?;
             let resp = match self
                 .http
                 .get(page_url.as_str())
@@ -613,6 +699,7 @@ impl EhClient {
                 continue;
             }
             let html = resp.text().await?;
+            self.limiter.detect_ban(&html)?;
             if let Some(src) = parser::parse_image_src(&html) {
                 image_urls.push(src);
             }
@@ -637,6 +724,7 @@ impl EhClient {
     pub async fn download_gallery_images(&self, gid: u64, token: &str, dest: &Path) -> Result<u64> {
         // Step 1: Fetch gallery page to get image page URLs and page count
         let gallery_url = format!("{}/g/{}/{}/", self.base_url, gid, token);
+        self.limiter.acquire().await?;
         let resp = self
             .http
             .get(&gallery_url)
@@ -651,6 +739,7 @@ impl EhClient {
             });
         }
         let gallery_html = resp.text().await?;
+        self.limiter.detect_ban(&gallery_html)?;
 
         let total_pages = parser::parse_page_count(&gallery_html).unwrap_or(1);
 
@@ -662,6 +751,7 @@ impl EhClient {
         for page_num in 1..total_pages {
             tokio::time::sleep(std::time::Duration::from_secs(3)).await;
             let page_url = format!("{}/g/{}/{}/?p={}", self.base_url, gid, token, page_num);
+            self.limiter.acquire().await?;
 
             let resp = match self
                 .http
@@ -700,6 +790,7 @@ impl EhClient {
                     return Err(e.into());
                 }
             };
+            self.limiter.detect_ban(&html)?;
             let urls = parser::parse_image_page_urls(&html);
             if urls.is_empty() {
                 break;
@@ -751,6 +842,11 @@ impl EhClient {
             }
 
             // Fetch image page
+            if let Err(e) = self.limiter.acquire().await {
+                drop(zip_writer);
+                cleanup_paths(&temp_path, dest);
+                return Err(e);
+            }
             let resp = match self
                 .http
                 .get(image_page_url.as_str())
@@ -793,6 +889,12 @@ impl EhClient {
                 }
             };
 
+            if let Err(e) = self.limiter.detect_ban(&html) {
+                drop(zip_writer);
+                cleanup_paths(&temp_path, dest);
+                return Err(e);
+            }
+
             let image_url = match parser::parse_image_src(&html) {
                 Some(u) => u,
                 None => {
@@ -912,6 +1014,7 @@ pub struct EhClientBuilder {
     base_url: String,
     api_url: String,
     cookies: EhCookies,
+    requests_per_minute: u32,
 }
 
 impl Default for EhClientBuilder {
@@ -923,6 +1026,8 @@ impl Default for EhClientBuilder {
                 nw: true,
                 ..Default::default()
             },
+            // Unpaced so tests against mock servers run at full speed
+            requests_per_minute: 0,
         }
     }
 }
@@ -943,9 +1048,14 @@ impl EhClientBuilder {
         self.cookies = c;
         self
     }
+    pub fn requests_per_minute(mut self, n: u32) -> Self {
+        self.requests_per_minute = n;
+        self
+    }
     pub fn build(self) -> EhClient {
-        EhClient::new(&self.base_url, &self.api_url, self.cookies)
+        EhClient::new(&self.base_url, &self.api_url, self.cookies, None)
             .expect("failed to build EhClient")
+            .with_requests_per_minute(self.requests_per_minute)
     }
 }
 
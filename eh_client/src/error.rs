@@ -16,6 +16,11 @@ pub enum Error {
         retry_after_secs: Option<u64>,
     },
     Other(String),
+    /// E-Hentai served its temporary IP ban notice. Requests to the site fail
+    /// with this error without touching the network until `until` has passed.
+    TemporarilyBanned {
+        until: chrono::DateTime<chrono::Utc>,
+    },
     /// Archive download failed but this attempt made real progress (>10KB/s).
     /// Preserve `.part` file for resumption instead of incrementing retry_count.
     DownloadInProgress {
@@ -39,6 +44,9 @@ impl fmt::Display for Error {
                 write!(f, "Rate limited (429), retry after {:?}", retry_after_secs)
             }
             Error::Other(msg) => write!(f, "{}", msg),
+            Error::TemporarilyBanned { until } => {
+                write!(f, "IP temporarily banned by E-Hentai until {}", until)
+            }
             Error::DownloadInProgress { inner, .. } => {
                 write!(f, "download failed but made progress: {}", inner)
             }
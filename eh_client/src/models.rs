@@ -1,3 +1,4 @@
+use crate::tag_translation::TagTranslations;
 use serde::{Deserialize, Serialize};
 
 /// Cookies for e-hentai/exhentai authentication.
@@ -62,6 +63,16 @@ pub struct EhGallery {
     pub tags: Vec<String>,
 }
 
+impl EhGallery {
+    /// Gallery tags rendered through the EhTagTranslation table, in original order.
+    pub fn translated_tags(&self, translations: &TagTranslations) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|tag| translations.translate(tag))
+            .collect()
+    }
+}
+
 /// E-hentai gallery categories with their bitmask values.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EhCategory {
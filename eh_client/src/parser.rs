@@ -493,6 +493,49 @@ pub fn parse_page_count(html: &str) -> Option<u32> {
     })
 }
 
+const IP_BAN_MARKER: &str = "Your IP address has been temporarily banned";
+
+/// The ban notice is served as a short plain-text body instead of the requested
+/// page. Longer bodies are real pages that merely quote the sentence (e.g. a comment).
+const IP_BAN_PAGE_MAX_LEN: usize = 4096;
+
+fn ban_duration_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(\d+)\s+(day|hour|minute|second)s?").expect("invalid ban_duration regex")
+    })
+}
+
+/// Whether a response body is E-Hentai's temporary IP ban notice.
+pub fn is_ip_ban_page(body: &str) -> bool {
+    body.len() <= IP_BAN_PAGE_MAX_LEN && body.contains(IP_BAN_MARKER)
+}
+
+/// Parse the remaining ban time from the ban notice, e.g.
+/// "The ban expires in 23 hours and 59 minutes".
+/// Returns None if the body is not a ban page or carries no expiry.
+pub fn parse_ip_ban_remaining(body: &str) -> Option<std::time::Duration> {
+    if !is_ip_ban_page(body) {
+        return None;
+    }
+    let expiry = &body[body.find("expires in")?..];
+    let expiry = expiry.split('.').next().unwrap_or(expiry);
+    let secs: u64 = ban_duration_re()
+        .captures_iter(expiry)
+        .filter_map(|cap| {
+            let amount = cap.get(1)?.as_str().parse::<u64>().ok()?;
+            let unit = match cap.get(2)?.as_str() {
+                "day" => 24 * 60 * 60,
+                "hour" => 60 * 60,
+                "minute" => 60,
+                _ => 1,
+            };
+            Some(amount * unit)
+        })
+        .sum();
+    (secs > 0).then(|| std::time::Duration::from_secs(secs))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1207,4 +1250,22 @@ mod tests {
         assert_eq!(DownloadCost::Free.gp_amount(), None);
         assert_eq!(DownloadCost::Unknown.gp_amount(), None);
     }
+
+    #[test]
+    fn test_parse_ip_ban_remaining() {
+        let body = "Your IP address has been temporarily banned for excessive pageloads which indicates that you are using automated mirroring/harvesting software. The ban expires in 1 day, 2 hours and 30 seconds";
+        assert!(is_ip_ban_page(body));
+        assert_eq!(
+            parse_ip_ban_remaining(body),
+            Some(std::time::Duration::from_secs(26 * 3600 + 30))
+        );
+
+        let no_expiry = "Your IP address has been temporarily banned for excessive pageloads.";
+        assert!(is_ip_ban_page(no_expiry));
+        assert_eq!(parse_ip_ban_remaining(no_expiry), None);
+
+        assert!(!is_ip_ban_page(SEARCH_HTML_SAMPLE));
+        let quoted = format!("{}{}", IP_BAN_MARKER, " ".repeat(IP_BAN_PAGE_MAX_LEN));
+        assert!(!is_ip_ban_page(&quoted));
+    }
 }
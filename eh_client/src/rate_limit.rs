@@ -0,0 +1,136 @@
+//! Pacing of E-Hentai page loads and the cool-down after an IP ban.
+//!
+//! E-Hentai bans IPs that load pages too quickly. Every request to the site
+//! waits for a slot from [`RateLimiter`], and once a ban notice is seen all
+//! further requests fail with [`Error::TemporarilyBanned`] until it expires.
+
+use crate::error::{Error, Result};
+use crate::parser;
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Requests per minute used by [`crate::EhClient::new`].
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 30;
+
+/// Cool-down when the ban notice does not say when the ban expires.
+const FALLBACK_BAN_SECS: u64 = 60 * 60;
+
+pub(crate) struct RateLimiter {
+    /// Minimum spacing between two requests, `None` when unlimited
+    interval: Option<Duration>,
+    next_slot: Mutex<Instant>,
+    banned_until: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl RateLimiter {
+    /// `requests_per_minute = 0` disables pacing; ban detection still applies.
+    pub(crate) fn new(requests_per_minute: u32) -> Self {
+        let interval =
+            (requests_per_minute > 0).then(|| Duration::from_secs(60) / requests_per_minute);
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+            banned_until: Mutex::new(None),
+        }
+    }
+
+    /// Wait for the next request slot. Fails fast while banned.
+    pub(crate) async fn acquire(&self) -> Result<()> {
+        self.check_ban()?;
+        let Some(interval) = self.interval else {
+            return Ok(());
+        };
+
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+
+        // A concurrent request may have hit the ban while this one waited
+        self.check_ban()
+    }
+
+    /// When the current ban ends, or `None` if not banned.
+    pub(crate) fn banned_until(&self) -> Option<DateTime<Utc>> {
+        let mut banned_until = self.banned_until.lock().unwrap();
+        if banned_until.is_some_and(|until| until <= Utc::now()) {
+            *banned_until = None;
+        }
+        *banned_until
+    }
+
+    fn check_ban(&self) -> Result<()> {
+        match self.banned_until() {
+            Some(until) => Err(Error::TemporarilyBanned { until }),
+            None => Ok(()),
+        }
+    }
+
+    /// Enter the cool-down if `body` is the ban notice.
+    pub(crate) fn detect_ban(&self, body: &str) -> Result<()> {
+        if !parser::is_ip_ban_page(body) {
+            return Ok(());
+        }
+        let remaining =
+            parser::parse_ip_ban_remaining(body).unwrap_or(Duration::from_secs(FALLBACK_BAN_SECS));
+        let until = Utc::now()
+            + chrono::Duration::from_std(remaining)
+                .unwrap_or_else(|_| chrono::Duration::seconds(FALLBACK_BAN_SECS as i64));
+        tracing::warn!(until = %until, "E-Hentai temporarily banned this IP");
+
+        let mut banned_until = self.banned_until.lock().unwrap();
+        let until = banned_until.map_or(until, |current| current.max(until));
+        *banned_until = Some(until);
+        Err(Error::TemporarilyBanned { until })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BAN_PAGE: &str = "Your IP address has been temporarily banned for excessive pageloads which indicates that you are using automated mirroring/harvesting software. The ban expires in 2 hours and 5 minutes";
+
+    #[test]
+    fn test_interval_from_requests_per_minute() {
+        assert_eq!(RateLimiter::new(30).interval, Some(Duration::from_secs(2)));
+        assert_eq!(
+            RateLimiter::new(40).interval,
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(RateLimiter::new(0).interval, None);
+    }
+
+    #[tokio::test]
+    async fn test_ban_page_starts_cool_down() {
+        let limiter = RateLimiter::new(0);
+        limiter.detect_ban("<html>gallery</html>").unwrap();
+        limiter.acquire().await.unwrap();
+
+        let until = match limiter.detect_ban(BAN_PAGE) {
+            Err(Error::TemporarilyBanned { until }) => until,
+            other => panic!("expected ban, got {:?}", other),
+        };
+        let remaining = until - Utc::now();
+        assert!(remaining > chrono::Duration::minutes(124));
+        assert!(remaining <= chrono::Duration::minutes(125));
+        assert_eq!(limiter.banned_until(), Some(until));
+        assert!(matches!(
+            limiter.acquire().await,
+            Err(Error::TemporarilyBanned { .. })
+        ));
+    }
+
+    #[test]
+    fn test_expired_ban_is_cleared() {
+        let limiter = RateLimiter::new(0);
+        *limiter.banned_until.lock().unwrap() = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert_eq!(limiter.banned_until(), None);
+        assert!(limiter.check_ban().is_ok());
+    }
+}
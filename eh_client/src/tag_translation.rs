@@ -0,0 +1,201 @@
+use crate::error::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Latest `db.text.json` release of the EhTagTranslation database.
+pub const DEFAULT_TAG_TRANSLATION_URL: &str =
+    "https://github.com/EhTagTranslation/Database/releases/latest/download/db.text.json";
+
+/// Namespace used by EhTagTranslation for the namespace names themselves.
+const ROWS_NAMESPACE: &str = "rows";
+
+/// Namespace of gallery tags that carry no explicit prefix.
+const DEFAULT_TAG_NAMESPACE: &str = "other";
+
+#[derive(Deserialize)]
+struct RawDatabase {
+    data: Vec<RawNamespace>,
+}
+
+#[derive(Deserialize)]
+struct RawNamespace {
+    namespace: String,
+    #[serde(default)]
+    data: HashMap<String, RawTag>,
+}
+
+#[derive(Deserialize)]
+struct RawTag {
+    name: String,
+}
+
+/// Tag translation table parsed from an EhTagTranslation database dump.
+#[derive(Debug, Default, Clone)]
+pub struct TagTranslations {
+    namespaces: HashMap<String, String>,
+    tags: HashMap<String, HashMap<String, String>>,
+}
+
+impl TagTranslations {
+    /// Parse the `db.text.json` dump (names are plain text, not markdown).
+    pub fn from_json(json: &str) -> Result<Self> {
+        let raw: RawDatabase = serde_json::from_str(json)?;
+        let mut translations = Self::default();
+
+        for namespace in raw.data {
+            let names = namespace
+                .data
+                .into_iter()
+                .map(|(tag, raw)| (tag, raw.name))
+                .filter(|(_, name)| !name.is_empty());
+            if namespace.namespace == ROWS_NAMESPACE {
+                translations.namespaces.extend(names);
+            } else {
+                translations
+                    .tags
+                    .insert(namespace.namespace, names.collect());
+            }
+        }
+
+        Ok(translations)
+    }
+
+    /// Number of translated tags (namespace names are not counted).
+    pub fn len(&self) -> usize {
+        self.tags.values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Translate a gallery tag such as `female:glasses` into `女性:眼镜`.
+    /// Parts without a translation keep their original text.
+    pub fn translate(&self, tag: &str) -> String {
+        let (namespace, name) = match tag.split_once(':') {
+            Some((namespace, name)) => (namespace, name),
+            None => (DEFAULT_TAG_NAMESPACE, tag),
+        };
+
+        let translated_name = self
+            .tags
+            .get(namespace)
+            .and_then(|names| names.get(name))
+            .map(String::as_str)
+            .unwrap_or(name);
+
+        if !tag.contains(':') {
+            return translated_name.to_string();
+        }
+
+        let translated_namespace = self
+            .namespaces
+            .get(namespace)
+            .map(String::as_str)
+            .unwrap_or(namespace);
+        format!("{}:{}", translated_namespace, translated_name)
+    }
+}
+
+/// Shared translation table that is reloaded from the EhTagTranslation release
+/// periodically. Starts empty, so translation degrades to the raw tags until the
+/// first successful load.
+#[derive(Clone)]
+pub struct TagTranslationDb {
+    http: reqwest::Client,
+    url: String,
+    current: Arc<RwLock<Arc<TagTranslations>>>,
+}
+
+impl TagTranslationDb {
+    pub fn new(url: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()?;
+        Ok(Self {
+            http,
+            url: url.to_string(),
+            current: Arc::new(RwLock::new(Arc::new(TagTranslations::default()))),
+        })
+    }
+
+    /// Current translation table.
+    pub fn snapshot(&self) -> Arc<TagTranslations> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Download and swap in the latest database. Returns the number of tags loaded.
+    pub async fn refresh(&self) -> Result<usize> {
+        let json = self
+            .http
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let translations = TagTranslations::from_json(&json)?;
+        let count = translations.len();
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(translations);
+        Ok(count)
+    }
+
+    /// Refresh now and then every `interval`. Failed refreshes keep the previous table.
+    pub async fn run_refresh(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+            match self.refresh().await {
+                Ok(count) => info!("Loaded {} EH tag translations", count),
+                Err(e) => warn!("Failed to refresh EH tag translations: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DB: &str = r#"{
+        "head": {},
+        "version": 6,
+        "data": [
+            {
+                "namespace": "rows",
+                "data": {
+                    "female": { "name": "女性", "intro": "", "links": "" },
+                    "language": { "name": "语言", "intro": "", "links": "" }
+                }
+            },
+            {
+                "namespace": "female",
+                "data": {
+                    "glasses": { "name": "眼镜", "intro": "", "links": "" }
+                }
+            },
+            {
+                "namespace": "other",
+                "data": {
+                    "full color": { "name": "全彩", "intro": "", "links": "" }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_translate_namespaced_and_plain_tags() {
+        let translations = TagTranslations::from_json(SAMPLE_DB).unwrap();
+
+        assert_eq!(translations.len(), 2);
+        assert_eq!(translations.translate("female:glasses"), "女性:眼镜");
+        assert_eq!(translations.translate("full color"), "全彩");
+        assert_eq!(translations.translate("language:chinese"), "语言:chinese");
+        assert_eq!(translations.translate("parody:unknown"), "parody:unknown");
+    }
+}
@@ -230,7 +230,7 @@ fn detect_content_type(data: &[u8]) -> String {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ImageUploadProvider {
     #[default]
@@ -240,7 +240,7 @@ pub enum ImageUploadProvider {
     IpfS3,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ImageUploadConfig {
     #[serde(default)]
     pub provider: ImageUploadProvider,
@@ -288,7 +288,7 @@ impl ImageUploadConfig {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatboxUploaderConfig {
     #[serde(default = "default_catbox_api_url")]
     pub api_url: String,
@@ -309,7 +309,7 @@ fn default_catbox_api_url() -> String {
     "https://catbox.moe/user/api.php".to_string()
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct S3UploaderConfig {
     #[serde(default)]
     pub endpoint_url: Option<String>,
@@ -335,31 +335,47 @@ fn default_s3_path_style() -> bool {
 
 impl S3UploaderConfig {
     fn required(&self) -> Result<ResolvedS3UploaderConfig> {
+        self.resolve("image_upload.s3", true)
+    }
+
+    /// `public_base_url` is only needed for public image URLs, not for presigned links
+    fn resolve(&self, section: &str, require_public_url: bool) -> Result<ResolvedS3UploaderConfig> {
+        let field = |name: &str| format!("{section}.{name}");
+        let endpoint_url = validate_http_url(
+            &field("endpoint_url"),
+            &required_config(&field("endpoint_url"), &self.endpoint_url)?,
+        )?;
+        let bucket = required_config(&field("bucket"), &self.bucket)?;
+        let region = required_config(&field("region"), &self.region)?;
+        let access_key_id = required_config(&field("access_key_id"), &self.access_key_id)?;
+        let secret_access_key =
+            required_config(&field("secret_access_key"), &self.secret_access_key)?;
+        let public_base_url = if require_public_url || self.public_base_url.is_some() {
+            Some(
+                validate_http_url(
+                    &field("public_base_url"),
+                    &required_config(&field("public_base_url"), &self.public_base_url)?,
+                )?
+                .trim_end_matches('/')
+                .to_string(),
+            )
+        } else {
+            None
+        };
         Ok(ResolvedS3UploaderConfig {
-            endpoint_url: validate_http_url(
-                "image_upload.s3.endpoint_url",
-                &required_config("image_upload.s3.endpoint_url", &self.endpoint_url)?,
-            )?,
-            bucket: required_config("image_upload.s3.bucket", &self.bucket)?,
-            region: required_config("image_upload.s3.region", &self.region)?,
-            access_key_id: required_config("image_upload.s3.access_key_id", &self.access_key_id)?,
-            secret_access_key: required_config(
-                "image_upload.s3.secret_access_key",
-                &self.secret_access_key,
-            )?,
-            public_base_url: validate_http_url(
-                "image_upload.s3.public_base_url",
-                &required_config("image_upload.s3.public_base_url", &self.public_base_url)?,
-            )?
-            .trim_end_matches('/')
-            .to_string(),
+            endpoint_url,
+            bucket,
+            region,
+            access_key_id,
+            secret_access_key,
+            public_base_url,
             key_prefix: self.key_prefix.trim_matches('/').to_string(),
             path_style: self.path_style,
         })
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct IpfS3UploaderConfig {
     #[serde(default)]
     pub endpoint_url: Option<String>,
@@ -488,7 +504,7 @@ struct ResolvedS3UploaderConfig {
     region: String,
     access_key_id: String,
     secret_access_key: String,
-    public_base_url: String,
+    public_base_url: Option<String>,
     key_prefix: String,
     path_style: bool,
 }
@@ -498,6 +514,12 @@ pub struct ImageUploadInput<'a> {
     pub bytes: &'a [u8],
 }
 
+/// A generated archive shared through an expiring link
+pub struct ArchiveUploadInput<'a> {
+    pub filename: &'a str,
+    pub bytes: &'a [u8],
+}
+
 pub struct ZipArchiveUploadInput<'a> {
     pub filename: &'a str,
     pub bytes: &'a [u8],
@@ -533,6 +555,16 @@ pub trait ImageUploader: Send + Sync {
     ) -> Result<Option<Vec<TelegraphImageUrlPair>>> {
         Ok(None)
     }
+
+    /// Upload an archive privately and return a link valid for `expires_in_secs`.
+    /// `None` when the provider can't hand out expiring links.
+    async fn upload_archive_with_expiring_url(
+        &self,
+        _archive: ArchiveUploadInput<'_>,
+        _expires_in_secs: u32,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 pub struct PixiUploader {
@@ -732,7 +764,16 @@ pub struct S3Uploader {
 
 impl S3Uploader {
     pub fn from_config(config: &S3UploaderConfig) -> Result<Self> {
-        let config = config.required()?;
+        Self::from_resolved(config.required()?)
+    }
+
+    /// Uploader for archives shared through presigned links; `public_base_url` may be left out.
+    /// `section` names the config table in error messages.
+    pub fn for_archives(config: &S3UploaderConfig, section: &str) -> Result<Self> {
+        Self::from_resolved(config.resolve(section, false)?)
+    }
+
+    fn from_resolved(config: ResolvedS3UploaderConfig) -> Result<Self> {
         let credentials = Credentials::new(
             Some(&config.access_key_id),
             Some(&config.secret_access_key),
@@ -765,8 +806,25 @@ impl S3Uploader {
         }
     }
 
-    fn public_url(&self, key: &str) -> String {
-        public_url_for_key(&self.config.public_base_url, key)
+    fn archive_key(&self, input: &ArchiveUploadInput<'_>) -> String {
+        let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+        let hash = short_hash_hex(input.bytes);
+        let filename = format!(
+            "archives/{timestamp}-{hash}-{}",
+            safe_upload_filename(input.filename, "zip")
+        );
+        if self.config.key_prefix.is_empty() {
+            filename
+        } else {
+            format!("{}/{}", self.config.key_prefix, filename)
+        }
+    }
+
+    fn public_url(&self, key: &str) -> Result<String> {
+        let public_base_url = self.config.public_base_url.as_deref().ok_or_else(|| {
+            Error::Other("image_upload.s3.public_base_url is required for image uploads".into())
+        })?;
+        Ok(public_url_for_key(public_base_url, key))
     }
 }
 
@@ -789,10 +847,36 @@ impl ImageUploader for S3Uploader {
                     status,
                 });
             }
-            urls.push(self.public_url(&key));
+            urls.push(self.public_url(&key)?);
         }
         Ok(urls)
     }
+
+    async fn upload_archive_with_expiring_url(
+        &self,
+        archive: ArchiveUploadInput<'_>,
+        expires_in_secs: u32,
+    ) -> Result<Option<String>> {
+        let key = self.archive_key(&archive);
+        let response = self
+            .bucket
+            .put_object_with_content_type(&key, archive.bytes, "application/zip")
+            .await
+            .map_err(|e| Error::Other(format!("S3 put_object failed for key {key}: {e}")))?;
+        let status = response.status_code();
+        if !(200..300).contains(&status) {
+            return Err(Error::Api {
+                message: format!("S3 put_object returned {status} for key {key}"),
+                status,
+            });
+        }
+        let url = self
+            .bucket
+            .presign_get(&key, expires_in_secs, None)
+            .await
+            .map_err(|e| Error::Other(format!("S3 presign failed for key {key}: {e}")))?;
+        Ok(Some(url))
+    }
 }
 
 pub struct IpfS3Uploader {
@@ -2389,6 +2473,21 @@ mod tests {
         assert!(err.to_string().contains("must not contain query"));
     }
 
+    #[test]
+    fn s3_archive_config_does_not_need_public_base_url() {
+        let mut cfg = complete_s3_config("https://s3.example.com", "https://cdn.example.com");
+        cfg.public_base_url = None;
+
+        let err = cfg.required().unwrap_err();
+        assert!(err.to_string().contains("image_upload.s3.public_base_url"));
+        let resolved = cfg.resolve("archive_upload.s3", false).unwrap();
+        assert_eq!(resolved.public_base_url, None);
+
+        cfg.bucket = None;
+        let err = cfg.resolve("archive_upload.s3", false).unwrap_err();
+        assert!(err.to_string().contains("archive_upload.s3.bucket"));
+    }
+
     #[test]
     fn public_url_encodes_key_segments_and_trims_base() {
         let url = public_url_for_key("https://cdn.example.com/base/", "eh/hello world/01#.jpg");
@@ -156,6 +156,32 @@ async fn test_search_error_status() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_resolve_token_picks_matching_gid() {
+    let server = MockServer::start().await;
+    for query in ["gid:789012", "gid:1"] {
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(query_param("f_search", query))
+            .respond_with(ResponseTemplate::new(200).set_body_string(SEARCH_HTML))
+            .mount(&server)
+            .await;
+    }
+
+    let client = client_at(&server);
+    let token = client
+        .resolve_token(789012)
+        .await
+        .expect("token should be resolved");
+    assert_eq!(token, "987654abcd");
+
+    let missing = client.resolve_token(1).await;
+    assert!(matches!(
+        missing,
+        Err(eh_client::Error::Api { status: 404, .. })
+    ));
+}
+
 #[tokio::test]
 async fn test_get_metadata_parses_json() {
     let server = MockServer::start().await;
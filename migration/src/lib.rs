@@ -14,6 +14,35 @@ mod m20260707_000300_eh_background_download;
 mod m20260707_000400_eh_telegraph_rewrite;
 mod m20260718_000000_eh_download_gp_cost;
 mod m20260719_000000_eh_gp_spend_attempts;
+mod m20260720_000000_add_task_poll_interval;
+mod m20260721_000000_add_subscription_version;
+mod m20260722_000000_add_chat_allow_r18;
+mod m20260723_000000_create_channel_admins;
+mod m20260724_000000_create_sent_artworks;
+mod m20260725_000000_create_push_log;
+mod m20260726_000000_add_subscription_created_by;
+mod m20260727_000000_add_subscription_spoiler_mode;
+mod m20260728_000000_add_chat_send_original;
+mod m20260805_000000_add_subscription_enabled;
+mod m20260812_000000_add_chat_bot_id;
+mod m20260819_000000_convert_timestamps_to_utc;
+mod m20260826_000000_add_chat_link_preview;
+mod m20260902_000000_add_subscription_push_mode;
+mod m20260909_000000_add_chat_blocked_authors;
+mod m20260916_000000_create_push_queue;
+mod m20260923_000000_create_tag_aliases;
+mod m20260930_000000_add_task_health;
+mod m20261007_000000_add_task_dead_at;
+mod m20261014_000000_add_chat_slow_mode_delay;
+mod m20261014_000100_add_subscription_silent;
+mod m20261015_000000_add_subscription_ranking_date_offset;
+mod m20261016_000000_create_subscription_targets;
+mod m20261016_000100_add_subscription_message_thread_id;
+mod m20261016_000200_add_ai_filter;
+mod m20261016_000300_add_chat_default_filter;
+mod m20261016_000400_add_subscription_image_size;
+mod m20261016_000500_create_dialog_states;
+mod m20261016_000600_add_chat_notify_failures;
 
 pub struct Migrator;
 
@@ -35,6 +64,35 @@ impl MigratorTrait for Migrator {
             Box::new(m20260707_000400_eh_telegraph_rewrite::Migration),
             Box::new(m20260718_000000_eh_download_gp_cost::Migration),
             Box::new(m20260719_000000_eh_gp_spend_attempts::Migration),
+            Box::new(m20260720_000000_add_task_poll_interval::Migration),
+            Box::new(m20260721_000000_add_subscription_version::Migration),
+            Box::new(m20260722_000000_add_chat_allow_r18::Migration),
+            Box::new(m20260723_000000_create_channel_admins::Migration),
+            Box::new(m20260724_000000_create_sent_artworks::Migration),
+            Box::new(m20260725_000000_create_push_log::Migration),
+            Box::new(m20260726_000000_add_subscription_created_by::Migration),
+            Box::new(m20260727_000000_add_subscription_spoiler_mode::Migration),
+            Box::new(m20260728_000000_add_chat_send_original::Migration),
+            Box::new(m20260805_000000_add_subscription_enabled::Migration),
+            Box::new(m20260812_000000_add_chat_bot_id::Migration),
+            Box::new(m20260819_000000_convert_timestamps_to_utc::Migration),
+            Box::new(m20260826_000000_add_chat_link_preview::Migration),
+            Box::new(m20260902_000000_add_subscription_push_mode::Migration),
+            Box::new(m20260909_000000_add_chat_blocked_authors::Migration),
+            Box::new(m20260916_000000_create_push_queue::Migration),
+            Box::new(m20260923_000000_create_tag_aliases::Migration),
+            Box::new(m20260930_000000_add_task_health::Migration),
+            Box::new(m20261007_000000_add_task_dead_at::Migration),
+            Box::new(m20261014_000000_add_chat_slow_mode_delay::Migration),
+            Box::new(m20261014_000100_add_subscription_silent::Migration),
+            Box::new(m20261015_000000_add_subscription_ranking_date_offset::Migration),
+            Box::new(m20261016_000000_create_subscription_targets::Migration),
+            Box::new(m20261016_000100_add_subscription_message_thread_id::Migration),
+            Box::new(m20261016_000200_add_ai_filter::Migration),
+            Box::new(m20261016_000300_add_chat_default_filter::Migration),
+            Box::new(m20261016_000400_add_subscription_image_size::Migration),
+            Box::new(m20261016_000500_create_dialog_states::Migration),
+            Box::new(m20261016_000600_add_chat_notify_failures::Migration),
         ]
     }
 }
@@ -0,0 +1,52 @@
+//! Adds `allow_r18` column to `chats` table.
+//!
+//! Controls whether R-18/R-18G Pixiv works (by `x_restrict`) are delivered to a chat.
+//! Existing private chats keep receiving them; groups and channels default to off.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_column(
+                        ColumnDef::new(Chats::AllowR18)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE chats SET allow_r18 = TRUE WHERE type = 'private'")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .drop_column(Chats::AllowR18)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    AllowR18,
+}
@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Users delegated to manage a channel's subscriptions from private chat.
+        // When a channel has no rows here, any Telegram admin of the channel may manage it.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChannelAdmins::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChannelAdmins::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ChannelAdmins::ChannelId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ChannelAdmins::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ChannelAdmins::AddedBy).big_integer())
+                    .col(
+                        ColumnDef::new(ChannelAdmins::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_channel_admins_chat")
+                            .from(ChannelAdmins::Table, ChannelAdmins::ChannelId)
+                            .to(Chats::Table, Chats::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_channel_admins_channel_user")
+                    .table(ChannelAdmins::Table)
+                    .col(ChannelAdmins::ChannelId)
+                    .col(ChannelAdmins::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChannelAdmins::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChannelAdmins {
+    Table,
+    Id,
+    ChannelId,
+    UserId,
+    AddedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    Id,
+}
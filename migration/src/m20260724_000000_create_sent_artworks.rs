@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Artworks already delivered to a chat, regardless of which subscription pushed them.
+        // Used to avoid sending the same illust twice (e.g. author + ranking) and by /resend.
+        manager
+            .create_table(
+                Table::create()
+                    .table(SentArtworks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SentArtworks::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SentArtworks::ChatId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SentArtworks::IllustId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SentArtworks::MessageId).integer().not_null())
+                    .col(
+                        ColumnDef::new(SentArtworks::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sent_artworks_chat")
+                            .from(SentArtworks::Table, SentArtworks::ChatId)
+                            .to(Chats::Table, Chats::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_sent_artworks_chat_illust")
+                    .table(SentArtworks::Table)
+                    .col(SentArtworks::ChatId)
+                    .col(SentArtworks::IllustId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SentArtworks::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SentArtworks {
+    Table,
+    Id,
+    ChatId,
+    IllustId,
+    MessageId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    Id,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per push attempt made by the engines, used for /stats time buckets
+        manager
+            .create_table(
+                Table::create()
+                    .table(PushLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PushLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PushLog::ChatId).big_integer().not_null())
+                    .col(ColumnDef::new(PushLog::SubscriptionId).integer().not_null())
+                    .col(
+                        ColumnDef::new(PushLog::Items)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(PushLog::Success).boolean().not_null())
+                    .col(
+                        ColumnDef::new(PushLog::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_push_log_chat_created")
+                    .table(PushLog::Table)
+                    .col(PushLog::ChatId)
+                    .col(PushLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_push_log_created")
+                    .table(PushLog::Table)
+                    .col(PushLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PushLog::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PushLog {
+    Table,
+    Id,
+    ChatId,
+    SubscriptionId,
+    Items,
+    Success,
+    CreatedAt,
+}
@@ -0,0 +1,69 @@
+//! Converts stored timestamps from server local time to UTC.
+//!
+//! Timestamps used to be written as naive local time, so a DST switch or a
+//! change of the server time zone shifted every stored `next_poll_at`. They are
+//! now written as naive UTC and only converted to local time for display.
+//!
+//! SQLite's `utc` modifier interprets the stored value in the time zone of the
+//! process running the migration, which is the bot's own.
+
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Every timestamp column, by table
+const TIMESTAMP_COLUMNS: &[(&str, &[&str])] = &[
+    ("channel_admins", &["created_at"]),
+    ("chats", &["created_at"]),
+    (
+        "eh_download_queue",
+        &[
+            "created_at",
+            "started_at",
+            "completed_at",
+            "next_retry_at",
+            "archive_sent_at",
+            "telegraph_sent_at",
+            "background_download_started_at",
+            "background_download_next_retry_at",
+            "telegraph_rewrite_after",
+            "telegraph_rewrite_started_at",
+            "telegraph_rewrite_next_retry_at",
+            "telegraph_rewritten_at",
+        ],
+    ),
+    ("eh_gp_spend_attempts", &["created_at"]),
+    ("messages", &["created_at"]),
+    ("push_log", &["created_at"]),
+    ("sent_artworks", &["created_at"]),
+    ("subscriptions", &["created_at"]),
+    ("tasks", &["next_poll_at", "last_polled_at"]),
+    ("users", &["created_at"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        convert_all(manager, "utc").await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        convert_all(manager, "localtime").await
+    }
+}
+
+async fn convert_all(manager: &SchemaManager<'_>, modifier: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    for (table, columns) in TIMESTAMP_COLUMNS {
+        for column in *columns {
+            db.execute_unprepared(&format!(
+                "UPDATE {table} SET {column} = strftime('%Y-%m-%d %H:%M:%f', {column}, '{modifier}') \
+                 WHERE {column} IS NOT NULL"
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
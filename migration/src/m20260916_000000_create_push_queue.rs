@@ -0,0 +1,184 @@
+//! Creates the `push_queue` table for author push retries.
+//!
+//! Works that failed or were only partly sent used to be retried from a
+//! `pending_illust` kept inside the subscription's `latest_data`. The retry now
+//! lives in its own row so it survives state resets and can be inspected. Pending
+//! author retries are moved into the queue, and the author cursor is advanced
+//! past them so the work is not pushed again as new.
+
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const MOVE_PENDING_AUTHOR_RETRIES: &str = "
+INSERT OR IGNORE INTO push_queue
+    (subscription_id, chat_id, illust_id, sent_pages, total_pages, spoiler,
+     status, attempts, next_attempt_at, created_at, updated_at)
+SELECT id, chat_id,
+       json_extract(latest_data, '$.state.pending_illust.illust_id'),
+       COALESCE(json_extract(latest_data, '$.state.pending_illust.sent_pages'), '[]'),
+       json_extract(latest_data, '$.state.pending_illust.total_pages'),
+       COALESCE(json_extract(latest_data, '$.state.pending_illust.spoiler'), 0),
+       'pending',
+       COALESCE(json_extract(latest_data, '$.state.pending_illust.retry_count'), 0),
+       CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+FROM subscriptions
+WHERE json_extract(latest_data, '$.type') = 'Author'
+  AND json_extract(latest_data, '$.state.pending_illust.illust_id')
+      > json_extract(latest_data, '$.state.latest_illust_id')
+";
+
+const CLEAR_PENDING_AUTHOR_STATES: &str = "
+UPDATE subscriptions
+SET latest_data = json_remove(
+        json_set(
+            latest_data,
+            '$.state.latest_illust_id',
+            max(
+                json_extract(latest_data, '$.state.latest_illust_id'),
+                COALESCE(json_extract(latest_data, '$.state.pending_illust.illust_id'), 0)
+            )
+        ),
+        '$.state.pending_illust'
+    )
+WHERE json_extract(latest_data, '$.type') = 'Author'
+  AND json_extract(latest_data, '$.state.pending_illust') IS NOT NULL
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PushQueue::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PushQueue::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PushQueue::SubscriptionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PushQueue::ChatId).big_integer().not_null())
+                    .col(ColumnDef::new(PushQueue::IllustId).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(PushQueue::SentPages)
+                            .json()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(ColumnDef::new(PushQueue::TotalPages).integer().not_null())
+                    .col(
+                        ColumnDef::new(PushQueue::Spoiler)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(PushQueue::Status).string_len(10).not_null())
+                    .col(
+                        ColumnDef::new(PushQueue::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PushQueue::NextAttemptAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PushQueue::LastError).text().null())
+                    .col(
+                        ColumnDef::new(PushQueue::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(PushQueue::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_push_queue_subscription")
+                            .from(PushQueue::Table, PushQueue::SubscriptionId)
+                            .to(Subscriptions::Table, Subscriptions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_push_queue_subscription_illust")
+                    .table(PushQueue::Table)
+                    .col(PushQueue::SubscriptionId)
+                    .col(PushQueue::IllustId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_push_queue_status_next_attempt")
+                    .table(PushQueue::Table)
+                    .col(PushQueue::Status)
+                    .col(PushQueue::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(MOVE_PENDING_AUTHOR_RETRIES).await?;
+        db.execute_unprepared(CLEAR_PENDING_AUTHOR_STATES).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PushQueue::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PushQueue {
+    Table,
+    Id,
+    SubscriptionId,
+    ChatId,
+    IllustId,
+    SentPages,
+    TotalPages,
+    Spoiler,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Subscriptions {
+    Table,
+    Id,
+}
@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Alternative spellings of a tag (translations, romaji) consulted by tag filters.
+        // Both columns hold normalized tags; each alias maps to exactly one tag.
+        manager
+            .create_table(
+                Table::create()
+                    .table(TagAliases::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TagAliases::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TagAliases::Alias).string().not_null())
+                    .col(ColumnDef::new(TagAliases::Tag).string().not_null())
+                    .col(
+                        ColumnDef::new(TagAliases::Source)
+                            .string_len(10)
+                            .not_null()
+                            .default("manual"),
+                    )
+                    .col(
+                        ColumnDef::new(TagAliases::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_tag_aliases_alias")
+                    .table(TagAliases::Table)
+                    .col(TagAliases::Alias)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_tag_aliases_tag")
+                    .table(TagAliases::Table)
+                    .col(TagAliases::Tag)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TagAliases::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TagAliases {
+    Table,
+    Id,
+    Alias,
+    Tag,
+    Source,
+    CreatedAt,
+}
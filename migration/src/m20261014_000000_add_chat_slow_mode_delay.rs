@@ -0,0 +1,41 @@
+//! Adds `slow_mode_delay` column to `chats` table.
+//!
+//! Seconds Telegram requires between two messages in a supergroup with slow
+//! mode, as reported by `getChat`. `NULL` until the chat has been checked,
+//! `0` when slow mode is off.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_column(ColumnDef::new(Chats::SlowModeDelay).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .drop_column(Chats::SlowModeDelay)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    SlowModeDelay,
+}
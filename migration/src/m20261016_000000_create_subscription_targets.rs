@@ -0,0 +1,113 @@
+//! Creates `subscription_targets` table.
+//!
+//! Extra chats an author subscription is mirrored to (`/mirror`). The author is
+//! still fetched once per poll; each target keeps its own push cursor.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubscriptionTargets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SubscriptionTargets::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubscriptionTargets::SubscriptionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubscriptionTargets::ChatId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    // Newest work already handled for this target, NULL before its first push
+                    .col(ColumnDef::new(SubscriptionTargets::LatestIllustId).big_integer())
+                    .col(ColumnDef::new(SubscriptionTargets::CreatedBy).big_integer())
+                    .col(
+                        ColumnDef::new(SubscriptionTargets::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_subscription_targets_subscription")
+                            .from(
+                                SubscriptionTargets::Table,
+                                SubscriptionTargets::SubscriptionId,
+                            )
+                            .to(Subscriptions::Table, Subscriptions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_subscription_targets_chat")
+                            .from(SubscriptionTargets::Table, SubscriptionTargets::ChatId)
+                            .to(Chats::Table, Chats::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_subscription_targets_subscription_chat")
+                    .table(SubscriptionTargets::Table)
+                    .col(SubscriptionTargets::SubscriptionId)
+                    .col(SubscriptionTargets::ChatId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubscriptionTargets::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionTargets {
+    Table,
+    Id,
+    SubscriptionId,
+    ChatId,
+    LatestIllustId,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Subscriptions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    Id,
+}
@@ -0,0 +1,75 @@
+//! Adds `ai_filter` columns to `chats` and `subscriptions` tables.
+//!
+//! Filters works Pixiv marks as AI-generated (`illust_ai_type`): `all` keeps
+//! them, `exclude` drops them and `only` keeps nothing else. The chat value is
+//! the default; a subscription's `auto` follows it.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_column(
+                        ColumnDef::new(Chats::AiFilter)
+                            .string_len(10)
+                            .not_null()
+                            .default("all"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subscriptions::Table)
+                    .add_column(
+                        ColumnDef::new(Subscriptions::AiFilter)
+                            .string_len(10)
+                            .not_null()
+                            .default("auto"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subscriptions::Table)
+                    .drop_column(Subscriptions::AiFilter)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .drop_column(Chats::AiFilter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    AiFilter,
+}
+
+#[derive(DeriveIden)]
+enum Subscriptions {
+    Table,
+    AiFilter,
+}
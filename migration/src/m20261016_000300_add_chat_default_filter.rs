@@ -0,0 +1,46 @@
+//! Adds `default_filter` column to `chats` table.
+//!
+//! Stores a chat-level tag filter that is merged into the filter of every
+//! Pixiv subscription created in the chat, so admins don't have to repeat
+//! common tags such as `-R-18` on each `/sub`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .add_column(
+                        ColumnDef::new(Chats::DefaultFilter)
+                            .json()
+                            .not_null()
+                            .default(r#"{"include":[],"exclude":[]}"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chats::Table)
+                    .drop_column(Chats::DefaultFilter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chats {
+    Table,
+    DefaultFilter,
+}
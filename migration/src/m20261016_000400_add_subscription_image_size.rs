@@ -0,0 +1,46 @@
+//! Adds `image_size` column to `subscriptions` table.
+//!
+//! Pixiv works of a subscription are pushed in this size (`original`, `large`
+//! or `medium`), set with `/sub size=<size>` or `/subrank size=<size>`. `auto`
+//! keeps the global `content.push_size`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subscriptions::Table)
+                    .add_column(
+                        ColumnDef::new(Subscriptions::ImageSize)
+                            .string_len(10)
+                            .not_null()
+                            .default("auto"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Subscriptions::Table)
+                    .drop_column(Subscriptions::ImageSize)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Subscriptions {
+    Table,
+    ImageSize,
+}
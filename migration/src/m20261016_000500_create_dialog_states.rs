@@ -0,0 +1,101 @@
+//! Creates `dialog_states` table.
+//!
+//! Pending multi-message settings dialogues ("send me the new tags"), so they
+//! survive restarts and are shared between instances on the same database.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DialogStates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DialogStates::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(DialogStates::ChatId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DialogStates::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DialogStates::State)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    // Settings panel message to refresh once the input arrives
+                    .col(
+                        ColumnDef::new(DialogStates::SettingsMessageId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DialogStates::ExpiresAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One pending dialogue per user and chat
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_dialog_states_chat_user")
+                    .table(DialogStates::Table)
+                    .col(DialogStates::ChatId)
+                    .col(DialogStates::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_dialog_states_expires_at")
+                    .table(DialogStates::Table)
+                    .col(DialogStates::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DialogStates::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum DialogStates {
+    Table,
+    Id,
+    ChatId,
+    UserId,
+    State,
+    SettingsMessageId,
+    ExpiresAt,
+}
@@ -9,6 +9,7 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 const APP_API_HOST: &str = "https://app-api.pixiv.net";
+const PIXIV_ME_HOST: &str = "https://pixiv.me";
 const USER_AGENT_VALUE: &str = "PixivIOSApp/7.13.3 (iOS 14.6; iPhone13,2)";
 
 /// Token 信息，包含 access_token 和过期时间
@@ -17,6 +18,8 @@ struct TokenInfo {
     access_token: String,
     /// Token 过期的时间点
     expires_at: Instant,
+    /// 账号是否为 Pixiv 高级会员
+    is_premium: bool,
 }
 
 impl TokenInfo {
@@ -34,11 +37,13 @@ pub struct PixivClient {
 }
 
 impl PixivClient {
-    /// 创建新的客户端
-    pub fn new(refresh_token: String) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()?;
+    /// 创建新的客户端，`proxy` 为 SOCKS5/HTTP 代理地址
+    pub fn new(refresh_token: String, proxy: Option<&str>) -> Result<Self> {
+        let mut builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        let client = builder.build()?;
 
         Ok(Self {
             client,
@@ -59,6 +64,7 @@ impl PixivClient {
         *token_info = Some(TokenInfo {
             access_token: auth_response.access_token,
             expires_at,
+            is_premium: auth_response.user.is_premium,
         });
 
         tracing::info!(
@@ -69,6 +75,20 @@ impl PixivClient {
         Ok(())
     }
 
+    /// 距离 access_token 过期的剩余时间，未登录时返回 None
+    pub async fn token_expires_in(&self) -> Option<Duration> {
+        let token_info = self.token_info.read().await;
+        token_info
+            .as_ref()
+            .map(|info| info.expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// 当前账号是否为高级会员，未登录时返回 false
+    pub async fn is_premium(&self) -> bool {
+        let token_info = self.token_info.read().await;
+        token_info.as_ref().is_some_and(|info| info.is_premium)
+    }
+
     /// 确保 token 有效，如果过期则自动刷新
     async fn ensure_token_valid(&self) -> Result<()> {
         let needs_refresh = {
@@ -175,6 +195,31 @@ impl PixivClient {
         self.get("/v1/user/illusts", &params).await
     }
 
+    /// 获取用户收藏的作品，按收藏时间从新到旧排列
+    ///
+    /// # 参数
+    /// - `user_id`: 用户 ID
+    /// - `restrict`: 收藏类型 ("public" 或 "private"，非本人只能获取公开收藏)
+    /// - `max_bookmark_id`: 分页游标，取自上一页 `next_url`
+    pub async fn user_bookmarks_illust(
+        &self,
+        user_id: u64,
+        restrict: &str,
+        max_bookmark_id: Option<u64>,
+    ) -> Result<UserBookmarksIllust> {
+        let mut params = vec![
+            ("user_id", user_id.to_string()),
+            ("restrict", restrict.to_string()),
+            ("filter", "for_ios".to_string()),
+        ];
+
+        if let Some(id) = max_bookmark_id {
+            params.push(("max_bookmark_id", id.to_string()));
+        }
+
+        self.get("/v1/user/bookmarks/illust", &params).await
+    }
+
     /// 获取作品详情
     #[allow(dead_code)]
     pub async fn illust_detail(&self, illust_id: u64) -> Result<IllustDetail> {
@@ -210,6 +255,54 @@ impl PixivClient {
         self.get("/v1/illust/ranking", &params).await
     }
 
+    /// 按关键词搜索作品 (标签部分匹配)
+    ///
+    /// # 参数
+    /// - `word`: 搜索关键词
+    /// - `sort`: 排序方式 ("date_desc", "date_asc"; "popular_desc" 仅高级会员可用)
+    /// - `offset`: 分页偏移量
+    pub async fn search_illusts(
+        &self,
+        word: &str,
+        sort: &str,
+        offset: Option<u32>,
+    ) -> Result<SearchIllusts> {
+        let mut params = vec![
+            ("word", word.to_string()),
+            ("search_target", "partial_match_for_tags".to_string()),
+            ("sort", sort.to_string()),
+            ("filter", "for_ios".to_string()),
+        ];
+
+        if let Some(o) = offset {
+            params.push(("offset", o.to_string()));
+        }
+
+        self.get("/v1/search/illust", &params).await
+    }
+
+    /// 获取与作品相似的相关作品
+    ///
+    /// # 参数
+    /// - `illust_id`: 作品 ID
+    /// - `offset`: 分页偏移量
+    pub async fn illust_related(
+        &self,
+        illust_id: u64,
+        offset: Option<u32>,
+    ) -> Result<RelatedIllusts> {
+        let mut params = vec![
+            ("illust_id", illust_id.to_string()),
+            ("filter", "for_ios".to_string()),
+        ];
+
+        if let Some(o) = offset {
+            params.push(("offset", o.to_string()));
+        }
+
+        self.get("/v2/illust/related", &params).await
+    }
+
     /// 获取用户详情
     ///
     /// # 参数
@@ -233,4 +326,57 @@ impl PixivClient {
         let params = vec![("illust_id", illust_id.to_string())];
         self.get("/v1/ugoira/metadata", &params).await
     }
+
+    /// 解析 pixiv.me 短链接的用户名为用户 ID
+    ///
+    /// `pixiv.me/<username>` 会重定向到 `www.pixiv.net/users/<id>`，
+    /// 跟随重定向后从最终 URL 中取出 ID。不需要认证。
+    pub async fn resolve_username(&self, username: &str) -> Result<u64> {
+        let url = format!("{}/{}", PIXIV_ME_HOST, username);
+        let response = self.client.get(&url).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::Api {
+                message: format!("failed to resolve pixiv.me/{}", username),
+                status: status.as_u16(),
+            });
+        }
+
+        user_id_from_profile_url(response.url()).ok_or_else(|| Error::Api {
+            message: format!("pixiv.me/{} does not point to a user", username),
+            status: 404,
+        })
+    }
+}
+
+/// 从 `https://www.pixiv.net/[en/]users/<id>` 形式的 URL 中取出用户 ID
+fn user_id_from_profile_url(url: &reqwest::Url) -> Option<u64> {
+    if !matches!(url.host_str()?, "www.pixiv.net" | "pixiv.net") {
+        return None;
+    }
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "users")?;
+    segments.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_id_from_profile_url_reads_users_path() {
+        let parse = |url: &str| user_id_from_profile_url(&reqwest::Url::parse(url).unwrap());
+
+        assert_eq!(
+            parse("https://www.pixiv.net/users/33611048"),
+            Some(33611048)
+        );
+        assert_eq!(
+            parse("https://www.pixiv.net/en/users/123/artworks"),
+            Some(123)
+        );
+        assert_eq!(parse("https://www.pixiv.net/"), None);
+        assert_eq!(parse("https://example.com/users/1"), None);
+    }
 }
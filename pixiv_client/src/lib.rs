@@ -10,4 +10,8 @@ mod error;
 mod models;
 
 pub use client::PixivClient;
-pub use models::{Illust, ImageSize, UgoiraFrame, UgoiraMetadata, UgoiraMetadataInfo, User};
+pub use error::{Error, Result};
+pub use models::{
+    AccessRestriction, Illust, ImageSize, ProfileImageUrls, UgoiraFrame, UgoiraMetadata,
+    UgoiraMetadataInfo, User, UserDetail, UserProfile,
+};
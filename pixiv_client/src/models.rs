@@ -12,6 +12,16 @@ pub struct User {
     pub account: String,
     #[serde(default)]
     pub is_followed: Option<bool>,
+    /// 头像 URL (作品列表中的作者信息同样带有此字段)
+    #[serde(default)]
+    pub profile_image_urls: Option<ProfileImageUrls>,
+}
+
+/// 用户头像 URL
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileImageUrls {
+    /// 170x170 头像
+    pub medium: String,
 }
 
 /// 图片 URL
@@ -75,6 +85,20 @@ pub struct Illust {
     pub is_muted: bool,
     #[serde(default)]
     pub total_comments: Option<u64>,
+    /// AI 生成标记：0 未标记 (旧作品)，1 非 AI 作品，2 AI 生成
+    #[serde(default)]
+    pub illust_ai_type: u32,
+}
+
+/// 作品的访问限制
+///
+/// 受限作品仍会出现在作品列表中，但 API 只返回占位图 (`/common/images/limit_*.png`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessRestriction {
+    /// 仅好P友 (My pixiv) 可见
+    MyPixiv,
+    /// 其他原因不可见 (仅关注者可见、账号权限不足等)
+    Unavailable,
 }
 
 /// 图片尺寸选项
@@ -96,11 +120,30 @@ impl Illust {
         self.illust_type == "ugoira"
     }
 
+    /// 是否被作者标记为 AI 生成作品
+    pub fn is_ai_generated(&self) -> bool {
+        self.illust_ai_type == 2
+    }
+
     /// 是否为多图作品
     pub fn is_multi_page(&self) -> bool {
         self.page_count > 1
     }
 
+    /// 当前账号无法查看作品内容时返回限制类型
+    pub fn access_restriction(&self) -> Option<AccessRestriction> {
+        let placeholder = [&self.image_urls.square_medium, &self.image_urls.large]
+            .into_iter()
+            .find(|url| url.contains("/common/images/limit_"));
+
+        match placeholder {
+            Some(url) if url.contains("limit_mypixiv") => Some(AccessRestriction::MyPixiv),
+            Some(_) => Some(AccessRestriction::Unavailable),
+            None if !self.visible => Some(AccessRestriction::Unavailable),
+            None => None,
+        }
+    }
+
     /// 获取所有图片的原图 URL
     /// 单图返回1个URL,多图返回所有页的URL
     pub fn get_all_image_urls(&self) -> Vec<String> {
@@ -166,6 +209,13 @@ pub struct UserIllusts {
     pub next_url: Option<String>,
 }
 
+/// 用户收藏作品列表响应
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserBookmarksIllust {
+    pub illusts: Vec<Illust>,
+    pub next_url: Option<String>,
+}
+
 /// 排行榜响应
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Ranking {
@@ -173,10 +223,43 @@ pub struct Ranking {
     pub next_url: Option<String>,
 }
 
+/// 作品搜索响应
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchIllusts {
+    pub illusts: Vec<Illust>,
+    pub next_url: Option<String>,
+}
+
+/// 相关作品响应
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RelatedIllusts {
+    pub illusts: Vec<Illust>,
+    pub next_url: Option<String>,
+}
+
 /// 用户详情响应
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct UserDetail {
     pub user: User,
+    #[serde(default)]
+    pub profile: Option<UserProfile>,
+}
+
+/// 用户主页资料
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserProfile {
+    /// 主页横幅背景图 URL，未设置时为空
+    #[serde(default)]
+    pub background_image_url: Option<String>,
+    /// 公开的插画数量
+    #[serde(default)]
+    pub total_illusts: u64,
+    /// 公开的漫画数量
+    #[serde(default)]
+    pub total_manga: u64,
+    /// 该用户关注的用户数量
+    #[serde(default)]
+    pub total_follow_users: u64,
 }
 
 /// Ugoira 帧信息
@@ -232,6 +315,7 @@ mod tests {
                 name: "Artist".to_string(),
                 account: "artist".to_string(),
                 is_followed: None,
+                profile_image_urls: None,
             },
             tags: vec![],
             create_date: "2024-01-01".to_string(),
@@ -251,6 +335,7 @@ mod tests {
             visible: true,
             is_muted: false,
             total_comments: None,
+            illust_ai_type: 1,
         }
     }
 
@@ -260,6 +345,16 @@ mod tests {
         assert!(illust.is_ugoira());
     }
 
+    #[test]
+    fn test_is_ai_generated_only_for_ai_type_2() {
+        let mut illust = make_illust("illust", 1);
+        assert!(!illust.is_ai_generated());
+        illust.illust_ai_type = 0;
+        assert!(!illust.is_ai_generated());
+        illust.illust_ai_type = 2;
+        assert!(illust.is_ai_generated());
+    }
+
     #[test]
     fn test_is_ugoira_false_for_illust() {
         let illust = make_illust("illust", 1);
@@ -272,6 +367,35 @@ mod tests {
         assert!(!illust.is_ugoira());
     }
 
+    #[test]
+    fn test_access_restriction() {
+        let illust = make_illust("illust", 1);
+        assert_eq!(illust.access_restriction(), None);
+
+        let mut illust = make_illust("illust", 1);
+        illust.visible = false;
+        illust.image_urls.square_medium =
+            "https://s.pximg.net/common/images/limit_mypixiv_360.png".to_string();
+        assert_eq!(
+            illust.access_restriction(),
+            Some(AccessRestriction::MyPixiv)
+        );
+
+        illust.image_urls.square_medium =
+            "https://s.pximg.net/common/images/limit_unknown_360.png".to_string();
+        assert_eq!(
+            illust.access_restriction(),
+            Some(AccessRestriction::Unavailable)
+        );
+
+        let mut illust = make_illust("illust", 1);
+        illust.visible = false;
+        assert_eq!(
+            illust.access_restriction(),
+            Some(AccessRestriction::Unavailable)
+        );
+    }
+
     #[test]
     fn test_ugoira_metadata_deserialization() {
         let json = r#"{
@@ -8,30 +8,135 @@ pub enum Command {
     Help,
     #[command(description = "[仅Admin私聊] 查看 Bot 状态信息")]
     Info,
-    #[command(description = "订阅作者\n  用法: /sub [ch=<频道ID>] <id,...> [+tag1 -tag2]")]
+    #[command(description = "查看 Bot 版本和构建信息")]
+    Version,
+    #[command(description = "查看推送统计")]
+    Stats,
+    #[command(
+        description = "订阅作者\n  用法: /sub [ch=<频道ID>] [interval=<分钟>] <id,...> [+tag1 -tag2]"
+    )]
     Sub(String),
-    #[command(description = "订阅排行榜\n  用法: /subrank [ch=<频道ID>] <mode>")]
+    #[command(
+        description = "订阅排行榜\n  用法: /subrank [ch=<频道ID>] [start=tomorrow|now] <mode,...>"
+    )]
     SubRank(String),
+    #[command(
+        description = "订阅 Pixiv 用户的公开收藏\n  用法: /subbookmarks [ch=<频道ID>] <user_id> [+tag1 -tag2]"
+    )]
+    SubBookmarks(String),
+    #[command(
+        description = "预览作者订阅会推送的作品\n  用法: /preview <author_id> [+tag1 -tag2]"
+    )]
+    Preview(String),
+    #[command(description = "随机推送一个作品\n  用法: /random [author_id]")]
+    Random(String),
+    #[command(description = "查看排行榜前 N 名\n  用法: /top <mode> [YYYY-MM-DD] [count]")]
+    Top(String),
+    #[command(description = "按关键词搜索 Pixiv 作品\n  用法: /search <关键词>")]
+    Search(String),
+    #[command(description = "查看与作品相似的 Pixiv 作品\n  用法: /related <illust_id|URL>")]
+    Related(String),
+    #[command(description = "查看作者的发布频率和收藏统计\n  用法: /authorstats <author_id>")]
+    AuthorStats(String),
     #[command(description = "取消订阅作者\n  用法: /unsub [ch=<频道ID>] <author_id,...>")]
     Unsub(String),
     #[command(description = "取消订阅排行榜\n  用法: /unsubrank [ch=<频道ID>] <mode>")]
     UnsubRank(String),
+    #[command(description = "取消订阅用户收藏\n  用法: /unsubbookmarks [ch=<频道ID>] <user_id>")]
+    UnsubBookmarks(String),
     #[command(description = "回复消息取消对应订阅")]
     UnsubThis,
+    #[command(
+        description = "批量取消订阅（不填类型则取消全部）\n  用法: /unsuball [ch=<频道ID>] [authors|rank|eh]"
+    )]
+    UnsubAll(String),
+    #[command(
+        description = "暂停订阅（不填则暂停全部）\n  用法: /pause [ch=<频道ID>] [author_id,...|all]"
+    )]
+    Pause(String),
+    #[command(
+        description = "恢复已暂停的订阅（不填则恢复全部）\n  用法: /resume [ch=<频道ID>] [author_id,...|all]"
+    )]
+    Resume(String),
+    #[command(
+        description = "修改已有订阅的标签过滤（clear 清除）\n  用法: /setfilter [ch=<频道ID>] <author_id|mode> <+tag1 -tag2|clear>"
+    )]
+    SetFilter(String),
     #[command(description = "列出当前订阅\n  用法: /list [ch=<频道ID>]")]
     List(String),
+    #[command(description = "导出订阅目录为 HTML 文件\n  用法: /exporthtml [ch=<频道ID>]")]
+    ExportHtml(String),
+    #[command(
+        description = "管理频道的订阅管理员\n  用法: /channeladmins ch=<频道ID> [add|remove <user_id>]"
+    )]
+    ChannelAdmins(String),
     #[command(description = "[仅Owner] 设置用户为管理员\n  用法: /setadmin <user_id>")]
     SetAdmin(String),
     #[command(description = "[仅Owner] 移除用户管理员角色\n  用法: /unsetadmin <user_id>")]
     UnsetAdmin(String),
-    #[command(description = "[仅Admin] 启用聊天\n  用法: /enablechat [chat_id]")]
+    #[command(
+        description = "[仅Owner私聊] 查看生效的配置（已隐藏密钥）\n  用法: /config [section]"
+    )]
+    Config(String),
+    #[command(
+        description = "[仅Owner私聊] 查看最近日志，指定级别时实时推送新日志\n  用法: /logs tail [warn|error]"
+    )]
+    Logs(String),
+    #[command(
+        description = "[仅Owner] 管理标签别名，过滤时别名与原标签等同\n  用法: /tagalias [list [标签]|add <标签> <别名...>|remove <别名...>|import]"
+    )]
+    TagAlias(String),
+    #[command(description = "[仅Admin] 启用聊天\n  用法: /enablechat [chat_id,...]")]
     EnableChat(String),
-    #[command(description = "[仅Admin] 禁用聊天\n  用法: /disablechat [chat_id]")]
+    #[command(description = "[仅Admin] 禁用聊天\n  用法: /disablechat [chat_id,...]")]
     DisableChat(String),
+    #[command(description = "[仅Admin] 允许或屏蔽 R-18 作品\n  用法: /r18 <on|off>")]
+    R18(String),
+    #[command(description = "[仅Admin] 推送单图作品后附带原图文件\n  用法: /original <on|off>")]
+    Original(String),
+    #[command(
+        description = "[仅Admin] 设置 AI 作品的默认过滤方式\n  用法: /ai <all|exclude|only>"
+    )]
+    Ai(String),
+    #[command(
+        description = "[仅Admin] 设置新建订阅自动合并的默认标签过滤（clear 清除，不填则显示当前设置）\n  用法: /defaultfilter <+tag1 -tag2|clear>"
+    )]
+    DefaultFilter(String),
+    #[command(
+        description = "[仅Admin] 屏蔽作者，不再推送和预览其作品（不填则列出已屏蔽作者）\n  用法: /blockauthor <author_id,...>"
+    )]
+    BlockAuthor(String),
+    #[command(description = "[仅Admin] 解除屏蔽作者\n  用法: /unblockauthor <author_id,...>")]
+    UnblockAuthor(String),
+    #[command(
+        description = "[仅Admin] 查看订阅健康状态\n  用法: /health [ch=<频道ID>]\n  Owner: /health failing 查看持续失败的任务"
+    )]
+    Health(String),
+    #[command(
+        description = "[仅Admin] 将一个聊天的全部订阅转移到另一个聊天\n  用法: /movesubs <from_chat_id> <to_chat_id>"
+    )]
+    MoveSubs(String),
+    #[command(description = "[仅Admin] 立即补全缺失的作者名称")]
+    RefreshNames,
+    #[command(
+        description = "[仅Admin] 模拟作者订阅的下一次推送，结果私聊发送\n  用法: /simulate [ch=<频道ID>] <author_id>"
+    )]
+    Simulate(String),
+    #[command(
+        description = "[仅Admin] 将作者订阅同时推送到其他聊天\n  用法: /mirror <sub_id> [<chat_id>|remove <chat_id>]"
+    )]
+    Mirror(String),
     #[command(description = "显示和管理聊天设置")]
     Settings,
     #[command(description = "下载作品原图\n  用法: /download <url|id> 或回复消息")]
     Download(String),
+    #[command(description = "重新发送已推送过的作品\n  用法: /resend <作品ID或链接>")]
+    Resend(String),
+    #[command(
+        description = "通过 SauceNAO 识别图片的 Pixiv 出处\n  用法: 回复图片发送 /sauce",
+        aliases = ["resolve"]
+    )]
+    Sauce,
     #[command(description = "订阅 Booru 标签\n  用法: /bsub [ch=<频道ID>] <站点:标签> [过滤条件]")]
     BSub(String),
     #[command(description = "取消 Booru 标签订阅\n  用法: /bunsub [ch=<频道ID>] <站点:标签>")]
@@ -52,6 +157,8 @@ pub enum Command {
     EUnsub(String),
     #[command(description = "直接下载 E-Hentai 画廊\n  用法: /edl <url> [telegraph=on]")]
     EDl(String),
+    #[command(description = "查看 E-Hentai 画廊信息\n  用法: /ehinfo <url> 或回复消息")]
+    EhInfo(String),
     #[command(description = "查看当前聊天的 E-Hentai 下载队列", parse_with = "split")]
     EStatus {},
     #[command(
@@ -67,16 +174,62 @@ impl Command {
     pub fn user_commands(has_booru: bool, has_ehentai: bool) -> Vec<BotCommand> {
         let mut commands = vec![
             BotCommand::new("sub", "订阅作者 - /sub [ch=<频道ID>] <id,...>"),
-            BotCommand::new("subrank", "订阅排行榜 - /subrank [ch=<频道ID>] <mode>"),
+            BotCommand::new("subrank", "订阅排行榜 - /subrank [ch=<频道ID>] <mode,...>"),
+            BotCommand::new(
+                "subbookmarks",
+                "订阅用户收藏 - /subbookmarks [ch=<频道ID>] <user_id>",
+            ),
             BotCommand::new("list", "列出当前订阅 - /list [ch=<频道ID>]"),
+            BotCommand::new(
+                "exporthtml",
+                "导出订阅目录为 HTML - /exporthtml [ch=<频道ID>]",
+            ),
+            BotCommand::new(
+                "channeladmins",
+                "频道订阅管理员 - /channeladmins ch=<频道ID> [add|remove <user_id>]",
+            ),
+            BotCommand::new(
+                "preview",
+                "预览作者订阅 - /preview <author_id> [+tag1 -tag2]",
+            ),
+            BotCommand::new("random", "随机推送作品 - /random [author_id]"),
+            BotCommand::new(
+                "top",
+                "查看排行榜前 N 名 - /top <mode> [YYYY-MM-DD] [count]",
+            ),
+            BotCommand::new("search", "搜索 Pixiv 作品 - /search <关键词>"),
+            BotCommand::new("related", "相关作品 - /related <illust_id|URL>"),
+            BotCommand::new("authorstats", "作者统计 - /authorstats <author_id>"),
             BotCommand::new("unsub", "取消订阅作者 - /unsub [ch=<频道ID>] <id,...>"),
             BotCommand::new(
                 "unsubrank",
                 "取消订阅排行榜 - /unsubrank [ch=<频道ID>] <mode>",
             ),
+            BotCommand::new(
+                "unsubbookmarks",
+                "取消订阅用户收藏 - /unsubbookmarks [ch=<频道ID>] <user_id>",
+            ),
             BotCommand::new("unsubthis", "回复消息取消对应订阅"),
+            BotCommand::new(
+                "unsuball",
+                "批量取消订阅 - /unsuball [ch=<频道ID>] [authors|rank|eh]",
+            ),
+            BotCommand::new(
+                "pause",
+                "暂停订阅 - /pause [ch=<频道ID>] [author_id,...|all]",
+            ),
+            BotCommand::new(
+                "resume",
+                "恢复订阅 - /resume [ch=<频道ID>] [author_id,...|all]",
+            ),
+            BotCommand::new(
+                "setfilter",
+                "修改订阅过滤 - /setfilter [ch=<频道ID>] <author_id|mode> <+tag -tag|clear>",
+            ),
             BotCommand::new("settings", "显示和管理聊天设置"),
             BotCommand::new("download", "下载作品原图 - /download <url|id> 或回复消息"),
+            BotCommand::new("resend", "重新发送已推送的作品 - /resend <id>"),
+            BotCommand::new("sauce", "识别图片的 Pixiv 出处 - 回复图片发送 /sauce"),
         ];
 
         if has_booru {
@@ -108,6 +261,7 @@ impl Command {
                 BotCommand::new("esub", "订阅EH画廊 - /esub <搜索词> [过滤条件]"),
                 BotCommand::new("eunsub", "取消EH订阅 - /eunsub <搜索词>"),
                 BotCommand::new("edl", "下载EH画廊 - /edl <url> [telegraph=on]"),
+                BotCommand::new("ehinfo", "查看EH画廊信息 - /ehinfo <url> 或回复消息"),
                 BotCommand::new("estatus", "查看当前聊天的EH下载队列"),
                 BotCommand::new(
                     "telegraph",
@@ -116,6 +270,8 @@ impl Command {
             ]);
         }
 
+        commands.push(BotCommand::new("stats", "查看推送统计"));
+        commands.push(BotCommand::new("version", "查看版本信息"));
         commands.push(BotCommand::new("help", "显示帮助信息"));
 
         commands
@@ -126,8 +282,40 @@ impl Command {
         let mut cmds = Self::user_commands(has_booru, has_ehentai);
         cmds.extend([
             BotCommand::new("info", "[Admin] 查看 Bot 状态信息"),
-            BotCommand::new("enablechat", "[Admin] 启用聊天 - /enablechat [chat_id]"),
-            BotCommand::new("disablechat", "[Admin] 禁用聊天 - /disablechat [chat_id]"),
+            BotCommand::new("enablechat", "[Admin] 启用聊天 - /enablechat [chat_id,...]"),
+            BotCommand::new(
+                "disablechat",
+                "[Admin] 禁用聊天 - /disablechat [chat_id,...]",
+            ),
+            BotCommand::new("r18", "[Admin] R-18 开关 - /r18 <on|off>"),
+            BotCommand::new("original", "[Admin] 原图文件开关 - /original <on|off>"),
+            BotCommand::new("ai", "[Admin] AI 作品过滤 - /ai <all|exclude|only>"),
+            BotCommand::new(
+                "defaultfilter",
+                "[Admin] 默认订阅过滤 - /defaultfilter <+tag -tag|clear>",
+            ),
+            BotCommand::new(
+                "blockauthor",
+                "[Admin] 屏蔽作者 - /blockauthor <author_id,...>",
+            ),
+            BotCommand::new(
+                "unblockauthor",
+                "[Admin] 解除屏蔽作者 - /unblockauthor <author_id,...>",
+            ),
+            BotCommand::new("health", "[Admin] 订阅健康报告 - /health [ch=<频道ID>]"),
+            BotCommand::new(
+                "movesubs",
+                "[Admin] 转移订阅 - /movesubs <from_chat_id> <to_chat_id>",
+            ),
+            BotCommand::new("refreshnames", "[Admin] 补全缺失的作者名称"),
+            BotCommand::new(
+                "simulate",
+                "[Admin] 模拟订阅推送 - /simulate [ch=<频道ID>] <author_id>",
+            ),
+            BotCommand::new(
+                "mirror",
+                "[Admin] 镜像推送订阅 - /mirror <sub_id> [<chat_id>|remove <chat_id>]",
+            ),
         ]);
         cmds
     }
@@ -138,9 +326,32 @@ impl Command {
         cmds.extend([
             BotCommand::new("setadmin", "[Owner] 设置管理员 - /setadmin <user_id>"),
             BotCommand::new("unsetadmin", "[Owner] 移除管理员 - /unsetadmin <user_id>"),
+            BotCommand::new("config", "[Owner] 查看生效的配置 - /config [section]"),
+            BotCommand::new("logs", "[Owner] 查看日志 - /logs tail [warn|error]"),
+            BotCommand::new(
+                "tagalias",
+                "[Owner] 管理标签别名 - /tagalias [list|add|remove|import]",
+            ),
         ]);
         cmds
     }
+
+    /// 是否为受冷却限制的高开销命令（调用 Pixiv API 或发送大量文件）
+    pub fn is_expensive(&self) -> bool {
+        matches!(
+            self,
+            Command::Download(_)
+                | Command::Search(_)
+                | Command::Related(_)
+                | Command::Random(_)
+                | Command::Top(_)
+                | Command::Preview(_)
+                | Command::EDl(_)
+                | Command::EhInfo(_)
+                | Command::Telegraph(_)
+                | Command::Sauce
+        )
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +450,12 @@ mod tests {
         assert!(Command::parse("/estatus unexpected", "").is_err());
     }
 
+    #[test]
+    fn resolve_is_an_alias_of_sauce() {
+        assert!(matches!(Command::parse("/sauce", ""), Ok(Command::Sauce)));
+        assert!(matches!(Command::parse("/resolve", ""), Ok(Command::Sauce)));
+    }
+
     #[test]
     fn estatus_visibility_follows_eh_configuration_for_all_roles() {
         for commands in [
@@ -1,17 +1,25 @@
 use crate::booru::BooruSiteRegistry;
+use crate::bot::handlers::{AuthorStatusCache, SearchSessions, PREVIEW_BUDGET_EXHAUSTED};
 use crate::bot::link_handler::{parse_pixiv_links, PixivLink};
-use crate::bot::notifier::{DownloadButtonConfig, Notifier, ThrottledBot};
+use crate::bot::notifier::{self, DownloadButtonConfig, Notifier, ThrottledBot};
 use crate::bot::Command;
+use crate::config::{ImageSizePolicy, SharedConfig};
 use crate::db::repo::Repo;
 use crate::db::types::{TagFilter, TaskType, UserRole};
+use crate::pixiv::avatar::AuthorImageCache;
 use crate::pixiv::client::PixivClient;
+use crate::saucenao::SauceNaoClient;
+use crate::scheduler::{AuthorEngine, LatestRelease, NameUpdateEngine, RankingEngine};
+use crate::utils::archive_upload::ArchiveUpload;
 use crate::utils::caption;
+use crate::utils::log_buffer::LogBuffer;
+use crate::utils::zip_workspace::ZipWorkspace;
 use booru_client::PopularScale;
 use std::sync::Arc;
 use teloxide::prelude::*;
-use teloxide::types::ParseMode;
+use teloxide::types::{ParseMode, UserId};
 use teloxide::utils::markdown;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 // ============================================================================
 // BotHandler - Core Handler Structure
@@ -22,12 +30,11 @@ pub struct BotHandler {
     pub(crate) repo: Arc<Repo>,
     pub(crate) pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
     pub(crate) notifier: Notifier,
-    pub(crate) default_sensitive_tags: Vec<String>,
+    /// 运行中的配置，修改 config.toml 后可热重载的部分会随之更新
+    pub(crate) config: SharedConfig,
     pub(crate) owner_id: Option<i64>,
     pub(crate) is_public_mode: bool,
-    pub(crate) image_size: pixiv_client::ImageSize,
-    /// 下载原图阈值 (1-10): 图片数量不超过此值时逐张发送原图
-    pub(crate) download_original_threshold: u8,
+    pub(crate) image_sizes: ImageSizePolicy,
     /// 群组中是否需要 @bot 才响应 (默认: true)
     pub(crate) require_mention_in_group: bool,
     /// 缓存目录路径 (用于管理员查看磁盘占用)
@@ -37,6 +44,30 @@ pub struct BotHandler {
     pub(crate) booru_registry: Arc<BooruSiteRegistry>,
     pub(crate) eh_client: Option<Arc<eh_client::EhClient>>,
     pub(crate) has_telegraph: bool,
+    /// 用于 /simulate 模拟作者订阅的下一次推送
+    pub(crate) author_engine: Arc<AuthorEngine>,
+    /// 用于订阅排行榜后立即推送
+    pub(crate) ranking_engine: Arc<RankingEngine>,
+    /// 用于 /refreshnames 立即补全作者名称
+    pub(crate) name_update_engine: Arc<NameUpdateEngine>,
+    /// /health 使用的作者存在性检查缓存
+    pub(crate) author_status_cache: AuthorStatusCache,
+    /// 作者头像、横幅 URL 缓存 (订阅确认使用)
+    pub(crate) author_images: AuthorImageCache,
+    /// /search 与 /related 的翻页会话
+    pub(crate) search_sessions: SearchSessions,
+    /// 更新检查发现的新版本 (/version 显示)
+    pub(crate) latest_release: LatestRelease,
+    /// 最近的日志 (/logs 显示)
+    pub(crate) log_buffer: LogBuffer,
+    /// 过大的下载 ZIP 上传到 S3 并发送有效期链接 (未启用时为 None)
+    pub(crate) archive_upload: Option<ArchiveUpload>,
+    /// 标签别名数据集文件 (/tagalias import 使用)
+    pub(crate) tag_alias_file: Option<String>,
+    /// /sauce 使用的 SauceNAO 客户端 (未配置 API Key 时为 None)
+    pub(crate) saucenao: Option<Arc<SauceNaoClient>>,
+    /// 下载打包 ZIP 的工作目录 (带空间上限)
+    pub(crate) zip_workspace: Arc<ZipWorkspace>,
 }
 
 impl BotHandler {
@@ -49,34 +80,88 @@ impl BotHandler {
         repo: Arc<Repo>,
         pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
         notifier: Notifier,
-        default_sensitive_tags: Vec<String>,
+        config: SharedConfig,
         owner_id: Option<i64>,
         is_public_mode: bool,
-        image_size: pixiv_client::ImageSize,
-        download_original_threshold: u8,
+        image_sizes: ImageSizePolicy,
         require_mention_in_group: bool,
         cache_dir: String,
         log_dir: String,
         booru_registry: Arc<BooruSiteRegistry>,
         eh_client: Option<Arc<eh_client::EhClient>>,
         has_telegraph: bool,
+        author_engine: Arc<AuthorEngine>,
+        ranking_engine: Arc<RankingEngine>,
+        name_update_engine: Arc<NameUpdateEngine>,
+        latest_release: LatestRelease,
+        log_buffer: LogBuffer,
+        archive_upload: Option<ArchiveUpload>,
+        tag_alias_file: Option<String>,
+        saucenao: Option<Arc<SauceNaoClient>>,
+        zip_workspace: Arc<ZipWorkspace>,
     ) -> Self {
         Self {
             repo,
             pixiv_client,
             notifier,
-            default_sensitive_tags,
+            config,
             owner_id,
             is_public_mode,
-            image_size,
-            download_original_threshold,
+            image_sizes,
             require_mention_in_group,
             cache_dir,
             log_dir,
             booru_registry,
             eh_client,
             has_telegraph,
+            author_engine,
+            ranking_engine,
+            name_update_engine,
+            author_status_cache: AuthorStatusCache::default(),
+            author_images: AuthorImageCache::default(),
+            search_sessions: SearchSessions::default(),
+            latest_release,
+            log_buffer,
+            archive_upload,
+            tag_alias_file,
+            saucenao,
+            zip_workspace,
+        }
+    }
+
+    /// 新聊天默认使用的敏感标签
+    pub(crate) fn default_sensitive_tags(&self) -> Vec<String> {
+        self.config.load().content.sensitive_tags.clone()
+    }
+
+    /// 下载原图阈值 (1-10): 图片数量不超过此值时逐张发送原图
+    pub(crate) fn download_original_threshold(&self) -> u8 {
+        self.config.load().content.download_threshold()
+    }
+
+    /// Route pushes to `chat_id` through `bot`, the last bot that heard from the chat.
+    ///
+    /// `current` is the stored assignment, nothing is written when it already matches.
+    pub(crate) async fn remember_chat_bot(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        current: Option<i64>,
+    ) {
+        let Some(bot_id) = notifier::bot_id(bot) else {
+            return;
+        };
+        if current == Some(bot_id) {
+            return;
+        }
+        if let Err(e) = self.repo.set_chat_bot_id(chat_id.0, bot_id).await {
+            warn!(
+                "Failed to assign bot {} to chat {}: {:#}",
+                bot_id, chat_id, e
+            );
+            return;
         }
+        self.notifier.bots().assign(chat_id, bot_id);
     }
 
     // ------------------------------------------------------------------------
@@ -118,19 +203,54 @@ impl BotHandler {
         match cmd {
             // Help and Info commands (defined in handlers/info.rs)
             Command::Help => self.handle_help(bot, chat_id).await,
+            Command::Version => self.handle_version(bot, chat_id).await,
+            Command::Stats => self.handle_stats(bot, chat_id, user_role.is_owner()).await,
             Command::Info if user_role.is_admin() && chat_id.is_user() => {
                 self.handle_info(bot, chat_id).await
             }
 
             // Subscription commands (defined in handlers/subscription.rs)
-            Command::Sub(args) => self.handle_sub_author(bot, chat_id, user_id, args).await,
+            Command::Sub(args) => {
+                self.handle_sub_author(bot, chat_id, user_id, args, user_role.is_admin())
+                    .await
+            }
             Command::SubRank(args) => self.handle_sub_ranking(bot, chat_id, user_id, args).await,
+            Command::SubBookmarks(args) => {
+                self.handle_sub_bookmarks(bot, chat_id, user_id, args).await
+            }
+            Command::Preview(args) => self.handle_preview(bot, chat_id, args).await,
+            Command::Random(args) => self.handle_random(bot, chat_id, args).await,
+            Command::Top(args) => self.handle_top(bot, chat_id, args).await,
+            Command::Search(args) => self.handle_search(bot, chat_id, args).await,
+            Command::Related(args) => self.handle_related(bot, chat_id, args).await,
+            Command::AuthorStats(args) => self.handle_author_stats(bot, chat_id, args).await,
             Command::Unsub(args) => self.handle_unsub_author(bot, chat_id, user_id, args).await,
             Command::UnsubRank(args) => {
                 self.handle_unsub_ranking(bot, chat_id, user_id, args).await
             }
+            Command::UnsubBookmarks(args) => {
+                self.handle_unsub_bookmarks(bot, chat_id, user_id, args)
+                    .await
+            }
             Command::UnsubThis => self.handle_unsub_this(bot, msg, chat_id).await,
+            Command::UnsubAll(args) => self.handle_unsub_all(bot, chat_id, user_id, args).await,
+            Command::Pause(args) => self.handle_pause(bot, chat_id, user_id, args).await,
+            Command::Resume(args) => self.handle_resume(bot, chat_id, user_id, args).await,
+            Command::SetFilter(args) => self.handle_set_filter(bot, chat_id, user_id, args).await,
             Command::List(args) => self.handle_list(bot, chat_id, user_id, args).await,
+            Command::ExportHtml(args) => self.handle_export_html(bot, chat_id, user_id, args).await,
+            Command::ChannelAdmins(args) => {
+                self.handle_channel_admins(bot, chat_id, user_id, args)
+                    .await
+            }
+            Command::Health(args)
+                if user_role.is_owner() && args.trim().eq_ignore_ascii_case("failing") =>
+            {
+                self.handle_health_failing(bot, chat_id).await
+            }
+            Command::Health(args) if user_role.is_admin() => {
+                self.handle_health(bot, chat_id, user_id, args).await
+            }
 
             // Chat settings command (defined in handlers/settings.rs)
             // Note: The actual settings panel is shown via handle_settings which uses inline keyboards
@@ -143,6 +263,12 @@ impl BotHandler {
             // Download command (defined in handlers/download.rs)
             Command::Download(args) => self.handle_download(bot.clone(), msg, chat_id, args).await,
 
+            // Resend command (defined in handlers/resend.rs)
+            Command::Resend(args) => self.handle_resend(bot, chat_id, args).await,
+
+            // SauceNAO lookup (defined in handlers/sauce.rs)
+            Command::Sauce => self.handle_sauce(bot, msg, chat_id).await,
+
             // Booru subscription commands (defined in handlers/subscription/booru.rs)
             Command::BSub(args) => self.handle_bsub(bot, chat_id, user_id, args).await,
             Command::BUnsub(args) => self.handle_bunsub(bot, chat_id, user_id, args).await,
@@ -168,6 +294,7 @@ impl BotHandler {
             Command::ESub(args) => self.handle_esub(bot, chat_id, user_id, args).await,
             Command::EUnsub(args) => self.handle_eunsub(bot, chat_id, user_id, args).await,
             Command::EDl(args) => self.handle_edl(bot, msg, chat_id, user_id, args).await,
+            Command::EhInfo(args) => self.handle_ehinfo(bot, msg, chat_id, args).await,
             Command::EStatus {} => self.handle_estatus(bot, chat_id).await,
             Command::Telegraph(args) => {
                 self.handle_telegraph(bot, msg, chat_id, user_id, args)
@@ -181,6 +308,34 @@ impl BotHandler {
             Command::DisableChat(args) if user_role.is_admin() => {
                 self.handle_enable_chat(bot, chat_id, args, false).await
             }
+            Command::R18(args) if user_role.is_admin() => self.handle_r18(bot, chat_id, args).await,
+            Command::Original(args) if user_role.is_admin() => {
+                self.handle_original(bot, chat_id, args).await
+            }
+            Command::Ai(args) if user_role.is_admin() => {
+                self.handle_ai_filter(bot, chat_id, args).await
+            }
+            Command::DefaultFilter(args) if user_role.is_admin() => {
+                self.handle_default_filter(bot, chat_id, args).await
+            }
+            Command::BlockAuthor(args) if user_role.is_admin() => {
+                self.handle_block_author(bot, chat_id, args, true).await
+            }
+            Command::UnblockAuthor(args) if user_role.is_admin() => {
+                self.handle_block_author(bot, chat_id, args, false).await
+            }
+            Command::MoveSubs(args) if user_role.is_admin() => {
+                self.handle_move_subs(bot, chat_id, args).await
+            }
+            Command::RefreshNames if user_role.is_admin() => {
+                self.handle_refresh_names(bot, chat_id).await
+            }
+            Command::Simulate(args) if user_role.is_admin() => {
+                self.handle_simulate(bot, chat_id, user_id, args).await
+            }
+            Command::Mirror(args) if user_role.is_admin() => {
+                self.handle_mirror(bot, chat_id, user_id, args).await
+            }
 
             // Owner commands (require owner role, defined in handlers/admin.rs)
             Command::SetAdmin(args) if user_role.is_owner() => {
@@ -189,6 +344,15 @@ impl BotHandler {
             Command::UnsetAdmin(args) if user_role.is_owner() => {
                 self.handle_set_admin(bot, chat_id, args, false).await
             }
+            Command::Config(args) if user_role.is_owner() && chat_id.is_user() => {
+                self.handle_config(bot, chat_id, args).await
+            }
+            Command::Logs(args) if user_role.is_owner() && chat_id.is_user() => {
+                self.handle_logs(bot, chat_id, args).await
+            }
+            Command::TagAlias(args) if user_role.is_owner() => {
+                self.handle_tag_alias(bot, chat_id, args).await
+            }
 
             // Silently ignore unauthorized commands
             _ => Ok(()),
@@ -204,7 +368,7 @@ impl BotHandler {
     /// - 作品链接 (https://www.pixiv.net/artworks/xxx): 一次性推送作品
     /// - 作者链接 (https://www.pixiv.net/users/xxx): 订阅作者
     ///
-    /// 群组中只在被 @ 时响应
+    /// 群组中只在被 @ 时响应；聊天关闭链接预览时不处理链接
     pub async fn handle_message(
         &self,
         bot: ThrottledBot,
@@ -212,6 +376,11 @@ impl BotHandler {
         text: &str,
         ctx: crate::bot::UserChatContext,
     ) -> ResponseResult<()> {
+        // 聊天只使用订阅和命令时忽略链接
+        if !ctx.chat.link_preview {
+            return Ok(());
+        }
+
         // 检查是否包含 Pixiv 链接
         let links = parse_pixiv_links(text);
         if links.is_empty() {
@@ -236,8 +405,33 @@ impl BotHandler {
                     self.handle_illust_link(bot.clone(), chat_id, illust_id, Some(chat_settings))
                         .await?;
                 }
-                PixivLink::User(user_id) => {
-                    self.handle_user_link(bot.clone(), chat_id, user_id).await?;
+                PixivLink::User(author_id) => {
+                    let sender = msg.from.as_ref().map(|u| u.id);
+                    self.handle_user_link(bot.clone(), chat_id, author_id, sender)
+                        .await?;
+                }
+                PixivLink::Username(username) => {
+                    let resolved = self
+                        .pixiv_client
+                        .read()
+                        .await
+                        .resolve_username(&username)
+                        .await;
+                    match resolved {
+                        Ok(author_id) => {
+                            let sender = msg.from.as_ref().map(|u| u.id);
+                            self.handle_user_link(bot.clone(), chat_id, author_id, sender)
+                                .await?;
+                        }
+                        Err(e) => {
+                            error!("Failed to resolve pixiv.me/{}: {:#}", username, e);
+                            bot.send_message(
+                                chat_id,
+                                format!("❌ 无法解析 pixiv.me/{} 对应的用户", username),
+                            )
+                            .await?;
+                        }
+                    }
                 }
             }
         }
@@ -257,6 +451,11 @@ impl BotHandler {
 
         // 获取作品详情
         let pixiv = self.pixiv_client.read().await;
+        if !pixiv.try_reserve_preview(1) {
+            drop(pixiv);
+            bot.send_message(chat_id, PREVIEW_BUDGET_EXHAUSTED).await?;
+            return Ok(());
+        }
         let illust = match pixiv.get_illust_detail(illust_id).await {
             Ok(illust) => illust,
             Err(e) => {
@@ -268,6 +467,40 @@ impl BotHandler {
         };
         drop(pixiv);
 
+        if chat_settings.is_some_and(|chat| crate::utils::sensitive::is_r18_blocked(chat, &illust))
+        {
+            bot.send_message(
+                chat_id,
+                format!("🔞 作品 {} 为 R-18 内容，本聊天未开启 R-18", illust_id),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        if chat_settings.is_some_and(|chat| chat.blocked_authors.contains(&illust.user.id)) {
+            bot.send_message(
+                chat_id,
+                format!("🚫 作品 {} 的作者已被本聊天屏蔽", illust_id),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        if let Some(chat) =
+            chat_settings.filter(|chat| !chat.ai_filter.allows(illust.is_ai_generated()))
+        {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "🤖 作品 {} 不符合本聊天的 AI 作品过滤设置 ({})",
+                    illust_id,
+                    chat.ai_filter.display_name()
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+
         let caption = if illust.is_ugoira() {
             caption::build_ugoira_caption(&illust)
         } else {
@@ -316,8 +549,8 @@ impl BotHandler {
             return Ok(());
         }
 
-        // 获取所有图片 URL (使用配置的尺寸)
-        let image_urls = illust.get_all_image_urls_with_size(self.image_size);
+        // 获取所有图片 URL (使用链接预览尺寸)
+        let image_urls = illust.get_all_image_urls_with_size(self.image_sizes.preview);
 
         // 发送图片
         let _ = self
@@ -340,6 +573,7 @@ impl BotHandler {
         bot: ThrottledBot,
         chat_id: ChatId,
         user_id: u64,
+        sender: Option<UserId>,
     ) -> ResponseResult<()> {
         info!("Subscribing to user {} for chat {}", user_id, chat_id);
 
@@ -356,42 +590,31 @@ impl BotHandler {
         };
         drop(pixiv);
 
-        // 创建或获取任务
+        // 创建任务与订阅
         match self
-            .repo
-            .get_or_create_task(
+            .create_subscription(
+                chat_id.0,
                 TaskType::Author,
-                user_id.to_string(),
-                Some(author.name.clone()),
+                &user_id.to_string(),
+                Some(&author.name),
+                TagFilter::default(),
+                sender,
             )
             .await
         {
-            Ok(task) => {
-                // 创建订阅
-                match self
-                    .repo
-                    .upsert_subscription(chat_id.0, task.id, TagFilter::default())
-                    .await
-                {
-                    Ok(_) => {
-                        let message = format!(
-                            "✅ 成功订阅作者 *{}* \\(ID: `{}`\\)",
-                            markdown::escape(&author.name),
-                            user_id
-                        );
-                        bot.send_message(chat_id, message)
-                            .parse_mode(ParseMode::MarkdownV2)
-                            .await?;
-                    }
-                    Err(e) => {
-                        error!("Failed to create subscription for {}: {:#}", user_id, e);
-                        bot.send_message(chat_id, "❌ 创建订阅失败").await?;
-                    }
-                }
+            Ok(_) => {
+                let message = format!(
+                    "✅ 成功订阅作者 *{}* \\(ID: `{}`\\)",
+                    markdown::escape(&author.name),
+                    user_id
+                );
+                bot.send_message(chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
             }
             Err(e) => {
-                error!("Failed to create task for {}: {:#}", user_id, e);
-                bot.send_message(chat_id, "❌ 创建任务失败").await?;
+                error!("Failed to create subscription for {}: {:#}", user_id, e);
+                bot.send_message(chat_id, "❌ 创建订阅失败").await?;
             }
         }
 
@@ -69,7 +69,7 @@ impl BotHandler {
     ///
     /// # Arguments
     /// * `current_chat_id` - 当前聊天ID（用于发送响应消息）
-    /// * `args` - 目标聊天ID（可选，默认为当前聊天）
+    /// * `args` - 目标聊天ID，多个用逗号或空格分隔（可选，默认为当前聊天）
     /// * `enabled` - true: 启用, false: 禁用
     pub async fn handle_enable_chat(
         &self,
@@ -78,19 +78,19 @@ impl BotHandler {
         args: String,
         enabled: bool,
     ) -> ResponseResult<()> {
-        // Parse target chat_id from args, or use current chat_id
-        let target_chat_id = if args.trim().is_empty() {
-            current_chat_id.0
+        // Parse target chat_ids from args, or use current chat_id
+        let target_chat_ids = if args.trim().is_empty() {
+            vec![current_chat_id.0]
         } else {
-            match args.trim().parse::<i64>() {
-                Ok(id) => id,
-                Err(_) => {
+            match parse_chat_ids(&args) {
+                Some(ids) => ids,
+                None => {
                     bot.send_message(
                         current_chat_id,
                         if enabled {
-                            "❌ 用法: `/enablechat [chat_id]`"
+                            "❌ 用法: `/enablechat [chat_id,...]`"
                         } else {
-                            "❌ 用法: `/disablechat [chat_id]`"
+                            "❌ 用法: `/disablechat [chat_id,...]`"
                         },
                     )
                     .parse_mode(ParseMode::MarkdownV2)
@@ -100,6 +100,15 @@ impl BotHandler {
             }
         };
 
+        let target_chat_id = match target_chat_ids.as_slice() {
+            [target_chat_id] => *target_chat_id,
+            _ => {
+                return self
+                    .set_chats_enabled(bot, current_chat_id, &target_chat_ids, enabled)
+                    .await;
+            }
+        };
+
         match self.repo.set_chat_enabled(target_chat_id, enabled).await {
             Ok(_) => {
                 // 判断是否是当前聊天
@@ -136,4 +145,183 @@ impl BotHandler {
 
         Ok(())
     }
+
+    /// 批量启用或禁用聊天
+    async fn set_chats_enabled(
+        &self,
+        bot: ThrottledBot,
+        current_chat_id: ChatId,
+        chat_ids: &[i64],
+        enabled: bool,
+    ) -> ResponseResult<()> {
+        match self.repo.set_chats_enabled(chat_ids, enabled).await {
+            Ok(count) => {
+                let ids = chat_ids
+                    .iter()
+                    .map(|id| format!("`{}`", id))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let message = format!(
+                    "✅ 已{} {} 个聊天: {}",
+                    if enabled { "启用" } else { "禁用" },
+                    count,
+                    ids
+                );
+                bot.send_message(current_chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+
+                info!(
+                    "Admin {} {} chats",
+                    if enabled { "enabled" } else { "disabled" },
+                    count
+                );
+            }
+            Err(e) => {
+                error!("Failed to set chats enabled status: {:#}", e);
+                bot.send_message(current_chat_id, "❌ 更新聊天状态失败")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将一个聊天的全部订阅转移到另一个聊天
+    ///
+    /// # Arguments
+    /// * `args` - `<from_chat_id> <to_chat_id>`
+    pub async fn handle_move_subs(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let parsed = match args.split_whitespace().collect::<Vec<_>>()[..] {
+            [from, to] => from.parse::<i64>().ok().zip(to.parse::<i64>().ok()),
+            _ => None,
+        };
+        let Some((from_chat_id, to_chat_id)) = parsed else {
+            bot.send_message(chat_id, "❌ 用法: `/movesubs <from_chat_id> <to_chat_id>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        if from_chat_id == to_chat_id {
+            bot.send_message(chat_id, "❌ 源聊天和目标聊天不能相同")
+                .await?;
+            return Ok(());
+        }
+
+        for (id, label) in [(from_chat_id, "源聊天"), (to_chat_id, "目标聊天")] {
+            match self.repo.get_chat(id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    bot.send_message(chat_id, format!("❌ {} `{}` 不存在", label, id))
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Failed to get chat {}: {:#}", id, e);
+                    bot.send_message(chat_id, "❌ 获取聊天信息失败").await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        match self.repo.move_subscriptions(from_chat_id, to_chat_id).await {
+            Ok(result) => {
+                let mut message = format!(
+                    "✅ 已将 {} 个订阅从 `{}` 转移到 `{}`",
+                    result.moved, from_chat_id, to_chat_id
+                );
+                if result.duplicates > 0 {
+                    message.push_str(&format!(
+                        "\n♻️ {} 个重复订阅已保留目标聊天的设置",
+                        result.duplicates
+                    ));
+                }
+                bot.send_message(chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+
+                info!(
+                    "Admin moved {} subscriptions from chat {} to chat {} ({} duplicates dropped)",
+                    result.moved, from_chat_id, to_chat_id, result.duplicates
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Failed to move subscriptions from {} to {}: {:#}",
+                    from_chat_id, to_chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 转移订阅失败，未做任何更改")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 立即为缺少名称的作者订阅补全名称
+    pub async fn handle_refresh_names(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+    ) -> ResponseResult<()> {
+        bot.send_message(chat_id, "🔄 正在补全作者名称...").await?;
+
+        let message = match self.name_update_engine.backfill_missing_names().await {
+            Ok(summary) if summary.updated == 0 && summary.failed == 0 => {
+                "✅ 所有作者均已有名称".to_string()
+            }
+            Ok(summary) => {
+                let mut message = format!("✅ 已补全 {} 个作者名称", summary.updated);
+                if summary.failed > 0 {
+                    message.push_str(&format!("\n⚠️ {} 个作者获取失败，详见日志", summary.failed));
+                }
+                message
+            }
+            Err(e) => {
+                error!("Failed to backfill author names: {:#}", e);
+                "❌ 补全作者名称失败".to_string()
+            }
+        };
+        bot.send_message(chat_id, message).await?;
+
+        Ok(())
+    }
+}
+
+/// Parse chat IDs separated by commas or whitespace, deduplicated in order; None if any is invalid
+fn parse_chat_ids(args: &str) -> Option<Vec<i64>> {
+    let mut chat_ids = Vec::new();
+    for part in args
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+    {
+        let chat_id = part.parse::<i64>().ok()?;
+        if !chat_ids.contains(&chat_id) {
+            chat_ids.push(chat_id);
+        }
+    }
+    (!chat_ids.is_empty()).then_some(chat_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_chat_ids_accepts_commas_and_spaces() {
+        assert_eq!(
+            parse_chat_ids("-1001, 42 -1001,,7"),
+            Some(vec![-1001, 42, 7])
+        );
+        assert_eq!(parse_chat_ids("42"), Some(vec![42]));
+        assert_eq!(parse_chat_ids("42,abc"), None);
+        assert_eq!(parse_chat_ids(" , "), None);
+    }
 }
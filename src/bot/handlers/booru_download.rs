@@ -1,10 +1,9 @@
-use super::download::sanitize_filename;
+use super::download::{sanitize_filename, send_failure_text};
 use crate::bot::link_handler::BooruPostRef;
 use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
 use anyhow::{Context, Result};
-use std::future::Future;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use teloxide::prelude::*;
 use teloxide::types::ChatAction;
 use teloxide::utils::markdown;
@@ -83,33 +82,28 @@ impl BotHandler {
 
         let caption = build_booru_caption(&titles, &failed);
 
-        if files.len() <= self.download_original_threshold as usize {
+        if files.len() <= self.download_original_threshold() as usize {
             for (i, (path, name)) in files.iter().enumerate() {
                 let cap = if i == 0 { caption.as_str() } else { "" };
-                if let Err(e) = self.send_document(&bot, chat_id, path, name, cap).await {
-                    warn!("Failed to send booru document {}: {:#}", name, e);
+                match self.send_document(&bot, chat_id, path, name, cap).await {
+                    Ok(()) => self.notifier.get_downloader().mark_delivered(path),
+                    Err(e) => warn!("Failed to send booru document {}: {:#}", name, e),
                 }
             }
         } else {
-            match self.create_zip_file(&files).await {
-                Ok(zip_path) => {
-                    let zip_name = format!(
-                        "booru_{}_files_{}.zip",
-                        files.len(),
-                        chrono::Local::now().format("%Y%m%d_%H%M%S")
-                    );
-                    let send_result = remove_file_after(
-                        &zip_path,
-                        self.send_document(&bot, chat_id, &zip_path, &zip_name, &caption),
-                    )
-                    .await;
-                    if let Err(e) = send_result {
-                        warn!("Failed to send booru zip: {:#}", e);
-                    }
-                }
+            let zip_name = format!(
+                "booru_{}_files_{}.zip",
+                files.len(),
+                chrono::Local::now().format("%Y%m%d_%H%M%S")
+            );
+            match self
+                .send_files_as_zip(&bot, chat_id, &zip_name, &caption, &files)
+                .await
+            {
+                Ok(()) => self.mark_files_delivered(&files),
                 Err(e) => {
-                    error!("Failed to create booru zip: {:#}", e);
-                    bot.send_message(chat_id, "❌ 打包失败").await?;
+                    error!("Failed to send booru zip: {:#}", e);
+                    bot.send_message(chat_id, send_failure_text(&e)).await?;
                 }
             }
         }
@@ -187,17 +181,6 @@ fn booru_post_image_urls(post: &booru_client::BooruPost) -> Vec<&str> {
         .collect()
 }
 
-async fn remove_file_after<T, E, Fut>(path: &Path, operation: Fut) -> std::result::Result<T, E>
-where
-    Fut: Future<Output = std::result::Result<T, E>>,
-{
-    let result = operation.await;
-    if let Err(e) = tokio::fs::remove_file(path).await {
-        warn!("Failed to remove temp ZIP file: {:#}", e);
-    }
-    result
-}
-
 fn build_booru_caption(titles: &[String], failed: &[String]) -> String {
     let mut s = String::from("📥 *下载完成*\n\n");
     if titles.len() == 1 {
@@ -216,7 +199,7 @@ fn build_booru_caption(titles: &[String], failed: &[String]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{booru_post_image_urls, remove_file_after};
+    use super::booru_post_image_urls;
     use booru_client::{BooruPost, BooruRating};
 
     fn make_post() -> BooruPost {
@@ -256,17 +239,4 @@ mod tests {
 
         assert_eq!(booru_post_image_urls(&post), ["jpeg"]);
     }
-
-    #[tokio::test]
-    async fn remove_file_after_cleans_zip_after_successful_send() {
-        let temp = tempfile::NamedTempFile::new().unwrap();
-        let path = temp.into_temp_path().to_path_buf();
-        tokio::fs::write(&path, b"zip data").await.unwrap();
-
-        remove_file_after(&path, async { Ok::<_, anyhow::Error>(()) })
-            .await
-            .unwrap();
-
-        assert!(!path.exists());
-    }
 }
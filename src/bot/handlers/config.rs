@@ -0,0 +1,97 @@
+use super::logs::chunk_lines;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tracing::{error, warn};
+
+/// Budget per message in UTF-16 units, leaving room for the code block and escapes
+const CONFIG_MESSAGE_BUDGET: usize = 3500;
+
+/// Lines of the `[section]` blocks named by `filter`, or all lines when it is empty
+fn select_sections(lines: &[String], filter: &str) -> Vec<String> {
+    let filter = filter.trim().trim_matches(['[', ']']).to_lowercase();
+    if filter.is_empty() {
+        return lines.to_vec();
+    }
+
+    let header = format!("[{}]", filter);
+    lines
+        .iter()
+        .skip_while(|line| **line != header)
+        .take_while(|line| !line.is_empty())
+        .cloned()
+        .collect()
+}
+
+impl BotHandler {
+    /// 查看当前生效的配置 (含热重载的修改)，密钥已隐藏，并标注来自环境变量或默认值的项 (仅 Owner 私聊)
+    pub async fn handle_config(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let effective_config = match self.config.load().effective_lines() {
+            Ok(lines) => lines,
+            Err(e) => {
+                error!("Failed to render effective configuration: {:#}", e);
+                bot.send_message(chat_id, "❌ 读取配置失败").await?;
+                return Ok(());
+            }
+        };
+        let lines = select_sections(&effective_config, &args_str);
+        if lines.is_empty() {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "❌ 没有名为 `{}` 的配置段",
+                    markdown::escape_code(args_str.trim())
+                ),
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+            return Ok(());
+        }
+
+        for chunk in chunk_lines(&lines, CONFIG_MESSAGE_BUDGET) {
+            if let Err(e) = bot
+                .send_message(chat_id, markdown::code_block_with_lang(&chunk, "toml"))
+                .parse_mode(ParseMode::MarkdownV2)
+                .await
+            {
+                warn!("Failed to send configuration to chat {}: {:#}", chat_id, e);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_sections_returns_one_section_or_everything() {
+        let lines: Vec<String> = [
+            "[http]",
+            "enabled = false",
+            "",
+            "[logging]",
+            "level = \"info\"",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        assert_eq!(select_sections(&lines, ""), lines);
+        assert_eq!(
+            select_sections(&lines, " HTTP "),
+            vec!["[http]".to_string(), "enabled = false".to_string()]
+        );
+        assert_eq!(select_sections(&lines, "[logging]").len(), 2);
+        assert!(select_sections(&lines, "pixiv").is_empty());
+    }
+}
@@ -7,13 +7,16 @@
 use crate::bot::link_handler::{
     parse_booru_post_links, parse_pixiv_links, BooruPostRef, PixivLink,
 };
-use crate::bot::notifier::ThrottledBot;
+use crate::bot::notifier::{check_upload_size, FileTooLarge, ThrottledBot};
 use crate::bot::BotHandler;
+use crate::utils::zip_workspace::{estimate_zip_size, WorkspaceFull};
 use anyhow::{Context, Result};
 use chrono::Local;
 use regex::Regex;
 use std::collections::HashSet;
+use std::future::Future;
 use std::io::Write;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use teloxide::prelude::*;
 use teloxide::types::{ChatAction, InputFile, MessageEntityKind, MessageEntityRef, ParseMode};
@@ -24,6 +27,21 @@ use tracing::{error, info, warn};
 /// Page number prefix for multi-page artworks in filenames
 const PAGE_PREFIX: &str = "p";
 
+/// Room left in each ZIP volume for entry headers and the central directory
+const ZIP_VOLUME_OVERHEAD: u64 = 1024 * 1024;
+
+/// Download refused because the work is R-18 and the chat has R-18 disabled
+#[derive(Debug)]
+struct R18Blocked;
+
+impl std::fmt::Display for R18Blocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("R-18 works are disabled in this chat")
+    }
+}
+
+impl std::error::Error for R18Blocked {}
+
 impl BotHandler {
     /// Handle /download command
     ///
@@ -244,16 +262,29 @@ impl BotHandler {
         illust_ids: Vec<u64>,
     ) -> ResponseResult<()> {
         let mut failed_ids = Vec::new();
+        let mut blocked_ids = Vec::new();
         let mut all_files: Vec<(PathBuf, String)> = Vec::new(); // (path, sanitized_filename)
         let mut work_info: Vec<(String, String)> = Vec::new(); // (title, artist)
 
+        let allow_r18 = match self.repo.get_chat(chat_id.0).await {
+            Ok(chat) => chat.is_some_and(|chat| chat.allow_r18),
+            Err(e) => {
+                error!("Failed to get chat {} for download: {:#}", chat_id, e);
+                false
+            }
+        };
+
         // Download all illusts
         for illust_id in &illust_ids {
-            match self.download_illust(*illust_id).await {
+            match self.download_illust(*illust_id, allow_r18).await {
                 Ok((files, title, artist)) => {
                     all_files.extend(files);
                     work_info.push((title, artist));
                 }
+                Err(e) if e.downcast_ref::<R18Blocked>().is_some() => {
+                    info!("Skipping R-18 illust {} in chat {}", illust_id, chat_id);
+                    blocked_ids.push(*illust_id);
+                }
                 Err(e) => {
                     error!("Failed to download illust {}: {:#}", illust_id, e);
                     failed_ids.push(*illust_id);
@@ -262,15 +293,20 @@ impl BotHandler {
         }
 
         if all_files.is_empty() {
-            bot.send_message(chat_id, "❌ 所有作品下载失败").await?;
+            let message = if failed_ids.is_empty() {
+                "🔞 作品为 R-18 内容，本聊天未开启 R-18"
+            } else {
+                "❌ 所有作品下载失败"
+            };
+            bot.send_message(chat_id, message).await?;
             return Ok(());
         }
 
         // Build caption with work info and errors
-        let caption = self.build_download_caption(&work_info, &failed_ids);
+        let caption = self.build_download_caption(&work_info, &failed_ids, &blocked_ids);
 
         // Send files based on threshold
-        let threshold = self.download_original_threshold as usize;
+        let threshold = self.download_original_threshold() as usize;
         if all_files.len() <= threshold {
             // Within threshold - send each file separately
             for (idx, (path, filename)) in all_files.iter().enumerate() {
@@ -278,33 +314,23 @@ impl BotHandler {
                 let cap = if idx == 0 { caption.as_str() } else { "" };
                 if let Err(e) = self.send_document(&bot, chat_id, path, filename, cap).await {
                     error!("Failed to send document {}: {:#}", filename, e);
-                    let _ = bot.send_message(chat_id, "❌ 发送文件失败").await;
+                    let _ = bot.send_message(chat_id, send_failure_text(&e)).await;
                     break;
                 }
+                self.notifier.get_downloader().mark_delivered(path);
                 // Rate limiting is now handled by the Throttle adaptor
             }
         } else {
             // Exceeds threshold - create ZIP and send
-            match self.create_zip_file(&all_files).await {
-                Ok(zip_path) => {
-                    let zip_filename =
-                        format!("pixiv_{}_works.zip", Local::now().format("%Y%m%d_%H%M%S"));
-                    if let Err(e) = self
-                        .send_document(&bot, chat_id, &zip_path, &zip_filename, &caption)
-                        .await
-                    {
-                        error!("Failed to send document: {:#}", e);
-                        bot.send_message(chat_id, "❌ 发送文件失败").await?;
-                    }
-
-                    // Clean up temp ZIP file
-                    if let Err(e) = tokio::fs::remove_file(&zip_path).await {
-                        warn!("Failed to remove temp ZIP file: {:#}", e);
-                    }
-                }
+            let zip_filename = format!("pixiv_{}_works.zip", Local::now().format("%Y%m%d_%H%M%S"));
+            match self
+                .send_files_as_zip(&bot, chat_id, &zip_filename, &caption, &all_files)
+                .await
+            {
+                Ok(()) => self.mark_files_delivered(&all_files),
                 Err(e) => {
-                    error!("Failed to create ZIP file: {:#}", e);
-                    bot.send_message(chat_id, "❌ 创建压缩文件失败").await?;
+                    error!("Failed to send ZIP: {:#}", e);
+                    bot.send_message(chat_id, send_failure_text(&e)).await?;
                 }
             }
         }
@@ -316,6 +342,7 @@ impl BotHandler {
     async fn download_illust(
         &self,
         illust_id: u64,
+        allow_r18: bool,
     ) -> Result<(Vec<(PathBuf, String)>, String, String)> {
         info!("Downloading illust {}", illust_id);
 
@@ -326,6 +353,10 @@ impl BotHandler {
             .await
             .context("Failed to fetch illust details")?;
 
+        if !allow_r18 && crate::utils::sensitive::is_r18(&illust) {
+            return Err(R18Blocked.into());
+        }
+
         // For ugoira works, download as MP4 instead of static images
         if illust.is_ugoira() {
             #[cfg(feature = "ffmpeg-codec")]
@@ -365,7 +396,7 @@ impl BotHandler {
 
         let title = illust.title.clone();
         let artist = illust.user.name.clone();
-        let urls = illust.get_all_image_urls();
+        let urls = illust.get_all_image_urls_with_size(self.image_sizes.download());
 
         // Download all pages
         let downloader = &self.notifier.get_downloader();
@@ -410,21 +441,24 @@ impl BotHandler {
         Ok((files, title, artist))
     }
 
-    /// Create a ZIP file from multiple files
+    /// Create a ZIP file from multiple files in the ZIP work directory
+    ///
+    /// The estimated size is held against the directory quota while writing, and a
+    /// partly written ZIP is removed when packing fails.
     pub(super) async fn create_zip_file(&self, files: &[(PathBuf, String)]) -> Result<PathBuf> {
-        let temp_dir = std::env::temp_dir();
-        let zip_filename = format!(
-            "pixivbot_download_{}.zip",
-            Local::now().format("%Y%m%d_%H%M%S%3f")
-        );
-        let zip_path = temp_dir.join(zip_filename);
+        let sizes = file_sizes(files).await?;
+        let _reservation = self
+            .zip_workspace
+            .reserve(estimate_zip_size(&sizes))
+            .await?;
+        let zip_path = self.zip_workspace.zip_path();
 
         // Clone data needed for the blocking task
         let files_clone: Vec<(PathBuf, String)> = files.to_vec();
         let zip_path_clone = zip_path.clone();
 
         // Run synchronous ZIP operations in a blocking task
-        tokio::task::spawn_blocking(move || {
+        let result = tokio::task::spawn_blocking(move || {
             let zip_file =
                 std::fs::File::create(&zip_path_clone).context("Failed to create ZIP file")?;
             let mut zip = zip::ZipWriter::new(zip_file);
@@ -444,10 +478,62 @@ impl BotHandler {
             Ok::<PathBuf, anyhow::Error>(zip_path_clone)
         })
         .await
-        .context("ZIP creation task panicked")?
+        .context("ZIP creation task panicked")
+        .and_then(|result| result);
+
+        if result.is_err() {
+            if let Err(e) = tokio::fs::remove_file(&zip_path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to remove partial ZIP {:?}: {:#}", zip_path, e);
+                }
+            }
+        }
+        result
     }
 
-    /// Send a document file
+    /// Pack `files` into a ZIP and send it, see [`Self::send_zip`]
+    ///
+    /// When the estimated size is already over the upload limit and the ZIP would not
+    /// be uploaded for a link, the volumes are built right away instead of a full ZIP first.
+    pub(super) async fn send_files_as_zip(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        filename: &str,
+        caption: &str,
+        files: &[(PathBuf, String)],
+    ) -> Result<()> {
+        let estimate = estimate_zip_size(&file_sizes(files).await?);
+        let limit = self.notifier.upload_limit();
+        let uploaded = self
+            .archive_upload
+            .as_ref()
+            .is_some_and(|archive_upload| archive_upload.should_upload(estimate));
+        if estimate > limit && !uploaded {
+            return self
+                .send_zip_volumes(bot, chat_id, filename, caption, files, limit)
+                .await;
+        }
+
+        let zip_path = self.create_zip_file(files).await?;
+        remove_file_after(
+            &zip_path,
+            self.send_zip(bot, chat_id, &zip_path, filename, caption, files),
+        )
+        .await
+    }
+
+    /// Report cached originals as delivered so the cache can drop them early
+    ///
+    /// Only has an effect when `telegram.local_file_cleanup` is enabled.
+    pub(super) fn mark_files_delivered(&self, files: &[(PathBuf, String)]) {
+        let downloader = self.notifier.get_downloader();
+        for (path, _) in files {
+            downloader.mark_delivered(path);
+        }
+    }
+
+    /// Send a document file, refusing files over the upload limit with [`FileTooLarge`]
     pub(super) async fn send_document(
         &self,
         bot: &ThrottledBot,
@@ -456,6 +542,7 @@ impl BotHandler {
         filename: &str,
         caption: &str,
     ) -> Result<()> {
+        check_upload_size(path, self.notifier.upload_limit()).await?;
         let input_file = InputFile::file(path).file_name(filename.to_string());
 
         bot.send_document(chat_id, input_file)
@@ -467,8 +554,109 @@ impl BotHandler {
         Ok(())
     }
 
+    /// Send a generated ZIP of `files`, uploading it for an expiring link instead when it is
+    /// too large, or splitting it into volumes under the Telegram upload limit
+    pub(super) async fn send_zip(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        path: &Path,
+        filename: &str,
+        caption: &str,
+        files: &[(PathBuf, String)],
+    ) -> Result<()> {
+        let size = tokio::fs::metadata(path)
+            .await
+            .context("Failed to read ZIP file size")?
+            .len();
+        let Some(archive_upload) = self
+            .archive_upload
+            .as_ref()
+            .filter(|archive_upload| archive_upload.should_upload(size))
+        else {
+            let limit = self.notifier.upload_limit();
+            if size <= limit {
+                return self
+                    .send_document(bot, chat_id, path, filename, caption)
+                    .await;
+            }
+            return self
+                .send_zip_volumes(bot, chat_id, filename, caption, files, limit)
+                .await;
+        };
+
+        info!(
+            "ZIP {} is {} bytes, uploading it for chat {}",
+            filename, size, chat_id
+        );
+        let url = archive_upload.upload(path, filename).await?;
+        let text = format!(
+            "{}\n\n📦 [{}]({}) \\({:.1} MB，链接 {} 小时内有效\\)",
+            caption,
+            markdown::escape(filename),
+            markdown::escape_link_url(&url),
+            size as f64 / (1024.0 * 1024.0),
+            archive_upload.link_expiry_hours()
+        );
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await
+            .context("Failed to send ZIP link")?;
+
+        Ok(())
+    }
+
+    /// Send `files` as several ZIP volumes that each fit the upload limit
+    async fn send_zip_volumes(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        filename: &str,
+        caption: &str,
+        files: &[(PathBuf, String)],
+        limit: u64,
+    ) -> Result<()> {
+        let sizes = file_sizes(files).await?;
+        let Some(volumes) = split_into_volumes(&sizes, limit.saturating_sub(ZIP_VOLUME_OVERHEAD))
+        else {
+            let size = sizes.iter().copied().max().unwrap_or_default();
+            return Err(FileTooLarge { size, limit }.into());
+        };
+
+        info!(
+            "ZIP {} is over the upload limit, sending {} volumes to chat {}",
+            filename,
+            volumes.len(),
+            chat_id
+        );
+        let stem = filename.strip_suffix(".zip").unwrap_or(filename);
+        for (index, range) in volumes.iter().enumerate() {
+            let volume_path = self.create_zip_file(&files[range.clone()]).await?;
+            let volume_name = format!("{}.part{}.zip", stem, index + 1);
+            let volume_caption = if index == 0 {
+                format!("{}\n\n📦 分卷 1/{}", caption.trim_end(), volumes.len())
+            } else {
+                format!("📦 分卷 {}/{}", index + 1, volumes.len())
+            };
+            let result = self
+                .send_document(bot, chat_id, &volume_path, &volume_name, &volume_caption)
+                .await;
+            if let Err(e) = tokio::fs::remove_file(&volume_path).await {
+                warn!("Failed to remove temp ZIP volume: {:#}", e);
+            }
+            result?;
+        }
+
+        Ok(())
+    }
+
     /// Build caption with work info and error report
-    fn build_download_caption(&self, work_info: &[(String, String)], failed_ids: &[u64]) -> String {
+    fn build_download_caption(
+        &self,
+        work_info: &[(String, String)],
+        failed_ids: &[u64],
+        blocked_ids: &[u64],
+    ) -> String {
         let mut caption = String::from("📥 *下载完成*\n\n");
 
         // Add work info
@@ -491,6 +679,13 @@ impl BotHandler {
             }
         }
 
+        if !blocked_ids.is_empty() {
+            caption.push_str("\n🔞 *以下作品为 R\\-18，本聊天未开启*\n");
+            for id in blocked_ids {
+                caption.push_str(&format!("• ID: `{}`\n", id));
+            }
+        }
+
         caption
     }
 
@@ -536,6 +731,70 @@ impl BotHandler {
     }
 }
 
+/// Run `operation`, then delete the temporary ZIP at `path` whatever the outcome
+async fn remove_file_after<T, E, Fut>(path: &Path, operation: Fut) -> std::result::Result<T, E>
+where
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let result = operation.await;
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        warn!("Failed to remove temp ZIP file: {:#}", e);
+    }
+    result
+}
+
+/// Sizes of the files to be packed, in order
+async fn file_sizes(files: &[(PathBuf, String)]) -> Result<Vec<u64>> {
+    let mut sizes = Vec::with_capacity(files.len());
+    for (path, _) in files {
+        let size = tokio::fs::metadata(path)
+            .await
+            .context("Failed to read file size")?
+            .len();
+        sizes.push(size);
+    }
+    Ok(sizes)
+}
+
+/// Split files, in order, into volumes whose summed size stays within `limit`
+///
+/// Returns `None` when a single file is larger than `limit`.
+fn split_into_volumes(sizes: &[u64], limit: u64) -> Option<Vec<Range<usize>>> {
+    let mut volumes = Vec::new();
+    let mut start = 0;
+    let mut volume_size = 0;
+    for (index, &size) in sizes.iter().enumerate() {
+        if size > limit {
+            return None;
+        }
+        if volume_size + size > limit {
+            volumes.push(start..index);
+            start = index;
+            volume_size = 0;
+        }
+        volume_size += size;
+    }
+    if start < sizes.len() {
+        volumes.push(start..sizes.len());
+    }
+    Some(volumes)
+}
+
+/// Reply for a failed file send, explaining the Telegram upload limit when it was hit
+pub(super) fn send_failure_text(e: &anyhow::Error) -> String {
+    match e.downcast_ref::<FileTooLarge>() {
+        Some(too_large) => format!(
+            "❌ 文件大小 {:.1} MB，超过 Telegram 上传上限 {} MB，无法发送",
+            too_large.size as f64 / (1024.0 * 1024.0),
+            too_large.limit / (1024 * 1024)
+        ),
+        None if e.downcast_ref::<WorkspaceFull>().is_some() => {
+            "❌ 临时存储空间不足，请稍后重试".to_string()
+        }
+        None => "❌ 发送文件失败".to_string(),
+    }
+}
+
 /// Sanitize filename by replacing illegal filesystem characters with underscore
 pub(super) fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -619,6 +878,16 @@ fn args_have_bare_pixiv_ids_outside_eh_urls(args: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_into_volumes_keeps_order_and_limit() {
+        assert_eq!(
+            split_into_volumes(&[30, 30, 50, 10, 100], 100),
+            Some(vec![0..2, 2..4, 4..5])
+        );
+        assert_eq!(split_into_volumes(&[30, 101], 100), None);
+        assert_eq!(split_into_volumes(&[], 100), Some(vec![]));
+    }
+
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("normal_title"), "normal_title");
@@ -758,4 +1027,17 @@ mod tests {
         assert_eq!(galleries.len(), 1);
         assert_eq!(galleries[0], (99999, "deadbeef00".to_string()));
     }
+
+    #[tokio::test]
+    async fn remove_file_after_cleans_zip_after_successful_send() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let path = temp.into_temp_path().to_path_buf();
+        tokio::fs::write(&path, b"zip data").await.unwrap();
+
+        remove_file_after(&path, async { Ok::<_, anyhow::Error>(()) })
+            .await
+            .unwrap();
+
+        assert!(!path.exists());
+    }
 }
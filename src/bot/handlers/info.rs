@@ -1,8 +1,13 @@
 use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
+use crate::db::repo::PushStats;
+use crate::utils::version;
+use chrono::{NaiveDateTime, Utc};
 use std::path::Path;
 use teloxide::prelude::*;
 use teloxide::types::ParseMode;
+use teloxide::utils::markdown;
+use tracing::error;
 
 /// 计算目录的总大小（递归）
 fn calculate_dir_size(path: &Path) -> u64 {
@@ -57,20 +62,67 @@ impl BotHandler {
 
 *可用命令:*
 
-📌 `/sub <id,...> [+tag1 \-tag2]`
+📌 `/sub [interval=<分钟>] <id,...> [+tag1 \-tag2] [spoiler=<模式>] [push_mode=<模式>] [silent=true] [topic=<话题ID>] [ai=<模式>] [size=<尺寸>]`
    订阅 Pixiv 作者
    \- `<id,...>`: 以逗号分隔的 Pixiv 用户 ID
+   \- `interval`: 自定义该作者的轮询间隔 \(10\-10080 分钟，0 恢复默认，仅管理员\)
+   \- `spoiler`: `always` 总是遮罩，`never` 从不遮罩，`auto` 跟随聊天设置
+   \- `push_mode`: `album` 将同一轮发现的多个新作品合并为相册发送，`single` 逐个推送 \(默认\)
+   \- `silent=true`: 该订阅的推送不发出通知提醒
+   \- `topic=<话题ID>`: 超级群组中推送到指定话题，`off` 恢复默认
+   \- `ai=exclude|only`: 排除或只推送 AI 生成作品，`all` 不过滤，`auto` 跟随聊天设置
+   \- `size=original|large|medium`: 推送图片的尺寸，`auto` 跟随全局配置
    \- `\+tag`: 仅包含带有此标签的作品
    \- `\-tag`: 排除带有此标签的作品
    \- 示例: `/sub 123456,789012 \+原神 \-R\-18`
 
-📊 `/subrank <mode> [+tag1 \-tag2]`
+📊 `/subrank [start=tomorrow|now] [date=prev] [silent=true] [topic=<话题ID>] [ai=<模式>] [size=<尺寸>] <mode,...> [+tag1 \-tag2]`
    订阅 Pixiv 排行榜
    \- 模式: `day`, `week`, `month`, `day_male`, `day_female`, `week_original`, `week_rookie`, `day_manga`
    \- R18 模式: `day_r18`, `week_r18`, `week_r18g`, `day_male_r18`, `day_female_r18`
    \- `\+tag`: 仅包含带有此标签的作品
    \- `\-tag`: 排除带有此标签的作品
-   \- 示例: `/subrank day \+原神`
+   \- `start=tomorrow`（默认）: 今日排行已推送时跳过今日作品；`start=now`: 立即推送今日排行
+   \- `date=prev`: 推送前一天已结算的排行而不是最新排行，`date=\-<天数>` 推送更早的排行 \(最多 7 天\)
+   \- `silent=true`: 该订阅的推送不发出通知提醒
+   \- `topic=<话题ID>`: 超级群组中推送到指定话题，`off` 恢复默认
+   \- `ai=exclude|only`: 排除或只推送 AI 生成作品，`all` 不过滤，`auto` 跟随聊天设置
+   \- `size=original|large|medium`: 推送图片的尺寸，`auto` 跟随全局配置
+   \- 可用逗号分隔一次订阅多个模式，也可使用 `daily`、`weekly`、`original` 等别名
+   \- 示例: `/subrank day \+原神`、`/subrank daily,weekly,original`
+
+🔖 `/subbookmarks <user_id> [+tag1 \-tag2]`
+   订阅 Pixiv 用户的公开收藏，推送其新收藏的作品
+   \- 首次订阅只推送最近收藏的一个作品
+   \- 示例: `/subbookmarks 123456 \-R\-18`
+
+🔍 `/preview <author_id> [+tag1 \-tag2]`
+   预览订阅该作者会推送哪些作品（不会创建订阅）
+   \- 示例: `/preview 123456 \-R\-18`
+
+🎲 `/random [author_id]`
+   随机推送一个作品
+   \- 不指定作者时从本聊天订阅的作者中随机挑选
+   \- 遵循订阅和聊天的标签过滤及模糊设置
+
+📊 `/top <mode> [YYYY\-MM\-DD] [count]`
+   立即查看排行榜前 N 名（不会创建订阅）
+   \- 默认 10 个，最多 30 个；不指定日期时为最新一期
+   \- 遵循聊天的排除标签、屏蔽作者和 R\-18 设置
+   \- 示例: `/top daily 5`、`/top week 2026\-01\-01`
+
+🔍 `/search <关键词>`
+   按标签关键词搜索 Pixiv 作品，每页 10 个，点击按钮查看下一页
+   \- 高级会员账号按热度排序，否则按时间排序
+   \- 遵循聊天的排除标签、R\-18 和模糊设置
+
+🔗 `/related <illust_id|URL>`
+   查看与作品相似的作品，每批 10 个，点击按钮查看更多
+   \- 遵循聊天的排除标签、R\-18 和模糊设置
+
+📊 `/authorstats <author_id>`
+   查看作者的作品数量、近 8 周的发布频率、平均收藏数和本聊天的推送情况
+   \- 示例: `/authorstats 123456`
 
 🗑 `/unsub <author_id,...>`
    取消订阅作者
@@ -81,6 +133,37 @@ impl BotHandler {
    取消订阅排行榜
    \- 示例: `/unsubrank day`
 
+🗑 `/unsubbookmarks <user_id>`
+   取消订阅用户收藏
+
+🧹 `/unsuball [authors|rank|eh]`
+   一次取消本聊天某一类型的全部订阅，需点击按钮确认
+   \- 不填类型时取消全部订阅
+   \- 示例: `/unsuball rank`
+
+⏸ `/pause [author_id,...|all]`
+   暂停订阅，暂停期间不推送
+   \- 不填或 `all` 暂停本聊天的全部订阅
+   \- 示例: `/pause 123456`
+
+▶️ `/resume [author_id,...|all]`
+   恢复已暂停的订阅，从暂停前的进度继续推送
+
+🏷 `/setfilter <author_id|mode> <+tag1 \-tag2|clear>`
+   修改已有订阅的标签过滤，推送进度保持不变
+   \- `clear` 清除过滤条件
+   \- 示例: `/setfilter 123456 \+原神 \-R\-18`
+
+📄 `/exporthtml`
+   将本聊天的订阅导出为 HTML 文件，方便分享给社区
+   \- 包含作者名称与链接、过滤条件和最近推送时间
+
+👥 `/channeladmins ch=<频道ID> [add|remove <user_id>]`
+   指定可管理频道订阅的用户
+   \- 列表为空时所有频道管理员均可管理
+   \- 示例: `/channeladmins ch=@mychannel add 123456`
+   \- 不知道频道 ID 时，私聊转发一条频道消息给 Bot 即可通过按钮订阅
+
 🔒 `/blursensitive <on|off>`
    启用或禁用敏感内容模糊
    \- 示例: `/blursensitive on`
@@ -98,6 +181,21 @@ impl BotHandler {
 
 🗑 `/clearexcludedtags`
    清除所有排除的标签
+
+🔁 `/resend <id>`
+   重新发送已推送到此聊天的作品
+   \- 同一作品不会被不同订阅重复推送，可用此命令找回
+   \- 示例: `/resend 12345678`
+
+🔍 `/sauce`
+   回复一张图片，通过 SauceNAO 查找 Pixiv 出处
+   \- 找到后可直接订阅画师或下载原图
+
+📈 `/stats`
+   查看本聊天的订阅数、近 7/30 天推送作品数和失败次数
+
+🤖 `/version`
+   查看版本和构建信息
 "#;
 
         bot.send_message(chat_id, help_text)
@@ -106,6 +204,35 @@ impl BotHandler {
         Ok(())
     }
 
+    // ------------------------------------------------------------------------
+    // Version Command
+    // ------------------------------------------------------------------------
+
+    /// 显示版本、提交和构建时间，以及更新检查发现的新版本
+    pub async fn handle_version(&self, bot: ThrottledBot, chat_id: ChatId) -> ResponseResult<()> {
+        let mut message = format!(
+            "🤖 *PixivBot*\n\n\
+            🏷 版本: `{}`\n\
+            🔖 提交: `{}`\n\
+            🕐 构建时间: `{}`",
+            markdown::escape_code(version::VERSION),
+            markdown::escape_code(version::GIT_COMMIT),
+            markdown::escape_code(&version::build_date())
+        );
+        if let Some(tag) = self.latest_release.get() {
+            message.push_str(&format!(
+                "\n\n🆕 有新版本可用: `{}`",
+                markdown::escape_code(&tag)
+            ));
+        }
+
+        bot.send_message(chat_id, message)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+
     // ------------------------------------------------------------------------
     // Info Command
     // ------------------------------------------------------------------------
@@ -124,6 +251,7 @@ impl BotHandler {
 
         let cache_size = calculate_dir_size(cache_path);
         let log_size = calculate_dir_size(log_path);
+        let dedupe = self.notifier.get_downloader().dedupe_stats();
 
         let message = format!(
             "📊 *PixivBot 状态信息*\n\n\
@@ -133,12 +261,15 @@ impl BotHandler {
             📝 任务数: `{}`\n\n\
             💾 *磁盘占用*\n\
             📁 缓存目录: `{}`\n\
+            ♻️ 缓存去重: `{}` 个文件，节省 `{}`\n\
             📄 日志目录: `{}`",
             admin_count,
             enabled_chat_count,
             subscription_count,
             task_count,
             format_size(cache_size),
+            dedupe.linked_files,
+            format_size(dedupe.bytes_saved),
             format_size(log_size)
         );
 
@@ -148,4 +279,76 @@ impl BotHandler {
 
         Ok(())
     }
+
+    // ------------------------------------------------------------------------
+    // Stats Command
+    // ------------------------------------------------------------------------
+
+    /// 显示当前聊天的推送统计；Owner 额外显示全局统计
+    pub async fn handle_stats(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        is_owner: bool,
+    ) -> ResponseResult<()> {
+        let now = Utc::now().naive_utc();
+        let week_ago = now - chrono::Duration::days(7);
+        let month_ago = now - chrono::Duration::days(30);
+
+        let subscription_count = self
+            .repo
+            .count_subscriptions_by_chat(chat_id.0)
+            .await
+            .unwrap_or(0);
+        let week = self.push_stats_or_default(Some(chat_id.0), week_ago).await;
+        let month = self.push_stats_or_default(Some(chat_id.0), month_ago).await;
+
+        let mut message = format!(
+            "📈 *推送统计*\n\n\
+            📋 本聊天订阅数: `{}`\n\
+            🖼 近 7 天推送作品: `{}` \\(失败 `{}` 次\\)\n\
+            🗓 近 30 天推送作品: `{}` \\(失败 `{}` 次\\)",
+            subscription_count, week.pushed, week.failures, month.pushed, month.failures
+        );
+
+        if is_owner {
+            let enabled_chat_count = self.repo.count_enabled_chats().await.unwrap_or(0);
+            let all_subscription_count = self.repo.count_all_subscriptions().await.unwrap_or(0);
+            let task_count = self.repo.count_all_tasks().await.unwrap_or(0);
+            let global_week = self.push_stats_or_default(None, week_ago).await;
+            let global_month = self.push_stats_or_default(None, month_ago).await;
+
+            message.push_str(&format!(
+                "\n\n🌐 *全局统计*\n\
+                💬 启用的聊天数: `{}`\n\
+                📋 订阅数: `{}`\n\
+                📝 任务数: `{}`\n\
+                🖼 近 7 天推送作品: `{}` \\(失败 `{}` 次\\)\n\
+                🗓 近 30 天推送作品: `{}` \\(失败 `{}` 次\\)",
+                enabled_chat_count,
+                all_subscription_count,
+                task_count,
+                global_week.pushed,
+                global_week.failures,
+                global_month.pushed,
+                global_month.failures
+            ));
+        }
+
+        bot.send_message(chat_id, message)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn push_stats_or_default(&self, chat_id: Option<i64>, since: NaiveDateTime) -> PushStats {
+        self.repo
+            .get_push_stats(chat_id, since)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to load push stats: {:#}", e);
+                PushStats::default()
+            })
+    }
 }
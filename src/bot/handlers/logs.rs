@@ -0,0 +1,181 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn, Level};
+
+/// Lines sent by `/logs tail`
+const TAIL_LINES: usize = 50;
+
+/// How long a live tail keeps forwarding new lines
+const LIVE_TAIL_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// Live lines are collected and sent together at this interval
+const LIVE_TAIL_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Budget per message in UTF-16 units, leaving room for the code block and escapes
+const LOG_MESSAGE_BUDGET: usize = 3500;
+
+const LOGS_USAGE: &str = "❌ 用法: `/logs tail [warn|error]`";
+
+/// Parse `/logs` arguments: `[tail] [warn|error]`, None when invalid.
+///
+/// Returns the minimum level to show and whether to keep tailing new lines.
+fn parse_logs_args(args: &str) -> Option<(Level, bool)> {
+    let mut words = args.split_whitespace().peekable();
+    words.next_if(|word| word.eq_ignore_ascii_case("tail"));
+    let request = match words.next().map(str::to_ascii_lowercase).as_deref() {
+        None => (Level::TRACE, false),
+        Some("warn" | "warning") => (Level::WARN, true),
+        Some("error") => (Level::ERROR, true),
+        Some(_) => return None,
+    };
+    words.next().is_none().then_some(request)
+}
+
+/// Group lines into messages within `budget` UTF-16 units, cutting lines that don't fit alone
+pub(super) fn chunk_lines(lines: &[String], budget: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    let mut chunk_units = 0;
+
+    for line in lines {
+        let line: String = line
+            .chars()
+            .scan(0, |units, c| {
+                *units += c.len_utf16();
+                (*units <= budget).then_some(c)
+            })
+            .collect();
+        let units = line.encode_utf16().count() + 1;
+        if chunk_units + units > budget && !chunk.is_empty() {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_units = 0;
+        }
+        chunk.push_str(&line);
+        chunk.push('\n');
+        chunk_units += units;
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+async fn send_log_lines(bot: &ThrottledBot, chat_id: ChatId, lines: &[String]) {
+    for chunk in chunk_lines(lines, LOG_MESSAGE_BUDGET) {
+        if let Err(e) = bot
+            .send_message(chat_id, markdown::code_block(&chunk))
+            .parse_mode(ParseMode::MarkdownV2)
+            .await
+        {
+            warn!("Failed to send log lines to chat {}: {:#}", chat_id, e);
+            return;
+        }
+    }
+}
+
+impl BotHandler {
+    /// 查看最近的日志，指定 warn/error 时继续实时推送该级别以上的新日志 (仅 Owner 私聊)
+    pub async fn handle_logs(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some((min_level, live)) = parse_logs_args(&args_str) else {
+            bot.send_message(chat_id, LOGS_USAGE)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        // Subscribe first so nothing logged while the history is sent gets lost
+        let receiver = live.then(|| self.log_buffer.subscribe());
+
+        let lines = self.log_buffer.tail(TAIL_LINES, min_level);
+        if lines.is_empty() {
+            bot.send_message(chat_id, "📭 暂无日志").await?;
+        } else {
+            send_log_lines(&bot, chat_id, &lines).await;
+        }
+
+        let Some(mut receiver) = receiver else {
+            return Ok(());
+        };
+
+        bot.send_message(
+            chat_id,
+            format!(
+                "📡 将在 {} 分钟内实时推送 {} 及以上级别的日志",
+                LIVE_TAIL_DURATION.as_secs() / 60,
+                min_level
+            ),
+        )
+        .await?;
+        info!(
+            "Live tailing {} logs to chat {} for {:?}",
+            min_level, chat_id, LIVE_TAIL_DURATION
+        );
+
+        tokio::spawn(async move {
+            let deadline = tokio::time::sleep(LIVE_TAIL_DURATION);
+            tokio::pin!(deadline);
+            let mut flush = tokio::time::interval(LIVE_TAIL_FLUSH_INTERVAL);
+            let mut pending = Vec::new();
+
+            loop {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    _ = flush.tick() => {
+                        if !pending.is_empty() {
+                            send_log_lines(&bot, chat_id, &std::mem::take(&mut pending)).await;
+                        }
+                    }
+                    line = receiver.recv() => match line {
+                        Ok(line) if line.level <= min_level => pending.push(line.text),
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            pending.push(format!("... 跳过了 {} 行日志", skipped));
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+
+            send_log_lines(&bot, chat_id, &pending).await;
+            if let Err(e) = bot.send_message(chat_id, "⏹ 实时日志已结束").await {
+                warn!("Failed to end live log tail in chat {}: {:#}", chat_id, e);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_logs_args_accepts_optional_tail_and_level() {
+        assert_eq!(parse_logs_args(""), Some((Level::TRACE, false)));
+        assert_eq!(parse_logs_args("tail"), Some((Level::TRACE, false)));
+        assert_eq!(parse_logs_args("tail WARN"), Some((Level::WARN, true)));
+        assert_eq!(parse_logs_args("error"), Some((Level::ERROR, true)));
+        assert_eq!(parse_logs_args("tail info"), None);
+        assert_eq!(parse_logs_args("tail warn now"), None);
+    }
+
+    #[test]
+    fn chunk_lines_respects_budget() {
+        let lines = vec!["aaaa".to_string(), "bb".to_string(), "c".repeat(20)];
+        assert_eq!(
+            chunk_lines(&lines, 8),
+            vec!["aaaa\nbb\n".to_string(), "cccccccc\n".to_string()]
+        );
+    }
+}
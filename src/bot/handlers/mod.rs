@@ -13,16 +13,50 @@ pub use settings::{
 
 // Subscription related handlers
 mod subscription;
-pub use subscription::{parse_list_callback_data, ListPaginationAction, LIST_CALLBACK_PREFIX};
+pub use subscription::{
+    parse_eh_info_callback_data, parse_forward_sub_callback_data, parse_list_callback_data,
+    parse_unsub_all_callback_data, AuthorStatusCache, ListPaginationAction,
+    EH_INFO_CALLBACK_PREFIX, FORWARD_SUB_CALLBACK_PREFIX, LIST_CALLBACK_PREFIX,
+    RANKING_PUSH_CALLBACK_PREFIX, UNSUB_ALL_CALLBACK_PREFIX,
+};
 
 // Download handler
 mod download;
 
 mod booru_download;
 
+// Resend handler
+mod resend;
+
+// Owner log viewer
+mod logs;
+
+// Owner view of the effective configuration
+mod config;
+
+// Owner management of tag aliases
+mod tag_alias;
+
+// Pixiv keyword search and related works
+mod search;
+pub use search::{SearchSessions, SEARCH_CALLBACK_PREFIX};
+
+// Spoiler removal for pushed items
+mod unblur;
+
+// SauceNAO image source lookup
+mod sauce;
+pub use sauce::{parse_sauce_sub_callback_data, SAUCE_SUB_CALLBACK_PREFIX};
+
 /// Callback data prefix for download button (Pixiv illust).
 pub const DOWNLOAD_CALLBACK_PREFIX: &str = "dl:";
 
 /// Callback data prefix for download button (Booru post).
 /// Format: `dlb:<site_name>:<post_id>`.
 pub const BOORU_DOWNLOAD_CALLBACK_PREFIX: &str = "dlb:";
+
+/// Reply sent when link previews have used up their share of the Pixiv API budget.
+pub(crate) const PREVIEW_BUDGET_EXHAUSTED: &str = "⏳ 预览请求过多，请稍后再试";
+
+/// Callback data for the unblur button; the target is the message carrying it.
+pub const UNBLUR_CALLBACK_DATA: &str = "unblur";
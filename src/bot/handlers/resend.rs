@@ -0,0 +1,109 @@
+//! Resend handler - re-posts an artwork that was already pushed to this chat
+//!
+//! Supports:
+//! - /resend <url|id>
+
+use crate::bot::link_handler::{parse_pixiv_links, PixivLink};
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::utils::time::to_local;
+use teloxide::prelude::*;
+use teloxide::types::MessageId;
+use tracing::{error, warn};
+
+/// Parse a bare illust id or the first Pixiv artwork link from the arguments
+fn parse_resend_target(args: &str) -> Option<u64> {
+    let args = args.trim();
+    if let Ok(id) = args.parse::<u64>() {
+        return Some(id);
+    }
+
+    parse_pixiv_links(args)
+        .into_iter()
+        .find_map(|link| match link {
+            PixivLink::Illust(id) => Some(id),
+            PixivLink::User(_) | PixivLink::Username(_) => None,
+        })
+}
+
+impl BotHandler {
+    /// Handle /resend command
+    ///
+    /// Looks up the earlier push in `sent_artworks` and copies that message
+    /// back to the bottom of the chat.
+    pub async fn handle_resend(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let Some(illust_id) = parse_resend_target(&args) else {
+            bot.send_message(chat_id, "❌ 用法: /resend <作品ID或链接>")
+                .await?;
+            return Ok(());
+        };
+
+        let record = match self
+            .repo
+            .get_sent_artwork(chat_id.0, illust_id as i64)
+            .await
+        {
+            Ok(record) => record,
+            Err(e) => {
+                error!("Failed to look up sent artwork {}: {:#}", illust_id, e);
+                bot.send_message(chat_id, "❌ 查询推送记录失败").await?;
+                return Ok(());
+            }
+        };
+
+        let Some(record) = record else {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "❌ 作品 {} 尚未推送到此聊天，可使用 /download {} 获取原图",
+                    illust_id, illust_id
+                ),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = bot
+            .copy_message(chat_id, chat_id, MessageId(record.message_id))
+            .await
+        {
+            warn!(
+                "Failed to copy message {} for illust {} in chat {}: {:#}",
+                record.message_id, illust_id, chat_id, e
+            );
+            bot.send_message(
+                chat_id,
+                format!(
+                    "⚠️ 作品 {} 已于 {} 推送，但原消息已不可用，可使用 /download {} 获取原图",
+                    illust_id,
+                    to_local(record.created_at).format("%Y-%m-%d %H:%M"),
+                    illust_id
+                ),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_resend_target;
+
+    #[test]
+    fn parse_resend_target_accepts_id_or_artwork_link() {
+        assert_eq!(parse_resend_target(" 12345 "), Some(12345));
+        assert_eq!(
+            parse_resend_target("https://www.pixiv.net/artworks/67890"),
+            Some(67890)
+        );
+        assert_eq!(parse_resend_target("https://www.pixiv.net/users/123"), None);
+        assert_eq!(parse_resend_target(""), None);
+    }
+}
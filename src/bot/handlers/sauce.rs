@@ -0,0 +1,186 @@
+//! SauceNAO handler - finds the Pixiv source of an image
+//!
+//! Supports:
+//! - /sauce (as reply to a photo or image file)
+
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::saucenao::SauceMatch;
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::{
+    ChatAction, FileId, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode, ReplyParameters,
+    UserId,
+};
+use teloxide::utils::markdown;
+use tracing::{error, info, warn};
+
+use super::DOWNLOAD_CALLBACK_PREFIX;
+
+/// Callback data prefix for subscribing the author of a SauceNAO match.
+/// Format: `saucesub:<author_id>`.
+pub const SAUCE_SUB_CALLBACK_PREFIX: &str = "saucesub:";
+
+fn sauce_sub_callback_data(author_id: u64) -> String {
+    format!("{}{}", SAUCE_SUB_CALLBACK_PREFIX, author_id)
+}
+
+/// Parse callback data produced for a SauceNAO match
+pub fn parse_sauce_sub_callback_data(data: &str) -> Option<u64> {
+    data.strip_prefix(SAUCE_SUB_CALLBACK_PREFIX)?.parse().ok()
+}
+
+/// 消息中的图片：照片取最大尺寸，文件只接受图片类型
+fn image_file_id(msg: &Message) -> Option<FileId> {
+    if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+        return Some(photo.file.id.clone());
+    }
+    msg.document()
+        .filter(|document| {
+            document
+                .mime_type
+                .as_ref()
+                .is_some_and(|mime| mime.type_() == "image")
+        })
+        .map(|document| document.file.id.clone())
+}
+
+/// 识别结果 (MarkdownV2)
+fn format_sauce_match(sauce: &SauceMatch) -> String {
+    let title = if sauce.title.is_empty() {
+        sauce.illust_id.to_string()
+    } else {
+        sauce.title.clone()
+    };
+    let author = if sauce.author_name.is_empty() {
+        sauce.author_id.to_string()
+    } else {
+        sauce.author_name.clone()
+    };
+    format!(
+        "🔍 找到出处 \\(相似度 {}%\\)\n\n🎨 [{}](https://www.pixiv.net/artworks/{})\n👤 [{}](https://www.pixiv.net/users/{}) \\(ID: `{}`\\)",
+        markdown::escape(&format!("{:.1}", sauce.similarity)),
+        markdown::escape(&title),
+        sauce.illust_id,
+        markdown::escape(&author),
+        sauce.author_id,
+        sauce.author_id
+    )
+}
+
+impl BotHandler {
+    /// 通过 SauceNAO 识别回复的图片的 Pixiv 出处
+    ///
+    /// 找到作品时附带订阅作者和下载按钮。
+    pub async fn handle_sauce(
+        &self,
+        bot: ThrottledBot,
+        msg: Message,
+        chat_id: ChatId,
+    ) -> ResponseResult<()> {
+        let Some(saucenao) = self.saucenao.clone() else {
+            bot.send_message(chat_id, "SauceNAO 功能未启用").await?;
+            return Ok(());
+        };
+
+        let Some(file_id) = msg
+            .reply_to_message()
+            .and_then(image_file_id)
+            .or_else(|| image_file_id(&msg))
+        else {
+            bot.send_message(chat_id, "❌ 请回复一张图片使用 /sauce")
+                .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        info!("Looking up image source for chat {}", chat_id);
+
+        let file = bot.get_file(file_id).await?;
+        let mut image = Vec::with_capacity(file.meta.size as usize);
+        if let Err(e) = bot.download_file(&file.path, &mut image).await {
+            error!("Failed to download image for SauceNAO: {:#}", e);
+            bot.send_message(chat_id, "❌ 下载图片失败").await?;
+            return Ok(());
+        }
+
+        let sauce = match saucenao.search(image).await {
+            Ok(Some(sauce)) => sauce,
+            Ok(None) => {
+                bot.send_message(chat_id, "🔍 没有找到相似的 Pixiv 作品")
+                    .reply_parameters(ReplyParameters::new(msg.id))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("SauceNAO search failed: {:#}", e);
+                bot.send_message(chat_id, "❌ 识别失败，请稍后重试").await?;
+                return Ok(());
+            }
+        };
+
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("🎨 订阅作者", sauce_sub_callback_data(sauce.author_id)),
+            InlineKeyboardButton::callback(
+                "📥 下载",
+                format!("{}{}", DOWNLOAD_CALLBACK_PREFIX, sauce.illust_id),
+            ),
+        ]]);
+        bot.send_message(chat_id, format_sauce_match(&sauce))
+            .parse_mode(ParseMode::MarkdownV2)
+            .reply_parameters(ReplyParameters::new(msg.id))
+            .reply_markup(keyboard)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 处理识别结果的订阅按钮，等同于执行 `/sub <author_id>`
+    pub async fn handle_sauce_sub_callback(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: UserId,
+        author_id: u64,
+    ) -> ResponseResult<()> {
+        info!(
+            "User {} subscribing chat {} to author {} from SauceNAO match",
+            user_id, chat_id, author_id
+        );
+        self.handle_sub_author(bot, chat_id, Some(user_id), author_id.to_string(), false)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sauce_sub_callback_data_round_trips() {
+        let data = sauce_sub_callback_data(123456789);
+        assert!(data.len() <= 64, "callback data too long: {}", data);
+        assert_eq!(parse_sauce_sub_callback_data(&data), Some(123456789));
+
+        assert_eq!(parse_sauce_sub_callback_data("saucesub:abc"), None);
+        assert_eq!(parse_sauce_sub_callback_data("dl:1"), None);
+    }
+
+    #[test]
+    fn format_sauce_match_escapes_title_and_falls_back_to_ids() {
+        let sauce = SauceMatch {
+            similarity: 93.456,
+            illust_id: 1,
+            title: "a.b".to_string(),
+            author_id: 2,
+            author_name: String::new(),
+        };
+        let text = format_sauce_match(&sauce);
+        assert!(text.contains("相似度 93\\.5%"));
+        assert!(text.contains("[a\\.b](https://www.pixiv.net/artworks/1)"));
+        assert!(text.contains("[2](https://www.pixiv.net/users/2)"));
+    }
+}
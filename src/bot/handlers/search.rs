@@ -0,0 +1,409 @@
+use crate::bot::link_handler::{parse_pixiv_links, PixivLink};
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::entities::chats;
+use crate::db::types::TagFilter;
+use crate::scheduler::filter_illusts_for_chat;
+use crate::utils::{caption, sensitive};
+use pixiv_client::Illust;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use teloxide::types::{
+    CallbackQuery, ChatAction, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode,
+};
+use teloxide::utils::markdown;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// Callback data prefix for the next page button.
+/// Format: `search:<session_id>`.
+pub const SEARCH_CALLBACK_PREFIX: &str = "search:";
+
+/// Results per page, the most a media group can hold
+const SEARCH_PAGE_SIZE: usize = 10;
+
+/// Search pages fetched at most to fill one page after the chat's filters
+const SEARCH_MAX_FETCHES_PER_PAGE: usize = 3;
+
+/// How long the next page button of a search keeps working
+const SEARCH_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// What a session pages through
+#[derive(Debug)]
+enum SearchQuery {
+    /// `/search <keyword>`
+    Keyword(String),
+    /// `/related <illust_id>`
+    Related(u64),
+}
+
+impl SearchQuery {
+    /// Page heading (MarkdownV2)
+    fn title(&self, page: usize) -> String {
+        match self {
+            SearchQuery::Keyword(keyword) => {
+                format!("🔍 *{}* \\- 第 {} 页\n\n", markdown::escape(keyword), page)
+            }
+            SearchQuery::Related(illust_id) => {
+                format!("🔗 *相关作品* `{}` \\- 第 {} 页\n\n", illust_id, page)
+            }
+        }
+    }
+
+    fn more_button_text(&self) -> &'static str {
+        match self {
+            SearchQuery::Keyword(_) => "下一页 ▶️",
+            SearchQuery::Related(_) => "更多 ▶️",
+        }
+    }
+
+    fn failure_text(&self) -> &'static str {
+        match self {
+            SearchQuery::Keyword(_) => "❌ 搜索失败，请稍后再试",
+            SearchQuery::Related(_) => "❌ 获取相关作品失败，请稍后再试",
+        }
+    }
+}
+
+/// A search waiting for its next page
+struct SearchSession {
+    chat_id: ChatId,
+    query: SearchQuery,
+    /// Results that passed the chat's filters but were not shown yet
+    pending: Vec<Illust>,
+    /// Offset of the next page of API results, None once all were fetched
+    next_offset: Option<u32>,
+    /// Pages sent so far
+    page: usize,
+    created_at: Instant,
+}
+
+impl SearchSession {
+    fn has_more(&self) -> bool {
+        !self.pending.is_empty() || self.next_offset.is_some()
+    }
+}
+
+/// In-memory store of `/search` and `/related` sessions shared by all chats
+#[derive(Clone, Default)]
+pub struct SearchSessions {
+    next_id: Arc<AtomicU64>,
+    sessions: Arc<Mutex<HashMap<u64, SearchSession>>>,
+}
+
+impl SearchSessions {
+    async fn insert(&self, session: SearchSession) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut sessions = self.sessions.lock().await;
+        sessions.retain(|_, session| session.created_at.elapsed() < SEARCH_SESSION_TTL);
+        sessions.insert(id, session);
+        id
+    }
+
+    /// Remove a live session of `chat_id` so its next page is only sent once
+    async fn take(&self, id: u64, chat_id: ChatId) -> Option<SearchSession> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions.get(&id)?;
+        if session.chat_id != chat_id {
+            return None;
+        }
+        sessions
+            .remove(&id)
+            .filter(|session| session.created_at.elapsed() < SEARCH_SESSION_TTL)
+    }
+}
+
+impl BotHandler {
+    /// 按关键词搜索 Pixiv 作品，每页以媒体组发送，并附带下一页按钮
+    ///
+    /// 结果遵循聊天的排除标签、R-18 与模糊设置。
+    pub async fn handle_search(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let keyword = args_str.trim();
+        if keyword.is_empty() {
+            bot.send_message(chat_id, "❌ 用法: `/search <关键词>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        }
+
+        let Some(chat) = self.get_search_chat(&bot, chat_id).await? else {
+            return Ok(());
+        };
+
+        info!("Searching {:?} for chat {}", keyword, chat_id);
+        let session = SearchSession {
+            chat_id,
+            query: SearchQuery::Keyword(keyword.to_string()),
+            pending: Vec::new(),
+            next_offset: Some(0),
+            page: 0,
+            created_at: Instant::now(),
+        };
+        self.send_search_page(&bot, &chat, session).await
+    }
+
+    /// 查看与作品相似的 Pixiv 作品，每批以媒体组发送，并附带查看更多按钮
+    ///
+    /// 结果遵循聊天的排除标签、R-18 与模糊设置。
+    pub async fn handle_related(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(illust_id) = parse_related_target(&args_str) else {
+            bot.send_message(chat_id, "❌ 用法: `/related <作品ID|链接>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        let Some(chat) = self.get_search_chat(&bot, chat_id).await? else {
+            return Ok(());
+        };
+
+        info!(
+            "Fetching illusts related to {} for chat {}",
+            illust_id, chat_id
+        );
+        let session = SearchSession {
+            chat_id,
+            query: SearchQuery::Related(illust_id),
+            pending: Vec::new(),
+            next_offset: Some(0),
+            page: 0,
+            created_at: Instant::now(),
+        };
+        self.send_search_page(&bot, &chat, session).await
+    }
+
+    /// 处理搜索结果的下一页按钮
+    pub async fn handle_search_callback(
+        &self,
+        bot: ThrottledBot,
+        q: CallbackQuery,
+        session_id: u64,
+    ) -> ResponseResult<()> {
+        let Some(msg) = q.regular_message() else {
+            bot.answer_callback_query(q.id).await?;
+            return Ok(());
+        };
+        let chat_id = msg.chat.id;
+
+        let Some(session) = self.search_sessions.take(session_id, chat_id).await else {
+            bot.answer_callback_query(q.id.clone())
+                .text("⌛ 搜索已过期，请重新搜索")
+                .await?;
+            return Ok(());
+        };
+        bot.answer_callback_query(q.id.clone()).await?;
+
+        // The page is being sent, drop the button so it can't be requested twice
+        if let Err(e) = bot.edit_message_reply_markup(chat_id, msg.id).await {
+            warn!(
+                "Failed to remove search button in chat {}: {:#}",
+                chat_id, e
+            );
+        }
+
+        let Some(chat) = self.get_search_chat(&bot, chat_id).await? else {
+            return Ok(());
+        };
+        self.send_search_page(&bot, &chat, session).await
+    }
+
+    async fn get_search_chat(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+    ) -> ResponseResult<Option<chats::Model>> {
+        match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) => Ok(Some(chat)),
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 未找到聊天").await?;
+                Ok(None)
+            }
+            Err(e) => {
+                error!("Failed to get chat {} for search: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 获取聊天设置失败").await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Send the next page of a search and keep the session if more results remain
+    async fn send_search_page(
+        &self,
+        bot: &ThrottledBot,
+        chat: &chats::Model,
+        mut session: SearchSession,
+    ) -> ResponseResult<()> {
+        let chat_id = session.chat_id;
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::UploadPhoto).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        if let Err(e) = self.fill_search_page(chat, &mut session).await {
+            error!("Failed to search {:?}: {:#}", session.query, e);
+            bot.send_message(chat_id, session.query.failure_text())
+                .await?;
+            return Ok(());
+        }
+
+        if session.pending.is_empty() {
+            let message = if session.page == 0 {
+                "🔍 没有找到符合条件的作品"
+            } else {
+                "🔍 没有更多结果了"
+            };
+            bot.send_message(chat_id, message).await?;
+            return Ok(());
+        }
+
+        let count = session.pending.len().min(SEARCH_PAGE_SIZE);
+        let illusts: Vec<Illust> = session.pending.drain(..count).collect();
+        session.page += 1;
+
+        let title = session.query.title(session.page);
+        let mut image_urls = Vec::new();
+        let mut captions = Vec::new();
+        for (index, illust) in illusts.iter().enumerate() {
+            let image_url = illust
+                .get_all_image_urls_with_size(self.image_sizes.push)
+                .first()
+                .cloned()
+                .unwrap_or_else(|| illust.image_urls.large.clone());
+            image_urls.push(image_url);
+            captions.push(caption::build_ranking_caption(&title, index, None, illust));
+        }
+        let has_spoiler = illusts
+            .iter()
+            .any(|illust| sensitive::should_blur(chat, illust));
+
+        let result = self
+            .notifier
+            .notify_with_individual_captions(chat_id, &image_urls, &captions, has_spoiler)
+            .await;
+        if result.is_complete_failure() {
+            bot.send_message(chat_id, "❌ 发送搜索结果失败").await?;
+            return Ok(());
+        }
+
+        if !session.has_more() {
+            return Ok(());
+        }
+
+        let page = session.page;
+        let more_text = session.query.more_button_text();
+        session.created_at = Instant::now();
+        let session_id = self.search_sessions.insert(session).await;
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            more_text,
+            format!("{}{}", SEARCH_CALLBACK_PREFIX, session_id),
+        )]]);
+        bot.send_message(chat_id, format!("🔍 已显示第 {} 页", page))
+            .reply_markup(keyboard)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fetch results until a full page passed the chat's filters or the search is exhausted
+    async fn fill_search_page(
+        &self,
+        chat: &chats::Model,
+        session: &mut SearchSession,
+    ) -> anyhow::Result<()> {
+        for _ in 0..SEARCH_MAX_FETCHES_PER_PAGE {
+            if session.pending.len() >= SEARCH_PAGE_SIZE {
+                break;
+            }
+            let Some(offset) = session.next_offset else {
+                break;
+            };
+
+            let pixiv = self.pixiv_client.read().await;
+            let (illusts, next_offset) = match &session.query {
+                SearchQuery::Keyword(keyword) => pixiv.search_illusts(keyword, offset).await?,
+                SearchQuery::Related(illust_id) => {
+                    pixiv.get_related_illusts(*illust_id, offset).await?
+                }
+            };
+            drop(pixiv);
+            session.next_offset = next_offset;
+            session.pending.extend(
+                filter_illusts_for_chat(&TagFilter::default(), chat, illusts.iter())
+                    .into_iter()
+                    .cloned(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `/related` 的参数：作品 ID 或作品链接
+fn parse_related_target(args: &str) -> Option<u64> {
+    let args = args.trim();
+    if let Ok(illust_id) = args.parse() {
+        return Some(illust_id);
+    }
+    parse_pixiv_links(args)
+        .into_iter()
+        .find_map(|link| match link {
+            PixivLink::Illust(illust_id) => Some(illust_id),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(chat_id: i64, created_at: Instant) -> SearchSession {
+        SearchSession {
+            chat_id: ChatId(chat_id),
+            query: SearchQuery::Keyword("原神".to_string()),
+            pending: Vec::new(),
+            next_offset: Some(30),
+            page: 1,
+            created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn search_sessions_are_taken_once_by_their_chat() {
+        let sessions = SearchSessions::default();
+        let id = sessions.insert(session(1, Instant::now())).await;
+
+        assert!(sessions.take(id, ChatId(2)).await.is_none());
+        let taken = sessions.take(id, ChatId(1)).await.unwrap();
+        assert_eq!(taken.next_offset, Some(30));
+        assert!(sessions.take(id, ChatId(1)).await.is_none());
+
+        let Some(expired_at) = Instant::now().checked_sub(SEARCH_SESSION_TTL) else {
+            return;
+        };
+        let id = sessions.insert(session(1, expired_at)).await;
+        assert!(sessions.take(id, ChatId(1)).await.is_none());
+    }
+
+    #[test]
+    fn parse_related_target_accepts_ids_and_links() {
+        assert_eq!(parse_related_target(" 12345 "), Some(12345));
+        assert_eq!(
+            parse_related_target("https://www.pixiv.net/artworks/67890"),
+            Some(67890)
+        );
+        assert_eq!(parse_related_target("https://www.pixiv.net/users/1"), None);
+        assert_eq!(parse_related_target(""), None);
+    }
+}
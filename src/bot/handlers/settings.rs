@@ -2,8 +2,7 @@ use crate::bot::notifier::ThrottledBot;
 use crate::bot::state::{SettingsState, SettingsStorage};
 use crate::bot::BotHandler;
 use crate::db::entities::chats;
-use crate::db::types::Tags;
-use std::time::Instant;
+use crate::db::types::{AiFilter, TagFilter, Tags};
 use teloxide::prelude::*;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
 use teloxide::utils::markdown;
@@ -49,6 +48,330 @@ impl BotHandler {
         Ok(())
     }
 
+    /// 设置是否允许推送 R-18/R-18G 作品 (`/r18 on|off`，无参数时显示当前状态)
+    pub async fn handle_r18(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let allow = match args.trim().to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            "" => {
+                let message = match self.repo.get_chat(chat_id.0).await {
+                    Ok(Some(chat)) if chat.allow_r18 => "🔞 当前聊天已允许 R-18 作品",
+                    Ok(Some(_)) => "🔞 当前聊天已屏蔽 R-18 作品",
+                    Ok(None) => "❌ 未找到聊天",
+                    Err(e) => {
+                        error!("Failed to get chat {} for /r18: {:#}", chat_id, e);
+                        "❌ 获取设置失败"
+                    }
+                };
+                bot.send_message(chat_id, message).await?;
+                return Ok(());
+            }
+            _ => {
+                bot.send_message(chat_id, "❌ 用法: `/r18 <on|off>`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match self.repo.set_allow_r18(chat_id.0, allow).await {
+            Ok(_) => {
+                info!("Chat {} allow_r18 set to {}", chat_id, allow);
+                let message = if allow {
+                    "✅ 已允许 R-18 作品"
+                } else {
+                    "✅ 已屏蔽 R-18 作品"
+                };
+                bot.send_message(chat_id, message).await?;
+            }
+            Err(e) => {
+                error!("Failed to set allow_r18 for chat {}: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 更新设置失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 设置聊天默认的 AI 作品过滤方式 (`/ai all|exclude|only`，无参数时显示当前设置)
+    ///
+    /// 订阅未通过 `ai=` 单独设置时使用，同时作用于链接预览、搜索和排行榜。
+    pub async fn handle_ai_filter(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let arg = args.trim();
+        if arg.is_empty() {
+            let message = match self.repo.get_chat(chat_id.0).await {
+                Ok(Some(chat)) => {
+                    format!(
+                        "🤖 当前聊天的 AI 作品过滤: {}",
+                        chat.ai_filter.display_name()
+                    )
+                }
+                Ok(None) => "❌ 未找到聊天".to_string(),
+                Err(e) => {
+                    error!("Failed to get chat {} for /ai: {:#}", chat_id, e);
+                    "❌ 获取设置失败".to_string()
+                }
+            };
+            bot.send_message(chat_id, message).await?;
+            return Ok(());
+        }
+
+        // `auto` only makes sense for subscriptions, which follow the chat
+        let filter = match AiFilter::parse(arg) {
+            Some(filter) if filter != AiFilter::Auto => filter,
+            _ => {
+                bot.send_message(chat_id, "❌ 用法: `/ai <all|exclude|only>`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match self.repo.set_chat_ai_filter(chat_id.0, filter).await {
+            Ok(_) => {
+                info!("Chat {} ai_filter set to {}", chat_id, filter);
+                bot.send_message(
+                    chat_id,
+                    format!("✅ AI 作品过滤已设为: {}", filter.display_name()),
+                )
+                .await?;
+            }
+            Err(e) => {
+                error!("Failed to set ai_filter for chat {}: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 更新设置失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 设置聊天的默认标签过滤 (`/defaultfilter +tag1 -tag2`，`clear` 清除，无参数时显示当前设置)
+    ///
+    /// 之后新建的 Pixiv 作者/排行榜订阅会合并这些标签，已有订阅不受影响。
+    pub async fn handle_default_filter(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        if parts.is_empty() {
+            let message = match self.repo.get_chat(chat_id.0).await {
+                Ok(Some(chat)) if chat.default_filter.is_empty() => {
+                    "🧩 当前聊天没有默认过滤".to_string()
+                }
+                Ok(Some(chat)) => format!(
+                    "🧩 当前聊天的默认过滤: {}",
+                    chat.default_filter.format_plain()
+                ),
+                Ok(None) => "❌ 未找到聊天".to_string(),
+                Err(e) => {
+                    error!("Failed to get chat {} for /defaultfilter: {:#}", chat_id, e);
+                    "❌ 获取设置失败".to_string()
+                }
+            };
+            bot.send_message(chat_id, message).await?;
+            return Ok(());
+        }
+
+        let filter = if parts == ["clear"] {
+            TagFilter::default()
+        } else {
+            TagFilter::parse_from_args(&parts)
+        };
+
+        match self
+            .repo
+            .set_chat_default_filter(chat_id.0, filter.clone())
+            .await
+        {
+            Ok(_) => {
+                info!(
+                    "Chat {} default filter set to '{}'",
+                    chat_id,
+                    filter.format_plain()
+                );
+                let message = if filter.is_empty() {
+                    "✅ 已清除默认过滤".to_string()
+                } else {
+                    format!(
+                        "✅ 默认过滤已设为: {}\n之后新建的订阅会自动加上这些标签",
+                        filter.format_plain()
+                    )
+                };
+                bot.send_message(chat_id, message).await?;
+            }
+            Err(e) => {
+                error!("Failed to set default filter for chat {}: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 更新设置失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 设置是否在推送单图作品后额外发送原图文件 (`/original on|off`，无参数时显示当前状态)
+    pub async fn handle_original(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+    ) -> ResponseResult<()> {
+        let enabled = match args.trim().to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            "" => {
+                let message = match self.repo.get_chat(chat_id.0).await {
+                    Ok(Some(chat)) if chat.send_original => {
+                        "📎 当前聊天推送单图作品时会附带原图文件"
+                    }
+                    Ok(Some(_)) => "📎 当前聊天推送时不附带原图文件",
+                    Ok(None) => "❌ 未找到聊天",
+                    Err(e) => {
+                        error!("Failed to get chat {} for /original: {:#}", chat_id, e);
+                        "❌ 获取设置失败"
+                    }
+                };
+                bot.send_message(chat_id, message).await?;
+                return Ok(());
+            }
+            _ => {
+                bot.send_message(chat_id, "❌ 用法: `/original <on|off>`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match self.repo.set_send_original(chat_id.0, enabled).await {
+            Ok(_) => {
+                info!("Chat {} send_original set to {}", chat_id, enabled);
+                let message = if enabled {
+                    "✅ 已开启原图文件：推送单图作品后将额外发送原图（20MB 以内）"
+                } else {
+                    "✅ 已关闭原图文件"
+                };
+                bot.send_message(chat_id, message).await?;
+            }
+            Err(e) => {
+                error!("Failed to set send_original for chat {}: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 更新设置失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 屏蔽或解除屏蔽作者 (`/blockauthor <id,...>`、`/unblockauthor <id,...>`，无参数时列出已屏蔽的作者)
+    ///
+    /// 被屏蔽作者的作品不会出现在排行榜推送、作者推送、搜索和链接预览中。
+    pub async fn handle_block_author(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args: String,
+        block: bool,
+    ) -> ResponseResult<()> {
+        let chat = match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) => chat,
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 未找到聊天").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get chat {} for blocked authors: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 获取设置失败").await?;
+                return Ok(());
+            }
+        };
+
+        if args.trim().is_empty() {
+            let message = if chat.blocked_authors.is_empty() {
+                "🚫 当前聊天没有屏蔽作者".to_string()
+            } else {
+                format!(
+                    "🚫 已屏蔽的作者: {}",
+                    format_author_ids(&chat.blocked_authors)
+                )
+            };
+            bot.send_message(chat_id, message)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        }
+
+        let Some(author_ids) = parse_author_ids_input(&args) else {
+            let usage = if block {
+                "❌ 用法: `/blockauthor <author_id,...>`"
+            } else {
+                "❌ 用法: `/unblockauthor <author_id,...>`"
+            };
+            bot.send_message(chat_id, usage)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        let mut blocked_authors = chat.blocked_authors;
+        if block {
+            for author_id in &author_ids {
+                if !blocked_authors.contains(author_id) {
+                    blocked_authors.push(*author_id);
+                }
+            }
+        } else {
+            blocked_authors.retain(|author_id| !author_ids.contains(author_id));
+        }
+
+        match self
+            .repo
+            .set_blocked_authors(chat_id.0, blocked_authors)
+            .await
+        {
+            Ok(_) => {
+                info!(
+                    "Chat {} {} authors {:?}",
+                    chat_id,
+                    if block { "blocked" } else { "unblocked" },
+                    author_ids
+                );
+                let action = if block {
+                    "已屏蔽作者"
+                } else {
+                    "已解除屏蔽作者"
+                };
+                bot.send_message(
+                    chat_id,
+                    format!("✅ {}: {}", action, format_author_ids(&author_ids)),
+                )
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to update blocked authors for chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 更新设置失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Update the settings panel message (edit existing message)
     pub async fn refresh_settings_panel(
         &self,
@@ -86,6 +409,32 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
         "*已禁用*"
     };
 
+    let r18_status = if chat.allow_r18 {
+        "*允许*"
+    } else {
+        "*屏蔽*"
+    };
+
+    let ai_status = format!("*{}*", chat.ai_filter.display_name());
+
+    let original_status = if chat.send_original {
+        "*附带*"
+    } else {
+        "*不附带*"
+    };
+
+    let link_preview_status = if chat.link_preview {
+        "*开启*"
+    } else {
+        "*关闭*"
+    };
+
+    let notify_failures_status = if chat.notify_failures {
+        "*开启*"
+    } else {
+        "*关闭*"
+    };
+
     let mention_status = if chat.allow_without_mention {
         "*无需@响应*"
     } else {
@@ -112,6 +461,15 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
             .join(", ")
     };
 
+    let default_filter = if chat.default_filter.is_empty() {
+        "无".to_string()
+    } else {
+        format!(
+            "`{}`",
+            markdown::escape_code(&chat.default_filter.format_plain())
+        )
+    };
+
     // 私聊时不显示群组命令响应设置（该设置只对群组有意义）
     let is_private = chat.r#type == "private";
 
@@ -119,18 +477,47 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
         format!(
             "⚙️ *聊天设置*\n\n\
              🔒 敏感内容模糊: {}\n\
+             🔞 R\\-18 作品: {}\n\
+             🤖 AI 作品: {}\n\
+             📎 原图文件: {}\n\
+             🔗 链接预览: {}\n\
+             ⚠️ 失败通知: {}\n\
              🏷 敏感标签: {}\n\
-             🚫 排除标签: {}",
-            blur_status, sensitive_tags, excluded_tags
+             🚫 排除标签: {}\n\
+             🧩 默认过滤: {}",
+            blur_status,
+            r18_status,
+            ai_status,
+            original_status,
+            link_preview_status,
+            notify_failures_status,
+            sensitive_tags,
+            excluded_tags,
+            default_filter
         )
     } else {
         format!(
             "⚙️ *聊天设置*\n\n\
              🔒 敏感内容模糊: {}\n\
+             🔞 R\\-18 作品: {}\n\
+             🤖 AI 作品: {}\n\
+             📎 原图文件: {}\n\
+             🔗 链接预览: {}\n\
+             ⚠️ 失败通知: {}\n\
              📢 群组命令响应: {}\n\
              🏷 敏感标签: {}\n\
-             🚫 排除标签: {}",
-            blur_status, mention_status, sensitive_tags, excluded_tags
+             🚫 排除标签: {}\n\
+             🧩 默认过滤: {}",
+            blur_status,
+            r18_status,
+            ai_status,
+            original_status,
+            link_preview_status,
+            notify_failures_status,
+            mention_status,
+            sensitive_tags,
+            excluded_tags,
+            default_filter
         )
     };
 
@@ -146,7 +533,29 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
         format!("{}blur:toggle", SETTINGS_CALLBACK_PREFIX),
     );
 
-    // Row 2: Toggle mention requirement button (only meaningful for groups)
+    // Row 2: Toggle automatic handling of pasted Pixiv links
+    let link_preview_button_text = if chat.link_preview {
+        "🔗关闭链接预览"
+    } else {
+        "🔗开启链接预览"
+    };
+    let link_preview_button = InlineKeyboardButton::callback(
+        link_preview_button_text,
+        format!("{}link:toggle", SETTINGS_CALLBACK_PREFIX),
+    );
+
+    // Row 3: Toggle notices about works skipped after the last retry
+    let notify_failures_button_text = if chat.notify_failures {
+        "⚠️关闭失败通知"
+    } else {
+        "⚠️开启失败通知"
+    };
+    let notify_failures_button = InlineKeyboardButton::callback(
+        notify_failures_button_text,
+        format!("{}failure:toggle", SETTINGS_CALLBACK_PREFIX),
+    );
+
+    // Row 4: Toggle mention requirement button (only meaningful for groups)
     let mention_button_text = if chat.allow_without_mention {
         // Currently allows commands without @; pressing will turn on @ requirement
         "📢开启@要求"
@@ -159,7 +568,7 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
         format!("{}mention:toggle", SETTINGS_CALLBACK_PREFIX),
     );
 
-    // Row 3: Edit tags buttons
+    // Row 5: Edit tags buttons
     let sensitive_tags_button = InlineKeyboardButton::callback(
         "✏️敏感标签",
         format!("{}edit:sensitive", SETTINGS_CALLBACK_PREFIX),
@@ -173,11 +582,15 @@ fn build_settings_panel(chat: &chats::Model) -> (String, InlineKeyboardMarkup) {
     let keyboard = if is_private {
         InlineKeyboardMarkup::new(vec![
             vec![blur_button],
+            vec![link_preview_button],
+            vec![notify_failures_button],
             vec![sensitive_tags_button, excluded_tags_button],
         ])
     } else {
         InlineKeyboardMarkup::new(vec![
             vec![blur_button],
+            vec![link_preview_button],
+            vec![notify_failures_button],
             vec![mention_button],
             vec![sensitive_tags_button, excluded_tags_button],
         ])
@@ -195,11 +608,30 @@ pub fn parse_tags_input(input: &str) -> Vec<String> {
         .collect()
 }
 
+/// Parse comma-separated author IDs, None when any of them is not a number
+fn parse_author_ids_input(input: &str) -> Option<Vec<u64>> {
+    let ids = parse_tags_input(input)
+        .iter()
+        .map(|id| id.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    (!ids.is_empty()).then_some(ids)
+}
+
+fn format_author_ids(author_ids: &[u64]) -> String {
+    author_ids
+        .iter()
+        .map(|id| format!("`{}`", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Process settings callback query
 ///
 /// This function handles callback queries from the settings panel buttons.
 /// It's called from the dispatcher and handles:
 /// - `settings:blur:toggle` - Toggle blur setting
+/// - `settings:link:toggle` - Toggle automatic handling of pasted links
+/// - `settings:mention:toggle` - Toggle the @ requirement in groups
 /// - `settings:edit:sensitive` - Prompt for sensitive tags input
 /// - `settings:edit:exclude` - Prompt for excluded tags input
 pub async fn handle_settings_callback(
@@ -316,6 +748,114 @@ pub async fn handle_settings_callback(
                 }
             }
         }
+        "link:toggle" => {
+            // Toggle link_preview setting
+            match handler.repo.get_chat(chat_id.0).await {
+                Ok(Some(chat)) => {
+                    let new_link_preview = !chat.link_preview;
+                    match handler
+                        .repo
+                        .set_link_preview(chat_id.0, new_link_preview)
+                        .await
+                    {
+                        Ok(_) => {
+                            info!(
+                                "Chat {} link_preview toggled to {} by user {}",
+                                chat_id, new_link_preview, user_id
+                            );
+
+                            // Refresh the settings panel
+                            handler
+                                .refresh_settings_panel(bot.clone(), chat_id, message_id)
+                                .await?;
+
+                            bot.answer_callback_query(q.id).await?;
+                        }
+                        Err(e) => {
+                            error!("Failed to toggle link preview setting: {:#}", e);
+                            bot.answer_callback_query(q.id)
+                                .text("更新设置失败")
+                                .show_alert(true)
+                                .await?;
+                        }
+                    }
+                }
+                Ok(None) => {
+                    warn!(
+                        "Chat {} not found when toggling link_preview by user {}",
+                        chat_id, user_id
+                    );
+                    bot.answer_callback_query(q.id)
+                        .text("获取聊天信息失败")
+                        .show_alert(true)
+                        .await?;
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to fetch chat {} for link preview toggle by user {}: {:#}",
+                        chat_id, user_id, e
+                    );
+                    bot.answer_callback_query(q.id)
+                        .text("获取聊天信息失败")
+                        .show_alert(true)
+                        .await?;
+                }
+            }
+        }
+        "failure:toggle" => {
+            // Toggle notify_failures setting
+            match handler.repo.get_chat(chat_id.0).await {
+                Ok(Some(chat)) => {
+                    let new_notify_failures = !chat.notify_failures;
+                    match handler
+                        .repo
+                        .set_notify_failures(chat_id.0, new_notify_failures)
+                        .await
+                    {
+                        Ok(_) => {
+                            info!(
+                                "Chat {} notify_failures toggled to {} by user {}",
+                                chat_id, new_notify_failures, user_id
+                            );
+
+                            // Refresh the settings panel
+                            handler
+                                .refresh_settings_panel(bot.clone(), chat_id, message_id)
+                                .await?;
+
+                            bot.answer_callback_query(q.id).await?;
+                        }
+                        Err(e) => {
+                            error!("Failed to toggle failure notice setting: {:#}", e);
+                            bot.answer_callback_query(q.id)
+                                .text("更新设置失败")
+                                .show_alert(true)
+                                .await?;
+                        }
+                    }
+                }
+                Ok(None) => {
+                    warn!(
+                        "Chat {} not found when toggling notify_failures by user {}",
+                        chat_id, user_id
+                    );
+                    bot.answer_callback_query(q.id)
+                        .text("获取聊天信息失败")
+                        .show_alert(true)
+                        .await?;
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to fetch chat {} for failure notice toggle by user {}: {:#}",
+                        chat_id, user_id, e
+                    );
+                    bot.answer_callback_query(q.id)
+                        .text("获取聊天信息失败")
+                        .show_alert(true)
+                        .await?;
+                }
+            }
+        }
         "mention:toggle" => {
             // Toggle allow_without_mention setting
             match handler.repo.get_chat(chat_id.0).await {
@@ -376,19 +916,24 @@ pub async fn handle_settings_callback(
             let state = if is_sensitive {
                 SettingsState::WaitingForSensitiveTags {
                     settings_message_id: message_id,
-                    created_at: Instant::now(),
                 }
             } else {
                 SettingsState::WaitingForExcludedTags {
                     settings_message_id: message_id,
-                    created_at: Instant::now(),
                 }
             };
 
             // Store the state
-            {
-                let mut storage_guard = storage.write().await;
-                storage_guard.insert((chat_id, user_id), state);
+            if let Err(e) = storage.insert(chat_id, user_id, &state).await {
+                error!(
+                    "Failed to save settings dialogue for user {} in chat {}: {:#}",
+                    user_id, chat_id, e
+                );
+                bot.answer_callback_query(q.id)
+                    .text("保存状态失败，请重试")
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
             }
 
             let tag_type = if is_sensitive {
@@ -450,10 +995,7 @@ pub async fn handle_settings_input(
     };
 
     // Check if user has an active dialogue state
-    let state = {
-        let storage_guard = storage.read().await;
-        storage_guard.get(&(chat_id, user_id)).cloned()
-    };
+    let state = storage.get(chat_id, user_id).await?;
 
     let (is_sensitive, settings_message_id) = match &state {
         Some(s @ SettingsState::WaitingForSensitiveTags { .. }) => (true, s.settings_message_id()),
@@ -501,10 +1043,7 @@ pub async fn handle_settings_input(
         if tags.is_empty() {
             bot.send_message(chat_id, "❌ 未提供有效的标签").await?;
             // Clear state and return as handled
-            {
-                let mut storage_guard = storage.write().await;
-                storage_guard.remove(&(chat_id, user_id));
-            }
+            storage.remove(chat_id, user_id).await?;
             return Ok(true);
         }
 
@@ -545,10 +1084,7 @@ pub async fn handle_settings_input(
     }
 
     // Clear state after processing
-    {
-        let mut storage_guard = storage.write().await;
-        storage_guard.remove(&(chat_id, user_id));
-    }
+    storage.remove(chat_id, user_id).await?;
 
     // Refresh the settings panel
     handler
@@ -574,10 +1110,7 @@ pub async fn handle_settings_cancel(
     };
 
     // Check if user has an active dialogue state
-    let had_state = {
-        let mut storage_guard = storage.write().await;
-        storage_guard.remove(&(chat_id, user_id)).is_some()
-    };
+    let had_state = storage.remove(chat_id, user_id).await?;
 
     if had_state {
         bot.send_message(chat_id, "✅ 操作已取消").await?;
@@ -594,6 +1127,16 @@ pub async fn handle_settings_cancel(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_author_ids_input() {
+        assert_eq!(
+            parse_author_ids_input("123, 456，789"),
+            Some(vec![123, 456, 789])
+        );
+        assert_eq!(parse_author_ids_input("123,abc"), None);
+        assert_eq!(parse_author_ids_input(" , "), None);
+    }
+
     #[test]
     fn test_parse_tags_input_normal_comma() {
         let result = parse_tags_input("tag1, tag2, tag3");
@@ -1,13 +1,33 @@
 mod author;
+mod author_stats;
+mod bookmarks;
 mod booru;
 mod channel;
+mod channel_admins;
 mod ehentai;
+mod ehinfo;
+mod export;
+mod forward;
+mod health;
 mod helpers;
 mod list;
+mod mirror;
+mod pause;
+mod preview;
+mod random;
 mod ranking;
+mod set_filter;
+mod simulate;
+mod top;
 mod types;
+mod unsub_all;
 
+pub use ehinfo::{parse_eh_info_callback_data, EH_INFO_CALLBACK_PREFIX};
+pub use forward::{parse_forward_sub_callback_data, FORWARD_SUB_CALLBACK_PREFIX};
+pub use health::AuthorStatusCache;
 pub use list::{parse_list_callback_data, LIST_CALLBACK_PREFIX};
+pub use ranking::RANKING_PUSH_CALLBACK_PREFIX;
 pub use types::ListPaginationAction;
+pub use unsub_all::{parse_unsub_all_callback_data, UNSUB_ALL_CALLBACK_PREFIX};
 
 pub(super) use types::{BatchResult, PAGE_SIZE};
@@ -1,22 +1,65 @@
+use super::helpers::{
+    ai_filter_summary, author_profile_summary, image_size_summary, log_task_cleanup,
+    parse_silent_arg, parse_topic_arg, silent_summary, topic_summary,
+};
 use super::BatchResult;
 use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
-use crate::db::types::{TagFilter, TaskType};
+use crate::db::types::{AiFilter, PushImageSize, PushMode, SpoilerMode, TagFilter, TaskType};
+use crate::pixiv::avatar::AuthorImages;
 use crate::pixiv::model::RankingMode;
 use crate::utils::args;
+use std::collections::HashMap;
 use teloxide::prelude::*;
-use teloxide::types::{ChatAction, ChatId, ParseMode, UserId};
+use teloxide::types::{ChatAction, ChatId, InputFile, ParseMode, UserId};
 use teloxide::utils::markdown;
 use tracing::{error, warn};
 
+/// Telegram 图片说明的长度上限 (UTF-16)
+const CAPTION_MAX_UTF16_UNITS: usize = 1024;
+
+/// 单个作者允许设置的轮询间隔范围（分钟）
+const MIN_POLL_INTERVAL_MIN: i32 = 10;
+const MAX_POLL_INTERVAL_MIN: i32 = 7 * 24 * 60;
+
+/// 解析 `interval=<分钟>` 参数；`0` 表示清除覆盖、恢复全局配置
+fn parse_poll_interval(value: &str) -> Result<Option<i32>, ()> {
+    match value.parse::<i32>() {
+        Ok(0) => Ok(None),
+        Ok(minutes) if (MIN_POLL_INTERVAL_MIN..=MAX_POLL_INTERVAL_MIN).contains(&minutes) => {
+            Ok(Some(minutes))
+        }
+        _ => Err(()),
+    }
+}
+
+/// 从作者 ID 之后的参数中取出 `<key>=<value>`（如 `spoiler=`、`push_mode=`），其余参数原样返回
+fn split_option_arg<'a>(args: &[&'a str], key: &str) -> (Option<&'a str>, Vec<&'a str>) {
+    let mut value = None;
+    let mut rest = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg
+            .strip_prefix(key)
+            .and_then(|suffix| suffix.strip_prefix('='))
+        {
+            Some(found) => value = Some(found),
+            None => rest.push(*arg),
+        }
+    }
+    (value, rest)
+}
+
 impl BotHandler {
     /// 订阅 Pixiv 作者
+    ///
+    /// `interval=` 修改的是所有订阅该作者的聊天共用的任务，因此只有 `can_set_interval` 时才接受。
     pub async fn handle_sub_author(
         &self,
         bot: ThrottledBot,
         chat_id: ChatId,
         user_id: Option<UserId>,
         args_str: String,
+        can_set_interval: bool,
     ) -> ResponseResult<()> {
         if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
             warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
@@ -44,13 +87,130 @@ impl BotHandler {
         if parts.is_empty() {
             bot.send_message(
                 chat_id,
-                "❌ 用法: `/sub [ch=<频道ID>] <id,...> [+tag1 -tag2]`",
+                "❌ 用法: `/sub [ch=<频道ID>] [interval=<分钟>] <id,...> [+tag1 -tag2]`",
             )
             .parse_mode(ParseMode::MarkdownV2)
             .await?;
             return Ok(());
         }
 
+        if parsed.get("interval").is_some() && !can_set_interval {
+            bot.send_message(
+                chat_id,
+                "❌ 轮询间隔对所有订阅该作者的聊天生效，只有管理员可以设置",
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let poll_interval = match parsed.get("interval").map(parse_poll_interval) {
+            Some(Ok(interval)) => Some(interval),
+            Some(Err(())) => {
+                bot.send_message(
+                    chat_id,
+                    format!(
+                        "❌ 轮询间隔必须是 {}\\-{} 之间的分钟数，或 0 表示使用默认间隔",
+                        MIN_POLL_INTERVAL_MIN, MAX_POLL_INTERVAL_MIN
+                    ),
+                )
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let (trailing_spoiler, rest_args) = split_option_arg(&parts[1..], "spoiler");
+        let (trailing_push_mode, rest_args) = split_option_arg(&rest_args, "push_mode");
+        let (trailing_silent, rest_args) = split_option_arg(&rest_args, "silent");
+        let (trailing_topic, rest_args) = split_option_arg(&rest_args, "topic");
+        let (trailing_ai, rest_args) = split_option_arg(&rest_args, "ai");
+        let (trailing_size, tag_args) = split_option_arg(&rest_args, "size");
+        let spoiler_arg = parsed.get("spoiler").or(trailing_spoiler);
+        let spoiler_mode = match spoiler_arg.map(SpoilerMode::parse) {
+            Some(Some(mode)) => Some(mode),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `spoiler` 只能是 `always`、`never` 或 `auto`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let push_mode_arg = parsed.get("push_mode").or(trailing_push_mode);
+        let push_mode = match push_mode_arg.map(PushMode::parse) {
+            Some(Some(mode)) => Some(mode),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `push_mode` 只能是 `single` 或 `album`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let silent = match parsed
+            .get("silent")
+            .or(trailing_silent)
+            .map(parse_silent_arg)
+        {
+            Some(Some(silent)) => Some(silent),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `silent` 只能是 `true` 或 `false`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let ai_filter = match parsed.get("ai").or(trailing_ai).map(AiFilter::parse) {
+            Some(Some(filter)) => Some(filter),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `ai` 只能是 `all`、`exclude`、`only` 或 `auto`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let image_size = match parsed
+            .get("size")
+            .or(trailing_size)
+            .map(PushImageSize::parse)
+        {
+            Some(Some(size)) => Some(size),
+            Some(None) => {
+                bot.send_message(
+                    chat_id,
+                    "❌ `size` 只能是 `original`、`large`、`medium` 或 `auto`",
+                )
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let topic = match parsed.get("topic").or(trailing_topic).map(parse_topic_arg) {
+            Some(Some(_)) if is_channel || !target_chat_id.is_channel_or_supergroup() => {
+                bot.send_message(chat_id, "❌ 只有超级群组可以指定 `topic`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            Some(Some(topic)) => Some(topic),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `topic` 只能是话题 ID 或 `off`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
         let author_ids: Vec<&str> = parts[0]
             .split(',')
             .map(|s| s.trim())
@@ -63,7 +223,28 @@ impl BotHandler {
             return Ok(());
         }
 
-        let filter_tags = TagFilter::parse_from_args(&parts[1..]);
+        let mut filter_tags = TagFilter::parse_from_args(&tag_args);
+
+        let blocked_authors = match self.repo.get_chat(target_chat_id.0).await {
+            Ok(Some(chat)) => {
+                filter_tags.merge(&chat.default_filter);
+                chat.blocked_authors
+            }
+            Ok(None) => Default::default(),
+            Err(e) => {
+                warn!(
+                    "Failed to get settings of chat {} for /sub: {:#}",
+                    target_chat_id, e
+                );
+                Default::default()
+            }
+        };
+        let mut blocked_subscribed = Vec::new();
+        let mut subscribed_ids = Vec::new();
+        let mut profiles = HashMap::new();
+        // `interval=` changes the shared task, report authors where that failed
+        let mut interval_set = false;
+        let mut interval_failed = Vec::new();
 
         let mut result = BatchResult::new();
 
@@ -76,10 +257,15 @@ impl BotHandler {
                 }
             };
 
-            let author_name = {
+            let (author_name, profile) = {
                 let pixiv = self.pixiv_client.read().await;
-                match pixiv.get_user_detail(author_id).await {
-                    Ok(user) => user.name,
+                match pixiv.get_user_profile(author_id).await {
+                    Ok(detail) => {
+                        self.author_images
+                            .insert(author_id, AuthorImages::from_detail(&detail))
+                            .await;
+                        (detail.user.name, detail.profile)
+                    }
                     Err(e) => {
                         error!("Failed to get user detail for {}: {:#}", author_id, e);
                         result.add_failure(format!("`{}` \\(未找到\\)", author_id));
@@ -95,10 +281,59 @@ impl BotHandler {
                     author_id_str,
                     Some(&author_name),
                     filter_tags.clone(),
+                    user_id,
                 )
                 .await
             {
-                Ok(_) => {
+                Ok((task, mut subscription)) => {
+                    self.apply_subscription_silent(&mut subscription, silent)
+                        .await;
+                    self.apply_subscription_topic(&mut subscription, topic)
+                        .await;
+                    self.apply_subscription_ai_filter(&mut subscription, ai_filter)
+                        .await;
+                    self.apply_subscription_image_size(&mut subscription, image_size)
+                        .await;
+                    if let Some(mode) = spoiler_mode {
+                        if let Err(e) = self
+                            .repo
+                            .update_subscription_spoiler_mode(subscription.id, mode)
+                            .await
+                        {
+                            error!(
+                                "Failed to set spoiler mode for author {}: {:#}",
+                                author_id, e
+                            );
+                        }
+                    }
+                    if let Some(mode) = push_mode {
+                        if let Err(e) = self
+                            .repo
+                            .update_subscription_push_mode(subscription.id, mode)
+                            .await
+                        {
+                            error!("Failed to set push mode for author {}: {:#}", author_id, e);
+                        }
+                    }
+                    if let Some(interval) = poll_interval {
+                        match self.repo.update_task_poll_interval(task.id, interval).await {
+                            Ok(_) => interval_set = true,
+                            Err(e) => {
+                                error!(
+                                    "Failed to set poll interval for author {}: {:#}",
+                                    author_id, e
+                                );
+                                interval_failed.push(format!("`{}`", author_id));
+                            }
+                        }
+                    }
+                    if blocked_authors.contains(&author_id) {
+                        blocked_subscribed.push(format!("`{}`", author_id));
+                    }
+                    subscribed_ids.push(author_id);
+                    if let Some(profile) = profile {
+                        profiles.insert(author_id, profile);
+                    }
                     result.add_success(format!(
                         "*{}* \\(ID: `{}`\\)",
                         markdown::escape(&author_name),
@@ -116,21 +351,72 @@ impl BotHandler {
         if !filter_tags.is_empty() {
             suffix_parts.push(format!("🏷 {}", filter_tags.format_for_display()));
         }
+        match poll_interval {
+            Some(Some(minutes)) if interval_set => {
+                suffix_parts.push(format!("⏱ 轮询间隔: {} 分钟", minutes))
+            }
+            Some(None) if interval_set => suffix_parts.push("⏱ 轮询间隔: 默认".to_string()),
+            _ => {}
+        }
+        if !interval_failed.is_empty() {
+            suffix_parts.push(format!(
+                "❌ 作者 {} 的轮询间隔设置失败，请稍后重试",
+                interval_failed.join(", ")
+            ));
+        }
+        if let Some(mode) = spoiler_mode {
+            suffix_parts.push(format!("🫣 遮罩: {}", mode.display_name()));
+        }
+        if let Some(mode) = push_mode {
+            suffix_parts.push(format!("🖼 推送方式: {}", mode.display_name()));
+        }
+        if let Some(silent) = silent {
+            suffix_parts.push(silent_summary(silent).to_string());
+        }
+        if let Some(topic) = topic {
+            suffix_parts.push(topic_summary(topic));
+        }
+        if let Some(ai_filter) = ai_filter {
+            suffix_parts.push(ai_filter_summary(ai_filter));
+        }
+        if let Some(image_size) = image_size {
+            suffix_parts.push(image_size_summary(image_size));
+        }
         if is_channel {
             suffix_parts.push(format!("📢 频道: `{}`", target_chat_id.0));
         }
+        if !blocked_subscribed.is_empty() {
+            suffix_parts.push(format!(
+                "⚠️ 作者 {} 已被屏蔽，其作品不会推送，可用 /unblockauthor 解除",
+                blocked_subscribed.join(", ")
+            ));
+        }
         let filter_suffix = if suffix_parts.is_empty() {
             None
         } else {
             Some(format!("\n{}", suffix_parts.join("\n")))
         };
 
-        let response = result.build_response_with_suffix(
+        let mut response = result.build_response_with_suffix(
             "✅ 成功订阅:",
             "❌ 订阅失败:",
             filter_suffix.as_deref(),
         );
 
+        // A single new subscription is confirmed with the author's card: banner or
+        // avatar, with the profile stats under the subscription summary
+        if let [author_id] = subscribed_ids.as_slice() {
+            if let Some(profile) = profiles.get(author_id) {
+                response.push_str(&format!("\n\n{}", author_profile_summary(profile)));
+            }
+            if self
+                .send_author_card(&bot, chat_id, *author_id, &response)
+                .await
+            {
+                return Ok(());
+            }
+        }
+
         bot.send_message(chat_id, response)
             .parse_mode(ParseMode::MarkdownV2)
             .await?;
@@ -138,6 +424,47 @@ impl BotHandler {
         Ok(())
     }
 
+    /// 以作者横幅（无横幅时为头像）作为图片发送作者卡片，无法发送时返回 false
+    pub(super) async fn send_author_card(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        author_id: u64,
+        caption: &str,
+    ) -> bool {
+        if caption.encode_utf16().count() > CAPTION_MAX_UTF16_UNITS {
+            return false;
+        }
+        let Some(images) = self.author_images.get(author_id).await else {
+            return false;
+        };
+        let Some(url) = images.preferred_url() else {
+            return false;
+        };
+
+        // Pixiv images need a referer, so they go through the (caching) downloader
+        let path = match self.notifier.get_downloader().download(url).await {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Failed to download images of author {}: {:#}", author_id, e);
+                return false;
+            }
+        };
+
+        match bot
+            .send_photo(chat_id, InputFile::file(path))
+            .caption(caption)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await
+        {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Failed to send author {} card: {:#}", author_id, e);
+                false
+            }
+        }
+    }
+
     /// 取消订阅作者
     pub async fn handle_unsub_author(
         &self,
@@ -260,6 +587,13 @@ impl BotHandler {
         };
 
         let (_msg_record, subscription, task) = message_info;
+
+        // 镜像推送的消息记录指向源聊天的订阅，不能在目标聊天中取消
+        if subscription.chat_id != chat_id.0 {
+            bot.send_message(chat_id, "❌ 该消息来自镜像推送，请在源聊天中取消订阅")
+                .await?;
+            return Ok(());
+        }
         let task = match task {
             Some(t) => t,
             None => {
@@ -277,15 +611,15 @@ impl BotHandler {
         let task_type = task.r#type;
         let task_value = task.value.clone();
 
-        if let Err(e) = self.repo.delete_subscription(subscription_id).await {
-            error!("Failed to delete subscription {}: {:#}", subscription_id, e);
-            bot.send_message(chat_id, "❌ 取消订阅失败").await?;
-            return Ok(());
+        match self.repo.unsubscribe_tx(subscription_id, task_id).await {
+            Ok(task_deleted) => log_task_cleanup(task_id, task_type, &task_value, task_deleted),
+            Err(e) => {
+                error!("Failed to delete subscription {}: {:#}", subscription_id, e);
+                bot.send_message(chat_id, "❌ 取消订阅失败").await?;
+                return Ok(());
+            }
         }
 
-        self.cleanup_orphaned_task(task_id, task_type, &task_value)
-            .await;
-
         let display_name = match task_type {
             TaskType::Author => {
                 if let Some(ref name) = task.author_name {
@@ -318,6 +652,14 @@ impl BotHandler {
                     markdown::escape(&task_value)
                 )
             }
+            TaskType::Bookmarks => match task.author_name.as_deref() {
+                Some(name) => format!(
+                    "*{}* 的收藏 \\(ID: `{}`\\)",
+                    markdown::escape(name),
+                    task_value
+                ),
+                None => format!("收藏 `{}`", task_value),
+            },
         };
 
         bot.send_message(chat_id, format!("✅ 成功取消订阅 {}", display_name))
@@ -327,3 +669,35 @@ impl BotHandler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_poll_interval, split_option_arg};
+
+    #[test]
+    fn parse_poll_interval_accepts_range_and_reset() {
+        assert_eq!(parse_poll_interval("30"), Ok(Some(30)));
+        assert_eq!(parse_poll_interval("0"), Ok(None));
+        assert_eq!(parse_poll_interval("5"), Err(()));
+        assert_eq!(parse_poll_interval("abc"), Err(()));
+        assert_eq!(parse_poll_interval(""), Err(()));
+    }
+
+    #[test]
+    fn split_option_arg_separates_options_from_tags() {
+        let (spoiler, rest) = split_option_arg(
+            &["+原神", "spoiler=always", "push_mode=album", "-R-18"],
+            "spoiler",
+        );
+        assert_eq!(spoiler, Some("always"));
+        assert_eq!(rest, ["+原神", "push_mode=album", "-R-18"]);
+
+        let (push_mode, tags) = split_option_arg(&rest, "push_mode");
+        assert_eq!(push_mode, Some("album"));
+        assert_eq!(tags, ["+原神", "-R-18"]);
+
+        let (spoiler, tags) = split_option_arg(&["+tag", "spoilers=x"], "spoiler");
+        assert_eq!(spoiler, None);
+        assert_eq!(tags, ["+tag", "spoilers=x"]);
+    }
+}
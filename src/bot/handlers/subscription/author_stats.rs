@@ -0,0 +1,270 @@
+use super::helpers::author_profile_summary;
+use crate::bot::handlers::PREVIEW_BUDGET_EXHAUSTED;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::repo::PushStats;
+use crate::db::types::TaskType;
+use crate::pixiv::avatar::AuthorImages;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tracing::{error, warn};
+
+/// Pages of the author's works fetched for the statistics (30 works per page)
+const AUTHOR_STATS_FETCH_PAGES: usize = 2;
+
+/// Number of weeks shown in the posting sparkline
+const SPARKLINE_WEEKS: usize = 8;
+
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Pushes of this chat's subscription to the author
+struct SubscriptionPushes {
+    stats: PushStats,
+    enabled: bool,
+}
+
+impl BotHandler {
+    /// 作者统计：主页资料、近期发布频率、平均收藏数和本聊天的推送情况
+    ///
+    /// 与 `/sub` 的确认消息一样以作者卡片（横幅或头像）发送，无法发送图片时退回纯文本。
+    pub async fn handle_author_stats(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(author_id) = args_str.trim().parse::<u64>().ok() else {
+            bot.send_message(chat_id, "❌ 用法: `/authorstats <author_id>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let (author, illusts) = {
+            let pixiv = self.pixiv_client.read().await;
+            // User detail + illust pages
+            if !pixiv.try_reserve_preview(1 + AUTHOR_STATS_FETCH_PAGES) {
+                drop(pixiv);
+                bot.send_message(chat_id, PREVIEW_BUDGET_EXHAUSTED).await?;
+                return Ok(());
+            }
+            let author = match pixiv.get_user_profile(author_id).await {
+                Ok(detail) => detail,
+                Err(e) => {
+                    error!("Failed to get user detail for {}: {:#}", author_id, e);
+                    bot.send_message(chat_id, format!("❌ 获取用户 {} 失败", author_id))
+                        .await?;
+                    return Ok(());
+                }
+            };
+            let illusts = match pixiv
+                .get_user_illusts_pages(author_id, AUTHOR_STATS_FETCH_PAGES)
+                .await
+            {
+                Ok(illusts) => illusts,
+                Err(e) => {
+                    error!("Failed to get illusts for {}: {:#}", author_id, e);
+                    bot.send_message(chat_id, "❌ 获取作品列表失败").await?;
+                    return Ok(());
+                }
+            };
+            (author, illusts)
+        };
+
+        let today = Local::now().date_naive();
+        let post_dates: Vec<NaiveDate> = illusts
+            .iter()
+            .filter_map(|illust| parse_post_date(&illust.create_date))
+            .collect();
+        let weekly = weekly_post_counts(&post_dates, today, SPARKLINE_WEEKS);
+        let recent_30d = post_dates
+            .iter()
+            .filter(|date| (today - **date).num_days() < 30)
+            .count();
+
+        self.author_images
+            .insert(author_id, AuthorImages::from_detail(&author))
+            .await;
+
+        let mut message = format!(
+            "📊 *作者统计* \\- *{}* \\(ID: `{}`\\)\n\n",
+            markdown::escape(&author.user.name),
+            author_id
+        );
+        if let Some(profile) = &author.profile {
+            message.push_str(&format!("{}\n", author_profile_summary(profile)));
+        }
+
+        if illusts.is_empty() {
+            message.push_str("📭 该作者暂无公开作品\n");
+        } else {
+            let weekly_total: u32 = weekly.iter().sum();
+            message.push_str(&format!(
+                "📈 近 {} 周发布: `{}` \\(共 `{}` 部，平均每周 `{}`\\)\n",
+                SPARKLINE_WEEKS,
+                sparkline(&weekly),
+                weekly_total,
+                markdown::escape(&format!(
+                    "{:.1}",
+                    weekly_total as f64 / SPARKLINE_WEEKS as f64
+                ))
+            ));
+            message.push_str(&format!("🗓 近 30 天作品: `{}`\n", recent_30d));
+            if let Some(latest) = post_dates.iter().max() {
+                message.push_str(&format!("🕒 最近发布: `{}`\n", latest.format("%Y-%m-%d")));
+            }
+            let total_bookmarks: u64 = illusts.iter().map(|illust| illust.total_bookmarks).sum();
+            message.push_str(&format!(
+                "❤️ 最近 {} 部作品平均收藏: `{}`\n",
+                illusts.len(),
+                total_bookmarks / illusts.len() as u64
+            ));
+        }
+
+        message.push('\n');
+        match self.subscription_pushes(chat_id, author_id).await {
+            Ok(Some(pushes)) => {
+                message.push_str(&format!(
+                    "📬 本聊天近 30 天推送: `{}` 部 \\(失败 `{}` 次\\)",
+                    pushes.stats.pushed, pushes.stats.failures
+                ));
+                if !pushes.enabled {
+                    message.push_str(" ⏸ *已暂停*");
+                }
+            }
+            Ok(None) => message.push_str("📭 本聊天未订阅该作者"),
+            Err(e) => {
+                error!(
+                    "Failed to load push stats of author {} in chat {}: {:#}",
+                    author_id, chat_id, e
+                );
+                message.push_str("❌ 获取本聊天推送记录失败");
+            }
+        }
+
+        if self
+            .send_author_card(&bot, chat_id, author_id, &message)
+            .await
+        {
+            return Ok(());
+        }
+
+        bot.send_message(chat_id, message)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Push stats of the chat's subscription to the author, None if not subscribed
+    async fn subscription_pushes(
+        &self,
+        chat_id: ChatId,
+        author_id: u64,
+    ) -> Result<Option<SubscriptionPushes>> {
+        let Some(task) = self
+            .repo
+            .get_task_by_type_value(TaskType::Author, &author_id.to_string())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(subscription) = self
+            .repo
+            .get_subscription_by_chat_task(chat_id.0, task.id)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let since = Utc::now().naive_utc() - chrono::Duration::days(30);
+        let stats = self
+            .repo
+            .get_subscription_push_stats(subscription.id, since)
+            .await?;
+        Ok(Some(SubscriptionPushes {
+            stats,
+            enabled: subscription.enabled,
+        }))
+    }
+}
+
+/// Local posting date of a Pixiv `create_date` (RFC 3339)
+fn parse_post_date(create_date: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(create_date)
+        .ok()
+        .map(|date| date.with_timezone(&Local).date_naive())
+}
+
+/// Works posted per week over the last `weeks` weeks, oldest week first
+fn weekly_post_counts(dates: &[NaiveDate], today: NaiveDate, weeks: usize) -> Vec<u32> {
+    let mut counts = vec![0; weeks];
+    for date in dates {
+        let days_ago = (today - *date).num_days();
+        if days_ago < 0 {
+            continue;
+        }
+        let week = (days_ago / 7) as usize;
+        if week < weeks {
+            counts[weeks - 1 - week] += 1;
+        }
+    }
+    counts
+}
+
+/// One bar per value, scaled to the largest value
+fn sparkline(values: &[u32]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            let level = if max == 0 {
+                0
+            } else {
+                (value as usize * (SPARKLINE_BARS.len() - 1)).div_ceil(max as usize)
+            };
+            SPARKLINE_BARS[level]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekly_post_counts_buckets_by_week_oldest_first() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let days_ago = |days: i64| today - chrono::Duration::days(days);
+        let dates = [
+            days_ago(0),
+            days_ago(6),
+            days_ago(7),
+            days_ago(20),
+            days_ago(60),
+            days_ago(-1),
+        ];
+
+        assert_eq!(weekly_post_counts(&dates, today, 4), [0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn sparkline_scales_to_largest_value() {
+        assert_eq!(sparkline(&[0, 1, 4, 7]), "▁▂▅█");
+        assert_eq!(sparkline(&[0, 0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn parse_post_date_reads_pixiv_timestamps() {
+        assert!(parse_post_date("2026-01-01T00:00:00+09:00").is_some());
+        assert!(parse_post_date("not a date").is_none());
+    }
+}
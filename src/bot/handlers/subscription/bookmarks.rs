@@ -0,0 +1,182 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::{TagFilter, TaskType};
+use crate::utils::args;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode, UserId};
+use teloxide::utils::markdown;
+use tracing::{error, warn};
+
+impl BotHandler {
+    /// 订阅 Pixiv 用户的公开收藏
+    pub async fn handle_sub_bookmarks(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let parsed = args::parse_args(&args_str);
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let parts: Vec<&str> = parsed.remaining.split_whitespace().collect();
+        let Some(pixiv_user_id) = parts.first().and_then(|s| s.parse::<u64>().ok()) else {
+            bot.send_message(
+                chat_id,
+                "❌ 用法: `/subbookmarks [ch=<频道ID>] <用户ID> [+tag1 -tag2]`",
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+            return Ok(());
+        };
+
+        let mut filter_tags = TagFilter::parse_from_args(&parts[1..]);
+        match self.repo.get_chat(target_chat_id.0).await {
+            Ok(Some(chat)) => filter_tags.merge(&chat.default_filter),
+            Ok(None) => {}
+            Err(e) => warn!(
+                "Failed to get settings of chat {} for /subbookmarks: {:#}",
+                target_chat_id, e
+            ),
+        }
+
+        let user_name = {
+            let pixiv = self.pixiv_client.read().await;
+            match pixiv.get_user_profile(pixiv_user_id).await {
+                Ok(detail) => detail.user.name,
+                Err(e) => {
+                    error!("Failed to get user detail for {}: {:#}", pixiv_user_id, e);
+                    bot.send_message(chat_id, format!("❌ 未找到用户 {}", pixiv_user_id))
+                        .await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        if let Err(e) = self
+            .create_subscription(
+                target_chat_id.0,
+                TaskType::Bookmarks,
+                &pixiv_user_id.to_string(),
+                Some(&user_name),
+                filter_tags.clone(),
+                user_id,
+            )
+            .await
+        {
+            error!(
+                "Failed to subscribe to bookmarks of user {}: {:#}",
+                pixiv_user_id, e
+            );
+            bot.send_message(chat_id, "❌ 订阅失败").await?;
+            return Ok(());
+        }
+
+        let mut response = format!(
+            "✅ 成功订阅 *{}* \\(ID: `{}`\\) 的公开收藏\n新收藏的作品将自动推送",
+            markdown::escape(&user_name),
+            pixiv_user_id
+        );
+        if !filter_tags.is_empty() {
+            response.push_str(&format!("\n🏷 {}", filter_tags.format_for_display()));
+        }
+        if is_channel {
+            response.push_str(&format!("\n📢 频道: `{}`", target_chat_id.0));
+        }
+
+        bot.send_message(chat_id, response)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 取消订阅 Pixiv 用户的收藏
+    pub async fn handle_unsub_bookmarks(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let pixiv_user_id = parsed.remaining.trim();
+        if pixiv_user_id.is_empty() {
+            bot.send_message(chat_id, "❌ 用法: `/unsubbookmarks [ch=<频道ID>] <用户ID>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        }
+
+        let response = match self
+            .delete_subscription(target_chat_id.0, TaskType::Bookmarks, pixiv_user_id)
+            .await
+        {
+            Ok(user_name) => {
+                let display = match user_name {
+                    Some(name) => format!(
+                        "*{}* \\(ID: `{}`\\)",
+                        markdown::escape(&name),
+                        markdown::escape(pixiv_user_id)
+                    ),
+                    None => format!("`{}`", markdown::escape(pixiv_user_id)),
+                };
+                let mut response = format!("✅ 已取消订阅 {} 的收藏", display);
+                if is_channel {
+                    response.push_str(&format!("\n📢 频道: `{}`", target_chat_id.0));
+                }
+                response
+            }
+            Err(e) => {
+                error!(
+                    "Failed to unsubscribe from bookmarks of user {}: {:#}",
+                    pixiv_user_id, e
+                );
+                format!(
+                    "❌ 未找到用户 `{}` 的收藏订阅",
+                    markdown::escape(pixiv_user_id)
+                )
+            }
+        };
+
+        bot.send_message(chat_id, response)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -200,6 +200,7 @@ impl BotHandler {
                 Some(&display_name),
                 tag_filter.clone(),
                 booru_filter.clone(),
+                user_id,
             )
             .await
         {
@@ -672,6 +673,7 @@ impl BotHandler {
                 Some(&display_name),
                 tag_filter.clone(),
                 booru_filter.clone(),
+                user_id,
             )
             .await
         {
@@ -845,6 +847,7 @@ impl BotHandler {
                 Some(&display_name),
                 tag_filter.clone(),
                 booru_filter.clone(),
+                user_id,
             )
             .await
         {
@@ -2,7 +2,7 @@ use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
 use crate::utils::args;
 use crate::utils::channel::{self, BotChannelExt};
-use teloxide::types::{ChatId, UserId};
+use teloxide::types::{ChatId, ChatMemberStatus, UserId};
 use tracing::{error, warn};
 
 impl BotHandler {
@@ -35,31 +35,67 @@ impl BotHandler {
                 let channel_id = bot
                     .validate_channel_permissions(&channel_identifier, user_id)
                     .await?;
+                self.check_channel_delegate(bot, channel_id, user_id)
+                    .await?;
 
-                if let Err(e) = self
+                let chat = self
                     .repo
                     .upsert_chat(
                         channel_id.0,
                         "channel".to_string(),
                         None,
                         true,
-                        crate::db::types::Tags::from(self.default_sensitive_tags.clone()),
+                        crate::db::types::Tags::from(self.default_sensitive_tags()),
                     )
                     .await
-                {
-                    error!(
-                        "Failed to create chat record for channel {} during subscription: {:#}",
-                        channel_id, e
-                    );
-                    return Err(format!(
-                        "创建频道记录失败 (Failed to create chat record for channel {})",
-                        channel_id
-                    ));
-                }
+                    .map_err(|e| {
+                        error!(
+                            "Failed to create chat record for channel {} during subscription: {:#}",
+                            channel_id, e
+                        );
+                        format!(
+                            "创建频道记录失败 (Failed to create chat record for channel {})",
+                            channel_id
+                        )
+                    })?;
+
+                // The bot that just checked its admin rights in the channel posts there
+                self.remember_chat_bot(bot, channel_id, chat.bot_id).await;
 
                 Ok((channel_id, true))
             }
             _ => Ok((current_chat_id, false)),
         }
     }
+
+    /// Once a channel has delegated subscription managers, only they and the
+    /// channel owner may manage it; otherwise every channel admin may.
+    pub(super) async fn check_channel_delegate(
+        &self,
+        bot: &ThrottledBot,
+        channel_id: ChatId,
+        user_id: UserId,
+    ) -> Result<(), String> {
+        let delegates = self
+            .repo
+            .list_channel_admins(channel_id.0)
+            .await
+            .map_err(|e| {
+                error!("Failed to list channel admins for {}: {:#}", channel_id, e);
+                "获取频道订阅管理员失败".to_string()
+            })?;
+
+        if delegates.is_empty() || delegates.iter().any(|d| d.user_id == user_id.0 as i64) {
+            return Ok(());
+        }
+
+        let status = bot
+            .user_channel_status(&channel::ChannelIdentifier::Id(channel_id), user_id)
+            .await?;
+        if status == ChatMemberStatus::Owner {
+            return Ok(());
+        }
+
+        Err("该频道已指定订阅管理员，您不在列表中".to_string())
+    }
 }
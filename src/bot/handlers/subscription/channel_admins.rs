@@ -0,0 +1,200 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::utils::args;
+use crate::utils::channel::{BotChannelExt, ChannelIdentifier};
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode, UserId};
+use tracing::{error, info};
+
+const USAGE: &str = "❌ 用法: `/channeladmins ch=<频道ID> [add|remove <user_id>]`";
+
+impl BotHandler {
+    /// 管理频道的订阅管理员：查看、添加或移除
+    pub async fn handle_channel_admins(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let Some(caller) = user_id else {
+            bot.send_message(chat_id, "❌ 无法获取用户信息").await?;
+            return Ok(());
+        };
+
+        if parsed.get_any(&["channel", "ch"]).is_none_or(str::is_empty) {
+            bot.send_message(chat_id, USAGE)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        }
+
+        let channel_id = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok((channel_id, _)) => channel_id,
+            Err(e) => {
+                bot.send_message(chat_id, format!("❌ {}", e)).await?;
+                return Ok(());
+            }
+        };
+
+        let delegates = match self.repo.list_channel_admins(channel_id.0).await {
+            Ok(delegates) => delegates,
+            Err(e) => {
+                error!("Failed to list channel admins for {}: {:#}", channel_id, e);
+                bot.send_message(chat_id, "❌ 获取频道订阅管理员失败")
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let parts: Vec<&str> = parsed.remaining.split_whitespace().collect();
+        let (action, target) = match parts[..] {
+            [] => {
+                let message = if delegates.is_empty() {
+                    format!(
+                        "📭 频道 `{}` 未指定订阅管理员，所有频道管理员均可管理订阅",
+                        channel_id.0
+                    )
+                } else {
+                    let lines: Vec<String> = delegates
+                        .iter()
+                        .map(|d| format!("  • `{}`", d.user_id))
+                        .collect();
+                    format!(
+                        "👥 频道 `{}` 的订阅管理员:\n{}\n\n频道创建者始终可以管理订阅",
+                        channel_id.0,
+                        lines.join("\n")
+                    )
+                };
+                bot.send_message(chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            [action, target] => match target.parse::<u64>() {
+                Ok(target) => (action, UserId(target)),
+                Err(_) => {
+                    bot.send_message(chat_id, USAGE)
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                    return Ok(());
+                }
+            },
+            _ => {
+                bot.send_message(chat_id, USAGE)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match action {
+            "add" => {
+                let channel = ChannelIdentifier::Id(channel_id);
+                match bot.is_user_channel_admin(&channel, target).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        bot.send_message(
+                            chat_id,
+                            format!("❌ 用户 `{}` 不是该频道的管理员", target.0),
+                        )
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        bot.send_message(chat_id, format!("❌ {}", e)).await?;
+                        return Ok(());
+                    }
+                }
+
+                // The first delegate restricts the channel, so keep the caller in the list
+                let mut added = vec![target];
+                if delegates.is_empty() && target != caller {
+                    added.push(caller);
+                }
+
+                for user in &added {
+                    if let Err(e) = self
+                        .repo
+                        .add_channel_admin(channel_id.0, user.0 as i64, Some(caller.0 as i64))
+                        .await
+                    {
+                        error!(
+                            "Failed to add channel admin {} to {}: {:#}",
+                            user, channel_id, e
+                        );
+                        bot.send_message(chat_id, "❌ 添加订阅管理员失败").await?;
+                        return Ok(());
+                    }
+                }
+
+                info!(
+                    "User {} added channel admins {:?} to channel {}",
+                    caller, added, channel_id
+                );
+
+                let mut message = format!(
+                    "✅ 已将用户 `{}` 添加为频道 `{}` 的订阅管理员",
+                    target.0, channel_id.0
+                );
+                if added.len() > 1 {
+                    message
+                        .push_str("\n你也已被加入列表，此后仅列表中的用户和频道创建者可管理订阅");
+                }
+                bot.send_message(chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+            }
+            "remove" => match self
+                .repo
+                .remove_channel_admin(channel_id.0, target.0 as i64)
+                .await
+            {
+                Ok(true) => {
+                    info!(
+                        "User {} removed channel admin {} from channel {}",
+                        caller, target, channel_id
+                    );
+                    let mut message = format!(
+                        "✅ 已将用户 `{}` 从频道 `{}` 的订阅管理员中移除",
+                        target.0, channel_id.0
+                    );
+                    if delegates.len() == 1 {
+                        message.push_str("\n列表已清空，所有频道管理员均可管理订阅");
+                    }
+                    bot.send_message(chat_id, message)
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                }
+                Ok(false) => {
+                    bot.send_message(
+                        chat_id,
+                        format!("❌ 用户 `{}` 不在订阅管理员列表中", target.0),
+                    )
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to remove channel admin {} from {}: {:#}",
+                        target, channel_id, e
+                    );
+                    bot.send_message(chat_id, "❌ 移除订阅管理员失败").await?;
+                }
+            },
+            _ => {
+                bot.send_message(chat_id, USAGE)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use crate::db::repo::eh_download_queue::{
 };
 use crate::db::types::{EhFilter, EhTaskKey, TagFilter, TaskType};
 use crate::utils::args;
-use eh_client::EhCategory;
+use eh_client::{EhCategory, EhClient};
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, ParseMode, UserId};
 use teloxide::utils::markdown;
@@ -16,6 +16,8 @@ use tracing::{error, warn};
 const EH_QUEUE_MAX_VISIBLE_ACTIVE_ITEMS: usize = 20;
 const EH_QUEUE_MAX_TITLE_CHARS: usize = 80;
 const TELEGRAM_MAX_MESSAGE_UTF16_UNITS: usize = 4096;
+/// Sample-page previews are sent as a single album, which Telegram caps at 10
+const MAX_PREVIEW_PAGES: u32 = 10;
 const EH_QUEUE_ACTIVE_STAGE_ORDER: [&str; 8] = [
     "后台下载中",
     "后台排队",
@@ -32,7 +34,7 @@ impl BotHandler {
         &self,
         bot: ThrottledBot,
         chat_id: ChatId,
-        _user_id: Option<UserId>,
+        user_id: Option<UserId>,
         args_str: String,
     ) -> ResponseResult<()> {
         if self.eh_client.is_none() {
@@ -44,7 +46,7 @@ impl BotHandler {
 
         // Resolve target chat (ch= param)
         let (target_chat, _is_channel) = match self
-            .resolve_subscription_target(&bot, chat_id, _user_id, &parsed)
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
             .await
         {
             Ok((chat_id, is_ch)) => (chat_id, is_ch),
@@ -69,7 +71,8 @@ impl BotHandler {
                      • pages>=N — 最低页数\n\
                      • pages<=N — 最高页数\n\
                      • cat=<类别> — 分类筛选 (逗号分隔)\n\
-                     • telegraph=on — 启用 Telegraph 上传",
+                     • telegraph=on — 启用 Telegraph 上传\n\
+                     • pages=N — 只推送预览: 0 为封面+信息, 1-10 为前 N 页组图",
                 )
                 .await;
             return Ok(());
@@ -89,6 +92,7 @@ impl BotHandler {
         let filter_args = parsed_esub.filter_args;
         let cat_str = parsed_esub.cat_str;
         let telegraph_on = parsed_esub.telegraph_on;
+        let preview_pages = parsed_esub.preview_pages;
 
         // Parse filter
         let mut eh_filter = match parse_eh_filter(&filter_args) {
@@ -102,6 +106,7 @@ impl BotHandler {
             }
         };
         eh_filter.telegraph = telegraph_on;
+        eh_filter.preview_pages = preview_pages;
 
         // Reject telegraph=on when Telegraph is not configured
         if should_reject_telegraph_request(telegraph_on, self.has_telegraph) {
@@ -139,6 +144,7 @@ impl BotHandler {
                 None,
                 TagFilter::default(),
                 eh_filter.clone(),
+                user_id,
             )
             .await
         {
@@ -349,6 +355,7 @@ impl BotHandler {
                         "用法: /edl <画廊URL> [telegraph=on]\n\n\
                          支持:\n\
                          • 画廊 URL: https://e-hentai.org/g/12345/token/\n\
+                         • 画廊 GID: 12345\n\
                          • 回复包含画廊链接的消息使用 /edl",
                     )
                     .await;
@@ -375,13 +382,11 @@ impl BotHandler {
             return Ok(());
         }
 
-        // Parse gallery URL
-        let (gid, token) = match parse_gallery_ref(&input) {
-            Some(g) => g,
-            None => {
-                let _ = bot
-                    .send_message(chat_id, "❌ 无法解析画廊标识。请提供画廊 URL。")
-                    .await;
+        // Parse gallery URL or GID
+        let (gid, token) = match resolve_gallery_ref(&eh_client, &input).await {
+            Ok(g) => g,
+            Err(message) => {
+                let _ = bot.send_message(chat_id, message).await;
                 return Ok(());
             }
         };
@@ -494,7 +499,7 @@ impl BotHandler {
                 let _ = bot
                     .send_message(
                         chat_id,
-                        "用法: /telegraph <画廊URL>\n\n\
+                        "用法: /telegraph <画廊URL或GID>\n\n\
                          下载画廊并上传 Telegraph，发送阅读链接。\n\
                          也可回复包含画廊链接的消息使用 /telegraph",
                     )
@@ -503,13 +508,11 @@ impl BotHandler {
             }
         };
 
-        // Parse gallery URL
-        let (gid, token) = match parse_gallery_ref(&input) {
-            Some(g) => g,
-            None => {
-                let _ = bot
-                    .send_message(chat_id, "❌ 无法解析画廊标识。请提供画廊 URL。")
-                    .await;
+        // Parse gallery URL or GID
+        let (gid, token) = match resolve_gallery_ref(&eh_client, &input).await {
+            Ok(g) => g,
+            Err(message) => {
+                let _ = bot.send_message(chat_id, message).await;
                 return Ok(());
             }
         };
@@ -619,6 +622,7 @@ struct ParsedEhSubscriptionArgs {
     filter_args: Vec<String>,
     cat_str: Option<String>,
     telegraph_on: bool,
+    preview_pages: Option<u32>,
 }
 
 fn parse_esub_remaining(remaining: &str) -> Result<ParsedEhSubscriptionArgs, String> {
@@ -626,6 +630,7 @@ fn parse_esub_remaining(remaining: &str) -> Result<ParsedEhSubscriptionArgs, Str
     let mut filter_args = Vec::new();
     let mut cat_str: Option<String> = None;
     let mut telegraph_on = false;
+    let mut preview_pages: Option<u32> = None;
 
     for part in remaining.split_whitespace() {
         if let Some(val) = part.strip_prefix("rating>=") {
@@ -649,6 +654,13 @@ fn parse_esub_remaining(remaining: &str) -> Result<ParsedEhSubscriptionArgs, Str
                 .parse::<u32>()
                 .map_err(|_| format!("无效的页数: {val}"))?;
             filter_args.push(format!("pages<={}", n.saturating_sub(1)));
+        } else if let Some(val) = part.strip_prefix("pages=") {
+            let n = val
+                .parse::<u32>()
+                .ok()
+                .filter(|n| *n <= MAX_PREVIEW_PAGES)
+                .ok_or_else(|| format!("无效的预览页数: {val} (0-{MAX_PREVIEW_PAGES})"))?;
+            preview_pages = Some(n);
         } else if let Some(val) = part.strip_prefix("cat=") {
             cat_str = Some(val.to_string());
         } else if part == "telegraph=on" {
@@ -662,11 +674,16 @@ fn parse_esub_remaining(remaining: &str) -> Result<ParsedEhSubscriptionArgs, Str
         return Err("请提供搜索词".to_string());
     }
 
+    if telegraph_on && preview_pages.is_some() {
+        return Err("pages= 预览推送不能与 telegraph=on 同时使用".to_string());
+    }
+
     Ok(ParsedEhSubscriptionArgs {
         query: query_parts.join(" "),
         filter_args,
         cat_str,
         telegraph_on,
+        preview_pages,
     })
 }
 
@@ -676,7 +693,7 @@ fn eh_task_value_for_query<'a>(task_value: &'a str, query: &str) -> Option<&'a s
 }
 
 /// Parse a gallery URL or GID into (gid, token).
-fn parse_gallery_ref(s: &str) -> Option<(u64, String)> {
+pub(super) fn parse_gallery_ref(s: &str) -> Option<(u64, String)> {
     let s = s.trim();
 
     // Try URL format: https://e-hentai.org/g/{gid}/{token}/
@@ -697,11 +714,35 @@ fn parse_gallery_ref(s: &str) -> Option<(u64, String)> {
         return None;
     }
 
-    // Try GID only — need to make an API call to get token, but we can't here.
-    // For GID-only, we'd need to use the gtoken API method. For now, require URL.
+    // GID only — the token is looked up by `resolve_gallery_ref`
     None
 }
 
+/// Parse a bare gallery GID, whose token still has to be resolved.
+fn parse_gallery_gid(s: &str) -> Option<u64> {
+    s.trim().parse::<u64>().ok().filter(|&gid| gid > 0)
+}
+
+/// 解析画廊 URL 或 GID，仅有 GID 时通过搜索查出 token。失败时返回提示文案
+pub(super) async fn resolve_gallery_ref(
+    eh_client: &EhClient,
+    input: &str,
+) -> Result<(u64, String), String> {
+    if let Some(gallery) = parse_gallery_ref(input) {
+        return Ok(gallery);
+    }
+    let Some(gid) = parse_gallery_gid(input) else {
+        return Err("❌ 无法解析画廊标识。请提供画廊 URL 或 GID。".to_string());
+    };
+    match eh_client.resolve_token(gid).await {
+        Ok(token) => Ok((gid, token)),
+        Err(e) => {
+            warn!("Failed to resolve token of eh gallery {}: {:#}", gid, e);
+            Err(format!("❌ 未找到画廊 {}", gid))
+        }
+    }
+}
+
 fn is_telegraph_enabled_value(value: &str) -> bool {
     value.eq_ignore_ascii_case("on") || value.eq_ignore_ascii_case("true") || value == "1"
 }
@@ -728,7 +769,7 @@ fn split_edl_remaining_and_telegraph(remaining: &str) -> (String, bool) {
 }
 
 /// Extract the first e-hentai/exhentai gallery URL from a text message.
-fn extract_gallery_url_from_text(text: &str) -> Option<String> {
+pub(super) fn extract_gallery_url_from_text(text: &str) -> Option<String> {
     for word in text.split_whitespace() {
         if (word.contains("e-hentai.org/g/") || word.contains("exhentai.org/g/"))
             && parse_gallery_ref(word).is_some()
@@ -1075,6 +1116,20 @@ mod tests {
         assert!(parsed.telegraph_on);
     }
 
+    #[test]
+    fn test_parse_esub_remaining_preview_pages() {
+        let parsed = parse_esub_remaining("foo pages>=20 pages=4").unwrap();
+        assert_eq!(parsed.query, "foo");
+        assert_eq!(parsed.filter_args, ["pages>=20"]);
+        assert_eq!(parsed.preview_pages, Some(4));
+
+        let parsed = parse_esub_remaining("foo pages=0").unwrap();
+        assert_eq!(parsed.preview_pages, Some(0));
+
+        assert!(parse_esub_remaining("foo pages=11").is_err());
+        assert!(parse_esub_remaining("foo pages=2 telegraph=on").is_err());
+    }
+
     #[test]
     fn test_eh_task_value_for_query_preserves_legacy_value() {
         let legacy = "eh:~foo%7Cbar|f=r4";
@@ -1113,8 +1168,11 @@ mod tests {
 
     #[test]
     fn test_parse_gallery_ref_gid_only() {
-        // GID only is not supported (need token)
+        // GID only has no token; it is resolved separately
         assert!(parse_gallery_ref("12345").is_none());
+        assert_eq!(parse_gallery_gid(" 12345 "), Some(12345));
+        assert_eq!(parse_gallery_gid("0"), None);
+        assert_eq!(parse_gallery_gid("https://e-hentai.org/g/12345/"), None);
     }
 
     #[test]
@@ -0,0 +1,420 @@
+use super::ehentai::{extract_gallery_url_from_text, resolve_gallery_ref};
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::utils::time::to_local;
+use chrono::DateTime;
+use eh_client::EhGallery;
+use teloxide::prelude::*;
+use teloxide::types::{
+    ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode, UserId,
+};
+use teloxide::utils::markdown;
+use tracing::{info, warn};
+
+/// Callback data prefix for the gallery card buttons.
+/// Format: `ehinfo:<gid>:<token>:d` (download) or `ehinfo:<gid>:<token>:s<tag_index>` (subscribe).
+pub const EH_INFO_CALLBACK_PREFIX: &str = "ehinfo:";
+
+/// 画廊卡片中最多提供订阅按钮的作者/社团数
+const MAX_SUBSCRIBE_BUTTONS: usize = 3;
+
+/// Telegram 图片文案的长度上限 (UTF-16)
+const CAPTION_MAX_UTF16_UNITS: usize = 1024;
+
+/// 可订阅的标签命名空间，按钮顺序与此相同
+const SUBSCRIBE_NAMESPACES: [&str; 2] = ["artist", "group"];
+
+/// 画廊卡片按钮的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EhInfoAction {
+    Download,
+    /// 订阅画廊第 N 个标签 (artist/group)
+    Subscribe(usize),
+}
+
+fn eh_info_callback_data(gid: u64, token: &str, action: EhInfoAction) -> String {
+    match action {
+        EhInfoAction::Download => format!("{}{}:{}:d", EH_INFO_CALLBACK_PREFIX, gid, token),
+        EhInfoAction::Subscribe(index) => {
+            format!("{}{}:{}:s{}", EH_INFO_CALLBACK_PREFIX, gid, token, index)
+        }
+    }
+}
+
+/// Parse callback data produced for a gallery card
+pub fn parse_eh_info_callback_data(data: &str) -> Option<(u64, String, EhInfoAction)> {
+    let mut parts = data.strip_prefix(EH_INFO_CALLBACK_PREFIX)?.splitn(3, ':');
+    let gid = parts.next()?.parse().ok()?;
+    let token = parts.next()?.to_string();
+    let action = match parts.next()? {
+        "d" => EhInfoAction::Download,
+        index => EhInfoAction::Subscribe(index.strip_prefix('s')?.parse().ok()?),
+    };
+    Some((gid, token, action))
+}
+
+/// Tags grouped by namespace in first-seen order, tags without a namespace go to `misc`
+fn group_tags(tags: &[String]) -> Vec<(&str, Vec<&str>)> {
+    let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+    for tag in tags {
+        let (namespace, name) = tag.split_once(':').unwrap_or(("misc", tag));
+        match groups.iter_mut().find(|(ns, _)| *ns == namespace) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((namespace, vec![name])),
+        }
+    }
+    groups
+}
+
+/// E-Hentai search query matching exactly one artist or group tag
+fn subscribe_query(tag: &str) -> Option<String> {
+    let (namespace, name) = tag.split_once(':')?;
+    if !SUBSCRIBE_NAMESPACES.contains(&namespace) || name.is_empty() {
+        return None;
+    }
+    if name.contains(' ') {
+        Some(format!("{}:\"{}$\"", namespace, name))
+    } else {
+        Some(format!("{}:{}$", namespace, name))
+    }
+}
+
+/// Gallery card text (MarkdownV2), listing at most `max_tags` tags per namespace
+fn format_gallery_card(base_url: &str, gallery: &EhGallery, max_tags: Option<usize>) -> String {
+    let gallery_url = format!(
+        "{}/g/{}/{}/",
+        base_url.trim_end_matches('/'),
+        gallery.gid,
+        gallery.token
+    );
+
+    let mut card = format!("📖 *{}*\n", markdown::escape(&gallery.title));
+    if let Some(title_jpn) = gallery
+        .title_jpn
+        .as_deref()
+        .filter(|title| !title.is_empty() && *title != gallery.title)
+    {
+        card.push_str(&format!("{}\n", markdown::escape(title_jpn)));
+    }
+    card.push_str(&format!(
+        "\n📂 {} \\| 📄 {} 页 \\| ⭐ {}\n",
+        markdown::escape(&gallery.category),
+        gallery.filecount,
+        markdown::escape(&format!("{:.2}", gallery.rating))
+    ));
+    let posted = DateTime::from_timestamp(gallery.posted, 0)
+        .map(|time| {
+            to_local(time.naive_utc())
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "未知".to_string());
+    card.push_str(&format!(
+        "👤 {} \\| 🗓 {} \\| 💾 {}\n",
+        markdown::escape(&gallery.uploader),
+        markdown::escape(&posted),
+        markdown::escape(&format!(
+            "{:.1} MB",
+            gallery.filesize as f64 / (1024.0 * 1024.0)
+        ))
+    ));
+    if gallery.expunged {
+        card.push_str("⚠️ 画廊已被删除\n");
+    }
+
+    let groups = group_tags(&gallery.tags);
+    if !groups.is_empty() {
+        card.push('\n');
+    }
+    for (namespace, names) in groups {
+        let limit = max_tags.unwrap_or(names.len());
+        let mut listed: Vec<String> = names
+            .iter()
+            .take(limit)
+            .map(|name| markdown::escape(name))
+            .collect();
+        if names.len() > limit {
+            listed.push(markdown::escape(&format!("…+{}", names.len() - limit)));
+        }
+        card.push_str(&format!(
+            "🏷 *{}*: {}\n",
+            markdown::escape(namespace),
+            listed.join(", ")
+        ));
+    }
+
+    card.push_str(&format!(
+        "\n🔗 [来源]({})",
+        markdown::escape_link_url(&gallery_url)
+    ));
+    card
+}
+
+/// Gallery card that fits a photo caption, listing fewer tags per namespace if needed
+fn build_gallery_card(base_url: &str, gallery: &EhGallery) -> String {
+    [Some(8), Some(3), Some(0)].into_iter().fold(
+        format_gallery_card(base_url, gallery, None),
+        |card, max_tags| {
+            if card.encode_utf16().count() <= CAPTION_MAX_UTF16_UNITS {
+                card
+            } else {
+                format_gallery_card(base_url, gallery, max_tags)
+            }
+        },
+    )
+}
+
+fn gallery_card_keyboard(gallery: &EhGallery) -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = gallery
+        .tags
+        .iter()
+        .enumerate()
+        .filter(|(_, tag)| subscribe_query(tag).is_some())
+        .take(MAX_SUBSCRIBE_BUTTONS)
+        .map(|(index, tag)| {
+            vec![InlineKeyboardButton::callback(
+                format!("🔔 订阅 {}", tag),
+                eh_info_callback_data(gallery.gid, &gallery.token, EhInfoAction::Subscribe(index)),
+            )]
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        "⬇️ 下载",
+        eh_info_callback_data(gallery.gid, &gallery.token, EhInfoAction::Download),
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+impl BotHandler {
+    /// 查看 E-Hentai 画廊信息卡片，带订阅作者/社团和下载按钮
+    pub async fn handle_ehinfo(
+        &self,
+        bot: ThrottledBot,
+        msg: Message,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(eh_client) = self.eh_client.clone() else {
+            let _ = bot.send_message(chat_id, "E-Hentai 功能未启用").await;
+            return Ok(());
+        };
+
+        let args = args_str.trim();
+        let input = if args.is_empty() {
+            msg.reply_to_message()
+                .and_then(|reply| reply.text())
+                .and_then(extract_gallery_url_from_text)
+        } else {
+            Some(args.to_string())
+        };
+        let Some(input) = input else {
+            let _ = bot
+                .send_message(
+                    chat_id,
+                    "用法: /ehinfo <画廊URL或GID>\n\n也可回复包含画廊链接的消息使用 /ehinfo",
+                )
+                .await;
+            return Ok(());
+        };
+        let (gid, token) = match resolve_gallery_ref(&eh_client, &input).await {
+            Ok(gallery) => gallery,
+            Err(message) => {
+                let _ = bot.send_message(chat_id, message).await;
+                return Ok(());
+            }
+        };
+
+        let gallery = match eh_client.get_metadata(&[(gid, &token)]).await {
+            Ok(galleries) => match galleries.into_iter().next() {
+                Some(gallery) => gallery,
+                None => {
+                    let _ = bot.send_message(chat_id, "❌ 未找到画廊").await;
+                    return Ok(());
+                }
+            },
+            Err(e) => {
+                warn!("Failed to fetch eh metadata for {}: {:#}", gid, e);
+                let _ = bot.send_message(chat_id, "❌ 获取画廊信息失败").await;
+                return Ok(());
+            }
+        };
+
+        let card = build_gallery_card(eh_client.base_url(), &gallery);
+        let keyboard = gallery_card_keyboard(&gallery);
+        let spoiler = match self.repo.get_chat(chat_id.0).await {
+            Ok(chat) => chat.is_some_and(|chat| chat.blur_sensitive_tags),
+            Err(e) => {
+                warn!("Failed to get chat {} for eh info: {:#}", chat_id, e);
+                true
+            }
+        };
+
+        // The cover goes through the downloader, Telegram cannot always reach the EH CDN
+        match self
+            .notifier
+            .get_downloader()
+            .download(&gallery.thumb)
+            .await
+        {
+            Ok(path) => {
+                let sent = bot
+                    .send_photo(chat_id, InputFile::file(path))
+                    .caption(card.clone())
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .has_spoiler(spoiler)
+                    .reply_markup(keyboard.clone())
+                    .await;
+                match sent {
+                    Ok(_) => return Ok(()),
+                    Err(e) => warn!("Failed to send eh gallery {} cover: {:#}", gid, e),
+                }
+            }
+            Err(e) => warn!("Failed to download eh gallery {} cover: {:#}", gid, e),
+        }
+
+        bot.send_message(chat_id, card)
+            .parse_mode(ParseMode::MarkdownV2)
+            .reply_markup(keyboard)
+            .await?;
+        Ok(())
+    }
+
+    /// 处理画廊卡片按钮，等同于执行 `/esub <作者/社团>` 或 `/edl <画廊URL>`
+    pub async fn handle_eh_info_callback(
+        &self,
+        bot: ThrottledBot,
+        msg: Message,
+        user_id: UserId,
+        gid: u64,
+        token: String,
+        action: EhInfoAction,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id;
+        let Some(eh_client) = self.eh_client.clone() else {
+            let _ = bot.send_message(chat_id, "E-Hentai 功能未启用").await;
+            return Ok(());
+        };
+
+        match action {
+            EhInfoAction::Download => {
+                let gallery_url = format!(
+                    "{}/g/{}/{}/",
+                    eh_client.base_url().trim_end_matches('/'),
+                    gid,
+                    token
+                );
+                self.handle_edl(bot, msg, chat_id, Some(user_id), gallery_url)
+                    .await
+            }
+            EhInfoAction::Subscribe(index) => {
+                // Tags are looked up again so the callback data stays short
+                let query = match eh_client.get_metadata(&[(gid, &token)]).await {
+                    Ok(galleries) => galleries
+                        .into_iter()
+                        .next()
+                        .and_then(|gallery| gallery.tags.get(index).cloned())
+                        .and_then(|tag| subscribe_query(&tag)),
+                    Err(e) => {
+                        warn!("Failed to fetch eh metadata for {}: {:#}", gid, e);
+                        None
+                    }
+                };
+                let Some(query) = query else {
+                    let _ = bot.send_message(chat_id, "❌ 获取画廊标签失败").await;
+                    return Ok(());
+                };
+                info!(
+                    "User {} subscribing chat {} to {} from gallery {}",
+                    user_id, chat_id, query, gid
+                );
+                self.handle_esub(bot, chat_id, Some(user_id), query).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gallery(tags: &[&str]) -> EhGallery {
+        EhGallery {
+            gid: 2345678,
+            token: "0123abcdef".to_string(),
+            title: "[Circle (Artist)] Title".to_string(),
+            title_jpn: Some("タイトル".to_string()),
+            category: "Doujinshi".to_string(),
+            thumb: "https://ehgt.org/t/cover.jpg".to_string(),
+            uploader: "uploader".to_string(),
+            posted: 1_700_000_000,
+            filecount: 24,
+            filesize: 52_428_800,
+            expunged: false,
+            rating: 4.5,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn eh_info_callback_data_round_trips() {
+        for action in [EhInfoAction::Download, EhInfoAction::Subscribe(12)] {
+            let data = eh_info_callback_data(2345678, "0123abcdef", action);
+            assert!(data.len() <= 64, "callback data too long: {}", data);
+            assert_eq!(
+                parse_eh_info_callback_data(&data),
+                Some((2345678, "0123abcdef".to_string(), action))
+            );
+        }
+        assert_eq!(parse_eh_info_callback_data("ehinfo:1:token:x"), None);
+        assert_eq!(parse_eh_info_callback_data("dl:1"), None);
+    }
+
+    #[test]
+    fn tags_are_grouped_and_artists_subscribable() {
+        let tags = [
+            "artist:foo bar",
+            "female:glasses",
+            "group:circle",
+            "female:twintails",
+            "full color",
+        ]
+        .map(String::from);
+        assert_eq!(
+            group_tags(&tags),
+            vec![
+                ("artist", vec!["foo bar"]),
+                ("female", vec!["glasses", "twintails"]),
+                ("group", vec!["circle"]),
+                ("misc", vec!["full color"]),
+            ]
+        );
+
+        assert_eq!(
+            subscribe_query("artist:foo bar").as_deref(),
+            Some("artist:\"foo bar$\"")
+        );
+        assert_eq!(
+            subscribe_query("group:circle").as_deref(),
+            Some("group:circle$")
+        );
+        assert_eq!(subscribe_query("female:glasses"), None);
+        assert_eq!(subscribe_query("full color"), None);
+    }
+
+    #[test]
+    fn gallery_card_fits_caption_limit() {
+        let short = gallery(&["artist:foo", "female:glasses"]);
+        let card = build_gallery_card("https://e-hentai.org", &short);
+        assert!(card.contains("*artist*: foo"));
+        assert!(card.contains("タイトル"));
+        assert!(card.contains("(https://e-hentai.org/g/2345678/0123abcdef/)"));
+
+        let many: Vec<String> = (0..200)
+            .map(|i| format!("female:tag number {}", i))
+            .collect();
+        let long = gallery(&many.iter().map(String::as_str).collect::<Vec<_>>());
+        let card = build_gallery_card("https://e-hentai.org", &long);
+        assert!(card.encode_utf16().count() <= CAPTION_MAX_UTF16_UNITS);
+        assert!(card.contains("…\\+"));
+    }
+}
@@ -0,0 +1,338 @@
+use super::helpers::ranking_date_label;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::entities::{subscriptions, tasks};
+use crate::db::types::{AiFilter, PushImageSize, PushMode, SpoilerMode, TaskType};
+use crate::pixiv::model::RankingMode;
+use crate::utils::args;
+use crate::utils::time::to_local;
+use chrono::{Local, NaiveDateTime};
+use std::collections::HashMap;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, InputFile, UserId};
+use tracing::{error, warn};
+
+const CATALOG_STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', 'PingFang SC', 'Microsoft YaHei', sans-serif;
+       max-width: 960px; margin: 2em auto; padding: 0 1em; color: #222; background: #fafafa; }
+h1 { font-size: 1.6em; margin-bottom: 0.2em; }
+.meta { color: #777; margin-top: 0; }
+table { width: 100%; border-collapse: collapse; background: #fff; box-shadow: 0 1px 3px rgba(0,0,0,.1); }
+th, td { padding: 0.6em 0.8em; text-align: left; border-bottom: 1px solid #eee; vertical-align: top; }
+th { background: #0096fa; color: #fff; font-weight: 600; }
+tr:hover td { background: #f3f9ff; }
+a { color: #0096fa; text-decoration: none; }
+a:hover { text-decoration: underline; }
+.id, .empty { color: #999; font-size: 0.85em; }
+.filter { font-family: ui-monospace, Menlo, Consolas, monospace; font-size: 0.9em; }
+";
+
+impl BotHandler {
+    /// 将当前聊天的订阅导出为可分享的 HTML 文件
+    pub async fn handle_export_html(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let (target_chat_id, _) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let subscriptions = match self.repo.list_subscriptions_by_chat(target_chat_id.0).await {
+            Ok(subs) => subs,
+            Err(e) => {
+                error!("Failed to list subscriptions for HTML export: {:#}", e);
+                bot.send_message(chat_id, "❌ 获取订阅列表失败").await?;
+                return Ok(());
+            }
+        };
+
+        if subscriptions.is_empty() {
+            bot.send_message(chat_id, "📭 没有生效的订阅").await?;
+            return Ok(());
+        }
+
+        if let Err(e) = bot
+            .send_chat_action(chat_id, ChatAction::UploadDocument)
+            .await
+        {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let last_push_times = match self
+            .repo
+            .get_last_push_times_by_chat(target_chat_id.0)
+            .await
+        {
+            Ok(times) => times,
+            Err(e) => {
+                error!("Failed to query last push times: {:#}", e);
+                HashMap::new()
+            }
+        };
+
+        let chat_label = match self.repo.get_chat(target_chat_id.0).await {
+            Ok(Some(chat)) => chat.title.unwrap_or_else(|| target_chat_id.0.to_string()),
+            Ok(None) => target_chat_id.0.to_string(),
+            Err(e) => {
+                warn!(
+                    "Failed to get chat {} for HTML export: {:#}",
+                    target_chat_id, e
+                );
+                target_chat_id.0.to_string()
+            }
+        };
+
+        let html = render_catalog_html(
+            &chat_label,
+            &subscriptions,
+            &last_push_times,
+            Local::now().naive_local(),
+        );
+
+        bot.send_document(
+            chat_id,
+            InputFile::memory(html.into_bytes())
+                .file_name(format!("subscriptions_{}.html", target_chat_id.0)),
+        )
+        .caption(format!("📄 订阅目录（共 {} 条）", subscriptions.len()))
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Standalone HTML page listing a chat's subscriptions
+fn render_catalog_html(
+    chat_label: &str,
+    subscriptions: &[(subscriptions::Model, tasks::Model)],
+    last_push_times: &HashMap<i32, NaiveDateTime>,
+    generated_at: NaiveDateTime,
+) -> String {
+    let title = format!("{} 的订阅目录", chat_label);
+
+    let mut rows = String::new();
+    for (sub, task) in subscriptions {
+        let last_push = match last_push_times.get(&sub.id) {
+            Some(time) => to_local(*time).format("%Y-%m-%d %H:%M").to_string(),
+            None => "<span class=\"empty\">暂无</span>".to_string(),
+        };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            task_type_label(task.r#type),
+            task_cell(task),
+            filter_cell(sub),
+            last_push
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"zh-CN\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<style>{style}</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class=\"meta\">共 {count} 条订阅 · 生成于 {generated}</p>
+<table>
+<thead><tr><th>类型</th><th>订阅</th><th>过滤</th><th>最近推送</th></tr></thead>
+<tbody>
+{rows}</tbody>
+</table>
+</body>
+</html>
+",
+        title = escape_html(&title),
+        style = CATALOG_STYLE,
+        count = subscriptions.len(),
+        generated = generated_at.format("%Y-%m-%d %H:%M"),
+        rows = rows,
+    )
+}
+
+fn task_type_label(task_type: TaskType) -> &'static str {
+    match task_type {
+        TaskType::Author => "🎨 Pixiv 作者",
+        TaskType::Ranking => "📊 Pixiv 排行榜",
+        TaskType::BooruTag => "🏷 Booru 标签",
+        TaskType::BooruPool => "📦 Booru 图集",
+        TaskType::BooruRanking => "🏆 Booru 排行榜",
+        TaskType::Ehentai => "📖 E-Hentai",
+        TaskType::Bookmarks => "🔖 Pixiv 收藏",
+    }
+}
+
+fn task_cell(task: &tasks::Model) -> String {
+    match task.r#type {
+        TaskType::Author => {
+            let name = task.author_name.as_deref().unwrap_or(&task.value);
+            format!(
+                "<a href=\"https://www.pixiv.net/users/{id}\">{name}</a> <span class=\"id\">ID {id}</span>",
+                id = escape_html(&task.value),
+                name = escape_html(name)
+            )
+        }
+        TaskType::Ranking => match RankingMode::from_str(&task.value) {
+            Some(mode) => format!(
+                "{} <span class=\"id\">{}</span>",
+                mode.display_name(),
+                mode.as_str()
+            ),
+            None => escape_html(&task.value),
+        },
+        _ => match task.author_name.as_deref() {
+            Some(name) => format!(
+                "{} <span class=\"id\">{}</span>",
+                escape_html(name),
+                escape_html(&task.value)
+            ),
+            None => escape_html(&task.value),
+        },
+    }
+}
+
+fn filter_cell(sub: &subscriptions::Model) -> String {
+    let mut parts = Vec::new();
+    if !sub.filter_tags.is_empty() {
+        parts.push(sub.filter_tags.format_plain());
+    }
+    if let Some(filter) = sub.booru_filter.as_ref().filter(|f| !f.is_empty()) {
+        parts.push(filter.format_for_display());
+    }
+    if let Some(filter) = sub.eh_filter.as_ref().filter(|f| !f.is_empty()) {
+        parts.push(filter.format_for_display());
+    }
+    if sub.spoiler_mode != SpoilerMode::Auto {
+        parts.push(format!("遮罩: {}", sub.spoiler_mode.display_name()));
+    }
+    if sub.push_mode != PushMode::Single {
+        parts.push(format!("推送: {}", sub.push_mode.display_name()));
+    }
+    if sub.ranking_date_offset > 0 {
+        parts.push(format!(
+            "排行日期: {}",
+            ranking_date_label(sub.ranking_date_offset)
+        ));
+    }
+    if sub.silent {
+        parts.push("静默推送".to_string());
+    }
+    if sub.ai_filter != AiFilter::Auto {
+        parts.push(format!("AI 作品: {}", sub.ai_filter.display_name()));
+    }
+    if sub.image_size != PushImageSize::Auto {
+        parts.push(format!("图片尺寸: {}", sub.image_size.display_name()));
+    }
+    if let Some(thread_id) = sub.message_thread_id {
+        parts.push(format!("话题: {}", thread_id));
+    }
+
+    if parts.is_empty() {
+        return "<span class=\"empty\">无</span>".to_string();
+    }
+    parts
+        .iter()
+        .map(|part| format!("<div class=\"filter\">{}</div>", escape_html(part)))
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::types::TagFilter;
+
+    #[test]
+    fn render_catalog_html_links_authors_and_escapes_text() {
+        let at = NaiveDateTime::parse_from_str("2026-10-14 20:30", "%Y-%m-%d %H:%M").unwrap();
+        let sub = |id: i32, filter_tags: TagFilter| subscriptions::Model {
+            id,
+            chat_id: -100,
+            task_id: id,
+            filter_tags,
+            booru_filter: None,
+            eh_filter: None,
+            latest_data: None,
+            created_at: at,
+            version: 0,
+            created_by: None,
+            spoiler_mode: SpoilerMode::Always,
+            enabled: true,
+            push_mode: Default::default(),
+            silent: false,
+            ranking_date_offset: 0,
+            message_thread_id: None,
+            ai_filter: Default::default(),
+            image_size: Default::default(),
+        };
+        let task =
+            |id: i32, r#type: TaskType, value: &str, author_name: Option<&str>| tasks::Model {
+                id,
+                r#type,
+                value: value.to_string(),
+                next_poll_at: at,
+                last_polled_at: None,
+                author_name: author_name.map(str::to_string),
+                poll_interval_min: None,
+                last_success_at: None,
+                last_error: None,
+                last_error_at: None,
+                failure_count: 0,
+                dead_at: None,
+            };
+        let subscriptions = vec![
+            (
+                sub(1, TagFilter::parse_from_args(&["+<b>", "-R-18"])),
+                task(1, TaskType::Author, "123", Some("A & B")),
+            ),
+            (
+                sub(2, TagFilter::default()),
+                task(2, TaskType::Ranking, "day", None),
+            ),
+        ];
+        let last_push_times = HashMap::from([(1, at)]);
+
+        let html = render_catalog_html("Art <club>", &subscriptions, &last_push_times, at);
+
+        assert!(html.contains("<title>Art &lt;club&gt; 的订阅目录</title>"));
+        assert!(html.contains("<a href=\"https://www.pixiv.net/users/123\">A &amp; B</a>"));
+        assert!(html.contains("+&lt;b&gt; -R-18"));
+        assert!(html.contains("遮罩: 总是遮罩"));
+        assert!(html.contains("日榜"));
+        assert!(html.contains("2026-10-14 20:30"));
+        assert!(html.contains("暂无"));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::bot::link_handler::{parse_pixiv_links, PixivLink};
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::pixiv::model::RankingMode;
+use crate::utils::args;
+use teloxide::prelude::*;
+use teloxide::types::{
+    ChatId, InlineKeyboardButton, InlineKeyboardMarkup, MessageEntityKind, ParseMode, UserId,
+};
+use teloxide::utils::markdown;
+use tracing::info;
+
+/// Callback data prefix for subscribing a forwarded channel.
+/// Format: `fwdsub:<channel_id>:a:<author_id>` or `fwdsub:<channel_id>:r:<ranking_mode>`.
+pub const FORWARD_SUB_CALLBACK_PREFIX: &str = "fwdsub:";
+
+/// 转发消息中最多提供订阅按钮的作者数
+const MAX_AUTHOR_BUTTONS: usize = 3;
+
+/// What a forwarded channel gets subscribed to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwardTarget {
+    Author(u64),
+    Ranking(RankingMode),
+}
+
+fn forward_sub_callback_data(channel_id: ChatId, target: &ForwardTarget) -> String {
+    match target {
+        ForwardTarget::Author(author_id) => format!(
+            "{}{}:a:{}",
+            FORWARD_SUB_CALLBACK_PREFIX, channel_id.0, author_id
+        ),
+        ForwardTarget::Ranking(mode) => format!(
+            "{}{}:r:{}",
+            FORWARD_SUB_CALLBACK_PREFIX,
+            channel_id.0,
+            mode.as_str()
+        ),
+    }
+}
+
+/// Parse callback data produced for a forwarded channel message
+pub fn parse_forward_sub_callback_data(data: &str) -> Option<(ChatId, ForwardTarget)> {
+    let mut parts = data
+        .strip_prefix(FORWARD_SUB_CALLBACK_PREFIX)?
+        .splitn(3, ':');
+    let channel_id = parts.next()?.parse().ok().map(ChatId)?;
+    let target = match (parts.next()?, parts.next()?) {
+        ("a", author_id) => ForwardTarget::Author(author_id.parse().ok()?),
+        ("r", mode) => ForwardTarget::Ranking(RankingMode::from_str(mode)?),
+        _ => return None,
+    };
+    Some((channel_id, target))
+}
+
+/// Pixiv authors linked from the message text, caption or text links, in order
+fn linked_author_ids(msg: &Message) -> Vec<u64> {
+    let mut text = msg
+        .text()
+        .or_else(|| msg.caption())
+        .unwrap_or_default()
+        .to_string();
+    let entities = msg
+        .parse_entities()
+        .or_else(|| msg.parse_caption_entities())
+        .unwrap_or_default();
+    for entity in entities {
+        if let MessageEntityKind::TextLink { url } = entity.kind() {
+            text.push('\n');
+            text.push_str(url.as_str());
+        }
+    }
+
+    let mut author_ids = Vec::new();
+    for link in parse_pixiv_links(&text) {
+        if let PixivLink::User(author_id) = link {
+            if !author_ids.contains(&author_id) {
+                author_ids.push(author_id);
+            }
+        }
+    }
+    author_ids
+}
+
+impl BotHandler {
+    /// 私聊中转发频道消息时，提供为该频道订阅消息中的作者或排行榜的按钮
+    ///
+    /// 权限检查与 `/sub ch=<频道ID>` 相同，点击按钮时会再次检查。
+    pub async fn handle_forwarded_channel_message(
+        &self,
+        bot: ThrottledBot,
+        msg: Message,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id;
+        let Some(channel) = msg.forward_from_chat() else {
+            return Ok(());
+        };
+        let user_id = msg.from.as_ref().map(|u| u.id);
+
+        let parsed = args::parse_args(&format!("ch={}", channel.id.0));
+        let channel_id = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok((channel_id, _)) => channel_id,
+            Err(e) => {
+                bot.send_message(chat_id, format!("❌ {}", e)).await?;
+                return Ok(());
+            }
+        };
+
+        info!(
+            "Offering subscriptions for forwarded channel {} to user {:?}",
+            channel_id, user_id
+        );
+
+        let mut rows: Vec<Vec<InlineKeyboardButton>> = linked_author_ids(&msg)
+            .into_iter()
+            .take(MAX_AUTHOR_BUTTONS)
+            .map(|author_id| {
+                vec![InlineKeyboardButton::callback(
+                    format!("🎨 订阅作者 {}", author_id),
+                    forward_sub_callback_data(channel_id, &ForwardTarget::Author(author_id)),
+                )]
+            })
+            .collect();
+        rows.push(
+            [RankingMode::Day, RankingMode::Week, RankingMode::Month]
+                .into_iter()
+                .map(|mode| {
+                    InlineKeyboardButton::callback(
+                        format!("📊 {}", mode.display_name()),
+                        forward_sub_callback_data(channel_id, &ForwardTarget::Ranking(mode)),
+                    )
+                })
+                .collect(),
+        );
+
+        let title = channel.title().unwrap_or("频道");
+        let text = format!(
+            "📢 *{}* \\(ID: `{}`\\)\n\n选择要为该频道添加的订阅，订阅其他作者请使用 `/sub ch={} <作者ID>`",
+            markdown::escape(title),
+            channel_id.0,
+            channel_id.0
+        );
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::MarkdownV2)
+            .reply_markup(InlineKeyboardMarkup::new(rows))
+            .await?;
+
+        Ok(())
+    }
+
+    /// 处理转发频道消息的订阅按钮，等同于执行 `/sub` 或 `/subrank` 并带上 `ch=<频道ID>`
+    pub async fn handle_forward_sub_callback(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: UserId,
+        channel_id: ChatId,
+        target: ForwardTarget,
+    ) -> ResponseResult<()> {
+        match target {
+            ForwardTarget::Author(author_id) => {
+                self.handle_sub_author(
+                    bot,
+                    chat_id,
+                    Some(user_id),
+                    format!("ch={} {}", channel_id.0, author_id),
+                    false,
+                )
+                .await
+            }
+            ForwardTarget::Ranking(mode) => {
+                self.handle_sub_ranking(
+                    bot,
+                    chat_id,
+                    Some(user_id),
+                    format!("ch={} {}", channel_id.0, mode.as_str()),
+                )
+                .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forward_sub_callback_data_round_trips() {
+        let channel_id = ChatId(-1001234567890);
+        for target in [
+            ForwardTarget::Author(123456),
+            ForwardTarget::Ranking(RankingMode::Week),
+        ] {
+            let data = forward_sub_callback_data(channel_id, &target);
+            assert!(data.len() <= 64, "callback data too long: {}", data);
+            assert_eq!(
+                parse_forward_sub_callback_data(&data),
+                Some((channel_id, target))
+            );
+        }
+
+        assert_eq!(parse_forward_sub_callback_data("fwdsub:-100:x:1"), None);
+        assert_eq!(parse_forward_sub_callback_data("fwdsub:-100:a:abc"), None);
+        assert_eq!(parse_forward_sub_callback_data("rkpush:1"), None);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::entities::{push_queue, tasks};
+use crate::db::types::TaskType;
+use crate::pixiv::client::is_not_found;
+use crate::pixiv::model::RankingMode;
+use crate::utils::args;
+use crate::utils::time::to_local;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode, UserId};
+use teloxide::utils::markdown;
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+
+/// How long an author existence check is reused before asking Pixiv again
+const AUTHOR_STATUS_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Telegram message limit in UTF-16 code units
+const TELEGRAM_MAX_MESSAGE_UTF16_UNITS: usize = 4096;
+
+/// Consecutive failures before a task shows up in `/health failing`
+const FAILING_TASK_MIN_FAILURES: i32 = 3;
+
+/// Characters of the stored error shown per task
+const ERROR_PREVIEW_CHARS: usize = 200;
+
+/// Result of checking whether a Pixiv author still exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorStatus {
+    Exists,
+    NotFound,
+    Unknown,
+}
+
+/// In-memory cache of author existence checks shared by `/health` invocations
+#[derive(Clone, Default)]
+pub struct AuthorStatusCache {
+    entries: Arc<Mutex<HashMap<u64, (AuthorStatus, Instant)>>>,
+}
+
+impl AuthorStatusCache {
+    async fn get(&self, author_id: u64) -> Option<AuthorStatus> {
+        let entries = self.entries.lock().await;
+        entries
+            .get(&author_id)
+            .filter(|(_, checked_at)| checked_at.elapsed() < AUTHOR_STATUS_TTL)
+            .map(|(status, _)| *status)
+    }
+
+    async fn insert(&self, author_id: u64, status: AuthorStatus) {
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, (_, checked_at)| checked_at.elapsed() < AUTHOR_STATUS_TTL);
+        entries.insert(author_id, (status, Instant::now()));
+    }
+}
+
+impl BotHandler {
+    /// 订阅健康报告：汇总当前聊天每个订阅的推送、重试和轮询状态
+    pub async fn handle_health(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let subscriptions = match self.repo.list_subscriptions_by_chat(target_chat_id.0).await {
+            Ok(subs) => subs,
+            Err(e) => {
+                error!("Failed to list subscriptions for health report: {:#}", e);
+                bot.send_message(chat_id, "❌ 获取订阅列表失败").await?;
+                return Ok(());
+            }
+        };
+
+        if subscriptions.is_empty() {
+            bot.send_message(chat_id, "📭 没有生效的订阅").await?;
+            return Ok(());
+        }
+
+        let last_push_times = match self
+            .repo
+            .get_last_push_times_by_chat(target_chat_id.0)
+            .await
+        {
+            Ok(times) => times,
+            Err(e) => {
+                error!("Failed to query last push times: {:#}", e);
+                HashMap::new()
+            }
+        };
+
+        let mut retries: HashMap<i32, Vec<push_queue::Model>> = HashMap::new();
+        match self
+            .repo
+            .list_pending_push_retries_by_chat(target_chat_id.0)
+            .await
+        {
+            Ok(entries) => {
+                for entry in entries {
+                    retries
+                        .entry(entry.subscription_id)
+                        .or_default()
+                        .push(entry);
+                }
+            }
+            Err(e) => error!("Failed to query push retries: {:#}", e),
+        }
+
+        let mut entries = Vec::with_capacity(subscriptions.len());
+        for (sub, task) in &subscriptions {
+            let author_status = if task.r#type == TaskType::Author {
+                match task.value.parse::<u64>() {
+                    Ok(author_id) => Some(self.check_author_status(author_id).await),
+                    Err(_) => Some(AuthorStatus::Unknown),
+                }
+            } else {
+                None
+            };
+
+            entries.push(format_health_entry(
+                task,
+                last_push_times.get(&sub.id).copied(),
+                retries.get(&sub.id).map(Vec::as_slice).unwrap_or_default(),
+                author_status,
+            ));
+        }
+
+        let header = if is_channel {
+            format!(
+                "🩺 *频道* `{}` *订阅健康报告* \\(共 {} 条\\)",
+                target_chat_id.0,
+                entries.len()
+            )
+        } else {
+            format!("🩺 *订阅健康报告* \\(共 {} 条\\)", entries.len())
+        };
+
+        for message in split_report(&header, &entries) {
+            bot.send_message(chat_id, message)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 持续失败任务报告 (仅 Owner)：列出连续失败的任务及最近的错误，便于清理失效订阅
+    pub async fn handle_health_failing(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+    ) -> ResponseResult<()> {
+        let tasks = match self
+            .repo
+            .list_failing_tasks(FAILING_TASK_MIN_FAILURES)
+            .await
+        {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                error!("Failed to list failing tasks: {:#}", e);
+                bot.send_message(chat_id, "❌ 获取任务状态失败").await?;
+                return Ok(());
+            }
+        };
+
+        if tasks.is_empty() {
+            bot.send_message(
+                chat_id,
+                format!("✅ 没有连续失败 {} 次以上的任务", FAILING_TASK_MIN_FAILURES),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let header = format!("🩺 *持续失败的任务* \\(共 {} 个\\)", tasks.len());
+        let entries: Vec<String> = tasks.iter().map(format_failing_task).collect();
+
+        for message in split_report(&header, &entries) {
+            bot.send_message(chat_id, message)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check whether an author still exists, reusing recent results
+    async fn check_author_status(&self, author_id: u64) -> AuthorStatus {
+        if let Some(status) = self.author_status_cache.get(author_id).await {
+            return status;
+        }
+
+        let result = {
+            let pixiv = self.pixiv_client.read().await;
+            pixiv.get_user_detail(author_id).await
+        };
+
+        let status = match result {
+            Ok(_) => AuthorStatus::Exists,
+            Err(e) if is_not_found(&e) => AuthorStatus::NotFound,
+            Err(e) => {
+                warn!("Failed to check author {} status: {:#}", author_id, e);
+                AuthorStatus::Unknown
+            }
+        };
+
+        // Transient failures are not cached so the next report retries them
+        if status != AuthorStatus::Unknown {
+            self.author_status_cache.insert(author_id, status).await;
+        }
+
+        status
+    }
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    markdown::escape(&to_local(time).format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn format_task_title(task: &tasks::Model) -> String {
+    match task.r#type {
+        TaskType::Author => match task.author_name.as_deref() {
+            Some(name) => format!("🎨 *{}* \\(ID: `{}`\\)", markdown::escape(name), task.value),
+            None => format!("🎨 作者 `{}`", task.value),
+        },
+        TaskType::Ranking => match RankingMode::from_str(&task.value) {
+            Some(mode) => format!("📊 {}", markdown::escape(mode.display_name())),
+            None => format!("📊 排行榜 `{}`", markdown::escape(&task.value)),
+        },
+        other => format!(
+            "📌 {} `{}`",
+            markdown::escape(&other.to_string()),
+            markdown::escape(&task.value)
+        ),
+    }
+}
+
+fn format_error_preview(task: &tasks::Model) -> Option<String> {
+    let error = task.last_error.as_deref()?;
+    let mut preview: String = error.chars().take(ERROR_PREVIEW_CHARS).collect();
+    if preview.len() < error.len() {
+        preview.push('…');
+    }
+    Some(markdown::code_inline(&preview))
+}
+
+fn format_health_entry(
+    task: &tasks::Model,
+    last_push: Option<NaiveDateTime>,
+    retries: &[push_queue::Model],
+    author_status: Option<AuthorStatus>,
+) -> String {
+    let mut lines = vec![format_task_title(task)];
+
+    match author_status {
+        Some(AuthorStatus::Exists) => lines.push("  ✅ 作者状态正常".to_string()),
+        Some(AuthorStatus::NotFound) => lines.push("  ❌ 作者不存在或已注销".to_string()),
+        Some(AuthorStatus::Unknown) => lines.push("  ❔ 作者状态未知".to_string()),
+        None => {}
+    }
+
+    lines.push(match last_push {
+        Some(time) => format!("  📤 上次推送: {}", format_time(time)),
+        None => "  📤 上次推送: 无记录".to_string(),
+    });
+
+    if let Some(time) = task.dead_at {
+        lines.push(format!(
+            "  💀 作者持续无法访问，已于 {} 停止检查",
+            format_time(time)
+        ));
+    } else if task.failure_count > 0 {
+        lines.push(format!("  ⚠️ 连续失败 {} 次", task.failure_count));
+        if let Some(error) = format_error_preview(task) {
+            lines.push(format!("  {}", error));
+        }
+    }
+
+    if task.dead_at.is_none() {
+        lines.push(format!("  ⏰ 下次轮询: {}", format_time(task.next_poll_at)));
+    }
+
+    for retry in retries {
+        lines.push(format!(
+            "  🔁 待重试: 作品 `{}` \\({}/{} 页已发送, 已重试 {} 次, 下次 {}\\)",
+            retry.illust_id,
+            retry.sent_pages.len(),
+            retry.total_pages,
+            retry.attempts,
+            format_time(retry.next_attempt_at)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn format_failing_task(task: &tasks::Model) -> String {
+    let mut lines = vec![
+        format!("{} \\[任务 {}\\]", format_task_title(task), task.id),
+        match task.last_error_at {
+            Some(time) => format!(
+                "  ❌ 连续失败 {} 次, 最近: {}",
+                task.failure_count,
+                format_time(time)
+            ),
+            None => format!("  ❌ 连续失败 {} 次", task.failure_count),
+        },
+        match task.last_success_at {
+            Some(time) => format!("  ✅ 上次成功: {}", format_time(time)),
+            None => "  ✅ 上次成功: 无记录".to_string(),
+        },
+    ];
+    if let Some(time) = task.dead_at {
+        lines.push(format!("  💀 已停止检查: {}", format_time(time)));
+    }
+    if let Some(error) = format_error_preview(task) {
+        lines.push(format!("  {}", error));
+    }
+    lines.join("\n")
+}
+
+/// Split the report into messages that fit Telegram's length limit
+fn split_report(header: &str, entries: &[String]) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = header.to_string();
+
+    for entry in entries {
+        let candidate_len = current.encode_utf16().count() + 2 + entry.encode_utf16().count();
+        if candidate_len > TELEGRAM_MAX_MESSAGE_UTF16_UNITS && !current.is_empty() {
+            messages.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(entry);
+    }
+
+    if !current.is_empty() {
+        messages.push(current);
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_task(task_type: TaskType, value: &str, author_name: Option<&str>) -> tasks::Model {
+        tasks::Model {
+            id: 1,
+            r#type: task_type,
+            value: value.to_string(),
+            next_poll_at: NaiveDateTime::parse_from_str("2026-01-02 03:04:00", "%Y-%m-%d %H:%M:%S")
+                .unwrap(),
+            last_polled_at: None,
+            author_name: author_name.map(str::to_string),
+            poll_interval_min: None,
+            last_success_at: None,
+            last_error: None,
+            last_error_at: None,
+            failure_count: 0,
+            dead_at: None,
+        }
+    }
+
+    #[test]
+    fn format_health_entry_reports_pending_and_author_status() {
+        let task = make_task(TaskType::Author, "123", Some("Alice"));
+        let retry = push_queue::Model {
+            id: 1,
+            subscription_id: 1,
+            chat_id: -100,
+            illust_id: 99,
+            sent_pages: vec![0, 1].into(),
+            total_pages: 4,
+            spoiler: false,
+            status: "pending".to_string(),
+            attempts: 2,
+            next_attempt_at: task.next_poll_at,
+            last_error: None,
+            created_at: task.next_poll_at,
+            updated_at: task.next_poll_at,
+        };
+
+        let entry = format_health_entry(&task, None, &[retry], Some(AuthorStatus::NotFound));
+
+        assert_eq!(
+            entry,
+            format!(
+                "🎨 *Alice* \\(ID: `123`\\)\n  ❌ 作者不存在或已注销\n  📤 上次推送: 无记录\n  ⏰ 下次轮询: {time}\n  🔁 待重试: 作品 `99` \\(2/4 页已发送, 已重试 2 次, 下次 {time}\\)",
+                time = format_time(task.next_poll_at)
+            )
+        );
+    }
+
+    #[test]
+    fn format_failing_task_shows_failures_and_last_error() {
+        let mut task = make_task(TaskType::Author, "123", None);
+        task.failure_count = 4;
+        task.last_error_at = Some(task.next_poll_at);
+        task.last_error = Some("Pixiv API error: 404 `user`".to_string());
+
+        assert_eq!(
+            format_failing_task(&task),
+            format!(
+                "🎨 作者 `123` \\[任务 1\\]\n  ❌ 连续失败 4 次, 最近: {}\n  ✅ 上次成功: 无记录\n  `Pixiv API error: 404 \\`user\\``",
+                format_time(task.next_poll_at)
+            )
+        );
+    }
+
+    #[test]
+    fn split_report_keeps_messages_within_limit() {
+        let entries: Vec<String> = (0..200)
+            .map(|i| format!("entry {i} {}", "x".repeat(60)))
+            .collect();
+
+        let messages = split_report("header", &entries);
+
+        assert!(messages.len() > 1);
+        assert!(messages[0].starts_with("header"));
+        assert!(messages
+            .iter()
+            .all(|m| m.encode_utf16().count() <= TELEGRAM_MAX_MESSAGE_UTF16_UNITS));
+        assert_eq!(
+            messages
+                .iter()
+                .map(|m| m.matches("entry ").count())
+                .sum::<usize>(),
+            200
+        );
+    }
+}
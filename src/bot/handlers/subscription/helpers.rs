@@ -1,7 +1,11 @@
 use crate::bot::BotHandler;
-use crate::db::types::{BooruFilter, EhFilter, TagFilter, TaskType};
+use crate::db::entities::{subscriptions, tasks};
+use crate::db::repo::SourceFilter;
+use crate::db::types::{AiFilter, BooruFilter, EhFilter, PushImageSize, TagFilter, TaskType};
 use anyhow::{Context, Result};
-use tracing::{error, info};
+use pixiv_client::UserProfile;
+use teloxide::types::UserId;
+use tracing::{error, info, warn};
 
 impl BotHandler {
     pub(crate) async fn create_subscription(
@@ -11,23 +15,24 @@ impl BotHandler {
         task_value: &str,
         author_name: Option<&str>,
         filter_tags: TagFilter,
-    ) -> Result<()> {
-        let task = self
+        created_by: Option<UserId>,
+    ) -> Result<(tasks::Model, subscriptions::Model)> {
+        let (task, subscription) = self
             .repo
-            .get_or_create_task(
+            .subscribe_tx(
+                chat_id,
                 task_type,
-                task_value.to_string(),
-                author_name.map(|s| s.to_string()),
+                task_value,
+                author_name,
+                filter_tags,
+                SourceFilter::Plain,
             )
             .await
-            .context("Failed to create task")?;
-
-        self.repo
-            .upsert_subscription(chat_id, task.id, filter_tags)
-            .await
-            .context("Failed to upsert subscription")?;
+            .context("Failed to create subscription")?;
+        self.record_subscription_creator(chat_id, task.id, created_by)
+            .await;
 
-        Ok(())
+        Ok((task, subscription))
     }
 
     pub(crate) async fn create_booru_subscription(
@@ -38,27 +43,28 @@ impl BotHandler {
         display_name: Option<&str>,
         filter_tags: TagFilter,
         booru_filter: BooruFilter,
+        created_by: Option<UserId>,
     ) -> Result<()> {
-        let task = self
-            .repo
-            .get_or_create_task(
-                task_type,
-                task_value.to_string(),
-                display_name.map(|s| s.to_string()),
-            )
-            .await
-            .context("Failed to create task")?;
-
         let booru_filter_opt = if booru_filter.is_empty() {
             None
         } else {
             Some(booru_filter)
         };
 
-        self.repo
-            .upsert_booru_subscription(chat_id, task.id, filter_tags, booru_filter_opt)
+        let (task, _) = self
+            .repo
+            .subscribe_tx(
+                chat_id,
+                task_type,
+                task_value,
+                display_name,
+                filter_tags,
+                SourceFilter::Booru(booru_filter_opt),
+            )
             .await
-            .context("Failed to upsert booru subscription")?;
+            .context("Failed to create booru subscription")?;
+        self.record_subscription_creator(chat_id, task.id, created_by)
+            .await;
 
         Ok(())
     }
@@ -71,31 +77,180 @@ impl BotHandler {
         display_name: Option<&str>,
         filter_tags: TagFilter,
         eh_filter: EhFilter,
+        created_by: Option<UserId>,
     ) -> Result<()> {
-        let task = self
-            .repo
-            .get_or_create_task(
-                task_type,
-                task_value.to_string(),
-                display_name.map(|s| s.to_string()),
-            )
-            .await
-            .context("Failed to create task")?;
-
         let eh_filter_opt = if eh_filter.is_empty() {
             None
         } else {
             Some(eh_filter)
         };
 
-        self.repo
-            .upsert_eh_subscription(chat_id, task.id, filter_tags, eh_filter_opt)
+        let (task, _) = self
+            .repo
+            .subscribe_tx(
+                chat_id,
+                task_type,
+                task_value,
+                display_name,
+                filter_tags,
+                SourceFilter::Eh(eh_filter_opt),
+            )
             .await
-            .context("Failed to upsert eh subscription")?;
+            .context("Failed to create eh subscription")?;
+        self.record_subscription_creator(chat_id, task.id, created_by)
+            .await;
 
         Ok(())
     }
 
+    /// Remember who added the subscription, for the group subscription digest
+    pub(crate) async fn record_subscription_creator(
+        &self,
+        chat_id: i64,
+        task_id: i32,
+        created_by: Option<UserId>,
+    ) {
+        let Some(user_id) = created_by else {
+            return;
+        };
+        if let Err(e) = self
+            .repo
+            .set_subscription_creator(chat_id, task_id, user_id.0 as i64)
+            .await
+        {
+            warn!(
+                "Failed to record creator of subscription (chat {}, task {}): {:#}",
+                chat_id, task_id, e
+            );
+        }
+    }
+
+    /// Store the `silent=` option of `/sub` and `/subrank`, keeping `subscription` in sync
+    pub(super) async fn apply_subscription_silent(
+        &self,
+        subscription: &mut subscriptions::Model,
+        silent: Option<bool>,
+    ) {
+        let Some(silent) = silent else {
+            return;
+        };
+        match self
+            .repo
+            .update_subscription_silent(subscription.id, silent)
+            .await
+        {
+            Ok(()) => subscription.silent = silent,
+            Err(e) => error!(
+                "Failed to set silent flag of subscription {}: {:#}",
+                subscription.id, e
+            ),
+        }
+    }
+
+    /// Merge the chat's `/defaultfilter` into the tags given to `/sub` or `/subrank`
+    pub(super) async fn with_chat_default_filter(
+        &self,
+        chat_id: i64,
+        filter_tags: TagFilter,
+    ) -> TagFilter {
+        match self.repo.get_chat(chat_id).await {
+            Ok(Some(chat)) => filter_tags.merged(&chat.default_filter),
+            Ok(None) => filter_tags,
+            Err(e) => {
+                warn!("Failed to get default filter of chat {}: {:#}", chat_id, e);
+                filter_tags
+            }
+        }
+    }
+
+    /// Store the `ai=` option of `/sub` and `/subrank`, keeping `subscription` in sync
+    pub(super) async fn apply_subscription_ai_filter(
+        &self,
+        subscription: &mut subscriptions::Model,
+        ai_filter: Option<AiFilter>,
+    ) {
+        let Some(ai_filter) = ai_filter else {
+            return;
+        };
+        match self
+            .repo
+            .update_subscription_ai_filter(subscription.id, ai_filter)
+            .await
+        {
+            Ok(()) => subscription.ai_filter = ai_filter,
+            Err(e) => error!(
+                "Failed to set AI filter of subscription {}: {:#}",
+                subscription.id, e
+            ),
+        }
+    }
+
+    /// Store the `size=` option of `/sub` and `/subrank`, keeping `subscription` in sync
+    pub(super) async fn apply_subscription_image_size(
+        &self,
+        subscription: &mut subscriptions::Model,
+        image_size: Option<PushImageSize>,
+    ) {
+        let Some(image_size) = image_size else {
+            return;
+        };
+        match self
+            .repo
+            .update_subscription_image_size(subscription.id, image_size)
+            .await
+        {
+            Ok(()) => subscription.image_size = image_size,
+            Err(e) => error!(
+                "Failed to set image size of subscription {}: {:#}",
+                subscription.id, e
+            ),
+        }
+    }
+
+    /// Store the `topic=` option of `/sub` and `/subrank`, keeping `subscription` in sync
+    pub(super) async fn apply_subscription_topic(
+        &self,
+        subscription: &mut subscriptions::Model,
+        topic: Option<Option<i32>>,
+    ) {
+        let Some(message_thread_id) = topic else {
+            return;
+        };
+        match self
+            .repo
+            .update_subscription_message_thread_id(subscription.id, message_thread_id)
+            .await
+        {
+            Ok(()) => subscription.message_thread_id = message_thread_id,
+            Err(e) => error!(
+                "Failed to set topic of subscription {}: {:#}",
+                subscription.id, e
+            ),
+        }
+    }
+
+    /// Store the `date=` option of `/subrank`, keeping `subscription` in sync
+    pub(super) async fn apply_ranking_date_offset(
+        &self,
+        subscription: &mut subscriptions::Model,
+        offset_days: Option<i32>,
+    ) {
+        let Some(offset_days) = offset_days else {
+            return;
+        };
+        match self
+            .repo
+            .update_subscription_ranking_date_offset(subscription.id, offset_days)
+            .await
+        {
+            Ok(()) => subscription.ranking_date_offset = offset_days,
+            Err(e) => error!(
+                "Failed to set ranking date offset of subscription {}: {:#}",
+                subscription.id, e
+            ),
+        }
+    }
+
     pub(crate) async fn delete_subscription(
         &self,
         chat_id: i64,
@@ -118,48 +273,131 @@ impl BotHandler {
             .context("Failed to query subscription")?
             .ok_or_else(|| anyhow::anyhow!("未订阅"))?;
 
-        if task_type == TaskType::Ehentai {
+        let task_deleted = if task_type == TaskType::Ehentai {
             self.repo
-                .delete_eh_subscription_and_cancel_queue(subscription.id)
+                .delete_eh_subscription_and_cancel_queue(subscription.id, task.id)
                 .await
-                .context("Failed to delete EH subscription and cancel queued downloads")?;
+                .context("Failed to delete EH subscription and cancel queued downloads")?
         } else {
             self.repo
-                .delete_subscription(subscription.id)
+                .unsubscribe_tx(subscription.id, task.id)
                 .await
-                .context("未订阅")?;
-        }
-
-        self.cleanup_orphaned_task(task.id, task_type, task_value)
-            .await;
+                .context("未订阅")?
+        };
+        log_task_cleanup(task.id, task_type, task_value, task_deleted);
 
         Ok(author_name)
     }
+}
 
-    pub(super) async fn cleanup_orphaned_task(
-        &self,
-        task_id: i32,
-        task_type: TaskType,
-        task_value: &str,
-    ) {
-        match self.repo.count_subscriptions_for_task(task_id).await {
-            Ok(0) => {
-                if let Err(e) = self.repo.delete_task(task_id).await {
-                    error!("Failed to delete task {}: {:#}", task_id, e);
-                } else {
-                    info!(
-                        "Deleted task {} ({} {}) - no more subscriptions",
-                        task_id, task_type, task_value
-                    );
-                }
-            }
-            Err(e) => {
-                error!(
-                    "Failed to count subscriptions for task {}: {:#}",
-                    task_id, e
-                );
-            }
-            _ => {}
-        }
+/// 记录取消订阅后被一并删除的任务
+pub(super) fn log_task_cleanup(
+    task_id: i32,
+    task_type: TaskType,
+    task_value: &str,
+    task_deleted: bool,
+) {
+    if task_deleted {
+        info!(
+            "Deleted task {} ({} {}) - no more subscriptions",
+            task_id, task_type, task_value
+        );
+    }
+}
+
+/// 解析 `silent=` 参数：`true`/`on` 静默推送，`false`/`off` 正常通知
+pub(super) fn parse_silent_arg(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" => Some(true),
+        "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 订阅确认消息中的静默推送说明
+pub(super) fn silent_summary(silent: bool) -> &'static str {
+    if silent {
+        "🔕 静默推送: 开启"
+    } else {
+        "🔔 静默推送: 关闭"
+    }
+}
+
+/// 解析 `topic=` 参数：话题 ID 推送到该话题，`off`/`0` 恢复为直接发到聊天
+pub(super) fn parse_topic_arg(value: &str) -> Option<Option<i32>> {
+    match value.to_lowercase().as_str() {
+        "off" | "none" | "0" => Some(None),
+        id => id.parse::<i32>().ok().filter(|id| *id > 0).map(Some),
+    }
+}
+
+/// 订阅确认消息中的 AI 作品过滤说明
+pub(super) fn ai_filter_summary(ai_filter: AiFilter) -> String {
+    format!("🤖 AI 作品: {}", ai_filter.display_name())
+}
+
+/// 订阅确认消息中的图片尺寸说明
+pub(super) fn image_size_summary(image_size: PushImageSize) -> String {
+    format!("📐 图片尺寸: {}", image_size.display_name())
+}
+
+/// 订阅确认消息中的推送话题说明
+pub(super) fn topic_summary(message_thread_id: Option<i32>) -> String {
+    match message_thread_id {
+        Some(id) => format!("🧵 推送话题: {}", id),
+        None => "🧵 推送话题: 默认".to_string(),
+    }
+}
+
+/// 排行榜订阅的日期说明，如 `前一天` 或 `3 天前`
+pub(super) fn ranking_date_label(offset_days: i32) -> String {
+    match offset_days {
+        0 => "最新".to_string(),
+        1 => "前一天".to_string(),
+        days => format!("{} 天前", days),
+    }
+}
+
+/// 作者卡片中的主页统计 (MarkdownV2)，`/sub` 确认消息与 `/authorstats` 共用
+pub(super) fn author_profile_summary(profile: &UserProfile) -> String {
+    format!(
+        "🎨 插画 `{}` · 漫画 `{}` · 关注 `{}` 人",
+        profile.total_illusts, profile.total_manga, profile.total_follow_users
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{author_profile_summary, parse_silent_arg, parse_topic_arg};
+
+    #[test]
+    fn parse_silent_arg_accepts_true_false_on_off() {
+        assert_eq!(parse_silent_arg("true"), Some(true));
+        assert_eq!(parse_silent_arg("ON"), Some(true));
+        assert_eq!(parse_silent_arg("off"), Some(false));
+        assert_eq!(parse_silent_arg("1"), None);
+    }
+
+    #[test]
+    fn parse_topic_arg_accepts_thread_ids_and_off() {
+        assert_eq!(parse_topic_arg("42"), Some(Some(42)));
+        assert_eq!(parse_topic_arg("off"), Some(None));
+        assert_eq!(parse_topic_arg("0"), Some(None));
+        assert_eq!(parse_topic_arg("-3"), None);
+        assert_eq!(parse_topic_arg("general"), None);
+    }
+
+    #[test]
+    fn author_profile_summary_reads_missing_stats_as_zero() {
+        let profile: pixiv_client::UserProfile = serde_json::from_value(serde_json::json!({
+            "background_image_url": null,
+            "total_illusts": 120,
+            "total_manga": 3
+        }))
+        .unwrap();
+        assert_eq!(
+            author_profile_summary(&profile),
+            "🎨 插画 `120` · 漫画 `3` · 关注 `0` 人"
+        );
     }
 }
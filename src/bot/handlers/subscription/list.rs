@@ -1,9 +1,14 @@
+use super::helpers::ranking_date_label;
 use super::{ListPaginationAction, PAGE_SIZE};
 use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
-use crate::db::types::{BooruRankingMode, BooruTaskKey, TaskType};
+use crate::db::entities::{subscriptions, tasks};
+use crate::db::types::{
+    AiFilter, BooruRankingMode, BooruTaskKey, PushImageSize, PushMode, SpoilerMode, TaskType,
+};
 use crate::pixiv::model::RankingMode;
 use crate::utils::args;
+use std::ops::Range;
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode, UserId};
 use teloxide::utils::markdown;
@@ -12,6 +17,10 @@ use tracing::error;
 /// Callback data prefix for list pagination
 pub const LIST_CALLBACK_PREFIX: &str = "list:";
 
+/// UTF-16 units available to the entries of one page, leaving room for the header
+/// and footer within Telegram's 4096 character limit
+const LIST_ENTRIES_BUDGET: usize = 3600;
+
 impl BotHandler {
     /// 列出当前聊天的所有订阅 (从命令调用，默认第一页)
     pub async fn handle_list(
@@ -82,12 +91,17 @@ impl BotHandler {
                 let all_subscriptions: Vec<_> = rankings.into_iter().chain(authors).collect();
 
                 let total = all_subscriptions.len();
-                let total_pages = total.div_ceil(PAGE_SIZE);
+                let entries: Vec<String> = all_subscriptions
+                    .iter()
+                    .map(|(sub, task)| format_subscription_entry(sub, task))
+                    .collect();
+                // Long names and filters can push a full page past Telegram's message limit
+                let pages = paginate_entries(&entries, PAGE_SIZE, LIST_ENTRIES_BUDGET);
+                let total_pages = pages.len();
                 let page = page.min(total_pages.saturating_sub(1));
 
-                let start = page * PAGE_SIZE;
-                let end = (start + PAGE_SIZE).min(total);
-                let page_subscriptions = &all_subscriptions[start..end];
+                let page_range = pages[page].clone();
+                let page_subscriptions = &all_subscriptions[page_range.clone()];
                 let page_has_booru_subscription = page_subscriptions.iter().any(|(_, task)| {
                     matches!(
                         task.r#type,
@@ -122,71 +136,8 @@ impl BotHandler {
                 };
                 let mut message = header;
 
-                for (sub, task) in page_subscriptions {
-                    let (type_emoji, display_info) = if matches!(
-                        task.r#type,
-                        TaskType::BooruTag | TaskType::BooruPool | TaskType::BooruRanking
-                    ) {
-                        booru_list_display(task.r#type, task.author_name.as_deref(), &task.value)
-                    } else {
-                        let type_emoji = match task.r#type {
-                            TaskType::Author => "🎨",
-                            TaskType::Ranking => "📊",
-                            TaskType::BooruTag | TaskType::BooruPool | TaskType::BooruRanking => {
-                                unreachable!("booru task types are handled above")
-                            }
-                            TaskType::Ehentai => "📖",
-                        };
-
-                        let display_info = if task.r#type == TaskType::Author {
-                            if let Some(ref name) = task.author_name {
-                                format!("{} \\| ID: `{}`", markdown::escape(name), task.value)
-                            } else {
-                                format!("ID: `{}`", task.value)
-                            }
-                        } else if task.r#type == TaskType::Ranking {
-                            match RankingMode::from_str(&task.value) {
-                                Some(mode) => {
-                                    format!(
-                                        "排行榜 \\({}\\) \\| MODE: `{}`",
-                                        mode.display_name(),
-                                        mode.as_str()
-                                    )
-                                }
-                                None => {
-                                    format!(
-                                        "排行榜 \\({}\\) \\| MODE: `{}`",
-                                        task.value.replace('_', "\\_"),
-                                        task.value
-                                    )
-                                }
-                            }
-                        } else {
-                            markdown::escape(&task.value)
-                        };
-                        (type_emoji, display_info)
-                    };
-
-                    let filter_info = if !sub.filter_tags.is_empty() {
-                        format!("\n  🏷 {}", sub.filter_tags.format_for_display())
-                    } else {
-                        String::new()
-                    };
-
-                    let booru_filter_info = if let Some(ref bf) = sub.booru_filter {
-                        if !bf.is_empty() {
-                            format!("\n  🔍 {}", markdown::escape(&bf.format_for_display()))
-                        } else {
-                            String::new()
-                        }
-                    } else {
-                        String::new()
-                    };
-
-                    message.push_str(&format!(
-                        "{} {}{}{}\n",
-                        type_emoji, display_info, filter_info, booru_filter_info
-                    ));
+                for entry in &entries[page_range] {
+                    message.push_str(entry);
                 }
 
                 if is_channel {
@@ -253,6 +204,157 @@ impl BotHandler {
     }
 }
 
+/// One `/list` line (plus its filter lines) in MarkdownV2
+fn format_subscription_entry(sub: &subscriptions::Model, task: &tasks::Model) -> String {
+    let (type_emoji, display_info) = if matches!(
+        task.r#type,
+        TaskType::BooruTag | TaskType::BooruPool | TaskType::BooruRanking
+    ) {
+        booru_list_display(task.r#type, task.author_name.as_deref(), &task.value)
+    } else {
+        let type_emoji = match task.r#type {
+            TaskType::Author => "🎨",
+            TaskType::Ranking => "📊",
+            TaskType::BooruTag | TaskType::BooruPool | TaskType::BooruRanking => {
+                unreachable!("booru task types are handled above")
+            }
+            TaskType::Ehentai => "📖",
+            TaskType::Bookmarks => "🔖",
+        };
+
+        let display_info = if task.r#type == TaskType::Author {
+            if let Some(ref name) = task.author_name {
+                format!("{} \\| ID: `{}`", markdown::escape(name), task.value)
+            } else {
+                format!("ID: `{}`", task.value)
+            }
+        } else if task.r#type == TaskType::Bookmarks {
+            if let Some(ref name) = task.author_name {
+                format!("{} 的收藏 \\| ID: `{}`", markdown::escape(name), task.value)
+            } else {
+                format!("收藏 \\| ID: `{}`", task.value)
+            }
+        } else if task.r#type == TaskType::Ranking {
+            match RankingMode::from_str(&task.value) {
+                Some(mode) => {
+                    format!(
+                        "排行榜 \\({}\\) \\| MODE: `{}`",
+                        mode.display_name(),
+                        mode.as_str()
+                    )
+                }
+                None => {
+                    format!(
+                        "排行榜 \\({}\\) \\| MODE: `{}`",
+                        task.value.replace('_', "\\_"),
+                        task.value
+                    )
+                }
+            }
+        } else {
+            markdown::escape(&task.value)
+        };
+        (type_emoji, display_info)
+    };
+
+    let filter_info = if !sub.filter_tags.is_empty() {
+        format!("\n  🏷 {}", sub.filter_tags.format_for_display())
+    } else {
+        String::new()
+    };
+
+    let booru_filter_info = if let Some(ref bf) = sub.booru_filter {
+        if !bf.is_empty() {
+            format!("\n  🔍 {}", markdown::escape(&bf.format_for_display()))
+        } else {
+            String::new()
+        }
+    } else {
+        String::new()
+    };
+
+    let spoiler_info = if sub.spoiler_mode != SpoilerMode::Auto {
+        format!("\n  🫣 {}", sub.spoiler_mode.display_name())
+    } else {
+        String::new()
+    };
+
+    let push_mode_info = if sub.push_mode != PushMode::Single {
+        format!("\n  🖼 {}", sub.push_mode.display_name())
+    } else {
+        String::new()
+    };
+
+    let ranking_date_info = if sub.ranking_date_offset > 0 {
+        format!("\n  📅 {}", ranking_date_label(sub.ranking_date_offset))
+    } else {
+        String::new()
+    };
+
+    let silent_info = if sub.silent {
+        "\n  🔕 静默推送"
+    } else {
+        ""
+    };
+
+    let ai_filter_info = if sub.ai_filter != AiFilter::Auto {
+        format!("\n  🤖 {}", sub.ai_filter.display_name())
+    } else {
+        String::new()
+    };
+
+    let image_size_info = if sub.image_size != PushImageSize::Auto {
+        format!("\n  📐 {}", sub.image_size.display_name())
+    } else {
+        String::new()
+    };
+
+    let topic_info = match sub.message_thread_id {
+        Some(thread_id) => format!("\n  🧵 话题 {}", thread_id),
+        None => String::new(),
+    };
+
+    let paused_badge = if sub.enabled { "" } else { " ⏸ *已暂停*" };
+
+    format!(
+        "{} {}{}{}{}{}{}{}{}{}{}{}\n",
+        type_emoji,
+        display_info,
+        paused_badge,
+        filter_info,
+        booru_filter_info,
+        spoiler_info,
+        push_mode_info,
+        ranking_date_info,
+        silent_info,
+        topic_info,
+        ai_filter_info,
+        image_size_info
+    )
+}
+
+/// Split entries into pages of at most `max_entries` entries and `budget` UTF-16 units.
+///
+/// An entry longer than the budget still gets a page of its own.
+fn paginate_entries(entries: &[String], max_entries: usize, budget: usize) -> Vec<Range<usize>> {
+    let mut pages = Vec::new();
+    let mut start = 0;
+    let mut used = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let len = entry.encode_utf16().count();
+        if i > start && (i - start >= max_entries || used + len > budget) {
+            pages.push(start..i);
+            start = i;
+            used = 0;
+        }
+        used += len;
+    }
+    if start < entries.len() {
+        pages.push(start..entries.len());
+    }
+    pages
+}
+
 fn build_list_callback_data(page: usize, target_chat_id: ChatId, is_channel: bool) -> String {
     format!(
         "{}{page}:{}:{}",
@@ -271,7 +373,7 @@ fn booru_list_display(
         TaskType::BooruTag => "🏷",
         TaskType::BooruPool => "📦",
         TaskType::BooruRanking => booru_ranking_list_emoji(task_value),
-        TaskType::Author | TaskType::Ranking | TaskType::Ehentai => {
+        TaskType::Author | TaskType::Ranking | TaskType::Ehentai | TaskType::Bookmarks => {
             unreachable!("not a booru task type")
         }
     };
@@ -287,7 +389,7 @@ fn booru_list_display(
             TaskType::BooruTag => "标签",
             TaskType::BooruPool => "Pool",
             TaskType::BooruRanking => "排行",
-            TaskType::Author | TaskType::Ranking | TaskType::Ehentai => {
+            TaskType::Author | TaskType::Ranking | TaskType::Ehentai | TaskType::Bookmarks => {
                 unreachable!("not a booru task type")
             }
         };
@@ -400,6 +502,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_paginate_entries_respects_count_and_length() {
+        let entries: Vec<String> = ["aaaa", "bb", "cccccc", "d", "e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(paginate_entries(&entries, 2, 100), [0..2, 2..4, 4..5]);
+        assert_eq!(paginate_entries(&entries, 50, 6), [0..2, 2..3, 3..5]);
+        // An oversized entry still gets its own page
+        assert_eq!(paginate_entries(&entries, 50, 3), [0..1, 1..2, 2..3, 3..5]);
+        assert!(paginate_entries(&[], 50, 100).is_empty());
+    }
+
     #[test]
     fn test_build_list_callback_data_encodes_context() {
         assert_eq!(
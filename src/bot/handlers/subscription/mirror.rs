@@ -0,0 +1,213 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::TaskType;
+use crate::scheduler::author_subscription_state;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode, UserId};
+use tracing::{error, info};
+
+/// `/mirror` 的操作
+#[derive(Debug, PartialEq, Eq)]
+enum MirrorAction {
+    List,
+    Add(i64),
+    Remove(i64),
+}
+
+/// 解析 `/mirror <sub_id> [<chat_id>|remove <chat_id>]`
+fn parse_mirror_args(args: &str) -> Option<(i32, MirrorAction)> {
+    let action = match args.split_whitespace().collect::<Vec<_>>()[..] {
+        [sub_id] => (sub_id, MirrorAction::List),
+        [sub_id, chat_id] => (sub_id, MirrorAction::Add(chat_id.parse().ok()?)),
+        [sub_id, "remove", chat_id] => (sub_id, MirrorAction::Remove(chat_id.parse().ok()?)),
+        _ => return None,
+    };
+    Some((action.0.parse().ok()?, action.1))
+}
+
+impl BotHandler {
+    /// 管理作者订阅的镜像推送目标
+    ///
+    /// 作者只抓取一次，新作品同时推送到订阅所在聊天和所有镜像聊天，
+    /// 每个镜像聊天单独记录推送进度。
+    pub async fn handle_mirror(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args: String,
+    ) -> ResponseResult<()> {
+        let Some((subscription_id, action)) = parse_mirror_args(&args) else {
+            bot.send_message(
+                chat_id,
+                "❌ 用法: `/mirror <sub_id> [<chat_id>|remove <chat_id>]`",
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+            return Ok(());
+        };
+
+        let subscription = match self.repo.get_subscription(subscription_id).await {
+            Ok(Some(subscription)) => subscription,
+            Ok(None) => {
+                bot.send_message(chat_id, format!("❌ 订阅 #{} 不存在", subscription_id))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get subscription {}: {:#}", subscription_id, e);
+                bot.send_message(chat_id, "❌ 查询订阅失败").await?;
+                return Ok(());
+            }
+        };
+
+        match self.repo.get_task(subscription.task_id).await {
+            Ok(Some(task)) if task.r#type == TaskType::Author => {}
+            Ok(_) => {
+                bot.send_message(chat_id, "❌ 只有作者订阅支持镜像推送")
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get task {}: {:#}", subscription.task_id, e);
+                bot.send_message(chat_id, "❌ 查询订阅失败").await?;
+                return Ok(());
+            }
+        }
+
+        let message = match action {
+            MirrorAction::List => {
+                match self.repo.list_subscription_targets(subscription_id).await {
+                    Ok(targets) if targets.is_empty() => format!(
+                        "📭 订阅 #{} (聊天 {}) 没有镜像推送目标",
+                        subscription_id, subscription.chat_id
+                    ),
+                    Ok(targets) => {
+                        let mut lines = vec![format!(
+                            "🪞 订阅 #{} (聊天 {}) 的镜像推送目标:",
+                            subscription_id, subscription.chat_id
+                        )];
+                        lines.extend(targets.iter().map(|target| match target.latest_illust_id {
+                            Some(illust_id) => {
+                                format!("• {} - 已推送到作品 {}", target.chat_id, illust_id)
+                            }
+                            None => format!("• {} - 尚未推送", target.chat_id),
+                        }));
+                        lines.join("\n")
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to list targets of subscription {}: {:#}",
+                            subscription_id, e
+                        );
+                        "❌ 查询镜像推送目标失败".to_string()
+                    }
+                }
+            }
+            MirrorAction::Add(target_chat_id) => {
+                if target_chat_id == subscription.chat_id {
+                    bot.send_message(chat_id, "❌ 目标聊天就是订阅所在的聊天")
+                        .await?;
+                    return Ok(());
+                }
+                match self.repo.get_chat(target_chat_id).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        bot.send_message(chat_id, format!("❌ 目标聊天 {} 不存在", target_chat_id))
+                            .await?;
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        error!("Failed to get chat {}: {:#}", target_chat_id, e);
+                        bot.send_message(chat_id, "❌ 获取聊天信息失败").await?;
+                        return Ok(());
+                    }
+                }
+
+                // Start where the subscription is, so the mirror doesn't replay older works
+                let cursor = author_subscription_state(&subscription)
+                    .map(|state| state.latest_illust_id as i64);
+                match self
+                    .repo
+                    .add_subscription_target(
+                        subscription_id,
+                        target_chat_id,
+                        cursor,
+                        user_id.map(|id| id.0 as i64),
+                    )
+                    .await
+                {
+                    Ok(true) => {
+                        info!(
+                            "Admin mirrored subscription {} to chat {}",
+                            subscription_id, target_chat_id
+                        );
+                        format!(
+                            "✅ 订阅 #{} 的新作品将同时推送到 {}",
+                            subscription_id, target_chat_id
+                        )
+                    }
+                    Ok(false) => {
+                        format!("ℹ️ 订阅 #{} 已镜像到 {}", subscription_id, target_chat_id)
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to mirror subscription {} to chat {}: {:#}",
+                            subscription_id, target_chat_id, e
+                        );
+                        "❌ 添加镜像推送目标失败".to_string()
+                    }
+                }
+            }
+            MirrorAction::Remove(target_chat_id) => match self
+                .repo
+                .remove_subscription_target(subscription_id, target_chat_id)
+                .await
+            {
+                Ok(true) => {
+                    info!(
+                        "Admin removed mirror of subscription {} to chat {}",
+                        subscription_id, target_chat_id
+                    );
+                    format!(
+                        "✅ 已停止将订阅 #{} 推送到 {}",
+                        subscription_id, target_chat_id
+                    )
+                }
+                Ok(false) => format!("ℹ️ 订阅 #{} 没有镜像到 {}", subscription_id, target_chat_id),
+                Err(e) => {
+                    error!(
+                        "Failed to remove mirror of subscription {} to chat {}: {:#}",
+                        subscription_id, target_chat_id, e
+                    );
+                    "❌ 移除镜像推送目标失败".to_string()
+                }
+            },
+        };
+
+        bot.send_message(chat_id, message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mirror_args_accepts_list_add_and_remove() {
+        assert_eq!(parse_mirror_args("12"), Some((12, MirrorAction::List)));
+        assert_eq!(
+            parse_mirror_args("12 -1001234567890"),
+            Some((12, MirrorAction::Add(-1001234567890)))
+        );
+        assert_eq!(
+            parse_mirror_args("12 remove -100"),
+            Some((12, MirrorAction::Remove(-100)))
+        );
+        assert_eq!(parse_mirror_args(""), None);
+        assert_eq!(parse_mirror_args("abc -100"), None);
+        assert_eq!(parse_mirror_args("12 remove"), None);
+        assert_eq!(parse_mirror_args("12 add -100"), None);
+    }
+}
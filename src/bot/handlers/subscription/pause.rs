@@ -0,0 +1,168 @@
+use super::BatchResult;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::TaskType;
+use crate::utils::args;
+use anyhow::{Context, Result};
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode, UserId};
+use teloxide::utils::markdown;
+use tracing::error;
+
+impl BotHandler {
+    /// 暂停订阅 (`/pause [ch=<频道ID>] [author_id,...|all]`，无参数时暂停全部)
+    pub async fn handle_pause(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        self.set_subscriptions_enabled(bot, chat_id, user_id, args_str, false)
+            .await
+    }
+
+    /// 恢复已暂停的订阅 (`/resume [ch=<频道ID>] [author_id,...|all]`，无参数时恢复全部)
+    pub async fn handle_resume(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        self.set_subscriptions_enabled(bot, chat_id, user_id, args_str, true)
+            .await
+    }
+
+    async fn set_subscriptions_enabled(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+        enabled: bool,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let action = if enabled { "恢复" } else { "暂停" };
+        let channel_suffix = if is_channel {
+            format!("\n📢 频道: `{}`", target_chat_id.0)
+        } else {
+            String::new()
+        };
+
+        let ids_str = parsed.remaining.trim();
+        if ids_str.is_empty() || ids_str.eq_ignore_ascii_case("all") {
+            let message = match self
+                .repo
+                .set_chat_subscriptions_enabled(target_chat_id.0, enabled)
+                .await
+            {
+                Ok(0) if enabled => "📭 没有已暂停的订阅".to_string(),
+                Ok(0) => "📭 没有可暂停的订阅".to_string(),
+                Ok(count) => format!("✅ 已{} {} 条订阅{}", action, count, channel_suffix),
+                Err(e) => {
+                    error!(
+                        "Failed to {} subscriptions of chat {}: {:#}",
+                        if enabled { "resume" } else { "pause" },
+                        target_chat_id,
+                        e
+                    );
+                    format!("❌ {}订阅失败", action)
+                }
+            };
+            bot.send_message(chat_id, message)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        }
+
+        let mut result = BatchResult::new();
+        for author_id in ids_str.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if author_id.parse::<u64>().is_err() {
+                result.add_failure(format!(
+                    "`{}` \\(无效的ID\\)",
+                    markdown::escape_code(author_id)
+                ));
+                continue;
+            }
+
+            match self
+                .set_author_subscription_enabled(target_chat_id.0, author_id, enabled)
+                .await
+            {
+                Ok(author_name) => {
+                    let display = if let Some(name) = author_name {
+                        format!("*{}* \\(ID: `{}`\\)", markdown::escape(&name), author_id)
+                    } else {
+                        format!("`{}`", author_id)
+                    };
+                    result.add_success(display);
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to set subscription of author {} enabled={}: {:#}",
+                        author_id, enabled, e
+                    );
+                    result.add_failure(format!("`{}` \\(未找到订阅\\)", author_id));
+                }
+            }
+        }
+
+        let mut response = result.build_response(
+            &format!("✅ 已{}订阅:", action),
+            &format!("❌ {}失败:", action),
+        );
+        if result.has_success() {
+            response.push_str(&channel_suffix);
+        }
+        bot.send_message(chat_id, response)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the author name of the updated subscription
+    async fn set_author_subscription_enabled(
+        &self,
+        chat_id: i64,
+        author_id: &str,
+        enabled: bool,
+    ) -> Result<Option<String>> {
+        let task = self
+            .repo
+            .get_task_by_type_value(TaskType::Author, author_id)
+            .await
+            .context("Failed to query task")?
+            .context("Author task not found")?;
+
+        let subscription = self
+            .repo
+            .get_subscription_by_chat_task(chat_id, task.id)
+            .await
+            .context("Failed to query subscription")?
+            .context("Not subscribed")?;
+
+        self.repo
+            .set_subscription_enabled(subscription.id, enabled)
+            .await?;
+
+        Ok(task.author_name)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::bot::handlers::PREVIEW_BUDGET_EXHAUSTED;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::TagFilter;
+use crate::scheduler::filter_illusts_for_chat;
+use crate::utils::sensitive;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tracing::{error, warn};
+
+/// Number of latest works fetched for a preview (same as the author engine)
+const PREVIEW_FETCH_LIMIT: usize = 10;
+
+impl BotHandler {
+    /// 预览作者订阅：展示按过滤条件会推送的作品，但不创建订阅
+    pub async fn handle_preview(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parts: Vec<&str> = args_str.split_whitespace().collect();
+
+        let Some(author_id) = parts.first().and_then(|s| s.parse::<u64>().ok()) else {
+            bot.send_message(chat_id, "❌ 用法: `/preview <author_id> [+tag1 -tag2]`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let filter_tags = TagFilter::parse_from_args(&parts[1..]);
+
+        let chat = match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) => chat,
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 未找到聊天").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get chat {} for preview: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 获取聊天设置失败").await?;
+                return Ok(());
+            }
+        };
+
+        let (author, illusts) = {
+            let pixiv = self.pixiv_client.read().await;
+            // User detail + illust list
+            if !pixiv.try_reserve_preview(2) {
+                drop(pixiv);
+                bot.send_message(chat_id, PREVIEW_BUDGET_EXHAUSTED).await?;
+                return Ok(());
+            }
+            let author = match pixiv.get_user_detail(author_id).await {
+                Ok(user) => user,
+                Err(e) => {
+                    error!("Failed to get user detail for {}: {:#}", author_id, e);
+                    bot.send_message(chat_id, format!("❌ 获取用户 {} 失败", author_id))
+                        .await?;
+                    return Ok(());
+                }
+            };
+            let illusts = match pixiv.get_user_illusts(author_id, PREVIEW_FETCH_LIMIT).await {
+                Ok(illusts) => illusts,
+                Err(e) => {
+                    error!("Failed to get illusts for {}: {:#}", author_id, e);
+                    bot.send_message(chat_id, "❌ 获取作品列表失败").await?;
+                    return Ok(());
+                }
+            };
+            (author, illusts)
+        };
+
+        let matched = filter_illusts_for_chat(&filter_tags, &chat, illusts.iter());
+
+        let mut message = format!(
+            "🔍 *订阅预览* \\- *{}* \\(ID: `{}`\\)\n",
+            markdown::escape(&author.name),
+            author_id
+        );
+        if !filter_tags.is_empty() {
+            message.push_str(&format!("🏷 {}\n", filter_tags.format_for_display()));
+        }
+        message.push('\n');
+
+        if matched.is_empty() {
+            message.push_str(&format!(
+                "最近 {} 个作品中没有符合过滤条件的作品",
+                illusts.len()
+            ));
+        } else {
+            message.push_str(&format!(
+                "最近 {} 个作品中将推送 {} 个:\n",
+                illusts.len(),
+                matched.len()
+            ));
+            for illust in &matched {
+                message.push_str(&format!(
+                    "  • [{}](https://pixiv\\.net/artworks/{})\n",
+                    markdown::escape(&illust.title),
+                    illust.id
+                ));
+            }
+        }
+
+        bot.send_message(chat_id, message)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        if matched.is_empty() {
+            return Ok(());
+        }
+
+        let thumbnails: Vec<String> = matched
+            .iter()
+            .map(|illust| illust.image_urls.square_medium.clone())
+            .collect();
+        let has_spoiler = matched
+            .iter()
+            .any(|illust| sensitive::should_blur(&chat, illust));
+
+        let result = self
+            .notifier
+            .notify_with_images(chat_id, &thumbnails, None, has_spoiler)
+            .await;
+        if !result.is_complete_success() {
+            warn!(
+                "Preview thumbnails partially failed for chat {}: {:?}",
+                chat_id, result.failed_indices
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::bot::notifier::{DownloadButtonConfig, ThrottledBot};
+use crate::bot::BotHandler;
+use crate::db::types::{SpoilerMode, TagFilter, TaskType};
+use crate::scheduler::filter_illusts_for_chat;
+use crate::utils::{caption, sensitive};
+use rand::seq::IndexedRandom;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode};
+use tracing::{error, info, warn};
+
+/// Pages of the author's works sampled for /random (30 works per page)
+const RANDOM_FETCH_PAGES: usize = 3;
+
+impl BotHandler {
+    /// 随机推送一个作品：指定作者，或从当前聊天订阅的作者中随机挑选
+    ///
+    /// 已订阅的作者会使用订阅的标签过滤与遮罩设置，并始终应用聊天的排除标签、R-18 与模糊设置。
+    pub async fn handle_random(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let arg = args_str.trim();
+        let requested_author = if arg.is_empty() {
+            None
+        } else {
+            match arg.parse::<u64>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    bot.send_message(chat_id, "❌ 用法: `/random [author_id]`")
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        let chat = match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) => chat,
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 未找到聊天").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get chat {} for random: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 获取聊天设置失败").await?;
+                return Ok(());
+            }
+        };
+
+        let subscribed_authors: Vec<(u64, TagFilter, SpoilerMode)> =
+            match self.repo.list_subscriptions_by_chat(chat_id.0).await {
+                Ok(subs) => subs
+                    .into_iter()
+                    .filter(|(_, task)| task.r#type == TaskType::Author)
+                    .filter_map(|(sub, task)| {
+                        task.value
+                            .parse::<u64>()
+                            .ok()
+                            .map(|id| (id, sub.filter_tags, sub.spoiler_mode))
+                    })
+                    .collect(),
+                Err(e) => {
+                    error!("Failed to list subscriptions for chat {}: {:#}", chat_id, e);
+                    bot.send_message(chat_id, "❌ 获取订阅列表失败").await?;
+                    return Ok(());
+                }
+            };
+
+        let (author_id, filter_tags, spoiler_mode) = match requested_author {
+            Some(author_id) => subscribed_authors
+                .into_iter()
+                .find(|(id, _, _)| *id == author_id)
+                .unwrap_or((author_id, TagFilter::default(), SpoilerMode::Auto)),
+            None => {
+                // ThreadRng is not Send, so pick before the next await
+                let picked = subscribed_authors.choose(&mut rand::rng()).cloned();
+                match picked {
+                    Some(picked) => picked,
+                    None => {
+                        bot.send_message(
+                            chat_id,
+                            "❌ 当前聊天没有订阅任何作者，请使用 `/random <author_id>` 指定作者",
+                        )
+                        .parse_mode(ParseMode::MarkdownV2)
+                        .await?;
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::UploadPhoto).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        let illusts = {
+            let pixiv = self.pixiv_client.read().await;
+            match pixiv
+                .get_user_illusts_pages(author_id, RANDOM_FETCH_PAGES)
+                .await
+            {
+                Ok(illusts) => illusts,
+                Err(e) => {
+                    error!("Failed to get illusts for {}: {:#}", author_id, e);
+                    bot.send_message(chat_id, format!("❌ 获取作者 {} 的作品失败", author_id))
+                        .await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        let candidates = filter_illusts_for_chat(&filter_tags, &chat, illusts.iter());
+        let picked = candidates.choose(&mut rand::rng()).copied();
+        let Some(illust) = picked else {
+            bot.send_message(
+                chat_id,
+                format!("❌ 作者 {} 没有符合过滤条件的作品", author_id),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        info!(
+            "Sending random illust {} of author {} to chat {}",
+            illust.id, author_id, chat_id
+        );
+
+        let image_urls = illust.get_all_image_urls_with_size(self.image_sizes.push);
+        let result = self
+            .notifier
+            .notify_with_images_and_button(
+                chat_id,
+                &image_urls,
+                Some(&caption::build_illust_caption(illust)),
+                spoiler_mode.resolve(sensitive::should_blur(&chat, illust)),
+                &DownloadButtonConfig::for_pixiv_chat(illust.id, &chat),
+            )
+            .await;
+
+        if result.is_complete_failure() {
+            bot.send_message(chat_id, "❌ 发送作品失败").await?;
+        } else if !result.is_complete_success() {
+            warn!(
+                "Random illust {} partially failed for chat {}: {:?}",
+                illust.id, chat_id, result.failed_indices
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,15 +1,72 @@
+use super::helpers::{
+    ai_filter_summary, image_size_summary, parse_silent_arg, parse_topic_arg, ranking_date_label,
+    silent_summary, topic_summary,
+};
+use super::BatchResult;
 use crate::bot::notifier::ThrottledBot;
 use crate::bot::BotHandler;
-use crate::db::types::{TagFilter, TaskType};
+use crate::db::entities::subscriptions;
+use crate::db::types::{AiFilter, PushImageSize, TagFilter, TaskType};
 use crate::pixiv::model::RankingMode;
+use crate::scheduler::RankingPushOutcome;
 use crate::utils::args;
+use crate::utils::channel::{BotChannelExt, ChannelIdentifier};
+use anyhow::Context;
+use std::collections::HashSet;
 use teloxide::prelude::*;
-use teloxide::types::{ChatAction, ChatId, ParseMode, UserId};
+use teloxide::types::{
+    ChatAction, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode, UserId,
+};
 use teloxide::utils::markdown;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
+
+/// Callback data prefix for the "send today's ranking now" button.
+/// Format: `rkpush:<subscription_id>`.
+pub const RANKING_PUSH_CALLBACK_PREFIX: &str = "rkpush:";
+
+/// 新排行榜订阅的首次推送时机（`start=` 参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RankingStart {
+    /// 今日排行已推送时跳过今日作品，从下次推送开始
+    Tomorrow,
+    /// 订阅后立即推送今日排行
+    Now,
+}
+
+impl RankingStart {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "tomorrow" => Some(Self::Tomorrow),
+            "now" => Some(Self::Now),
+            _ => None,
+        }
+    }
+}
+
+/// `date=` 参数允许的最大天数
+const MAX_RANKING_DATE_OFFSET: i32 = 7;
+
+/// 解析 `date=` 参数：`prev`/`yesterday` 为前一天已结算的排行，`-<天数>` 为更早的排行，`latest` 为最新排行
+fn parse_ranking_date_offset(value: &str) -> Option<i32> {
+    match value.to_lowercase().as_str() {
+        "latest" | "0" => Some(0),
+        "prev" | "yesterday" => Some(1),
+        other => other
+            .strip_prefix('-')
+            .and_then(|days| days.parse::<i32>().ok())
+            .filter(|days| (1..=MAX_RANKING_DATE_OFFSET).contains(days)),
+    }
+}
+
+/// 订阅确认消息中的排行日期说明，最新排行不显示
+fn ranking_date_summary(offset_days: Option<i32>) -> Option<String> {
+    offset_days
+        .filter(|&days| days > 0)
+        .map(|days| format!("📅 排行日期: {}", ranking_date_label(days)))
+}
 
 impl BotHandler {
-    /// 订阅 Pixiv 排行榜
+    /// 订阅 Pixiv 排行榜，可用逗号分隔一次订阅多个模式
     pub async fn handle_sub_ranking(
         &self,
         bot: ThrottledBot,
@@ -38,6 +95,87 @@ impl BotHandler {
             }
         };
 
+        let start = match parsed.get("start") {
+            None => RankingStart::Tomorrow,
+            Some(value) => match RankingStart::parse(value) {
+                Some(start) => start,
+                None => {
+                    bot.send_message(chat_id, "❌ start 参数无效，可选: tomorrow, now")
+                        .await?;
+                    return Ok(());
+                }
+            },
+        };
+
+        let silent = match parsed.get("silent").map(parse_silent_arg) {
+            Some(Some(silent)) => Some(silent),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `silent` 只能是 `true` 或 `false`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let ai_filter = match parsed.get("ai").map(AiFilter::parse) {
+            Some(Some(filter)) => Some(filter),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `ai` 只能是 `all`、`exclude`、`only` 或 `auto`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let image_size = match parsed.get("size").map(PushImageSize::parse) {
+            Some(Some(size)) => Some(size),
+            Some(None) => {
+                bot.send_message(
+                    chat_id,
+                    "❌ `size` 只能是 `original`、`large`、`medium` 或 `auto`",
+                )
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let topic = match parsed.get("topic").map(parse_topic_arg) {
+            Some(Some(_)) if is_channel || !target_chat_id.is_channel_or_supergroup() => {
+                bot.send_message(chat_id, "❌ 只有超级群组可以指定 `topic`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            Some(Some(topic)) => Some(topic),
+            Some(None) => {
+                bot.send_message(chat_id, "❌ `topic` 只能是话题 ID 或 `off`")
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let date_offset = match parsed.get("date").map(parse_ranking_date_offset) {
+            Some(Some(days)) => Some(days),
+            Some(None) => {
+                bot.send_message(
+                    chat_id,
+                    format!(
+                        "❌ date 参数无效，可选: latest, prev, -<天数> (1-{})",
+                        MAX_RANKING_DATE_OFFSET
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            None => None,
+        };
+
         let parts: Vec<&str> = parsed.remaining.split_whitespace().collect();
 
         if parts.is_empty() {
@@ -45,7 +183,7 @@ impl BotHandler {
             bot.send_message(
                 chat_id,
                 format!(
-                    "❌ 用法: `/subrank [ch=<频道ID>] <mode> [+tag1 -tag2]`\n可用模式: {}",
+                    "❌ 用法: `/subrank [ch=<频道ID>] [start=tomorrow|now] [date=prev] [silent=true] [topic=<话题ID>] [ai=exclude|only] [size=original|large|medium] <mode,...> [+tag1 -tag2]`\n可用模式: {}",
                     markdown::escape(&available_modes)
                 ),
             )
@@ -54,7 +192,33 @@ impl BotHandler {
             return Ok(());
         }
 
-        let mode = match RankingMode::from_str(parts[0]) {
+        let filter_tags = self
+            .with_chat_default_filter(target_chat_id.0, TagFilter::parse_from_args(&parts[1..]))
+            .await;
+
+        let mode_args = split_mode_list(parts[0]);
+        if mode_args.len() > 1 {
+            return self
+                .sub_ranking_modes(
+                    &bot,
+                    chat_id,
+                    user_id,
+                    target_chat_id,
+                    is_channel,
+                    start,
+                    silent,
+                    topic,
+                    ai_filter,
+                    image_size,
+                    date_offset,
+                    &mode_args,
+                    &filter_tags,
+                )
+                .await;
+        }
+
+        let mode_arg = mode_args.first().copied().unwrap_or_default();
+        let mode = match RankingMode::from_str(mode_arg) {
             Some(mode) => mode,
             None => {
                 let available_modes = RankingMode::all_modes().join(", ");
@@ -67,8 +231,6 @@ impl BotHandler {
             }
         };
 
-        let filter_tags = TagFilter::parse_from_args(&parts[1..]);
-
         match self
             .create_subscription(
                 target_chat_id.0,
@@ -76,20 +238,69 @@ impl BotHandler {
                 mode.as_str(),
                 None,
                 filter_tags.clone(),
+                user_id,
             )
             .await
         {
-            Ok(_) => {
+            Ok((_, mut subscription)) => {
+                self.apply_subscription_silent(&mut subscription, silent)
+                    .await;
+                self.apply_subscription_topic(&mut subscription, topic)
+                    .await;
+                self.apply_subscription_ai_filter(&mut subscription, ai_filter)
+                    .await;
+                self.apply_subscription_image_size(&mut subscription, image_size)
+                    .await;
+                self.apply_ranking_date_offset(&mut subscription, date_offset)
+                    .await;
+                let seeded = match start {
+                    RankingStart::Tomorrow => self.seed_ranking_subscription(&subscription).await,
+                    RankingStart::Now => false,
+                };
+
                 let mut message = format!("✅ 成功订阅 {}", mode.display_name());
                 if !filter_tags.is_empty() {
                     message.push_str(&format!("\n\n🏷 {}", filter_tags.format_for_display()));
                 }
+                if let Some(date) = ranking_date_summary(date_offset) {
+                    message.push_str(&format!("\n{}", date));
+                }
+                if let Some(silent) = silent {
+                    message.push_str(&format!("\n{}", silent_summary(silent)));
+                }
+                if let Some(topic) = topic {
+                    message.push_str(&format!("\n{}", topic_summary(topic)));
+                }
+                if let Some(ai_filter) = ai_filter {
+                    message.push_str(&format!("\n{}", ai_filter_summary(ai_filter)));
+                }
+                if let Some(image_size) = image_size {
+                    message.push_str(&format!("\n{}", image_size_summary(image_size)));
+                }
                 if is_channel {
                     message.push_str(&format!("\n📢 频道: `{}`", target_chat_id.0));
                 }
-                bot.send_message(chat_id, message)
-                    .parse_mode(ParseMode::MarkdownV2)
-                    .await?;
+                if seeded {
+                    message.push_str("\n\n📅 今日排行已推送过，将从下次推送开始发送新作品");
+                }
+
+                let mut request = bot
+                    .send_message(chat_id, message)
+                    .parse_mode(ParseMode::MarkdownV2);
+                // Seeded subscriptions have nothing left to send today
+                if start == RankingStart::Tomorrow && !seeded {
+                    request = request.reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                        InlineKeyboardButton::callback(
+                            "📤 立即发送今日排行",
+                            format!("{}{}", RANKING_PUSH_CALLBACK_PREFIX, subscription.id),
+                        ),
+                    ]]));
+                }
+                request.await?;
+
+                if start == RankingStart::Now {
+                    self.push_ranking_now(&bot, chat_id, &subscription).await?;
+                }
             }
             Err(e) => {
                 error!("Failed to subscribe to ranking {}: {:#}", mode.as_str(), e);
@@ -100,6 +311,142 @@ impl BotHandler {
         Ok(())
     }
 
+    /// `/subrank day,week,...`: subscribe to several modes and reply with one summary
+    #[allow(clippy::too_many_arguments)]
+    async fn sub_ranking_modes(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        target_chat_id: ChatId,
+        is_channel: bool,
+        start: RankingStart,
+        silent: Option<bool>,
+        topic: Option<Option<i32>>,
+        ai_filter: Option<AiFilter>,
+        image_size: Option<PushImageSize>,
+        date_offset: Option<i32>,
+        mode_args: &[&str],
+        filter_tags: &TagFilter,
+    ) -> ResponseResult<()> {
+        let mut result = BatchResult::new();
+        let mut seen = HashSet::new();
+        let mut push_buttons = Vec::new();
+        let mut push_now = Vec::new();
+
+        for mode_arg in mode_args {
+            let Some(mode) = RankingMode::from_str(mode_arg) else {
+                result.add_failure(format!(
+                    "`{}` \\(无效的模式\\)",
+                    markdown::escape_code(mode_arg)
+                ));
+                continue;
+            };
+            if !seen.insert(mode.as_str()) {
+                continue;
+            }
+
+            match self
+                .create_subscription(
+                    target_chat_id.0,
+                    TaskType::Ranking,
+                    mode.as_str(),
+                    None,
+                    filter_tags.clone(),
+                    user_id,
+                )
+                .await
+            {
+                Ok((_, mut subscription)) => {
+                    self.apply_subscription_silent(&mut subscription, silent)
+                        .await;
+                    self.apply_subscription_topic(&mut subscription, topic)
+                        .await;
+                    self.apply_subscription_ai_filter(&mut subscription, ai_filter)
+                        .await;
+                    self.apply_subscription_image_size(&mut subscription, image_size)
+                        .await;
+                    self.apply_ranking_date_offset(&mut subscription, date_offset)
+                        .await;
+                    let mut item = markdown::escape(mode.display_name());
+                    match start {
+                        RankingStart::Tomorrow => {
+                            if self.seed_ranking_subscription(&subscription).await {
+                                item.push_str(" \\(今日已推送，从下次开始\\)");
+                            } else {
+                                push_buttons.push(vec![InlineKeyboardButton::callback(
+                                    format!("📤 立即发送今日{}", mode.display_name()),
+                                    format!("{}{}", RANKING_PUSH_CALLBACK_PREFIX, subscription.id),
+                                )]);
+                            }
+                        }
+                        RankingStart::Now => push_now.push(subscription),
+                    }
+                    result.add_success(item);
+                }
+                Err(e) => {
+                    error!("Failed to subscribe to ranking {}: {:#}", mode.as_str(), e);
+                    result.add_failure(format!(
+                        "{} \\(订阅失败\\)",
+                        markdown::escape(mode.display_name())
+                    ));
+                }
+            }
+        }
+
+        let mut suffix_parts = Vec::new();
+        if !filter_tags.is_empty() {
+            suffix_parts.push(format!("🏷 {}", filter_tags.format_for_display()));
+        }
+        suffix_parts.extend(ranking_date_summary(date_offset));
+        if let Some(silent) = silent {
+            suffix_parts.push(silent_summary(silent).to_string());
+        }
+        if let Some(topic) = topic {
+            suffix_parts.push(topic_summary(topic));
+        }
+        if let Some(ai_filter) = ai_filter {
+            suffix_parts.push(ai_filter_summary(ai_filter));
+        }
+        if let Some(image_size) = image_size {
+            suffix_parts.push(image_size_summary(image_size));
+        }
+        if is_channel {
+            suffix_parts.push(format!("📢 频道: `{}`", target_chat_id.0));
+        }
+        let suffix = (!suffix_parts.is_empty()).then(|| format!("\n{}", suffix_parts.join("\n")));
+
+        let response =
+            result.build_response_with_suffix("✅ 成功订阅:", "❌ 订阅失败:", suffix.as_deref());
+        let mut request = bot
+            .send_message(chat_id, response)
+            .parse_mode(ParseMode::MarkdownV2);
+        if !push_buttons.is_empty() {
+            request = request.reply_markup(InlineKeyboardMarkup::new(push_buttons));
+        }
+        request.await?;
+
+        for subscription in &push_now {
+            self.push_ranking_now(bot, chat_id, subscription).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Skip today's works when today's ranking was already pushed; false on errors
+    async fn seed_ranking_subscription(&self, subscription: &subscriptions::Model) -> bool {
+        self.ranking_engine
+            .seed_new_subscription(subscription)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to seed ranking subscription {}: {:#}",
+                    subscription.id, e
+                );
+                false
+            })
+    }
+
     /// 取消订阅排行榜
     pub async fn handle_unsub_ranking(
         &self,
@@ -172,4 +519,201 @@ impl BotHandler {
 
         Ok(())
     }
+
+    /// 处理 "立即发送今日排行" 按钮：仅为该订阅立即拉取并推送一次排行榜
+    pub async fn handle_ranking_push_callback(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: UserId,
+        subscription_id: i32,
+    ) -> ResponseResult<()> {
+        let subscription = match self.repo.get_subscription(subscription_id).await {
+            Ok(Some(subscription)) => subscription,
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 订阅不存在或已取消").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get subscription {}: {:#}", subscription_id, e);
+                bot.send_message(chat_id, "❌ 获取订阅失败").await?;
+                return Ok(());
+            }
+        };
+
+        // Channel subscriptions are created from another chat, so the clicker
+        // must still be allowed to manage the channel the ranking is pushed to
+        if subscription.chat_id != chat_id.0 {
+            let channel_id = ChatId(subscription.chat_id);
+            let channel = ChannelIdentifier::Id(channel_id);
+            match bot.is_user_channel_admin(&channel, user_id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(
+                        "User {} attempted to push ranking subscription {} without channel admin rights",
+                        user_id, subscription_id
+                    );
+                    bot.send_message(chat_id, "❌ 您不是该频道的管理员").await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    bot.send_message(chat_id, format!("❌ {}", e)).await?;
+                    return Ok(());
+                }
+            }
+            if let Err(e) = self.check_channel_delegate(&bot, channel_id, user_id).await {
+                bot.send_message(chat_id, format!("❌ {}", e)).await?;
+                return Ok(());
+            }
+        } else {
+            match self
+                .can_push_ranking_now(&bot, chat_id, user_id, &subscription)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(
+                        "User {} attempted to push ranking subscription {} without permission",
+                        user_id, subscription_id
+                    );
+                    bot.send_message(chat_id, "❌ 只有订阅者或群组管理员可以立即推送")
+                        .await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to check ranking push permission of user {} in chat {}: {:#}",
+                        user_id, chat_id, e
+                    );
+                    bot.send_message(chat_id, "❌ 无法确认您的权限").await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        info!(
+            "Pushing ranking subscription {} on demand (requested by user {})",
+            subscription_id, user_id
+        );
+
+        self.push_ranking_now(&bot, chat_id, &subscription).await
+    }
+
+    /// 私聊、订阅创建者、Bot 管理员以及群组管理员可以立即推送群组的排行订阅
+    async fn can_push_ranking_now(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        user_id: UserId,
+        subscription: &subscriptions::Model,
+    ) -> anyhow::Result<bool> {
+        if chat_id.is_user() || subscription.created_by == Some(user_id.0 as i64) {
+            return Ok(true);
+        }
+
+        let is_bot_admin = self
+            .repo
+            .get_user(user_id.0 as i64)
+            .await?
+            .is_some_and(|user| user.role.is_admin());
+        if is_bot_admin {
+            return Ok(true);
+        }
+
+        let member = bot
+            .get_chat_member(chat_id, user_id)
+            .await
+            .context("Failed to get chat member")?;
+        Ok(member.is_privileged())
+    }
+
+    /// 立即为订阅推送今日排行，并在 `chat_id` 中回报结果
+    async fn push_ranking_now(
+        &self,
+        bot: &ThrottledBot,
+        chat_id: ChatId,
+        subscription: &subscriptions::Model,
+    ) -> ResponseResult<()> {
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        match self
+            .ranking_engine
+            .push_subscription_now(subscription)
+            .await
+        {
+            Ok(RankingPushOutcome::Pushed) => {}
+            Ok(RankingPushOutcome::NothingNew) => {
+                bot.send_message(chat_id, "📭 今日排行的作品都已推送过了")
+                    .await?;
+            }
+            Ok(RankingPushOutcome::ChatInactive) => {
+                bot.send_message(chat_id, "❌ 目标聊天未启用，无法推送")
+                    .await?;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to push ranking subscription {} on demand: {:#}",
+                    subscription.id, e
+                );
+                bot.send_message(chat_id, "❌ 获取排行榜失败，请稍后重试")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Split `day,week` into modes, ignoring empty entries
+fn split_mode_list(arg: &str) -> Vec<&str> {
+    arg.split(',')
+        .map(str::trim)
+        .filter(|mode| !mode.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ranking_date_offset, split_mode_list, RankingStart};
+    use crate::pixiv::model::RankingMode;
+
+    #[test]
+    fn split_mode_list_accepts_aliases() {
+        let modes = split_mode_list("daily,,weekly,original");
+        assert_eq!(modes, ["daily", "weekly", "original"]);
+        assert_eq!(
+            modes
+                .iter()
+                .map(|mode| RankingMode::from_str(mode))
+                .collect::<Vec<_>>(),
+            [
+                Some(RankingMode::Day),
+                Some(RankingMode::Week),
+                Some(RankingMode::WeekOriginal)
+            ]
+        );
+    }
+
+    #[test]
+    fn ranking_start_parses_known_values() {
+        assert_eq!(RankingStart::parse("now"), Some(RankingStart::Now));
+        assert_eq!(
+            RankingStart::parse("Tomorrow"),
+            Some(RankingStart::Tomorrow)
+        );
+        assert_eq!(RankingStart::parse("later"), None);
+    }
+
+    #[test]
+    fn parse_ranking_date_offset_accepts_prev_and_day_counts() {
+        assert_eq!(parse_ranking_date_offset("prev"), Some(1));
+        assert_eq!(parse_ranking_date_offset("Yesterday"), Some(1));
+        assert_eq!(parse_ranking_date_offset("-3"), Some(3));
+        assert_eq!(parse_ranking_date_offset("latest"), Some(0));
+        assert_eq!(parse_ranking_date_offset("-0"), None);
+        assert_eq!(parse_ranking_date_offset("-8"), None);
+        assert_eq!(parse_ranking_date_offset("2026-10-15"), None);
+    }
 }
@@ -0,0 +1,164 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::{TagFilter, TaskType};
+use crate::pixiv::model::RankingMode;
+use crate::utils::args;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode, UserId};
+use teloxide::utils::markdown;
+use tracing::{error, info};
+
+/// 解析 `/setfilter <author_id|mode> <+tag1 -tag2|clear>`
+///
+/// 返回订阅目标与新的标签过滤，`clear` 表示清空过滤条件。
+fn parse_set_filter_args(args: &str) -> Option<(&str, TagFilter)> {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let (target, tags) = parts.split_first()?;
+    match tags {
+        [] => None,
+        ["clear"] => Some((target, TagFilter::default())),
+        _ => Some((target, TagFilter::parse_from_args(tags))),
+    }
+}
+
+impl BotHandler {
+    /// 修改已有订阅的标签过滤 (`/setfilter [ch=<频道ID>] <author_id|mode> <+tag1 -tag2|clear>`)
+    ///
+    /// 直接替换订阅的过滤条件，推送进度等状态保持不变。
+    pub async fn handle_set_filter(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parsed = args::parse_args(&args_str);
+
+        let Some((target, filter_tags)) = parse_set_filter_args(&parsed.remaining) else {
+            bot.send_message(
+                chat_id,
+                "❌ 用法: `/setfilter [ch=<频道ID>] <author_id|mode> <+tag1 -tag2|clear>`",
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+            return Ok(());
+        };
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, user_id, &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let (task_type, task_value, mode) = if target.parse::<u64>().is_ok() {
+            (TaskType::Author, target.to_string(), None)
+        } else if let Some(mode) = RankingMode::from_str(target) {
+            (TaskType::Ranking, mode.as_str().to_string(), Some(mode))
+        } else {
+            bot.send_message(chat_id, "❌ 请提供作者 ID 或排行榜模式")
+                .await?;
+            return Ok(());
+        };
+
+        let task = match self
+            .repo
+            .get_task_by_type_value(task_type, &task_value)
+            .await
+        {
+            Ok(task) => task,
+            Err(e) => {
+                error!("Failed to query {} task {}: {:#}", task_type, task_value, e);
+                bot.send_message(chat_id, "❌ 查询订阅失败").await?;
+                return Ok(());
+            }
+        };
+        let subscription = match task {
+            Some(ref task) => self
+                .repo
+                .get_subscription_by_chat_task(target_chat_id.0, task.id)
+                .await
+                .unwrap_or_else(|e| {
+                    error!(
+                        "Failed to query subscription of task {} in chat {}: {:#}",
+                        task.id, target_chat_id, e
+                    );
+                    None
+                }),
+            None => None,
+        };
+        let (Some(task), Some(subscription)) = (task, subscription) else {
+            bot.send_message(chat_id, format!("❌ 未找到 {} 的订阅", target))
+                .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = self
+            .repo
+            .update_subscription_filter_tags(subscription.id, filter_tags.clone())
+            .await
+        {
+            error!(
+                "Failed to update filter of subscription {}: {:#}",
+                subscription.id, e
+            );
+            bot.send_message(chat_id, "❌ 更新过滤条件失败").await?;
+            return Ok(());
+        }
+        info!(
+            "Subscription {} filter set to '{}'",
+            subscription.id,
+            filter_tags.format_plain()
+        );
+
+        let title = match (mode, task.author_name.as_deref()) {
+            (Some(mode), _) => markdown::escape(mode.display_name()),
+            (None, Some(name)) => format!("*{}* \\(ID: `{}`\\)", markdown::escape(name), target),
+            (None, None) => format!("`{}`", target),
+        };
+        let mut message = if filter_tags.is_empty() {
+            format!("✅ 已清除 {} 的过滤条件", title)
+        } else {
+            format!(
+                "✅ 已更新 {} 的过滤条件\n\n🏷 {}",
+                title,
+                filter_tags.format_for_display()
+            )
+        };
+        if is_channel {
+            message.push_str(&format!("\n📢 频道: `{}`", target_chat_id.0));
+        }
+        bot.send_message(chat_id, message)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_set_filter_args_requires_target_and_tags() {
+        let (target, filter) = parse_set_filter_args("123 +cat -R-18").unwrap();
+        assert_eq!(target, "123");
+        assert_eq!(filter, TagFilter::parse_from_args(&["+cat", "-R-18"]));
+
+        let (target, filter) = parse_set_filter_args("daily clear").unwrap();
+        assert_eq!(target, "daily");
+        assert!(filter.is_empty());
+
+        assert!(parse_set_filter_args("").is_none());
+        assert!(parse_set_filter_args("123").is_none());
+    }
+}
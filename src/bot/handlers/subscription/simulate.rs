@@ -0,0 +1,328 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::entities::subscriptions;
+use crate::db::types::{PushMode, SpoilerMode, TaskType};
+use crate::scheduler::{
+    AuthorSimulation, FilterRejection, SimulatedOutcome, SimulatedPush, SimulatedPushKind,
+};
+use crate::utils::args;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode, UserId};
+use teloxide::utils::markdown;
+use tracing::{error, info, warn};
+
+/// 过滤原因的中文说明
+fn rejection_label(rejection: FilterRejection) -> &'static str {
+    match rejection {
+        FilterRejection::ChatExcludedTag => "聊天排除标签",
+        FilterRejection::SubscriptionTags => "订阅标签过滤",
+        FilterRejection::BlockedAuthor => "作者已屏蔽",
+        FilterRejection::R18Blocked => "未开启 R\\-18",
+        FilterRejection::AiFiltered => "AI 作品过滤",
+    }
+}
+
+/// 模拟推送的发送形式说明 (MarkdownV2)
+fn push_summary(push: &SimulatedPush) -> String {
+    match push.kind {
+        SimulatedPushKind::Images => format!(
+            "📤 将推送作品 `{}`，共 {} 页",
+            push.illust_ids[0],
+            push.image_urls.len()
+        ),
+        SimulatedPushKind::Album => format!(
+            "📤 将以相册推送 {} 个作品 \\(每个作品第一页\\): {}",
+            push.illust_ids.len(),
+            push.illust_ids
+                .iter()
+                .map(|id| format!("`{}`", id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SimulatedPushKind::Ugoira => format!(
+            "📤 将以动图推送作品 `{}` \\(预览仅显示封面\\)",
+            push.illust_ids[0]
+        ),
+        SimulatedPushKind::Restricted => {
+            format!("📤 作品 `{}` 无法访问，将推送文字通知", push.illust_ids[0])
+        }
+    }
+}
+
+/// 模拟报告：订阅设置、每个新作品的过滤结果以及本次推送的内容
+fn build_simulation_report(
+    title: &str,
+    subscription: &subscriptions::Model,
+    simulation: &AuthorSimulation,
+) -> String {
+    let mut lines = vec![
+        format!("🧪 *模拟推送* \\- {}", title),
+        format!(
+            "订阅 \\#{} · 聊天 `{}`",
+            subscription.id, subscription.chat_id
+        ),
+    ];
+    if !subscription.enabled {
+        lines.push("⏸ 订阅已暂停，实际不会推送".to_string());
+    }
+    if !simulation.chat_notified {
+        lines.push("🚫 聊天未启用，实际不会推送".to_string());
+    }
+    if !subscription.filter_tags.is_empty() {
+        lines.push(format!(
+            "🏷 {}",
+            subscription.filter_tags.format_for_display()
+        ));
+    }
+    if subscription.spoiler_mode != SpoilerMode::Auto {
+        lines.push(format!(
+            "🫣 遮罩: {}",
+            subscription.spoiler_mode.display_name()
+        ));
+    }
+    if subscription.push_mode != PushMode::Single {
+        lines.push(format!(
+            "🖼 推送方式: {}",
+            subscription.push_mode.display_name()
+        ));
+    }
+    if subscription.silent {
+        lines.push("🔕 静默推送".to_string());
+    }
+    match simulation.cursor {
+        Some(cursor) => lines.push(format!("📍 上次推送到作品 `{}`", cursor)),
+        None => lines.push("📍 尚未推送过，只会发送最新作品".to_string()),
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "最近 {} 个作品中有 {} 个新作品:",
+        simulation.fetched,
+        simulation.new_illusts.len()
+    ));
+    for (illust, rejection) in &simulation.new_illusts {
+        let verdict = match rejection {
+            None => "✅".to_string(),
+            Some(rejection) => format!("❌ {}", rejection_label(*rejection)),
+        };
+        lines.push(format!(
+            "  {} [{}](https://pixiv\\.net/artworks/{})",
+            verdict,
+            markdown::escape(&illust.title),
+            illust.id
+        ));
+    }
+
+    lines.push(String::new());
+    match &simulation.outcome {
+        SimulatedOutcome::NothingNew => lines.push("📭 没有新作品，不会推送".to_string()),
+        SimulatedOutcome::AllFiltered => {
+            lines.push("🚫 新作品全部被过滤，只会更新推送进度".to_string())
+        }
+        SimulatedOutcome::AlreadySent { illust_id } => lines.push(format!(
+            "♻️ 作品 `{}` 已由本聊天的其他订阅推送过，将跳过",
+            illust_id
+        )),
+        SimulatedOutcome::Push(push) => {
+            lines.push(push_summary(push));
+            lines.push(format!(
+                "遮罩: {} · 通知: {}",
+                if push.spoiler { "是" } else { "否" },
+                if subscription.silent {
+                    "静默"
+                } else {
+                    "正常"
+                }
+            ));
+            lines.push("以下为推送预览 \\(不含按钮\\)".to_string());
+        }
+    }
+
+    lines.join("\n")
+}
+
+impl BotHandler {
+    /// 模拟作者订阅的下一次推送
+    ///
+    /// 使用实时的 Pixiv 数据走一遍作者推送流程，但结果只私聊发送给执行命令的管理员，
+    /// 不发送到订阅的聊天，也不修改订阅状态。用于排查过滤条件和文案问题。
+    pub async fn handle_simulate(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(user_id) = user_id else {
+            return Ok(());
+        };
+        let parsed = args::parse_args(&args_str);
+
+        let Some(author_id) = parsed.remaining.trim().parse::<u64>().ok() else {
+            bot.send_message(chat_id, "❌ 用法: `/simulate [ch=<频道ID>] <author_id>`")
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        let (target_chat_id, _) = match self
+            .resolve_subscription_target(&bot, chat_id, Some(user_id), &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve simulate target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let task = match self
+            .repo
+            .get_task_by_type_value(TaskType::Author, &author_id.to_string())
+            .await
+        {
+            Ok(task) => task,
+            Err(e) => {
+                error!("Failed to query author task {}: {:#}", author_id, e);
+                bot.send_message(chat_id, "❌ 查询订阅失败").await?;
+                return Ok(());
+            }
+        };
+        let subscription = match task {
+            Some(ref task) => self
+                .repo
+                .get_subscription_by_chat_task(target_chat_id.0, task.id)
+                .await
+                .unwrap_or_else(|e| {
+                    error!(
+                        "Failed to query subscription of task {} in chat {}: {:#}",
+                        task.id, target_chat_id, e
+                    );
+                    None
+                }),
+            None => None,
+        };
+        let (Some(task), Some(subscription)) = (task, subscription) else {
+            bot.send_message(chat_id, format!("❌ 未订阅作者 {}", author_id))
+                .await?;
+            return Ok(());
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::Typing).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        info!(
+            "Simulating author subscription {} of chat {} for user {}",
+            subscription.id, target_chat_id, user_id
+        );
+
+        let simulation = match self.author_engine.simulate(&subscription, author_id).await {
+            Ok(simulation) => simulation,
+            Err(e) => {
+                error!(
+                    "Failed to simulate subscription {}: {:#}",
+                    subscription.id, e
+                );
+                bot.send_message(chat_id, "❌ 模拟失败，请稍后重试").await?;
+                return Ok(());
+            }
+        };
+
+        let title = match task.author_name.as_deref() {
+            Some(name) => format!("*{}* \\(ID: `{}`\\)", markdown::escape(name), author_id),
+            None => format!("`{}`", author_id),
+        };
+        let report = build_simulation_report(&title, &subscription, &simulation);
+
+        // The preview goes to the admin only, never to the subscribed chat
+        let private_chat = ChatId(user_id.0 as i64);
+        if let Err(e) = bot
+            .send_message(private_chat, report)
+            .parse_mode(ParseMode::MarkdownV2)
+            .await
+        {
+            warn!(
+                "Failed to send simulation report to user {}: {:#}",
+                user_id, e
+            );
+            bot.send_message(chat_id, "❌ 无法私聊发送模拟结果，请先私聊 Bot")
+                .await?;
+            return Ok(());
+        }
+        if private_chat != chat_id {
+            bot.send_message(chat_id, "📬 模拟结果已私聊发送").await?;
+        }
+
+        let SimulatedOutcome::Push(push) = simulation.outcome else {
+            return Ok(());
+        };
+        if push.image_urls.is_empty() {
+            if let Err(e) = self
+                .notifier
+                .send_text(private_chat, &push.caption, true)
+                .await
+            {
+                warn!(
+                    "Failed to send simulated text push to user {}: {:#}",
+                    user_id, e
+                );
+            }
+            return Ok(());
+        }
+
+        let result = self
+            .notifier
+            .notify_with_images(
+                private_chat,
+                &push.image_urls,
+                Some(&push.caption),
+                push.spoiler,
+            )
+            .await;
+        if !result.is_complete_success() {
+            warn!(
+                "Simulated push partially failed for user {}: {:?}",
+                user_id, result.failed_indices
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_summary_describes_album_and_restricted_pushes() {
+        let album = SimulatedPush {
+            kind: SimulatedPushKind::Album,
+            illust_ids: vec![1, 2],
+            image_urls: vec!["a".to_string(), "b".to_string()],
+            caption: String::new(),
+            spoiler: false,
+        };
+        assert_eq!(
+            push_summary(&album),
+            "📤 将以相册推送 2 个作品 \\(每个作品第一页\\): `1`, `2`"
+        );
+
+        let restricted = SimulatedPush {
+            kind: SimulatedPushKind::Restricted,
+            illust_ids: vec![3],
+            image_urls: Vec::new(),
+            caption: String::new(),
+            spoiler: false,
+        };
+        assert_eq!(
+            push_summary(&restricted),
+            "📤 作品 `3` 无法访问，将推送文字通知"
+        );
+    }
+}
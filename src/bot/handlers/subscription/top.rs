@@ -0,0 +1,194 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::pixiv::model::RankingMode;
+use crate::scheduler::TopPushOutcome;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tracing::{error, info, warn};
+
+/// Works sent by /top when no count is given
+const DEFAULT_TOP_COUNT: usize = 10;
+/// Upper bound for the /top count (one ranking page)
+const MAX_TOP_COUNT: usize = 30;
+
+/// `/top` 参数：排行榜模式之后的可选日期与数量
+#[derive(Debug, PartialEq, Eq)]
+struct TopArgs {
+    date: Option<String>,
+    count: usize,
+}
+
+/// 解析模式之后的参数，日期为 `YYYY-MM-DD`，数量为 1 到 [`MAX_TOP_COUNT`] 的整数
+fn parse_top_args(args: &[&str]) -> Result<TopArgs, String> {
+    let mut parsed = TopArgs {
+        date: None,
+        count: DEFAULT_TOP_COUNT,
+    };
+    let mut count_given = false;
+
+    for arg in args {
+        if let Ok(count) = arg.parse::<usize>() {
+            if count_given {
+                return Err("数量只能指定一次".to_string());
+            }
+            if !(1..=MAX_TOP_COUNT).contains(&count) {
+                return Err(format!("数量需在 1 到 {} 之间", MAX_TOP_COUNT));
+            }
+            parsed.count = count;
+            count_given = true;
+        } else if chrono::NaiveDate::parse_from_str(arg, "%Y-%m-%d").is_ok() {
+            if parsed.date.is_some() {
+                return Err("日期只能指定一次".to_string());
+            }
+            parsed.date = Some(arg.to_string());
+        } else {
+            return Err(format!("无法识别的参数: {}", arg));
+        }
+    }
+
+    Ok(parsed)
+}
+
+impl BotHandler {
+    /// 立即查看排行榜前 N 名，不创建订阅
+    ///
+    /// 应用聊天的排除标签、屏蔽作者与 R-18 设置，文案与排行榜订阅推送一致。
+    pub async fn handle_top(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let parts: Vec<&str> = args_str.split_whitespace().collect();
+        let Some((mode_arg, rest)) = parts.split_first() else {
+            let available_modes = RankingMode::all_modes().join(", ");
+            bot.send_message(
+                chat_id,
+                format!(
+                    "❌ 用法: `/top <mode> [YYYY-MM-DD] [count]`\n可用模式: {}",
+                    markdown::escape(&available_modes)
+                ),
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+            return Ok(());
+        };
+
+        let Some(mode) = RankingMode::from_str(mode_arg) else {
+            let available_modes = RankingMode::all_modes().join(", ");
+            bot.send_message(
+                chat_id,
+                format!("❌ 无效的排行榜模式。可用模式: {}", available_modes),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        let args = match parse_top_args(rest) {
+            Ok(args) => args,
+            Err(reason) => {
+                bot.send_message(chat_id, format!("❌ {}", reason)).await?;
+                return Ok(());
+            }
+        };
+
+        let chat = match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) => chat,
+            Ok(None) => {
+                bot.send_message(chat_id, "❌ 未找到聊天").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to get chat {} for top: {:#}", chat_id, e);
+                bot.send_message(chat_id, "❌ 获取聊天设置失败").await?;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = bot.send_chat_action(chat_id, ChatAction::UploadPhoto).await {
+            warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
+        }
+
+        info!(
+            "Sending top {} of {} ranking ({}) to chat {}",
+            args.count,
+            mode.as_str(),
+            args.date.as_deref().unwrap_or("latest"),
+            chat_id
+        );
+
+        let outcome = self
+            .ranking_engine
+            .push_top(&chat, mode.as_str(), args.date.as_deref(), args.count)
+            .await;
+
+        match outcome {
+            Ok(TopPushOutcome::Sent(result)) => {
+                if result.is_complete_failure() {
+                    bot.send_message(chat_id, "❌ 发送排行榜作品失败").await?;
+                } else if !result.is_complete_success() {
+                    warn!(
+                        "Top {} ranking partially failed for chat {}: {:?}",
+                        mode.as_str(),
+                        chat_id,
+                        result.failed_indices
+                    );
+                }
+            }
+            Ok(TopPushOutcome::Empty) => {
+                bot.send_message(chat_id, format!("📭 {} 暂无排行数据", mode.display_name()))
+                    .await?;
+            }
+            Ok(TopPushOutcome::AllFiltered) => {
+                bot.send_message(
+                    chat_id,
+                    format!(
+                        "📭 {} 前 {} 名的作品均被当前聊天的过滤设置排除",
+                        mode.display_name(),
+                        args.count
+                    ),
+                )
+                .await?;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get {} ranking for chat {}: {:#}",
+                    mode.as_str(),
+                    chat_id,
+                    e
+                );
+                bot.send_message(chat_id, "❌ 获取排行榜失败").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_top_args_accepts_date_and_count_in_any_order() {
+        assert_eq!(
+            parse_top_args(&[]),
+            Ok(TopArgs {
+                date: None,
+                count: DEFAULT_TOP_COUNT,
+            })
+        );
+        assert_eq!(
+            parse_top_args(&["5", "2026-01-02"]),
+            Ok(TopArgs {
+                date: Some("2026-01-02".to_string()),
+                count: 5,
+            })
+        );
+        assert!(parse_top_args(&["0"]).is_err());
+        assert!(parse_top_args(&["31"]).is_err());
+        assert!(parse_top_args(&["2026-13-01"]).is_err());
+        assert!(parse_top_args(&["3", "4"]).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::db::types::TaskType;
+use crate::utils::args;
+use sea_orm::Iterable;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, UserId};
+use tracing::{error, info, warn};
+
+/// Callback data prefix for the /unsuball confirmation buttons.
+/// Format: `unsuball:<y|n>:<scope>:<user_id>:<chat_id>`.
+pub const UNSUB_ALL_CALLBACK_PREFIX: &str = "unsuball:";
+
+/// `/unsuball` 清除的订阅类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsubAllScope {
+    All,
+    Authors,
+    Rankings,
+    Ehentai,
+}
+
+impl UnsubAllScope {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg.to_lowercase().as_str() {
+            "" | "all" => Some(Self::All),
+            "authors" | "author" => Some(Self::Authors),
+            "rank" | "ranking" => Some(Self::Rankings),
+            "eh" => Some(Self::Ehentai),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Authors => "authors",
+            Self::Rankings => "rank",
+            Self::Ehentai => "eh",
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            Self::All => "全部订阅",
+            Self::Authors => "作者订阅",
+            Self::Rankings => "排行榜订阅",
+            Self::Ehentai => "E-Hentai 订阅",
+        }
+    }
+
+    fn task_types(self) -> Vec<TaskType> {
+        match self {
+            Self::All => TaskType::iter().collect(),
+            Self::Authors => vec![TaskType::Author],
+            Self::Rankings => vec![TaskType::Ranking],
+            Self::Ehentai => vec![TaskType::Ehentai],
+        }
+    }
+}
+
+/// Button pressed on the /unsuball confirmation message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsubAllConfirmation {
+    pub confirmed: bool,
+    pub scope: UnsubAllScope,
+    /// User who ran the command; nobody else may answer the prompt
+    pub user_id: UserId,
+    pub target_chat_id: ChatId,
+}
+
+fn unsub_all_callback_data(confirmation: &UnsubAllConfirmation) -> String {
+    format!(
+        "{}{}:{}:{}:{}",
+        UNSUB_ALL_CALLBACK_PREFIX,
+        if confirmation.confirmed { "y" } else { "n" },
+        confirmation.scope.as_str(),
+        confirmation.user_id.0,
+        confirmation.target_chat_id.0
+    )
+}
+
+/// Parse callback data produced for the /unsuball confirmation buttons
+pub fn parse_unsub_all_callback_data(data: &str) -> Option<UnsubAllConfirmation> {
+    let mut parts = data.strip_prefix(UNSUB_ALL_CALLBACK_PREFIX)?.split(':');
+    let confirmed = match parts.next()? {
+        "y" => true,
+        "n" => false,
+        _ => return None,
+    };
+    let scope = match parts.next()? {
+        "" => return None,
+        scope => UnsubAllScope::from_arg(scope)?,
+    };
+    let user_id = UserId(parts.next()?.parse().ok()?);
+    let target_chat_id = ChatId(parts.next()?.parse().ok()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(UnsubAllConfirmation {
+        confirmed,
+        scope,
+        user_id,
+        target_chat_id,
+    })
+}
+
+impl BotHandler {
+    /// 批量取消订阅 (`/unsuball [ch=<频道ID>] [authors|rank|eh]`，不填则为全部类型)
+    ///
+    /// 只回复一条带确认按钮的消息，点击确认后才会删除。
+    pub async fn handle_unsub_all(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        user_id: Option<UserId>,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(user_id) = user_id else {
+            return Ok(());
+        };
+        let parsed = args::parse_args(&args_str);
+
+        let Some(scope) = UnsubAllScope::from_arg(parsed.remaining.trim()) else {
+            bot.send_message(
+                chat_id,
+                "❌ 用法: /unsuball [ch=<频道ID>] [authors|rank|eh]\n不填类型时取消全部订阅",
+            )
+            .await?;
+            return Ok(());
+        };
+
+        let (target_chat_id, is_channel) = match self
+            .resolve_subscription_target(&bot, chat_id, Some(user_id), &parsed)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Failed to resolve subscription target in chat {}: {:#}",
+                    chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 频道ID无效或无法访问").await?;
+                return Ok(());
+            }
+        };
+
+        let task_types = scope.task_types();
+        let count = match self.repo.list_subscriptions_by_chat(target_chat_id.0).await {
+            Ok(subscriptions) => subscriptions
+                .iter()
+                .filter(|(_, task)| task_types.contains(&task.r#type))
+                .count(),
+            Err(e) => {
+                error!(
+                    "Failed to list subscriptions for chat {}: {:#}",
+                    target_chat_id, e
+                );
+                bot.send_message(chat_id, "❌ 获取订阅列表失败").await?;
+                return Ok(());
+            }
+        };
+
+        if count == 0 {
+            bot.send_message(chat_id, format!("📭 没有可取消的{}", scope.display_name()))
+                .await?;
+            return Ok(());
+        }
+
+        let confirmation = UnsubAllConfirmation {
+            confirmed: true,
+            scope,
+            user_id,
+            target_chat_id,
+        };
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("✅ 确认取消", unsub_all_callback_data(&confirmation)),
+            InlineKeyboardButton::callback(
+                "取消",
+                unsub_all_callback_data(&UnsubAllConfirmation {
+                    confirmed: false,
+                    ..confirmation
+                }),
+            ),
+        ]]);
+
+        let channel_suffix = if is_channel {
+            format!("\n📢 频道: {}", target_chat_id.0)
+        } else {
+            String::new()
+        };
+        bot.send_message(
+            chat_id,
+            format!(
+                "⚠️ 确定要取消{}吗？共 {} 条，此操作无法撤销{}",
+                scope.display_name(),
+                count,
+                channel_suffix
+            ),
+        )
+        .reply_markup(keyboard)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 处理 `/unsuball` 确认消息的按钮
+    pub async fn handle_unsub_all_callback(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        message_id: MessageId,
+        confirmation: UnsubAllConfirmation,
+    ) -> ResponseResult<()> {
+        if !confirmation.confirmed {
+            bot.edit_message_text(chat_id, message_id, "已取消操作")
+                .await?;
+            return Ok(());
+        }
+
+        let target_chat_id = confirmation.target_chat_id;
+        let is_channel = target_chat_id != chat_id;
+        if is_channel {
+            // Channel rights may have changed since the prompt was sent
+            let parsed = args::parse_args(&format!("ch={}", target_chat_id.0));
+            if let Err(e) = self
+                .resolve_subscription_target(&bot, chat_id, Some(confirmation.user_id), &parsed)
+                .await
+            {
+                warn!(
+                    "User {} can no longer manage channel {}: {}",
+                    confirmation.user_id, target_chat_id, e
+                );
+                bot.edit_message_text(chat_id, message_id, "❌ 频道ID无效或无法访问")
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        let scope = confirmation.scope;
+        let message = match self
+            .repo
+            .delete_chat_subscriptions_and_cancel_queue(target_chat_id.0, &scope.task_types())
+            .await
+        {
+            Ok(cleared) if cleared.removed == 0 => {
+                format!("📭 没有可取消的{}", scope.display_name())
+            }
+            Ok(cleared) => {
+                info!(
+                    "User {} cleared {} {} subscriptions of chat {} ({} tasks deleted)",
+                    confirmation.user_id,
+                    cleared.removed,
+                    scope.as_str(),
+                    target_chat_id,
+                    cleared.deleted_tasks
+                );
+                let mut message =
+                    format!("✅ 已取消 {} 条{}", cleared.removed, scope.display_name());
+                if cleared.deleted_tasks > 0 {
+                    message.push_str(&format!(
+                        "，清理了 {} 个无人订阅的任务",
+                        cleared.deleted_tasks
+                    ));
+                }
+                if is_channel {
+                    message.push_str(&format!("\n📢 频道: {}", target_chat_id.0));
+                }
+                message
+            }
+            Err(e) => {
+                error!(
+                    "Failed to clear {} subscriptions of chat {}: {:#}",
+                    scope.as_str(),
+                    target_chat_id,
+                    e
+                );
+                "❌ 取消订阅失败".to_string()
+            }
+        };
+
+        bot.edit_message_text(chat_id, message_id, message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsub_all_callback_data_round_trips() {
+        for scope in [
+            UnsubAllScope::All,
+            UnsubAllScope::Authors,
+            UnsubAllScope::Rankings,
+            UnsubAllScope::Ehentai,
+        ] {
+            let confirmation = UnsubAllConfirmation {
+                confirmed: scope != UnsubAllScope::All,
+                scope,
+                user_id: UserId(1234567890),
+                target_chat_id: ChatId(-1001234567890),
+            };
+            let data = unsub_all_callback_data(&confirmation);
+            assert!(data.len() <= 64, "callback data too long: {}", data);
+            assert_eq!(parse_unsub_all_callback_data(&data), Some(confirmation));
+        }
+
+        assert_eq!(parse_unsub_all_callback_data("unsuball:x:all:1:-100"), None);
+        assert_eq!(parse_unsub_all_callback_data("unsuball:y::1:-100"), None);
+        assert_eq!(
+            parse_unsub_all_callback_data("unsuball:y:tags:1:-100"),
+            None
+        );
+        assert_eq!(parse_unsub_all_callback_data("fwdsub:-100:a:1"), None);
+    }
+}
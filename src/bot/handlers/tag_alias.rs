@@ -0,0 +1,213 @@
+use super::logs::chunk_lines;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use crate::utils::tag;
+use std::collections::BTreeMap;
+use std::path::Path;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+use teloxide::utils::markdown;
+use tracing::{error, info, warn};
+
+/// Budget per message in UTF-16 units, leaving room for the code block and escapes
+const ALIAS_MESSAGE_BUDGET: usize = 3500;
+
+/// Groups listed by `/tagalias list`; a tag argument narrows an imported dataset down
+const MAX_LISTED_GROUPS: usize = 100;
+
+const TAG_ALIAS_USAGE: &str = "❌ 用法:\n\
+    `/tagalias list [标签]` \\- 查看别名\n\
+    `/tagalias add <标签> <别名...>` \\- 添加别名\n\
+    `/tagalias remove <别名...>` \\- 删除别名\n\
+    `/tagalias import` \\- 重新导入别名数据集文件";
+
+#[derive(Debug, PartialEq, Eq)]
+enum TagAliasAction {
+    List(Option<String>),
+    Add(String, Vec<String>),
+    Remove(Vec<String>),
+    Import,
+}
+
+/// Parse `/tagalias` arguments into an action with normalized tags, None when invalid
+fn parse_tag_alias_args(args: &str) -> Option<TagAliasAction> {
+    let mut words = args.split_whitespace();
+    let action = words.next().map(str::to_ascii_lowercase);
+    let tags: Vec<String> = words
+        .map(tag::normalize_tag)
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    match (action.as_deref(), tags.as_slice()) {
+        (None | Some("list"), []) => Some(TagAliasAction::List(None)),
+        (Some("list"), [tag]) => Some(TagAliasAction::List(Some(tag.clone()))),
+        (Some("add"), [tag, aliases @ ..]) if !aliases.is_empty() => {
+            Some(TagAliasAction::Add(tag.clone(), aliases.to_vec()))
+        }
+        (Some("remove"), aliases) if !aliases.is_empty() => {
+            Some(TagAliasAction::Remove(aliases.to_vec()))
+        }
+        (Some("import"), []) => Some(TagAliasAction::Import),
+        _ => None,
+    }
+}
+
+impl BotHandler {
+    /// 管理标签别名：查看、添加、删除、重新导入数据集 (仅 Owner)
+    pub async fn handle_tag_alias(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        args_str: String,
+    ) -> ResponseResult<()> {
+        let Some(action) = parse_tag_alias_args(&args_str) else {
+            bot.send_message(chat_id, TAG_ALIAS_USAGE)
+                .parse_mode(ParseMode::MarkdownV2)
+                .await?;
+            return Ok(());
+        };
+
+        let reply = match action {
+            TagAliasAction::List(filter) => {
+                return self.send_tag_alias_list(bot, chat_id, filter).await;
+            }
+            TagAliasAction::Add(tag_name, aliases) => {
+                match self.repo.add_tag_aliases(&tag_name, &aliases).await {
+                    Ok(canonical) => {
+                        info!("Owner mapped tag aliases {:?} to {}", aliases, canonical);
+                        format!(
+                            "✅ 已将 {} 设为 #{} 的别名",
+                            aliases
+                                .iter()
+                                .map(|alias| format!("#{}", alias))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                            canonical
+                        )
+                    }
+                    Err(e) => {
+                        error!("Failed to add tag aliases: {:#}", e);
+                        "❌ 添加别名失败".to_string()
+                    }
+                }
+            }
+            TagAliasAction::Remove(aliases) => match self.repo.remove_tag_aliases(&aliases).await {
+                Ok(0) => "❌ 没有找到这些别名".to_string(),
+                Ok(removed) => format!("✅ 已删除 {} 个别名", removed),
+                Err(e) => {
+                    error!("Failed to remove tag aliases: {:#}", e);
+                    "❌ 删除别名失败".to_string()
+                }
+            },
+            TagAliasAction::Import => {
+                let Some(path) = &self.tag_alias_file else {
+                    bot.send_message(chat_id, "❌ 未配置 content.tag_alias_file")
+                        .await?;
+                    return Ok(());
+                };
+                match self.repo.import_tag_alias_file(Path::new(path)).await {
+                    Ok(added) => {
+                        format!("✅ 已从数据集导入 {} 个新别名（已有别名保持不变）", added)
+                    }
+                    Err(e) => {
+                        error!("Failed to import tag aliases: {:#}", e);
+                        "❌ 导入别名失败，请查看日志".to_string()
+                    }
+                }
+            }
+        };
+
+        // Filters pick up the change right away
+        if let Err(e) = self.repo.reload_tag_aliases().await {
+            error!("Failed to reload tag aliases: {:#}", e);
+        }
+
+        bot.send_message(chat_id, reply).await?;
+        Ok(())
+    }
+
+    async fn send_tag_alias_list(
+        &self,
+        bot: ThrottledBot,
+        chat_id: ChatId,
+        filter: Option<String>,
+    ) -> ResponseResult<()> {
+        let rows = match self.repo.list_tag_aliases().await {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to list tag aliases: {:#}", e);
+                bot.send_message(chat_id, "❌ 获取别名失败").await?;
+                return Ok(());
+            }
+        };
+
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for row in rows {
+            groups.entry(row.tag).or_default().push(row.alias);
+        }
+        if let Some(filter) = &filter {
+            groups.retain(|tag, aliases| tag == filter || aliases.contains(filter));
+        }
+
+        if groups.is_empty() {
+            bot.send_message(chat_id, "📭 暂无标签别名").await?;
+            return Ok(());
+        }
+
+        let mut lines: Vec<String> = groups
+            .iter()
+            .take(MAX_LISTED_GROUPS)
+            .map(|(tag, aliases)| format!("{} = {}", tag, aliases.join(", ")))
+            .collect();
+        if groups.len() > MAX_LISTED_GROUPS {
+            lines.push(format!(
+                "... 另有 {} 组，使用 /tagalias list <标签> 查看",
+                groups.len() - MAX_LISTED_GROUPS
+            ));
+        }
+
+        for chunk in chunk_lines(&lines, ALIAS_MESSAGE_BUDGET) {
+            if let Err(e) = bot
+                .send_message(chat_id, markdown::code_block(&chunk))
+                .parse_mode(ParseMode::MarkdownV2)
+                .await
+            {
+                warn!("Failed to send tag aliases to chat {}: {:#}", chat_id, e);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tag_alias_args_normalizes_tags() {
+        assert_eq!(parse_tag_alias_args(""), Some(TagAliasAction::List(None)));
+        assert_eq!(
+            parse_tag_alias_args("list Genshin-Impact"),
+            Some(TagAliasAction::List(Some("genshinimpact".to_string())))
+        );
+        assert_eq!(
+            parse_tag_alias_args("ADD 原神 Genshin genshin_impact"),
+            Some(TagAliasAction::Add(
+                "原神".to_string(),
+                vec!["genshin".to_string(), "genshin_impact".to_string()]
+            ))
+        );
+        assert_eq!(
+            parse_tag_alias_args("remove genshin"),
+            Some(TagAliasAction::Remove(vec!["genshin".to_string()]))
+        );
+        assert_eq!(parse_tag_alias_args("import"), Some(TagAliasAction::Import));
+
+        assert_eq!(parse_tag_alias_args("add 原神"), None);
+        assert_eq!(parse_tag_alias_args("remove"), None);
+        assert_eq!(parse_tag_alias_args("import now"), None);
+        assert_eq!(parse_tag_alias_args("rename a b"), None);
+    }
+}
@@ -0,0 +1,182 @@
+use super::UNBLUR_CALLBACK_DATA;
+use crate::bot::notifier::ThrottledBot;
+use crate::bot::BotHandler;
+use anyhow::{Context, Result};
+use teloxide::prelude::*;
+use teloxide::types::{
+    CallbackQuery, InlineKeyboardButtonKind, InlineKeyboardMarkup, InputFile, InputMedia,
+    InputMediaAnimation, InputMediaPhoto, UserId,
+};
+use tracing::{error, info};
+
+/// Result of an unblur request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnblurOutcome {
+    Done,
+    /// The user is neither a bot admin nor an admin of the chat
+    NotAllowed,
+    /// The message is not a recorded subscription push
+    NotAPush,
+    /// The message carries no photo or animation
+    Unsupported,
+}
+
+impl BotHandler {
+    /// 取消推送消息的遮罩 (仅聊天管理员或 Bot 管理员)
+    ///
+    /// 复用消息中已有的 file_id 编辑媒体，不会重新下载图片。
+    pub async fn handle_unblur_callback(
+        &self,
+        bot: ThrottledBot,
+        q: CallbackQuery,
+    ) -> ResponseResult<()> {
+        let outcome = match q.regular_message() {
+            Some(msg) => self.unblur_message(&bot, msg, q.from.id).await,
+            None => Ok(UnblurOutcome::Unsupported),
+        };
+
+        let notice = match outcome {
+            Ok(UnblurOutcome::Done) => "👁 已取消遮罩",
+            Ok(UnblurOutcome::NotAllowed) => "❌ 仅管理员可以取消遮罩",
+            Ok(UnblurOutcome::NotAPush) => "❌ 只能取消订阅推送的遮罩",
+            Ok(UnblurOutcome::Unsupported) => "❌ 此消息无法取消遮罩",
+            Err(e) => {
+                error!("Failed to unblur message for user {}: {:#}", q.from.id, e);
+                "❌ 取消遮罩失败"
+            }
+        };
+
+        bot.answer_callback_query(q.id).text(notice).await?;
+        Ok(())
+    }
+
+    async fn unblur_message(
+        &self,
+        bot: &ThrottledBot,
+        msg: &Message,
+        user_id: UserId,
+    ) -> Result<UnblurOutcome> {
+        let chat_id = msg.chat.id;
+
+        if !self.can_unblur(bot, &msg.chat, user_id).await? {
+            return Ok(UnblurOutcome::NotAllowed);
+        }
+
+        let Some((record, _)) = self
+            .repo
+            .get_message_with_subscription(chat_id.0, msg.id.0)
+            .await?
+        else {
+            return Ok(UnblurOutcome::NotAPush);
+        };
+
+        let caption = msg.caption().map(str::to_owned);
+        let entities = msg.caption_entities().map(<[_]>::to_vec);
+        let media = if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+            let mut media = InputMediaPhoto::new(InputFile::file_id(photo.file.id.clone()));
+            if let Some(caption) = caption {
+                media = media.caption(caption);
+            }
+            if let Some(entities) = entities {
+                media = media.caption_entities(entities);
+            }
+            InputMedia::Photo(media)
+        } else if let Some(animation) = msg.animation() {
+            let mut media = InputMediaAnimation::new(InputFile::file_id(animation.file.id.clone()));
+            if let Some(caption) = caption {
+                media = media.caption(caption);
+            }
+            if let Some(entities) = entities {
+                media = media.caption_entities(entities);
+            }
+            InputMedia::Animation(media)
+        } else {
+            return Ok(UnblurOutcome::Unsupported);
+        };
+
+        let mut req = bot.edit_message_media(chat_id, msg.id, media);
+        if let Some(keyboard) = msg.reply_markup().and_then(without_unblur_button) {
+            req = req.reply_markup(keyboard);
+        }
+        req.await.context("Failed to edit message media")?;
+
+        info!(
+            "User {} unblurred message {} (illust {:?}) in chat {}",
+            user_id, msg.id, record.illust_id, chat_id
+        );
+        Ok(UnblurOutcome::Done)
+    }
+
+    /// Bot admins anywhere; otherwise the user of a private chat or an admin of the group
+    async fn can_unblur(
+        &self,
+        bot: &ThrottledBot,
+        chat: &teloxide::types::Chat,
+        user_id: UserId,
+    ) -> Result<bool> {
+        let is_bot_admin = self
+            .repo
+            .get_user(user_id.0 as i64)
+            .await?
+            .is_some_and(|user| user.role.is_admin());
+        if is_bot_admin {
+            return Ok(true);
+        }
+
+        if chat.is_private() {
+            return Ok(chat.id.0 == user_id.0 as i64);
+        }
+
+        let member = bot
+            .get_chat_member(chat.id, user_id)
+            .await
+            .context("Failed to get chat member")?;
+        Ok(member.is_privileged())
+    }
+}
+
+/// The message's keyboard without the unblur button, None if nothing is left
+fn without_unblur_button(keyboard: &InlineKeyboardMarkup) -> Option<InlineKeyboardMarkup> {
+    let rows: Vec<_> = keyboard
+        .inline_keyboard
+        .iter()
+        .map(|row| {
+            row.iter()
+                .filter(|button| {
+                    !matches!(
+                        &button.kind,
+                        InlineKeyboardButtonKind::CallbackData(data) if data == UNBLUR_CALLBACK_DATA
+                    )
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect();
+
+    (!rows.is_empty()).then(|| InlineKeyboardMarkup::new(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::types::InlineKeyboardButton;
+
+    #[test]
+    fn without_unblur_button_keeps_other_buttons() {
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("📥 下载", "dl:1"),
+            InlineKeyboardButton::callback("👁 取消遮罩", UNBLUR_CALLBACK_DATA),
+        ]]);
+        let stripped = without_unblur_button(&keyboard).unwrap();
+        assert_eq!(stripped.inline_keyboard.len(), 1);
+        assert_eq!(stripped.inline_keyboard[0].len(), 1);
+        assert_eq!(stripped.inline_keyboard[0][0].text, "📥 下载");
+
+        let only_unblur = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            "👁 取消遮罩",
+            UNBLUR_CALLBACK_DATA,
+        )]]);
+        assert!(without_unblur_button(&only_unblur).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 //! Pixiv 链接解析与处理
 //!
-//! 处理用户发送的 Pixiv 作品链接和作者链接
+//! 处理用户发送的 Pixiv 作品链接和作者链接，包括 pixiv.me 短链接、
+//! i.pximg.net 图片直链和旧版 `member_illust.php` 链接
 
 use booru_client::BooruEngineType;
 use regex::Regex;
@@ -9,22 +10,38 @@ use std::sync::LazyLock;
 use crate::booru::BooruSiteRegistry;
 
 /// Pixiv 作品链接正则表达式
-/// 匹配格式: https://www.pixiv.net/artworks/126608911
-static ILLUST_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"https?://(?:www\.)?pixiv\.net/(?:en/)?artworks/(\d+)").unwrap());
+/// 匹配格式:
+/// - https://www.pixiv.net/artworks/126608911 (或 /en/artworks/、/i/ 短链接)
+/// - https://www.pixiv.net/member_illust.php?mode=medium&illust_id=126608911
+/// - https://i.pximg.net/img-original/img/2024/01/01/00/00/00/126608911_p0.png
+static ILLUST_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"https?://(?:www\.)?pixiv\.net/(?:(?:en/)?artworks|i)/(\d+)",
+        r"|https?://(?:www\.)?pixiv\.net/member_illust\.php\?[^\s]*?\billust_id=(\d+)",
+        r"|https?://i\.pximg\.net/[^\s]*?/(\d+)_(?:p\d+|ugoira\d+)",
+    ))
+    .unwrap()
+});
 
 /// Pixiv 用户链接正则表达式
 /// 匹配格式: https://www.pixiv.net/users/33611048
 static USER_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"https?://(?:www\.)?pixiv\.net/(?:en/)?users/(\d+)").unwrap());
 
+/// pixiv.me 短链接正则表达式 (需要解析用户名)
+/// 匹配格式: https://pixiv.me/username
+static PIXIV_ME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"https?://pixiv\.me/([\w-]+)").unwrap());
+
 /// 解析到的 Pixiv 链接类型
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PixivLink {
     /// 作品链接，包含作品 ID
     Illust(u64),
     /// 用户链接，包含用户 ID
     User(u64),
+    /// pixiv.me 短链接，包含待解析的用户名
+    Username(String),
 }
 
 /// 从文本中解析所有 Pixiv 链接
@@ -33,9 +50,10 @@ pub enum PixivLink {
 pub fn parse_pixiv_links(text: &str) -> Vec<PixivLink> {
     let mut links = Vec::new();
 
-    // 解析作品链接
+    // 解析作品链接 (每种格式各占一个捕获组)
     for caps in ILLUST_REGEX.captures_iter(text) {
-        if let (Some(full_match), Some(id_str)) = (caps.get(0), caps.get(1)) {
+        let id_str = caps.iter().skip(1).flatten().next();
+        if let (Some(full_match), Some(id_str)) = (caps.get(0), id_str) {
             if let Ok(id) = id_str.as_str().parse::<u64>() {
                 links.push((full_match.start(), PixivLink::Illust(id)));
             }
@@ -51,6 +69,16 @@ pub fn parse_pixiv_links(text: &str) -> Vec<PixivLink> {
         }
     }
 
+    // 解析 pixiv.me 短链接
+    for caps in PIXIV_ME_REGEX.captures_iter(text) {
+        if let (Some(full_match), Some(name)) = (caps.get(0), caps.get(1)) {
+            links.push((
+                full_match.start(),
+                PixivLink::Username(name.as_str().to_string()),
+            ));
+        }
+    }
+
     links.sort_by_key(|(start, _)| *start);
     links.into_iter().map(|(_, link)| link).collect()
 }
@@ -182,6 +210,34 @@ mod tests {
         assert_eq!(links.len(), 2);
     }
 
+    #[test]
+    fn test_parse_short_legacy_and_image_links() {
+        let text = "https://www.pixiv.net/i/1 \
+            https://www.pixiv.net/member_illust.php?mode=medium&illust_id=2 \
+            https://i.pximg.net/img-original/img/2024/01/01/00/00/00/3_p0.png \
+            https://i.pximg.net/c/250x250_80_a2/img-master/img/2024/01/01/00/00/00/4_p1_square1200.jpg \
+            https://i.pximg.net/img-zip-ugoira/img/2024/01/01/00/00/00/5_ugoira600x600.zip \
+            https://pixiv.me/some-artist";
+        let links = parse_pixiv_links(text);
+        assert_eq!(
+            links,
+            [
+                PixivLink::Illust(1),
+                PixivLink::Illust(2),
+                PixivLink::Illust(3),
+                PixivLink::Illust(4),
+                PixivLink::Illust(5),
+                PixivLink::Username("some-artist".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pximg_user_profile_images_are_ignored() {
+        let text = "https://i.pximg.net/user-profile/img/2024/01/01/00/00/00/123_abc_170.jpg";
+        assert!(parse_pixiv_links(text).is_empty());
+    }
+
     use crate::booru::BooruSiteRegistry;
     use crate::config::BooruSiteConfig;
 
@@ -1,8 +1,13 @@
+use crate::bot::link_handler::parse_pixiv_links;
+use crate::bot::notifier::ThrottledBot;
+use crate::config::CommandCooldownConfig;
 use crate::db::entities::{chats, users};
 use crate::db::repo::Repo;
 use crate::db::types::{Tags, UserRole};
 use anyhow::{Context, Result};
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use teloxide::dispatching::DpHandlerDescription;
 use teloxide::dptree::{self, Handler};
 use teloxide::prelude::*;
@@ -31,6 +36,107 @@ impl UserChatContext {
     }
 }
 
+// ============================================================================
+// CommandCooldown - 高开销命令限流
+// ============================================================================
+
+/// 超过该数量的限流记录时清理已过期的条目
+const COOLDOWN_PRUNE_THRESHOLD: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum CooldownKey {
+    User(i64),
+    Chat(i64),
+}
+
+/// 按用户和按聊天的滑动窗口限流器
+///
+/// 每个窗口内同一用户、同一聊天可执行的高开销命令次数分别受限，
+/// 作为 dptree 依赖注入，所有 Bot 共享。
+#[derive(Clone)]
+pub struct CommandCooldown {
+    config: CommandCooldownConfig,
+    hits: Arc<Mutex<HashMap<CooldownKey, VecDeque<Instant>>>>,
+}
+
+impl CommandCooldown {
+    pub fn new(config: CommandCooldownConfig) -> Self {
+        Self {
+            config,
+            hits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 记录一次高开销操作；超出限制时不记录，返回距下次可用的时间
+    fn try_acquire(&self, user_id: i64, chat_id: i64, now: Instant) -> Result<(), Duration> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let window = Duration::from_secs(self.config.window_secs);
+        let limits = [
+            (CooldownKey::User(user_id), self.config.per_user),
+            (CooldownKey::Chat(chat_id), self.config.per_chat),
+        ];
+
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+        if hits.len() > COOLDOWN_PRUNE_THRESHOLD {
+            hits.retain(|_, times| {
+                times
+                    .back()
+                    .is_some_and(|last| now.duration_since(*last) < window)
+            });
+        }
+
+        let mut wait = Duration::ZERO;
+        for (key, limit) in limits {
+            // 0 表示不限制
+            if limit == 0 {
+                continue;
+            }
+            let times = hits.entry(key).or_default();
+            while times
+                .front()
+                .is_some_and(|first| now.duration_since(*first) >= window)
+            {
+                times.pop_front();
+            }
+            if times.len() >= limit {
+                let oldest = times[times.len() - limit];
+                wait = wait.max(window.saturating_sub(now.duration_since(oldest)));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for (key, limit) in limits {
+            if limit > 0 {
+                hits.entry(key).or_default().push_back(now);
+            }
+        }
+        Ok(())
+    }
+
+    /// 管理员不受限制；被限流时返回剩余冷却时间
+    fn check(&self, ctx: &UserChatContext) -> Option<Duration> {
+        if ctx.user_role().is_admin() {
+            return None;
+        }
+        let wait = self
+            .try_acquire(ctx.user.id, ctx.chat.id, Instant::now())
+            .err()?;
+        info!(
+            "User {} in chat {} is on command cooldown for {:?}",
+            ctx.user.id, ctx.chat.id, wait
+        );
+        Some(wait)
+    }
+}
+
+/// 被限流的操作距下次可用的时间，注入给冷却提示处理器
+#[derive(Clone, Copy, Debug)]
+pub struct CooldownWait(pub Duration);
+
 // ============================================================================
 // 中间件过滤器
 // ============================================================================
@@ -39,11 +145,12 @@ impl UserChatContext {
 ///
 /// 从消息中提取用户和聊天信息，在数据库中创建或更新记录，
 /// 然后将 `UserChatContext` 注入到依赖链中供后续处理器使用。
+/// 收到消息的 Bot 会被记录为该聊天的推送 Bot。
 ///
 /// **依赖要求:**
+/// - `ThrottledBot` - 收到消息的 Bot
 /// - `Message` - 当前消息
-/// - `Arc<Repo>` - 数据库仓库
-/// - `BotHandler` - Bot 处理器（获取配置）
+/// - `BotHandler` - Bot 处理器（获取配置和数据库仓库）
 ///
 /// **注入依赖:**
 /// - `UserChatContext` - 用户和聊天上下文
@@ -53,13 +160,16 @@ where
     Output: Send + Sync + 'static,
 {
     dptree::filter_map_async(
-        move |message: Message, repo: Arc<Repo>, handler: super::BotHandler| async move {
-            match ensure_user_and_chat(&message, &repo, &handler).await {
+        move |bot: ThrottledBot, message: Message, handler: super::BotHandler| async move {
+            match ensure_user_and_chat(&message, &handler.repo, &handler).await {
                 Ok(ctx) => {
                     info!(
                         "User {} (role: {:?}) in chat {} (enabled: {})",
                         ctx.user.id, ctx.user.role, ctx.chat.id, ctx.chat.enabled
                     );
+                    handler
+                        .remember_chat_bot(&bot, message.chat.id, ctx.chat.bot_id)
+                        .await;
                     Some(ctx)
                 }
                 Err(e) => {
@@ -110,7 +220,7 @@ async fn ensure_user_and_chat(
     let chat_title = msg.chat.title().map(|s| s.to_string());
 
     // Convert default sensitive tags to Tags for new chats
-    let default_sensitive_tags = Tags::from(handler.default_sensitive_tags.clone());
+    let default_sensitive_tags = Tags::from(handler.default_sensitive_tags());
 
     // Upsert chat - new chats get enabled status based on bot mode
     let chat = repo
@@ -473,11 +583,131 @@ where
     )
 }
 
+/// 高开销命令的冷却检查
+///
+/// 此过滤器在 `filter_chat_accessible` 之后执行。命令未被限流时记录本次使用并过滤掉，
+/// 交由后续的命令处理器执行；被限流时注入剩余冷却时间。
+///
+/// **依赖要求:**
+/// - `Command` - 解析后的命令
+/// - `UserChatContext` - 用户和聊天上下文
+/// - `CommandCooldown` - 限流器
+///
+/// **注入依赖:**
+/// - `CooldownWait` - 剩余冷却时间
+#[must_use]
+pub fn filter_command_on_cooldown<Output>() -> Handler<'static, Output, DpHandlerDescription>
+where
+    Output: Send + Sync + 'static,
+{
+    dptree::filter_map(
+        move |cmd: super::Command, ctx: UserChatContext, cooldown: CommandCooldown| {
+            if !cmd.is_expensive() {
+                return None;
+            }
+            cooldown.check(&ctx).map(CooldownWait)
+        },
+    )
+}
+
+/// Pixiv 链接预览的冷却检查
+///
+/// 与 `filter_command_on_cooldown` 相同，只对会触发预览的消息生效
+/// （聊天开启了链接预览且消息包含 Pixiv 链接）。
+///
+/// **依赖要求:**
+/// - `String` - 消息文本
+/// - `UserChatContext` - 用户和聊天上下文
+/// - `CommandCooldown` - 限流器
+///
+/// **注入依赖:**
+/// - `CooldownWait` - 剩余冷却时间
+#[must_use]
+pub fn filter_link_on_cooldown<Output>() -> Handler<'static, Output, DpHandlerDescription>
+where
+    Output: Send + Sync + 'static,
+{
+    dptree::filter_map(
+        move |text: String, ctx: UserChatContext, cooldown: CommandCooldown| {
+            if !ctx.chat.link_preview || parse_pixiv_links(&text).is_empty() {
+                return None;
+            }
+            cooldown.check(&ctx).map(CooldownWait)
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use teloxide::types::User;
 
+    // ========================================================================
+    // CommandCooldown 测试
+    // ========================================================================
+
+    fn cooldown(per_user: usize, per_chat: usize) -> CommandCooldown {
+        CommandCooldown::new(CommandCooldownConfig {
+            enabled: true,
+            window_secs: 60,
+            per_user,
+            per_chat,
+        })
+    }
+
+    #[test]
+    fn test_cooldown_limits_user_within_window() {
+        let cooldown = cooldown(3, 0);
+        let start = Instant::now();
+
+        for i in 0..3 {
+            assert!(cooldown
+                .try_acquire(1, 1, start + Duration::from_secs(i * 10))
+                .is_ok());
+        }
+        // 第 4 次需等待最早一次滑出窗口
+        assert_eq!(
+            cooldown.try_acquire(1, 1, start + Duration::from_secs(30)),
+            Err(Duration::from_secs(30))
+        );
+        // 其他用户不受影响
+        assert!(cooldown
+            .try_acquire(2, 2, start + Duration::from_secs(30))
+            .is_ok());
+        // 窗口滑过后恢复
+        assert!(cooldown
+            .try_acquire(1, 1, start + Duration::from_secs(60))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_cooldown_limits_chat_across_users() {
+        let cooldown = cooldown(3, 2);
+        let start = Instant::now();
+
+        assert!(cooldown.try_acquire(1, -100, start).is_ok());
+        assert!(cooldown.try_acquire(2, -100, start).is_ok());
+        assert_eq!(
+            cooldown.try_acquire(3, -100, start + Duration::from_secs(15)),
+            Err(Duration::from_secs(45))
+        );
+        // 被拒绝的请求不计入用户次数
+        assert!(cooldown.try_acquire(3, -200, start).is_ok());
+        assert!(cooldown.try_acquire(3, -200, start).is_ok());
+    }
+
+    #[test]
+    fn test_cooldown_disabled_allows_everything() {
+        let cooldown = CommandCooldown::new(CommandCooldownConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        for _ in 0..20 {
+            assert!(cooldown.try_acquire(1, 1, now).is_ok());
+        }
+    }
+
     // ========================================================================
     // should_accept_command 测试
     // ========================================================================
@@ -7,23 +7,33 @@ pub mod notifier;
 pub mod state;
 
 use crate::booru::BooruSiteRegistry;
-use crate::config::TelegramConfig;
+use crate::config::{ImageSizePolicy, SharedConfig, TelegramConfig};
 use crate::db::repo::Repo;
 use crate::db::types::UserRole;
 use crate::pixiv::client::PixivClient;
+use crate::saucenao::SauceNaoClient;
+use crate::scheduler::{AuthorEngine, LatestRelease, NameUpdateEngine, RankingEngine};
+use crate::utils::archive_upload::ArchiveUpload;
+use crate::utils::log_buffer::LogBuffer;
+use crate::utils::zip_workspace::ZipWorkspace;
 use anyhow::Result;
 use handlers::{
     handle_settings_callback, handle_settings_cancel, handle_settings_input,
-    parse_list_callback_data, ListPaginationAction, BOORU_DOWNLOAD_CALLBACK_PREFIX,
-    DOWNLOAD_CALLBACK_PREFIX, LIST_CALLBACK_PREFIX, SETTINGS_CALLBACK_PREFIX,
+    parse_eh_info_callback_data, parse_forward_sub_callback_data, parse_list_callback_data,
+    parse_sauce_sub_callback_data, parse_unsub_all_callback_data, ListPaginationAction,
+    BOORU_DOWNLOAD_CALLBACK_PREFIX, DOWNLOAD_CALLBACK_PREFIX, EH_INFO_CALLBACK_PREFIX,
+    FORWARD_SUB_CALLBACK_PREFIX, LIST_CALLBACK_PREFIX, RANKING_PUSH_CALLBACK_PREFIX,
+    SAUCE_SUB_CALLBACK_PREFIX, SEARCH_CALLBACK_PREFIX, SETTINGS_CALLBACK_PREFIX,
+    UNBLUR_CALLBACK_DATA, UNSUB_ALL_CALLBACK_PREFIX,
 };
+use middleware::{CommandCooldown, CooldownWait};
 use notifier::ThrottledBot;
 use state::SettingsStorage;
 use std::sync::Arc;
 use teloxide::dispatching::{Dispatcher, DpHandlerDescription, UpdateFilterExt};
 use teloxide::dptree::{self, Handler};
 use teloxide::prelude::*;
-use teloxide::types::BotCommandScope;
+use teloxide::types::{BotCommandScope, ReplyParameters};
 use tracing::{error, info, warn};
 
 pub use commands::Command;
@@ -35,19 +45,26 @@ type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn run(
-    bot: ThrottledBot,
     config: TelegramConfig,
     repo: Arc<Repo>,
     pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
     notifier: notifier::Notifier,
-    sensitive_tags: Vec<String>,
-    image_size: pixiv_client::ImageSize,
-    download_original_threshold: u8,
+    shared_config: SharedConfig,
+    image_sizes: ImageSizePolicy,
     cache_dir: String,
     log_dir: String,
     booru_registry: Arc<BooruSiteRegistry>,
     eh_client: Option<Arc<eh_client::EhClient>>,
     has_telegraph: bool,
+    author_engine: Arc<AuthorEngine>,
+    ranking_engine: Arc<RankingEngine>,
+    name_update_engine: Arc<NameUpdateEngine>,
+    latest_release: LatestRelease,
+    log_buffer: LogBuffer,
+    archive_upload: Option<ArchiveUpload>,
+    tag_alias_file: Option<String>,
+    saucenao: Option<Arc<SauceNaoClient>>,
+    zip_workspace: Arc<ZipWorkspace>,
 ) -> Result<()> {
     info!("Starting Telegram Bot...");
 
@@ -75,38 +92,64 @@ pub async fn run(
         repo.clone(),
         pixiv_client.clone(),
         notifier.clone(),
-        sensitive_tags,
+        shared_config,
         config.owner_id,
         is_public_mode,
-        image_size,
-        download_original_threshold,
+        image_sizes,
         config.require_mention_in_group,
         cache_dir,
         log_dir,
         booru_registry,
         eh_client,
         has_telegraph,
+        author_engine,
+        ranking_engine,
+        name_update_engine,
+        latest_release,
+        log_buffer,
+        archive_upload,
+        tag_alias_file,
+        saucenao,
+        zip_workspace,
     );
 
     info!("✅ Bot initialized, starting command handler");
 
     // Initialize settings dialogue storage
-    let settings_storage = state::new_settings_storage();
-
-    // 设置命令可见性
-    setup_commands(&bot, &repo, has_booru, has_ehentai).await;
-
-    // 构建 handler 树
-    let handler_tree = build_handler_tree();
+    let settings_storage = SettingsStorage::new(repo.clone());
+    match settings_storage.purge_expired().await {
+        Ok(0) => {}
+        Ok(count) => info!("Removed {} expired settings dialogues", count),
+        Err(e) => warn!("Failed to remove expired settings dialogues: {:#}", e),
+    }
 
-    // 使用 Dispatcher
-    Dispatcher::builder(bot, handler_tree)
-        .dependencies(dptree::deps![handler, repo, notifier, settings_storage])
-        .default_handler(|_| async {})
-        .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+    // 高开销命令的限流记录，所有 Bot 共享
+    let command_cooldown = CommandCooldown::new(config.command_cooldown.clone());
+
+    // 每个 Bot 一个 Dispatcher，共享同一个 handler 树和依赖
+    let dispatchers = notifier.bots().bots().cloned().map(|bot| {
+        let repo = repo.clone();
+        let deps = dptree::deps![
+            handler.clone(),
+            repo.clone(),
+            notifier.clone(),
+            settings_storage.clone(),
+            command_cooldown.clone()
+        ];
+        async move {
+            // 设置命令可见性
+            setup_commands(&bot, &repo, has_booru, has_ehentai).await;
+
+            Dispatcher::builder(bot, build_handler_tree())
+                .dependencies(deps)
+                .default_handler(|_| async {})
+                .enable_ctrlc_handler()
+                .build()
+                .dispatch()
+                .await;
+        }
+    });
+    futures_util::future::join_all(dispatchers).await;
 
     Ok(())
 }
@@ -139,6 +182,29 @@ fn build_handler_tree(
         .chain(middleware::filter_chat_accessible())
         .endpoint(handle_command);
 
+    // 高开销命令冷却中 - 未被限流时过滤掉，交由 command_handler 处理
+    let command_cooldown_handler = Message::filter_text()
+        .chain(middleware::filter_hybrid_command::<Command, HandlerResult>())
+        .chain(middleware::filter_user_chat())
+        .chain(middleware::filter_mention_requirement::<
+            Command,
+            HandlerResult,
+        >())
+        .chain(middleware::filter_chat_accessible())
+        .chain(middleware::filter_command_on_cooldown())
+        .endpoint(handle_cooldown);
+
+    // Pixiv 链接预览冷却中 - 未被限流时过滤掉，交由 message_handler 处理
+    let link_cooldown_handler = Message::filter_text()
+        .chain(middleware::filter_relevant_message::<HandlerResult>())
+        .chain(middleware::filter_user_chat())
+        .chain(middleware::filter_message_mention_requirement::<
+            HandlerResult,
+        >())
+        .chain(middleware::filter_chat_accessible())
+        .chain(middleware::filter_link_on_cooldown())
+        .endpoint(handle_cooldown);
+
     let message_handler = Message::filter_text()
         .chain(middleware::filter_relevant_message::<HandlerResult>())
         .chain(middleware::filter_user_chat())
@@ -166,6 +232,17 @@ fn build_handler_tree(
         .chain(filter_in_settings_dialogue())
         .endpoint(handle_settings_dialogue);
 
+    // Channel messages forwarded in private chats - offer to subscribe that channel
+    let forwarded_channel_handler = dptree::filter(|msg: Message| {
+        msg.chat.is_private()
+            && msg
+                .forward_from_chat()
+                .is_some_and(|chat| chat.is_channel())
+    })
+    .chain(middleware::filter_user_chat())
+    .chain(middleware::filter_chat_accessible())
+    .endpoint(handle_forwarded_channel_message);
+
     // Cancel command handler for settings dialogue
     // Uses middleware to ensure user/chat exist and chat is accessible
     // Middleware is applied before command parsing to avoid unnecessary parsing for inaccessible chats
@@ -186,8 +263,11 @@ fn build_handler_tree(
             .branch(migration_handler)
             .branch(admin_chat_control_handler)
             .branch(cancel_handler)
+            .branch(command_cooldown_handler)
             .branch(command_handler)
             .branch(settings_dialogue_handler)
+            .branch(forwarded_channel_handler)
+            .branch(link_cooldown_handler)
             .branch(message_handler),
     )
 }
@@ -230,11 +310,76 @@ fn build_callback_handlers(
         })
         .endpoint(wrap_settings_callback);
 
+    let ranking_push_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(RANKING_PUSH_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_ranking_push_callback);
+
+    let forward_sub_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(FORWARD_SUB_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_forward_sub_callback);
+
+    let eh_info_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(EH_INFO_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_eh_info_callback);
+
+    let search_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(SEARCH_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_search_callback);
+
+    let unsub_all_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(UNSUB_ALL_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_unsub_all_callback);
+
+    let sauce_sub_callback_handler = Update::filter_callback_query()
+        .filter_map(|q: CallbackQuery| {
+            q.data
+                .as_ref()
+                .filter(|data| data.starts_with(SAUCE_SUB_CALLBACK_PREFIX))
+                .cloned()
+        })
+        .endpoint(handle_sauce_sub_callback);
+
+    let unblur_callback_handler = Update::filter_callback_query()
+        .filter(|q: CallbackQuery| q.data.as_deref() == Some(UNBLUR_CALLBACK_DATA))
+        .endpoint(handle_unblur_callback);
+
     dptree::entry()
         .branch(callback_handler)
         .branch(download_callback_handler)
         .branch(booru_download_callback_handler)
         .branch(settings_callback_handler)
+        .branch(ranking_push_callback_handler)
+        .branch(forward_sub_callback_handler)
+        .branch(eh_info_callback_handler)
+        .branch(search_callback_handler)
+        .branch(unsub_all_callback_handler)
+        .branch(sauce_sub_callback_handler)
+        .branch(unblur_callback_handler)
 }
 
 /// 处理命令
@@ -261,6 +406,15 @@ async fn handle_message(
     Ok(())
 }
 
+/// 提示用户高开销操作的剩余冷却时间
+async fn handle_cooldown(bot: ThrottledBot, msg: Message, wait: CooldownWait) -> HandlerResult {
+    let secs = wait.0.as_secs() + u64::from(wait.0.subsec_nanos() > 0);
+    bot.send_message(msg.chat.id, format!("⏳ 操作太频繁，请 {} 秒后再试", secs))
+        .reply_parameters(ReplyParameters::new(msg.id).allow_sending_without_reply())
+        .await?;
+    Ok(())
+}
+
 /// Filter to check if user is in a settings dialogue state.
 /// This filter checks the storage before entering the dialogue handler,
 /// preventing unnecessary processing and avoiding error logging when
@@ -279,25 +433,16 @@ where
             None => return false,
         };
 
-        // Check if user has an active dialogue state
-        let state = {
-            let storage_guard = storage.read().await;
-            storage_guard.get(&(chat_id, user_id)).cloned()
-        };
-
-        match state {
-            Some(s) if s.is_expired() => {
-                // State has expired, remove it
-                let mut storage_guard = storage.write().await;
-                storage_guard.remove(&(chat_id, user_id));
-                info!(
-                    "Settings dialogue expired for user {} in chat {}",
-                    user_id, chat_id
+        // Check if user has an active dialogue state (expired ones are removed)
+        match storage.get(chat_id, user_id).await {
+            Ok(state) => state.is_some(),
+            Err(e) => {
+                error!(
+                    "Failed to get settings dialogue for user {} in chat {}: {:#}",
+                    user_id, chat_id, e
                 );
                 false
             }
-            Some(_) => true, // Valid active state
-            None => false,   // No state
         }
     })
 }
@@ -391,6 +536,215 @@ async fn handle_list_callback(
     Ok(())
 }
 
+/// 处理 "立即发送今日排行" 按钮回调
+async fn handle_ranking_push_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    // Cache the answer so rapid repeated clicks don't trigger duplicate pushes
+    if let Err(e) = bot
+        .answer_callback_query(q.id.clone())
+        .text("⏳ 正在获取今日排行...")
+        .cache_time(30)
+        .await
+    {
+        warn!("Failed to answer callback query: {:#}", e);
+    }
+
+    let Some(subscription_id) = callback_data
+        .strip_prefix(RANKING_PUSH_CALLBACK_PREFIX)
+        .and_then(|id| id.parse::<i32>().ok())
+    else {
+        warn!("Invalid ranking push callback data: {}", callback_data);
+        return Ok(());
+    };
+
+    let Some(msg) = &q.message else {
+        warn!("No message found in ranking push callback query");
+        return Ok(());
+    };
+
+    handler
+        .handle_ranking_push_callback(bot, msg.chat().id, q.from.id, subscription_id)
+        .await?;
+
+    Ok(())
+}
+
+/// 处理私聊中转发的频道消息
+async fn handle_forwarded_channel_message(
+    bot: ThrottledBot,
+    msg: Message,
+    handler: BotHandler,
+) -> HandlerResult {
+    handler.handle_forwarded_channel_message(bot, msg).await?;
+    Ok(())
+}
+
+/// 处理转发频道消息的订阅按钮回调
+async fn handle_forward_sub_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    if let Err(e) = bot.answer_callback_query(q.id.clone()).await {
+        warn!("Failed to answer callback query: {:#}", e);
+    }
+
+    let Some((channel_id, target)) = parse_forward_sub_callback_data(&callback_data) else {
+        warn!(
+            "Invalid forward subscription callback data: {}",
+            callback_data
+        );
+        return Ok(());
+    };
+
+    let Some(msg) = &q.message else {
+        warn!("No message found in forward subscription callback query");
+        return Ok(());
+    };
+
+    handler
+        .handle_forward_sub_callback(bot, msg.chat().id, q.from.id, channel_id, target)
+        .await?;
+
+    Ok(())
+}
+
+/// 处理 /unsuball 确认按钮回调
+async fn handle_unsub_all_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    let Some(confirmation) = parse_unsub_all_callback_data(&callback_data) else {
+        warn!("Invalid unsuball callback data: {}", callback_data);
+        if let Err(e) = bot.answer_callback_query(q.id.clone()).await {
+            warn!("Failed to answer callback query: {:#}", e);
+        }
+        return Ok(());
+    };
+
+    // Only the user who asked for the batch removal may confirm it
+    if q.from.id != confirmation.user_id {
+        if let Err(e) = bot
+            .answer_callback_query(q.id.clone())
+            .text("❌ 只有发起命令的用户可以操作")
+            .await
+        {
+            warn!("Failed to answer callback query: {:#}", e);
+        }
+        return Ok(());
+    }
+
+    if let Err(e) = bot.answer_callback_query(q.id.clone()).await {
+        warn!("Failed to answer callback query: {:#}", e);
+    }
+
+    let Some(msg) = &q.message else {
+        warn!("No message found in unsuball callback query");
+        return Ok(());
+    };
+
+    handler
+        .handle_unsub_all_callback(bot, msg.chat().id, msg.id(), confirmation)
+        .await?;
+
+    Ok(())
+}
+
+/// 处理 E-Hentai 画廊卡片的订阅/下载按钮回调
+async fn handle_eh_info_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    if let Err(e) = bot.answer_callback_query(q.id.clone()).await {
+        warn!("Failed to answer callback query: {:#}", e);
+    }
+
+    let Some((gid, token, action)) = parse_eh_info_callback_data(&callback_data) else {
+        warn!("Invalid eh info callback data: {}", callback_data);
+        return Ok(());
+    };
+
+    let Some(msg) = q.regular_message() else {
+        warn!("No message found in eh info callback query");
+        return Ok(());
+    };
+
+    handler
+        .handle_eh_info_callback(bot, msg.clone(), q.from.id, gid, token, action)
+        .await?;
+
+    Ok(())
+}
+
+/// 处理 /sauce 识别结果的订阅作者按钮回调
+async fn handle_sauce_sub_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    if let Err(e) = bot.answer_callback_query(q.id.clone()).await {
+        warn!("Failed to answer callback query: {:#}", e);
+    }
+
+    let Some(author_id) = parse_sauce_sub_callback_data(&callback_data) else {
+        warn!(
+            "Invalid sauce subscription callback data: {}",
+            callback_data
+        );
+        return Ok(());
+    };
+
+    let Some(msg) = &q.message else {
+        warn!("No message found in sauce subscription callback query");
+        return Ok(());
+    };
+
+    handler
+        .handle_sauce_sub_callback(bot, msg.chat().id, q.from.id, author_id)
+        .await?;
+
+    Ok(())
+}
+
+/// 处理搜索结果的下一页按钮回调
+async fn handle_search_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    callback_data: String,
+    handler: BotHandler,
+) -> HandlerResult {
+    let Some(session_id) = callback_data
+        .strip_prefix(SEARCH_CALLBACK_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        warn!("Invalid search callback data: {}", callback_data);
+        return Ok(());
+    };
+
+    handler.handle_search_callback(bot, q, session_id).await?;
+    Ok(())
+}
+
+/// 处理取消遮罩按钮回调
+async fn handle_unblur_callback(
+    bot: ThrottledBot,
+    q: CallbackQuery,
+    handler: BotHandler,
+) -> HandlerResult {
+    handler.handle_unblur_callback(bot, q).await?;
+    Ok(())
+}
+
 /// 处理下载按钮回调
 async fn handle_download_callback(
     bot: ThrottledBot,
@@ -615,7 +969,12 @@ async fn wrap_settings_callback(
 }
 
 /// 处理聊天迁移（普通群组升级为超级群组）
-async fn handle_chat_migration(msg: Message, repo: Arc<Repo>) -> HandlerResult {
+async fn handle_chat_migration(
+    bot: ThrottledBot,
+    msg: Message,
+    repo: Arc<Repo>,
+    handler: BotHandler,
+) -> HandlerResult {
     let chat_id = msg.chat.id;
 
     // Handle migrate_to_chat_id (old group → new supergroup)
@@ -636,6 +995,7 @@ async fn handle_chat_migration(msg: Message, repo: Arc<Repo>) -> HandlerResult {
                 "✅ Successfully migrated chat data from {} to {}",
                 chat_id, new_chat_id
             );
+            handler.remember_chat_bot(&bot, *new_chat_id, None).await;
         }
     }
 
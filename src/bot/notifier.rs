@@ -1,39 +1,245 @@
+use crate::config::CustomEmojiConfig;
+use crate::db::entities::{chats, subscriptions};
+use crate::db::repo::Repo;
 use crate::pixiv::downloader::Downloader;
-use crate::utils::caption::MAX_PER_GROUP;
+use crate::utils::caption::{
+    self as caption_utils, EntityCaption, MAX_CAPTION_UTF16_UNITS, MAX_PER_GROUP,
+};
+use eh_client::TelegraphClient;
+use pixiv_client::Illust;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use teloxide::adaptors::Throttle;
 use teloxide::prelude::*;
-use tracing::warn;
+use teloxide::types::{MessageId, ThreadId};
+use teloxide::RequestError;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
 
 mod batch;
 mod button;
 mod caption;
+mod channel;
+mod hook;
 mod media;
 mod numbering;
+mod order;
+mod pool;
 mod result;
 mod ugoira;
 
 /// Button label for download button
 const DOWNLOAD_BUTTON_LABEL: &str = "📥 下载";
+const UNBLUR_BUTTON_LABEL: &str = "👁 取消遮罩";
+
+/// Bot API upload limit without a local Bot API server
+const DEFAULT_UPLOAD_LIMIT: u64 = 50 * 1024 * 1024;
 
 /// Type alias for the throttled bot
 pub type ThrottledBot = Throttle<Bot>;
 
 pub use button::DownloadButtonConfig;
+pub use hook::{AutoHashtagHook, CaptionHook};
+pub use media::{check_upload_size, FileTooLarge};
 pub use numbering::ContinuationNumbering;
+pub use order::PushTurn;
+pub use pool::{bot_id, BotPool};
 pub use result::BatchSendResult;
 
 use caption::CaptionStrategy;
+use order::{ChatSendQueue, SendSlot};
 
 #[derive(Clone)]
 pub struct Notifier {
-    bot: ThrottledBot,
+    bots: BotPool,
     downloader: Arc<Downloader>,
+    custom_emoji: Arc<CustomEmojiConfig>,
+    /// Channel post link prefix per channel, `None` when it has no discussion group
+    channel_links: Arc<Mutex<HashMap<ChatId, Option<String>>>>,
+    /// Keeps logical pushes to the same chat from interleaving
+    send_queue: ChatSendQueue,
+    caption_hooks: Vec<Arc<dyn CaptionHook>>,
+    /// Longest caption sent, in rendered UTF-16 units
+    caption_max_len: usize,
+    /// Holds the full text of truncated captions when set
+    caption_telegraph: Option<Arc<TelegraphClient>>,
+    /// Largest file sent as a document, in bytes
+    upload_limit: u64,
+    /// Stores the slow mode delays found for chats when set
+    repo: Option<Arc<Repo>>,
+    /// Send every message without a notification sound
+    silent: bool,
+    /// Forum topic every message is sent into
+    topic: Option<ThreadId>,
 }
 
 impl Notifier {
     pub fn new(bot: ThrottledBot, downloader: Arc<Downloader>) -> Self {
-        Self { bot, downloader }
+        Self {
+            bots: BotPool::new(bot, Vec::new()),
+            downloader,
+            custom_emoji: Arc::new(CustomEmojiConfig::default()),
+            channel_links: Arc::new(Mutex::new(HashMap::new())),
+            send_queue: ChatSendQueue::default(),
+            caption_hooks: Vec::new(),
+            caption_max_len: MAX_CAPTION_UTF16_UNITS,
+            caption_telegraph: None,
+            upload_limit: DEFAULT_UPLOAD_LIMIT,
+            repo: None,
+            silent: false,
+            topic: None,
+        }
+    }
+
+    /// 额外的 Bot 分担推送，每个聊天由最近与其交互的 Bot 负责
+    pub fn with_extra_bots(mut self, extra: Vec<ThrottledBot>) -> Self {
+        self.bots = BotPool::new(self.bots.primary().clone(), extra);
+        self
+    }
+
+    pub fn bots(&self) -> &BotPool {
+        &self.bots
+    }
+
+    /// 启用自定义表情替换（仅对配置中列出的聊天生效）
+    pub fn with_custom_emoji(mut self, custom_emoji: CustomEmojiConfig) -> Self {
+        self.custom_emoji = Arc::new(custom_emoji);
+        self
+    }
+
+    /// 注册推送文案后处理钩子，按注册顺序执行
+    pub fn with_caption_hook(mut self, hook: impl CaptionHook + 'static) -> Self {
+        self.caption_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// 限制推送文案长度，超出时截断（不超过 Telegram 的 1024 上限）
+    pub fn with_caption_limit(mut self, max_len: usize) -> Self {
+        self.caption_max_len = max_len.min(MAX_CAPTION_UTF16_UNITS);
+        self
+    }
+
+    /// 文案被截断时把完整内容发布到 Telegraph，并在文案末尾附上链接
+    pub fn with_caption_telegraph(mut self, telegraph: Arc<TelegraphClient>) -> Self {
+        self.caption_telegraph = Some(telegraph);
+        self
+    }
+
+    /// 设置文件上传大小上限（字节），本地 Bot API 服务器可放宽到 2000 MB
+    pub fn with_upload_limit(mut self, bytes: u64) -> Self {
+        self.upload_limit = bytes;
+        self
+    }
+
+    /// 文件上传大小上限（字节）
+    pub fn upload_limit(&self) -> u64 {
+        self.upload_limit
+    }
+
+    /// 保存查询到的聊天慢速模式间隔，重启后无需重新查询
+    pub fn with_repo(mut self, repo: Arc<Repo>) -> Self {
+        self.repo = Some(repo);
+        self
+    }
+
+    /// 记录聊天的慢速模式间隔（秒，`0` 为未开启），之后发送的每条消息之间至少间隔这么久
+    pub fn set_slow_mode(&self, chat_id: ChatId, delay_secs: u32) {
+        self.send_queue
+            .set_slow_mode(chat_id, Duration::from_secs(u64::from(delay_secs)));
+    }
+
+    /// Look up the chat's slow mode with `getChat`, remember and store it
+    async fn refresh_slow_mode(&self, chat_id: ChatId) {
+        let delay_secs = match self.bots.for_chat(chat_id).get_chat(chat_id).await {
+            Ok(chat) => chat.slow_mode_delay().map_or(0, |delay| delay.seconds()),
+            Err(e) => {
+                warn!("Failed to get slow mode of chat {}: {:#}", chat_id, e);
+                // Don't ask again on every push, a rejected send triggers a new check
+                self.send_queue.set_slow_mode(chat_id, Duration::ZERO);
+                return;
+            }
+        };
+        if delay_secs > 0 {
+            info!("Chat {} has a {}s slow mode", chat_id, delay_secs);
+        }
+        self.set_slow_mode(chat_id, delay_secs);
+
+        if let Some(repo) = &self.repo {
+            let delay_secs = i32::try_from(delay_secs).unwrap_or(i32::MAX);
+            if let Err(e) = repo.set_chat_slow_mode_delay(chat_id.0, delay_secs).await {
+                warn!("Failed to store slow mode of chat {}: {:#}", chat_id, e);
+            }
+        }
+    }
+
+    /// Check the chat's slow mode again when Telegram rejected a send as too fast
+    pub(super) async fn note_send_error(&self, chat_id: ChatId, error: &anyhow::Error) {
+        let too_fast = error.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<RequestError>(),
+                Some(RequestError::RetryAfter(_))
+            )
+        });
+        if too_fast && chat_id.is_channel_or_supergroup() {
+            self.refresh_slow_mode(chat_id).await;
+        }
+    }
+
+    /// 订阅推送用的 Notifier：按订阅的 `silent` 和 `topic` 设置发送
+    ///
+    /// 静默订阅的消息都不触发通知，指定了话题的订阅所有消息都发到该话题，只影响该次推送。
+    pub fn for_subscription(&self, subscription: &subscriptions::Model) -> Cow<'_, Notifier> {
+        let silent = self.silent || subscription.silent;
+        let topic = subscription
+            .message_thread_id
+            .map(|id| ThreadId(MessageId(id)))
+            .or(self.topic);
+        if silent == self.silent && topic == self.topic {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(Self {
+                silent,
+                topic,
+                ..self.clone()
+            })
+        }
+    }
+
+    /// 依次对作品文案执行已注册的钩子
+    pub fn process_caption(&self, caption: String, illust: &Illust, chat: &chats::Model) -> String {
+        self.caption_hooks
+            .iter()
+            .fold(caption, |caption, hook| hook.process(caption, illust, chat))
+    }
+
+    /// 把 MarkdownV2 文案转换为纯文本和实体，按聊天配置把其中的 emoji 标记为自定义表情
+    pub(super) fn caption_entities(&self, chat_id: ChatId, caption: &str) -> EntityCaption {
+        let mut caption = caption_utils::markdown_to_entities(caption);
+        if self.custom_emoji.enabled_for(chat_id.0) {
+            caption_utils::apply_custom_emoji(&mut caption, &self.custom_emoji.emojis);
+        }
+        caption
+    }
+
+    /// Wait for this chat's turn and hold it for one logical push
+    ///
+    /// Take it before a multi-message push (batched albums, continuations, a ranking
+    /// list) so pushes from different engines don't interleave in the chat. Other
+    /// chats are unaffected. Don't take it again for the same chat while holding it.
+    /// Supergroups are checked for slow mode before their first push, and every
+    /// message sent there then keeps the slow mode delay apart.
+    pub async fn begin_push(&self, chat_id: ChatId) -> PushTurn {
+        if chat_id.is_channel_or_supergroup() && !self.send_queue.knows_slow_mode(chat_id) {
+            self.refresh_slow_mode(chat_id).await;
+        }
+        self.send_queue.acquire(chat_id).await
+    }
+
+    /// Wait for the chat's slow mode before sending one message, hold the slot until it is sent
+    pub(super) async fn pace_send(&self, chat_id: ChatId) -> SendSlot {
+        self.send_queue.pace(chat_id).await
     }
 
     /// Get reference to the downloader (used by download handler)
@@ -42,7 +248,6 @@ impl Notifier {
     }
 
     /// 发送多张图片（共享文案）
-    #[allow(dead_code)]
     pub async fn notify_with_images(
         &self,
         chat_id: ChatId,
@@ -163,6 +368,15 @@ mod tests {
             sensitive_tags: Tags::default(),
             created_at: chrono::Utc::now().naive_utc(),
             allow_without_mention: false,
+            allow_r18: false,
+            send_original: false,
+            bot_id: None,
+            link_preview: true,
+            blocked_authors: Default::default(),
+            slow_mode_delay: None,
+            ai_filter: Default::default(),
+            default_filter: Default::default(),
+            notify_failures: false,
         }
     }
 
@@ -225,11 +439,13 @@ mod tests {
             succeeded_indices: vec![0, 1],
             failed_indices: Vec::new(),
             first_message_id: Some(42),
+            message_ids: vec![(0, 42), (1, 43)],
         };
         let partial = BatchSendResult {
             succeeded_indices: vec![0],
             failed_indices: vec![1],
             first_message_id: Some(7),
+            message_ids: vec![(0, 7)],
         };
 
         assert!(success.is_complete_success());
@@ -237,6 +453,9 @@ mod tests {
 
         assert!(!partial.is_complete_success());
         assert!(!partial.is_complete_failure());
+
+        assert_eq!(success.message_id_of(1), Some(43));
+        assert_eq!(partial.message_id_of(1), None);
     }
 
     #[test]
@@ -1,4 +1,5 @@
-use super::caption::CaptionStrategy;
+use super::caption::{CaptionStrategy, CONTINUATION_PREFIX_RESERVE};
+use super::media::is_photo_rejected;
 use super::{
     BatchSendResult, ContinuationNumbering, DownloadButtonConfig, Notifier, MAX_PER_GROUP,
 };
@@ -23,7 +24,30 @@ impl Notifier {
             return BatchSendResult::all_failed(0);
         }
 
-        let keyboard = download_config.build_keyboard();
+        let keyboard = download_config.build_push_keyboard(has_spoiler);
+
+        // Custom emoji substitution and length limits apply to the raw captions so
+        // continuation numbering below still goes through caption.rs unchanged.
+        let shared_caption;
+        let mut individual_captions: Vec<String> = Vec::new();
+        let caption_strategy = match caption_strategy {
+            CaptionStrategy::Shared(c) => {
+                shared_caption = match c {
+                    Some(c) => Some(self.prepare_caption(chat_id, c, 0).await),
+                    None => None,
+                };
+                CaptionStrategy::Shared(shared_caption.as_deref())
+            }
+            CaptionStrategy::Individual(cs) => {
+                for c in cs {
+                    individual_captions.push(
+                        self.prepare_caption(chat_id, c, CONTINUATION_PREFIX_RESERVE)
+                            .await,
+                    );
+                }
+                CaptionStrategy::Individual(&individual_captions)
+            }
+        };
 
         if total == 1 {
             let numbering = continuation_numbering
@@ -48,14 +72,13 @@ impl Notifier {
                 .await
             {
                 Ok(msg_id) => {
-                    return BatchSendResult {
-                        succeeded_indices: vec![0],
-                        failed_indices: Vec::new(),
-                        first_message_id: Some(msg_id),
-                    };
+                    self.attach_channel_buttons(chat_id, msg_id, download_config)
+                        .await;
+                    return BatchSendResult::sent_one(msg_id);
                 }
                 Err(e) => {
                     error!("Single image send failed for chat {}: {:#}", chat_id, e);
+                    self.note_send_error(chat_id, &e).await;
                     return BatchSendResult::all_failed(1);
                 }
             }
@@ -64,14 +87,15 @@ impl Notifier {
         info!("Batch processing {} images for chat {}", total, chat_id);
 
         if let Err(e) = self
-            .bot
+            .bots
+            .for_chat(chat_id)
             .send_chat_action(chat_id, ChatAction::UploadPhoto)
             .await
         {
             warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
         }
 
-        let local_paths = match self.downloader.download_all(image_urls).await {
+        let local_paths = match self.downloader.download_all_photos(image_urls).await {
             Ok(paths) => paths,
             Err(e) => {
                 error!("Batch download failed for chat {}: {:#}", chat_id, e);
@@ -87,7 +111,7 @@ impl Notifier {
         let mut succeeded = Vec::new();
         let mut failed = Vec::new();
         let mut current_idx = 0;
-        let mut first_message_id: Option<i32> = None;
+        let mut message_ids = Vec::new();
 
         for (batch_idx, path_chunk) in chunks.into_iter().enumerate() {
             let batch_size = path_chunk.len();
@@ -115,10 +139,44 @@ impl Notifier {
                 )
                 .await
             {
-                Ok(msg_id) => {
+                Ok(batch_message_ids) => {
                     succeeded.extend(current_idx..batch_end_idx);
-                    if first_message_id.is_none() {
-                        first_message_id = msg_id;
+                    message_ids.extend(
+                        batch_message_ids
+                            .into_iter()
+                            .enumerate()
+                            .map(|(offset, message_id)| (current_idx + offset, message_id)),
+                    );
+                }
+                Err(e) if !has_spoiler && is_photo_rejected(&e) => {
+                    // One image can't be a photo; send the batch one by one so only
+                    // that image goes out as a document instead of retrying them all
+                    warn!(
+                        "Batch {}/{} rejected by chat {}, sending images one by one: {:#}",
+                        continuation_numbering.display_batch_number(batch_idx),
+                        total_batches,
+                        chat_id,
+                        e
+                    );
+                    let sent = self
+                        .send_media_batch_individually(
+                            chat_id,
+                            path_chunk,
+                            &caption_strategy,
+                            batch_captions_slice,
+                            has_spoiler,
+                            batch_idx,
+                            continuation_numbering,
+                        )
+                        .await;
+                    for offset in 0..batch_size {
+                        match sent.iter().find(|(sent_offset, _)| *sent_offset == offset) {
+                            Some(&(_, message_id)) => {
+                                succeeded.push(current_idx + offset);
+                                message_ids.push((current_idx + offset, message_id));
+                            }
+                            None => failed.push(current_idx + offset),
+                        }
                     }
                 }
                 Err(e) => {
@@ -129,6 +187,7 @@ impl Notifier {
                         chat_id,
                         e
                     );
+                    self.note_send_error(chat_id, &e).await;
                     failed.extend(current_idx..batch_end_idx);
                 }
             }
@@ -150,7 +209,8 @@ impl Notifier {
         BatchSendResult {
             succeeded_indices: succeeded,
             failed_indices: failed,
-            first_message_id,
+            first_message_id: message_ids.first().map(|&(_, message_id)| message_id),
+            message_ids,
         }
     }
 
@@ -168,13 +228,14 @@ impl Notifier {
             chat_id, image_url
         );
         if let Err(e) = self
-            .bot
+            .bots
+            .for_chat(chat_id)
             .send_chat_action(chat_id, ChatAction::UploadPhoto)
             .await
         {
             warn!("Failed to set chat action for chat {}: {:#}", chat_id, e);
         }
-        let local_path = self.downloader.download(image_url).await?;
+        let local_path = self.downloader.download_photo(image_url).await?;
         self.send_photo_file_with_id(chat_id, &local_path, caption, has_spoiler, keyboard)
             .await
     }
@@ -1,8 +1,15 @@
-use crate::bot::handlers::{BOORU_DOWNLOAD_CALLBACK_PREFIX, DOWNLOAD_CALLBACK_PREFIX};
+use crate::bot::handlers::{
+    BOORU_DOWNLOAD_CALLBACK_PREFIX, DOWNLOAD_CALLBACK_PREFIX, UNBLUR_CALLBACK_DATA,
+};
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 
 const TELEGRAM_CALLBACK_DATA_MAX_BYTES: usize = 64;
 
+/// Button label linking a channel post to its comment thread
+const DISCUSS_BUTTON_LABEL: &str = "💬 讨论";
+/// Button label linking a channel post to the original work
+const SOURCE_BUTTON_LABEL: &str = "🔗 来源";
+
 #[derive(Clone, Debug)]
 pub enum DownloadTarget {
     Pixiv(u64),
@@ -13,6 +20,9 @@ pub enum DownloadTarget {
 pub struct DownloadButtonConfig {
     target: Option<DownloadTarget>,
     is_channel: bool,
+    source_url: Option<String>,
+    /// Offer "unblur" next to the download button on spoilered pushes
+    unblur: bool,
 }
 
 impl DownloadButtonConfig {
@@ -20,6 +30,8 @@ impl DownloadButtonConfig {
         Self {
             target: Some(DownloadTarget::Pixiv(illust_id)),
             is_channel: false,
+            source_url: None,
+            unblur: false,
         }
     }
 
@@ -30,6 +42,8 @@ impl DownloadButtonConfig {
                 post_id,
             }),
             is_channel: false,
+            source_url: None,
+            unblur: false,
         }
     }
 
@@ -60,6 +74,62 @@ impl DownloadButtonConfig {
         self
     }
 
+    /// Link used by the channel "来源" button; Pixiv targets derive it automatically
+    pub fn with_source_url(mut self, url: impl Into<String>) -> Self {
+        self.source_url = Some(url.into());
+        self
+    }
+
+    /// Let chat admins lift the spoiler of a single-item push; the message must be
+    /// recorded in the messages table for the callback to accept it
+    pub fn with_unblur_button(mut self) -> Self {
+        self.unblur = true;
+        self
+    }
+
+    pub(super) fn is_channel(&self) -> bool {
+        self.is_channel
+    }
+
+    fn source_url(&self) -> Option<String> {
+        if let Some(url) = &self.source_url {
+            return Some(url.clone());
+        }
+        match self.target.as_ref()? {
+            DownloadTarget::Pixiv(id) => Some(format!("https://www.pixiv.net/artworks/{}", id)),
+            DownloadTarget::Booru { .. } => None,
+        }
+    }
+
+    /// Channel posts get URL buttons instead of the download callback:
+    /// the comment thread (when the channel has a discussion group) and the source.
+    pub(super) fn build_channel_keyboard(
+        &self,
+        discussion_url: Option<&str>,
+    ) -> Option<InlineKeyboardMarkup> {
+        if !self.is_channel {
+            return None;
+        }
+
+        let buttons: Vec<InlineKeyboardButton> = [
+            discussion_url.map(|url| (DISCUSS_BUTTON_LABEL, url.to_string())),
+            self.source_url().map(|url| (SOURCE_BUTTON_LABEL, url)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|(label, url)| {
+            let url = url::Url::parse(&url).ok()?;
+            Some(InlineKeyboardButton::url(label, url))
+        })
+        .collect();
+
+        if buttons.is_empty() {
+            None
+        } else {
+            Some(InlineKeyboardMarkup::new(vec![buttons]))
+        }
+    }
+
     pub(super) fn should_show_button(&self) -> bool {
         self.target.is_some() && !self.is_channel
     }
@@ -84,6 +154,18 @@ impl DownloadButtonConfig {
         let button = InlineKeyboardButton::callback(super::DOWNLOAD_BUTTON_LABEL, callback_data);
         Some(InlineKeyboardMarkup::new(vec![vec![button]]))
     }
+
+    /// Keyboard of a single-item push: the download button, plus "unblur" when spoilered
+    pub(super) fn build_push_keyboard(&self, has_spoiler: bool) -> Option<InlineKeyboardMarkup> {
+        let mut keyboard = self.build_keyboard()?;
+        if has_spoiler && self.unblur {
+            keyboard.inline_keyboard[0].push(InlineKeyboardButton::callback(
+                super::UNBLUR_BUTTON_LABEL,
+                UNBLUR_CALLBACK_DATA,
+            ));
+        }
+        Some(keyboard)
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +183,15 @@ mod tests {
             sensitive_tags: Default::default(),
             created_at: Default::default(),
             allow_without_mention: false,
+            allow_r18: false,
+            send_original: false,
+            bot_id: None,
+            link_preview: true,
+            blocked_authors: Default::default(),
+            slow_mode_delay: None,
+            ai_filter: Default::default(),
+            default_filter: Default::default(),
+            notify_failures: false,
         }
     }
 
@@ -130,6 +221,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn channel_keyboard_links_discussion_and_source() {
+        let cfg = DownloadButtonConfig::for_pixiv_chat(42, &chat("channel"));
+        let kb = cfg
+            .build_channel_keyboard(Some("https://t.me/example/7?comment=1"))
+            .expect("expected keyboard");
+        let urls: Vec<String> = kb.inline_keyboard[0]
+            .iter()
+            .map(|button| match &button.kind {
+                teloxide::types::InlineKeyboardButtonKind::Url(url) => url.to_string(),
+                _ => panic!("expected url button"),
+            })
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://t.me/example/7?comment=1",
+                "https://www.pixiv.net/artworks/42"
+            ]
+        );
+
+        assert!(
+            DownloadButtonConfig::for_booru_chat("y", 1, &chat("channel"))
+                .build_channel_keyboard(None)
+                .is_none()
+        );
+        assert!(DownloadButtonConfig::for_pixiv_chat(42, &chat("private"))
+            .build_channel_keyboard(Some("https://t.me/example/7?comment=1"))
+            .is_none());
+    }
+
     #[test]
     fn channel_chat_hides_button_for_both_targets() {
         assert!(DownloadButtonConfig::for_pixiv_chat(1, &chat("channel"))
@@ -150,6 +272,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unblur_button_only_on_spoilered_opt_in_pushes() {
+        let callback_data = |kb: InlineKeyboardMarkup| -> Vec<String> {
+            kb.inline_keyboard[0]
+                .iter()
+                .map(|button| match &button.kind {
+                    teloxide::types::InlineKeyboardButtonKind::CallbackData(s) => s.clone(),
+                    _ => panic!("expected callback data"),
+                })
+                .collect()
+        };
+
+        let cfg = DownloadButtonConfig::pixiv(7).with_unblur_button();
+        assert_eq!(
+            callback_data(cfg.build_push_keyboard(true).unwrap()),
+            ["dl:7", "unblur"]
+        );
+        assert_eq!(
+            callback_data(cfg.build_push_keyboard(false).unwrap()),
+            ["dl:7"]
+        );
+        assert_eq!(
+            callback_data(
+                DownloadButtonConfig::pixiv(7)
+                    .build_push_keyboard(true)
+                    .unwrap()
+            ),
+            ["dl:7"]
+        );
+        assert!(DownloadButtonConfig::for_pixiv_chat(7, &chat("channel"))
+            .with_unblur_button()
+            .build_push_keyboard(true)
+            .is_none());
+    }
+
     #[test]
     fn booru_button_is_hidden_when_callback_data_exceeds_telegram_limit() {
         let long_site_name = "a".repeat(61);
@@ -1,4 +1,18 @@
-use super::ContinuationNumbering;
+use super::{ContinuationNumbering, Notifier};
+use crate::utils::caption::{self as caption_utils, CaptionSpan};
+use anyhow::{Context, Result};
+use eh_client::telegraph::Node;
+use eh_client::TelegraphClient;
+use serde_json::json;
+use teloxide::types::ChatId;
+use teloxide::utils::markdown;
+use tracing::warn;
+
+/// 独立文案为续传编号 `\(continued N/M\)` 预留的长度
+pub(super) const CONTINUATION_PREFIX_RESERVE: usize = 24;
+
+/// 截断且没有 Telegraph 页面时追加的标记
+const TRUNCATED_SUFFIX: &str = "\n…";
 
 /// 文案策略：区分“共享文案”和“独立文案”
 pub(super) enum CaptionStrategy<'a> {
@@ -48,3 +62,73 @@ pub(super) fn individual_batch_caption(
         Some(raw_caption.to_owned())
     }
 }
+
+impl Notifier {
+    /// 发送前处理文案：截断到长度上限 (减去 `reserve`)
+    ///
+    /// 启用 Telegraph 时完整文案会发布为页面，截断后的文案末尾附上链接；
+    /// 页面创建失败时仍然发送截断的文案。
+    pub(super) async fn prepare_caption(
+        &self,
+        chat_id: ChatId,
+        caption: &str,
+        reserve: usize,
+    ) -> String {
+        let max_len = self.caption_max_len.saturating_sub(reserve);
+        if caption_utils::caption_len(caption) <= max_len {
+            return caption.to_owned();
+        }
+
+        let suffix = match &self.caption_telegraph {
+            Some(telegraph) => match create_caption_page(telegraph, caption).await {
+                Ok(url) => format!("\n\n📖 [完整内容]({})", markdown::escape_link_url(&url)),
+                Err(e) => {
+                    warn!(
+                        "Failed to create Telegraph page for long caption in chat {}: {:#}",
+                        chat_id, e
+                    );
+                    TRUNCATED_SUFFIX.to_string()
+                }
+            },
+            None => TRUNCATED_SUFFIX.to_string(),
+        };
+        caption_utils::truncate_caption(caption, max_len, &suffix)
+    }
+}
+
+async fn create_caption_page(telegraph: &TelegraphClient, caption: &str) -> Result<String> {
+    let content = caption_page_content(caption);
+    let title = content
+        .first()
+        .and_then(|node| node.children.as_ref()?.first()?.as_str().map(str::trim))
+        .filter(|title| !title.is_empty())
+        .unwrap_or("PixivBot")
+        .to_string();
+    telegraph
+        .create_page(&title, &content)
+        .await
+        .context("Failed to create caption page")
+}
+
+/// 完整文案的 Telegraph 页面内容：每行一个段落，保留链接
+fn caption_page_content(caption: &str) -> Vec<Node> {
+    caption
+        .split('\n')
+        .map(caption_utils::caption_spans)
+        .filter(|spans| !spans.is_empty())
+        .map(|spans| Node {
+            tag: "p".into(),
+            attrs: None,
+            children: Some(
+                spans
+                    .into_iter()
+                    .map(|CaptionSpan { text, url }| match url {
+                        Some(url) => serde_json::to_value(Node::link(&url, &text))
+                            .unwrap_or_else(|_| json!(text)),
+                        None => json!(text),
+                    })
+                    .collect(),
+            ),
+        })
+        .collect()
+}
@@ -0,0 +1,102 @@
+use super::{DownloadButtonConfig, Notifier};
+use teloxide::prelude::*;
+use teloxide::types::MessageId;
+use tracing::warn;
+
+/// Offset between a channel's Bot API id (`-100xxxxxxxxxx`) and its `t.me/c/` id
+const CHANNEL_ID_OFFSET: i64 = 1_000_000_000_000;
+
+impl Notifier {
+    /// 频道推送成功后补充讨论区/来源按钮
+    ///
+    /// 讨论链接需要已发送消息的 ID，所以在发送后通过编辑按钮附加。
+    /// 媒体组消息无法携带按钮，只对单条消息调用。
+    pub(super) async fn attach_channel_buttons(
+        &self,
+        chat_id: ChatId,
+        message_id: i32,
+        download_config: &DownloadButtonConfig,
+    ) {
+        if !download_config.is_channel() {
+            return;
+        }
+
+        let discussion_url = self
+            .channel_post_base_url(chat_id)
+            .await
+            .map(|base| discussion_url(&base, message_id));
+        let Some(keyboard) = download_config.build_channel_keyboard(discussion_url.as_deref())
+        else {
+            return;
+        };
+
+        if let Err(e) = self
+            .bots
+            .for_chat(chat_id)
+            .edit_message_reply_markup(chat_id, MessageId(message_id))
+            .reply_markup(keyboard)
+            .await
+        {
+            warn!(
+                "Failed to attach channel buttons to message {} in chat {}: {:#}",
+                message_id, chat_id, e
+            );
+        }
+    }
+
+    /// 获取频道消息链接前缀；频道未关联讨论组时返回 None（结果会缓存）
+    async fn channel_post_base_url(&self, chat_id: ChatId) -> Option<String> {
+        if let Some(cached) = self.channel_links.lock().await.get(&chat_id) {
+            return cached.clone();
+        }
+
+        let resolved = match self.bots.for_chat(chat_id).get_chat(chat_id).await {
+            Ok(info) => info
+                .linked_chat_id()
+                .map(|_| channel_post_base_url(chat_id, info.username())),
+            Err(e) => {
+                // Not cached, so the next push retries the lookup
+                warn!("Failed to get linked chat for channel {}: {:#}", chat_id, e);
+                return None;
+            }
+        };
+
+        self.channel_links
+            .lock()
+            .await
+            .insert(chat_id, resolved.clone());
+        resolved
+    }
+}
+
+fn channel_post_base_url(chat_id: ChatId, username: Option<&str>) -> String {
+    match username {
+        Some(username) => format!("https://t.me/{}", username),
+        None => format!("https://t.me/c/{}", -chat_id.0 - CHANNEL_ID_OFFSET),
+    }
+}
+
+/// Link that opens the comment thread of a channel post in the discussion group
+fn discussion_url(base_url: &str, message_id: i32) -> String {
+    format!("{}/{}?comment=1", base_url, message_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discussion_url_uses_username_or_private_channel_id() {
+        assert_eq!(
+            discussion_url(
+                &channel_post_base_url(ChatId(-1001234567890), Some("pixiv_feed")),
+                42
+            ),
+            "https://t.me/pixiv_feed/42?comment=1"
+        );
+        assert_eq!(
+            discussion_url(&channel_post_base_url(ChatId(-1001234567890), None), 42),
+            "https://t.me/c/1234567890/42?comment=1"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::db::entities::chats;
+use crate::utils::tag;
+use pixiv_client::Illust;
+use std::collections::HashMap;
+use teloxide::utils::markdown;
+
+/// 推送文案后处理钩子
+///
+/// 在 `main.rs` 中通过 [`Notifier::with_caption_hook`](super::Notifier::with_caption_hook)
+/// 注册，按注册顺序作用于订阅/榜单推送的作品文案，方便在不改动引擎代码的情况下
+/// 追加话题标签、推广链接等。输入和输出都是 MarkdownV2，实现方需自行转义。
+pub trait CaptionHook: Send + Sync {
+    fn process(&self, caption: String, illust: &Illust, chat: &chats::Model) -> String;
+}
+
+/// 示例钩子：作品带有指定标签时追加额外的话题标签
+///
+/// 标签按别名表比较，例如配置 `原神 = "Genshin"` 后带 `genshin` 别名的作品同样会追加 `#Genshin`。
+pub struct AutoHashtagHook {
+    /// 标签 -> 追加的话题标签（不含 `#`）
+    hashtags: Vec<(String, String)>,
+}
+
+impl AutoHashtagHook {
+    pub fn new(hashtags: &HashMap<String, String>) -> Self {
+        let mut hashtags: Vec<(String, String)> = hashtags
+            .iter()
+            .filter_map(|(tag_name, hashtag)| {
+                let hashtag = tag::format_tags(&[hashtag.trim_start_matches('#')]).remove(0);
+                (!tag_name.is_empty() && !hashtag.is_empty()).then(|| (tag_name.clone(), hashtag))
+            })
+            .collect();
+        // Stable output regardless of map order
+        hashtags.sort();
+        Self { hashtags }
+    }
+}
+
+impl CaptionHook for AutoHashtagHook {
+    fn process(&self, mut caption: String, illust: &Illust, _chat: &chats::Model) -> String {
+        let illust_tags: Vec<String> = illust
+            .tags
+            .iter()
+            .map(|t| tag::canonical_tag(&t.name))
+            .collect();
+        // The caption already lists every tag as a hashtag
+        let shown: Vec<String> = illust
+            .tags
+            .iter()
+            .map(|t| tag::normalize_tag(&t.name))
+            .collect();
+
+        let mut added: Vec<&str> = Vec::new();
+        for (tag_name, hashtag) in &self.hashtags {
+            if illust_tags.contains(&tag::canonical_tag(tag_name))
+                && !shown.contains(&hashtag.to_lowercase())
+                && !added.contains(&hashtag.as_str())
+            {
+                added.push(hashtag);
+            }
+        }
+        if added.is_empty() {
+            return caption;
+        }
+
+        let line = added
+            .iter()
+            .map(|hashtag| markdown::escape(&format!("#{}", hashtag)))
+            .collect::<Vec<_>>()
+            .join("  ");
+        caption.push_str(if illust.tags.is_empty() { "\n\n" } else { "\n" });
+        caption.push_str(&line);
+        caption
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::types::Tags;
+    use serde_json::json;
+
+    fn make_chat() -> chats::Model {
+        chats::Model {
+            id: 1,
+            r#type: "channel".to_string(),
+            title: Some("test".to_string()),
+            enabled: true,
+            blur_sensitive_tags: false,
+            excluded_tags: Tags::default(),
+            sensitive_tags: Tags::default(),
+            created_at: chrono::Utc::now().naive_utc(),
+            allow_without_mention: false,
+            allow_r18: false,
+            send_original: false,
+            bot_id: None,
+            link_preview: true,
+            blocked_authors: Default::default(),
+            slow_mode_delay: None,
+            ai_filter: Default::default(),
+            default_filter: Default::default(),
+            notify_failures: false,
+        }
+    }
+
+    fn make_illust(tags: &[&str]) -> Illust {
+        serde_json::from_value(json!({
+            "id": 1,
+            "title": "illust",
+            "type": "illust",
+            "image_urls": {
+                "square_medium": "square",
+                "medium": "medium",
+                "large": "large",
+                "original": "original"
+            },
+            "caption": "",
+            "restrict": 0,
+            "user": {
+                "id": 2,
+                "name": "Author",
+                "account": "author"
+            },
+            "tags": tags.iter().map(|name| json!({ "name": name, "translated_name": null })).collect::<Vec<_>>(),
+            "create_date": "2026-01-01T00:00:00+00:00",
+            "page_count": 1,
+            "width": 100,
+            "height": 100,
+            "sanity_level": 2,
+            "x_restrict": 0,
+            "series": null,
+            "meta_single_page": { "original_image_url": "original" },
+            "meta_pages": [],
+            "total_view": 0,
+            "total_bookmarks": 0,
+            "is_bookmarked": false,
+            "visible": true,
+            "is_muted": false,
+            "total_comments": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn auto_hashtag_hook_appends_configured_hashtags_once() {
+        let hook = AutoHashtagHook::new(&HashMap::from([
+            ("原神".to_string(), "Genshin".to_string()),
+            ("Genshin Impact".to_string(), "#Genshin".to_string()),
+            ("風景画".to_string(), "GenshinImpact".to_string()),
+            ("風景".to_string(), "Landscape".to_string()),
+        ]));
+        let chat = make_chat();
+
+        let illust = make_illust(&["原神", "Genshin Impact", "風景画"]);
+        assert_eq!(
+            hook.process("caption".to_string(), &illust, &chat),
+            "caption\n\\#Genshin"
+        );
+
+        let illust = make_illust(&["オリジナル"]);
+        assert_eq!(
+            hook.process("caption".to_string(), &illust, &chat),
+            "caption"
+        );
+    }
+}
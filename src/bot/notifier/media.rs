@@ -3,10 +3,83 @@ use super::{ContinuationNumbering, Notifier};
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use teloxide::prelude::*;
-use teloxide::types::{InlineKeyboardMarkup, InputFile, InputMedia, InputMediaPhoto, ParseMode};
+use teloxide::types::{
+    InlineKeyboardMarkup, InputFile, InputMedia, InputMediaPhoto, MessageId, ParseMode,
+    ReplyParameters,
+};
+use teloxide::{ApiError, RequestError};
+use tracing::warn;
+
+/// Largest original sent as a document after a photo push
+const ORIGINAL_DOCUMENT_MAX_BYTES: u64 = 20 * 1024 * 1024;
+
+/// File larger than the Bot API accepts for upload
+#[derive(Debug)]
+pub struct FileTooLarge {
+    pub size: u64,
+    pub limit: u64,
+}
+
+impl std::fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file is {} bytes, over the {} byte upload limit",
+            self.size, self.limit
+        )
+    }
+}
+
+impl std::error::Error for FileTooLarge {}
+
+/// sendPhoto rejections that teloxide reports as [`ApiError::Unknown`]
+const PHOTO_REJECTED_ERRORS: &[&str] = &["PHOTO_INVALID_DIMENSIONS", "PHOTO_SAVE_FILE_INVALID"];
+
+/// Telegram 不接受该图片作为照片 (极端宽高比等)，但作为文件发送可以成功
+pub(super) fn is_photo_rejected(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| match cause.downcast_ref::<RequestError>() {
+            Some(RequestError::Api(ApiError::ImageProcessFailed)) => true,
+            Some(RequestError::Api(ApiError::Unknown(message))) => PHOTO_REJECTED_ERRORS
+                .iter()
+                .any(|rejected| message.contains(rejected)),
+            _ => false,
+        })
+}
+
+/// 媒体组中第 `i` 张图片的文案
+fn batch_item_caption(
+    strategy: &CaptionStrategy<'_>,
+    batch_captions: Option<&[String]>,
+    i: usize,
+    batch_idx: usize,
+    continuation_numbering: ContinuationNumbering,
+) -> Option<String> {
+    match strategy {
+        CaptionStrategy::Shared(base_cap) => {
+            shared_batch_caption(*base_cap, i, batch_idx, continuation_numbering)
+        }
+        CaptionStrategy::Individual(_) => batch_captions.and_then(|caps| {
+            individual_batch_caption(&caps[i], i, batch_idx, continuation_numbering)
+        }),
+    }
+}
+
+/// 上传前检查文件大小，超过上限时返回 [`FileTooLarge`]，否则返回文件大小
+pub async fn check_upload_size(path: &Path, limit: u64) -> Result<u64> {
+    let size = tokio::fs::metadata(path)
+        .await
+        .context("Failed to read file size")?
+        .len();
+    if size > limit {
+        return Err(FileTooLarge { size, limit }.into());
+    }
+    Ok(size)
+}
 
 impl Notifier {
-    /// 底层发送：构建 InputMedia 并调用 API，返回第一条消息的ID
+    /// 底层发送：构建 InputMedia 并调用 API，按顺序返回每张图片的消息ID
     #[allow(clippy::too_many_arguments)]
     pub(super) async fn send_media_batch(
         &self,
@@ -18,28 +91,26 @@ impl Notifier {
         batch_idx: usize,
         continuation_numbering: ContinuationNumbering,
         silent: bool,
-    ) -> Result<Option<i32>> {
+    ) -> Result<Vec<i32>> {
         let media_group: Vec<InputMedia> = paths
             .iter()
             .enumerate()
             .map(|(i, path)| {
                 let mut photo = InputMediaPhoto::new(InputFile::file(path));
 
-                let caption_text = match strategy {
-                    CaptionStrategy::Shared(base_cap) => {
-                        shared_batch_caption(*base_cap, i, batch_idx, continuation_numbering)
-                    }
-                    CaptionStrategy::Individual(_) => {
-                        if let Some(caps) = batch_captions {
-                            individual_batch_caption(&caps[i], i, batch_idx, continuation_numbering)
-                        } else {
-                            None
-                        }
-                    }
-                };
+                let caption_text = batch_item_caption(
+                    strategy,
+                    batch_captions,
+                    i,
+                    batch_idx,
+                    continuation_numbering,
+                );
 
                 if let Some(c) = caption_text {
-                    photo = photo.caption(c).parse_mode(ParseMode::MarkdownV2);
+                    let caption = self.caption_entities(chat_id, &c);
+                    photo = photo
+                        .caption(caption.text)
+                        .caption_entities(caption.entities);
                 }
                 if has_spoiler {
                     photo = photo.spoiler();
@@ -48,12 +119,56 @@ impl Notifier {
             })
             .collect();
 
-        let mut req = self.bot.send_media_group(chat_id, media_group);
-        if silent {
+        let mut req = self
+            .bots
+            .for_chat(chat_id)
+            .send_media_group(chat_id, media_group);
+        if silent || self.silent {
             req = req.disable_notification(true);
         }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let _slot = self.pace_send(chat_id).await;
         let messages = req.await.context("Send media group failed")?;
-        Ok(messages.first().map(|m| m.id.0))
+        Ok(messages.iter().map(|m| m.id.0).collect())
+    }
+
+    /// 媒体组被 Telegram 拒绝后逐张发送，被拒绝的图片改为文件发送
+    ///
+    /// 返回成功发送的图片在本批中的位置及其消息ID。
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn send_media_batch_individually(
+        &self,
+        chat_id: ChatId,
+        paths: &[PathBuf],
+        strategy: &CaptionStrategy<'_>,
+        batch_captions: Option<&[String]>,
+        has_spoiler: bool,
+        batch_idx: usize,
+        continuation_numbering: ContinuationNumbering,
+    ) -> Vec<(usize, i32)> {
+        let mut sent = Vec::new();
+        for (i, path) in paths.iter().enumerate() {
+            let caption = batch_item_caption(
+                strategy,
+                batch_captions,
+                i,
+                batch_idx,
+                continuation_numbering,
+            );
+            match self
+                .send_photo_file_with_id(chat_id, path, caption.as_deref(), has_spoiler, None)
+                .await
+            {
+                Ok(message_id) => sent.push((i, message_id)),
+                Err(e) => warn!(
+                    "Failed to send image {} of batch {} to chat {}: {:#}",
+                    i, batch_idx, chat_id, e
+                ),
+            }
+        }
+        sent
     }
 
     pub(super) async fn send_photo_file_with_id(
@@ -64,20 +179,93 @@ impl Notifier {
         has_spoiler: bool,
         keyboard: Option<InlineKeyboardMarkup>,
     ) -> Result<i32> {
-        let mut req = self.bot.send_photo(chat_id, InputFile::file(path));
+        let mut req = self
+            .bots
+            .for_chat(chat_id)
+            .send_photo(chat_id, InputFile::file(path));
         if let Some(c) = caption {
-            req = req.caption(c).parse_mode(ParseMode::MarkdownV2);
+            let caption = self.caption_entities(chat_id, c);
+            req = req.caption(caption.text).caption_entities(caption.entities);
         }
         if has_spoiler {
             req = req.has_spoiler(true);
         }
+        if let Some(kb) = keyboard.clone() {
+            req = req.reply_markup(kb);
+        }
+        if self.silent {
+            req = req.disable_notification(true);
+        }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let slot = self.pace_send(chat_id).await;
+        let result = req.await.context("Send photo failed");
+        drop(slot);
+        let error = match result {
+            Ok(message) => return Ok(message.id.0),
+            Err(e) => e,
+        };
+
+        // Documents can't be hidden behind a spoiler, so those keep failing
+        if has_spoiler || !is_photo_rejected(&error) {
+            return Err(error);
+        }
+        warn!(
+            "Photo {} rejected by chat {}, sending it as a document: {:#}",
+            path.display(),
+            chat_id,
+            error
+        );
+        self.send_photo_as_document(chat_id, path, caption, keyboard)
+            .await
+    }
+
+    /// 以文件形式发送图片，用于 Telegram 拒绝作为照片发送的图片
+    async fn send_photo_as_document(
+        &self,
+        chat_id: ChatId,
+        path: &Path,
+        caption: Option<&str>,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> Result<i32> {
+        check_upload_size(path, self.upload_limit).await?;
+        let mut req = self
+            .bots
+            .for_chat(chat_id)
+            .send_document(chat_id, InputFile::file(path))
+            .disable_content_type_detection(true);
+        if let Some(c) = caption {
+            let caption = self.caption_entities(chat_id, c);
+            req = req.caption(caption.text).caption_entities(caption.entities);
+        }
         if let Some(kb) = keyboard {
             req = req.reply_markup(kb);
         }
-        let message = req.await.context("Send photo failed")?;
+        if self.silent {
+            req = req.disable_notification(true);
+        }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let _slot = self.pace_send(chat_id).await;
+        let message = req.await.context("Send photo as document failed")?;
         Ok(message.id.0)
     }
 
+    /// 发送单张本地图片并返回消息ID
+    ///
+    /// caption 使用 MarkdownV2 格式。
+    pub async fn send_photo_file(
+        &self,
+        chat_id: ChatId,
+        path: &Path,
+        caption: Option<&str>,
+    ) -> Result<i32> {
+        self.send_photo_file_with_id(chat_id, path, caption, false, None)
+            .await
+    }
+
     /// 发送动画 (MP4/GIF) 文件并返回消息ID
     #[cfg(feature = "ffmpeg-codec")]
     pub(super) async fn send_animation_file(
@@ -88,9 +276,13 @@ impl Notifier {
         has_spoiler: bool,
         keyboard: Option<InlineKeyboardMarkup>,
     ) -> Result<i32> {
-        let mut req = self.bot.send_animation(chat_id, InputFile::file(path));
+        let mut req = self
+            .bots
+            .for_chat(chat_id)
+            .send_animation(chat_id, InputFile::file(path));
         if let Some(c) = caption {
-            req = req.caption(c).parse_mode(ParseMode::MarkdownV2);
+            let caption = self.caption_entities(chat_id, c);
+            req = req.caption(caption.text).caption_entities(caption.entities);
         }
         if has_spoiler {
             req = req.has_spoiler(true);
@@ -98,6 +290,13 @@ impl Notifier {
         if let Some(kb) = keyboard {
             req = req.reply_markup(kb);
         }
+        if self.silent {
+            req = req.disable_notification(true);
+        }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let _slot = self.pace_send(chat_id).await;
         let message = req.await.context("Send animation failed")?;
         Ok(message.id.0)
     }
@@ -105,6 +304,7 @@ impl Notifier {
     /// 发送文档 (ZIP/文件) 并返回消息ID
     ///
     /// 用于 e-hentai 归档下载发送。caption 使用 MarkdownV2 格式。
+    /// 超过上传上限的文件不会发送，返回 [`FileTooLarge`] 错误。
     pub async fn send_document(
         &self,
         chat_id: ChatId,
@@ -112,27 +312,113 @@ impl Notifier {
         filename: &str,
         caption: &str,
     ) -> Result<i32> {
-        let mut req = self.bot.send_document(
+        check_upload_size(path, self.upload_limit).await?;
+        let mut req = self.bots.for_chat(chat_id).send_document(
             chat_id,
             InputFile::file(path).file_name(filename.to_string()),
         );
         req = req.caption(caption).parse_mode(ParseMode::MarkdownV2);
+        if self.silent {
+            req = req.disable_notification(true);
+        }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let _slot = self.pace_send(chat_id).await;
         let message = req.await.context("Send document failed")?;
         Ok(message.id.0)
     }
 
+    /// 下载原图并作为文件发送，回复到已推送的图片消息
+    ///
+    /// Telegram 会压缩图片，开启 `/original` 的聊天额外收到无损原图。
+    /// 超过大小上限时不发送，返回 `false`。
+    pub async fn send_original_document(
+        &self,
+        chat_id: ChatId,
+        original_url: &str,
+        reply_to: Option<i32>,
+    ) -> Result<bool> {
+        let path = self
+            .downloader
+            .download(original_url)
+            .await
+            .context("Failed to download original")?;
+        let size = tokio::fs::metadata(&path)
+            .await
+            .context("Failed to read original size")?
+            .len();
+        if size > ORIGINAL_DOCUMENT_MAX_BYTES {
+            return Ok(false);
+        }
+
+        let filename = original_url
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("original.jpg");
+        let mut req = self
+            .bots
+            .for_chat(chat_id)
+            .send_document(
+                chat_id,
+                InputFile::file(&path).file_name(filename.to_string()),
+            )
+            .disable_notification(true)
+            .disable_content_type_detection(true);
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        if let Some(message_id) = reply_to {
+            req = req.reply_parameters(
+                ReplyParameters::new(MessageId(message_id)).allow_sending_without_reply(),
+            );
+        }
+        let _slot = self.pace_send(chat_id).await;
+        req.await.context("Send original document failed")?;
+        self.downloader.mark_delivered(&path);
+        Ok(true)
+    }
+
     /// 发送纯文本消息并返回消息ID
     ///
     /// 用于发送 Telegraph 链接等。text 使用 MarkdownV2 格式。
     pub async fn send_text(&self, chat_id: ChatId, text: &str, silent: bool) -> Result<i32> {
         let mut req = self
-            .bot
+            .bots
+            .for_chat(chat_id)
             .send_message(chat_id, text)
             .parse_mode(ParseMode::MarkdownV2);
-        if silent {
+        if silent || self.silent {
             req = req.disable_notification(true);
         }
+        if let Some(thread_id) = self.topic {
+            req = req.message_thread_id(thread_id);
+        }
+        let _slot = self.pace_send(chat_id).await;
         let message = req.await.context("Send text failed")?;
         Ok(message.id.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_photo_rejected_matches_dimension_errors_only() {
+        let rejected = |error: ApiError| {
+            is_photo_rejected(
+                &anyhow::Error::from(RequestError::Api(error)).context("Send photo failed"),
+            )
+        };
+        assert!(rejected(ApiError::ImageProcessFailed));
+        assert!(rejected(ApiError::Unknown(
+            "Bad Request: PHOTO_INVALID_DIMENSIONS".to_string()
+        )));
+        assert!(!rejected(ApiError::BotBlocked));
+        assert!(!is_photo_rejected(&anyhow::anyhow!(
+            "PHOTO_INVALID_DIMENSIONS"
+        )));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::types::ChatId;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+type SlowModes = Arc<std::sync::Mutex<HashMap<ChatId, SlowMode>>>;
+
+/// Held for the duration of one logical push to a chat
+pub struct PushTurn {
+    _turn: OwnedMutexGuard<()>,
+}
+
+/// Held while one message is being sent, see [`ChatSendQueue::pace`]
+pub(super) struct SendSlot {
+    chat_id: ChatId,
+    /// `None` when the chat has no slow mode to keep
+    slow_modes: Option<SlowModes>,
+}
+
+impl Drop for SendSlot {
+    fn drop(&mut self) {
+        let Some(slow_modes) = &self.slow_modes else {
+            return;
+        };
+        let mut slow_modes = slow_modes.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(slow_mode) = slow_modes.get_mut(&self.chat_id) {
+            slow_mode.last_send = Some(Instant::now());
+        }
+    }
+}
+
+/// Telegram slow mode of a chat
+#[derive(Debug, Clone, Copy)]
+struct SlowMode {
+    /// Minimum time between two messages, zero when slow mode is off
+    delay: Duration,
+    last_send: Option<Instant>,
+}
+
+impl SlowMode {
+    /// How long the next message has to wait at `now`
+    fn wait_at(&self, now: Instant) -> Duration {
+        self.last_send
+            .map(|end| (end + self.delay).saturating_duration_since(now))
+            .unwrap_or_default()
+    }
+}
+
+/// Per-chat FIFO of logical pushes
+///
+/// Each chat has a fair mutex, so pushes waiting on the same chat start in the order
+/// they asked for their turn while other chats proceed concurrently. In chats with
+/// slow mode, every message also waits until the delay has passed since the previous
+/// one, including the later messages of the same push.
+#[derive(Clone, Default)]
+pub(super) struct ChatSendQueue {
+    turns: Arc<Mutex<HashMap<ChatId, Arc<Mutex<()>>>>>,
+    slow_modes: SlowModes,
+}
+
+impl ChatSendQueue {
+    pub(super) async fn acquire(&self, chat_id: ChatId) -> PushTurn {
+        let turn = {
+            let mut turns = self.turns.lock().await;
+            // Drop chats nobody is pushing to or waiting on
+            turns.retain(|_, turn| Arc::strong_count(turn) > 1);
+            Arc::clone(turns.entry(chat_id).or_default())
+        };
+        PushTurn {
+            _turn: turn.lock_owned().await,
+        }
+    }
+
+    /// Wait until the chat's slow mode lets the next message through
+    ///
+    /// Hold the returned slot until the send finished; the delay counts from then.
+    pub(super) async fn pace(&self, chat_id: ChatId) -> SendSlot {
+        let wait = {
+            let slow_modes = self.slow_modes.lock().unwrap_or_else(|e| e.into_inner());
+            match slow_modes.get(&chat_id) {
+                Some(slow_mode) if !slow_mode.delay.is_zero() => {
+                    Some(slow_mode.wait_at(Instant::now()))
+                }
+                _ => None,
+            }
+        };
+        let Some(wait) = wait else {
+            return SendSlot {
+                chat_id,
+                slow_modes: None,
+            };
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        SendSlot {
+            chat_id,
+            slow_modes: Some(Arc::clone(&self.slow_modes)),
+        }
+    }
+
+    /// Whether the chat's slow mode has been recorded
+    pub(super) fn knows_slow_mode(&self, chat_id: ChatId) -> bool {
+        self.slow_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&chat_id)
+    }
+
+    /// Record the chat's slow mode delay, zero when slow mode is off
+    pub(super) fn set_slow_mode(&self, chat_id: ChatId, delay: Duration) {
+        self.slow_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(chat_id)
+            .and_modify(|slow_mode| slow_mode.delay = delay)
+            .or_insert(SlowMode {
+                delay,
+                last_send: None,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pushes_to_one_chat_run_in_arrival_order() {
+        let queue = ChatSendQueue::default();
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let turn = queue.acquire(ChatId(1)).await;
+        // A different chat is not blocked
+        let _other = queue.acquire(ChatId(2)).await;
+
+        let mut waiting = Vec::new();
+        for push in 0..3 {
+            let queue = queue.clone();
+            let order = order.clone();
+            waiting.push(tokio::spawn(async move {
+                let _turn = queue.acquire(ChatId(1)).await;
+                order.lock().unwrap().push(push);
+            }));
+            // Let the push register its place in the queue
+            for _ in 0..5 {
+                tokio::task::yield_now().await;
+            }
+        }
+        assert!(order.lock().unwrap().is_empty());
+
+        drop(turn);
+        for push in waiting {
+            push.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn slow_mode_waits_for_delay_since_previous_send() {
+        let now = Instant::now();
+        let mut slow_mode = SlowMode {
+            delay: Duration::from_secs(30),
+            last_send: None,
+        };
+        assert_eq!(slow_mode.wait_at(now), Duration::ZERO);
+
+        slow_mode.last_send = Some(now);
+        assert_eq!(
+            slow_mode.wait_at(now + Duration::from_secs(10)),
+            Duration::from_secs(20)
+        );
+        assert_eq!(
+            slow_mode.wait_at(now + Duration::from_secs(45)),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_within_one_push_keep_the_slow_mode_delay() {
+        let queue = ChatSendQueue::default();
+        let delay = Duration::from_millis(50);
+        queue.set_slow_mode(ChatId(1), delay);
+
+        let _turn = queue.acquire(ChatId(1)).await;
+        drop(queue.pace(ChatId(1)).await);
+        let second = Instant::now();
+        drop(queue.pace(ChatId(1)).await);
+        assert!(second.elapsed() >= delay);
+
+        // Chats without slow mode are not held back
+        let other = Instant::now();
+        drop(queue.pace(ChatId(2)).await);
+        drop(queue.pace(ChatId(2)).await);
+        assert!(other.elapsed() < delay);
+    }
+}
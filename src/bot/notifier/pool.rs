@@ -0,0 +1,95 @@
+use super::ThrottledBot;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use teloxide::types::ChatId;
+
+/// Telegram user ID of a bot, taken from the numeric prefix of its token
+pub fn bot_id(bot: &ThrottledBot) -> Option<i64> {
+    bot.inner()
+        .token()
+        .split_once(':')
+        .and_then(|(id, _)| id.parse().ok())
+}
+
+/// Bots sharing the push load, each with its own rate limits.
+///
+/// A chat is served by the bot assigned to it, which is the last bot that received
+/// an update from it. Chats without an assignment go through the primary bot.
+#[derive(Clone)]
+pub struct BotPool {
+    bots: Arc<[(Option<i64>, ThrottledBot)]>,
+    assignments: Arc<RwLock<HashMap<ChatId, usize>>>,
+}
+
+impl BotPool {
+    pub fn new(primary: ThrottledBot, extra: Vec<ThrottledBot>) -> Self {
+        let bots = std::iter::once(primary)
+            .chain(extra)
+            .map(|bot| (bot_id(&bot), bot))
+            .collect();
+        Self {
+            bots,
+            assignments: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn primary(&self) -> &ThrottledBot {
+        &self.bots[0].1
+    }
+
+    pub fn bots(&self) -> impl Iterator<Item = &ThrottledBot> {
+        self.bots.iter().map(|(_, bot)| bot)
+    }
+
+    /// The bot that sends to `chat_id`
+    pub fn for_chat(&self, chat_id: ChatId) -> &ThrottledBot {
+        let index = self
+            .assignments
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&chat_id)
+            .copied()
+            .unwrap_or(0);
+        &self.bots[index].1
+    }
+
+    /// Send to `chat_id` through the bot with the given ID.
+    ///
+    /// Returns false when no bot in the pool has that ID.
+    pub fn assign(&self, chat_id: ChatId, bot_id: i64) -> bool {
+        let Some(index) = self.bots.iter().position(|(id, _)| *id == Some(bot_id)) else {
+            return false;
+        };
+        self.assignments
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(chat_id, index);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::adaptors::throttle::Limits;
+    use teloxide::requests::RequesterExt;
+    use teloxide::Bot;
+
+    fn throttled(token: &str) -> ThrottledBot {
+        Bot::new(token).throttle(Limits::default())
+    }
+
+    #[tokio::test]
+    async fn assigned_chats_use_their_bot() {
+        let pool = BotPool::new(throttled("111:primary"), vec![throttled("222:extra")]);
+        assert_eq!(pool.bots().count(), 2);
+        assert_eq!(bot_id(pool.for_chat(ChatId(-1))), Some(111));
+
+        assert!(pool.assign(ChatId(-1), 222));
+        assert_eq!(bot_id(pool.for_chat(ChatId(-1))), Some(222));
+        assert_eq!(bot_id(pool.for_chat(ChatId(-2))), Some(111));
+
+        assert!(!pool.assign(ChatId(-2), 333));
+        assert_eq!(bot_id(pool.for_chat(ChatId(-2))), Some(111));
+    }
+}
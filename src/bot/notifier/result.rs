@@ -4,6 +4,8 @@ pub struct BatchSendResult {
     pub failed_indices: Vec<usize>,
     /// The first message ID from the batch (for tracking/reply purposes)
     pub first_message_id: Option<i32>,
+    /// `(index, message ID)` of every item that was sent, in send order
+    pub message_ids: Vec<(usize, i32)>,
 }
 
 impl BatchSendResult {
@@ -12,9 +14,28 @@ impl BatchSendResult {
             succeeded_indices: Vec::new(),
             failed_indices: (0..total).collect(),
             first_message_id: None,
+            message_ids: Vec::new(),
         }
     }
 
+    /// Result of a single item sent as message `message_id`
+    pub(super) fn sent_one(message_id: i32) -> Self {
+        Self {
+            succeeded_indices: vec![0],
+            failed_indices: Vec::new(),
+            first_message_id: Some(message_id),
+            message_ids: vec![(0, message_id)],
+        }
+    }
+
+    /// Message the item at `index` was sent in, `None` if it was not sent
+    pub fn message_id_of(&self, index: usize) -> Option<i32> {
+        self.message_ids
+            .iter()
+            .find(|(sent, _)| *sent == index)
+            .map(|(_, message_id)| *message_id)
+    }
+
     pub fn is_complete_success(&self) -> bool {
         self.failed_indices.is_empty()
     }
@@ -19,10 +19,15 @@ impl Notifier {
         has_spoiler: bool,
         download_config: &DownloadButtonConfig,
     ) -> BatchSendResult {
-        let keyboard = download_config.build_keyboard();
+        let keyboard = download_config.build_push_keyboard(has_spoiler);
+        let caption = match caption {
+            Some(c) => Some(self.prepare_caption(chat_id, c, 0).await),
+            None => None,
+        };
 
         if let Err(e) = self
-            .bot
+            .bots
+            .for_chat(chat_id)
             .send_chat_action(chat_id, ChatAction::UploadVideo)
             .await
         {
@@ -41,14 +46,20 @@ impl Notifier {
         };
 
         match self
-            .send_animation_file(chat_id, &mp4_path, caption, has_spoiler, keyboard)
+            .send_animation_file(
+                chat_id,
+                &mp4_path,
+                caption.as_deref(),
+                has_spoiler,
+                keyboard,
+            )
             .await
         {
-            Ok(msg_id) => BatchSendResult {
-                succeeded_indices: vec![0],
-                failed_indices: Vec::new(),
-                first_message_id: Some(msg_id),
-            },
+            Ok(msg_id) => {
+                self.attach_channel_buttons(chat_id, msg_id, download_config)
+                    .await;
+                BatchSendResult::sent_one(msg_id)
+            }
             Err(e) => {
                 error!(
                     "Failed to send ugoira animation to chat {}: {:#}",
@@ -1,14 +1,17 @@
 //! Dialogue state management for multi-step interactions.
 //!
 //! This module provides the state machine for handling interactive settings
-//! where users need to provide input across multiple messages.
+//! where users need to provide input across multiple messages. States live in
+//! the `dialog_states` table so a pending dialogue survives a restart.
 
-use std::collections::HashMap;
+use crate::db::repo::Repo;
+use anyhow::Result;
+use chrono::Utc;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use teloxide::prelude::*;
 use teloxide::types::MessageId;
-use tokio::sync::RwLock;
+use tracing::{info, warn};
 
 /// Timeout duration for settings dialogue (5 minutes)
 pub const DIALOGUE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
@@ -17,53 +20,143 @@ pub const DIALOGUE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 ///
 /// Each user in a chat has their own independent state, preventing
 /// interference between concurrent users editing settings.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SettingsState {
     /// Waiting for user to input sensitive tags
     WaitingForSensitiveTags {
         /// The message ID of the settings panel to update after input
         settings_message_id: MessageId,
-        /// When this state was created
-        created_at: Instant,
     },
     /// Waiting for user to input excluded tags
     WaitingForExcludedTags {
         /// The message ID of the settings panel to update after input
         settings_message_id: MessageId,
-        /// When this state was created
-        created_at: Instant,
     },
 }
 
 impl SettingsState {
-    /// Check if this state has expired
-    pub fn is_expired(&self) -> bool {
-        let created_at = match self {
-            SettingsState::WaitingForSensitiveTags { created_at, .. } => created_at,
-            SettingsState::WaitingForExcludedTags { created_at, .. } => created_at,
-        };
-        created_at.elapsed() > DIALOGUE_TIMEOUT
-    }
-
     /// Get the settings message ID
     pub fn settings_message_id(&self) -> MessageId {
         match self {
             SettingsState::WaitingForSensitiveTags {
                 settings_message_id,
-                ..
             } => *settings_message_id,
             SettingsState::WaitingForExcludedTags {
                 settings_message_id,
-                ..
             } => *settings_message_id,
         }
     }
+
+    /// Value stored in `dialog_states.state`
+    fn as_str(&self) -> &'static str {
+        match self {
+            SettingsState::WaitingForSensitiveTags { .. } => "sensitive_tags",
+            SettingsState::WaitingForExcludedTags { .. } => "excluded_tags",
+        }
+    }
+
+    fn from_row(state: &str, settings_message_id: MessageId) -> Option<Self> {
+        match state {
+            "sensitive_tags" => Some(SettingsState::WaitingForSensitiveTags {
+                settings_message_id,
+            }),
+            "excluded_tags" => Some(SettingsState::WaitingForExcludedTags {
+                settings_message_id,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Storage for dialogue states keyed by (ChatId, UserId), backed by the database
+#[derive(Clone)]
+pub struct SettingsStorage {
+    repo: Arc<Repo>,
+}
+
+impl SettingsStorage {
+    pub fn new(repo: Arc<Repo>) -> Self {
+        Self { repo }
+    }
+
+    /// Active state of a user in a chat
+    ///
+    /// An expired state is removed and reported as absent.
+    pub async fn get(&self, chat_id: ChatId, user_id: UserId) -> Result<Option<SettingsState>> {
+        let user = user_id.0 as i64;
+        let Some(row) = self.repo.get_dialog_state(chat_id.0, user).await? else {
+            return Ok(None);
+        };
+
+        if row.expires_at <= Utc::now().naive_utc() {
+            self.repo.delete_dialog_state(chat_id.0, user).await?;
+            info!(
+                "Settings dialogue expired for user {} in chat {}",
+                user_id, chat_id
+            );
+            return Ok(None);
+        }
+
+        let state = SettingsState::from_row(&row.state, MessageId(row.settings_message_id));
+        if state.is_none() {
+            warn!(
+                "Unknown settings dialogue state {:?} for user {} in chat {}",
+                row.state, user_id, chat_id
+            );
+        }
+        Ok(state)
+    }
+
+    /// Start a dialogue, replacing any the user already had in this chat
+    pub async fn insert(
+        &self,
+        chat_id: ChatId,
+        user_id: UserId,
+        state: &SettingsState,
+    ) -> Result<()> {
+        self.repo
+            .set_dialog_state(
+                chat_id.0,
+                user_id.0 as i64,
+                state.as_str(),
+                state.settings_message_id().0,
+                DIALOGUE_TIMEOUT,
+            )
+            .await
+    }
+
+    /// End the dialogue of a user in a chat, returning whether one was active
+    pub async fn remove(&self, chat_id: ChatId, user_id: UserId) -> Result<bool> {
+        self.repo
+            .delete_dialog_state(chat_id.0, user_id.0 as i64)
+            .await
+    }
+
+    /// Drop dialogues that expired while the bot was not running
+    pub async fn purge_expired(&self) -> Result<u64> {
+        self.repo.delete_expired_dialog_states().await
+    }
 }
 
-/// Storage for dialogue states - thread-safe HashMap keyed by (ChatId, UserId)
-pub type SettingsStorage = Arc<RwLock<HashMap<(ChatId, UserId), SettingsState>>>;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Create a new settings storage instance
-pub fn new_settings_storage() -> SettingsStorage {
-    Arc::new(RwLock::new(HashMap::new()))
+    #[test]
+    fn settings_state_round_trips_through_row() {
+        for state in [
+            SettingsState::WaitingForSensitiveTags {
+                settings_message_id: MessageId(7),
+            },
+            SettingsState::WaitingForExcludedTags {
+                settings_message_id: MessageId(8),
+            },
+        ] {
+            assert_eq!(
+                SettingsState::from_row(state.as_str(), state.settings_message_id()),
+                Some(state)
+            );
+        }
+        assert_eq!(SettingsState::from_row("unknown", MessageId(1)), None);
+    }
 }
@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+/// Cache deduplication counters since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeStats {
+    /// Files stored as hard links to identical content
+    pub linked_files: u64,
+    /// Bytes not written thanks to those links
+    pub bytes_saved: u64,
+}
+
+/// Content hash index of cached files, kept in a small SQLite file in the cache root.
+///
+/// Maps every cached path to the SHA-256 of its content so a new download with
+/// the same bytes can be hard-linked to the existing file instead of stored again.
+#[derive(Debug)]
+pub(super) struct ContentIndex {
+    db_path: PathBuf,
+    /// Opened on first use; None if the index could not be opened (dedupe disabled)
+    db: OnceCell<Option<DatabaseConnection>>,
+    linked_files: AtomicU64,
+    bytes_saved: AtomicU64,
+}
+
+impl ContentIndex {
+    pub(super) fn new(db_path: PathBuf) -> Self {
+        Self {
+            db_path,
+            db: OnceCell::new(),
+            linked_files: AtomicU64::new(0),
+            bytes_saved: AtomicU64::new(0),
+        }
+    }
+
+    async fn connection(&self) -> Option<&DatabaseConnection> {
+        self.db
+            .get_or_init(|| async {
+                match Self::open(&self.db_path).await {
+                    Ok(db) => Some(db),
+                    Err(e) => {
+                        warn!("Cache dedupe disabled: {:#}", e);
+                        None
+                    }
+                }
+            })
+            .await
+            .as_ref()
+    }
+
+    async fn open(db_path: &Path) -> Result<DatabaseConnection> {
+        if let Some(parent) = db_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create cache directory")?;
+        }
+
+        let db = Database::connect(format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .context("Failed to open cache index")?;
+        db.execute_unprepared(
+            "CREATE TABLE IF NOT EXISTS cache_entries (
+                path TEXT PRIMARY KEY NOT NULL,
+                hash TEXT NOT NULL
+            )",
+        )
+        .await
+        .context("Failed to create cache index table")?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_cache_entries_hash ON cache_entries(hash)",
+        )
+        .await
+        .context("Failed to create cache index")?;
+
+        Ok(db)
+    }
+
+    /// Find a cached file with the given content hash.
+    ///
+    /// Entries whose file has been removed (e.g. by cleanup) are pruned on the way.
+    pub(super) async fn find_by_hash(&self, hash: &str) -> Option<PathBuf> {
+        let db = self.connection().await?;
+
+        let rows = match db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT path FROM cache_entries WHERE hash = ?",
+                [hash.into()],
+            ))
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("Failed to query cache index: {:#}", e);
+                return None;
+            }
+        };
+
+        for row in rows {
+            let Ok(path) = row.try_get::<String>("", "path") else {
+                continue;
+            };
+            let path = PathBuf::from(path);
+            if tokio::fs::metadata(&path).await.is_ok() {
+                return Some(path);
+            }
+            self.remove(db, &path).await;
+        }
+
+        None
+    }
+
+    /// Record the content hash of a cached file
+    pub(super) async fn record(&self, path: &Path, hash: &str) {
+        let Some(db) = self.connection().await else {
+            return;
+        };
+
+        if let Err(e) = db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "INSERT INTO cache_entries (path, hash) VALUES (?, ?) \
+                 ON CONFLICT(path) DO UPDATE SET hash = excluded.hash",
+                [path.to_string_lossy().into_owned().into(), hash.into()],
+            ))
+            .await
+        {
+            warn!("Failed to update cache index for {:?}: {:#}", path, e);
+        }
+    }
+
+    async fn remove(&self, db: &DatabaseConnection, path: &Path) {
+        if let Err(e) = db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "DELETE FROM cache_entries WHERE path = ?",
+                [path.to_string_lossy().into_owned().into()],
+            ))
+            .await
+        {
+            warn!("Failed to prune cache index entry {:?}: {:#}", path, e);
+        }
+    }
+
+    pub(super) fn record_link(&self, size: u64) {
+        self.linked_files.fetch_add(1, Ordering::Relaxed);
+        self.bytes_saved.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub(super) fn stats(&self) -> DedupeStats {
+        DedupeStats {
+            linked_files: self.linked_files.load(Ordering::Relaxed),
+            bytes_saved: self.bytes_saved.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 of the content
+pub(super) fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
@@ -1,10 +1,22 @@
 use anyhow::{Context, Result};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::io::AsyncWriteExt;
 use tokio::time::Duration;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+mod index;
+mod quota;
+
+pub use index::DedupeStats;
+use index::{content_hash, ContentIndex};
+use quota::CacheUsage;
+
+/// Name of the content hash index file in the cache root
+const INDEX_FILE_NAME: &str = "index.db";
 
 /// File cache manager for storing and retrieving cached files.
 ///
@@ -13,10 +25,19 @@ use tracing::{error, info};
 /// - Storage strategy: Uses hash-prefixed directories (bucketing)
 /// - Persistence: Async file read/write operations
 /// - Lifecycle: Automatic cleanup of expired files
+/// - Delivery cleanup: Optional early removal of files already sent to Telegram
+/// - Deduplication: Identical content under different URLs is hard-linked
+/// - Size limit: Optional least-recently-used eviction beyond a maximum size
 #[derive(Clone, Debug)]
 pub struct FileCacheManager {
     /// Cache root directory (e.g., "./data/cache")
     root_dir: PathBuf,
+    /// Content hash index used for deduplication (None = disabled)
+    index: Option<Arc<ContentIndex>>,
+    /// Cached files confirmed delivered, awaiting age-based removal (None = disabled)
+    delivered: Option<Arc<Mutex<HashSet<PathBuf>>>>,
+    /// Tracked cache usage for the size limit (None = unlimited)
+    usage: Option<Arc<Mutex<CacheUsage>>>,
 }
 
 impl FileCacheManager {
@@ -31,11 +52,56 @@ impl FileCacheManager {
     /// deleting files older than `retention_days`.
     pub fn new(root_dir: impl Into<PathBuf>, retention_days: u64) -> Self {
         let root_dir = root_dir.into();
+        let index = Arc::new(ContentIndex::new(root_dir.join(INDEX_FILE_NAME)));
 
         // Start background cleanup task
-        Self::start_background_cleanup(root_dir.clone(), retention_days);
+        Self::start_background_cleanup(root_dir.clone(), retention_days, Arc::clone(&index));
 
-        Self { root_dir }
+        Self {
+            root_dir,
+            index: Some(index),
+            delivered: None,
+            usage: None,
+        }
+    }
+
+    /// Limit the cache to `max_bytes`, evicting least recently used files beyond it.
+    ///
+    /// The cache is scanned incrementally in the background, a few bucket
+    /// directories per sweep, so the limit may be exceeded briefly between sweeps.
+    pub fn with_size_limit(mut self, max_bytes: u64) -> Self {
+        let usage = Arc::new(Mutex::new(CacheUsage::default()));
+        Self::start_quota_cleanup(self.root_dir.clone(), Arc::clone(&usage), max_bytes);
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Enable removal of delivered files once they are older than `max_age`.
+    ///
+    /// Meant for deployments behind a local Bot API server, where every upload is
+    /// also kept on the server's disk. Only files reported via [`Self::mark_delivered`]
+    /// are touched; everything else still follows `retention_days`.
+    pub fn with_delivered_cleanup(mut self, max_age: Duration) -> Self {
+        let delivered = Arc::new(Mutex::new(HashSet::new()));
+        Self::start_delivered_cleanup(Arc::clone(&delivered), max_age);
+        self.delivered = Some(delivered);
+        self
+    }
+
+    /// Record that a cached file has been delivered successfully.
+    ///
+    /// No-op unless delivery cleanup is enabled or when the path is outside the cache.
+    pub fn mark_delivered(&self, path: &Path) {
+        let Some(delivered) = &self.delivered else {
+            return;
+        };
+        if !path.starts_with(&self.root_dir) {
+            return;
+        }
+        delivered
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path.to_path_buf());
     }
 
     /// Check if URL is cached.
@@ -57,7 +123,7 @@ impl FileCacheManager {
     /// # Behavior
     /// 1. Calculates target path
     /// 2. Creates parent directories if needed
-    /// 3. Writes data asynchronously
+    /// 3. Hard-links an existing file with the same content, or writes data asynchronously
     /// 4. Returns the written file path
     pub async fn save(&self, url: &str, data: &[u8]) -> Result<PathBuf> {
         let path = self.resolve_path(url);
@@ -69,19 +135,92 @@ impl FileCacheManager {
                 .context("Failed to create cache directory")?;
         }
 
-        // Write file
-        let mut file = tokio::fs::File::create(&path)
+        let Some(index) = &self.index else {
+            Self::write_file(&path, data).await?;
+            self.record_usage(&path, data.len() as u64);
+            return Ok(path);
+        };
+
+        let hash = content_hash(data);
+        if let Some(existing) = index.find_by_hash(&hash).await {
+            if existing != path {
+                match Self::link_file(&existing, &path).await {
+                    Ok(()) => {
+                        index.record_link(data.len() as u64);
+                        index.record(&path, &hash).await;
+                        self.record_usage(&path, data.len() as u64);
+                        return Ok(path);
+                    }
+                    Err(e) => warn!(
+                        "Failed to link cached duplicate {:?}, writing a copy: {:#}",
+                        existing, e
+                    ),
+                }
+            }
+        }
+
+        Self::write_file(&path, data).await?;
+        index.record(&path, &hash).await;
+        self.record_usage(&path, data.len() as u64);
+
+        Ok(path)
+    }
+
+    fn record_usage(&self, path: &Path, size: u64) {
+        if let Some(usage) = &self.usage {
+            usage
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .record(path, size);
+        }
+    }
+
+    /// Cache deduplication counters since startup
+    pub fn dedupe_stats(&self) -> DedupeStats {
+        self.index
+            .as_ref()
+            .map(|index| index.stats())
+            .unwrap_or_default()
+    }
+
+    async fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+        let mut file = tokio::fs::File::create(path)
             .await
             .context("Failed to create cache file")?;
         file.write_all(data)
             .await
             .context("Failed to write cache data")?;
 
-        Ok(path)
+        Ok(())
+    }
+
+    /// Hard-link `existing` to `path`, replacing whatever is at `path`.
+    async fn link_file(existing: &Path, path: &Path) -> Result<()> {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).context("Failed to replace cache file"),
+        }
+        tokio::fs::hard_link(existing, path)
+            .await
+            .context("Failed to hard-link cache file")?;
+
+        // Links share one inode, so bump its mtime to keep retention counting from now
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .await
+            .context("Failed to open linked cache file")?;
+        file.into_std()
+            .await
+            .set_modified(std::time::SystemTime::now())
+            .context("Failed to refresh linked cache file time")?;
+
+        Ok(())
     }
 
     /// Start background cleanup task.
-    fn start_background_cleanup(root_dir: PathBuf, retention_days: u64) {
+    fn start_background_cleanup(root_dir: PathBuf, retention_days: u64, index: Arc<ContentIndex>) {
         tokio::spawn(async move {
             const STARTUP_DELAY: Duration = Duration::from_secs(60);
             const CLEANUP_PERIOD: Duration = Duration::from_secs(24 * 3600);
@@ -102,10 +241,108 @@ impl FileCacheManager {
                     Ok(_) => (),
                     Err(e) => error!("❌ Cache cleanup failed: {:#}", e),
                 }
+
+                let stats = index.stats();
+                if stats.linked_files > 0 {
+                    info!(
+                        "Cache dedupe: {} files linked, {} bytes saved",
+                        stats.linked_files, stats.bytes_saved
+                    );
+                }
+            }
+        });
+    }
+
+    /// Start background enforcement of the cache size limit.
+    fn start_quota_cleanup(root_dir: PathBuf, usage: Arc<Mutex<CacheUsage>>, max_bytes: u64) {
+        tokio::spawn(async move {
+            const SWEEP_PERIOD: Duration = Duration::from_secs(300);
+            /// Bucket directories rescanned per sweep (256 in total)
+            const BUCKETS_PER_SWEEP: usize = 32;
+
+            let mut interval = tokio::time::interval(SWEEP_PERIOD);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+
+                quota::refresh(&usage, &root_dir, BUCKETS_PER_SWEEP).await;
+                let count = quota::evict(&usage, max_bytes).await;
+                if count > 0 {
+                    info!("✅ Cache size limit enforced: {} files evicted", count);
+                }
+            }
+        });
+    }
+
+    /// Start background removal of delivered files.
+    fn start_delivered_cleanup(delivered: Arc<Mutex<HashSet<PathBuf>>>, max_age: Duration) {
+        tokio::spawn(async move {
+            const SWEEP_PERIOD: Duration = Duration::from_secs(600);
+
+            let mut interval = tokio::time::interval(SWEEP_PERIOD);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+
+                let count = Self::sweep_delivered(&delivered, max_age).await;
+                if count > 0 {
+                    info!(
+                        "✅ Delivered file cleanup complete: {} files deleted",
+                        count
+                    );
+                }
             }
         });
     }
 
+    /// Delete delivered files older than `max_age` (static helper).
+    ///
+    /// Entries whose file is already gone are forgotten; younger files stay tracked.
+    async fn sweep_delivered(delivered: &Mutex<HashSet<PathBuf>>, max_age: Duration) -> usize {
+        let paths: Vec<PathBuf> = delivered
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect();
+
+        let mut finished = Vec::new();
+        let mut deleted_count = 0;
+        for path in paths {
+            let metadata = match tokio::fs::metadata(&path).await {
+                Ok(m) => m,
+                Err(_) => {
+                    finished.push(path);
+                    continue;
+                }
+            };
+
+            let expired = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|elapsed| elapsed >= max_age);
+            if !expired {
+                continue;
+            }
+
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => deleted_count += 1,
+                Err(e) => warn!("Failed to remove delivered file {:?}: {:#}", path, e),
+            }
+            finished.push(path);
+        }
+
+        let mut delivered = delivered.lock().unwrap_or_else(|e| e.into_inner());
+        for path in &finished {
+            delivered.remove(path);
+        }
+
+        deleted_count
+    }
+
     /// Execute cleanup logic (static helper).
     async fn cleanup_dir(root_dir: &Path, retention_days: u64) -> Result<usize> {
         let threshold = Duration::from_hours(retention_days * 24);
@@ -200,6 +437,9 @@ mod tests {
     fn test_generate_key_deterministic() {
         let cache = FileCacheManager {
             root_dir: PathBuf::from("/tmp/cache"),
+            index: None,
+            delivered: None,
+            usage: None,
         };
 
         let url = "https://example.com/image.jpg";
@@ -214,6 +454,9 @@ mod tests {
     fn test_safe_url_slug() {
         let cache = FileCacheManager {
             root_dir: PathBuf::from("/tmp/cache"),
+            index: None,
+            delivered: None,
+            usage: None,
         };
 
         assert_eq!(
@@ -231,6 +474,9 @@ mod tests {
     fn test_extract_extension() {
         let cache = FileCacheManager {
             root_dir: PathBuf::from("/tmp/cache"),
+            index: None,
+            delivered: None,
+            usage: None,
         };
 
         assert_eq!(
@@ -249,6 +495,9 @@ mod tests {
     fn test_resolve_path() {
         let cache = FileCacheManager {
             root_dir: PathBuf::from("/tmp/cache"),
+            index: None,
+            delivered: None,
+            usage: None,
         };
 
         let path = cache.resolve_path("https://example.com/test.jpg");
@@ -257,4 +506,129 @@ mod tests {
         assert!(path.starts_with("/tmp/cache"));
         assert!(path.to_string_lossy().ends_with(".jpg"));
     }
+
+    #[tokio::test]
+    async fn test_sweep_delivered_removes_only_tracked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let delivered_path = dir.path().join("ab").join("delivered.jpg");
+        let kept_path = dir.path().join("ab").join("kept.jpg");
+        tokio::fs::create_dir_all(delivered_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&delivered_path, b"a").await.unwrap();
+        tokio::fs::write(&kept_path, b"b").await.unwrap();
+
+        let cache = FileCacheManager {
+            root_dir: dir.path().to_path_buf(),
+            index: None,
+            delivered: Some(Arc::new(Mutex::new(HashSet::new()))),
+            usage: None,
+        };
+        cache.mark_delivered(&delivered_path);
+        // Files outside the cache root are never tracked
+        cache.mark_delivered(Path::new("/tmp/outside.jpg"));
+
+        let delivered = cache.delivered.as_ref().unwrap();
+        assert_eq!(delivered.lock().unwrap().len(), 1);
+
+        // Younger than max_age: kept and still tracked
+        let count = FileCacheManager::sweep_delivered(delivered, Duration::from_secs(3600)).await;
+        assert_eq!(count, 0);
+        assert!(delivered_path.exists());
+        assert_eq!(delivered.lock().unwrap().len(), 1);
+
+        let count = FileCacheManager::sweep_delivered(delivered, Duration::ZERO).await;
+        assert_eq!(count, 1);
+        assert!(!delivered_path.exists());
+        assert!(kept_path.exists());
+        assert!(delivered.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_links_duplicate_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCacheManager {
+            root_dir: dir.path().to_path_buf(),
+            index: Some(Arc::new(ContentIndex::new(
+                dir.path().join(INDEX_FILE_NAME),
+            ))),
+            delivered: None,
+            usage: None,
+        };
+
+        let first = cache
+            .save("https://example.com/img/1_master1200.jpg", b"same")
+            .await
+            .unwrap();
+        let second = cache
+            .save("https://example.com/img/1_p0.jpg", b"same")
+            .await
+            .unwrap();
+        let other = cache
+            .save("https://example.com/img/2_p0.jpg", b"different")
+            .await
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(tokio::fs::read(&second).await.unwrap(), b"same");
+        assert_eq!(tokio::fs::read(&other).await.unwrap(), b"different");
+        assert_eq!(
+            cache.dedupe_stats(),
+            DedupeStats {
+                linked_files: 1,
+                bytes_saved: 4,
+            }
+        );
+
+        // A removed original is pruned from the index and no longer linked against
+        tokio::fs::remove_file(&first).await.unwrap();
+        tokio::fs::remove_file(&second).await.unwrap();
+        cache
+            .save("https://example.com/img/1_original.jpg", b"same")
+            .await
+            .unwrap();
+        assert_eq!(cache.dedupe_stats().linked_files, 1);
+    }
+
+    #[tokio::test]
+    async fn test_size_limit_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = std::time::SystemTime::now();
+        let write = |bucket: &str, name: &str, age_secs: u64| {
+            let path = dir.path().join(bucket).join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, [0u8; 400]).unwrap();
+            let used = now - Duration::from_secs(age_secs);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_times(
+                    std::fs::FileTimes::new()
+                        .set_accessed(used)
+                        .set_modified(used),
+                )
+                .unwrap();
+            path
+        };
+        let oldest = write("aa", "oldest.jpg", 3000);
+        let older = write("bb", "older.jpg", 2000);
+        let recent = write("aa", "recent.jpg", 10);
+
+        let usage = Mutex::new(CacheUsage::default());
+        // One bucket per sweep: the second bucket is unknown until the next refresh
+        quota::refresh(&usage, dir.path(), 1).await;
+        quota::refresh(&usage, dir.path(), 1).await;
+        assert_eq!(usage.lock().unwrap().total_bytes(), 1200);
+
+        // Within the limit: nothing is evicted
+        assert_eq!(quota::evict(&usage, 1200).await, 0);
+
+        // Shrinks to 90% of the limit (630 bytes), oldest files first
+        assert_eq!(quota::evict(&usage, 700).await, 2);
+        assert!(!oldest.exists());
+        assert!(!older.exists());
+        assert!(recent.exists());
+        assert_eq!(usage.lock().unwrap().total_bytes(), 400);
+    }
 }
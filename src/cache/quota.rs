@@ -0,0 +1,177 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::warn;
+
+/// A cached file as seen by the last scan of its bucket
+#[derive(Debug, Clone)]
+struct CachedFile {
+    path: PathBuf,
+    size: u64,
+    /// Latest of access and modification time
+    last_used: SystemTime,
+}
+
+/// Incrementally maintained view of the cache contents, used to enforce the size limit.
+///
+/// Bucket directories are rescanned a few at a time, so a full pass over a large cache
+/// is spread across several sweeps instead of walking everything at once. Files written
+/// in between are tracked as soon as they are saved.
+#[derive(Debug, Default)]
+pub(super) struct CacheUsage {
+    buckets: HashMap<PathBuf, Vec<CachedFile>>,
+    /// Buckets left to rescan in the current pass
+    pending: VecDeque<PathBuf>,
+}
+
+impl CacheUsage {
+    /// Track a freshly saved file until its bucket is rescanned
+    pub(super) fn record(&mut self, path: &Path, size: u64) {
+        let Some(bucket) = path.parent() else {
+            return;
+        };
+        let files = self.buckets.entry(bucket.to_path_buf()).or_default();
+        files.retain(|file| file.path != path);
+        files.push(CachedFile {
+            path: path.to_path_buf(),
+            size,
+            last_used: SystemTime::now(),
+        });
+    }
+
+    pub(super) fn total_bytes(&self) -> u64 {
+        self.buckets.values().flatten().map(|file| file.size).sum()
+    }
+
+    /// Least recently used files to delete so the cache shrinks to 90% of `max_bytes`.
+    ///
+    /// Nothing is selected while the cache is within the limit; the extra headroom keeps
+    /// a busy cache from evicting on every sweep.
+    fn select_evictions(&self, max_bytes: u64) -> Vec<PathBuf> {
+        let mut total = self.total_bytes();
+        if total <= max_bytes {
+            return Vec::new();
+        }
+
+        let target = max_bytes - max_bytes / 10;
+        let mut files: Vec<&CachedFile> = self.buckets.values().flatten().collect();
+        files.sort_by_key(|file| file.last_used);
+
+        let mut evicted = Vec::new();
+        for file in files {
+            if total <= target {
+                break;
+            }
+            total = total.saturating_sub(file.size);
+            evicted.push(file.path.clone());
+        }
+        evicted
+    }
+
+    fn forget(&mut self, path: &Path) {
+        if let Some(files) = path
+            .parent()
+            .and_then(|bucket| self.buckets.get_mut(bucket))
+        {
+            files.retain(|file| file.path != path);
+        }
+    }
+}
+
+/// Rescan up to `max_buckets` bucket directories, starting a new pass when the last one ended
+pub(super) async fn refresh(usage: &Mutex<CacheUsage>, root_dir: &Path, max_buckets: usize) {
+    let needs_new_pass = usage
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .pending
+        .is_empty();
+    if needs_new_pass {
+        let buckets = list_buckets(root_dir).await;
+        let mut usage = usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.buckets.retain(|bucket, _| buckets.contains(bucket));
+        usage.pending = buckets.into();
+    }
+
+    let batch: Vec<PathBuf> = {
+        let mut usage = usage.lock().unwrap_or_else(|e| e.into_inner());
+        let count = max_buckets.min(usage.pending.len());
+        usage.pending.drain(..count).collect()
+    };
+
+    for bucket in batch {
+        let files = scan_bucket(&bucket).await;
+        usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .buckets
+            .insert(bucket, files);
+    }
+}
+
+/// Delete least recently used files until the cache fits in `max_bytes`.
+///
+/// Returns the number of deleted files.
+pub(super) async fn evict(usage: &Mutex<CacheUsage>, max_bytes: u64) -> usize {
+    let paths = usage
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .select_evictions(max_bytes);
+
+    let mut deleted_count = 0;
+    for path in paths {
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => deleted_count += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                warn!("Failed to evict cached file {:?}: {:#}", path, e);
+                continue;
+            }
+        }
+        usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .forget(&path);
+    }
+
+    deleted_count
+}
+
+async fn list_buckets(root_dir: &Path) -> Vec<PathBuf> {
+    let mut buckets = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(root_dir).await else {
+        return buckets; // Directory doesn't exist yet
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+            buckets.push(entry.path());
+        }
+    }
+    buckets
+}
+
+async fn scan_bucket(bucket: &Path) -> Vec<CachedFile> {
+    let mut files = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(bucket).await else {
+        return files;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let last_used = [metadata.accessed().ok(), metadata.modified().ok()]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        files.push(CachedFile {
+            path: entry.path(),
+            size: metadata.len(),
+            last_used,
+        });
+    }
+    files
+}
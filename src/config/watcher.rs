@@ -0,0 +1,220 @@
+//! Reloads the reload-safe parts of `config.toml` while the bot is running.
+//!
+//! The running configuration lives in a [`SharedConfig`]; handlers and engines
+//! read it on use instead of keeping their own copies, so a reload takes effect
+//! on the next command or poll. Everything else in the file still needs a restart.
+
+use super::Config;
+use arc_swap::ArcSwap;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// The running configuration, swapped as a whole on reload
+pub type SharedConfig = Arc<ArcSwap<Config>>;
+
+/// Handle for replacing the log filter installed in `main`
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
+
+/// File watched for changes, the same one `Config::load` reads
+const CONFIG_FILE: &str = "config.toml";
+
+/// Editors often write a file in several steps; wait for them to settle
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub fn shared(config: Config) -> SharedConfig {
+    Arc::new(ArcSwap::from_pointee(config))
+}
+
+/// Log filter for the configured level, keeping noisy dependencies at warn
+pub fn log_filter(level: tracing::Level) -> EnvFilter {
+    EnvFilter::from_default_env()
+        .add_directive(level.into())
+        .add_directive("sqlx=warn".parse().unwrap())
+        .add_directive("sea_orm=warn".parse().unwrap())
+        .add_directive("hyper_util=warn".parse().unwrap())
+}
+
+/// Copy the reload-safe settings of `new` onto `current`
+///
+/// Returns the merged configuration and the keys that changed.
+fn apply_reloadable(current: &Config, new: &Config) -> (Config, Vec<&'static str>) {
+    let mut merged = current.clone();
+    let mut changed = Vec::new();
+
+    if merged.content.sensitive_tags != new.content.sensitive_tags {
+        merged.content.sensitive_tags = new.content.sensitive_tags.clone();
+        changed.push("content.sensitive_tags");
+    }
+    if merged.content.download_original_threshold != new.content.download_original_threshold {
+        merged.content.download_original_threshold = new.content.download_original_threshold;
+        changed.push("content.download_original_threshold");
+    }
+    if merged.scheduler.min_task_interval_sec != new.scheduler.min_task_interval_sec {
+        merged.scheduler.min_task_interval_sec = new.scheduler.min_task_interval_sec;
+        changed.push("scheduler.min_task_interval_sec");
+    }
+    if merged.scheduler.max_task_interval_sec != new.scheduler.max_task_interval_sec {
+        merged.scheduler.max_task_interval_sec = new.scheduler.max_task_interval_sec;
+        changed.push("scheduler.max_task_interval_sec");
+    }
+    if !merged
+        .logging
+        .level
+        .eq_ignore_ascii_case(&new.logging.level)
+    {
+        merged.logging.level = new.logging.level.clone();
+        changed.push("logging.level");
+    }
+
+    (merged, changed)
+}
+
+pub struct ConfigWatcher {
+    config: SharedConfig,
+    log_handle: LogReloadHandle,
+    path: PathBuf,
+}
+
+impl ConfigWatcher {
+    pub fn new(config: SharedConfig, log_handle: LogReloadHandle) -> Self {
+        Self {
+            config,
+            log_handle,
+            path: PathBuf::from(CONFIG_FILE),
+        }
+    }
+
+    /// Watch the config file and reload it on change - runs indefinitely
+    pub async fn run(self) {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let file_name = self.path.file_name().map(|name| name.to_os_string());
+        let mut watcher =
+            match notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                match result {
+                    Ok(event) => {
+                        let relevant =
+                            matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                                && event.paths.iter().any(|path| {
+                                    path.file_name().map(|name| name.to_os_string()) == file_name
+                                });
+                        if relevant {
+                            let _ = tx.send(());
+                        }
+                    }
+                    Err(e) => warn!("Config watcher error: {:#}", e),
+                }
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    error!("Failed to create config watcher: {:#}", e);
+                    return;
+                }
+            };
+
+        // Watch the directory, editors often replace the file instead of writing to it
+        let dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            error!("Failed to watch {:?} for config changes: {:#}", dir, e);
+            return;
+        }
+        info!("👀 Watching {:?} for config changes", self.path);
+
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+            self.reload();
+        }
+    }
+
+    fn reload(&self) {
+        let new = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Ignoring invalid {:?}: {:#}", self.path, e);
+                return;
+            }
+        };
+
+        if new.scheduler.min_task_interval_sec > new.scheduler.max_task_interval_sec {
+            warn!(
+                "Ignoring {:?}: scheduler.min_task_interval_sec is larger than max_task_interval_sec",
+                self.path
+            );
+            return;
+        }
+
+        let current = self.config.load();
+        let (merged, changed) = apply_reloadable(&current, &new);
+        if changed.is_empty() {
+            debug!("{:?} changed, no reloadable settings differ", self.path);
+            return;
+        }
+
+        if changed.contains(&"logging.level") {
+            if let Err(e) = self.log_handle.reload(log_filter(merged.log_level())) {
+                warn!(
+                    "Failed to apply log level {}: {:#}",
+                    merged.logging.level, e
+                );
+            }
+        }
+        self.config.store(Arc::new(merged));
+        info!(
+            "🔄 Reloaded configuration: {} (other settings take effect after a restart)",
+            changed.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        serde_json::from_value(serde_json::json!({
+            "telegram": { "bot_token": "token" },
+            "pixiv": { "refresh_token": "token" },
+            "database": { "url": "sqlite::memory:" },
+            "logging": { "level": "info", "dir": "logs" },
+            "scheduler": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn apply_reloadable_copies_only_reload_safe_settings() {
+        let current = config();
+        let mut new = config();
+        new.content.sensitive_tags = vec!["R-18".to_string()];
+        new.scheduler.min_task_interval_sec = 60;
+        new.logging.level = "DEBUG".to_string();
+        new.logging.dir = "elsewhere".to_string();
+        new.telegram.bot_token = "other".to_string();
+
+        let (merged, changed) = apply_reloadable(&current, &new);
+
+        assert_eq!(
+            changed,
+            vec![
+                "content.sensitive_tags",
+                "scheduler.min_task_interval_sec",
+                "logging.level"
+            ]
+        );
+        assert_eq!(merged.content.sensitive_tags, vec!["R-18".to_string()]);
+        assert_eq!(merged.scheduler.min_task_interval_sec, 60);
+        assert_eq!(merged.logging.level, "DEBUG");
+        assert_eq!(merged.logging.dir, "logs");
+        assert_eq!(merged.telegram.bot_token, "token");
+
+        let (_, unchanged) = apply_reloadable(&merged, &new);
+        assert!(unchanged.is_empty());
+    }
+}
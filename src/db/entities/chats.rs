@@ -1,7 +1,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::db::types::Tags;
+use crate::db::types::{AiFilter, AuthorIds, TagFilter, Tags};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
 #[sea_orm(table_name = "chats")]
@@ -17,6 +17,24 @@ pub struct Model {
     pub created_at: DateTime,
     /// 是否允许在群组中不 @bot 也能响应命令
     pub allow_without_mention: bool,
+    /// 是否允许推送 R-18/R-18G 作品 (依据 Pixiv 的 x_restrict)
+    pub allow_r18: bool,
+    /// 是否在推送单图作品后额外发送原图文件
+    pub send_original: bool,
+    /// 负责向该聊天推送的 Bot 的用户 ID，为空时使用主 Bot
+    pub bot_id: Option<i64>,
+    /// 是否自动处理聊天中发送的 Pixiv 链接
+    pub link_preview: bool,
+    /// 不推送、不预览其作品的 Pixiv 作者 ID
+    pub blocked_authors: AuthorIds,
+    /// 慢速模式下两条消息之间的最短间隔（秒），`0` 为未开启，为空表示尚未查询
+    pub slow_mode_delay: Option<i32>,
+    /// 订阅未单独设置时使用的 AI 作品过滤方式
+    pub ai_filter: AiFilter,
+    /// 新建 Pixiv 订阅时合并进订阅标签过滤的默认过滤条件
+    pub default_filter: TagFilter,
+    /// 作品重试次数用尽被跳过时是否通知该聊天
+    pub notify_failures: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,8 +1,15 @@
 //! SeaORM Entities (Placeholder)
+pub mod channel_admins;
 pub mod chats;
+pub mod dialog_states;
 pub mod eh_download_queue;
 pub mod eh_gp_spend_attempts;
 pub mod messages;
+pub mod push_log;
+pub mod push_queue;
+pub mod sent_artworks;
+pub mod subscription_targets;
 pub mod subscriptions;
+pub mod tag_aliases;
 pub mod tasks;
 pub mod users;
@@ -0,0 +1,50 @@
+use crate::db::types::SentPages;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Author push waiting for a retry.
+///
+/// Enqueued by AuthorEngine when a work fails to send or is only partly sent;
+/// the RetryEngine resends the missing pages with exponential backoff.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "push_queue")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub subscription_id: i32,
+    pub chat_id: i64,
+    pub illust_id: i64,
+    pub sent_pages: SentPages,
+    pub total_pages: i32,
+    /// Whether the pages sent so far carried a spoiler
+    #[sea_orm(default = false)]
+    pub spoiler: bool,
+    /// "pending", "done", "abandoned"
+    pub status: String,
+    /// Retries made so far, not counting the original push
+    #[sea_orm(default = 0)]
+    pub attempts: i32,
+    pub next_attempt_at: DateTime,
+    #[sea_orm(nullable)]
+    pub last_error: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::subscriptions::Entity",
+        from = "Column::SubscriptionId",
+        to = "super::subscriptions::Column::Id"
+    )]
+    Subscription,
+}
+
+impl Related<super::subscriptions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Subscription.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
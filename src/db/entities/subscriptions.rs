@@ -1,7 +1,10 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::db::types::{BooruFilter, EhFilter, SubscriptionState, TagFilter};
+use crate::db::types::{
+    AiFilter, BooruFilter, EhFilter, PushImageSize, PushMode, SpoilerMode, SubscriptionState,
+    TagFilter,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
 #[sea_orm(table_name = "subscriptions")]
@@ -18,6 +21,40 @@ pub struct Model {
     pub eh_filter: Option<EhFilter>,
     pub latest_data: Option<SubscriptionState>,
     pub created_at: DateTime,
+    /// Optimistic lock version, bumped on every `latest_data` update
+    #[serde(default)]
+    pub version: i32,
+    /// Telegram user who first added the subscription (None for legacy rows)
+    #[serde(default)]
+    pub created_by: Option<i64>,
+    /// Overrides the chat's spoiler decision for pushes of this subscription
+    #[serde(default)]
+    pub spoiler_mode: SpoilerMode,
+    /// False while paused with `/pause`; engines skip it but keep `latest_data`
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Whether new works of an author are pushed one by one or as an album
+    #[serde(default)]
+    pub push_mode: PushMode,
+    /// Pushes of this subscription are sent without a notification sound
+    #[serde(default)]
+    pub silent: bool,
+    /// Days before today whose ranking a ranking subscription fetches (0 = latest)
+    #[serde(default)]
+    pub ranking_date_offset: i32,
+    /// Forum topic of the chat that pushes go into, `None` for the chat itself
+    #[serde(default)]
+    pub message_thread_id: Option<i32>,
+    /// AI-generated works filter, `auto` follows the chat's default
+    #[serde(default)]
+    pub ai_filter: AiFilter,
+    /// Image size of pushed works, `auto` follows the global config
+    #[serde(default)]
+    pub image_size: PushImageSize,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
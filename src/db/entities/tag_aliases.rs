@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Alternative spelling of a tag, e.g. `genshin` for `原神`.
+///
+/// `alias` and `tag` are stored normalized; tag filters treat every alias as
+/// the tag it maps to.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "tag_aliases")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub alias: String,
+    pub tag: String,
+    /// "manual" (added with /tagalias) or "import" (seeded from the dataset file)
+    pub source: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -15,6 +15,12 @@ pub struct Model {
     pub next_poll_at: DateTime,
     pub last_polled_at: Option<DateTime>,
     pub author_name: Option<String>, // 作者名字（仅 type="author" 时有值）
+    pub poll_interval_min: Option<i32>, // 自定义轮询间隔（分钟），为空时使用全局配置
+    pub last_success_at: Option<DateTime>, // 上次执行成功的时间
+    pub last_error: Option<String>,  // 最近一次执行失败的错误信息
+    pub last_error_at: Option<DateTime>,
+    pub failure_count: i32,        // 连续失败次数，成功后清零
+    pub dead_at: Option<DateTime>, // 作者持续 404/403 后停止轮询的时间，重新订阅时清除
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
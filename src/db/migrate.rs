@@ -0,0 +1,231 @@
+//! Startup migration runner and the `pixivbot migrate` operator commands.
+//!
+//! SQLite migrations are not wrapped in a transaction, so a failing migration
+//! leaves the schema at the last one that succeeded. Startup checks the
+//! migration table first and, on failure, names the migration that broke and
+//! how to recover instead of exiting with the bare database error.
+
+use crate::utils::time::to_local;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::DateTime;
+use migration::{Migrator, MigratorTrait, SchemaManager};
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use std::collections::HashMap;
+use tracing::{error, info};
+
+const MIGRATE_USAGE: &str = "Usage: pixivbot migrate [--status | --redo <name>]";
+
+/// A known migration and when it was applied (unix seconds)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MigrationStatus {
+    name: String,
+    applied_at: Option<i64>,
+}
+
+/// Every migration of this binary in order, plus the versions recorded in the
+/// database that this binary does not know about
+async fn migration_status(db: &DatabaseConnection) -> Result<(Vec<MigrationStatus>, Vec<String>)> {
+    Migrator::install(db)
+        .await
+        .context("Failed to create the migration table")?;
+    let mut applied: HashMap<String, i64> = Migrator::get_migration_models(db)
+        .await
+        .context("Failed to read the migration table")?
+        .into_iter()
+        .map(|model| (model.version, model.applied_at))
+        .collect();
+
+    let known: Vec<MigrationStatus> = Migrator::migrations()
+        .iter()
+        .map(|migration| {
+            let name = migration.name().to_string();
+            let applied_at = applied.remove(&name);
+            MigrationStatus { name, applied_at }
+        })
+        .collect();
+    let mut unknown: Vec<String> = applied.into_keys().collect();
+    unknown.sort();
+
+    Ok((known, unknown))
+}
+
+fn pending_names(status: &[MigrationStatus]) -> Vec<&str> {
+    status
+        .iter()
+        .filter(|migration| migration.applied_at.is_none())
+        .map(|migration| migration.name.as_str())
+        .collect()
+}
+
+/// Apply pending migrations, explaining how to recover when one fails
+pub async fn run_migrations(db: &DatabaseConnection) -> Result<()> {
+    let (status, unknown) = migration_status(db).await?;
+    if !unknown.is_empty() {
+        bail!(
+            "The database has migrations this build does not know ({}). It was last used by a \
+             newer PixivBot version; run that version again or restore a backup taken before it.",
+            unknown.join(", ")
+        );
+    }
+
+    let pending = pending_names(&status);
+    if pending.is_empty() {
+        info!(
+            "Database schema is up to date ({} migrations)",
+            status.len()
+        );
+        return Ok(());
+    }
+    info!(
+        "Applying {} pending migrations: {}",
+        pending.len(),
+        pending.join(", ")
+    );
+
+    let Err(e) = Migrator::up(db, None).await else {
+        return Ok(());
+    };
+
+    // Migrations run in order, so the first one still pending is the one that failed
+    let (status_after, _) = migration_status(db).await?;
+    let failed = pending_names(&status_after)
+        .first()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let last_applied = status_after
+        .iter()
+        .rev()
+        .find(|migration| migration.applied_at.is_some())
+        .map_or("none", |migration| migration.name.as_str());
+    error!(
+        "Migration {} failed; the database is left at {}. Back up the database file, fix the \
+         cause below and start the bot again. `pixivbot migrate --status` lists applied and \
+         pending migrations, `pixivbot migrate --redo <name>` rolls back and re-applies one.",
+        failed, last_applied
+    );
+
+    Err(anyhow!(e)).context(format!("Migration {} failed", failed))
+}
+
+/// Roll back and re-apply one applied migration in a single transaction
+pub async fn redo_migration(db: &DatabaseConnection, name: &str) -> Result<()> {
+    let migration = Migrator::migrations()
+        .into_iter()
+        .find(|migration| migration.name() == name)
+        .with_context(|| format!("Unknown migration {}", name))?;
+
+    let (status, _) = migration_status(db).await?;
+    let applied = status
+        .iter()
+        .any(|migration| migration.name == name && migration.applied_at.is_some());
+    if !applied {
+        bail!(
+            "Migration {} is not applied yet, start the bot to apply pending migrations",
+            name
+        );
+    }
+
+    let txn = db.begin().await.context("Failed to start transaction")?;
+    let manager = SchemaManager::new(&txn);
+    migration
+        .down(&manager)
+        .await
+        .with_context(|| format!("Failed to roll back migration {}", name))?;
+    migration
+        .up(&manager)
+        .await
+        .with_context(|| format!("Failed to re-apply migration {}", name))?;
+    txn.commit()
+        .await
+        .with_context(|| format!("Failed to commit redo of migration {}", name))?;
+
+    Ok(())
+}
+
+fn format_status(status: &[MigrationStatus], unknown: &[String]) -> Vec<String> {
+    let mut lines: Vec<String> = status
+        .iter()
+        .map(|migration| match migration.applied_at {
+            Some(applied_at) => {
+                let applied = DateTime::from_timestamp(applied_at, 0)
+                    .map(|time| {
+                        to_local(time.naive_utc())
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| applied_at.to_string());
+                format!("[x] {}  (applied {})", migration.name, applied)
+            }
+            None => format!("[ ] {}  (pending)", migration.name),
+        })
+        .collect();
+    lines.extend(
+        unknown
+            .iter()
+            .map(|name| format!("[?] {}  (not known to this build)", name)),
+    );
+    lines.push(format!(
+        "{} applied, {} pending",
+        status.len() - pending_names(status).len(),
+        pending_names(status).len()
+    ));
+    lines
+}
+
+/// `pixivbot migrate [--status | --redo <name>]`
+pub async fn run_cli(db: &DatabaseConnection, args: &[String]) -> Result<()> {
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["--status"] => {
+            let (status, unknown) = migration_status(db).await?;
+            for line in format_status(&status, &unknown) {
+                println!("{}", line);
+            }
+        }
+        ["--redo", name] => {
+            println!(
+                "Redoing migration {}, data its rollback drops is not restored",
+                name
+            );
+            redo_migration(db, name).await?;
+            println!("Migration {} rolled back and re-applied", name);
+        }
+        _ => bail!(MIGRATE_USAGE),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    #[tokio::test]
+    async fn migrations_apply_report_status_and_redo() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+
+        let (status, unknown) = migration_status(&db).await.unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(pending_names(&status).len(), Migrator::migrations().len());
+
+        run_migrations(&db).await.unwrap();
+        let (status, _) = migration_status(&db).await.unwrap();
+        assert!(pending_names(&status).is_empty());
+        let summary = format_status(&status, &["m20990101_000000_future".to_string()]);
+        assert_eq!(
+            summary.last().unwrap(),
+            &format!("{} applied, 0 pending", status.len())
+        );
+        assert!(summary.iter().any(|line| line.starts_with("[?] m20990101")));
+
+        let last = status.last().unwrap().name.clone();
+        redo_migration(&db, &last).await.unwrap();
+        assert!(redo_migration(&db, "m20990101_000000_future")
+            .await
+            .is_err());
+    }
+}
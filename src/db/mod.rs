@@ -1,5 +1,6 @@
 //! Database module
 pub mod entities;
+pub mod migrate;
 pub mod repo;
 pub mod types;
 
@@ -1,15 +1,25 @@
 use anyhow::{Context, Result};
 use sea_orm::DatabaseConnection;
 
+mod channel_admins;
 mod chats;
+mod dialog_states;
 pub mod eh_download_queue;
 pub mod eh_gp_spend_attempts;
 mod messages;
+mod push_log;
+pub mod push_queue;
+mod sent_artworks;
 mod stats;
+mod subscription_targets;
 mod subscriptions;
+mod tag_aliases;
 mod tasks;
 mod users;
 
+pub use push_log::PushStats;
+pub use subscriptions::{is_version_conflict, SourceFilter};
+
 pub struct Repo {
     db: DatabaseConnection,
 }
@@ -65,7 +75,16 @@ pub mod tests_helpers {
                 excluded_tags TEXT NOT NULL DEFAULT '[]',
                 sensitive_tags TEXT NOT NULL DEFAULT '[]',
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                allow_without_mention BOOLEAN NOT NULL DEFAULT 0
+                allow_without_mention BOOLEAN NOT NULL DEFAULT 0,
+                allow_r18 BOOLEAN NOT NULL DEFAULT 0,
+                send_original BOOLEAN NOT NULL DEFAULT 0,
+                bot_id INTEGER,
+                link_preview BOOLEAN NOT NULL DEFAULT 1,
+                blocked_authors TEXT NOT NULL DEFAULT '[]',
+                slow_mode_delay INTEGER,
+                ai_filter TEXT NOT NULL DEFAULT 'all',
+                default_filter TEXT NOT NULL DEFAULT '{"include":[],"exclude":[]}',
+                notify_failures BOOLEAN NOT NULL DEFAULT 0
             )
             "#,
         ))
@@ -103,6 +122,12 @@ pub mod tests_helpers {
                 author_name TEXT,
                 next_poll_at TIMESTAMP NOT NULL,
                 last_polled_at TIMESTAMP,
+                poll_interval_min INTEGER,
+                last_success_at TIMESTAMP,
+                last_error TEXT,
+                last_error_at TIMESTAMP,
+                failure_count INTEGER NOT NULL DEFAULT 0,
+                dead_at TIMESTAMP,
                 UNIQUE(type, value)
             )
             "#,
@@ -121,6 +146,16 @@ pub mod tests_helpers {
                 booru_filter TEXT,
                 eh_filter TEXT,
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                version INTEGER NOT NULL DEFAULT 0,
+                created_by INTEGER,
+                spoiler_mode TEXT NOT NULL DEFAULT 'auto',
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                push_mode TEXT NOT NULL DEFAULT 'single',
+                silent BOOLEAN NOT NULL DEFAULT 0,
+                ranking_date_offset INTEGER NOT NULL DEFAULT 0,
+                message_thread_id INTEGER,
+                ai_filter TEXT NOT NULL DEFAULT 'auto',
+                image_size TEXT NOT NULL DEFAULT 'auto',
                 FOREIGN KEY (chat_id) REFERENCES chats(id) ON DELETE CASCADE ON UPDATE CASCADE,
                 FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE ON UPDATE CASCADE,
                 UNIQUE(chat_id, task_id)
@@ -174,6 +209,22 @@ pub mod tests_helpers {
         ))
         .await?;
 
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE channel_admins (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                channel_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                added_by INTEGER,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (channel_id) REFERENCES chats(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                UNIQUE(channel_id, user_id)
+            )
+            "#,
+        ))
+        .await?;
+
         db.execute(Statement::from_string(
             DbBackend::Sqlite,
             r#"
@@ -189,6 +240,109 @@ pub mod tests_helpers {
         ))
         .await?;
 
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE sent_artworks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                chat_id INTEGER NOT NULL,
+                illust_id INTEGER NOT NULL,
+                message_id INTEGER NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (chat_id) REFERENCES chats(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                UNIQUE(chat_id, illust_id)
+            )
+            "#,
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE push_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                chat_id INTEGER NOT NULL,
+                subscription_id INTEGER NOT NULL,
+                items INTEGER NOT NULL DEFAULT 0,
+                success BOOLEAN NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE push_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                subscription_id INTEGER NOT NULL,
+                chat_id INTEGER NOT NULL,
+                illust_id INTEGER NOT NULL,
+                sent_pages TEXT NOT NULL DEFAULT '[]',
+                total_pages INTEGER NOT NULL,
+                spoiler BOOLEAN NOT NULL DEFAULT 0,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at TIMESTAMP NOT NULL,
+                last_error TEXT,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (subscription_id) REFERENCES subscriptions(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                UNIQUE(subscription_id, illust_id)
+            )
+            "#,
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE subscription_targets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                subscription_id INTEGER NOT NULL,
+                chat_id INTEGER NOT NULL,
+                latest_illust_id INTEGER,
+                created_by INTEGER,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (subscription_id) REFERENCES subscriptions(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                FOREIGN KEY (chat_id) REFERENCES chats(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                UNIQUE(subscription_id, chat_id)
+            )
+            "#,
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE tag_aliases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                alias TEXT NOT NULL UNIQUE,
+                tag TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT 'manual',
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            DbBackend::Sqlite,
+            r#"
+            CREATE TABLE dialog_states (
+                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                chat_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                state TEXT NOT NULL,
+                settings_message_id INTEGER NOT NULL,
+                expires_at TIMESTAMP NOT NULL,
+                UNIQUE(chat_id, user_id)
+            )
+            "#,
+        ))
+        .await?;
+
         Ok(Repo::new(db))
     }
 }
@@ -196,7 +350,8 @@ pub mod tests_helpers {
 #[cfg(test)]
 mod tests {
     use super::tests_helpers::setup_test_db;
-    use crate::db::types::{Tags, UserRole};
+    use super::SourceFilter;
+    use crate::db::types::{AuthorIds, TagFilter, Tags, UserRole};
 
     #[tokio::test]
     async fn test_migrate_chat_success() {
@@ -259,6 +414,938 @@ mod tests {
         assert_eq!(old_subs.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_update_subscription_latest_data_rejects_stale_version() {
+        use crate::db::types::{AuthorState, SubscriptionState};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        let task = repo
+            .get_or_create_task(crate::db::types::TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub = repo
+            .upsert_subscription(chat_id, task.id, crate::db::types::TagFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(sub.version, 0);
+
+        let state = |latest_illust_id| {
+            Some(SubscriptionState::Author(AuthorState {
+                latest_illust_id,
+                pending_illust: None,
+            }))
+        };
+
+        let updated = repo
+            .update_subscription_latest_data(sub.id, sub.version, state(1))
+            .await
+            .unwrap();
+        assert_eq!(updated.version, 1);
+
+        let err = repo
+            .update_subscription_latest_data(sub.id, sub.version, state(2))
+            .await
+            .unwrap_err();
+        assert!(super::is_version_conflict(&err));
+
+        let current = repo.get_subscription(sub.id).await.unwrap().unwrap();
+        assert_eq!(current.latest_data, state(1));
+        assert_eq!(current.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_move_subscriptions_keeps_target_duplicates() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let from_chat_id = -1001;
+        let to_chat_id = -1002;
+        for chat_id in [from_chat_id, to_chat_id] {
+            repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+
+        let shared = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let only_source = repo
+            .get_or_create_task(TaskType::Author, "2".to_string(), None)
+            .await
+            .unwrap();
+
+        let target_filter = TagFilter::parse_from_args(&["+keep"]);
+        repo.upsert_subscription(from_chat_id, shared.id, TagFilter::default())
+            .await
+            .unwrap();
+        repo.upsert_subscription(from_chat_id, only_source.id, TagFilter::default())
+            .await
+            .unwrap();
+        repo.upsert_subscription(to_chat_id, shared.id, target_filter.clone())
+            .await
+            .unwrap();
+
+        let result = repo
+            .move_subscriptions(from_chat_id, to_chat_id)
+            .await
+            .unwrap();
+        assert_eq!(result.moved, 1);
+        assert_eq!(result.duplicates, 1);
+
+        assert!(repo
+            .list_subscriptions_by_chat(from_chat_id)
+            .await
+            .unwrap()
+            .is_empty());
+        let moved = repo.list_subscriptions_by_chat(to_chat_id).await.unwrap();
+        assert_eq!(moved.len(), 2);
+        let kept = moved
+            .iter()
+            .find(|(_, task)| task.id == shared.id)
+            .map(|(sub, _)| sub)
+            .unwrap();
+        assert_eq!(kept.filter_tags, target_filter);
+    }
+
+    #[tokio::test]
+    async fn test_delete_chat_subscriptions_only_clears_given_types() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        let other_chat_id = -1002;
+        for id in [chat_id, other_chat_id] {
+            repo.upsert_chat(id, "group".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+
+        let shared = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let only_here = repo
+            .get_or_create_task(TaskType::Author, "2".to_string(), None)
+            .await
+            .unwrap();
+        let ranking = repo
+            .get_or_create_task(TaskType::Ranking, "daily".to_string(), None)
+            .await
+            .unwrap();
+        for task_id in [shared.id, only_here.id, ranking.id] {
+            repo.upsert_subscription(chat_id, task_id, TagFilter::default())
+                .await
+                .unwrap();
+        }
+        repo.upsert_subscription(other_chat_id, shared.id, TagFilter::default())
+            .await
+            .unwrap();
+
+        let result = repo
+            .delete_chat_subscriptions(chat_id, &[TaskType::Author])
+            .await
+            .unwrap();
+        assert_eq!(result.removed, 2);
+        assert_eq!(result.deleted_tasks, 1);
+        assert!(result.eh_subscription_ids.is_empty());
+
+        let left = repo.list_subscriptions_by_chat(chat_id).await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].1.id, ranking.id);
+        assert_eq!(
+            repo.count_subscriptions_for_task(shared.id).await.unwrap(),
+            1
+        );
+        assert!(repo
+            .get_task_by_type_value(TaskType::Author, "2")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_tx_deletes_task_only_without_subscribers() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        let other_chat_id = -1002;
+        for id in [chat_id, other_chat_id] {
+            repo.upsert_chat(id, "group".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+
+        let (task, sub) = repo
+            .subscribe_tx(
+                chat_id,
+                TaskType::Author,
+                "1",
+                Some("A"),
+                TagFilter::default(),
+                SourceFilter::Plain,
+            )
+            .await
+            .unwrap();
+        assert_eq!(task.author_name.as_deref(), Some("A"));
+        assert_eq!(sub.task_id, task.id);
+        let (same_task, other_sub) = repo
+            .subscribe_tx(
+                other_chat_id,
+                TaskType::Author,
+                "1",
+                Some("B"),
+                TagFilter::default(),
+                SourceFilter::Plain,
+            )
+            .await
+            .unwrap();
+        assert_eq!(same_task.id, task.id);
+        assert_eq!(same_task.author_name.as_deref(), Some("A"));
+
+        assert!(!repo.unsubscribe_tx(sub.id, task.id).await.unwrap());
+        assert!(repo.get_task(task.id).await.unwrap().is_some());
+        assert!(repo.unsubscribe_tx(sub.id, task.id).await.is_err());
+
+        assert!(repo.unsubscribe_tx(other_sub.id, task.id).await.unwrap());
+        assert!(repo.get_task(task.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscription_targets_follow_their_subscription() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        let mirror_chat_id = -1002;
+        for id in [chat_id, mirror_chat_id] {
+            repo.upsert_chat(id, "channel".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub = repo
+            .upsert_subscription(chat_id, task.id, TagFilter::default())
+            .await
+            .unwrap();
+
+        assert!(repo
+            .add_subscription_target(sub.id, mirror_chat_id, Some(10), None)
+            .await
+            .unwrap());
+        assert!(!repo
+            .add_subscription_target(sub.id, mirror_chat_id, None, None)
+            .await
+            .unwrap());
+
+        let targets = repo.list_subscription_targets(sub.id).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].latest_illust_id, Some(10));
+
+        repo.update_subscription_target_cursor(targets[0].id, 20)
+            .await
+            .unwrap();
+        let targets = repo.list_subscription_targets(sub.id).await.unwrap();
+        assert_eq!(targets[0].latest_illust_id, Some(20));
+
+        assert!(repo
+            .remove_subscription_target(sub.id, mirror_chat_id)
+            .await
+            .unwrap());
+        assert!(!repo
+            .remove_subscription_target(sub.id, mirror_chat_id)
+            .await
+            .unwrap());
+        assert!(repo
+            .list_subscription_targets(sub.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_paused_subscriptions_are_hidden_from_engines() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_a = -1001;
+        let chat_b = -1002;
+        for chat_id in [chat_a, chat_b] {
+            repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub_a = repo
+            .upsert_subscription(chat_a, task.id, TagFilter::default())
+            .await
+            .unwrap();
+        repo.upsert_subscription(chat_b, task.id, TagFilter::default())
+            .await
+            .unwrap();
+        assert!(sub_a.enabled);
+
+        repo.set_subscription_enabled(sub_a.id, false)
+            .await
+            .unwrap();
+        let active = repo
+            .list_active_subscriptions_by_task(task.id)
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].chat_id, chat_b);
+        assert_eq!(
+            repo.list_subscriptions_by_task(task.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        assert_eq!(
+            repo.set_chat_subscriptions_enabled(chat_b, false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(repo
+            .list_active_subscriptions_by_task(task.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            repo.set_chat_subscriptions_enabled(chat_a, true)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.set_chat_subscriptions_enabled(chat_a, true)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscription_creator_and_group_digest_listing() {
+        use crate::db::types::{TagFilter, TaskType};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let group_id = -1001;
+        let private_id = 1001;
+        repo.upsert_chat(group_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        repo.upsert_chat(
+            private_id,
+            "private".to_string(),
+            None,
+            true,
+            Tags::default(),
+        )
+        .await
+        .unwrap();
+
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        for chat_id in [group_id, private_id] {
+            repo.upsert_subscription(chat_id, task.id, TagFilter::default())
+                .await
+                .unwrap();
+        }
+
+        // The first member to add the subscription is kept
+        repo.set_subscription_creator(group_id, task.id, 42)
+            .await
+            .unwrap();
+        repo.set_subscription_creator(group_id, task.id, 43)
+            .await
+            .unwrap();
+
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(7);
+        let listed = repo
+            .list_group_subscriptions_created_since(since)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0.chat_id, group_id);
+        assert_eq!(listed[0].0.created_by, Some(42));
+        assert_eq!(listed[0].1.id, task.id);
+
+        let all = repo.list_all_subscriptions().await.unwrap();
+        assert_eq!(
+            all.iter().map(|(sub, _)| sub.chat_id).collect::<Vec<_>>(),
+            [group_id, private_id]
+        );
+        assert_eq!(repo.list_chats().await.unwrap().len(), 2);
+
+        let later = chrono::Utc::now().naive_utc() + chrono::Duration::days(1);
+        assert!(repo
+            .list_group_subscriptions_created_since(later)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tag_aliases_merge_groups_and_keep_manual_entries_on_import() {
+        use super::tag_aliases::{SOURCE_IMPORT, SOURCE_MANUAL};
+        use std::io::Write;
+
+        let repo = setup_test_db().await.unwrap();
+
+        repo.add_tag_aliases("genshinimpact", &["genshin".to_string()])
+            .await
+            .unwrap();
+        // Adding to an alias joins its tag, and the old group follows
+        let tag = repo
+            .add_tag_aliases("原神", &["genshinimpact".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(tag, "原神");
+        let tag = repo
+            .add_tag_aliases("genshin", &["げんしん".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(tag, "原神");
+
+        let aliases = repo.list_tag_aliases().await.unwrap();
+        assert_eq!(aliases.len(), 3);
+        assert!(aliases.iter().all(|row| row.tag == "原神"));
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "# dataset\ngenshinimpact,genshin\nbluearchive,ブルアカ"
+        )
+        .unwrap();
+        assert_eq!(repo.import_tag_alias_file(file.path()).await.unwrap(), 1);
+
+        let aliases = repo.list_tag_aliases().await.unwrap();
+        let genshin = aliases.iter().find(|row| row.alias == "genshin").unwrap();
+        assert_eq!(genshin.tag, "原神");
+        assert_eq!(genshin.source, SOURCE_MANUAL);
+        let imported = aliases.iter().find(|row| row.alias == "ブルアカ").unwrap();
+        assert_eq!(imported.tag, "bluearchive");
+        assert_eq!(imported.source, SOURCE_IMPORT);
+
+        let removed = repo
+            .remove_tag_aliases(&["genshin".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(repo.list_tag_aliases().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_push_queue_retries_are_queued_drained_and_purged() {
+        use super::push_queue::STATUS_DONE;
+        use crate::db::types::{TagFilter, TaskType};
+        use chrono::{Duration, Utc};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub = repo
+            .upsert_subscription(chat_id, task.id, TagFilter::default())
+            .await
+            .unwrap();
+
+        let now = Utc::now().naive_utc();
+        repo.enqueue_push_retry(
+            sub.id,
+            chat_id,
+            10,
+            vec![0],
+            3,
+            true,
+            now - Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+        repo.enqueue_push_retry(
+            sub.id,
+            chat_id,
+            11,
+            vec![],
+            1,
+            false,
+            now + Duration::hours(1),
+        )
+        .await
+        .unwrap();
+
+        let due = repo.get_due_push_retries(10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].illust_id, 10);
+        assert_eq!(*due[0].sent_pages, vec![0]);
+        assert!(due[0].spoiler);
+        assert_eq!(
+            repo.list_pending_push_retries_by_chat(chat_id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let mut entry = due.into_iter().next().unwrap();
+        entry.status = STATUS_DONE.to_string();
+        repo.save_push_retry(entry).await.unwrap();
+
+        // Queuing the same work again restarts its retry instead of duplicating it
+        repo.enqueue_push_retry(sub.id, chat_id, 11, vec![0], 2, false, now)
+            .await
+            .unwrap();
+        let pending = repo
+            .list_pending_push_retries_by_chat(chat_id)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].illust_id, 11);
+        assert_eq!(*pending[0].sent_pages, vec![0]);
+        assert_eq!(pending[0].total_pages, 2);
+
+        assert_eq!(
+            repo.purge_finished_push_retries(now + Duration::hours(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(repo.get_due_push_retries(10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expedite_pending_push_retries_makes_them_due() {
+        use super::push_queue::STATUS_DONE;
+        use crate::db::types::{TagFilter, TaskType};
+        use chrono::{Duration, Utc};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub = repo
+            .upsert_subscription(chat_id, task.id, TagFilter::default())
+            .await
+            .unwrap();
+
+        let now = Utc::now().naive_utc();
+        for illust_id in [10, 11, 12] {
+            repo.enqueue_push_retry(
+                sub.id,
+                chat_id,
+                illust_id,
+                vec![0],
+                2,
+                false,
+                now + Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        }
+        let mut finished = repo
+            .list_pending_push_retries_by_chat(chat_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.illust_id == 12)
+            .unwrap();
+        finished.status = STATUS_DONE.to_string();
+        repo.save_push_retry(finished).await.unwrap();
+        assert!(repo.get_due_push_retries(10).await.unwrap().is_empty());
+
+        let pending = repo.expedite_pending_push_retries().await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending.iter().all(|entry| entry.next_attempt_at > now));
+
+        let due = repo.get_due_push_retries(10).await.unwrap();
+        let mut due_ids: Vec<i64> = due.iter().map(|entry| entry.illust_id).collect();
+        due_ids.sort_unstable();
+        assert_eq!(due_ids, vec![10, 11]);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_subscription_states_repairs_legacy_states() {
+        use crate::db::types::{AuthorState, PendingIllust, SubscriptionState, TaskType};
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+
+        let mut subs = Vec::new();
+        for (task_type, value) in [
+            (TaskType::Author, "1"),
+            (TaskType::Author, "2"),
+            (TaskType::Author, "3"),
+            (TaskType::Ranking, "day"),
+        ] {
+            let task = repo
+                .get_or_create_task(task_type, value.to_string(), None)
+                .await
+                .unwrap();
+            subs.push(
+                repo.upsert_subscription(chat_id, task.id, crate::db::types::TagFilter::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let author_state = |pending_illust| {
+            Some(SubscriptionState::Author(AuthorState {
+                latest_illust_id: 100,
+                pending_illust,
+            }))
+        };
+        let stale_pending = PendingIllust {
+            illust_id: 50,
+            sent_pages: vec![0],
+            total_pages: 2,
+            retry_count: 1,
+            spoiler: false,
+        };
+
+        // Healthy state is left untouched
+        repo.update_subscription_latest_data(subs[0].id, 0, author_state(None))
+            .await
+            .unwrap();
+        // Pending illust behind the cursor
+        repo.update_subscription_latest_data(subs[1].id, 0, author_state(Some(stale_pending)))
+            .await
+            .unwrap();
+        // Author state on a ranking subscription
+        repo.update_subscription_latest_data(subs[3].id, 0, author_state(None))
+            .await
+            .unwrap();
+        // Legacy JSON the current types cannot read
+        repo.db
+            .execute(Statement::from_string(
+                DbBackend::Sqlite,
+                format!(
+                    r#"UPDATE subscriptions SET latest_data = '{{"type":"Legacy","state":{{}}}}' WHERE id = {}"#,
+                    subs[2].id
+                ),
+            ))
+            .await
+            .unwrap();
+
+        let report = repo.normalize_subscription_states().await.unwrap();
+        assert_eq!(report.unreadable, 1);
+        assert_eq!(report.mismatched, 1);
+        assert_eq!(report.stale_pending, 1);
+
+        for (sub, expected) in subs
+            .iter()
+            .zip([author_state(None), author_state(None), None, None])
+        {
+            let current = repo.get_subscription(sub.id).await.unwrap().unwrap();
+            assert_eq!(current.latest_data, expected);
+        }
+
+        let report = repo.normalize_subscription_states().await.unwrap();
+        assert_eq!(report.total(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_channel_admins_add_is_idempotent_and_remove_reports() {
+        let repo = setup_test_db().await.unwrap();
+
+        let channel_id = -1001234567890;
+        repo.upsert_chat(
+            channel_id,
+            "channel".to_string(),
+            None,
+            true,
+            Tags::default(),
+        )
+        .await
+        .unwrap();
+
+        repo.add_channel_admin(channel_id, 42, Some(1))
+            .await
+            .unwrap();
+        repo.add_channel_admin(channel_id, 42, Some(2))
+            .await
+            .unwrap();
+        repo.add_channel_admin(channel_id, 43, None).await.unwrap();
+
+        let admins = repo.list_channel_admins(channel_id).await.unwrap();
+        assert_eq!(
+            admins.iter().map(|a| a.user_id).collect::<Vec<_>>(),
+            vec![42, 43]
+        );
+        assert_eq!(admins[0].added_by, Some(1));
+
+        assert!(repo.remove_channel_admin(channel_id, 42).await.unwrap());
+        assert!(!repo.remove_channel_admin(channel_id, 42).await.unwrap());
+        assert_eq!(repo.list_channel_admins(channel_id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_stats_sum_items_and_count_failures_per_window() {
+        let repo = setup_test_db().await.unwrap();
+
+        repo.record_push(1, 10, 3, true).await.unwrap();
+        repo.record_push(1, 11, 1, true).await.unwrap();
+        repo.record_push(1, 10, 0, false).await.unwrap();
+        repo.record_push(2, 20, 5, true).await.unwrap();
+
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(7);
+        let chat = repo.get_push_stats(Some(1), since).await.unwrap();
+        assert_eq!(chat.pushed, 4);
+        assert_eq!(chat.failures, 1);
+
+        let global = repo.get_push_stats(None, since).await.unwrap();
+        assert_eq!(global.pushed, 9);
+        assert_eq!(global.failures, 1);
+
+        let subscription = repo.get_subscription_push_stats(10, since).await.unwrap();
+        assert_eq!(subscription.pushed, 3);
+        assert_eq!(subscription.failures, 1);
+
+        let future = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1);
+        assert_eq!(
+            repo.get_push_stats(Some(1), future).await.unwrap(),
+            super::PushStats::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sent_artworks_upsert_and_lookup() {
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = 1001;
+        repo.upsert_chat(chat_id, "private".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+
+        repo.record_sent_artwork(chat_id, 111, 10).await.unwrap();
+        repo.record_sent_artwork(chat_id, 222, 20).await.unwrap();
+        repo.record_sent_artwork(chat_id, 111, 30).await.unwrap();
+
+        assert!(repo.is_artwork_sent(chat_id, 111).await.unwrap());
+        assert!(!repo.is_artwork_sent(chat_id, 333).await.unwrap());
+        assert!(!repo.is_artwork_sent(2002, 111).await.unwrap());
+
+        let record = repo.get_sent_artwork(chat_id, 111).await.unwrap().unwrap();
+        assert_eq!(record.message_id, 30);
+
+        let sent = repo
+            .get_sent_illust_ids(chat_id, &[111, 222, 333])
+            .await
+            .unwrap();
+        assert_eq!(sent, [111, 222].into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn test_get_last_push_times_by_chat_groups_per_subscription() {
+        let repo = setup_test_db().await.unwrap();
+
+        let chat_id = -1001;
+        repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        let task = repo
+            .get_or_create_task(crate::db::types::TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let sub = repo
+            .upsert_subscription(chat_id, task.id, crate::db::types::TagFilter::default())
+            .await
+            .unwrap();
+
+        let first = repo
+            .save_message(chat_id, 1, sub.id, Some(10))
+            .await
+            .unwrap();
+        let second = repo
+            .save_message(chat_id, 2, sub.id, Some(11))
+            .await
+            .unwrap();
+        repo.save_message(-2002, 3, sub.id + 1, Some(12))
+            .await
+            .unwrap();
+
+        let times = repo.get_last_push_times_by_chat(chat_id).await.unwrap();
+        assert_eq!(times.len(), 1);
+        assert_eq!(times[&sub.id], first.created_at.max(second.created_at));
+    }
+
+    #[tokio::test]
+    async fn test_get_author_tasks_without_name_pages_by_id() {
+        use crate::db::types::TaskType;
+
+        let repo = setup_test_db().await.unwrap();
+
+        let named = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), Some("A".to_string()))
+            .await
+            .unwrap();
+        let first = repo
+            .get_or_create_task(TaskType::Author, "2".to_string(), None)
+            .await
+            .unwrap();
+        repo.get_or_create_task(TaskType::Ranking, "day".to_string(), None)
+            .await
+            .unwrap();
+        let second = repo
+            .get_or_create_task(TaskType::Author, "3".to_string(), None)
+            .await
+            .unwrap();
+
+        let page = repo.get_author_tasks_without_name(0, 1).await.unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), [first.id]);
+
+        let page = repo
+            .get_author_tasks_without_name(first.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), [second.id]);
+        assert!(page.iter().all(|t| t.id != named.id));
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_tasks_by_types_filters_due_tasks() {
+        use crate::db::types::TaskType;
+
+        let repo = setup_test_db().await.unwrap();
+        let past = chrono::Utc::now() - chrono::Duration::minutes(5);
+
+        let author = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let tag = repo
+            .get_or_create_task(TaskType::BooruTag, "yandere:tag".to_string(), None)
+            .await
+            .unwrap();
+        repo.get_or_create_task(TaskType::BooruRanking, "yandere:day".to_string(), None)
+            .await
+            .unwrap();
+        for task in [&author, &tag] {
+            repo.update_task_after_poll(task.id, past).await.unwrap();
+        }
+
+        let due = repo
+            .get_pending_tasks_by_types(&[TaskType::BooruTag, TaskType::BooruRanking], 10)
+            .await
+            .unwrap();
+        // The ranking task is not due yet and the author task has another type
+        assert_eq!(due.iter().map(|t| t.id).collect::<Vec<_>>(), [tag.id]);
+    }
+
+    #[tokio::test]
+    async fn test_task_failures_count_until_success() {
+        use crate::db::types::TaskType;
+
+        let repo = setup_test_db().await.unwrap();
+        let dead = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        let flaky = repo
+            .get_or_create_task(TaskType::Author, "2".to_string(), None)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            repo.record_task_failure(dead.id, "404 Not Found")
+                .await
+                .unwrap();
+        }
+        repo.record_task_failure(flaky.id, "timeout").await.unwrap();
+        repo.record_task_success(flaky.id).await.unwrap();
+
+        let failing = repo.list_failing_tasks(1).await.unwrap();
+        assert_eq!(failing.iter().map(|t| t.id).collect::<Vec<_>>(), [dead.id]);
+        assert_eq!(failing[0].failure_count, 3);
+        assert_eq!(failing[0].last_error.as_deref(), Some("404 Not Found"));
+        assert!(failing[0].last_success_at.is_none());
+
+        let flaky = repo.get_task(flaky.id).await.unwrap().unwrap();
+        assert_eq!(flaky.failure_count, 0);
+        assert!(flaky.last_success_at.is_some());
+        // The last error is kept for reference after recovering
+        assert_eq!(flaky.last_error.as_deref(), Some("timeout"));
+
+        assert!(repo.list_failing_tasks(4).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_task_is_skipped_until_resubscribed() {
+        use crate::db::types::TaskType;
+
+        let repo = setup_test_db().await.unwrap();
+        let past = chrono::Utc::now() - chrono::Duration::minutes(5);
+        let task = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        repo.update_task_after_poll(task.id, past).await.unwrap();
+
+        repo.mark_task_dead(task.id).await.unwrap();
+        assert!(repo.get_pending_tasks(10).await.unwrap().is_empty());
+        assert!(repo
+            .get_pending_tasks_by_type(TaskType::Author, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let revived = repo
+            .get_or_create_task(TaskType::Author, "1".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(revived.id, task.id);
+        assert!(revived.dead_at.is_none());
+        assert_eq!(
+            repo.get_pending_tasks(10)
+                .await
+                .unwrap()
+                .iter()
+                .map(|t| t.id)
+                .collect::<Vec<_>>(),
+            [task.id]
+        );
+    }
+
     #[tokio::test]
     async fn test_migrate_chat_idempotent() {
         let repo = setup_test_db().await.unwrap();
@@ -297,6 +1384,109 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
 
+    #[tokio::test]
+    async fn test_chat_bot_id_is_listed_and_kept_on_migration() {
+        let repo = setup_test_db().await.unwrap();
+
+        for chat_id in [-888888, -777777] {
+            repo.upsert_chat(chat_id, "group".to_string(), None, true, Tags::default())
+                .await
+                .unwrap();
+        }
+        assert!(repo.list_chat_bot_ids().await.unwrap().is_empty());
+
+        repo.set_chat_bot_id(-888888, 42).await.unwrap();
+        assert_eq!(repo.list_chat_bot_ids().await.unwrap(), [(-888888, 42)]);
+
+        repo.migrate_chat(-888888, -1009999999999).await.unwrap();
+        assert_eq!(
+            repo.list_chat_bot_ids().await.unwrap(),
+            [(-1009999999999, 42)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_default_filter_is_kept_on_migration() {
+        let repo = setup_test_db().await.unwrap();
+        repo.upsert_chat(-888888, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+
+        let filter = TagFilter::parse_from_args(&["-R-18"]);
+        repo.set_chat_default_filter(-888888, filter.clone())
+            .await
+            .unwrap();
+        repo.migrate_chat(-888888, -1009999999999).await.unwrap();
+
+        let chat = repo.get_chat(-1009999999999).await.unwrap().unwrap();
+        assert_eq!(chat.default_filter, filter);
+    }
+
+    #[tokio::test]
+    async fn test_chat_slow_mode_delay_is_listed_once_checked() {
+        let repo = setup_test_db().await.unwrap();
+
+        for chat_id in [-1001111111111, -1002222222222] {
+            repo.upsert_chat(
+                chat_id,
+                "supergroup".to_string(),
+                None,
+                true,
+                Tags::default(),
+            )
+            .await
+            .unwrap();
+        }
+        assert!(repo.list_chat_slow_mode_delays().await.unwrap().is_empty());
+
+        repo.set_chat_slow_mode_delay(-1001111111111, 30)
+            .await
+            .unwrap();
+        repo.set_chat_slow_mode_delay(-1002222222222, 0)
+            .await
+            .unwrap();
+        let mut delays = repo.list_chat_slow_mode_delays().await.unwrap();
+        delays.sort_unstable();
+        assert_eq!(delays, [(-1002222222222, 0), (-1001111111111, 30)]);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_authors_are_stored_and_kept_on_migration() {
+        let repo = setup_test_db().await.unwrap();
+        let chat = repo
+            .upsert_chat(-666666, "group".to_string(), None, true, Tags::default())
+            .await
+            .unwrap();
+        assert!(chat.blocked_authors.is_empty());
+
+        repo.set_blocked_authors(-666666, AuthorIds(vec![123, 456]))
+            .await
+            .unwrap();
+        repo.migrate_chat(-666666, -1006666666666).await.unwrap();
+
+        let chat = repo.get_chat(-1006666666666).await.unwrap().unwrap();
+        assert_eq!(chat.blocked_authors, AuthorIds(vec![123, 456]));
+    }
+
+    #[tokio::test]
+    async fn test_set_chats_enabled_upserts_and_dedups() {
+        let repo = setup_test_db().await.unwrap();
+        repo.upsert_chat(-555555, "group".to_string(), None, false, Tags::default())
+            .await
+            .unwrap();
+
+        let count = repo
+            .set_chats_enabled(&[-555555, 123456, -555555], true)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        for chat_id in [-555555, 123456] {
+            let chat = repo.get_chat(chat_id).await.unwrap().unwrap();
+            assert!(chat.enabled);
+        }
+    }
+
     #[tokio::test]
     async fn test_migrate_chat_with_preexisting_new_chat() {
         let repo = setup_test_db().await.unwrap();
@@ -465,6 +1655,34 @@ mod tests {
         assert_eq!(owner_updated.role, UserRole::Owner);
         assert_eq!(owner_updated.username, Some("owner_updated".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_dialog_state_replace_and_expire() {
+        use std::time::Duration;
+
+        let repo = setup_test_db().await.unwrap();
+
+        repo.set_dialog_state(-100, 1, "sensitive_tags", 10, Duration::from_secs(300))
+            .await
+            .unwrap();
+        repo.set_dialog_state(-100, 1, "excluded_tags", 11, Duration::from_secs(300))
+            .await
+            .unwrap();
+        let state = repo.get_dialog_state(-100, 1).await.unwrap().unwrap();
+        assert_eq!(state.state, "excluded_tags");
+        assert_eq!(state.settings_message_id, 11);
+        assert!(repo.get_dialog_state(-100, 2).await.unwrap().is_none());
+
+        repo.set_dialog_state(-100, 2, "excluded_tags", 12, Duration::ZERO)
+            .await
+            .unwrap();
+        // Expired dialogues are swept whenever a dialogue is saved
+        assert!(repo.get_dialog_state(-100, 2).await.unwrap().is_none());
+        assert_eq!(repo.delete_expired_dialog_states().await.unwrap(), 0);
+
+        assert!(repo.delete_dialog_state(-100, 1).await.unwrap());
+        assert!(!repo.delete_dialog_state(-100, 1).await.unwrap());
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,63 @@
+use super::Repo;
+use crate::db::entities::channel_admins;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{sea_query::OnConflict, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+
+impl Repo {
+    /// Delegate management of a channel's subscriptions to a user.
+    ///
+    /// Adding an existing delegate is a no-op.
+    pub async fn add_channel_admin(
+        &self,
+        channel_id: i64,
+        user_id: i64,
+        added_by: Option<i64>,
+    ) -> Result<()> {
+        let new_admin = channel_admins::ActiveModel {
+            channel_id: Set(channel_id),
+            user_id: Set(user_id),
+            added_by: Set(added_by),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        channel_admins::Entity::insert(new_admin)
+            .on_conflict(
+                OnConflict::columns([
+                    channel_admins::Column::ChannelId,
+                    channel_admins::Column::UserId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(&self.db)
+            .await
+            .context("Failed to add channel admin")?;
+
+        Ok(())
+    }
+
+    /// Returns whether a delegate was actually removed.
+    pub async fn remove_channel_admin(&self, channel_id: i64, user_id: i64) -> Result<bool> {
+        let result = channel_admins::Entity::delete_many()
+            .filter(channel_admins::Column::ChannelId.eq(channel_id))
+            .filter(channel_admins::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to remove channel admin")?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn list_channel_admins(&self, channel_id: i64) -> Result<Vec<channel_admins::Model>> {
+        channel_admins::Entity::find()
+            .filter(channel_admins::Column::ChannelId.eq(channel_id))
+            .order_by_asc(channel_admins::Column::CreatedAt)
+            .order_by_asc(channel_admins::Column::Id)
+            .all(&self.db)
+            .await
+            .context("Failed to list channel admins")
+    }
+}
@@ -1,11 +1,12 @@
 use super::Repo;
 use crate::db::entities::chats;
-use crate::db::types::Tags;
+use crate::db::types::{AiFilter, AuthorIds, TagFilter, Tags};
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::Utc;
 use sea_orm::{
-    sea_query::OnConflict, ActiveModelTrait, ConnectionTrait, EntityTrait, IntoActiveModel, Set,
-    Statement,
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, Set, Statement,
 };
 
 impl Repo {
@@ -17,7 +18,9 @@ impl Repo {
         default_enabled: bool,
         default_sensitive_tags: Tags,
     ) -> Result<chats::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
+        // R-18 is opt-in for groups and channels, on by default for private chats
+        let allow_r18_by_default = chat_type == "private";
 
         let new_chat = chats::ActiveModel {
             id: Set(chat_id),
@@ -29,6 +32,15 @@ impl Repo {
             sensitive_tags: Set(default_sensitive_tags),
             created_at: Set(now),
             allow_without_mention: Set(false),
+            allow_r18: Set(allow_r18_by_default),
+            send_original: Set(false),
+            bot_id: Set(None),
+            link_preview: Set(true),
+            blocked_authors: Set(AuthorIds::default()),
+            slow_mode_delay: Set(None),
+            ai_filter: Set(AiFilter::All),
+            default_filter: Set(TagFilter::default()),
+            notify_failures: Set(false),
         };
 
         chats::Entity::insert(new_chat)
@@ -49,7 +61,7 @@ impl Repo {
     }
 
     pub async fn set_chat_enabled(&self, chat_id: i64, enabled: bool) -> Result<chats::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let new_chat = chats::ActiveModel {
             id: Set(chat_id),
@@ -61,6 +73,15 @@ impl Repo {
             sensitive_tags: Set(Tags::default()),
             created_at: Set(now),
             allow_without_mention: Set(false),
+            allow_r18: Set(false),
+            send_original: Set(false),
+            bot_id: Set(None),
+            link_preview: Set(true),
+            blocked_authors: Set(AuthorIds::default()),
+            slow_mode_delay: Set(None),
+            ai_filter: Set(AiFilter::All),
+            default_filter: Set(TagFilter::default()),
+            notify_failures: Set(false),
         };
 
         chats::Entity::insert(new_chat)
@@ -80,6 +101,50 @@ impl Repo {
             .ok_or_else(|| anyhow::anyhow!("Chat {} not found after upsert", chat_id))
     }
 
+    /// Enable or disable many chats at once, creating the unknown ones
+    pub async fn set_chats_enabled(&self, chat_ids: &[i64], enabled: bool) -> Result<usize> {
+        let mut chat_ids = chat_ids.to_vec();
+        chat_ids.sort_unstable();
+        chat_ids.dedup();
+        if chat_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now().naive_utc();
+        let new_chats = chat_ids.iter().map(|&chat_id| chats::ActiveModel {
+            id: Set(chat_id),
+            r#type: Set("unknown".to_string()),
+            title: Set(None),
+            enabled: Set(enabled),
+            blur_sensitive_tags: Set(true),
+            excluded_tags: Set(Tags::default()),
+            sensitive_tags: Set(Tags::default()),
+            created_at: Set(now),
+            allow_without_mention: Set(false),
+            allow_r18: Set(false),
+            send_original: Set(false),
+            bot_id: Set(None),
+            link_preview: Set(true),
+            blocked_authors: Set(AuthorIds::default()),
+            slow_mode_delay: Set(None),
+            ai_filter: Set(AiFilter::All),
+            default_filter: Set(TagFilter::default()),
+            notify_failures: Set(false),
+        });
+
+        chats::Entity::insert_many(new_chats)
+            .on_conflict(
+                OnConflict::column(chats::Column::Id)
+                    .update_column(chats::Column::Enabled)
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await
+            .context("Failed to upsert chats enabled status")?;
+
+        Ok(chat_ids.len())
+    }
+
     pub async fn set_allow_without_mention(
         &self,
         chat_id: i64,
@@ -99,6 +164,151 @@ impl Repo {
             .context("Failed to update allow_without_mention")
     }
 
+    pub async fn set_allow_r18(&self, chat_id: i64, allow: bool) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.allow_r18 = Set(allow);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update allow_r18")
+    }
+
+    pub async fn set_chat_ai_filter(&self, chat_id: i64, filter: AiFilter) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.ai_filter = Set(filter);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update ai_filter")
+    }
+
+    pub async fn set_chat_default_filter(
+        &self,
+        chat_id: i64,
+        filter: TagFilter,
+    ) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.default_filter = Set(filter);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update default_filter")
+    }
+
+    pub async fn set_send_original(&self, chat_id: i64, enabled: bool) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.send_original = Set(enabled);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update send_original")
+    }
+
+    /// Route pushes to a chat through the bot with the given Telegram user ID
+    pub async fn set_link_preview(&self, chat_id: i64, enabled: bool) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.link_preview = Set(enabled);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update link_preview")
+    }
+
+    /// Notify the chat when a work is abandoned after its last retry
+    pub async fn set_notify_failures(&self, chat_id: i64, enabled: bool) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.notify_failures = Set(enabled);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update notify_failures")
+    }
+
+    pub async fn set_chat_bot_id(&self, chat_id: i64, bot_id: i64) -> Result<()> {
+        chats::Entity::update_many()
+            .col_expr(chats::Column::BotId, Expr::value(bot_id))
+            .filter(chats::Column::Id.eq(chat_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update chat bot_id")?;
+        Ok(())
+    }
+
+    /// `(chat_id, bot_id)` of every chat assigned to a bot
+    pub async fn list_chat_bot_ids(&self) -> Result<Vec<(i64, i64)>> {
+        let chats = chats::Entity::find()
+            .filter(chats::Column::BotId.is_not_null())
+            .all(&self.db)
+            .await
+            .context("Failed to list chat bot assignments")?;
+
+        Ok(chats
+            .into_iter()
+            .filter_map(|chat| chat.bot_id.map(|bot_id| (chat.id, bot_id)))
+            .collect())
+    }
+
+    /// Store the slow mode delay reported by Telegram, `0` when slow mode is off
+    pub async fn set_chat_slow_mode_delay(&self, chat_id: i64, delay_secs: i32) -> Result<()> {
+        chats::Entity::update_many()
+            .col_expr(chats::Column::SlowModeDelay, Expr::value(delay_secs))
+            .filter(chats::Column::Id.eq(chat_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update chat slow_mode_delay")?;
+        Ok(())
+    }
+
+    /// `(chat_id, delay_secs)` of every chat whose slow mode has been checked
+    pub async fn list_chat_slow_mode_delays(&self) -> Result<Vec<(i64, i32)>> {
+        let chats = chats::Entity::find()
+            .filter(chats::Column::SlowModeDelay.is_not_null())
+            .all(&self.db)
+            .await
+            .context("Failed to list chat slow mode delays")?;
+
+        Ok(chats
+            .into_iter()
+            .filter_map(|chat| chat.slow_mode_delay.map(|delay| (chat.id, delay)))
+            .collect())
+    }
+
     pub async fn set_blur_sensitive_tags(&self, chat_id: i64, blur: bool) -> Result<chats::Model> {
         let chat = chats::Entity::find_by_id(chat_id)
             .one(&self.db)
@@ -144,6 +354,34 @@ impl Repo {
             .context("Failed to update sensitive_tags")
     }
 
+    pub async fn set_blocked_authors(
+        &self,
+        chat_id: i64,
+        authors: AuthorIds,
+    ) -> Result<chats::Model> {
+        let chat = chats::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query chat")?
+            .ok_or_else(|| anyhow::anyhow!("Chat {} not found", chat_id))?;
+
+        let mut active: chats::ActiveModel = chat.into_active_model();
+        active.blocked_authors = Set(authors);
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update blocked_authors")
+    }
+
+    /// Every known chat ordered by ID, for subscription backups
+    pub async fn list_chats(&self) -> Result<Vec<chats::Model>> {
+        chats::Entity::find()
+            .order_by_asc(chats::Column::Id)
+            .all(&self.db)
+            .await
+            .context("Failed to list chats")
+    }
+
     pub async fn get_chat(&self, chat_id: i64) -> Result<Option<chats::Model>> {
         chats::Entity::find_by_id(chat_id)
             .one(&self.db)
@@ -183,6 +421,16 @@ impl Repo {
             sensitive_tags: Set(old_chat.sensitive_tags),
             created_at: Set(old_chat.created_at),
             allow_without_mention: Set(old_chat.allow_without_mention),
+            allow_r18: Set(old_chat.allow_r18),
+            send_original: Set(old_chat.send_original),
+            bot_id: Set(old_chat.bot_id),
+            link_preview: Set(old_chat.link_preview),
+            blocked_authors: Set(old_chat.blocked_authors),
+            // Slow mode is a supergroup setting, check the new chat again
+            slow_mode_delay: Set(None),
+            ai_filter: Set(old_chat.ai_filter),
+            default_filter: Set(old_chat.default_filter),
+            notify_failures: Set(old_chat.notify_failures),
         };
 
         chats::Entity::insert(new_chat)
@@ -196,6 +444,15 @@ impl Repo {
                         chats::Column::ExcludedTags,
                         chats::Column::SensitiveTags,
                         chats::Column::AllowWithoutMention,
+                        chats::Column::AllowR18,
+                        chats::Column::SendOriginal,
+                        chats::Column::BotId,
+                        chats::Column::LinkPreview,
+                        chats::Column::BlockedAuthors,
+                        chats::Column::SlowModeDelay,
+                        chats::Column::AiFilter,
+                        chats::Column::DefaultFilter,
+                        chats::Column::NotifyFailures,
                     ])
                     .to_owned(),
             )
@@ -223,6 +480,16 @@ impl Repo {
             .await
             .context("Failed to update messages")?;
 
+        let update_subscription_targets = Statement::from_sql_and_values(
+            self.db.get_database_backend(),
+            "UPDATE subscription_targets SET chat_id = ? WHERE chat_id = ?",
+            vec![new_chat_id.into(), old_chat_id.into()],
+        );
+
+        txn.execute(update_subscription_targets)
+            .await
+            .context("Failed to update subscription targets")?;
+
         chats::Entity::delete_by_id(old_chat_id)
             .exec(&txn)
             .await
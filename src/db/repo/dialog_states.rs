@@ -0,0 +1,82 @@
+use super::Repo;
+use crate::db::entities::dialog_states;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{sea_query::OnConflict, ColumnTrait, EntityTrait, QueryFilter, Set};
+use std::time::Duration;
+
+impl Repo {
+    /// Start (or replace) the pending dialogue of a user in a chat, expiring after `ttl`
+    ///
+    /// Dialogues of other users that already expired are swept at the same time.
+    pub async fn set_dialog_state(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        state: &str,
+        settings_message_id: i32,
+        ttl: Duration,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let ttl = chrono::Duration::from_std(ttl).context("Dialog TTL out of range")?;
+        let row = dialog_states::ActiveModel {
+            chat_id: Set(chat_id),
+            user_id: Set(user_id),
+            state: Set(state.to_string()),
+            settings_message_id: Set(settings_message_id),
+            expires_at: Set(now + ttl),
+            ..Default::default()
+        };
+        dialog_states::Entity::insert(row)
+            .on_conflict(
+                OnConflict::columns([dialog_states::Column::ChatId, dialog_states::Column::UserId])
+                    .update_columns([
+                        dialog_states::Column::State,
+                        dialog_states::Column::SettingsMessageId,
+                        dialog_states::Column::ExpiresAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .context("Failed to save dialog state")?;
+
+        self.delete_expired_dialog_states().await?;
+        Ok(())
+    }
+
+    /// Pending dialogue of a user in a chat, including one that already expired
+    pub async fn get_dialog_state(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+    ) -> Result<Option<dialog_states::Model>> {
+        dialog_states::Entity::find()
+            .filter(dialog_states::Column::ChatId.eq(chat_id))
+            .filter(dialog_states::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await
+            .context("Failed to get dialog state")
+    }
+
+    /// End the pending dialogue of a user in a chat, returning whether there was one
+    pub async fn delete_dialog_state(&self, chat_id: i64, user_id: i64) -> Result<bool> {
+        let result = dialog_states::Entity::delete_many()
+            .filter(dialog_states::Column::ChatId.eq(chat_id))
+            .filter(dialog_states::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to delete dialog state")?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Remove every expired dialogue, returning how many were removed
+    pub async fn delete_expired_dialog_states(&self) -> Result<u64> {
+        let result = dialog_states::Entity::delete_many()
+            .filter(dialog_states::Column::ExpiresAt.lte(Utc::now().naive_utc()))
+            .exec(&self.db)
+            .await
+            .context("Failed to delete expired dialog states")?;
+        Ok(result.rows_affected)
+    }
+}
@@ -1,7 +1,8 @@
+use super::subscriptions::ClearedSubscriptions;
 use super::Repo;
 use crate::db::entities::eh_download_queue;
 use anyhow::{Context, Result};
-use chrono::{Local, Timelike};
+use chrono::{Timelike, Utc};
 use eh_client::ArchiveArtifacts;
 use sea_orm::prelude::DateTime;
 use sea_orm::sea_query::{Expr, SimpleExpr};
@@ -14,6 +15,7 @@ use std::sync::LazyLock;
 use tracing::warn;
 
 use crate::db::entities::subscriptions;
+use crate::db::types::TaskType;
 
 /// Serializes EH publish side effects with subscription-queue cancellation.
 ///
@@ -270,7 +272,7 @@ impl Repo {
         &self,
         req: EhEnqueueRequest<'_>,
     ) -> Result<eh_download_queue::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         // Check for existing entry
         let existing = eh_download_queue::Entity::find()
@@ -628,7 +630,7 @@ impl Repo {
             .context("Failed to fetch pending eh download")?;
 
         if let Some(model) = entry {
-            let now = Local::now().naive_local();
+            let now = Utc::now().naive_utc();
             let generation = next_claim_generation(now, model.started_at)?;
             let result = eh_download_queue::Entity::update_many()
                 .col_expr(
@@ -677,7 +679,7 @@ impl Repo {
             .context("Failed to fetch eh download")?
             .ok_or_else(|| anyhow::anyhow!("EH download {} not found", id))?;
 
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let completed_at = entry.completed_at.unwrap_or(now);
 
         let result = eh_download_queue::Entity::update_many()
@@ -725,7 +727,7 @@ impl Repo {
             .context("Failed to fetch eh download")?
             .ok_or_else(|| anyhow::anyhow!("EH download {} not found", id))?;
 
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let new_retry_count = entry.retry_count + 1;
         let mut active: eh_download_queue::ActiveModel = entry.into();
         active.status = Set(STATUS_FAILED.to_string());
@@ -802,7 +804,7 @@ impl Repo {
                 "background running claim",
             ),
         };
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::Status,
@@ -862,7 +864,7 @@ impl Repo {
     /// Uses `completed_at` from the download stage (not overwritten by upload/publish stages).
     /// Uses SQL aggregate for efficiency.
     pub async fn get_eh_downloaded_bytes_in_window(&self, hours: u64) -> Result<i64> {
-        let cutoff = Local::now().naive_local() - chrono::Duration::hours(hours as i64);
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::hours(hours as i64);
 
         let result = eh_download_queue::Entity::find()
             .filter(eh_download_queue::Column::Status.is_in([
@@ -1020,17 +1022,37 @@ impl Repo {
         Ok(result.rows_affected)
     }
 
-    /// Delete an EH subscription and cancel/prune its queued work in one
-    /// publish/cancel critical section.
+    /// Delete an EH subscription (and its task if left without subscribers) and
+    /// cancel/prune its queued work in one publish/cancel critical section.
+    ///
+    /// Returns whether the task was deleted.
     pub async fn delete_eh_subscription_and_cancel_queue(
         &self,
         subscription_id: i32,
-    ) -> Result<()> {
+        task_id: i32,
+    ) -> Result<bool> {
         let _guard = EH_PUBLISH_CANCEL_LOCK.lock().await;
-        self.delete_subscription(subscription_id).await?;
+        let task_deleted = self.unsubscribe_tx(subscription_id, task_id).await?;
         self.cancel_eh_subscription_queue_entries_inner(subscription_id)
             .await?;
-        Ok(())
+        Ok(task_deleted)
+    }
+
+    /// Clear a chat's subscriptions of the given types and cancel the queued
+    /// downloads owned by the removed EH subscriptions, under the same lock as
+    /// [`Repo::delete_eh_subscription_and_cancel_queue`].
+    pub async fn delete_chat_subscriptions_and_cancel_queue(
+        &self,
+        chat_id: i64,
+        task_types: &[TaskType],
+    ) -> Result<ClearedSubscriptions> {
+        let _guard = EH_PUBLISH_CANCEL_LOCK.lock().await;
+        let cleared = self.delete_chat_subscriptions(chat_id, task_types).await?;
+        for &subscription_id in &cleared.eh_subscription_ids {
+            self.cancel_eh_subscription_queue_entries_inner(subscription_id)
+                .await?;
+        }
+        Ok(cleared)
     }
 
     async fn cancel_eh_subscription_queue_entries_inner(
@@ -1489,7 +1511,7 @@ impl Repo {
 
     /// Reset stale Telegraph rewrite claims back to pending rewrite work.
     pub async fn reset_stale_eh_telegraph_rewrites(&self, stale_sec: i64) -> Result<u64> {
-        let cutoff = Local::now().naive_local() - chrono::Duration::seconds(stale_sec);
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::seconds(stale_sec);
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::TelegraphRewriteStatus,
@@ -1564,7 +1586,7 @@ impl Repo {
         zip_path: &str,
         gp_cost: i64,
     ) -> Result<eh_download_queue::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
@@ -1995,7 +2017,7 @@ impl Repo {
     /// Get next entry for the download stage: status=pending, next_retry_at is NULL or <= now.
     /// Uses a conditional UPDATE to atomically claim the entry.
     pub async fn get_next_for_download(&self) -> Result<Option<eh_download_queue::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         self.get_next_for_download_at(now).await
     }
 
@@ -2101,7 +2123,7 @@ impl Repo {
     /// Get next entry for the upload stage: status=downloaded, telegraph=true, next_retry_at ok.
     /// Uses a conditional UPDATE to atomically claim the entry.
     pub async fn get_next_for_upload(&self) -> Result<Option<eh_download_queue::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let entry = eh_download_queue::Entity::find()
             .filter(eh_download_queue::Column::Status.eq(STATUS_DOWNLOADED))
             .filter(eh_download_queue::Column::Telegraph.eq(true))
@@ -2163,7 +2185,7 @@ impl Repo {
     /// Get next entry for the publish stage: either (downloaded, telegraph=false) or (uploaded).
     /// Uses a conditional UPDATE to atomically claim the entry.
     pub async fn get_next_for_publish(&self) -> Result<Option<eh_download_queue::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let entry = eh_download_queue::Entity::find()
             .filter(
                 sea_orm::Condition::any()
@@ -2245,7 +2267,7 @@ impl Repo {
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::ArchiveSentAt,
-                Expr::value(Local::now().naive_local()),
+                Expr::value(Utc::now().naive_utc()),
             )
             .filter(eh_download_queue::Column::Id.eq(id))
             .filter(eh_download_queue::Column::Status.eq(STATUS_PUBLISHING))
@@ -2276,7 +2298,7 @@ impl Repo {
         id: i32,
         rewrite_delay_secs: Option<i64>,
     ) -> Result<()> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         if rewrite_delay_secs.is_none() {
             let result = eh_download_queue::Entity::update_many()
                 .col_expr(eh_download_queue::Column::TelegraphSentAt, Expr::value(now))
@@ -2361,7 +2383,7 @@ impl Repo {
         id: i32,
         delay_secs: i64,
     ) -> Result<()> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::TelegraphRewriteStatus,
@@ -2407,7 +2429,7 @@ impl Repo {
 
     /// Claim the next due Telegraph rewrite job.
     pub async fn get_next_for_telegraph_rewrite(&self) -> Result<Option<eh_download_queue::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let entry = eh_download_queue::Entity::find()
             .filter(
                 eh_download_queue::Column::TelegraphRewriteStatus
@@ -2481,7 +2503,7 @@ impl Repo {
 
     /// Mark a claimed Telegraph rewrite as complete and clear rewrite payload.
     pub async fn mark_eh_telegraph_rewritten(&self, id: i32) -> Result<()> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::TelegraphRewriteData,
@@ -2549,7 +2571,7 @@ impl Repo {
             .ok_or_else(|| anyhow::anyhow!("EH Telegraph rewrite {} not found", id))?;
         let retry_count = entry.telegraph_rewrite_retry_count + 1;
         let is_permanent = retry_count > max_retry_count as i32;
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         if is_permanent {
             let result = eh_download_queue::Entity::update_many()
@@ -2666,7 +2688,7 @@ impl Repo {
             )
             .col_expr(
                 eh_download_queue::Column::NextRetryAt,
-                Expr::value(Local::now().naive_local() + chrono::Duration::seconds(delay_secs)),
+                Expr::value(Utc::now().naive_utc() + chrono::Duration::seconds(delay_secs)),
             )
             .filter(eh_download_queue::Column::Id.eq(id))
             .filter(current_filter)
@@ -2734,7 +2756,7 @@ impl Repo {
 
         let new_retry_count = entry.retry_count + 1;
         let is_permanent = new_retry_count > max_retry_count as i32;
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         // Determine the valid current-status filter for the expected stage and
         // retry target (same for transient and permanent failure).
@@ -2874,7 +2896,7 @@ impl Repo {
         expected_status: &str,
         error: &str,
     ) -> Result<eh_download_queue::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::Status,
@@ -2948,7 +2970,7 @@ impl Repo {
         delay_secs: i64,
         reason: &str,
     ) -> Result<eh_download_queue::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::BackgroundDownloadStatus,
@@ -2992,7 +3014,7 @@ impl Repo {
     }
 
     pub async fn reset_stale_background_downloads(&self, stale_sec: u64) -> Result<u64> {
-        let cutoff = Local::now().naive_local() - chrono::Duration::seconds(stale_sec as i64);
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::seconds(stale_sec as i64);
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::BackgroundDownloadStatus,
@@ -3092,7 +3114,7 @@ impl Repo {
     pub async fn get_next_for_background_download(
         &self,
     ) -> Result<Option<eh_download_queue::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         self.get_next_for_background_download_at(now).await
     }
 
@@ -3173,7 +3195,7 @@ impl Repo {
         zip_path: &str,
         gp_cost: i64,
     ) -> Result<eh_download_queue::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let result = eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::Status,
@@ -3248,7 +3270,7 @@ impl Repo {
             .ok_or_else(|| anyhow::anyhow!("EH download {} not found", id))?;
         let new_attempts = entry.background_download_attempt_count + 1;
         let permanent = new_attempts >= max_attempts as i32;
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let mut update = eh_download_queue::Entity::update_many();
         if permanent {
@@ -3583,7 +3605,7 @@ mod tests {
             .col_expr(
                 Column::NextRetryAt,
                 Expr::value(Some(
-                    chrono::Local::now().naive_local() + chrono::Duration::hours(1),
+                    chrono::Utc::now().naive_utc() + chrono::Duration::hours(1),
                 )),
             )
             .filter(Column::Id.eq(merged.id))
@@ -3725,7 +3747,7 @@ mod tests {
     #[tokio::test]
     async fn test_inactive_check_preserves_concurrent_live_subscription_owner() {
         let repo = tests_helpers::setup_test_db().await.unwrap();
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         repo.upsert_chat(-100, "private".to_string(), None, true, Default::default())
             .await
             .unwrap();
@@ -3797,7 +3819,7 @@ mod tests {
     #[tokio::test]
     async fn test_inactive_check_cancels_row_without_live_subscription_owner() {
         let repo = tests_helpers::setup_test_db().await.unwrap();
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         let row = eh_download_queue::ActiveModel {
             chat_id: Set(-100i64),
             gid: Set(47i64),
@@ -3837,7 +3859,7 @@ mod tests {
     #[tokio::test]
     async fn test_cancel_legacy_subscription_queue_entries_without_owner_tracking() {
         let repo = tests_helpers::setup_test_db().await.unwrap();
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         let legacy = eh_download_queue::ActiveModel {
             chat_id: Set(-100i64),
             gid: Set(48i64),
@@ -4621,7 +4643,7 @@ mod tests {
             .col_expr(
                 Column::BackgroundDownloadStartedAt,
                 Expr::value(Some(
-                    Local::now().naive_local() - chrono::Duration::seconds(7200),
+                    Utc::now().naive_utc() - chrono::Duration::seconds(7200),
                 )),
             )
             .filter(Column::Id.eq(model.id))
@@ -4645,9 +4667,7 @@ mod tests {
         Entity::update_many()
             .col_expr(
                 Column::BackgroundDownloadNextRetryAt,
-                Expr::value(Some(
-                    Local::now().naive_local() - chrono::Duration::seconds(1),
-                )),
+                Expr::value(Some(Utc::now().naive_utc() - chrono::Duration::seconds(1))),
             )
             .filter(Column::Id.eq(model.id))
             .exec(&repo.db)
@@ -5309,7 +5329,7 @@ mod tests {
     #[tokio::test]
     async fn test_background_archive_policy_aba_does_not_fail_reclaimed_row() {
         let repo = tests_helpers::setup_test_db().await.unwrap();
-        let claim_now = Local::now().naive_local() + Duration::minutes(1);
+        let claim_now = Utc::now().naive_utc() + Duration::minutes(1);
         let model = repo
             .enqueue_eh_subscription_download(-100, 124, 65, "tok", "Title", false)
             .await
@@ -5612,7 +5632,7 @@ mod tests {
         let repo = tests_helpers::setup_test_db().await.unwrap();
 
         // Simulate a concurrent caller that inserted the row first.
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         let conflict = eh_download_queue::ActiveModel {
             chat_id: Set(-100i64),
             gid: Set(70i64),
@@ -5734,7 +5754,7 @@ mod tests {
         let repo = tests_helpers::setup_test_db().await.unwrap();
 
         // Pre-insert a row so the helper's re-select finds it
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         let conflict = eh_download_queue::ActiveModel {
             chat_id: Set(-100i64),
             gid: Set(71i64),
@@ -5823,7 +5843,7 @@ mod tests {
         let repo = tests_helpers::setup_test_db().await.unwrap();
 
         // Construct a STATUS_UPLOADING row with stale Telegraph state
-        let now = chrono::Local::now().naive_local();
+        let now = chrono::Utc::now().naive_utc();
         let active = eh_download_queue::ActiveModel {
             chat_id: Set(-100i64),
             gid: Set(90i64),
@@ -6127,7 +6147,7 @@ mod tests {
             .col_expr(
                 Column::TelegraphRewriteStartedAt,
                 Expr::value(Some(
-                    Local::now().naive_local() - chrono::Duration::seconds(7200),
+                    Utc::now().naive_utc() - chrono::Duration::seconds(7200),
                 )),
             )
             .filter(Column::Id.eq(model.id))
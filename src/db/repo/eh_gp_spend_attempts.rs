@@ -1,7 +1,7 @@
 use super::Repo;
 use crate::db::entities::eh_gp_spend_attempts;
 use anyhow::{Context, Result};
-use chrono::{Duration, Local};
+use chrono::{Duration, Utc};
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 
 impl Repo {
@@ -22,7 +22,7 @@ impl Repo {
             queue_id: Set(Some(queue_id)),
             gid: Set(gid),
             gp_cost: Set(gp_cost),
-            created_at: Set(Local::now().naive_local()),
+            created_at: Set(Utc::now().naive_utc()),
             ..Default::default()
         }
         .insert(&self.db)
@@ -36,8 +36,8 @@ impl Repo {
             .context("EH GP spend window hours exceed the supported range")?;
         let duration = Duration::try_hours(window_hours)
             .context("EH GP spend window hours exceed Chrono duration range")?;
-        let cutoff = Local::now()
-            .naive_local()
+        let cutoff = Utc::now()
+            .naive_utc()
             .checked_sub_signed(duration)
             .context("EH GP spend window cutoff is outside the supported datetime range")?;
 
@@ -60,7 +60,7 @@ mod tests {
     use super::super::tests_helpers::setup_test_db;
     use crate::db::entities::{eh_download_queue, eh_gp_spend_attempts};
     use anyhow::{bail, Result};
-    use chrono::{Duration, Local};
+    use chrono::{Duration, Utc};
     use migration::{MigrationTrait, Migrator, MigratorTrait, SchemaManager};
     use sea_orm::{
         ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, DbBackend, EntityTrait,
@@ -357,7 +357,7 @@ mod tests {
         assert_eq!(attempt.queue_id, Some(queue.id));
         assert_eq!(attempt.gid, queue.gid);
         assert_eq!(attempt.gp_cost, 218);
-        assert!(attempt.created_at <= Local::now().naive_local());
+        assert!(attempt.created_at <= Utc::now().naive_utc());
 
         let rows = eh_gp_spend_attempts::Entity::find().all(repo.db()).await?;
         assert_eq!(rows, vec![attempt]);
@@ -392,7 +392,7 @@ mod tests {
             queue_id: Set(None),
             gid: Set(103),
             gp_cost: Set(218),
-            created_at: Set(Local::now().naive_local() - Duration::hours(25)),
+            created_at: Set(Utc::now().naive_utc() - Duration::hours(25)),
             ..Default::default()
         }
         .insert(repo.db())
@@ -401,7 +401,7 @@ mod tests {
             queue_id: Set(None),
             gid: Set(104),
             gp_cost: Set(7),
-            created_at: Set(Local::now().naive_local()),
+            created_at: Set(Utc::now().naive_utc()),
             ..Default::default()
         }
         .insert(repo.db())
@@ -455,7 +455,7 @@ mod tests {
         let queue_id = queue.id;
         let mut queue: eh_download_queue::ActiveModel = queue.into();
         queue.gp_cost = Set(218);
-        queue.completed_at = Set(Some(Local::now().naive_local()));
+        queue.completed_at = Set(Some(Utc::now().naive_utc()));
         queue.update(repo.db()).await?;
 
         assert_eq!(repo.get_eh_gp_cost_in_window(24).await?, 0);
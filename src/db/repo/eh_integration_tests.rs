@@ -156,6 +156,7 @@ async fn test_upsert_eh_subscription_insert() {
         min_pages: None,
         max_pages: None,
         telegraph: true,
+        preview_pages: None,
     };
 
     let sub = repo
@@ -188,6 +189,7 @@ async fn test_upsert_eh_subscription_update_on_conflict() {
         min_pages: None,
         max_pages: None,
         telegraph: false,
+        preview_pages: None,
     };
     let sub1 = repo
         .upsert_eh_subscription(-100, task.id, TagFilter::default(), Some(filter1.clone()))
@@ -200,6 +202,7 @@ async fn test_upsert_eh_subscription_update_on_conflict() {
         min_pages: Some(20),
         max_pages: None,
         telegraph: true,
+        preview_pages: None,
     };
     let sub2 = repo
         .upsert_eh_subscription(-100, task.id, TagFilter::default(), Some(filter2.clone()))
@@ -257,7 +260,7 @@ async fn test_update_subscription_latest_data_eh_tag() {
         pending_high_water_ts: 0,
     });
 
-    repo.update_subscription_latest_data(sub.id, Some(state.clone()))
+    repo.update_subscription_latest_data(sub.id, sub.version, Some(state.clone()))
         .await
         .unwrap();
 
@@ -418,6 +421,7 @@ async fn test_eh_task_key_db_roundtrip() {
         min_pages: Some(20),
         max_pages: None,
         telegraph: false,
+        preview_pages: None,
     };
     let key = EhTaskKey::new("female:elf", 0, &filter);
     let task_value = key.to_task_value();
@@ -1,8 +1,9 @@
 use super::Repo;
 use crate::db::entities::{messages, subscriptions, tasks};
 use anyhow::{Context, Result};
-use chrono::Local;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, Set};
+use std::collections::HashMap;
 
 impl Repo {
     pub async fn save_message(
@@ -12,7 +13,7 @@ impl Repo {
         subscription_id: i32,
         illust_id: Option<i64>,
     ) -> Result<messages::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let new_message = messages::ActiveModel {
             chat_id: Set(chat_id),
@@ -58,4 +59,26 @@ impl Repo {
             None => Ok(None),
         }
     }
+
+    /// Latest push time per subscription of a chat (subscription_id -> created_at)
+    pub async fn get_last_push_times_by_chat(
+        &self,
+        chat_id: i64,
+    ) -> Result<HashMap<i32, NaiveDateTime>> {
+        let rows: Vec<(i32, Option<NaiveDateTime>)> = messages::Entity::find()
+            .select_only()
+            .column(messages::Column::SubscriptionId)
+            .column_as(messages::Column::CreatedAt.max(), "last_pushed_at")
+            .filter(messages::Column::ChatId.eq(chat_id))
+            .group_by(messages::Column::SubscriptionId)
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .context("Failed to query last push times")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(sub_id, last)| last.map(|t| (sub_id, t)))
+            .collect())
+    }
 }
@@ -0,0 +1,98 @@
+use super::Repo;
+use crate::db::entities::push_log;
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect, Select,
+    Set,
+};
+
+/// Push totals over a time window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PushStats {
+    /// Artworks delivered by successful pushes
+    pub pushed: u64,
+    /// Push attempts that failed completely
+    pub failures: u64,
+}
+
+impl Repo {
+    pub async fn record_push(
+        &self,
+        chat_id: i64,
+        subscription_id: i32,
+        items: usize,
+        success: bool,
+    ) -> Result<()> {
+        push_log::ActiveModel {
+            chat_id: Set(chat_id),
+            subscription_id: Set(subscription_id),
+            items: Set(items as i32),
+            success: Set(success),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await
+        .context("Failed to record push")?;
+
+        Ok(())
+    }
+
+    /// Push totals since `since`, for one chat or (with `None`) for all chats
+    pub async fn get_push_stats(
+        &self,
+        chat_id: Option<i64>,
+        since: NaiveDateTime,
+    ) -> Result<PushStats> {
+        let window = || -> Select<push_log::Entity> {
+            let query = push_log::Entity::find().filter(push_log::Column::CreatedAt.gte(since));
+            match chat_id {
+                Some(chat_id) => query.filter(push_log::Column::ChatId.eq(chat_id)),
+                None => query,
+            }
+        };
+
+        self.sum_push_stats(window).await
+    }
+
+    /// Push totals of a single subscription since `since`
+    pub async fn get_subscription_push_stats(
+        &self,
+        subscription_id: i32,
+        since: NaiveDateTime,
+    ) -> Result<PushStats> {
+        self.sum_push_stats(|| {
+            push_log::Entity::find()
+                .filter(push_log::Column::CreatedAt.gte(since))
+                .filter(push_log::Column::SubscriptionId.eq(subscription_id))
+        })
+        .await
+    }
+
+    async fn sum_push_stats(
+        &self,
+        window: impl Fn() -> Select<push_log::Entity>,
+    ) -> Result<PushStats> {
+        let pushed: Option<i64> = window()
+            .select_only()
+            .column_as(push_log::Column::Items.sum(), "pushed")
+            .filter(push_log::Column::Success.eq(true))
+            .into_tuple()
+            .one(&self.db)
+            .await
+            .context("Failed to sum pushed artworks")?
+            .flatten();
+
+        let failures = window()
+            .filter(push_log::Column::Success.eq(false))
+            .count(&self.db)
+            .await
+            .context("Failed to count failed pushes")?;
+
+        Ok(PushStats {
+            pushed: pushed.unwrap_or(0).max(0) as u64,
+            failures,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use super::Repo;
+use crate::db::entities::push_queue;
+use crate::db::types::SentPages;
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+
+/// Status constants for push_queue.
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DONE: &str = "done";
+pub const STATUS_ABANDONED: &str = "abandoned";
+
+impl Repo {
+    /// Queue a retry for a work that failed or was only partly sent.
+    ///
+    /// Queuing the same work of a subscription again restarts its retry.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn enqueue_push_retry(
+        &self,
+        subscription_id: i32,
+        chat_id: i64,
+        illust_id: u64,
+        sent_pages: Vec<usize>,
+        total_pages: usize,
+        spoiler: bool,
+        next_attempt_at: NaiveDateTime,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let entry = push_queue::ActiveModel {
+            subscription_id: Set(subscription_id),
+            chat_id: Set(chat_id),
+            illust_id: Set(illust_id as i64),
+            sent_pages: Set(SentPages(sent_pages)),
+            total_pages: Set(total_pages as i32),
+            spoiler: Set(spoiler),
+            status: Set(STATUS_PENDING.to_string()),
+            attempts: Set(0),
+            next_attempt_at: Set(next_attempt_at),
+            last_error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+
+        push_queue::Entity::insert(entry)
+            .on_conflict(
+                OnConflict::columns([
+                    push_queue::Column::SubscriptionId,
+                    push_queue::Column::IllustId,
+                ])
+                .update_columns([
+                    push_queue::Column::SentPages,
+                    push_queue::Column::TotalPages,
+                    push_queue::Column::Spoiler,
+                    push_queue::Column::Status,
+                    push_queue::Column::Attempts,
+                    push_queue::Column::NextAttemptAt,
+                    push_queue::Column::LastError,
+                    push_queue::Column::UpdatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .context("Failed to enqueue push retry")?;
+
+        Ok(())
+    }
+
+    /// Pending retries whose next attempt is due, oldest first
+    pub async fn get_due_push_retries(&self, limit: u64) -> Result<Vec<push_queue::Model>> {
+        push_queue::Entity::find()
+            .filter(push_queue::Column::Status.eq(STATUS_PENDING))
+            .filter(push_queue::Column::NextAttemptAt.lte(Utc::now().naive_utc()))
+            .order_by_asc(push_queue::Column::NextAttemptAt)
+            .limit(limit)
+            .all(&self.db)
+            .await
+            .context("Failed to query due push retries")
+    }
+
+    /// Make every pending retry due now, returning the pending entries as they were.
+    ///
+    /// Used once at startup so pushes interrupted by a shutdown don't sit out their backoff.
+    pub async fn expedite_pending_push_retries(&self) -> Result<Vec<push_queue::Model>> {
+        let pending = push_queue::Entity::find()
+            .filter(push_queue::Column::Status.eq(STATUS_PENDING))
+            .order_by_asc(push_queue::Column::NextAttemptAt)
+            .all(&self.db)
+            .await
+            .context("Failed to list pending push retries")?;
+
+        let now = Utc::now().naive_utc();
+        push_queue::Entity::update_many()
+            .col_expr(push_queue::Column::NextAttemptAt, Expr::value(now))
+            .col_expr(push_queue::Column::UpdatedAt, Expr::value(now))
+            .filter(push_queue::Column::Status.eq(STATUS_PENDING))
+            .filter(push_queue::Column::NextAttemptAt.gt(now))
+            .exec(&self.db)
+            .await
+            .context("Failed to expedite pending push retries")?;
+
+        Ok(pending)
+    }
+
+    /// Pending retries of a chat's subscriptions, for `/health`
+    pub async fn list_pending_push_retries_by_chat(
+        &self,
+        chat_id: i64,
+    ) -> Result<Vec<push_queue::Model>> {
+        push_queue::Entity::find()
+            .filter(push_queue::Column::ChatId.eq(chat_id))
+            .filter(push_queue::Column::Status.eq(STATUS_PENDING))
+            .order_by_asc(push_queue::Column::NextAttemptAt)
+            .all(&self.db)
+            .await
+            .context("Failed to list push retries")
+    }
+
+    /// Persist the outcome of a retry attempt (progress, status, next attempt)
+    pub async fn save_push_retry(&self, entry: push_queue::Model) -> Result<()> {
+        let mut entry = push_queue::ActiveModel::from(entry).reset_all();
+        entry.updated_at = Set(Utc::now().naive_utc());
+        entry
+            .update(&self.db)
+            .await
+            .context("Failed to update push retry")?;
+        Ok(())
+    }
+
+    /// Delete finished or abandoned retries last touched before `before`
+    pub async fn purge_finished_push_retries(&self, before: NaiveDateTime) -> Result<u64> {
+        let result = push_queue::Entity::delete_many()
+            .filter(push_queue::Column::Status.ne(STATUS_PENDING))
+            .filter(push_queue::Column::UpdatedAt.lt(before))
+            .exec(&self.db)
+            .await
+            .context("Failed to purge finished push retries")?;
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,84 @@
+use super::Repo;
+use crate::db::entities::sent_artworks;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{sea_query::OnConflict, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, Set};
+use std::collections::HashSet;
+
+impl Repo {
+    /// Record that an artwork was delivered to a chat.
+    ///
+    /// Re-sending the same artwork points the record at the newest message.
+    pub async fn record_sent_artwork(
+        &self,
+        chat_id: i64,
+        illust_id: i64,
+        message_id: i32,
+    ) -> Result<()> {
+        let record = sent_artworks::ActiveModel {
+            chat_id: Set(chat_id),
+            illust_id: Set(illust_id),
+            message_id: Set(message_id),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        sent_artworks::Entity::insert(record)
+            .on_conflict(
+                OnConflict::columns([
+                    sent_artworks::Column::ChatId,
+                    sent_artworks::Column::IllustId,
+                ])
+                .update_columns([
+                    sent_artworks::Column::MessageId,
+                    sent_artworks::Column::CreatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .context("Failed to record sent artwork")?;
+
+        Ok(())
+    }
+
+    pub async fn get_sent_artwork(
+        &self,
+        chat_id: i64,
+        illust_id: i64,
+    ) -> Result<Option<sent_artworks::Model>> {
+        sent_artworks::Entity::find()
+            .filter(sent_artworks::Column::ChatId.eq(chat_id))
+            .filter(sent_artworks::Column::IllustId.eq(illust_id))
+            .one(&self.db)
+            .await
+            .context("Failed to get sent artwork")
+    }
+
+    pub async fn is_artwork_sent(&self, chat_id: i64, illust_id: i64) -> Result<bool> {
+        Ok(self.get_sent_artwork(chat_id, illust_id).await?.is_some())
+    }
+
+    /// Subset of `illust_ids` that were already delivered to the chat
+    pub async fn get_sent_illust_ids(
+        &self,
+        chat_id: i64,
+        illust_ids: &[u64],
+    ) -> Result<HashSet<u64>> {
+        if illust_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let ids: Vec<i64> = sent_artworks::Entity::find()
+            .select_only()
+            .column(sent_artworks::Column::IllustId)
+            .filter(sent_artworks::Column::ChatId.eq(chat_id))
+            .filter(sent_artworks::Column::IllustId.is_in(illust_ids.iter().map(|&id| id as i64)))
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .context("Failed to query sent artworks")?;
+
+        Ok(ids.into_iter().map(|id| id as u64).collect())
+    }
+}
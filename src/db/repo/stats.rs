@@ -47,6 +47,14 @@ impl Repo {
             .context("Failed to count all subscriptions")
     }
 
+    pub async fn count_subscriptions_by_chat(&self, chat_id: i64) -> Result<u64> {
+        subscriptions::Entity::find()
+            .filter(subscriptions::Column::ChatId.eq(chat_id))
+            .count(&self.db)
+            .await
+            .context("Failed to count chat subscriptions")
+    }
+
     pub async fn count_all_tasks(&self) -> Result<u64> {
         tasks::Entity::find()
             .count(&self.db)
@@ -0,0 +1,97 @@
+use super::Repo;
+use crate::db::entities::subscription_targets;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+impl Repo {
+    /// Mirror a subscription to another chat.
+    ///
+    /// The target starts at `latest_illust_id` so it only receives works newer than
+    /// what the subscription already pushed. Returns whether a new target was added.
+    pub async fn add_subscription_target(
+        &self,
+        subscription_id: i32,
+        chat_id: i64,
+        latest_illust_id: Option<i64>,
+        created_by: Option<i64>,
+    ) -> Result<bool> {
+        let new_target = subscription_targets::ActiveModel {
+            subscription_id: Set(subscription_id),
+            chat_id: Set(chat_id),
+            latest_illust_id: Set(latest_illust_id),
+            created_by: Set(created_by),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        let result = subscription_targets::Entity::insert(new_target)
+            .on_conflict(
+                OnConflict::columns([
+                    subscription_targets::Column::SubscriptionId,
+                    subscription_targets::Column::ChatId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(&self.db)
+            .await
+            .context("Failed to add subscription target")?;
+
+        Ok(matches!(result, sea_orm::TryInsertResult::Inserted(_)))
+    }
+
+    /// Returns whether a target was actually removed.
+    pub async fn remove_subscription_target(
+        &self,
+        subscription_id: i32,
+        chat_id: i64,
+    ) -> Result<bool> {
+        let result = subscription_targets::Entity::delete_many()
+            .filter(subscription_targets::Column::SubscriptionId.eq(subscription_id))
+            .filter(subscription_targets::Column::ChatId.eq(chat_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to remove subscription target")?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn list_subscription_targets(
+        &self,
+        subscription_id: i32,
+    ) -> Result<Vec<subscription_targets::Model>> {
+        subscription_targets::Entity::find()
+            .filter(subscription_targets::Column::SubscriptionId.eq(subscription_id))
+            .order_by_asc(subscription_targets::Column::Id)
+            .all(&self.db)
+            .await
+            .context("Failed to list subscription targets")
+    }
+
+    /// Move a target's push cursor after a mirrored push
+    pub async fn update_subscription_target_cursor(
+        &self,
+        target_id: i32,
+        latest_illust_id: i64,
+    ) -> Result<()> {
+        let result = subscription_targets::Entity::update_many()
+            .col_expr(
+                subscription_targets::Column::LatestIllustId,
+                Expr::value(latest_illust_id),
+            )
+            .filter(subscription_targets::Column::Id.eq(target_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription target cursor")?;
+
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription target {} not found", target_id);
+        }
+        Ok(())
+    }
+}
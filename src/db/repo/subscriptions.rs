@@ -1,12 +1,80 @@
+use super::tasks::upsert_task_on;
 use super::Repo;
-use crate::db::entities::{subscriptions, tasks};
-use crate::db::types::{BooruFilter, EhFilter, SubscriptionState, TagFilter};
+use crate::db::entities::{chats, subscriptions, tasks};
+use crate::db::types::{
+    AiFilter, BooruFilter, EhFilter, PushImageSize, PushMode, SpoilerMode, SubscriptionState,
+    TagFilter, TaskType,
+};
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{NaiveDateTime, Utc};
 use sea_orm::{
-    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel,
-    PaginatorTrait, QueryFilter, Set,
+    sea_query::{Expr, OnConflict, Query},
+    ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
 };
+use std::collections::HashMap;
+use std::fmt;
+
+/// A `latest_data` update lost the race against a concurrent writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionVersionConflict {
+    pub subscription_id: i32,
+    pub expected_version: i32,
+}
+
+impl fmt::Display for SubscriptionVersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Subscription {} state was modified concurrently (expected version {})",
+            self.subscription_id, self.expected_version
+        )
+    }
+}
+
+impl std::error::Error for SubscriptionVersionConflict {}
+
+/// Outcome of [`Repo::move_subscriptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovedSubscriptions {
+    /// Subscriptions reassigned to the target chat
+    pub moved: u64,
+    /// Source subscriptions dropped because the target chat already had the same task
+    pub duplicates: u64,
+}
+
+/// Outcome of [`Repo::delete_chat_subscriptions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearedSubscriptions {
+    /// Subscriptions removed from the chat
+    pub removed: u64,
+    /// Tasks deleted because no subscription referenced them anymore
+    pub deleted_tasks: u64,
+    /// Removed E-Hentai subscriptions whose queued downloads still need canceling
+    pub eh_subscription_ids: Vec<i32>,
+}
+
+/// Outcome of [`Repo::normalize_subscription_states`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NormalizedStates {
+    /// States that no longer deserialize and were reset
+    pub unreadable: u64,
+    /// States whose variant does not belong to the task type and were reset
+    pub mismatched: u64,
+    /// States that had stale pending entries removed
+    pub stale_pending: u64,
+}
+
+impl NormalizedStates {
+    pub fn total(&self) -> u64 {
+        self.unreadable + self.mismatched + self.stale_pending
+    }
+}
+
+/// Whether an error is a [`SubscriptionVersionConflict`].
+pub fn is_version_conflict(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<SubscriptionVersionConflict>().is_some()
+}
 
 impl Repo {
     pub async fn upsert_subscription(
@@ -15,51 +83,226 @@ impl Repo {
         task_id: i32,
         filter_tags: TagFilter,
     ) -> Result<subscriptions::Model> {
-        let now = Local::now().naive_local();
+        upsert_subscription_on(&self.db, chat_id, task_id, filter_tags, SourceFilter::Plain).await
+    }
 
-        let new_sub = subscriptions::ActiveModel {
-            chat_id: Set(chat_id),
-            task_id: Set(task_id),
-            filter_tags: Set(filter_tags),
-            created_at: Set(now),
-            ..Default::default()
-        };
+    pub async fn list_subscriptions_by_chat(
+        &self,
+        chat_id: i64,
+    ) -> Result<Vec<(subscriptions::Model, tasks::Model)>> {
+        subscriptions::Entity::find()
+            .filter(subscriptions::Column::ChatId.eq(chat_id))
+            .find_also_related(tasks::Entity)
+            .all(&self.db)
+            .await
+            .context("Failed to list subscriptions by chat")
+            .map(|results| {
+                results
+                    .into_iter()
+                    .filter_map(|(sub, task)| task.map(|t| (sub, t)))
+                    .collect()
+            })
+    }
+
+    /// Every subscription with its task, ordered by chat, for subscription backups
+    pub async fn list_all_subscriptions(
+        &self,
+    ) -> Result<Vec<(subscriptions::Model, tasks::Model)>> {
+        subscriptions::Entity::find()
+            .order_by_asc(subscriptions::Column::ChatId)
+            .order_by_asc(subscriptions::Column::Id)
+            .find_also_related(tasks::Entity)
+            .all(&self.db)
+            .await
+            .context("Failed to list all subscriptions")
+            .map(|results| {
+                results
+                    .into_iter()
+                    .filter_map(|(sub, task)| task.map(|t| (sub, t)))
+                    .collect()
+            })
+    }
 
-        subscriptions::Entity::insert(new_sub)
-            .on_conflict(
-                OnConflict::columns([subscriptions::Column::ChatId, subscriptions::Column::TaskId])
-                    .update_column(subscriptions::Column::FilterTags)
-                    .to_owned(),
+    pub async fn update_subscription_spoiler_mode(
+        &self,
+        subscription_id: i32,
+        spoiler_mode: SpoilerMode,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(
+                subscriptions::Column::SpoilerMode,
+                Expr::value(spoiler_mode),
             )
+            .filter(subscriptions::Column::Id.eq(subscription_id))
             .exec(&self.db)
             .await
-            .context("Failed to upsert subscription")?;
+            .context("Failed to update subscription spoiler mode")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
 
-        subscriptions::Entity::find()
+    pub async fn update_subscription_push_mode(
+        &self,
+        subscription_id: i32,
+        push_mode: PushMode,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::PushMode, Expr::value(push_mode))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription push mode")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_silent(
+        &self,
+        subscription_id: i32,
+        silent: bool,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::Silent, Expr::value(silent))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription silent flag")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_filter_tags(
+        &self,
+        subscription_id: i32,
+        filter_tags: TagFilter,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::FilterTags, Expr::value(filter_tags))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription filter tags")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_ai_filter(
+        &self,
+        subscription_id: i32,
+        filter: AiFilter,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::AiFilter, Expr::value(filter))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription AI filter")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_image_size(
+        &self,
+        subscription_id: i32,
+        image_size: PushImageSize,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::ImageSize, Expr::value(image_size))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription image size")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_message_thread_id(
+        &self,
+        subscription_id: i32,
+        message_thread_id: Option<i32>,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(
+                subscriptions::Column::MessageThreadId,
+                Expr::value(message_thread_id),
+            )
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription message thread")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    pub async fn update_subscription_ranking_date_offset(
+        &self,
+        subscription_id: i32,
+        offset_days: i32,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(
+                subscriptions::Column::RankingDateOffset,
+                Expr::value(offset_days),
+            )
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription ranking date offset")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    /// Record the user who added a subscription; the first recorded creator is kept
+    pub async fn set_subscription_creator(
+        &self,
+        chat_id: i64,
+        task_id: i32,
+        user_id: i64,
+    ) -> Result<()> {
+        subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::CreatedBy, Expr::value(user_id))
             .filter(subscriptions::Column::ChatId.eq(chat_id))
             .filter(subscriptions::Column::TaskId.eq(task_id))
-            .one(&self.db)
+            .filter(subscriptions::Column::CreatedBy.is_null())
+            .exec(&self.db)
             .await
-            .context("Failed to fetch upserted subscription")?
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Subscription for chat {} task {} not found after upsert",
-                    chat_id,
-                    task_id
-                )
-            })
+            .context("Failed to set subscription creator")?;
+        Ok(())
     }
 
-    pub async fn list_subscriptions_by_chat(
+    /// Subscriptions of enabled group chats created at or after `since`,
+    /// ordered by chat and creation time
+    pub async fn list_group_subscriptions_created_since(
         &self,
-        chat_id: i64,
+        since: NaiveDateTime,
     ) -> Result<Vec<(subscriptions::Model, tasks::Model)>> {
         subscriptions::Entity::find()
-            .filter(subscriptions::Column::ChatId.eq(chat_id))
+            .inner_join(chats::Entity)
+            .filter(chats::Column::Type.eq("group"))
+            .filter(chats::Column::Enabled.eq(true))
+            .filter(subscriptions::Column::CreatedAt.gte(since))
+            .order_by_asc(subscriptions::Column::ChatId)
+            .order_by_asc(subscriptions::Column::CreatedAt)
             .find_also_related(tasks::Entity)
             .all(&self.db)
             .await
-            .context("Failed to list subscriptions by chat")
+            .context("Failed to list new group subscriptions")
             .map(|results| {
                 results
                     .into_iter()
@@ -79,6 +322,51 @@ impl Repo {
             .context("Failed to list subscriptions by task")
     }
 
+    /// Subscriptions of a task that are not paused, as seen by the engines
+    pub async fn list_active_subscriptions_by_task(
+        &self,
+        task_id: i32,
+    ) -> Result<Vec<subscriptions::Model>> {
+        subscriptions::Entity::find()
+            .filter(subscriptions::Column::TaskId.eq(task_id))
+            .filter(subscriptions::Column::Enabled.eq(true))
+            .all(&self.db)
+            .await
+            .context("Failed to list active subscriptions by task")
+    }
+
+    /// Pause or resume a single subscription
+    pub async fn set_subscription_enabled(
+        &self,
+        subscription_id: i32,
+        enabled: bool,
+    ) -> Result<()> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::Enabled, Expr::value(enabled))
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to update subscription enabled flag")?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("Subscription {} not found", subscription_id);
+        }
+        Ok(())
+    }
+
+    /// Pause or resume every subscription of a chat.
+    ///
+    /// Returns the number of subscriptions whose state changed.
+    pub async fn set_chat_subscriptions_enabled(&self, chat_id: i64, enabled: bool) -> Result<u64> {
+        let result = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::Enabled, Expr::value(enabled))
+            .filter(subscriptions::Column::ChatId.eq(chat_id))
+            .filter(subscriptions::Column::Enabled.eq(!enabled))
+            .exec(&self.db)
+            .await
+            .context("Failed to update chat subscriptions enabled flag")?;
+        Ok(result.rows_affected)
+    }
+
     pub async fn get_subscription_by_chat_task(
         &self,
         chat_id: i64,
@@ -116,69 +404,263 @@ impl Repo {
             .context("Failed to count subscriptions for task")
     }
 
-    pub async fn update_subscription_latest_data(
+    pub async fn get_subscription(
         &self,
         subscription_id: i32,
-        latest_data: Option<SubscriptionState>,
-    ) -> Result<subscriptions::Model> {
-        let subscription = subscriptions::Entity::find_by_id(subscription_id)
+    ) -> Result<Option<subscriptions::Model>> {
+        subscriptions::Entity::find_by_id(subscription_id)
             .one(&self.db)
             .await
-            .context("Failed to query subscription")?
-            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))?;
+            .context("Failed to query subscription")
+    }
+
+    /// Reassign every subscription of `from_chat_id` to `to_chat_id` in one transaction.
+    ///
+    /// When both chats subscribe to the same task, the target's subscription (and its
+    /// filters and state) is kept and the source's duplicate is removed.
+    pub async fn move_subscriptions(
+        &self,
+        from_chat_id: i64,
+        to_chat_id: i64,
+    ) -> Result<MovedSubscriptions> {
+        use sea_orm::TransactionTrait;
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        let target_task_ids: Vec<i32> = subscriptions::Entity::find()
+            .filter(subscriptions::Column::ChatId.eq(to_chat_id))
+            .all(&txn)
+            .await
+            .context("Failed to list target chat subscriptions")?
+            .into_iter()
+            .map(|sub| sub.task_id)
+            .collect();
+
+        let duplicates = subscriptions::Entity::delete_many()
+            .filter(subscriptions::Column::ChatId.eq(from_chat_id))
+            .filter(subscriptions::Column::TaskId.is_in(target_task_ids))
+            .exec(&txn)
+            .await
+            .context("Failed to delete duplicate subscriptions")?
+            .rows_affected;
 
-        let mut active: subscriptions::ActiveModel = subscription.into_active_model();
-        active.latest_data = Set(latest_data);
-        active
-            .update(&self.db)
+        let moved = subscriptions::Entity::update_many()
+            .col_expr(subscriptions::Column::ChatId, Expr::value(to_chat_id))
+            .filter(subscriptions::Column::ChatId.eq(from_chat_id))
+            .exec(&txn)
             .await
-            .context("Failed to update subscription latest_data")
+            .context("Failed to move subscriptions")?
+            .rows_affected;
+
+        txn.commit().await.context("Failed to commit transaction")?;
+
+        Ok(MovedSubscriptions { moved, duplicates })
     }
 
-    pub async fn upsert_booru_subscription(
+    /// Delete every subscription of `chat_id` whose task has one of `task_types`,
+    /// together with the tasks left without subscribers, in one transaction.
+    ///
+    /// Queued E-Hentai downloads are not touched here; use
+    /// [`Repo::delete_chat_subscriptions_and_cancel_queue`] when EH subscriptions
+    /// may be among the removed ones.
+    pub async fn delete_chat_subscriptions(
         &self,
         chat_id: i64,
-        task_id: i32,
-        filter_tags: TagFilter,
-        booru_filter: Option<BooruFilter>,
+        task_types: &[TaskType],
+    ) -> Result<ClearedSubscriptions> {
+        use sea_orm::TransactionTrait;
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        let matching: Vec<(subscriptions::Model, tasks::Model)> = subscriptions::Entity::find()
+            .filter(subscriptions::Column::ChatId.eq(chat_id))
+            .find_also_related(tasks::Entity)
+            .filter(tasks::Column::Type.is_in(task_types.iter().copied()))
+            .all(&txn)
+            .await
+            .context("Failed to list chat subscriptions")?
+            .into_iter()
+            .filter_map(|(sub, task)| task.map(|t| (sub, t)))
+            .collect();
+
+        let subscription_ids: Vec<i32> = matching.iter().map(|(sub, _)| sub.id).collect();
+        let eh_subscription_ids = matching
+            .iter()
+            .filter(|(_, task)| task.r#type == TaskType::Ehentai)
+            .map(|(sub, _)| sub.id)
+            .collect();
+        let mut task_ids: Vec<i32> = matching.iter().map(|(_, task)| task.id).collect();
+        task_ids.sort_unstable();
+        task_ids.dedup();
+
+        let removed = subscriptions::Entity::delete_many()
+            .filter(subscriptions::Column::Id.is_in(subscription_ids))
+            .exec(&txn)
+            .await
+            .context("Failed to delete chat subscriptions")?
+            .rows_affected;
+
+        let still_referenced: Vec<i32> = subscriptions::Entity::find()
+            .filter(subscriptions::Column::TaskId.is_in(task_ids.clone()))
+            .all(&txn)
+            .await
+            .context("Failed to list remaining task subscriptions")?
+            .into_iter()
+            .map(|sub| sub.task_id)
+            .collect();
+        let orphaned: Vec<i32> = task_ids
+            .into_iter()
+            .filter(|id| !still_referenced.contains(id))
+            .collect();
+
+        let deleted_tasks = tasks::Entity::delete_many()
+            .filter(tasks::Column::Id.is_in(orphaned))
+            .exec(&txn)
+            .await
+            .context("Failed to delete orphaned tasks")?
+            .rows_affected;
+
+        txn.commit().await.context("Failed to commit transaction")?;
+
+        Ok(ClearedSubscriptions {
+            removed,
+            deleted_tasks,
+            eh_subscription_ids,
+        })
+    }
+
+    /// Compare-and-swap update of `latest_data`.
+    ///
+    /// The write only applies if the row still has `expected_version`; otherwise a
+    /// [`SubscriptionVersionConflict`] is returned so the caller can reload and retry.
+    pub async fn update_subscription_latest_data(
+        &self,
+        subscription_id: i32,
+        expected_version: i32,
+        latest_data: Option<SubscriptionState>,
     ) -> Result<subscriptions::Model> {
-        let now = Local::now().naive_local();
-
-        let new_sub = subscriptions::ActiveModel {
-            chat_id: Set(chat_id),
-            task_id: Set(task_id),
-            filter_tags: Set(filter_tags),
-            booru_filter: Set(booru_filter),
-            created_at: Set(now),
+        let update = subscriptions::ActiveModel {
+            latest_data: Set(latest_data),
             ..Default::default()
         };
 
-        subscriptions::Entity::insert(new_sub)
-            .on_conflict(
-                OnConflict::columns([subscriptions::Column::ChatId, subscriptions::Column::TaskId])
-                    .update_columns([
-                        subscriptions::Column::FilterTags,
-                        subscriptions::Column::BooruFilter,
-                    ])
-                    .to_owned(),
+        let result = subscriptions::Entity::update_many()
+            .set(update)
+            .col_expr(
+                subscriptions::Column::Version,
+                Expr::col(subscriptions::Column::Version).add(1),
             )
+            .filter(subscriptions::Column::Id.eq(subscription_id))
+            .filter(subscriptions::Column::Version.eq(expected_version))
             .exec(&self.db)
             .await
-            .context("Failed to upsert booru subscription")?;
+            .context("Failed to update subscription latest_data")?;
 
-        subscriptions::Entity::find()
-            .filter(subscriptions::Column::ChatId.eq(chat_id))
-            .filter(subscriptions::Column::TaskId.eq(task_id))
-            .one(&self.db)
+        if result.rows_affected == 0 {
+            if !self.subscription_exists(subscription_id).await? {
+                anyhow::bail!("Subscription {} not found", subscription_id);
+            }
+            return Err(SubscriptionVersionConflict {
+                subscription_id,
+                expected_version,
+            }
+            .into());
+        }
+
+        self.get_subscription(subscription_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription_id))
+    }
+
+    /// One-time startup pass over every subscription's `latest_data`.
+    ///
+    /// States written by older versions are brought in line with what the current
+    /// engines expect: unreadable or wrong-variant states are reset (the engine
+    /// re-initializes them on the next poll) and stale pending entries are dropped.
+    pub async fn normalize_subscription_states(&self) -> Result<NormalizedStates> {
+        let task_types: HashMap<i32, TaskType> = tasks::Entity::find()
+            .select_only()
+            .column(tasks::Column::Id)
+            .column(tasks::Column::Type)
+            .into_tuple::<(i32, TaskType)>()
+            .all(&self.db)
             .await
-            .context("Failed to fetch upserted booru subscription")?
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Booru subscription for chat {} task {} not found after upsert",
-                    chat_id,
-                    task_id
-                )
-            })
+            .context("Failed to load task types")?
+            .into_iter()
+            .collect();
+
+        // Read the raw JSON so rows that no longer deserialize can still be repaired
+        let rows: Vec<(i32, i32, i32, Option<serde_json::Value>)> = subscriptions::Entity::find()
+            .select_only()
+            .column(subscriptions::Column::Id)
+            .column(subscriptions::Column::TaskId)
+            .column(subscriptions::Column::Version)
+            .column(subscriptions::Column::LatestData)
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .context("Failed to load subscription states")?;
+
+        let mut report = NormalizedStates::default();
+        for (subscription_id, task_id, version, raw) in rows {
+            let Some(raw) = raw.filter(|value| !value.is_null()) else {
+                continue;
+            };
+
+            let normalized = match serde_json::from_value::<SubscriptionState>(raw) {
+                Err(_) => {
+                    report.unreadable += 1;
+                    None
+                }
+                Ok(state) if task_types.get(&task_id) != Some(&state.task_type()) => {
+                    report.mismatched += 1;
+                    None
+                }
+                Ok(state) => match state.without_stale_pending() {
+                    Some(cleaned) => {
+                        report.stale_pending += 1;
+                        Some(cleaned)
+                    }
+                    None => continue,
+                },
+            };
+
+            self.update_subscription_latest_data(subscription_id, version, normalized)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to normalize state of subscription {}",
+                        subscription_id
+                    )
+                })?;
+        }
+
+        Ok(report)
+    }
+
+    pub async fn upsert_booru_subscription(
+        &self,
+        chat_id: i64,
+        task_id: i32,
+        filter_tags: TagFilter,
+        booru_filter: Option<BooruFilter>,
+    ) -> Result<subscriptions::Model> {
+        upsert_subscription_on(
+            &self.db,
+            chat_id,
+            task_id,
+            filter_tags,
+            SourceFilter::Booru(booru_filter),
+        )
+        .await
     }
 
     pub async fn upsert_eh_subscription(
@@ -188,42 +670,152 @@ impl Repo {
         filter_tags: TagFilter,
         eh_filter: Option<EhFilter>,
     ) -> Result<subscriptions::Model> {
-        let now = Local::now().naive_local();
-
-        let new_sub = subscriptions::ActiveModel {
-            chat_id: Set(chat_id),
-            task_id: Set(task_id),
-            filter_tags: Set(filter_tags),
-            eh_filter: Set(eh_filter),
-            created_at: Set(now),
-            ..Default::default()
-        };
+        upsert_subscription_on(
+            &self.db,
+            chat_id,
+            task_id,
+            filter_tags,
+            SourceFilter::Eh(eh_filter),
+        )
+        .await
+    }
 
-        subscriptions::Entity::insert(new_sub)
-            .on_conflict(
-                OnConflict::columns([subscriptions::Column::ChatId, subscriptions::Column::TaskId])
-                    .update_columns([
-                        subscriptions::Column::FilterTags,
-                        subscriptions::Column::EhFilter,
-                    ])
-                    .to_owned(),
-            )
-            .exec(&self.db)
+    /// Create (or revive) the task and subscribe `chat_id` to it in one transaction,
+    /// so a concurrent unsubscribe cannot delete the task in between.
+    pub async fn subscribe_tx(
+        &self,
+        chat_id: i64,
+        task_type: TaskType,
+        value: &str,
+        author_name: Option<&str>,
+        filter_tags: TagFilter,
+        source_filter: SourceFilter,
+    ) -> Result<(tasks::Model, subscriptions::Model)> {
+        use sea_orm::TransactionTrait;
+
+        let txn = self
+            .db
+            .begin()
             .await
-            .context("Failed to upsert eh subscription")?;
+            .context("Failed to begin transaction")?;
 
-        subscriptions::Entity::find()
-            .filter(subscriptions::Column::ChatId.eq(chat_id))
-            .filter(subscriptions::Column::TaskId.eq(task_id))
-            .one(&self.db)
+        let task = upsert_task_on(&txn, task_type, value, author_name).await?;
+        let subscription =
+            upsert_subscription_on(&txn, chat_id, task.id, filter_tags, source_filter).await?;
+
+        txn.commit().await.context("Failed to commit transaction")?;
+
+        Ok((task, subscription))
+    }
+
+    /// Delete a subscription and, if nothing else subscribes to its task, the task
+    /// itself in one transaction.
+    ///
+    /// Returns whether the task was deleted.
+    pub async fn unsubscribe_tx(&self, subscription_id: i32, task_id: i32) -> Result<bool> {
+        use sea_orm::TransactionTrait;
+
+        let txn = self
+            .db
+            .begin()
             .await
-            .context("Failed to fetch upserted eh subscription")?
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "EH subscription for chat {} task {} not found after upsert",
-                    chat_id,
-                    task_id
-                )
-            })
+            .context("Failed to begin transaction")?;
+
+        let removed = subscriptions::Entity::delete_by_id(subscription_id)
+            .exec(&txn)
+            .await
+            .context("Failed to delete subscription")?
+            .rows_affected;
+        if removed == 0 {
+            return Err(anyhow::anyhow!(
+                "Subscription {} does not exist",
+                subscription_id
+            ));
+        }
+
+        // Checked in the same statement so a subscription added meanwhile keeps the task
+        let deleted_tasks = tasks::Entity::delete_many()
+            .filter(tasks::Column::Id.eq(task_id))
+            .filter(
+                tasks::Column::Id.not_in_subquery(
+                    Query::select()
+                        .column(subscriptions::Column::TaskId)
+                        .from(subscriptions::Entity)
+                        .and_where(subscriptions::Column::TaskId.eq(task_id))
+                        .to_owned(),
+                ),
+            )
+            .exec(&txn)
+            .await
+            .context("Failed to delete orphaned task")?
+            .rows_affected;
+
+        txn.commit().await.context("Failed to commit transaction")?;
+
+        Ok(deleted_tasks > 0)
     }
 }
+
+/// Source-specific filter stored next to the tag filter of a subscription
+#[derive(Debug, Clone)]
+pub enum SourceFilter {
+    /// Pixiv subscriptions only filter by tags
+    Plain,
+    Booru(Option<BooruFilter>),
+    Eh(Option<EhFilter>),
+}
+
+/// Insert a subscription or update the filters of the existing one, on any connection
+async fn upsert_subscription_on<C: ConnectionTrait>(
+    db: &C,
+    chat_id: i64,
+    task_id: i32,
+    filter_tags: TagFilter,
+    source_filter: SourceFilter,
+) -> Result<subscriptions::Model> {
+    let now = Utc::now().naive_utc();
+
+    let mut new_sub = subscriptions::ActiveModel {
+        chat_id: Set(chat_id),
+        task_id: Set(task_id),
+        filter_tags: Set(filter_tags),
+        created_at: Set(now),
+        ..Default::default()
+    };
+    let mut update_columns = vec![subscriptions::Column::FilterTags];
+    match source_filter {
+        SourceFilter::Plain => {}
+        SourceFilter::Booru(booru_filter) => {
+            new_sub.booru_filter = Set(booru_filter);
+            update_columns.push(subscriptions::Column::BooruFilter);
+        }
+        SourceFilter::Eh(eh_filter) => {
+            new_sub.eh_filter = Set(eh_filter);
+            update_columns.push(subscriptions::Column::EhFilter);
+        }
+    }
+
+    subscriptions::Entity::insert(new_sub)
+        .on_conflict(
+            OnConflict::columns([subscriptions::Column::ChatId, subscriptions::Column::TaskId])
+                .update_columns(update_columns)
+                .to_owned(),
+        )
+        .exec(db)
+        .await
+        .context("Failed to upsert subscription")?;
+
+    subscriptions::Entity::find()
+        .filter(subscriptions::Column::ChatId.eq(chat_id))
+        .filter(subscriptions::Column::TaskId.eq(task_id))
+        .one(db)
+        .await
+        .context("Failed to fetch upserted subscription")?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Subscription for chat {} task {} not found after upsert",
+                chat_id,
+                task_id
+            )
+        })
+}
@@ -0,0 +1,151 @@
+use super::Repo;
+use crate::db::entities::tag_aliases;
+use crate::utils::tag;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Source constants for tag_aliases.
+pub const SOURCE_MANUAL: &str = "manual";
+pub const SOURCE_IMPORT: &str = "import";
+
+/// Rows per INSERT when importing, well below SQLite's bound parameter limit
+const IMPORT_BATCH_SIZE: usize = 500;
+
+impl Repo {
+    /// Map normalized `aliases` to the normalized `tag`, returning the tag they now map to.
+    ///
+    /// If `tag` is itself an alias, the aliases join the tag it belongs to. Aliases
+    /// that were the tag of other aliases bring those along.
+    pub async fn add_tag_aliases(&self, tag: &str, aliases: &[String]) -> Result<String> {
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        let canonical = tag_aliases::Entity::find()
+            .filter(tag_aliases::Column::Alias.eq(tag))
+            .one(&txn)
+            .await
+            .context("Failed to resolve tag alias")?
+            .map_or_else(|| tag.to_string(), |row| row.tag);
+        let aliases: Vec<&String> = aliases.iter().filter(|a| **a != canonical).collect();
+
+        let now = Utc::now().naive_utc();
+        for alias in &aliases {
+            let row = tag_aliases::ActiveModel {
+                alias: Set(alias.to_string()),
+                tag: Set(canonical.clone()),
+                source: Set(SOURCE_MANUAL.to_string()),
+                created_at: Set(now),
+                ..Default::default()
+            };
+            tag_aliases::Entity::insert(row)
+                .on_conflict(
+                    OnConflict::column(tag_aliases::Column::Alias)
+                        .update_columns([tag_aliases::Column::Tag, tag_aliases::Column::Source])
+                        .to_owned(),
+                )
+                .exec(&txn)
+                .await
+                .context("Failed to save tag alias")?;
+        }
+
+        tag_aliases::Entity::update_many()
+            .col_expr(tag_aliases::Column::Tag, Expr::value(canonical.clone()))
+            .filter(tag_aliases::Column::Tag.is_in(aliases.iter().map(|a| a.as_str())))
+            .exec(&txn)
+            .await
+            .context("Failed to merge tag alias groups")?;
+
+        txn.commit().await.context("Failed to commit tag aliases")?;
+        Ok(canonical)
+    }
+
+    /// Remove normalized aliases, returning how many existed
+    pub async fn remove_tag_aliases(&self, aliases: &[String]) -> Result<u64> {
+        let result = tag_aliases::Entity::delete_many()
+            .filter(tag_aliases::Column::Alias.is_in(aliases.iter().map(String::as_str)))
+            .exec(&self.db)
+            .await
+            .context("Failed to remove tag aliases")?;
+        Ok(result.rows_affected)
+    }
+
+    pub async fn list_tag_aliases(&self) -> Result<Vec<tag_aliases::Model>> {
+        tag_aliases::Entity::find()
+            .order_by_asc(tag_aliases::Column::Tag)
+            .order_by_asc(tag_aliases::Column::Alias)
+            .all(&self.db)
+            .await
+            .context("Failed to list tag aliases")
+    }
+
+    /// Seed aliases from a dataset file (see [`tag::parse_alias_dataset`]).
+    ///
+    /// Aliases that already exist are kept as they are, so manual changes survive
+    /// a re-import. Returns the number of aliases added.
+    pub async fn import_tag_alias_file(&self, path: &Path) -> Result<u64> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read tag alias file {}", path.display()))?;
+
+        let now = Utc::now().naive_utc();
+        let rows: Vec<tag_aliases::ActiveModel> = tag::parse_alias_dataset(&text)
+            .into_iter()
+            .flat_map(|(tag, aliases)| {
+                aliases
+                    .into_iter()
+                    .map(move |alias| tag_aliases::ActiveModel {
+                        alias: Set(alias),
+                        tag: Set(tag.clone()),
+                        source: Set(SOURCE_IMPORT.to_string()),
+                        created_at: Set(now),
+                        ..Default::default()
+                    })
+            })
+            .collect();
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        let mut added = 0;
+        for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+            added += tag_aliases::Entity::insert_many(batch.to_vec())
+                .on_conflict(
+                    OnConflict::column(tag_aliases::Column::Alias)
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await
+                .context("Failed to import tag aliases")?;
+        }
+        txn.commit()
+            .await
+            .context("Failed to commit tag alias import")?;
+
+        Ok(added)
+    }
+
+    /// Load the alias table into the cache consulted by tag filters, returning its size
+    pub async fn reload_tag_aliases(&self) -> Result<usize> {
+        let aliases: HashMap<String, String> = self
+            .list_tag_aliases()
+            .await?
+            .into_iter()
+            .map(|row| (row.alias, row.tag))
+            .collect();
+        let count = aliases.len();
+        tag::set_tag_aliases(aliases);
+        Ok(count)
+    }
+}
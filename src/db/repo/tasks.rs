@@ -2,13 +2,24 @@ use super::Repo;
 use crate::db::entities::tasks;
 use crate::db::types::TaskType;
 use anyhow::{Context, Result};
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, QuerySelect, Set,
 };
 
+/// Longest error message kept per task
+const MAX_TASK_ERROR_CHARS: usize = 1000;
+
 impl Repo {
+    pub async fn get_task(&self, task_id: i32) -> Result<Option<tasks::Model>> {
+        tasks::Entity::find_by_id(task_id)
+            .one(&self.db)
+            .await
+            .context("Failed to get task")
+    }
+
     pub async fn get_task_by_type_value(
         &self,
         task_type: TaskType,
@@ -28,33 +39,21 @@ impl Repo {
         value: String,
         author_name: Option<String>,
     ) -> Result<tasks::Model> {
-        let next_poll = Local::now() + chrono::Duration::seconds(60);
-
-        let new_task = tasks::ActiveModel {
-            r#type: Set(task_type),
-            value: Set(value.clone()),
-            next_poll_at: Set(next_poll.naive_local()),
-            last_polled_at: Set(None),
-            author_name: Set(author_name.clone()),
-            ..Default::default()
-        };
+        upsert_task_on(&self.db, task_type, &value, author_name.as_deref()).await
+    }
 
-        // On conflict (same type+value), do NOT overwrite author_name.
-        // The first subscriber's display_name should be preserved;
-        // otherwise later subscribers could overwrite it for all chats.
-        let conflict_handler = OnConflict::columns([tasks::Column::Type, tasks::Column::Value])
-            .update_column(tasks::Column::Value)
-            .to_owned();
+    /// Tasks of any type that are due for polling, oldest first
+    pub async fn get_pending_tasks(&self, limit: u64) -> Result<Vec<tasks::Model>> {
+        let now = Utc::now().naive_utc();
 
-        tasks::Entity::insert(new_task)
-            .on_conflict(conflict_handler)
-            .exec_without_returning(&self.db)
+        tasks::Entity::find()
+            .filter(tasks::Column::NextPollAt.lte(now))
+            .filter(tasks::Column::DeadAt.is_null())
+            .order_by_asc(tasks::Column::NextPollAt)
+            .limit(limit)
+            .all(&self.db)
             .await
-            .context("Failed to upsert task")?;
-
-        self.get_task_by_type_value(task_type, &value)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Task with value {} not found after upsert", value))
+            .context("Failed to get pending tasks")
     }
 
     pub async fn get_pending_tasks_by_type(
@@ -62,10 +61,11 @@ impl Repo {
         task_type: TaskType,
         limit: u64,
     ) -> Result<Vec<tasks::Model>> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         tasks::Entity::find()
             .filter(tasks::Column::NextPollAt.lte(now))
+            .filter(tasks::Column::DeadAt.is_null())
             .filter(tasks::Column::Type.eq(task_type))
             .order_by_asc(tasks::Column::NextPollAt)
             .limit(limit)
@@ -74,6 +74,25 @@ impl Repo {
             .context("Failed to get pending tasks by type")
     }
 
+    /// Due tasks of any of `task_types`, oldest first, as candidates for fair selection
+    pub async fn get_pending_tasks_by_types(
+        &self,
+        task_types: &[TaskType],
+        limit: u64,
+    ) -> Result<Vec<tasks::Model>> {
+        let now = Utc::now().naive_utc();
+
+        tasks::Entity::find()
+            .filter(tasks::Column::NextPollAt.lte(now))
+            .filter(tasks::Column::DeadAt.is_null())
+            .filter(tasks::Column::Type.is_in(task_types.iter().copied()))
+            .order_by_asc(tasks::Column::NextPollAt)
+            .limit(limit)
+            .all(&self.db)
+            .await
+            .context("Failed to get pending tasks by types")
+    }
+
     pub async fn get_all_tasks_by_type(&self, task_type: TaskType) -> Result<Vec<tasks::Model>> {
         tasks::Entity::find()
             .filter(tasks::Column::Type.eq(task_type))
@@ -83,10 +102,28 @@ impl Repo {
             .context("Failed to get all tasks by type")
     }
 
+    /// Author tasks without a stored name, in id order starting after `after_id`
+    pub async fn get_author_tasks_without_name(
+        &self,
+        after_id: i32,
+        limit: u64,
+    ) -> Result<Vec<tasks::Model>> {
+        tasks::Entity::find()
+            .filter(tasks::Column::Type.eq(TaskType::Author))
+            .filter(tasks::Column::AuthorName.is_null())
+            .filter(tasks::Column::DeadAt.is_null())
+            .filter(tasks::Column::Id.gt(after_id))
+            .order_by_asc(tasks::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+            .context("Failed to get author tasks without name")
+    }
+
     pub async fn update_task_after_poll(
         &self,
         task_id: i32,
-        next_poll_at: DateTime<Local>,
+        next_poll_at: DateTime<Utc>,
     ) -> Result<tasks::Model> {
         let task = tasks::Entity::find_by_id(task_id)
             .one(&self.db)
@@ -94,9 +131,9 @@ impl Repo {
             .context("Failed to query task")?
             .ok_or_else(|| anyhow::anyhow!("Task {} not found", task_id))?;
 
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let mut active: tasks::ActiveModel = task.into_active_model();
-        active.next_poll_at = Set(next_poll_at.naive_local());
+        active.next_poll_at = Set(next_poll_at.naive_utc());
         active.last_polled_at = Set(Some(now));
 
         active
@@ -125,6 +162,84 @@ impl Repo {
             .context("Failed to update task author_name")
     }
 
+    /// Set or clear (`None`) the per-task poll interval override in minutes.
+    pub async fn update_task_poll_interval(
+        &self,
+        task_id: i32,
+        poll_interval_min: Option<i32>,
+    ) -> Result<tasks::Model> {
+        let task = tasks::Entity::find_by_id(task_id)
+            .one(&self.db)
+            .await
+            .context("Failed to query task")?
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", task_id))?;
+
+        let mut active: tasks::ActiveModel = task.into_active_model();
+        active.poll_interval_min = Set(poll_interval_min);
+
+        active
+            .update(&self.db)
+            .await
+            .context("Failed to update task poll interval")
+    }
+
+    /// Mark a task run as successful, resetting its consecutive failure count
+    pub async fn record_task_success(&self, task_id: i32) -> Result<()> {
+        tasks::Entity::update_many()
+            .col_expr(
+                tasks::Column::LastSuccessAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .col_expr(tasks::Column::FailureCount, Expr::value(0))
+            .filter(tasks::Column::Id.eq(task_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to record task success")?;
+        Ok(())
+    }
+
+    /// Store the error of a failed task run and count it as a consecutive failure
+    pub async fn record_task_failure(&self, task_id: i32, error: &str) -> Result<()> {
+        let error: String = error.chars().take(MAX_TASK_ERROR_CHARS).collect();
+        tasks::Entity::update_many()
+            .col_expr(tasks::Column::LastError, Expr::value(error))
+            .col_expr(
+                tasks::Column::LastErrorAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .col_expr(
+                tasks::Column::FailureCount,
+                Expr::col(tasks::Column::FailureCount).add(1),
+            )
+            .filter(tasks::Column::Id.eq(task_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to record task failure")?;
+        Ok(())
+    }
+
+    /// Tasks whose latest runs failed at least `min_failures` times in a row, worst first
+    pub async fn list_failing_tasks(&self, min_failures: i32) -> Result<Vec<tasks::Model>> {
+        tasks::Entity::find()
+            .filter(tasks::Column::FailureCount.gte(min_failures.max(1)))
+            .order_by_desc(tasks::Column::FailureCount)
+            .order_by_asc(tasks::Column::Id)
+            .all(&self.db)
+            .await
+            .context("Failed to list failing tasks")
+    }
+
+    /// Stop polling a task until it is revived by a new subscription
+    pub async fn mark_task_dead(&self, task_id: i32) -> Result<()> {
+        tasks::Entity::update_many()
+            .col_expr(tasks::Column::DeadAt, Expr::value(Utc::now().naive_utc()))
+            .filter(tasks::Column::Id.eq(task_id))
+            .exec(&self.db)
+            .await
+            .context("Failed to mark task dead")?;
+        Ok(())
+    }
+
     pub async fn delete_task(&self, task_id: i32) -> Result<()> {
         tasks::Entity::delete_by_id(task_id)
             .exec(&self.db)
@@ -133,3 +248,45 @@ impl Repo {
         Ok(())
     }
 }
+
+/// Insert a task, or revive the existing one with the same type and value, on any connection
+pub(super) async fn upsert_task_on<C: ConnectionTrait>(
+    db: &C,
+    task_type: TaskType,
+    value: &str,
+    author_name: Option<&str>,
+) -> Result<tasks::Model> {
+    let next_poll = Utc::now() + chrono::Duration::seconds(60);
+
+    let new_task = tasks::ActiveModel {
+        r#type: Set(task_type),
+        value: Set(value.to_string()),
+        next_poll_at: Set(next_poll.naive_utc()),
+        last_polled_at: Set(None),
+        author_name: Set(author_name.map(|s| s.to_string())),
+        poll_interval_min: Set(None),
+        ..Default::default()
+    };
+
+    // On conflict (same type+value), do NOT overwrite author_name.
+    // The first subscriber's display_name should be preserved;
+    // otherwise later subscribers could overwrite it for all chats.
+    // A new subscription revives a dead task so the author is checked again.
+    let conflict_handler = OnConflict::columns([tasks::Column::Type, tasks::Column::Value])
+        .update_columns([tasks::Column::Value, tasks::Column::DeadAt])
+        .to_owned();
+
+    tasks::Entity::insert(new_task)
+        .on_conflict(conflict_handler)
+        .exec_without_returning(db)
+        .await
+        .context("Failed to upsert task")?;
+
+    tasks::Entity::find()
+        .filter(tasks::Column::Type.eq(task_type))
+        .filter(tasks::Column::Value.eq(value))
+        .one(db)
+        .await
+        .context("Failed to find task by type and value")?
+        .ok_or_else(|| anyhow::anyhow!("Task with value {} not found after upsert", value))
+}
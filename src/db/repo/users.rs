@@ -2,7 +2,7 @@ use super::Repo;
 use crate::db::entities::users;
 use crate::db::types::UserRole;
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::Utc;
 use sea_orm::{
     sea_query::OnConflict, ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel,
     PaginatorTrait, QueryFilter, Set,
@@ -15,7 +15,7 @@ impl Repo {
         username: Option<String>,
         role: UserRole,
     ) -> Result<users::Model> {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let new_user = users::ActiveModel {
             id: Set(user_id),
@@ -73,7 +73,7 @@ impl Repo {
     ) -> Result<users::Model> {
         use sea_orm::TransactionTrait;
 
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
 
         let txn = self
             .db
@@ -0,0 +1,97 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Filter for works Pixiv marks as AI-generated
+///
+/// Chats store their default; a subscription's `Auto` follows it. `Auto` as a
+/// chat default keeps every work, like `All`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize, Default,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[serde(rename_all = "lowercase")]
+pub enum AiFilter {
+    /// Follow the chat's default
+    #[sea_orm(string_value = "auto")]
+    #[default]
+    Auto,
+    #[sea_orm(string_value = "all")]
+    All,
+    #[sea_orm(string_value = "exclude")]
+    Exclude,
+    #[sea_orm(string_value = "only")]
+    Only,
+}
+
+impl AiFilter {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Some(AiFilter::Auto),
+            "all" => Some(AiFilter::All),
+            "exclude" => Some(AiFilter::Exclude),
+            "only" => Some(AiFilter::Only),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            AiFilter::Auto => "auto",
+            AiFilter::All => "all",
+            AiFilter::Exclude => "exclude",
+            AiFilter::Only => "only",
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            AiFilter::Auto => "跟随聊天设置",
+            AiFilter::All => "不过滤",
+            AiFilter::Exclude => "排除 AI 作品",
+            AiFilter::Only => "仅 AI 作品",
+        }
+    }
+
+    /// Apply the override to the chat's default
+    pub fn resolve(&self, chat_default: AiFilter) -> AiFilter {
+        match self {
+            AiFilter::Auto => chat_default,
+            filter => *filter,
+        }
+    }
+
+    /// Whether a work with the given AI flag passes the filter
+    pub fn allows(&self, ai_generated: bool) -> bool {
+        match self {
+            AiFilter::Auto | AiFilter::All => true,
+            AiFilter::Exclude => !ai_generated,
+            AiFilter::Only => ai_generated,
+        }
+    }
+}
+
+impl std::fmt::Display for AiFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ai_filter_follows_chat_default_and_filters_ai_works() {
+        assert_eq!(AiFilter::parse("Exclude"), Some(AiFilter::Exclude));
+        assert_eq!(AiFilter::parse("maybe"), None);
+
+        assert_eq!(AiFilter::Auto.resolve(AiFilter::Only), AiFilter::Only);
+        assert_eq!(AiFilter::All.resolve(AiFilter::Exclude), AiFilter::All);
+
+        assert!(AiFilter::All.allows(true));
+        assert!(!AiFilter::Exclude.allows(true));
+        assert!(AiFilter::Exclude.allows(false));
+        assert!(AiFilter::Only.allows(true));
+        assert!(!AiFilter::Only.allows(false));
+    }
+}
@@ -0,0 +1,27 @@
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+
+/// Pixiv author IDs stored as a JSON array, e.g. a chat's blocked authors
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(transparent)]
+pub struct AuthorIds(pub Vec<u64>);
+
+impl Deref for AuthorIds {
+    type Target = Vec<u64>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for AuthorIds {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u64>> for AuthorIds {
+    fn from(ids: Vec<u64>) -> Self {
+        AuthorIds(ids)
+    }
+}
@@ -18,6 +18,11 @@ pub struct EhFilter {
     pub max_pages: Option<u32>,
     #[serde(default)]
     pub telegraph: bool,
+    /// Push a preview instead of the archive: `0` sends the cover with metadata,
+    /// `N` sends the first N sample pages as an album. Like `telegraph`, this is a
+    /// delivery preference and not part of the task signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_pages: Option<u32>,
 }
 
 impl EhFilter {
@@ -32,6 +37,7 @@ impl EhFilter {
             && self.min_pages.is_none()
             && self.max_pages.is_none()
             && !self.telegraph
+            && self.preview_pages.is_none()
     }
 
     /// Task-value filter-key signature using value-encoding (not just presence).
@@ -116,6 +122,7 @@ impl EhFilter {
             min_pages,
             max_pages,
             telegraph,
+            preview_pages: None,
         }
     }
 
@@ -133,6 +140,11 @@ impl EhFilter {
         if self.telegraph {
             parts.push("telegraph=on".to_string());
         }
+        match self.preview_pages {
+            Some(0) => parts.push("仅封面".to_string()),
+            Some(pages) => parts.push(format!("预览{pages}页")),
+            None => {}
+        }
         parts.join(" ")
     }
 }
@@ -156,6 +168,7 @@ mod tests {
             min_pages: None,
             max_pages: None,
             telegraph: false,
+            preview_pages: None,
         };
         assert_eq!(f.task_value_signature(), "r4");
 
@@ -164,6 +177,7 @@ mod tests {
             min_pages: Some(20),
             max_pages: None,
             telegraph: true,
+            preview_pages: None,
         };
         assert_eq!(f.task_value_signature(), "r4p20");
 
@@ -172,6 +186,7 @@ mod tests {
             min_pages: None,
             max_pages: Some(500),
             telegraph: false,
+            preview_pages: None,
         };
         assert_eq!(f.task_value_signature(), "P500");
 
@@ -180,6 +195,7 @@ mod tests {
             min_pages: Some(10),
             max_pages: Some(200),
             telegraph: false,
+            preview_pages: None,
         };
         assert_eq!(f.task_value_signature(), "r3p10P200");
     }
@@ -280,12 +296,14 @@ mod tests {
             min_pages: Some(20),
             max_pages: Some(500),
             telegraph: false,
+            preview_pages: None,
         };
         let f2 = EhFilter {
             min_rating: Some(3),
             min_pages: Some(10),
             max_pages: Some(1000),
             telegraph: true,
+            preview_pages: None,
         };
 
         let agg = EhFilter::aggregate(&[Some(&f1), Some(&f2)]);
@@ -312,6 +330,7 @@ mod tests {
             min_pages: Some(20),
             max_pages: None,
             telegraph: true,
+            preview_pages: None,
         };
         let display = f.format_for_display();
         assert!(display.contains("rating≥4"));
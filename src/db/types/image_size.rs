@@ -0,0 +1,92 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Image size a subscription pushes Pixiv works in
+///
+/// `Auto` uses the global `content.push_size` (or `content.image_size`).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize, Default,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[serde(rename_all = "lowercase")]
+pub enum PushImageSize {
+    /// Follow the global config
+    #[sea_orm(string_value = "auto")]
+    #[default]
+    Auto,
+    #[sea_orm(string_value = "original")]
+    Original,
+    #[sea_orm(string_value = "large")]
+    Large,
+    #[sea_orm(string_value = "medium")]
+    Medium,
+}
+
+impl PushImageSize {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Some(PushImageSize::Auto),
+            "original" => Some(PushImageSize::Original),
+            "large" => Some(PushImageSize::Large),
+            "medium" => Some(PushImageSize::Medium),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PushImageSize::Auto => "auto",
+            PushImageSize::Original => "original",
+            PushImageSize::Large => "large",
+            PushImageSize::Medium => "medium",
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            PushImageSize::Auto => "跟随全局设置",
+            PushImageSize::Original => "原图",
+            PushImageSize::Large => "大图",
+            PushImageSize::Medium => "中图",
+        }
+    }
+
+    /// Apply the override to the globally configured push size
+    pub fn resolve(&self, default: pixiv_client::ImageSize) -> pixiv_client::ImageSize {
+        match self {
+            PushImageSize::Auto => default,
+            PushImageSize::Original => pixiv_client::ImageSize::Original,
+            PushImageSize::Large => pixiv_client::ImageSize::Large,
+            PushImageSize::Medium => pixiv_client::ImageSize::Medium,
+        }
+    }
+}
+
+impl std::fmt::Display for PushImageSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_image_size_overrides_global_size() {
+        assert_eq!(
+            PushImageSize::parse("Original"),
+            Some(PushImageSize::Original)
+        );
+        assert_eq!(PushImageSize::parse("square_medium"), None);
+
+        assert_eq!(
+            PushImageSize::Auto.resolve(pixiv_client::ImageSize::Large),
+            pixiv_client::ImageSize::Large
+        );
+        assert_eq!(
+            PushImageSize::Medium.resolve(pixiv_client::ImageSize::Original),
+            pixiv_client::ImageSize::Medium
+        );
+    }
+}
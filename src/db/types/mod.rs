@@ -1,17 +1,29 @@
+mod ai_filter;
+mod author_ids;
 mod booru_filter;
 mod booru_task_key;
 mod eh_filter;
 mod eh_task_key;
+mod image_size;
+mod push_mode;
 mod role;
+mod sent_pages;
+mod spoiler;
 mod state;
 mod tag;
 mod task_type;
 
+pub use ai_filter::*;
+pub use author_ids::*;
 pub use booru_filter::*;
 pub use booru_task_key::*;
 pub use eh_filter::*;
 pub use eh_task_key::*;
+pub use image_size::*;
+pub use push_mode::*;
 pub use role::*;
+pub use sent_pages::*;
+pub use spoiler::*;
 pub use state::*;
 pub use tag::*;
 pub use task_type::*;
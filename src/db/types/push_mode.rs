@@ -0,0 +1,61 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How an author subscription delivers new works found in one poll
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize, Default,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[serde(rename_all = "lowercase")]
+pub enum PushMode {
+    /// One work per poll, with all of its pages
+    #[sea_orm(string_value = "single")]
+    #[default]
+    Single,
+    /// Up to a media group of new works per poll, first page of each
+    #[sea_orm(string_value = "album")]
+    Album,
+}
+
+impl PushMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "single" => Some(PushMode::Single),
+            "album" => Some(PushMode::Album),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PushMode::Single => "single",
+            PushMode::Album => "album",
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            PushMode::Single => "逐个推送",
+            PushMode::Album => "合并为相册",
+        }
+    }
+}
+
+impl std::fmt::Display for PushMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_mode_parses_case_insensitively() {
+        assert_eq!(PushMode::parse("Album"), Some(PushMode::Album));
+        assert_eq!(PushMode::parse("single"), Some(PushMode::Single));
+        assert_eq!(PushMode::parse("batch"), None);
+        assert_eq!(PushMode::default().as_str(), "single");
+    }
+}
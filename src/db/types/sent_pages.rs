@@ -0,0 +1,27 @@
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+
+/// Zero-based page indices of a work already delivered, stored as a JSON array
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(transparent)]
+pub struct SentPages(pub Vec<usize>);
+
+impl Deref for SentPages {
+    type Target = Vec<usize>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SentPages {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<usize>> for SentPages {
+    fn from(pages: Vec<usize>) -> Self {
+        SentPages(pages)
+    }
+}
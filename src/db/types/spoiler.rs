@@ -0,0 +1,79 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Per-subscription media spoiler override
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize, Default,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(10))")]
+#[serde(rename_all = "lowercase")]
+pub enum SpoilerMode {
+    /// Follow the chat's sensitive tag settings
+    #[sea_orm(string_value = "auto")]
+    #[default]
+    Auto,
+    #[sea_orm(string_value = "always")]
+    Always,
+    #[sea_orm(string_value = "never")]
+    Never,
+}
+
+impl SpoilerMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Some(SpoilerMode::Auto),
+            "always" => Some(SpoilerMode::Always),
+            "never" => Some(SpoilerMode::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SpoilerMode::Auto => "auto",
+            SpoilerMode::Always => "always",
+            SpoilerMode::Never => "never",
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            SpoilerMode::Auto => "跟随聊天设置",
+            SpoilerMode::Always => "总是遮罩",
+            SpoilerMode::Never => "从不遮罩",
+        }
+    }
+
+    /// Apply the override to the chat-level decision
+    pub fn resolve(&self, chat_default: bool) -> bool {
+        match self {
+            SpoilerMode::Auto => chat_default,
+            SpoilerMode::Always => true,
+            SpoilerMode::Never => false,
+        }
+    }
+}
+
+impl std::fmt::Display for SpoilerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spoiler_mode_parses_and_overrides_chat_default() {
+        assert_eq!(SpoilerMode::parse("Always"), Some(SpoilerMode::Always));
+        assert_eq!(SpoilerMode::parse("never"), Some(SpoilerMode::Never));
+        assert_eq!(SpoilerMode::parse("auto"), Some(SpoilerMode::Auto));
+        assert_eq!(SpoilerMode::parse("sometimes"), None);
+
+        assert!(SpoilerMode::Auto.resolve(true));
+        assert!(!SpoilerMode::Auto.resolve(false));
+        assert!(SpoilerMode::Always.resolve(false));
+        assert!(!SpoilerMode::Never.resolve(true));
+    }
+}
@@ -1,3 +1,4 @@
+use super::TaskType;
 use chrono::{DateTime, Utc};
 use sea_orm::FromJsonQueryResult;
 use serde::{Deserialize, Serialize};
@@ -11,11 +12,142 @@ pub enum SubscriptionState {
     BooruPool(BooruPoolState),
     BooruRanking(BooruRankingState),
     EhTag(EhTagState),
+    Bookmarks(BookmarksState),
+}
+
+impl SubscriptionState {
+    /// Resolve a write conflict between this (our) state and the state a
+    /// concurrent writer already persisted.
+    ///
+    /// Progress made by either side is kept: pushed-ID sets and booru hot posts
+    /// are unioned and cursors take the furthest value. Per-item retry progress
+    /// (pending work and queues) stays ours, since it describes the push we just
+    /// performed.
+    pub fn merge_concurrent(self, theirs: &SubscriptionState) -> SubscriptionState {
+        match (self, theirs) {
+            (SubscriptionState::Author(mut ours), SubscriptionState::Author(theirs)) => {
+                ours.latest_illust_id = ours.latest_illust_id.max(theirs.latest_illust_id);
+                SubscriptionState::Author(ours)
+            }
+            (SubscriptionState::Ranking(mut ours), SubscriptionState::Ranking(theirs)) => {
+                ours.pushed_ids = merge_pushed_ids(&theirs.pushed_ids, ours.pushed_ids);
+                SubscriptionState::Ranking(ours)
+            }
+            (SubscriptionState::BooruTag(mut ours), SubscriptionState::BooruTag(theirs)) => {
+                ours.latest_post_id = ours.latest_post_id.max(theirs.latest_post_id);
+                ours.hot_posts = merge_hot_posts(&theirs.hot_posts, ours.hot_posts);
+                SubscriptionState::BooruTag(ours)
+            }
+            (SubscriptionState::BooruPool(mut ours), SubscriptionState::BooruPool(theirs)) => {
+                ours.pushed_post_ids =
+                    merge_pushed_ids(&theirs.pushed_post_ids, ours.pushed_post_ids);
+                SubscriptionState::BooruPool(ours)
+            }
+            (
+                SubscriptionState::BooruRanking(mut ours),
+                SubscriptionState::BooruRanking(theirs),
+            ) => {
+                ours.pushed_ids = merge_pushed_ids(&theirs.pushed_ids, ours.pushed_ids);
+                SubscriptionState::BooruRanking(ours)
+            }
+            (SubscriptionState::EhTag(mut ours), SubscriptionState::EhTag(theirs)) => {
+                ours.pushed_gids = merge_pushed_ids(&theirs.pushed_gids, ours.pushed_gids);
+                ours.latest_posted_ts = ours.latest_posted_ts.max(theirs.latest_posted_ts);
+                SubscriptionState::EhTag(ours)
+            }
+            (SubscriptionState::Bookmarks(mut ours), SubscriptionState::Bookmarks(theirs)) => {
+                ours.pushed_ids = merge_pushed_ids(&theirs.pushed_ids, ours.pushed_ids);
+                SubscriptionState::Bookmarks(ours)
+            }
+            // Different state kinds cannot be merged; the newer write wins
+            (ours, _) => ours,
+        }
+    }
+
+    /// Task type whose engine writes this state variant
+    pub fn task_type(&self) -> TaskType {
+        match self {
+            SubscriptionState::Author(_) => TaskType::Author,
+            SubscriptionState::Ranking(_) => TaskType::Ranking,
+            SubscriptionState::BooruTag(_) => TaskType::BooruTag,
+            SubscriptionState::BooruPool(_) => TaskType::BooruPool,
+            SubscriptionState::BooruRanking(_) => TaskType::BooruRanking,
+            SubscriptionState::EhTag(_) => TaskType::Ehentai,
+            SubscriptionState::Bookmarks(_) => TaskType::Bookmarks,
+        }
+    }
+
+    /// Copy of this state without pending work the current engines can no longer
+    /// resume, or `None` if nothing is stale.
+    ///
+    /// Ranking and booru ranking pending items were only written by the old combined
+    /// scheduler; author retries moved to the `push_queue` table, so any author
+    /// pending push left in the state is stale.
+    pub fn without_stale_pending(&self) -> Option<SubscriptionState> {
+        match self {
+            SubscriptionState::Author(state) => {
+                state.pending_illust.as_ref()?;
+                Some(SubscriptionState::Author(AuthorState {
+                    latest_illust_id: state.latest_illust_id,
+                    pending_illust: None,
+                }))
+            }
+            SubscriptionState::Ranking(state) => {
+                state.pending_illust.as_ref()?;
+                Some(SubscriptionState::Ranking(RankingState {
+                    pushed_ids: state.pushed_ids.clone(),
+                    pending_illust: None,
+                }))
+            }
+            SubscriptionState::BooruRanking(state) => {
+                state.pending_post.as_ref()?;
+                Some(SubscriptionState::BooruRanking(BooruRankingState {
+                    pending_post: None,
+                    ..state.clone()
+                }))
+            }
+            SubscriptionState::BooruTag(_)
+            | SubscriptionState::BooruPool(_)
+            | SubscriptionState::EhTag(_)
+            | SubscriptionState::Bookmarks(_) => None,
+        }
+    }
+}
+
+/// Union two pushed-ID lists, keeping push-chronological order: IDs only the
+/// other writer knows about go first so that front-trimming drops them first.
+fn merge_pushed_ids(theirs: &[u64], ours: Vec<u64>) -> Vec<u64> {
+    let mut merged: Vec<u64> = theirs
+        .iter()
+        .copied()
+        .filter(|id| !ours.contains(id))
+        .collect();
+    merged.extend(ours);
+    merged
+}
+
+/// Union of two hot-post lists; a post tracked by both sides keeps the earliest
+/// sighting, counts as pushed if either side pushed it and keeps the higher attempt count.
+fn merge_hot_posts(theirs: &[HotPost], mut ours: Vec<HotPost>) -> Vec<HotPost> {
+    let mut merged = Vec::with_capacity(theirs.len() + ours.len());
+    for their_post in theirs {
+        match ours.iter_mut().find(|post| post.id == their_post.id) {
+            Some(post) => {
+                post.first_seen = post.first_seen.min(their_post.first_seen);
+                post.pushed |= their_post.pushed;
+                post.attempts = post.attempts.max(their_post.attempts);
+            }
+            None => merged.push(their_post.clone()),
+        }
+    }
+    merged.extend(ours);
+    merged
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AuthorState {
     pub latest_illust_id: u64,
+    /// Written by versions that kept retries in the state; retries now live in `push_queue`
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pending_illust: Option<PendingIllust>,
 }
@@ -33,6 +165,31 @@ pub struct PendingIllust {
     pub sent_pages: Vec<usize>,
     pub total_pages: usize,
     pub retry_count: u8,
+    /// Whether the earlier batches of this work were sent with a spoiler.
+    /// Pixiv only exposes sensitivity per work, so continuation batches reuse
+    /// this decision instead of re-evaluating against possibly changed settings.
+    #[serde(default)]
+    pub spoiler: bool,
+}
+
+/// State for bookmark subscriptions.
+///
+/// Bookmarks come newest-bookmarked first, not by illust ID, so the works already
+/// handled are remembered instead of a cursor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarksState {
+    /// Works pushed or skipped, in the order they were handled
+    pub pushed_ids: Vec<u64>,
+}
+
+impl BookmarksState {
+    /// Drop the front of `pushed_ids` until length <= cap.
+    pub fn trim_pushed(&mut self, cap: usize) {
+        if self.pushed_ids.len() > cap {
+            let drop = self.pushed_ids.len() - cap;
+            self.pushed_ids.drain(0..drop);
+        }
+    }
 }
 
 /// State for booru tag subscriptions.
@@ -203,6 +360,37 @@ impl EhTagState {
             self.pushed_gids.drain(0..drop);
         }
     }
+
+    /// Newest (largest) GID in the pushed set.
+    pub fn newest_pushed_gid(&self) -> Option<u64> {
+        self.pushed_gids.iter().copied().max()
+    }
+
+    /// Whether a gallery still needs to be pushed.
+    ///
+    /// With `require_newer`, galleries at or below the newest pushed GID are
+    /// treated as seen even after they have been trimmed out of `pushed_gids`.
+    /// E-Hentai GIDs grow with upload time, so only galleries uploaded after the
+    /// newest pushed one pass. Rating-filtered subscriptions must not use this,
+    /// since older galleries may reach the rating threshold later.
+    pub fn is_unseen(&self, gid: u64, require_newer: bool) -> bool {
+        if self.pushed_gids.contains(&gid) {
+            return false;
+        }
+        !require_newer || self.newest_pushed_gid().is_none_or(|newest| gid > newest)
+    }
+
+    /// Forget expunged galleries: drop them from the pushed set and the
+    /// pending backlog. A re-post gets a new GID, so the expunged one will
+    /// never show up in search results again.
+    pub fn expunge_gids(&mut self, gids: &[u64]) {
+        if gids.is_empty() {
+            return;
+        }
+        self.pushed_gids.retain(|gid| !gids.contains(gid));
+        self.pending_galleries
+            .retain(|gallery| !gids.contains(&gallery.gid));
+    }
 }
 
 /// A queued booru post with full data for pending delivery.
@@ -246,6 +434,180 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_concurrent_author_keeps_furthest_cursor() {
+        let ours = SubscriptionState::Author(AuthorState {
+            latest_illust_id: 10,
+            pending_illust: None,
+        });
+        let theirs = SubscriptionState::Author(AuthorState {
+            latest_illust_id: 20,
+            pending_illust: None,
+        });
+
+        assert_eq!(ours.merge_concurrent(&theirs), theirs);
+    }
+
+    #[test]
+    fn test_merge_concurrent_author_keeps_our_pending_illust() {
+        let pending = PendingIllust {
+            illust_id: 15,
+            sent_pages: vec![0],
+            total_pages: 3,
+            retry_count: 1,
+            spoiler: true,
+        };
+        let ours = SubscriptionState::Author(AuthorState {
+            latest_illust_id: 10,
+            pending_illust: Some(pending.clone()),
+        });
+        let theirs = SubscriptionState::Author(AuthorState {
+            latest_illust_id: 20,
+            pending_illust: None,
+        });
+
+        assert_eq!(
+            ours.merge_concurrent(&theirs),
+            SubscriptionState::Author(AuthorState {
+                latest_illust_id: 20,
+                pending_illust: Some(pending),
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_concurrent_booru_tag_keeps_our_queue_and_merges_hot_posts() {
+        let earlier = Utc::now() - chrono::Duration::hours(1);
+        let now = Utc::now();
+        let hot = |id, first_seen, pushed, attempts| HotPost {
+            id,
+            first_seen,
+            pushed,
+            attempts,
+        };
+        let ours = SubscriptionState::BooruTag(BooruTagState {
+            latest_post_id: 100,
+            pending_queue: vec![make_queued_post(101), make_queued_post(102)],
+            retry_count: 1,
+            hot_posts: vec![hot(1, now, false, 2), hot(2, now, false, 0)],
+        });
+        let theirs = SubscriptionState::BooruTag(BooruTagState {
+            latest_post_id: 200,
+            pending_queue: Vec::new(),
+            retry_count: 0,
+            hot_posts: vec![hot(1, earlier, true, 1), hot(3, earlier, false, 0)],
+        });
+
+        let SubscriptionState::BooruTag(merged) = ours.merge_concurrent(&theirs) else {
+            panic!("expected BooruTag state");
+        };
+        assert_eq!(merged.latest_post_id, 200);
+        assert_eq!(
+            merged.pending_queue,
+            vec![make_queued_post(101), make_queued_post(102)]
+        );
+        assert_eq!(merged.retry_count, 1);
+        assert_eq!(
+            merged.hot_posts,
+            vec![
+                hot(3, earlier, false, 0),
+                hot(1, earlier, true, 2),
+                hot(2, now, false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_concurrent_eh_tag_unions_pushed_gids() {
+        let ours = SubscriptionState::EhTag(EhTagState {
+            pushed_gids: vec![2, 3],
+            latest_posted_ts: 100,
+            ..EhTagState::cleared()
+        });
+        let theirs = SubscriptionState::EhTag(EhTagState {
+            pushed_gids: vec![1, 2],
+            latest_posted_ts: 200,
+            ..EhTagState::cleared()
+        });
+
+        let SubscriptionState::EhTag(merged) = ours.merge_concurrent(&theirs) else {
+            panic!("expected EhTag state");
+        };
+        assert_eq!(merged.pushed_gids, vec![1, 2, 3]);
+        assert_eq!(merged.latest_posted_ts, 200);
+    }
+
+    #[test]
+    fn test_merge_concurrent_bookmarks_unions_and_trims_pushed_ids() {
+        let ours = SubscriptionState::Bookmarks(BookmarksState {
+            pushed_ids: vec![2, 3],
+        });
+        let theirs = SubscriptionState::Bookmarks(BookmarksState {
+            pushed_ids: vec![1, 2],
+        });
+
+        let SubscriptionState::Bookmarks(mut merged) = ours.merge_concurrent(&theirs) else {
+            panic!("expected Bookmarks state");
+        };
+        assert_eq!(merged.pushed_ids, vec![1, 2, 3]);
+
+        merged.trim_pushed(2);
+        assert_eq!(merged.pushed_ids, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_without_stale_pending_drops_unresumable_pending_work() {
+        let pending = |illust_id, sent_pages: Vec<usize>, total_pages| PendingIllust {
+            illust_id,
+            sent_pages,
+            total_pages,
+            retry_count: 0,
+            spoiler: false,
+        };
+        let author = |pending_illust| {
+            SubscriptionState::Author(AuthorState {
+                latest_illust_id: 100,
+                pending_illust,
+            })
+        };
+
+        assert_eq!(author(None).without_stale_pending(), None);
+        for stale in [
+            pending(120, vec![0], 3),
+            pending(90, vec![0], 3),
+            pending(120, vec![0, 1, 2], 3),
+            pending(120, vec![5], 3),
+            pending(120, vec![], 0),
+        ] {
+            assert_eq!(
+                author(Some(stale)).without_stale_pending(),
+                Some(author(None))
+            );
+        }
+
+        let ranking = SubscriptionState::Ranking(RankingState {
+            pushed_ids: vec![1, 2],
+            pending_illust: Some(pending(3, vec![0], 2)),
+        });
+        assert_eq!(
+            ranking.without_stale_pending(),
+            Some(SubscriptionState::Ranking(RankingState {
+                pushed_ids: vec![1, 2],
+                pending_illust: None,
+            }))
+        );
+        assert_eq!(ranking.task_type(), TaskType::Ranking);
+    }
+
+    #[test]
+    fn test_pending_illust_without_spoiler_field_defaults_to_false() {
+        let pending: PendingIllust = serde_json::from_str(
+            r#"{"illust_id":1,"sent_pages":[0],"total_pages":3,"retry_count":1}"#,
+        )
+        .unwrap();
+        assert!(!pending.spoiler);
+    }
+
     #[test]
     fn test_booru_tag_state_cleared() {
         let state = BooruTagState::cleared(42);
@@ -366,6 +728,38 @@ mod tests {
         assert_eq!(state.pushed_gids, vec![3, 4, 5]);
     }
 
+    #[test]
+    fn test_eh_tag_state_is_unseen_respects_newest_gid_after_trim() {
+        let mut state = EhTagState {
+            pushed_gids: vec![10, 30, 20],
+            ..EhTagState::cleared()
+        };
+        state.trim_pushed(1);
+        assert_eq!(state.newest_pushed_gid(), Some(20));
+        assert!(!state.is_unseen(20, false));
+        // Trimmed GIDs stay seen only under the newest-GID rule
+        assert!(state.is_unseen(10, false));
+        assert!(!state.is_unseen(10, true));
+        assert!(state.is_unseen(21, true));
+        assert!(EhTagState::cleared().is_unseen(1, true));
+    }
+
+    #[test]
+    fn test_eh_tag_state_expunge_gids() {
+        let pending = |gid| EhPendingGallery {
+            gid,
+            ..Default::default()
+        };
+        let mut state = EhTagState {
+            pushed_gids: vec![1, 2, 3],
+            pending_galleries: vec![pending(4), pending(5)],
+            ..EhTagState::cleared()
+        };
+        state.expunge_gids(&[2, 5]);
+        assert_eq!(state.pushed_gids, vec![1, 3]);
+        assert_eq!(state.pending_galleries, vec![pending(4)]);
+    }
+
     #[test]
     fn test_eh_tag_state_pending_defaults_empty() {
         let state = EhTagState::cleared();
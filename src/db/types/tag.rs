@@ -41,7 +41,8 @@ impl From<Vec<String>> for Tags {
 /// A unified tag filter for include/exclude filtering.
 ///
 /// Tags are stored in their original form for display purposes.
-/// Normalization is done on-the-fly during matching for case-insensitive comparison.
+/// Normalization and alias resolution are done on-the-fly during matching, so
+/// `+genshin` also matches works tagged `原神` when the two are aliases.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
 pub struct TagFilter {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -113,36 +114,25 @@ impl TagFilter {
     ///
     /// Returns a string like `\+tag1 \+tag2 \-tag3`
     pub fn format_for_display(&self) -> String {
-        let mut parts = Vec::new();
-
-        if !self.include.is_empty() {
-            let include_str = self
-                .include
-                .iter()
-                .map(|s| markdown::escape(format!("+{}", s).as_str()))
-                .collect::<Vec<_>>()
-                .join(" ");
-            parts.push(include_str);
-        }
-
-        if !self.exclude.is_empty() {
-            let exclude_str = self
-                .exclude
-                .iter()
-                .map(|s| markdown::escape(format!("-{}", s).as_str()))
-                .collect::<Vec<_>>()
-                .join(" ");
-            parts.push(exclude_str);
-        }
+        markdown::escape(&self.format_plain())
+    }
 
-        parts.join(" ")
+    /// Format as plain text, e.g. `+tag1 +tag2 -tag3`
+    pub fn format_plain(&self) -> String {
+        self.include
+            .iter()
+            .map(|s| format!("+{}", s))
+            .chain(self.exclude.iter().map(|s| format!("-{}", s)))
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
     /// Check if an illust matches this filter.
     ///
     /// - If exclude tags are specified, the illust must NOT contain any of them.
     /// - If include tags are specified, the illust must contain at least one of them.
-    /// - Tags are compared case-insensitively after normalization.
+    /// - Tags are compared case-insensitively after normalization, with aliases
+    ///   resolved to the tag they belong to.
     pub fn matches(&self, illust: &Illust) -> bool {
         // Early return if no filter
         if self.is_empty() {
@@ -153,7 +143,7 @@ impl TagFilter {
         let illust_tags: Vec<String> = illust
             .tags
             .iter()
-            .map(|t| tag::normalize_tag(&t.name))
+            .map(|t| tag::canonical_tag(&t.name))
             .collect();
 
         self.matches_normalized_tags(&illust_tags)
@@ -168,13 +158,13 @@ impl TagFilter {
             return true;
         }
 
-        let normalized: Vec<String> = tags.iter().map(|t| tag::normalize_tag(t)).collect();
+        let normalized: Vec<String> = tags.iter().map(|t| tag::canonical_tag(t)).collect();
         self.matches_normalized_tags(&normalized)
     }
 
     fn matches_normalized_tags(&self, normalized_tags: &[String]) -> bool {
         for exclude_tag in &self.exclude {
-            let normalized = tag::normalize_tag(exclude_tag);
+            let normalized = tag::canonical_tag(exclude_tag);
             if normalized_tags.iter().any(|t| t == &normalized) {
                 return false;
             }
@@ -182,7 +172,7 @@ impl TagFilter {
 
         if !self.include.is_empty() {
             for include_tag in &self.include {
-                let normalized = tag::normalize_tag(include_tag);
+                let normalized = tag::canonical_tag(include_tag);
                 if normalized_tags.iter().any(|t| t == &normalized) {
                     return true;
                 }
@@ -206,10 +196,19 @@ impl TagFilter {
         iter.into_iter().filter(|i| self.matches(i)).collect()
     }
 
-    /// Merge another filter into this one (combine include/exclude lists).
+    /// Merge another filter into this one (combine include/exclude lists,
+    /// skipping tags that are already present).
     pub fn merge(&mut self, other: &TagFilter) {
-        self.include.extend(other.include.iter().cloned());
-        self.exclude.extend(other.exclude.iter().cloned());
+        for tag in &other.include {
+            if !self.include.contains(tag) {
+                self.include.push(tag.clone());
+            }
+        }
+        for tag in &other.exclude {
+            if !self.exclude.contains(tag) {
+                self.exclude.push(tag.clone());
+            }
+        }
     }
 
     /// Create a merged filter from two filters.
@@ -283,5 +282,26 @@ mod tests {
         filter1.merge(&filter2);
         assert_eq!(filter1.include, vec!["tag1"]);
         assert_eq!(filter1.exclude, vec!["tag2"]);
+
+        let merged = filter1.merged(&TagFilter::parse_from_args(&["+tag1", "-tag2", "-tag3"]));
+        assert_eq!(merged.include, vec!["tag1"]);
+        assert_eq!(merged.exclude, vec!["tag2", "tag3"]);
+    }
+
+    #[test]
+    fn test_matches_resolves_tag_aliases() {
+        // The alias table is process-wide; unique tags keep other tests unaffected
+        tag::set_tag_aliases(std::collections::HashMap::from([(
+            "aliastestgenshin".to_string(),
+            "aliastest原神".to_string(),
+        )]));
+        assert_eq!(tag::canonical_tag("AliasTest-Genshin"), "aliastest原神");
+
+        let include = TagFilter::parse_from_args(&["+AliasTest Genshin"]);
+        assert!(include.matches_tag_strings(&["aliastest原神"]));
+        assert!(!include.matches_tag_strings(&["other"]));
+
+        let exclude = TagFilter::parse_from_args(&["-aliastest原神"]);
+        assert!(!exclude.matches_tag_strings(&["aliastest_genshin", "aliastestgenshin"]));
     }
 }
@@ -3,7 +3,9 @@ use core::fmt;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
 #[sea_orm(rs_type = "String", db_type = "String(StringLen::N(20))")]
 pub enum TaskType {
     #[sea_orm(string_value = "author")]
@@ -18,6 +20,8 @@ pub enum TaskType {
     BooruRanking,
     #[sea_orm(string_value = "ehentai")]
     Ehentai,
+    #[sea_orm(string_value = "bookmarks")]
+    Bookmarks,
 }
 
 impl fmt::Display for TaskType {
@@ -29,6 +33,7 @@ impl fmt::Display for TaskType {
             TaskType::BooruPool => write!(f, "booru_pool"),
             TaskType::BooruRanking => write!(f, "booru_ranking"),
             TaskType::Ehentai => write!(f, "ehentai"),
+            TaskType::Bookmarks => write!(f, "bookmarks"),
         }
     }
 }
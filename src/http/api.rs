@@ -0,0 +1,158 @@
+//! Read-only JSON API over repo data
+//!
+//! Every route requires `Authorization: Bearer <http.api_token>`.
+//!
+//! - `GET /api/chats/{id}` - chat settings
+//! - `GET /api/chats/{id}/subscriptions` - subscriptions of a chat with their tasks
+//! - `GET /api/tasks/pending` - tasks due for polling
+
+use crate::db::entities::{chats, subscriptions, tasks};
+use crate::db::repo::Repo;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+
+/// Maximum number of tasks returned by `/api/tasks/pending`
+const PENDING_TASKS_LIMIT: u64 = 500;
+
+#[derive(Clone)]
+struct ApiState {
+    repo: Arc<Repo>,
+    token: Arc<str>,
+}
+
+pub(super) fn router(repo: Arc<Repo>, token: String) -> Router {
+    let state = ApiState {
+        repo,
+        token: token.into(),
+    };
+
+    Router::new()
+        .route("/api/chats/{id}", get(get_chat))
+        .route(
+            "/api/chats/{id}/subscriptions",
+            get(list_chat_subscriptions),
+        )
+        .route("/api/tasks/pending", get(list_pending_tasks))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| token_matches(token, &state.token));
+
+    if !authorized {
+        return ApiError::Unauthorized.into_response();
+    }
+    next.run(request).await
+}
+
+/// Compare tokens without stopping at the first differing byte
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+enum ApiError {
+    Unauthorized,
+    NotFound,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: &'static str,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized"),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found"),
+            ApiError::Internal(e) => {
+                error!("API request failed: {:#}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+            }
+        };
+        (status, Json(ErrorBody { error })).into_response()
+    }
+}
+
+/// A subscription together with the task it follows
+#[derive(Serialize)]
+struct SubscriptionView {
+    #[serde(flatten)]
+    subscription: subscriptions::Model,
+    task: tasks::Model,
+}
+
+async fn get_chat(
+    State(state): State<ApiState>,
+    Path(chat_id): Path<i64>,
+) -> Result<Json<chats::Model>, ApiError> {
+    state
+        .repo
+        .get_chat(chat_id)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
+
+async fn list_chat_subscriptions(
+    State(state): State<ApiState>,
+    Path(chat_id): Path<i64>,
+) -> Result<Json<Vec<SubscriptionView>>, ApiError> {
+    if state.repo.get_chat(chat_id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let subscriptions = state.repo.list_subscriptions_by_chat(chat_id).await?;
+    Ok(Json(
+        subscriptions
+            .into_iter()
+            .map(|(subscription, task)| SubscriptionView { subscription, task })
+            .collect(),
+    ))
+}
+
+async fn list_pending_tasks(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<tasks::Model>>, ApiError> {
+    Ok(Json(
+        state.repo.get_pending_tasks(PENDING_TASKS_LIMIT).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::token_matches;
+
+    #[test]
+    fn token_matches_requires_exact_token() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret2", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+}
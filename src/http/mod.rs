@@ -0,0 +1,25 @@
+//! Optional embedded HTTP server
+//!
+//! Currently serves the read-only JSON API under `/api` (see [`api`]).
+
+mod api;
+
+use crate::config::HttpConfig;
+use crate::db::repo::Repo;
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::info;
+
+/// Bind the configured address and serve until the task is aborted
+pub async fn serve(config: HttpConfig, api_token: String, repo: Arc<Repo>) -> Result<()> {
+    let router = api::router(repo, api_token);
+
+    let listener = tokio::net::TcpListener::bind(&config.listen)
+        .await
+        .with_context(|| format!("Failed to bind HTTP server to {}", config.listen))?;
+    info!("🚀 HTTP server listening on {}", config.listen);
+
+    axum::serve(listener, router)
+        .await
+        .context("HTTP server stopped")
+}
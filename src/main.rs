@@ -3,23 +3,31 @@ mod bot;
 mod cache;
 mod config;
 mod db;
+mod http;
 mod pixiv;
+mod saucenao;
 mod scheduler;
 mod utils;
 
 use crate::config::Config;
-use anyhow::Result;
-use sea_orm_migration::MigratorTrait;
+use anyhow::{Context, Result};
 use teloxide::requests::RequesterExt;
 use tracing::{error, info, warn};
 use tracing_subscriber::fmt::time::ChronoLocal;
-use tracing_subscriber::{prelude::*, EnvFilter};
+use tracing_subscriber::prelude::*;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load configuration
     let config = Config::load()?;
 
+    // Operator subcommand: `pixivbot migrate [--status | --redo <name>]`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        let db = db::establish_connection(&config.database.url).await?;
+        return db::migrate::run_cli(&db, &args[1..]).await;
+    }
+
     // Initialize variables
     let log_level = config.log_level();
     let log_dir = &config.logging.dir;
@@ -47,29 +55,33 @@ async fn main() -> Result<()> {
         .with_timer(local_timer)
         .with_writer(non_blocking);
 
-    // Filter layer based on config
-    let filter_layer = EnvFilter::from_default_env()
-        .add_directive(log_level.into())
-        .add_directive("sqlx=warn".parse().unwrap())
-        .add_directive("sea_orm=warn".parse().unwrap())
-        .add_directive("hyper_util=warn".parse().unwrap());
+    // Keep recent lines in memory for /logs
+    let log_buffer = utils::log_buffer::LogBuffer::default();
+
+    // Filter layer based on config, swapped when the log level is reloaded
+    let (filter_layer, log_reload_handle) =
+        tracing_subscriber::reload::Layer::new(config::log_filter(log_level));
 
     // Combine layers
     tracing_subscriber::registry()
         .with(filter_layer)
         .with(stdout_layer)
         .with(file_layer)
+        .with(log_buffer.clone())
         .init();
 
     info!("Starting PixivBot...");
     info!("Logs are written to: {}", log_dir);
 
+    // Settings read on use by handlers and engines, updated when config.toml changes
+    let shared_config = config::shared(config.clone());
+
     // Connect to database
     let db = db::establish_connection(&config.database.url).await?;
     info!("Database connection established");
 
     // Run migrations
-    migration::Migrator::up(&db, None).await?;
+    db::migrate::run_migrations(&db).await?;
     info!("✅ Database migrations completed");
 
     // Initialize repository
@@ -79,8 +91,46 @@ async fn main() -> Result<()> {
     repo.ping().await?;
     info!("✅ Database ping successful");
 
+    // Seed tag aliases from the dataset file and load them for tag filters
+    if let Some(path) = &config.content.tag_alias_file {
+        match repo.import_tag_alias_file(std::path::Path::new(path)).await {
+            Ok(added) => info!("Imported {} new tag aliases from {}", added, path),
+            Err(e) => warn!("Failed to import tag aliases: {:#}", e),
+        }
+    }
+    let alias_count = repo.reload_tag_aliases().await?;
+    info!("✅ Loaded {} tag aliases", alias_count);
+
+    // Whitelisted chats skip the one-by-one /enablechat of private mode
+    if !config.telegram.bot_mode.is_public() && !config.telegram.chat_whitelist.is_empty() {
+        match repo
+            .set_chats_enabled(&config.telegram.chat_whitelist, true)
+            .await
+        {
+            Ok(count) => info!("✅ Enabled {} whitelisted chats", count),
+            Err(e) => warn!("Failed to enable whitelisted chats: {:#}", e),
+        }
+    }
+
+    // Repair subscription states written by older versions before any engine reads them
+    match repo.normalize_subscription_states().await {
+        Ok(report) if report.total() > 0 => warn!(
+            "Normalized {} subscription states ({} unreadable reset, {} mismatched reset, {} stale pending removed)",
+            report.total(),
+            report.unreadable,
+            report.mismatched,
+            report.stale_pending
+        ),
+        Ok(_) => info!("✅ Subscription states validated"),
+        Err(e) => warn!("Failed to normalize subscription states: {:#}", e),
+    }
+
     // Initialize Pixiv Client
-    let mut pixiv_client = pixiv::client::PixivClient::new(config.pixiv.clone())?;
+    if config.network.pixiv().is_some() {
+        info!("Using proxy for Pixiv requests");
+    }
+    let mut pixiv_client =
+        pixiv::client::PixivClient::new(config.pixiv.clone(), config.network.pixiv())?;
     pixiv_client.login().await?;
     let pixiv_client = std::sync::Arc::new(tokio::sync::RwLock::new(pixiv_client));
     info!("✅ Pixiv client initialized");
@@ -88,94 +138,262 @@ async fn main() -> Result<()> {
     // Initialize cache manager (starts background cleanup task)
     let cache_dir = &config.scheduler.cache_dir;
     let cache_retention_days = config.scheduler.cache_retention_days;
-    let cache_manager = cache::FileCacheManager::new(cache_dir, cache_retention_days);
+    let mut cache_manager = cache::FileCacheManager::new(cache_dir, cache_retention_days);
     info!(
         "✅ Cache manager initialized (retention: {} days)",
         cache_retention_days
     );
+    let cache_max_mb = config.scheduler.cache_max_mb;
+    if cache_max_mb > 0 {
+        cache_manager = cache_manager.with_size_limit(cache_max_mb * 1024 * 1024);
+        info!("✅ Cache size limit enabled ({} MB)", cache_max_mb);
+    }
+    let local_cleanup = &config.telegram.local_file_cleanup;
+    if local_cleanup.enabled {
+        if config.telegram.api_url.is_none() {
+            warn!("local_file_cleanup is enabled but no custom Telegram API URL is configured");
+        }
+        cache_manager = cache_manager.with_delivered_cleanup(std::time::Duration::from_secs(
+            local_cleanup.max_age_hours * 3600,
+        ));
+        info!(
+            "✅ Delivered file cleanup enabled (max age: {}h)",
+            local_cleanup.max_age_hours
+        );
+    }
 
     // Initialize Downloader (use reqwest client)
-    let http_client = reqwest::Client::builder()
+    let mut http_client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36")
-        .build()?;
-    let downloader = std::sync::Arc::new(pixiv::downloader::Downloader::new(
-        http_client,
-        cache_manager,
-    ));
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36");
+    if let Some(proxy) = config.network.pixiv() {
+        http_client = http_client.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    let http_client = http_client.build()?;
+    let downloader = std::sync::Arc::new(
+        pixiv::downloader::Downloader::new(http_client, cache_manager)
+            .with_config(config.download.clone()),
+    );
     info!("✅ Downloader initialized");
 
     info!("PixivBot initialization complete");
 
-    // Initialize Telegram Bot with automatic rate limiting
-    let mut bot = teloxide::Bot::new(config.telegram.bot_token.clone());
-
-    // Set custom API URL if configured
-    if let Some(api_url) = &config.telegram.api_url {
-        match url::Url::parse(api_url) {
+    // Parse custom API URL if configured
+    let api_url = match &config.telegram.api_url {
+        Some(api_url) => match url::Url::parse(api_url) {
             Ok(parsed_url) => {
                 info!("Using custom Telegram API URL: {}", api_url);
-                bot = bot.set_api_url(parsed_url);
+                Some(parsed_url)
             }
             Err(e) => {
                 error!("Failed to parse custom API URL '{}': {:#}", api_url, e);
                 return Err(anyhow::anyhow!("Invalid Telegram API URL in configuration"));
             }
+        },
+        None => None,
+    };
+
+    // All bots share one HTTP client; without a configured proxy teloxide's
+    // default client still honours TELOXIDE_PROXY
+    let telegram_client = match config.network.telegram() {
+        Some(proxy) => {
+            info!("Using proxy for Telegram requests");
+            teloxide::net::default_reqwest_settings()
+                .proxy(reqwest::Proxy::all(proxy)?)
+                .build()
+                .context("Failed to build Telegram HTTP client")?
+        }
+        None => teloxide::net::client_from_env(),
+    };
+
+    // Initialize Telegram Bot with automatic rate limiting
+    // The Throttle adaptor replaces manual sleep() calls throughout the codebase
+    let build_bot = |token: &str| {
+        let mut bot = teloxide::Bot::with_client(token, telegram_client.clone());
+        if let Some(url) = &api_url {
+            bot = bot.set_api_url(url.clone());
+        }
+        bot.throttle(teloxide::adaptors::throttle::Limits::default())
+    };
+    let bot = build_bot(&config.telegram.bot_token);
+    let mut extra_bots = Vec::new();
+    for (i, token) in config.telegram.extra_bot_tokens.iter().enumerate() {
+        let extra_bot = build_bot(token);
+        if bot::notifier::bot_id(&extra_bot).is_none() {
+            return Err(anyhow::anyhow!(
+                "Invalid token in telegram.extra_bot_tokens[{}]",
+                i
+            ));
         }
+        extra_bots.push(extra_bot);
     }
+    info!(
+        "✅ Telegram bot initialized with automatic rate limiting ({} extra bots)",
+        extra_bots.len()
+    );
 
-    // Wrap bot with Throttle adaptor for automatic rate limiting
-    // This replaces manual sleep() calls throughout the codebase
-    let bot = bot.throttle(teloxide::adaptors::throttle::Limits::default());
-    info!("✅ Telegram bot initialized with automatic rate limiting");
+    let telegraph_client = if let Some(token) = config.ehentai.telegraph_access_token.as_ref() {
+        Some(std::sync::Arc::new(eh_client::TelegraphClient::new(
+            token.clone(),
+        )))
+    } else if config.ehentai.upload_telegraph || config.content.caption_telegraph {
+        match eh_client::TelegraphClient::create_account("PixivBot", Some("PixivBot"), None).await {
+            Ok(client) => {
+                info!("✅ Telegraph account auto-created for this process");
+                Some(std::sync::Arc::new(client))
+            }
+            Err(e) => {
+                warn!(
+                    "Telegraph account auto-creation failed; \
+                     Telegraph upload and caption pages are disabled: {:#}",
+                    e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     // Initialize Notifier
-    let notifier = bot::notifier::Notifier::new(bot.clone(), downloader.clone());
+    let mut notifier = bot::notifier::Notifier::new(bot, downloader.clone())
+        .with_extra_bots(extra_bots)
+        .with_custom_emoji(config.content.custom_emoji.clone())
+        .with_caption_limit(config.content.caption_max_length)
+        .with_upload_limit(config.telegram.upload_limit_bytes())
+        .with_repo(repo.clone());
+    if config.content.caption_telegraph {
+        match telegraph_client.as_ref() {
+            Some(telegraph) => notifier = notifier.with_caption_telegraph(telegraph.clone()),
+            None => {
+                warn!("content.caption_telegraph is enabled but no Telegraph account is available")
+            }
+        }
+    }
+    // Caption hooks run on subscription and ranking captions; register custom ones here
+    if !config.content.auto_hashtags.is_empty() {
+        notifier = notifier.with_caption_hook(bot::notifier::AutoHashtagHook::new(
+            &config.content.auto_hashtags,
+        ));
+    }
+    match repo.list_chat_bot_ids().await {
+        Ok(assignments) => {
+            for (chat_id, bot_id) in assignments {
+                notifier
+                    .bots()
+                    .assign(teloxide::types::ChatId(chat_id), bot_id);
+            }
+        }
+        Err(e) => warn!("Failed to load chat bot assignments: {:#}", e),
+    }
+    match repo.list_chat_slow_mode_delays().await {
+        Ok(delays) => {
+            for (chat_id, delay_secs) in delays {
+                notifier.set_slow_mode(
+                    teloxide::types::ChatId(chat_id),
+                    u32::try_from(delay_secs).unwrap_or(0),
+                );
+            }
+        }
+        Err(e) => warn!("Failed to load chat slow mode delays: {:#}", e),
+    }
 
     // Initialize author engine
     let scheduler_config = config.scheduler.clone();
-    let image_size = config.content.image_size.to_pixiv_image_size();
-    let author_engine = scheduler::AuthorEngine::new(
+    let image_sizes = config.content.image_size_policy();
+    let subscription_delay = scheduler::SubscriptionDelay::new(
+        scheduler_config.subscription_delay_ms,
+        scheduler_config.subscription_delay_max_ms,
+    );
+    let author_engine = std::sync::Arc::new(scheduler::AuthorEngine::new(
         repo.clone(),
         pixiv_client.clone(),
         notifier.clone(),
+        shared_config.clone(),
         scheduler_config.tick_interval_sec,
-        scheduler_config.min_task_interval_sec,
-        scheduler_config.max_task_interval_sec,
         scheduler_config.max_retry_count,
-        image_size,
+        image_sizes,
+        config.content.keep_continuation_spoiler,
+        scheduler_config.concurrent_tasks,
+        scheduler_config.dead_author_threshold,
+        subscription_delay,
+        scheduler_config.recover_pending_on_startup,
+    ));
+    let author_engine_for_bot = author_engine.clone();
+
+    // Initialize retry engine, draining the push queue filled by the author engine
+    let retry_engine = scheduler::RetryEngine::new(
+        repo.clone(),
+        pixiv_client.clone(),
+        notifier.clone(),
+        scheduler_config.tick_interval_sec,
+        scheduler_config.max_retry_count,
+        image_sizes,
+        config.content.keep_continuation_spoiler,
     );
 
     // Initialize ranking engine
-    let ranking_engine = scheduler::RankingEngine::new(
+    let ranking_engine = std::sync::Arc::new(scheduler::RankingEngine::new(
         repo.clone(),
         pixiv_client.clone(),
         notifier.clone(),
         scheduler_config.ranking_execution_time.clone(),
-        image_size,
+        image_sizes,
+        scheduler_config.retry.ranking,
+        scheduler_config.ranking_medals,
+        subscription_delay,
+    ));
+    let ranking_engine_for_bot = ranking_engine.clone();
+
+    // Initialize bookmark engine
+    let bookmark_engine = scheduler::BookmarkEngine::new(
+        repo.clone(),
+        pixiv_client.clone(),
+        notifier.clone(),
+        shared_config.clone(),
+        scheduler_config.tick_interval_sec,
+        scheduler_config.max_retry_count,
+        image_sizes,
+        subscription_delay,
     );
 
     // Initialize name update engine
-    let name_update_engine = scheduler::NameUpdateEngine::new(
+    let name_update_engine = std::sync::Arc::new(scheduler::NameUpdateEngine::new(
         repo.clone(),
         pixiv_client.clone(),
         scheduler_config.author_name_update_time.clone(),
-    );
+    ));
+    let name_update_engine_for_bot = name_update_engine.clone();
 
-    info!("✅ Author, Ranking, and Name Update engines initialized");
+    info!("✅ Author, Retry, Ranking, Bookmark, and Name Update engines initialized");
 
     // Spawn all engines in background
-    let author_engine_handle = tokio::spawn(async move {
+    let pixiv_token_refresher_handle =
+        tokio::spawn(pixiv::client::run_token_refresher(pixiv_client.clone()));
+
+    // Engine requests go through the background lane, leaving a slot for user commands
+    let author_engine_handle = tokio::spawn(pixiv::lanes::background(async move {
         author_engine.run().await;
-    });
+    }));
+
+    let retry_engine_handle = tokio::spawn(pixiv::lanes::background(async move {
+        retry_engine.run().await;
+    }));
 
-    let ranking_engine_handle = tokio::spawn(async move {
+    let ranking_engine_handle = tokio::spawn(pixiv::lanes::background(async move {
         ranking_engine.run().await;
-    });
+    }));
+
+    let bookmark_engine_handle = tokio::spawn(pixiv::lanes::background(async move {
+        bookmark_engine.run().await;
+    }));
 
-    let name_update_engine_handle = tokio::spawn(async move {
+    let name_update_engine_handle = tokio::spawn(pixiv::lanes::background(async move {
         name_update_engine.run().await;
-    });
+    }));
+
+    let config_watcher = config::ConfigWatcher::new(shared_config.clone(), log_reload_handle);
+    let config_watcher_handle = tokio::spawn(config_watcher.run());
 
     let booru_registry = booru::BooruSiteRegistry::from_configs(&config.booru.sites);
 
@@ -184,7 +402,7 @@ async fn main() -> Result<()> {
             repo.clone(),
             notifier.clone(),
             scheduler_config.tick_interval_sec,
-            scheduler_config.max_retry_count,
+            scheduler_config.booru_retry_count(),
             booru_registry.clone(),
             std::sync::Arc::new(config.booru.clone()),
         );
@@ -218,8 +436,15 @@ async fn main() -> Result<()> {
             let api_url = "https://api.e-hentai.org/api.php";
             let cookies = config.ehentai.to_cookies();
 
-            match eh_client::EhClient::new(base_url, api_url, cookies) {
+            let proxy = config.network.ehentai();
+            if proxy.is_some() {
+                info!("Using proxy for E-Hentai requests");
+            }
+
+            match eh_client::EhClient::new(base_url, api_url, cookies, proxy) {
                 Ok(client) => {
+                    let client =
+                        client.with_requests_per_minute(config.ehentai.requests_per_minute);
                     info!(
                         "✅ E-Hentai client initialized (site: {})",
                         config.ehentai.site
@@ -237,28 +462,6 @@ async fn main() -> Result<()> {
         None
     };
 
-    let telegraph_client = if let Some(token) = config.ehentai.telegraph_access_token.as_ref() {
-        Some(std::sync::Arc::new(eh_client::TelegraphClient::new(
-            token.clone(),
-        )))
-    } else if config.ehentai.upload_telegraph {
-        match eh_client::TelegraphClient::create_account("PixivBot", Some("PixivBot"), None).await {
-            Ok(client) => {
-                info!("✅ Telegraph account auto-created for this process");
-                Some(std::sync::Arc::new(client))
-            }
-            Err(e) => {
-                warn!(
-                    "ehentai.upload_telegraph=true but Telegraph account auto-creation failed; \
-                     Telegraph upload is disabled: {:#}",
-                    e
-                );
-                None
-            }
-        }
-    } else {
-        None
-    };
     let eh_telegraph_rewrite_config = config.image_upload.ipfs3_preview_rewrite_config();
     let eh_telegraph_rewrite_enabled =
         telegraph_client.is_some() && eh_telegraph_rewrite_config.is_some();
@@ -317,14 +520,39 @@ async fn main() -> Result<()> {
         }
     }
 
+    let eh_tag_translations =
+        if eh_client.is_some() && config.ehentai.tag_translation_refresh_hours > 0 {
+            match eh_client::TagTranslationDb::new(&config.ehentai.tag_translation_url) {
+                Ok(db) => Some(db),
+                Err(e) => {
+                    warn!("Failed to set up EH tag translations: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+    let eh_tag_translation_handle = eh_tag_translations.clone().map(|db| {
+        let interval =
+            std::time::Duration::from_secs(config.ehentai.tag_translation_refresh_hours * 3600);
+        tokio::spawn(async move {
+            db.run_refresh(interval).await;
+        })
+    });
+
     let eh_engine_handle = if let Some(ref eh_client) = eh_client {
-        let eh_engine = scheduler::EhEngine::new(
+        let mut eh_engine = scheduler::EhEngine::new(
             repo.clone(),
             std::sync::Arc::clone(eh_client),
+            notifier.clone(),
             std::sync::Arc::new(config.ehentai.clone()),
             telegraph_client.is_some(),
             scheduler_config.tick_interval_sec,
         );
+        if let Some(db) = eh_tag_translations {
+            eh_engine = eh_engine.with_tag_translations(db);
+        }
         info!("✅ E-Hentai engine initialized");
         Some(tokio::spawn(async move {
             eh_engine.run().await;
@@ -428,6 +656,95 @@ async fn main() -> Result<()> {
         None
     };
 
+    let latest_release = scheduler::LatestRelease::default();
+    let update_checker_handle = if config.update_check.enabled {
+        match scheduler::UpdateChecker::new(
+            notifier.clone(),
+            config.telegram.owner_id,
+            config.update_check.repo.clone(),
+            config.update_check.interval_hours,
+            latest_release.clone(),
+        ) {
+            Ok(checker) => Some(tokio::spawn(async move {
+                checker.run().await;
+            })),
+            Err(e) => {
+                warn!("Failed to start update checker: {:#}", e);
+                None
+            }
+        }
+    } else {
+        info!("Update check disabled");
+        None
+    };
+
+    if config.self_test.enabled {
+        let self_test = scheduler::SelfTest::new(
+            pixiv_client.clone(),
+            notifier.clone(),
+            config.telegram.owner_id,
+            config.self_test.illust_id,
+            image_sizes.push,
+        );
+        tokio::spawn(pixiv::lanes::background(self_test.run()));
+    }
+
+    let subscription_digest_handle = if config.subscription_digest.enabled {
+        match scheduler::SubscriptionDigestEngine::new(
+            repo.clone(),
+            notifier.clone(),
+            &config.subscription_digest.weekday,
+            &config.subscription_digest.time,
+        ) {
+            Ok(engine) => Some(tokio::spawn(async move {
+                engine.run().await;
+            })),
+            Err(e) => {
+                warn!("Failed to start subscription digest: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let subscription_backup_handle = if config.subscription_backup.enabled {
+        match scheduler::SubscriptionBackupEngine::new(
+            repo.clone(),
+            notifier.clone(),
+            config.telegram.owner_id,
+            &config.subscription_backup,
+        ) {
+            Ok(engine) => Some(tokio::spawn(async move {
+                engine.run().await;
+            })),
+            Err(e) => {
+                warn!("Failed to start subscription backup: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let http_api_token = config.http.api_token.clone().filter(|t| !t.is_empty());
+    let http_handle = match http_api_token {
+        Some(api_token) if config.http.enabled => {
+            let http_config = config.http.clone();
+            let repo = repo.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = http::serve(http_config, api_token, repo).await {
+                    error!("HTTP server error: {:#}", e);
+                }
+            }))
+        }
+        None if config.http.enabled => {
+            warn!("HTTP server not started: http.api_token is required");
+            None
+        }
+        _ => None,
+    };
+
     info!("🤖 Starting Telegram Bot...");
 
     // Setup Ctrl+C handler
@@ -441,29 +758,58 @@ async fn main() -> Result<()> {
     });
 
     // Start Bot in a separate task (non-blocking)
-    let sensitive_tags_for_bot = config.content.sensitive_tags.clone();
-    let image_size_for_bot = config.content.image_size.to_pixiv_image_size();
-    let download_threshold_for_bot = config.content.download_threshold();
     let cache_dir_for_bot = config.scheduler.cache_dir.clone();
     let log_dir_for_bot = config.logging.dir.clone();
     let booru_registry_for_bot = booru_registry.clone();
     let eh_client_for_bot = eh_client.clone();
     let has_telegraph_for_bot = telegraph_client.is_some();
+    let latest_release_for_bot = latest_release.clone();
+    let archive_upload = utils::archive_upload::ArchiveUpload::from_config(&config.archive_upload)?;
+    if let Some(ref upload) = archive_upload {
+        info!(
+            "✅ Archive upload enabled (links valid for {}h)",
+            upload.link_expiry_hours()
+        );
+    }
+    let zip_workspace = std::sync::Arc::new(utils::zip_workspace::ZipWorkspace::new(
+        &config.content.zip_work_dir,
+        config.content.zip_work_quota_mb,
+    )?);
+    let tag_alias_file = config.content.tag_alias_file.clone();
+    let saucenao = saucenao::SauceNaoClient::from_config(
+        &config.saucenao,
+        config
+            .network
+            .proxy
+            .as_deref()
+            .filter(|proxy| !proxy.is_empty()),
+    )?
+    .map(std::sync::Arc::new);
+    if saucenao.is_some() {
+        info!("✅ SauceNAO lookup enabled");
+    }
     let bot_handle = tokio::spawn(async move {
         if let Err(e) = bot::run(
-            bot,
             config.telegram,
             repo.clone(),
             pixiv_client.clone(),
             notifier.clone(),
-            sensitive_tags_for_bot,
-            image_size_for_bot,
-            download_threshold_for_bot,
+            shared_config,
+            image_sizes,
             cache_dir_for_bot,
             log_dir_for_bot,
             booru_registry_for_bot,
             eh_client_for_bot,
             has_telegraph_for_bot,
+            author_engine_for_bot,
+            ranking_engine_for_bot,
+            name_update_engine_for_bot,
+            latest_release_for_bot,
+            log_buffer,
+            archive_upload,
+            tag_alias_file,
+            saucenao,
+            zip_workspace,
         )
         .await
         {
@@ -478,11 +824,30 @@ async fn main() -> Result<()> {
     // Abort tasks
     bot_handle.abort();
     author_engine_handle.abort();
+    retry_engine_handle.abort();
+    pixiv_token_refresher_handle.abort();
     ranking_engine_handle.abort();
+    bookmark_engine_handle.abort();
     name_update_engine_handle.abort();
+    config_watcher_handle.abort();
     if let Some(handle) = booru_engine_handle {
         handle.abort();
     }
+    if let Some(handle) = update_checker_handle {
+        handle.abort();
+    }
+    if let Some(handle) = subscription_digest_handle {
+        handle.abort();
+    }
+    if let Some(handle) = subscription_backup_handle {
+        handle.abort();
+    }
+    if let Some(handle) = http_handle {
+        handle.abort();
+    }
+    if let Some(handle) = eh_tag_translation_handle {
+        handle.abort();
+    }
     if let Some(handle) = eh_engine_handle {
         handle.abort();
     }
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How long an account that got a rate-limit or ban response is left out of rotation
+const ACCOUNT_BENCH_DURATION: Duration = Duration::from_secs(15 * 60);
+
+struct Account {
+    client: pixiv_client::PixivClient,
+    /// Whether the last token refresh of this account succeeded
+    authenticated: AtomicBool,
+    benched_until: Mutex<Option<Instant>>,
+}
+
+impl Account {
+    fn benched_until(&self, now: Instant) -> Option<Instant> {
+        let benched_until = *self.benched_until.lock().unwrap_or_else(|e| e.into_inner());
+        benched_until.filter(|until| *until > now)
+    }
+}
+
+/// Pixiv accounts used in turn for API requests.
+///
+/// Accounts whose token can't be refreshed, or that recently answered with a
+/// rate-limit or ban response, are skipped while another account is usable.
+pub struct AccountPool {
+    accounts: Vec<Account>,
+    next: AtomicUsize,
+}
+
+impl AccountPool {
+    pub fn new(refresh_tokens: Vec<String>, proxy: Option<&str>) -> Result<Self> {
+        let accounts = refresh_tokens
+            .into_iter()
+            .map(|token| {
+                Ok(Account {
+                    client: pixiv_client::PixivClient::new(token, proxy)?,
+                    authenticated: AtomicBool::new(false),
+                    benched_until: Mutex::new(None),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        anyhow::ensure!(!accounts.is_empty(), "No Pixiv refresh token configured");
+
+        Ok(Self {
+            accounts,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Account to send the next request with, as `(index, client)`
+    pub fn next(&self) -> (usize, &pixiv_client::PixivClient) {
+        let index = self.pick(Instant::now());
+        (index, &self.accounts[index].client)
+    }
+
+    fn pick(&self, now: Instant) -> usize {
+        let count = self.accounts.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % count;
+        let rotation = || (0..count).map(|offset| (start + offset) % count);
+
+        rotation()
+            .find(|&i| {
+                let account = &self.accounts[i];
+                account.authenticated.load(Ordering::Relaxed)
+                    && account.benched_until(now).is_none()
+            })
+            .or_else(|| rotation().find(|&i| self.accounts[i].benched_until(now).is_none()))
+            // Everything is benched: use the account that comes back first
+            .unwrap_or_else(|| {
+                rotation()
+                    .min_by_key(|&i| self.accounts[i].benched_until(now))
+                    .unwrap_or(start)
+            })
+    }
+
+    /// Bench the account that sent a request if the response says it is limited or banned
+    pub fn observe<T>(&self, index: usize, result: &pixiv_client::Result<T>) {
+        let Err(e) = result else {
+            return;
+        };
+        if !is_account_limited(e) {
+            return;
+        }
+
+        *self.accounts[index]
+            .benched_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + ACCOUNT_BENCH_DURATION);
+        if self.accounts.len() > 1 {
+            warn!(
+                "Pixiv account #{} benched for {} minutes: {}",
+                index + 1,
+                ACCOUNT_BENCH_DURATION.as_secs() / 60,
+                e
+            );
+        }
+    }
+
+    /// Refresh every account's token; succeeds when at least one account is usable
+    pub async fn refresh_all(&self) -> Result<()> {
+        let mut last_error = None;
+        for (index, account) in self.accounts.iter().enumerate() {
+            match account.client.login().await {
+                Ok(()) => account.authenticated.store(true, Ordering::Relaxed),
+                Err(e) => {
+                    account.authenticated.store(false, Ordering::Relaxed);
+                    if self.accounts.len() > 1 {
+                        warn!("Failed to refresh Pixiv account #{}: {}", index + 1, e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let authenticated = self.authenticated_count();
+        match last_error {
+            Some(e) if authenticated == 0 => Err(e.into()),
+            _ => {
+                if self.accounts.len() > 1 {
+                    info!(
+                        "Pixiv tokens refreshed for {}/{} accounts",
+                        authenticated,
+                        self.accounts.len()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn authenticated_count(&self) -> usize {
+        self.accounts
+            .iter()
+            .filter(|account| account.authenticated.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Time until the first account token expires, `None` before any login
+    pub async fn token_expires_in(&self) -> Option<Duration> {
+        let mut soonest: Option<Duration> = None;
+        for account in &self.accounts {
+            if let Some(left) = account.client.token_expires_in().await {
+                soonest = Some(soonest.map_or(left, |soonest| soonest.min(left)));
+            }
+        }
+        soonest
+    }
+}
+
+/// Whether an error means the account itself is throttled or banned, not the request
+fn is_account_limited(err: &pixiv_client::Error) -> bool {
+    match err {
+        pixiv_client::Error::Api { status: 429, .. } => true,
+        pixiv_client::Error::Api {
+            status: 403,
+            message,
+        } => message.to_ascii_lowercase().contains("rate limit"),
+        // The token could not be refreshed mid-request
+        pixiv_client::Error::Auth(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_pool(count: usize) -> AccountPool {
+        let pool =
+            AccountPool::new((0..count).map(|i| format!("token-{i}")).collect(), None).unwrap();
+        for account in &pool.accounts {
+            account.authenticated.store(true, Ordering::Relaxed);
+        }
+        pool
+    }
+
+    #[test]
+    fn pick_rotates_and_skips_benched_accounts() {
+        let pool = make_pool(3);
+        let now = Instant::now();
+        assert_eq!(
+            (0..4).map(|_| pool.pick(now)).collect::<Vec<_>>(),
+            [0, 1, 2, 0]
+        );
+
+        pool.observe::<()>(
+            2,
+            &Err(pixiv_client::Error::Api {
+                status: 403,
+                message: r#"{"error":{"message":"Rate Limit"}}"#.to_string(),
+            }),
+        );
+        assert_eq!(
+            (0..3).map(|_| pool.pick(now)).collect::<Vec<_>>(),
+            [1, 0, 0]
+        );
+
+        // Hidden works also answer 403, that is not the account's fault
+        pool.observe::<()>(
+            1,
+            &Err(pixiv_client::Error::Api {
+                status: 403,
+                message: "forbidden".to_string(),
+            }),
+        );
+        assert_eq!(pool.pick(now), 1);
+    }
+
+    #[test]
+    fn pick_falls_back_to_soonest_available_account() {
+        let pool = make_pool(2);
+        let now = Instant::now();
+        *pool.accounts[0].benched_until.lock().unwrap() = Some(now + Duration::from_secs(60));
+        *pool.accounts[1].benched_until.lock().unwrap() = Some(now + Duration::from_secs(30));
+
+        assert_eq!(pool.pick(now), 1);
+        assert_eq!(pool.pick(now), 1);
+    }
+}
@@ -0,0 +1,94 @@
+use pixiv_client::UserDetail;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How long author image URLs are reused before user detail is fetched again
+const AUTHOR_IMAGES_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Avatar and profile banner URLs of a Pixiv author
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthorImages {
+    pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
+}
+
+impl AuthorImages {
+    pub fn from_detail(detail: &UserDetail) -> Self {
+        Self {
+            avatar_url: detail
+                .user
+                .profile_image_urls
+                .as_ref()
+                .map(|urls| urls.medium.clone())
+                .filter(|url| !url.is_empty()),
+            banner_url: detail
+                .profile
+                .as_ref()
+                .and_then(|profile| profile.background_image_url.clone())
+                .filter(|url| !url.is_empty()),
+        }
+    }
+
+    /// Banner when the author has one, otherwise the avatar
+    pub fn preferred_url(&self) -> Option<&str> {
+        self.banner_url.as_deref().or(self.avatar_url.as_deref())
+    }
+}
+
+/// In-memory cache of author image URLs keyed by author ID
+///
+/// The images themselves are cached on disk by the downloader, so a hit here
+/// renders an author card without calling the Pixiv API at all.
+#[derive(Clone, Default)]
+pub struct AuthorImageCache {
+    entries: Arc<Mutex<HashMap<u64, (AuthorImages, Instant)>>>,
+}
+
+impl AuthorImageCache {
+    pub async fn get(&self, author_id: u64) -> Option<AuthorImages> {
+        let entries = self.entries.lock().await;
+        entries
+            .get(&author_id)
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < AUTHOR_IMAGES_TTL)
+            .map(|(images, _)| images.clone())
+    }
+
+    pub async fn insert(&self, author_id: u64, images: AuthorImages) {
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, (_, fetched_at)| fetched_at.elapsed() < AUTHOR_IMAGES_TTL);
+        entries.insert(author_id, (images, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn author_images_prefer_banner_over_avatar() {
+        let detail: UserDetail = serde_json::from_value(serde_json::json!({
+            "user": {
+                "id": 1,
+                "name": "Artist",
+                "account": "artist",
+                "profile_image_urls": { "medium": "https://i.pximg.net/avatar.jpg" }
+            },
+            "profile": { "background_image_url": null }
+        }))
+        .unwrap();
+
+        let mut images = AuthorImages::from_detail(&detail);
+        assert_eq!(
+            images.preferred_url(),
+            Some("https://i.pximg.net/avatar.jpg")
+        );
+
+        images.banner_url = Some("https://i.pximg.net/banner.jpg".to_string());
+        assert_eq!(
+            images.preferred_url(),
+            Some("https://i.pximg.net/banner.jpg")
+        );
+    }
+}
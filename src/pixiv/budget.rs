@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Sliding window the budget is counted over
+const BUDGET_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Soft hourly budget of Pixiv API calls shared by subscriptions and link previews.
+///
+/// Subscription polling is never refused, it only counts towards the limit. Previews
+/// must reserve their calls up front and are turned away once they used their share
+/// or once the whole budget is spent, so heavy preview usage can't starve pushes.
+#[derive(Debug)]
+pub struct ApiBudget {
+    /// 0 disables accounting
+    hourly_limit: usize,
+    preview_limit: usize,
+    usage: Mutex<BudgetUsage>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetUsage {
+    /// Every API call, whatever the source
+    calls: VecDeque<Instant>,
+    /// Calls reserved by previews
+    preview_calls: VecDeque<Instant>,
+}
+
+impl BudgetUsage {
+    fn prune(&mut self, now: Instant) {
+        for window in [&mut self.calls, &mut self.preview_calls] {
+            while window
+                .front()
+                .is_some_and(|at| now.saturating_duration_since(*at) >= BUDGET_WINDOW)
+            {
+                window.pop_front();
+            }
+        }
+    }
+}
+
+impl ApiBudget {
+    pub fn new(hourly_limit: u32, preview_share_percent: u8) -> Self {
+        let hourly_limit = hourly_limit as usize;
+        Self {
+            hourly_limit,
+            preview_limit: hourly_limit * usize::from(preview_share_percent.min(100)) / 100,
+            usage: Mutex::new(BudgetUsage::default()),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.hourly_limit > 0
+    }
+
+    /// Count one API call against the budget
+    pub fn record_call(&self) {
+        self.record_call_at(Instant::now());
+    }
+
+    /// Reserve `calls` API calls for a preview, false when previews should try later
+    pub fn try_reserve_preview(&self, calls: usize) -> bool {
+        self.try_reserve_preview_at(Instant::now(), calls)
+    }
+
+    fn record_call_at(&self, now: Instant) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.prune(now);
+        usage.calls.push_back(now);
+    }
+
+    fn try_reserve_preview_at(&self, now: Instant, calls: usize) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.prune(now);
+        if usage.preview_calls.len() + calls > self.preview_limit
+            || usage.calls.len() + calls > self.hourly_limit
+        {
+            return false;
+        }
+        usage.preview_calls.extend(std::iter::repeat_n(now, calls));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_are_limited_to_their_share() {
+        let budget = ApiBudget::new(10, 30);
+        let now = Instant::now();
+
+        assert!(budget.try_reserve_preview_at(now, 2));
+        assert!(budget.try_reserve_preview_at(now, 1));
+        assert!(!budget.try_reserve_preview_at(now, 1));
+
+        let later = now + BUDGET_WINDOW;
+        assert!(budget.try_reserve_preview_at(later, 3));
+    }
+
+    #[test]
+    fn subscription_calls_squeeze_out_previews() {
+        let budget = ApiBudget::new(10, 50);
+        let now = Instant::now();
+
+        for _ in 0..9 {
+            budget.record_call_at(now);
+        }
+        assert!(budget.try_reserve_preview_at(now, 1));
+        budget.record_call_at(now);
+        assert!(!budget.try_reserve_preview_at(now, 1));
+    }
+
+    #[test]
+    fn zero_limit_disables_the_budget() {
+        let budget = ApiBudget::new(0, 0);
+        let now = Instant::now();
+
+        budget.record_call_at(now);
+        assert!(budget.try_reserve_preview_at(now, 100));
+    }
+}
@@ -1,38 +1,170 @@
+use super::accounts::AccountPool;
+use super::budget::ApiBudget;
+use super::lanes::{self, RequestLanes};
 use crate::config::PixivConfig;
 use anyhow::Result;
 use pixiv_client::{self, Illust};
-use tracing::info;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info};
+
+/// Refresh the access token this long before it expires
+const TOKEN_REFRESH_AHEAD: Duration = Duration::from_secs(300);
+
+/// First retry delay after a failed refresh; doubles up to `TOKEN_REFRESH_MAX_RETRY_DELAY`
+const TOKEN_REFRESH_MIN_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const TOKEN_REFRESH_MAX_RETRY_DELAY: Duration = Duration::from_secs(1800);
 
 pub struct PixivClient {
-    client: pixiv_client::PixivClient,
+    /// Accounts requests rotate over
+    accounts: AccountPool,
+    /// Cleared while no token can be refreshed; engines pause polling until it is set again
+    authenticated: Arc<AtomicBool>,
+    /// Soft API quota shared between subscription polling and link previews
+    budget: ApiBudget,
+    /// Keeps a request slot free for users while the engines poll
+    lanes: RequestLanes,
+}
+
+/// Whether an API error means the requested user/illust does not exist (HTTP 404)
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<pixiv_client::Error>(),
+        Some(pixiv_client::Error::Api { status: 404, .. })
+    )
+}
+
+/// Whether an API error means the user is gone or hidden from us (HTTP 404 or 403)
+pub fn is_gone(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<pixiv_client::Error>(),
+        Some(pixiv_client::Error::Api {
+            status: 403 | 404,
+            ..
+        })
+    )
 }
 
 impl PixivClient {
-    pub fn new(config: PixivConfig) -> Result<Self> {
-        let client = pixiv_client::PixivClient::new(config.refresh_token)?;
+    pub fn new(config: PixivConfig, proxy: Option<&str>) -> Result<Self> {
+        let refresh_tokens = std::iter::once(config.refresh_token)
+            .chain(config.extra_refresh_tokens)
+            .filter(|token| !token.is_empty())
+            .collect();
+        let accounts = AccountPool::new(refresh_tokens, proxy)?;
+        let budget = ApiBudget::new(
+            config.quota.hourly_limit,
+            config.quota.preview_share_percent,
+        );
+        let lanes = RequestLanes::new(config.max_concurrent_requests);
 
-        Ok(Self { client })
+        Ok(Self {
+            accounts,
+            authenticated: Arc::new(AtomicBool::new(false)),
+            budget,
+            lanes,
+        })
     }
 
     /// Login using refresh token
     pub async fn login(&mut self) -> Result<()> {
-        self.client.login().await?;
+        self.refresh_token().await?;
 
-        info!("✅ Pixiv authentication successful");
+        info!(
+            "✅ Pixiv authentication successful ({}/{} accounts)",
+            self.accounts.authenticated_count(),
+            self.accounts.account_count()
+        );
         Ok(())
     }
 
+    /// Exchange the refresh tokens for new access tokens and update the health flag
+    pub async fn refresh_token(&self) -> Result<()> {
+        let result = self.accounts.refresh_all().await;
+        self.authenticated.store(result.is_ok(), Ordering::Relaxed);
+        result
+    }
+
+    /// Whether the last token refresh succeeded
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::Relaxed)
+    }
+
+    /// Reserve API calls for a link preview; false means the preview should be retried later
+    pub fn try_reserve_preview(&self, calls: usize) -> bool {
+        self.budget.try_reserve_preview(calls)
+    }
+
+    /// Send one request with the next account of the pool, in the caller's lane
+    async fn call<'a, T, Fut>(
+        &'a self,
+        request: impl FnOnce(&'a pixiv_client::PixivClient) -> Fut,
+    ) -> Result<T>
+    where
+        Fut: Future<Output = pixiv_client::Result<T>>,
+    {
+        let _permit = self.lanes.acquire(lanes::current_priority()).await;
+        let (index, client) = self.accounts.next();
+        let result = request(client).await;
+        self.accounts.observe(index, &result);
+        Ok(result?)
+    }
+
     /// Get latest illusts from an author
     pub async fn get_user_illusts(&self, user_id: u64, limit: usize) -> Result<Vec<Illust>> {
+        self.budget.record_call();
         let response = self
-            .client
-            .user_illusts(user_id, Some("illust"), None)
+            .call(|client| client.user_illusts(user_id, Some("illust"), None))
             .await?;
 
         let illusts: Vec<_> = response.illusts.into_iter().take(limit).collect();
         Ok(illusts)
     }
 
+    /// Get up to `max_pages` pages of an author's illusts (newest first)
+    pub async fn get_user_illusts_pages(
+        &self,
+        user_id: u64,
+        max_pages: usize,
+    ) -> Result<Vec<Illust>> {
+        let mut illusts = Vec::new();
+
+        for _ in 0..max_pages {
+            let offset = (!illusts.is_empty()).then_some(illusts.len() as u32);
+            self.budget.record_call();
+            let response = self
+                .call(|client| client.user_illusts(user_id, Some("illust"), offset))
+                .await?;
+            illusts.extend(response.illusts);
+            if response.next_url.is_none() {
+                break;
+            }
+        }
+
+        Ok(illusts)
+    }
+
+    /// Get the works a user bookmarked publicly, most recently bookmarked first
+    pub async fn get_user_bookmarks(&self, user_id: u64, limit: usize) -> Result<Vec<Illust>> {
+        self.budget.record_call();
+        let response = self
+            .call(|client| client.user_bookmarks_illust(user_id, "public", None))
+            .await?;
+
+        let illusts: Vec<_> = response.illusts.into_iter().take(limit).collect();
+        info!(
+            "Fetched {} bookmarked illusts of user {}",
+            illusts.len(),
+            user_id
+        );
+
+        Ok(illusts)
+    }
+
     /// Get ranking illusts
     pub async fn get_ranking(
         &self,
@@ -40,7 +172,10 @@ impl PixivClient {
         date: Option<&str>,
         limit: usize,
     ) -> Result<Vec<Illust>> {
-        let response = self.client.illust_ranking(mode, date, None).await?;
+        self.budget.record_call();
+        let response = self
+            .call(|client| client.illust_ranking(mode, date, None))
+            .await?;
 
         let illusts: Vec<_> = response.illusts.into_iter().take(limit).collect();
         info!("Fetched {} ranking illusts", illusts.len());
@@ -48,22 +183,104 @@ impl PixivClient {
         Ok(illusts)
     }
 
+    /// Search illusts by tag keyword, most popular first when the account is premium.
+    ///
+    /// Returns one page of results and the offset of the next page, if any.
+    pub async fn search_illusts(
+        &self,
+        keyword: &str,
+        offset: u32,
+    ) -> Result<(Vec<Illust>, Option<u32>)> {
+        self.budget.record_call();
+        let (response, sort) = self
+            .call(|client| async move {
+                // Popular sorting depends on the account that sends the search
+                let sort = if client.is_premium().await {
+                    "popular_desc"
+                } else {
+                    "date_desc"
+                };
+                let response = client
+                    .search_illusts(keyword, sort, (offset > 0).then_some(offset))
+                    .await?;
+                Ok::<_, pixiv_client::Error>((response, sort))
+            })
+            .await?;
+
+        let next_offset = response
+            .next_url
+            .is_some()
+            .then(|| offset + response.illusts.len() as u32);
+        info!(
+            "Searched {:?} ({}), got {} illusts at offset {}",
+            keyword,
+            sort,
+            response.illusts.len(),
+            offset
+        );
+
+        Ok((response.illusts, next_offset))
+    }
+
+    /// Get illusts similar to `illust_id`.
+    ///
+    /// Returns one page of results and the offset of the next page, if any.
+    pub async fn get_related_illusts(
+        &self,
+        illust_id: u64,
+        offset: u32,
+    ) -> Result<(Vec<Illust>, Option<u32>)> {
+        self.budget.record_call();
+        let response = self
+            .call(|client| client.illust_related(illust_id, (offset > 0).then_some(offset)))
+            .await?;
+
+        let next_offset = response
+            .next_url
+            .is_some()
+            .then(|| offset + response.illusts.len() as u32);
+        info!(
+            "Fetched {} illusts related to {} at offset {}",
+            response.illusts.len(),
+            illust_id,
+            offset
+        );
+
+        Ok((response.illusts, next_offset))
+    }
+
     /// Get illust detail by ID
     pub async fn get_illust_detail(&self, illust_id: u64) -> Result<Illust> {
-        let response = self.client.illust_detail(illust_id).await?;
+        self.budget.record_call();
+        let response = self.call(|client| client.illust_detail(illust_id)).await?;
 
         Ok(response.illust)
     }
 
     /// 获取用户详情
     pub async fn get_user_detail(&self, user_id: u64) -> Result<pixiv_client::User> {
-        let response = self.client.user_detail(user_id).await?;
+        Ok(self.get_user_profile(user_id).await?.user)
+    }
+
+    /// 获取用户详情及主页资料 (头像、横幅)
+    pub async fn get_user_profile(&self, user_id: u64) -> Result<pixiv_client::UserDetail> {
+        self.budget.record_call();
+        let response = self.call(|client| client.user_detail(user_id)).await?;
 
         info!(
             "Successfully fetched user detail: {} ({})",
             response.user.name, response.user.id
         );
-        Ok(response.user)
+        Ok(response)
+    }
+
+    /// 将 pixiv.me 短链接中的用户名解析为用户 ID
+    pub async fn resolve_username(&self, username: &str) -> Result<u64> {
+        let user_id = self
+            .call(|client| client.resolve_username(username))
+            .await?;
+        info!("Resolved pixiv.me/{} to user {}", username, user_id);
+        Ok(user_id)
     }
 
     /// 获取 Ugoira (动图) 元数据
@@ -71,8 +288,85 @@ impl PixivClient {
         &self,
         illust_id: u64,
     ) -> Result<pixiv_client::UgoiraMetadataInfo> {
-        let response = self.client.ugoira_metadata(illust_id).await?;
+        self.budget.record_call();
+        let response = self
+            .call(|client| client.ugoira_metadata(illust_id))
+            .await?;
         info!("Fetched ugoira metadata for illust {}", illust_id);
         Ok(response.ugoira_metadata)
     }
 }
+
+fn next_retry_delay(delay: Duration) -> Duration {
+    (delay * 2).min(TOKEN_REFRESH_MAX_RETRY_DELAY)
+}
+
+/// Background task that refreshes the Pixiv access token ahead of expiry.
+///
+/// Failed refreshes are retried with exponential backoff; meanwhile
+/// [`PixivClient::is_authenticated`] reports false so engines skip their polls.
+pub async fn run_token_refresher(pixiv: Arc<RwLock<PixivClient>>) {
+    info!("🚀 Pixiv token refresher started");
+
+    loop {
+        let expires_in = pixiv.read().await.accounts.token_expires_in().await;
+        let wait = expires_in
+            .map(|left| left.saturating_sub(TOKEN_REFRESH_AHEAD))
+            .unwrap_or_default();
+        sleep(wait).await;
+
+        let mut retry_delay = TOKEN_REFRESH_MIN_RETRY_DELAY;
+        loop {
+            let result = pixiv.read().await.refresh_token().await;
+            match result {
+                Ok(()) => break,
+                Err(e) => {
+                    error!(
+                        "Failed to refresh Pixiv token, polling paused, retrying in {}s: {:#}",
+                        retry_delay.as_secs(),
+                        e
+                    );
+                    sleep(retry_delay).await;
+                    retry_delay = next_retry_delay(retry_delay);
+                }
+            }
+        }
+
+        if retry_delay > TOKEN_REFRESH_MIN_RETRY_DELAY {
+            info!("✅ Pixiv token refreshed after earlier failures, polling resumed");
+        }
+    }
+}
+
+/// Block until the Pixiv token is valid again, for engines whose run can't just be skipped
+pub async fn wait_until_authenticated(pixiv: &RwLock<PixivClient>) {
+    if pixiv.read().await.is_authenticated() {
+        return;
+    }
+
+    info!("Pixiv is not authenticated, waiting for token refresh");
+    while !pixiv.read().await.is_authenticated() {
+        sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_retry_delay_doubles_up_to_cap() {
+        assert_eq!(
+            next_retry_delay(TOKEN_REFRESH_MIN_RETRY_DELAY),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            next_retry_delay(Duration::from_secs(1200)),
+            TOKEN_REFRESH_MAX_RETRY_DELAY
+        );
+        assert_eq!(
+            next_retry_delay(TOKEN_REFRESH_MAX_RETRY_DELAY),
+            TOKEN_REFRESH_MAX_RETRY_DELAY
+        );
+    }
+}
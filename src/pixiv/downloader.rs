@@ -1,24 +1,60 @@
 use anyhow::{anyhow, Context, Result};
+use futures_util::future::join_all;
 #[cfg(feature = "ffmpeg-codec")]
 use pixiv_client::UgoiraFrame;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, ACCEPT_RANGES, RANGE, RETRY_AFTER};
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
 #[cfg(feature = "ffmpeg-codec")]
 use std::io::{Cursor, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "ffmpeg-codec")]
 use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
 use tracing::{info, warn};
 
-use crate::cache::FileCacheManager;
+use crate::cache::{DedupeStats, FileCacheManager};
+use crate::config::DownloadConfig;
+
+/// Upper bound for a single retry delay, including server-provided `Retry-After`
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Telegram rejects photos larger than this
+#[cfg(feature = "image")]
+const PHOTO_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Telegram rejects photos whose width and height add up to more than this
+#[cfg(feature = "image")]
+const PHOTO_MAX_DIMENSION_SUM: u32 = 10_000;
 
 pub struct Downloader {
     http_client: Client,
     cache: FileCacheManager,
+    /// Per-URL locks so concurrent callers for the same URL share one download
+    in_flight: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    /// Retry and concurrency limits
+    config: DownloadConfig,
+    /// Per-host request limits, created on first request to each host
+    host_limits: Mutex<HashMap<String, Arc<Semaphore>>>,
 }
 
 impl Downloader {
     pub fn new(http_client: Client, cache: FileCacheManager) -> Self {
-        Self { http_client, cache }
+        Self {
+            http_client,
+            cache,
+            in_flight: Mutex::new(HashMap::new()),
+            config: DownloadConfig::default(),
+            host_limits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use the given retry and concurrency limits instead of the defaults
+    pub fn with_config(mut self, config: DownloadConfig) -> Self {
+        self.config = config;
+        self
     }
 
     /// Download image and cache locally
@@ -30,21 +66,146 @@ impl Downloader {
             return Ok(path);
         }
 
-        // Cache miss - download
+        let url_lock = self.in_flight_lock(url);
+        let guard = url_lock.lock().await;
+
+        // Another caller may have finished the same download while we waited
+        let result = match self.cache.get(url).await {
+            Some(path) => {
+                info!("Cache hit after in-flight download for: {}", url);
+                Ok(path)
+            }
+            None => self.fetch_to_cache(url).await,
+        };
+
+        drop(guard);
+        self.release_in_flight(url, &url_lock);
+        result
+    }
+
+    fn in_flight_lock(&self, url: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(in_flight.entry(url.to_string()).or_default())
+    }
+
+    /// Drop the URL's lock entry once no other caller is waiting on it
+    fn release_in_flight(&self, url: &str, url_lock: &Arc<tokio::sync::Mutex<()>>) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        // One reference is held by the map and one by this caller
+        if Arc::strong_count(url_lock) <= 2 {
+            in_flight.remove(url);
+        }
+    }
+
+    fn host_limit(&self, url: &str) -> Option<Arc<Semaphore>> {
+        let host = url::Url::parse(url).ok()?.host_str()?.to_ascii_lowercase();
+        let mut host_limits = self.host_limits.lock().unwrap_or_else(|e| e.into_inner());
+        let limit = host_limits
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_per_host.max(1))));
+        Some(Arc::clone(limit))
+    }
+
+    /// Fetch a URL, retrying failed attempts with exponential backoff.
+    ///
+    /// A body interrupted midway is resumed with a range request when the server
+    /// advertises `Accept-Ranges: bytes`, so large originals are not fetched from scratch.
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let host_limit = self.host_limit(url);
+        let mut partial = PartialBody::default();
+        let mut attempt = 0;
+
+        loop {
+            let result = {
+                let _permit = match &host_limit {
+                    Some(limit) => limit.acquire().await.ok(),
+                    None => None,
+                };
+                self.fetch_attempt(url, &mut partial).await
+            };
+
+            let error = match result {
+                Ok(()) => return Ok(partial.data),
+                Err(e) => e,
+            };
+            if !error.retryable || attempt >= self.config.max_retries {
+                return Err(error.error);
+            }
+
+            attempt += 1;
+            let delay = error
+                .retry_after
+                .unwrap_or_else(|| retry_delay(self.config.retry_backoff_ms, attempt))
+                .min(MAX_RETRY_DELAY);
+            warn!(
+                "Download of {} failed (attempt {}/{}), retrying in {:?}{}: {:#}",
+                url,
+                attempt,
+                self.config.max_retries + 1,
+                delay,
+                match partial.resume_offset() {
+                    Some(_) => " with resume",
+                    None => "",
+                },
+                error.error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Single download attempt, appending to `partial` when resuming
+    async fn fetch_attempt(&self, url: &str, partial: &mut PartialBody) -> Result<(), FetchError> {
         let mut request = self.http_client.get(url);
         if let Some(referer) = download_referer(url) {
             request = request.header("Referer", referer);
         }
+        let resume_offset = partial.resume_offset();
+        if let Some(offset) = resume_offset {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
 
-        let bytes = request
-            .send()
-            .await
-            .context("Failed to send download request")?
-            .error_for_status()
-            .context("Download returned error status")?
-            .bytes()
-            .await
-            .context("Failed to read response bytes")?;
+        let mut response = request.send().await.map_err(|e| {
+            let retryable = !e.is_builder();
+            FetchError {
+                error: anyhow::Error::new(e).context("Failed to send download request"),
+                retryable,
+                retry_after: None,
+            }
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FetchError {
+                error: anyhow!("Download returned error status {}", status),
+                retryable: is_retryable_status(status),
+                retry_after: retry_after(response.headers()),
+            });
+        }
+
+        match resume_offset {
+            Some(offset) if status == StatusCode::PARTIAL_CONTENT => {
+                info!("Resuming download of {} from byte {}", url, offset);
+            }
+            _ => {
+                // Full body: first attempt, or the server ignored the range
+                partial.data.clear();
+                partial.resumable = accepts_ranges(response.headers());
+            }
+        }
+
+        while let Some(chunk) = response.chunk().await.map_err(|e| FetchError {
+            error: anyhow::Error::new(e).context("Failed to read response bytes"),
+            retryable: true,
+            retry_after: None,
+        })? {
+            partial.data.extend_from_slice(&chunk);
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_to_cache(&self, url: &str) -> Result<PathBuf> {
+        let bytes = self.fetch_bytes(url).await?;
 
         // Save to cache
         let path = self.cache.save(url, &bytes).await?;
@@ -52,15 +213,86 @@ impl Downloader {
         Ok(path)
     }
 
+    /// 标记缓存文件已成功发送，开启投递清理时到期后提前删除
+    pub fn mark_delivered(&self, path: &Path) {
+        self.cache.mark_delivered(path);
+    }
+
+    /// 缓存去重统计（启动以来）
+    pub fn dedupe_stats(&self) -> DedupeStats {
+        self.cache.dedupe_stats()
+    }
+
+    /// 下载图片并返回可作为 Telegram photo 发送的文件路径
+    ///
+    /// 超过 photo 限制（10 MB 或宽高之和超过 10000 px）的原图会缩放并重新编码为 JPEG，
+    /// 以单独的缓存键保存；原图缓存不变，下载命令仍发送原图。
+    /// 未启用 `image` feature 或处理失败时返回原图路径。
+    pub async fn download_photo(&self, url: &str) -> Result<PathBuf> {
+        let path = self.download(url).await?;
+        #[cfg(feature = "image")]
+        let path = self.fit_photo_limits(url, path).await;
+        Ok(path)
+    }
+
+    /// Cached photo-sized copy of an oversized original, or the original itself
+    #[cfg(feature = "image")]
+    async fn fit_photo_limits(&self, url: &str, path: PathBuf) -> PathBuf {
+        let photo_cache_key = format!("photo_{:x}.jpg", md5::compute(url));
+        if let Some(photo_path) = self.cache.get(&photo_cache_key).await {
+            return photo_path;
+        }
+
+        let original = path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            compress_for_photo(&original, PHOTO_MAX_BYTES, PHOTO_MAX_DIMENSION_SUM)
+        })
+        .await
+        .context("Photo compression task failed")
+        .and_then(|result| result);
+
+        let data = match result {
+            Ok(Some(data)) => data,
+            Ok(None) => return path,
+            Err(e) => {
+                warn!("Failed to compress oversized image {}: {:#}", url, e);
+                return path;
+            }
+        };
+        match self.cache.save(&photo_cache_key, &data).await {
+            Ok(photo_path) => {
+                info!(
+                    "Compressed oversized image {} to {} KB for sending as photo",
+                    url,
+                    data.len() / 1024
+                );
+                photo_path
+            }
+            Err(e) => {
+                warn!("Failed to cache compressed image {}: {:#}", url, e);
+                path
+            }
+        }
+    }
+
     /// 批量下载多张图片 (用于多图作品)
-    /// 返回所有下载成功的文件路径
-    pub async fn download_all(&self, urls: &[String]) -> Result<Vec<PathBuf>> {
+    /// 并行下载（受 `max_concurrent` 限制），按原顺序返回所有下载成功的、
+    /// 可作为 photo 发送的文件路径
+    pub async fn download_all_photos(&self, urls: &[String]) -> Result<Vec<PathBuf>> {
         info!("Batch downloading {} images", urls.len());
 
+        let limit = Semaphore::new(self.config.max_concurrent.max(1));
+        let limit = &limit;
+        let results = join_all(urls.iter().map(|url| async move {
+            let _permit = limit.acquire().await.ok();
+            self.download_photo(url).await
+        }))
+        .await;
+
         let mut paths = Vec::with_capacity(urls.len());
 
-        for (idx, url) in urls.iter().enumerate() {
-            match self.download(url).await {
+        for (idx, (url, result)) in urls.iter().zip(results).enumerate() {
+            match result {
                 Ok(path) => {
                     info!("Downloaded {}/{}: {:?}", idx + 1, urls.len(), path);
                     paths.push(path);
@@ -110,23 +342,12 @@ impl Downloader {
         info!("Downloading ugoira ZIP: {}", zip_url);
 
         // Download the ZIP file
-        let mut request = self.http_client.get(zip_url);
-        if let Some(referer) = download_referer(zip_url) {
-            request = request.header("Referer", referer);
-        }
-
-        let zip_bytes = request
-            .send()
+        let zip_data = self
+            .fetch_bytes(zip_url)
             .await
-            .context("Failed to download ugoira ZIP")?
-            .error_for_status()
-            .context("Ugoira ZIP download returned error status")?
-            .bytes()
-            .await
-            .context("Failed to read ugoira ZIP bytes")?;
+            .context("Failed to download ugoira ZIP")?;
 
         // Convert ZIP frames to MP4 in a blocking task (CPU-intensive)
-        let zip_data = zip_bytes.to_vec();
 
         let mp4_data = tokio::task::spawn_blocking(move || encode_ugoira_mp4(&zip_data, &frames))
             .await
@@ -139,6 +360,61 @@ impl Downloader {
     }
 }
 
+/// Body bytes received so far, kept across attempts for range resumption
+#[derive(Debug, Default)]
+struct PartialBody {
+    data: Vec<u8>,
+    /// Whether the server advertised byte range support for this URL
+    resumable: bool,
+}
+
+impl PartialBody {
+    /// Offset to resume from, if there is a resumable partial body
+    fn resume_offset(&self) -> Option<usize> {
+        (self.resumable && !self.data.is_empty()).then_some(self.data.len())
+    }
+}
+
+/// A failed download attempt
+struct FetchError {
+    error: anyhow::Error,
+    /// Network errors, timeouts, 408, 429 and 5xx are worth another attempt
+    retryable: bool,
+    /// Delay requested by the server via `Retry-After`
+    retry_after: Option<Duration>,
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+/// `Retry-After` in seconds (HTTP dates are ignored)
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+fn accepts_ranges(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("bytes"))
+}
+
+/// Backoff before the given retry: `base_ms`, then doubled for every further retry
+fn retry_delay(base_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(base_ms.saturating_mul(factor))
+}
+
 fn download_referer(url: &str) -> Option<&'static str> {
     let host = url::Url::parse(url).ok()?.host_str()?.to_ascii_lowercase();
 
@@ -149,6 +425,93 @@ fn download_referer(url: &str) -> Option<&'static str> {
     }
 }
 
+/// Re-encode an image as JPEG small enough for Telegram photos.
+///
+/// Returns `None` when the image already fits. Otherwise it is scaled down until
+/// the width and height add up to at most `max_dimension_sum`, and further by a
+/// quarter per step until the JPEG fits in `max_bytes`.
+#[cfg(feature = "image")]
+fn compress_for_photo(
+    path: &Path,
+    max_bytes: u64,
+    max_dimension_sum: u32,
+) -> Result<Option<Vec<u8>>> {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::imageops::FilterType;
+    use image::ImageReader;
+
+    const MAX_ATTEMPTS: usize = 5;
+    const JPEG_QUALITY: u8 = 90;
+
+    let size = std::fs::metadata(path)
+        .context("Failed to read image size")?
+        .len();
+    let (width, height) = ImageReader::open(path)
+        .context("Failed to open image")?
+        .with_guessed_format()
+        .context("Failed to detect image format")?
+        .into_dimensions()
+        .context("Failed to read image dimensions")?;
+    if size <= max_bytes && width + height <= max_dimension_sum {
+        return Ok(None);
+    }
+
+    let image = ImageReader::open(path)
+        .context("Failed to open image")?
+        .with_guessed_format()
+        .context("Failed to detect image format")?
+        .decode()
+        .context("Failed to decode image")?;
+    let image = flatten_alpha(image);
+
+    let mut scale = (max_dimension_sum as f64 / (width + height) as f64).min(1.0);
+    for _ in 0..MAX_ATTEMPTS {
+        let target_width = ((width as f64 * scale) as u32).max(1);
+        let target_height = ((height as f64 * scale) as u32).max(1);
+        let resized;
+        let frame = if scale < 1.0 {
+            resized = image::imageops::resize(
+                &image,
+                target_width,
+                target_height,
+                FilterType::CatmullRom,
+            );
+            &resized
+        } else {
+            &image
+        };
+
+        let mut data = Vec::new();
+        JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)
+            .encode_image(frame)
+            .context("Failed to encode JPEG")?;
+        if data.len() as u64 <= max_bytes {
+            return Ok(Some(data));
+        }
+        scale *= 0.75;
+    }
+
+    Err(anyhow!(
+        "Image still exceeds {} bytes after {} attempts",
+        max_bytes,
+        MAX_ATTEMPTS
+    ))
+}
+
+/// Drop the alpha channel, compositing transparent pixels onto white
+#[cfg(feature = "image")]
+fn flatten_alpha(image: image::DynamicImage) -> image::RgbImage {
+    if !image.color().has_alpha() {
+        return image.into_rgb8();
+    }
+    let rgba = image.into_rgba8();
+    image::RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
 /// Read a named entry from a ZIP archive into a byte vector.
 #[cfg(feature = "ffmpeg-codec")]
 fn read_zip_entry(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>> {
@@ -435,6 +798,156 @@ mod tests {
         assert_eq!(download_referer("not a url"), None);
     }
 
+    #[tokio::test]
+    async fn concurrent_downloads_of_same_url_share_one_request() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/image.jpg"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_bytes(b"image".to_vec())
+                    .set_delay(std::time::Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let downloader = Downloader::new(Client::new(), FileCacheManager::new(cache_dir.path(), 1));
+        let url = format!("{}/image.jpg", server.uri());
+
+        let (first, second) = tokio::join!(downloader.download(&url), downloader.download(&url));
+
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert!(downloader.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn retry_delay_doubles_per_attempt() {
+        assert_eq!(retry_delay(500, 1), Duration::from_millis(500));
+        assert_eq!(retry_delay(500, 2), Duration::from_millis(1000));
+        assert_eq!(retry_delay(500, 4), Duration::from_millis(4000));
+    }
+
+    fn fast_retry_downloader(cache_dir: &Path) -> Downloader {
+        Downloader::new(Client::new(), FileCacheManager::new(cache_dir, 1)).with_config(
+            DownloadConfig {
+                max_retries: 2,
+                retry_backoff_ms: 1,
+                ..DownloadConfig::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/image.jpg"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/image.jpg"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"image".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/missing.jpg"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let downloader = fast_retry_downloader(cache_dir.path());
+
+        let image = downloader
+            .fetch_bytes(&format!("{}/image.jpg", server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(image, b"image");
+
+        // Client errors other than 408/429 fail immediately
+        assert!(downloader
+            .fetch_bytes(&format!("{}/missing.jpg", server.uri()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn interrupted_download_resumes_with_range_request() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/original.png"))
+            .and(header("Range", "bytes=5-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(b" data".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let downloader = fast_retry_downloader(cache_dir.path());
+        let mut partial = PartialBody {
+            data: b"image".to_vec(),
+            resumable: true,
+        };
+
+        let result = downloader
+            .fetch_attempt(&format!("{}/original.png", server.uri()), &mut partial)
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(partial.data, b"image data");
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn oversized_images_are_compressed_for_photos() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("original.png");
+        // Noise, so the JPEG size follows the pixel count
+        image::RgbaImage::from_fn(300, 200, |x, y| {
+            let noise = (x * 31 + y * 17).wrapping_mul(2_654_435_761).to_be_bytes();
+            image::Rgba([noise[0], noise[1], noise[2], 128])
+        })
+        .save(&path)
+        .unwrap();
+
+        assert_eq!(
+            compress_for_photo(&path, 10 * 1024 * 1024, 500).unwrap(),
+            None
+        );
+
+        let data = compress_for_photo(&path, 10 * 1024 * 1024, 250)
+            .unwrap()
+            .unwrap();
+        let compressed = image::load_from_memory(&data).unwrap();
+        assert_eq!(
+            image::guess_format(&data).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+        assert!(compressed.width() + compressed.height() <= 250);
+        assert!(!compressed.color().has_alpha());
+
+        let data = compress_for_photo(&path, data.len() as u64 / 2, 250)
+            .unwrap()
+            .unwrap();
+        assert!(image::load_from_memory(&data).unwrap().width() < compressed.width());
+    }
+
     /// Create a minimal PNG image in memory (2x2 pixels with given color)
     #[cfg(feature = "ffmpeg-codec")]
     fn create_test_png(r: u8, g: u8, b: u8) -> Vec<u8> {
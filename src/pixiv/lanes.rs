@@ -0,0 +1,140 @@
+//! Priority lanes for Pixiv API requests.
+//!
+//! Commands and link previews share the Pixiv client with the scheduler engines.
+//! When a poll burst fills every request slot, a user's `/download` would queue
+//! behind it. Requests therefore run in one of two lanes: background requests may
+//! only use all but one of the slots, so an interactive request never waits for
+//! more than the interactive requests ahead of it and a single background one.
+//!
+//! The lane is taken from the calling task: futures wrapped in [`background`]
+//! (the engines, see `main`) are background, everything else is interactive.
+//! The lane is not inherited by `tokio::spawn` or `JoinSet::spawn`, so engines
+//! that fan out into worker tasks wrap each worker in [`background`] again.
+
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Concurrent Pixiv requests used when not configured
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Requests made on behalf of a waiting user
+    Interactive,
+    /// Scheduler polling, retries and other work nobody is waiting on
+    Background,
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+/// Run `future` with its Pixiv requests in the background lane
+pub async fn background<F: Future>(future: F) -> F::Output {
+    PRIORITY.scope(Priority::Background, future).await
+}
+
+/// Lane of the current task, interactive unless inside [`background`]
+pub fn current_priority() -> Priority {
+    PRIORITY
+        .try_with(|priority| *priority)
+        .unwrap_or(Priority::Interactive)
+}
+
+/// Slots held while a request is in flight
+pub struct LanePermit {
+    _background: Option<OwnedSemaphorePermit>,
+    _shared: Option<OwnedSemaphorePermit>,
+}
+
+pub struct RequestLanes {
+    /// All in-flight requests, `None` when unlimited
+    shared: Option<Arc<Semaphore>>,
+    /// In-flight or queued background requests, one fewer than `shared`
+    background: Option<Arc<Semaphore>>,
+}
+
+impl RequestLanes {
+    /// `max_concurrent = 0` disables the lanes; a single slot leaves nothing to reserve
+    pub fn new(max_concurrent: usize) -> Self {
+        if max_concurrent == 0 {
+            return Self {
+                shared: None,
+                background: None,
+            };
+        }
+        Self {
+            shared: Some(Arc::new(Semaphore::new(max_concurrent))),
+            background: Some(Arc::new(Semaphore::new(
+                max_concurrent.saturating_sub(1).max(1),
+            ))),
+        }
+    }
+
+    /// Wait for a request slot in the lane of `priority`
+    pub async fn acquire(&self, priority: Priority) -> LanePermit {
+        let (Some(shared), Some(background)) = (&self.shared, &self.background) else {
+            return LanePermit {
+                _background: None,
+                _shared: None,
+            };
+        };
+
+        // Background requests queue here first, so at most one fewer than the
+        // shared slots can ever be taken or waited for by them
+        let background = match priority {
+            Priority::Background => background.clone().acquire_owned().await.ok(),
+            Priority::Interactive => None,
+        };
+        let shared = shared.clone().acquire_owned().await.ok();
+        LanePermit {
+            _background: background,
+            _shared: shared,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn background_requests_leave_a_slot_for_interactive_ones() {
+        let lanes = RequestLanes::new(3);
+
+        let _first = lanes.acquire(Priority::Background).await;
+        let _second = lanes.acquire(Priority::Background).await;
+        let third = lanes.acquire(Priority::Background);
+        tokio::pin!(third);
+        assert!(futures_util::poll!(third.as_mut()).is_pending());
+
+        let interactive = lanes.acquire(Priority::Interactive);
+        tokio::pin!(interactive);
+        assert!(futures_util::poll!(interactive.as_mut()).is_ready());
+    }
+
+    #[tokio::test]
+    async fn priority_follows_the_background_scope() {
+        assert_eq!(current_priority(), Priority::Interactive);
+        assert_eq!(
+            background(async { current_priority() }).await,
+            Priority::Background
+        );
+    }
+
+    #[tokio::test]
+    async fn spawned_workers_keep_the_background_lane_only_when_wrapped() {
+        let (unwrapped, wrapped) = background(async {
+            let mut workers = tokio::task::JoinSet::new();
+            workers.spawn(async { current_priority() });
+            let unwrapped = workers.join_next().await.unwrap().unwrap();
+            workers.spawn(background(async { current_priority() }));
+            let wrapped = workers.join_next().await.unwrap().unwrap();
+            (unwrapped, wrapped)
+        })
+        .await;
+        assert_eq!(unwrapped, Priority::Interactive);
+        assert_eq!(wrapped, Priority::Background);
+    }
+}
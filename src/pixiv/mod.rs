@@ -1,3 +1,7 @@
+pub mod accounts;
+pub mod avatar;
+pub mod budget;
 pub mod client;
 pub mod downloader;
+pub mod lanes;
 pub mod model;
@@ -72,16 +72,18 @@ impl RankingMode {
     }
 
     /// 从字符串解析排行榜模式
+    ///
+    /// 除 API 模式名外也接受 `daily`、`weekly`、`monthly`、`original`、`rookie` 等别名
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
-            "day" => Some(RankingMode::Day),
-            "week" => Some(RankingMode::Week),
-            "month" => Some(RankingMode::Month),
+            "day" | "daily" => Some(RankingMode::Day),
+            "week" | "weekly" => Some(RankingMode::Week),
+            "month" | "monthly" => Some(RankingMode::Month),
             "day_male" => Some(RankingMode::DayMale),
             "day_female" => Some(RankingMode::DayFemale),
-            "week_original" => Some(RankingMode::WeekOriginal),
-            "week_rookie" => Some(RankingMode::WeekRookie),
-            "day_manga" => Some(RankingMode::DayManga),
+            "week_original" | "original" => Some(RankingMode::WeekOriginal),
+            "week_rookie" | "rookie" => Some(RankingMode::WeekRookie),
+            "day_manga" | "manga" => Some(RankingMode::DayManga),
             "day_r18" => Some(RankingMode::DayR18),
             "week_r18" => Some(RankingMode::WeekR18),
             "week_r18g" => Some(RankingMode::WeekR18g),
@@ -0,0 +1,198 @@
+//! SauceNAO reverse image search, used by `/sauce` to find the Pixiv work
+//! behind an image.
+//!
+//! Only the Pixiv index is queried. The image is uploaded rather than passed by
+//! URL, since Telegram file URLs contain the bot token.
+
+use crate::config::SauceNaoConfig;
+use crate::utils::version;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+const SEARCH_URL: &str = "https://saucenao.com/search.php";
+
+/// SauceNAO index id of Pixiv images
+const PIXIV_INDEX: u32 = 5;
+
+/// Results requested per search
+const MAX_RESULTS: u32 = 5;
+
+/// A Pixiv work SauceNAO considers similar to the searched image
+#[derive(Debug, Clone, PartialEq)]
+pub struct SauceMatch {
+    /// Similarity in percent
+    pub similarity: f64,
+    pub illust_id: u64,
+    pub title: String,
+    pub author_id: u64,
+    pub author_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    header: ResponseHeader,
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseHeader {
+    status: i32,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    header: ResultHeader,
+    data: ResultData,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultHeader {
+    /// Sent as a string, e.g. "93.48"
+    similarity: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultData {
+    #[serde(default)]
+    pixiv_id: Option<u64>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    member_id: Option<u64>,
+    #[serde(default)]
+    member_name: Option<String>,
+}
+
+/// Pixiv matches in a search response, most similar first
+fn parse_matches(body: &str) -> Result<Vec<SauceMatch>> {
+    let response: SearchResponse =
+        serde_json::from_str(body).context("Failed to parse SauceNAO response")?;
+    // Positive status is a server-side problem, negative a bad request; both
+    // may still come with results, which are then usable
+    if response.header.status != 0 && response.results.is_empty() {
+        anyhow::bail!(
+            "SauceNAO returned status {}: {}",
+            response.header.status,
+            response.header.message.as_deref().unwrap_or("no message")
+        );
+    }
+
+    let mut matches: Vec<SauceMatch> = response
+        .results
+        .into_iter()
+        .filter_map(|result| {
+            Some(SauceMatch {
+                similarity: result.header.similarity.parse().ok()?,
+                illust_id: result.data.pixiv_id?,
+                title: result.data.title.unwrap_or_default(),
+                author_id: result.data.member_id?,
+                author_name: result.data.member_name.unwrap_or_default(),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    Ok(matches)
+}
+
+pub struct SauceNaoClient {
+    http: reqwest::Client,
+    api_key: String,
+    min_similarity: f64,
+}
+
+impl SauceNaoClient {
+    /// None when no API key is configured
+    pub fn from_config(config: &SauceNaoConfig, proxy: Option<&str>) -> Result<Option<Self>> {
+        let Some(api_key) = config.api_key.clone().filter(|key| !key.is_empty()) else {
+            return Ok(None);
+        };
+        let mut http = reqwest::Client::builder()
+            .user_agent(format!("pixivbot/{}", version::VERSION))
+            .timeout(std::time::Duration::from_secs(30));
+        if let Some(proxy) = proxy {
+            http = http.proxy(reqwest::Proxy::all(proxy).context("Invalid SauceNAO proxy")?);
+        }
+        Ok(Some(Self {
+            http: http
+                .build()
+                .context("Failed to build SauceNAO HTTP client")?,
+            api_key,
+            min_similarity: config.min_similarity,
+        }))
+    }
+
+    /// Upload `image` and return the best Pixiv match above the configured
+    /// similarity, if any
+    pub async fn search(&self, image: Vec<u8>) -> Result<Option<SauceMatch>> {
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::bytes(image).file_name("image.jpg"),
+        );
+
+        let response = self
+            .http
+            .post(SEARCH_URL)
+            .query(&[
+                ("output_type", "2".to_string()),
+                ("api_key", self.api_key.clone()),
+                ("db", PIXIV_INDEX.to_string()),
+                ("numres", MAX_RESULTS.to_string()),
+            ])
+            .multipart(form)
+            .send()
+            .await
+            .context("Failed to request SauceNAO")?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            anyhow::bail!("SauceNAO rate limit reached");
+        }
+        let body = response
+            .error_for_status()
+            .context("SauceNAO returned error status")?
+            .text()
+            .await
+            .context("Failed to read SauceNAO response")?;
+
+        Ok(parse_matches(&body)?
+            .into_iter()
+            .find(|candidate| candidate.similarity >= self.min_similarity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_matches_keeps_pixiv_results_by_similarity() {
+        let body = r#"{
+            "header": {"status": 0, "results_returned": 3},
+            "results": [
+                {"header": {"similarity": "61.20", "index_id": 5},
+                 "data": {"pixiv_id": 2, "title": "B", "member_id": 20, "member_name": "b"}},
+                {"header": {"similarity": "93.48", "index_id": 5},
+                 "data": {"pixiv_id": 1, "title": "A", "member_id": 10, "member_name": "a"}},
+                {"header": {"similarity": "99.00", "index_id": 9},
+                 "data": {"danbooru_id": 3}}
+            ]
+        }"#;
+        let matches = parse_matches(body).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].illust_id, 1);
+        assert_eq!(matches[0].author_id, 10);
+        assert_eq!(matches[0].similarity, 93.48);
+        assert_eq!(matches[1].illust_id, 2);
+    }
+
+    #[test]
+    fn parse_matches_reports_errors_without_results() {
+        let body = r#"{"header": {"status": -2, "message": "Search Rate Too High."}}"#;
+        let error = parse_matches(body).unwrap_err();
+        assert!(error.to_string().contains("Search Rate Too High."));
+
+        let empty = r#"{"header": {"status": 0}, "results": []}"#;
+        assert!(parse_matches(empty).unwrap().is_empty());
+    }
+}
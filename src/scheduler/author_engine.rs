@@ -1,30 +1,144 @@
 use crate::bot::notifier::Notifier;
+use crate::config::{ImageSizePolicy, SharedConfig};
+use crate::db::entities::{subscriptions, tasks};
 use crate::db::repo::Repo;
-use crate::db::types::{AuthorState, PendingIllust, SubscriptionState, TaskType};
-use crate::pixiv::client::PixivClient;
+use crate::db::types::{AuthorState, PushMode, SubscriptionState, TaskType};
+use crate::pixiv::client::{is_gone, PixivClient};
+use crate::pixiv::lanes;
+use crate::scheduler::fair_queue::{FairQueue, FAIR_QUEUE_WINDOW};
 use crate::scheduler::helpers::{
-    apply_subscription_tag_filter, author_subscription_state, get_chat_if_should_notify,
-    process_illust_push, save_first_message_record, AuthorContext, PushResult,
-    INTER_SUBSCRIPTION_DELAY_MS,
+    already_sent_illust_ids, apply_subscription_tag_filter, author_subscription_state,
+    get_chat_if_should_notify, illust_filter_rejection, illust_spoiler, log_push,
+    process_illust_push, record_sent_artworks, record_sent_batch, record_task_result,
+    save_first_message_record, save_subscription_state, AuthorContext, FilterRejection, PushResult,
+    SubscriptionDelay,
 };
+use crate::scheduler::retry_engine::retry_delay;
+use crate::utils::caption;
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::Utc;
 use pixiv_client::Illust;
 use rand::RngExt;
+use std::collections::HashSet;
 use std::sync::Arc;
 use teloxide::prelude::*;
-use tokio::time::{sleep, Duration};
+use teloxide::utils::markdown;
+use tokio::task::JoinSet;
+use tokio::time::Duration;
 use tracing::{debug, error, info, warn};
 
+#[derive(Clone)]
 pub struct AuthorEngine {
     repo: Arc<Repo>,
     pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
     notifier: Notifier,
+    /// Poll intervals are read from here so config reloads apply to the next poll
+    config: SharedConfig,
     tick_interval_sec: u64,
-    min_task_interval_sec: u64,
-    max_task_interval_sec: u64,
     max_retry_count: i32,
     image_size: pixiv_client::ImageSize,
+    keep_continuation_spoiler: bool,
+    /// Maximum number of author tasks executed in parallel per tick
+    concurrent_tasks: usize,
+    /// Consecutive 404/403 failures before an author task is marked dead (0 = never)
+    dead_author_threshold: u32,
+    subscription_delay: SubscriptionDelay,
+    fair_queue: FairQueue,
+    /// Make interrupted pushes due before the first tick
+    recover_pending_on_startup: bool,
+}
+
+/// What one subscription's turn in a poll produced
+struct SubscriptionTurn {
+    /// State to persist, if it changed
+    new_state: Option<AuthorState>,
+    /// Whether anything was sent to the chat (successfully or not)
+    pushed: bool,
+}
+
+impl SubscriptionTurn {
+    fn idle(new_state: Option<AuthorState>) -> Self {
+        Self {
+            new_state,
+            pushed: false,
+        }
+    }
+
+    fn pushed(new_state: Option<AuthorState>) -> Self {
+        Self {
+            new_state,
+            pushed: true,
+        }
+    }
+}
+
+/// What one subscription's turn will push, decided before anything is sent
+struct TurnPlan<'a> {
+    /// Works newer than the cursor, newest first
+    new_illusts: Vec<&'a Illust>,
+    action: TurnAction<'a>,
+}
+
+enum TurnAction<'a> {
+    /// Nothing newer than the cursor
+    NothingNew,
+    /// Every new work was filtered out, the cursor skips past them
+    AllFiltered { newest_illust_id: u64 },
+    /// The oldest new work already reached the chat through another subscription
+    AlreadySent { illust_id: u64 },
+    /// Several new works as one media group (album push mode)
+    Album(Vec<&'a Illust>),
+    /// The oldest new work with all of its pages
+    Single(&'a Illust),
+}
+
+/// Dry run of an author subscription's next poll, see [`AuthorEngine::simulate`]
+pub struct AuthorSimulation {
+    /// Whether the subscribed chat receives pushes at all (enabled, or an admin's chat)
+    pub chat_notified: bool,
+    /// Number of latest works fetched from Pixiv
+    pub fetched: usize,
+    /// Newest work already handled, `None` before the first push
+    pub cursor: Option<u64>,
+    /// Works newer than the cursor, newest first, with the filter that drops each
+    pub new_illusts: Vec<(Illust, Option<FilterRejection>)>,
+    pub outcome: SimulatedOutcome,
+}
+
+/// What the simulated poll would do
+pub enum SimulatedOutcome {
+    NothingNew,
+    /// Every new work is filtered out
+    AllFiltered,
+    /// The work is skipped because the chat already received it
+    AlreadySent {
+        illust_id: u64,
+    },
+    Push(SimulatedPush),
+}
+
+/// A push as the poll would send it
+pub struct SimulatedPush {
+    pub kind: SimulatedPushKind,
+    /// Works in the push, oldest first
+    pub illust_ids: Vec<u64>,
+    /// Images in send order: every page, the cover of a ugoira, none for restricted works
+    pub image_urls: Vec<String>,
+    /// MarkdownV2 caption after the caption hooks ran
+    pub caption: String,
+    pub spoiler: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedPushKind {
+    /// One work with all of its pages
+    Images,
+    /// First page of several works in one media group
+    Album,
+    /// Ugoira converted to an MP4 animation
+    Ugoira,
+    /// Text notice for a work the account cannot see
+    Restricted,
 }
 
 impl AuthorEngine {
@@ -33,21 +147,30 @@ impl AuthorEngine {
         repo: Arc<Repo>,
         pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
         notifier: Notifier,
+        config: SharedConfig,
         tick_interval_sec: u64,
-        min_task_interval_sec: u64,
-        max_task_interval_sec: u64,
         max_retry_count: i32,
-        image_size: pixiv_client::ImageSize,
+        image_sizes: ImageSizePolicy,
+        keep_continuation_spoiler: bool,
+        concurrent_tasks: usize,
+        dead_author_threshold: u32,
+        subscription_delay: SubscriptionDelay,
+        recover_pending_on_startup: bool,
     ) -> Self {
         Self {
             repo,
             pixiv_client,
             notifier,
+            config,
             tick_interval_sec,
-            min_task_interval_sec,
-            max_task_interval_sec,
             max_retry_count,
-            image_size,
+            image_size: image_sizes.push,
+            keep_continuation_spoiler,
+            concurrent_tasks: concurrent_tasks.max(1),
+            dead_author_threshold,
+            subscription_delay,
+            fair_queue: FairQueue::new(&[]),
+            recover_pending_on_startup,
         }
     }
 
@@ -55,6 +178,10 @@ impl AuthorEngine {
     pub async fn run(&self) {
         info!("🚀 Author engine started");
 
+        if self.recover_pending_on_startup {
+            self.recover_interrupted_pushes().await;
+        }
+
         let mut interval = tokio::time::interval(Duration::from_secs(self.tick_interval_sec));
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
@@ -68,46 +195,111 @@ impl AuthorEngine {
         }
     }
 
-    /// Single tick - fetch and execute one pending author task
+    /// Startup pass over pushes the last run left unfinished
+    ///
+    /// Partly sent or failed works wait in `push_queue` with a backoff that may have
+    /// long elapsed, or still be hours away, by the time the bot is back. They are all
+    /// made due now so the retry engine finishes them before new works pile up.
+    async fn recover_interrupted_pushes(&self) {
+        let pending = match self.repo.expedite_pending_push_retries().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                error!("Failed to recover interrupted pushes: {:#}", e);
+                return;
+            }
+        };
+        if pending.is_empty() {
+            debug!("No interrupted pushes to recover");
+            return;
+        }
+
+        let now = Utc::now().naive_utc();
+        let subscriptions: HashSet<i32> = pending.iter().map(|e| e.subscription_id).collect();
+        let chats: HashSet<i64> = pending.iter().map(|e| e.chat_id).collect();
+        let partial = pending.iter().filter(|e| !e.sent_pages.is_empty()).count();
+        let brought_forward = pending.iter().filter(|e| e.next_attempt_at > now).count();
+        info!(
+            "♻️ Recovering {} interrupted pushes of {} subscriptions in {} chats ({} partly sent, {} brought forward)",
+            pending.len(),
+            subscriptions.len(),
+            chats.len(),
+            partial,
+            brought_forward
+        );
+    }
+
+    /// Single tick - pick up to `concurrent_tasks` pending author tasks fairly and execute them in parallel
     async fn tick(&self) -> Result<()> {
-        // Get one pending author task
-        let tasks = self
+        // Pause polling while the token refresher is failing
+        if !self.pixiv_client.read().await.is_authenticated() {
+            debug!("Pixiv is not authenticated, skipping author tick");
+            return Ok(());
+        }
+
+        let candidates = self
             .repo
-            .get_pending_tasks_by_type(TaskType::Author, 1)
+            .get_pending_tasks_by_types(
+                &[TaskType::Author],
+                self.concurrent_tasks as u64 * FAIR_QUEUE_WINDOW,
+            )
             .await?;
+        let tasks = self.fair_queue.select(candidates, self.concurrent_tasks);
 
-        let task = match tasks.first() {
-            Some(t) => t,
-            None => return Ok(()),
-        };
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let mut workers = JoinSet::new();
+        for task in tasks {
+            let worker = self.clone();
+            // The lane doesn't carry over into spawned tasks
+            workers.spawn(lanes::background(
+                async move { worker.run_task(&task).await },
+            ));
+        }
+
+        while let Some(result) = workers.join_next().await {
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!("Failed to reschedule author task: {:#}", e),
+                Err(e) => error!("Author task worker panicked: {:#}", e),
+            }
+        }
+
+        Ok(())
+    }
 
+    /// Execute one author task, rescheduling it even when execution fails
+    async fn run_task(&self, task: &tasks::Model) -> Result<()> {
         debug!(
             "⚙️  Executing author task [{}] {} {}",
             task.id, task.r#type, task.value
         );
 
-        // Execute task
-        let result = self.execute_author_task(task).await;
-
         // Note: task's next_poll_at is updated inside execute_author_task
         // We only log errors here, no need to update task again
+        let result = self.execute_author_task(task).await;
+        record_task_result(&self.repo, task.id, &result).await;
         if let Err(e) = result {
-            error!("Author task execution failed: {:#}", e);
+            error!("Author task [{}] execution failed: {:#}", task.id, e);
 
-            // On error, still update the poll time to avoid immediate retry
-            let random_interval_sec =
-                rand::rng().random_range(self.min_task_interval_sec..=self.max_task_interval_sec);
-            let next_poll = Local::now() + chrono::Duration::seconds(random_interval_sec as i64);
+            if self.is_dead_author(task, &e) {
+                match self.mark_author_dead(task).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) => error!("Failed to mark author task [{}] dead: {:#}", task.id, e),
+                }
+            }
 
-            self.repo.update_task_after_poll(task.id, next_poll).await?;
+            // On error, still update the poll time to avoid immediate retry
+            self.schedule_next_poll(task).await?;
         }
 
         Ok(())
     }
 
     /// Execute author subscription task (Orchestrator)
-    /// Fetches data once, iterates subscriptions, delegates to dispatcher
-    async fn execute_author_task(&self, task: &crate::db::entities::tasks::Model) -> Result<()> {
+    /// Fetches data once, iterates subscriptions, pushes new illusts of each
+    async fn execute_author_task(&self, task: &tasks::Model) -> Result<()> {
         let author_id: u64 = task.value.parse()?;
 
         // Get latest illusts from Pixiv API
@@ -116,16 +308,16 @@ impl AuthorEngine {
         drop(pixiv);
 
         if illusts.is_empty() {
-            self.schedule_next_poll(task.id).await?;
+            self.schedule_next_poll(task).await?;
             return Ok(());
         }
 
         // Get all subscriptions for this task
-        let subscriptions = self.repo.list_subscriptions_by_task(task.id).await?;
+        let subscriptions = self.repo.list_active_subscriptions_by_task(task.id).await?;
 
         if subscriptions.is_empty() {
             info!("No subscriptions for author task {}", task.id);
-            self.schedule_next_poll(task.id).await?;
+            self.schedule_next_poll(task).await?;
             return Ok(());
         }
 
@@ -143,87 +335,340 @@ impl AuthorEngine {
 
             let subscription_state = author_subscription_state(&subscription);
 
+            // Hold the chat's turn for the whole push so other pushes can't interleave
+            let _turn = self.notifier.begin_push(ChatId(subscription.chat_id)).await;
+
             let ctx = AuthorContext {
                 subscription: &subscription,
                 chat,
                 subscription_state,
+                mirror_target: false,
             };
 
-            // Delegate to dispatcher, get new state if any
-            match self
-                .process_single_author_sub(&ctx, &illusts)
+            // Push new illusts, get new state if any
+            let pushed = match self
+                .handle_new_illusts(&ctx, &illusts)
                 .await
                 .context(format!(
                     "Failed to process subscription {}",
                     subscription.id
                 )) {
-                Ok(Some(new_state)) => {
-                    // Worker returned new state, persist it
-                    if let Err(e) = self
-                        .update_subscription_state(subscription.id, new_state)
-                        .await
-                    {
-                        error!(
-                            "Failed to update subscription {} state: {:#}",
-                            subscription.id, e
-                        );
+                Ok(turn) => {
+                    if let Some(new_state) = turn.new_state {
+                        // Worker returned new state, persist it
+                        if let Err(e) = self
+                            .update_subscription_state(&subscription, new_state)
+                            .await
+                        {
+                            error!(
+                                "Failed to update subscription {} state: {:#}",
+                                subscription.id, e
+                            );
+                        }
                     }
-                }
-                Ok(None) => {
-                    // No state change
+                    turn.pushed
                 }
                 Err(e) => {
                     error!("{:#}", e);
+                    // The error may have come mid-push
+                    true
                 }
+            };
+
+            // Jittered delay between subscriptions, only when something was sent
+            if pushed {
+                self.subscription_delay.wait().await;
             }
+            drop(_turn);
 
-            // Small delay between subscriptions
-            sleep(Duration::from_millis(INTER_SUBSCRIPTION_DELAY_MS)).await;
+            self.push_mirror_targets(&subscription, &illusts).await;
         }
 
         // Schedule next poll
-        self.schedule_next_poll(task.id).await?;
+        self.schedule_next_poll(task).await?;
 
         Ok(())
     }
 
+    /// Push a subscription's new works to the chats it is mirrored to
+    ///
+    /// Each target is handled like a subscription of its own chat: the target chat's
+    /// settings apply and it keeps its own cursor, while the subscription's tag filter
+    /// and push options are shared. The works were fetched once for all of them.
+    async fn push_mirror_targets(&self, subscription: &subscriptions::Model, illusts: &[Illust]) {
+        let targets = match self.repo.list_subscription_targets(subscription.id).await {
+            Ok(targets) => targets,
+            Err(e) => {
+                error!(
+                    "Failed to list mirror targets of subscription {}: {:#}",
+                    subscription.id, e
+                );
+                return;
+            }
+        };
+
+        for target in targets {
+            let chat = match get_chat_if_should_notify(&self.repo, target.chat_id).await {
+                Ok(Some(chat)) => chat,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Failed to process chat {}: {:#}", target.chat_id, e);
+                    continue;
+                }
+            };
+
+            // The subscription's forum topic belongs to its own chat
+            let mirrored = subscriptions::Model {
+                chat_id: target.chat_id,
+                message_thread_id: None,
+                ..subscription.clone()
+            };
+            let _turn = self.notifier.begin_push(ChatId(target.chat_id)).await;
+
+            let ctx = AuthorContext {
+                subscription: &mirrored,
+                chat,
+                subscription_state: target
+                    .latest_illust_id
+                    .map(|id| Self::clear_pending_state(id as u64)),
+                mirror_target: true,
+            };
+
+            let pushed = match self.handle_new_illusts(&ctx, illusts).await {
+                Ok(turn) => {
+                    if let Some(new_state) = turn.new_state {
+                        if let Err(e) = self
+                            .repo
+                            .update_subscription_target_cursor(
+                                target.id,
+                                new_state.latest_illust_id as i64,
+                            )
+                            .await
+                        {
+                            error!(
+                                "Failed to update mirror target {} cursor: {:#}",
+                                target.id, e
+                            );
+                        }
+                    }
+                    turn.pushed
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to push subscription {} to mirror chat {}: {:#}",
+                        subscription.id, target.chat_id, e
+                    );
+                    true
+                }
+            };
+
+            if pushed {
+                self.subscription_delay.wait().await;
+            }
+        }
+    }
+
+    /// Run a subscription's next poll as a dry run
+    ///
+    /// Fetches the author's latest works and plans the push exactly like a poll,
+    /// but sends nothing and leaves the subscription state untouched.
+    pub async fn simulate(
+        &self,
+        subscription: &subscriptions::Model,
+        author_id: u64,
+    ) -> Result<AuthorSimulation> {
+        let pixiv = self.pixiv_client.read().await;
+        let illusts = pixiv.get_user_illusts(author_id, 10).await?;
+        drop(pixiv);
+
+        let (chat, chat_notified) =
+            match get_chat_if_should_notify(&self.repo, subscription.chat_id).await? {
+                Some(chat) => (chat, true),
+                None => {
+                    let chat = self
+                        .repo
+                        .get_chat(subscription.chat_id)
+                        .await?
+                        .with_context(|| format!("Chat {} not found", subscription.chat_id))?;
+                    (chat, false)
+                }
+            };
+
+        let ctx = AuthorContext {
+            subscription,
+            chat,
+            subscription_state: author_subscription_state(subscription),
+            mirror_target: false,
+        };
+        let plan = self.plan_turn(&ctx, &illusts).await;
+
+        let new_illusts = plan
+            .new_illusts
+            .iter()
+            .map(|&illust| {
+                let rejection = illust_filter_rejection(
+                    &subscription.filter_tags,
+                    subscription.ai_filter,
+                    &ctx.chat,
+                    illust,
+                );
+                (illust.clone(), rejection)
+            })
+            .collect();
+        let outcome = match plan.action {
+            TurnAction::NothingNew => SimulatedOutcome::NothingNew,
+            TurnAction::AllFiltered { .. } => SimulatedOutcome::AllFiltered,
+            TurnAction::AlreadySent { illust_id } => SimulatedOutcome::AlreadySent { illust_id },
+            TurnAction::Album(album) => SimulatedOutcome::Push(SimulatedPush {
+                kind: SimulatedPushKind::Album,
+                illust_ids: album.iter().map(|illust| illust.id).collect(),
+                image_urls: self.album_image_urls(&ctx, &album),
+                caption: caption::build_album_caption(&album),
+                spoiler: album
+                    .iter()
+                    .any(|illust| illust_spoiler(&ctx, illust, false)),
+            }),
+            TurnAction::Single(illust) => {
+                SimulatedOutcome::Push(self.simulate_single_push(&ctx, illust))
+            }
+        };
+
+        Ok(AuthorSimulation {
+            chat_notified,
+            fetched: illusts.len(),
+            cursor: ctx.subscription_state.as_ref().map(|s| s.latest_illust_id),
+            new_illusts,
+            outcome,
+        })
+    }
+
+    /// Mirror the caption and page choice of `process_illust_push` for a first push
+    fn simulate_single_push(&self, ctx: &AuthorContext<'_>, illust: &Illust) -> SimulatedPush {
+        let pages = illust
+            .get_all_image_urls_with_size(ctx.subscription.image_size.resolve(self.image_size));
+        let (kind, image_urls, caption) = if let Some(restriction) = illust.access_restriction() {
+            (
+                SimulatedPushKind::Restricted,
+                Vec::new(),
+                caption::build_restricted_caption(illust, restriction),
+            )
+        } else if illust.is_ugoira() {
+            (
+                SimulatedPushKind::Ugoira,
+                pages.into_iter().take(1).collect(),
+                caption::build_ugoira_caption(illust),
+            )
+        } else {
+            (
+                SimulatedPushKind::Images,
+                pages,
+                caption::build_illust_caption(illust),
+            )
+        };
+
+        SimulatedPush {
+            kind,
+            illust_ids: vec![illust.id],
+            image_urls,
+            caption: self.notifier.process_caption(caption, illust, &ctx.chat),
+            spoiler: illust_spoiler(ctx, illust, false),
+        }
+    }
+
     // ==================== Helper Methods ====================
 
-    /// Schedule next poll with randomized interval
-    async fn schedule_next_poll(&self, task_id: i32) -> Result<()> {
-        let random_interval_sec =
-            rand::rng().random_range(self.min_task_interval_sec..=self.max_task_interval_sec);
-        let next_poll = Local::now() + chrono::Duration::seconds(random_interval_sec as i64);
-        self.repo.update_task_after_poll(task_id, next_poll).await?;
+    /// Whether this failure makes the author count as deleted or private
+    fn is_dead_author(&self, task: &tasks::Model, error: &anyhow::Error) -> bool {
+        // The stored count does not include the failure just recorded
+        self.dead_author_threshold > 0
+            && is_gone(error)
+            && task.failure_count.saturating_add(1) >= self.dead_author_threshold as i32
+    }
+
+    /// Stop polling a dead author and tell each subscribed chat once
+    async fn mark_author_dead(&self, task: &tasks::Model) -> Result<()> {
+        self.repo.mark_task_dead(task.id).await?;
+        warn!(
+            "Author task [{}] {} failed {} times in a row, no longer polling",
+            task.id,
+            task.value,
+            task.failure_count + 1
+        );
+
+        let author = match task.author_name.as_deref() {
+            Some(name) => format!("*{}* \\(ID: `{}`\\)", markdown::escape(name), task.value),
+            None => format!("`{}`", task.value),
+        };
+        let text = format!(
+            "⚠️ 作者 {} 已连续 {} 次无法访问，可能已注销或设为私密，已停止检查\n\n使用 `/unsub {}` 取消订阅；重新订阅该作者会恢复检查",
+            author,
+            task.failure_count + 1,
+            task.value
+        );
+
+        let subscriptions = self.repo.list_subscriptions_by_task(task.id).await?;
+        let chat_ids: HashSet<i64> = subscriptions.iter().map(|sub| sub.chat_id).collect();
+        for chat_id in chat_ids {
+            if let Err(e) = self.notifier.send_text(ChatId(chat_id), &text, false).await {
+                warn!(
+                    "Failed to notify chat {} about dead author {}: {:#}",
+                    chat_id, task.value, e
+                );
+            }
+        }
+
         Ok(())
     }
 
+    /// Schedule next poll with randomized interval, honoring the task's own override
+    async fn schedule_next_poll(&self, task: &tasks::Model) -> Result<()> {
+        let (min_sec, max_sec) = {
+            let config = self.config.load();
+            Self::poll_interval_range(
+                task.poll_interval_min,
+                config.scheduler.min_task_interval_sec,
+                config.scheduler.max_task_interval_sec,
+            )
+        };
+        let random_interval_sec = rand::rng().random_range(min_sec..=max_sec);
+        let next_poll = Utc::now() + chrono::Duration::seconds(random_interval_sec as i64);
+        self.repo.update_task_after_poll(task.id, next_poll).await?;
+        Ok(())
+    }
+
+    /// Interval range (seconds) for the next poll of a task.
+    /// A per-task override gets ±10% jitter so overridden tasks don't poll in lockstep.
+    fn poll_interval_range(
+        poll_interval_min: Option<i32>,
+        default_min_sec: u64,
+        default_max_sec: u64,
+    ) -> (u64, u64) {
+        match poll_interval_min {
+            Some(minutes) if minutes > 0 => {
+                let base_sec = minutes as u64 * 60;
+                (base_sec - base_sec / 10, base_sec + base_sec / 10)
+            }
+            _ => (default_min_sec, default_max_sec),
+        }
+    }
+
     /// Update subscription state in database
     async fn update_subscription_state(
         &self,
-        subscription_id: i32,
+        subscription: &subscriptions::Model,
         state: AuthorState,
     ) -> Result<()> {
-        self.repo
-            .update_subscription_latest_data(
-                subscription_id,
-                Some(SubscriptionState::Author(state)),
-            )
-            .await?;
+        save_subscription_state(&self.repo, subscription, SubscriptionState::Author(state)).await?;
         Ok(())
     }
 
-    fn author_state(latest_illust_id: u64, pending_illust: Option<PendingIllust>) -> AuthorState {
+    fn clear_pending_state(latest_illust_id: u64) -> AuthorState {
         AuthorState {
             latest_illust_id,
-            pending_illust,
+            pending_illust: None,
         }
     }
 
-    fn clear_pending_state(latest_illust_id: u64) -> AuthorState {
-        Self::author_state(latest_illust_id, None)
-    }
-
     async fn save_push_message_record(
         &self,
         chat_id: ChatId,
@@ -239,140 +684,201 @@ impl AuthorEngine {
             Some(illust_id as i64),
         )
         .await;
+        record_sent_artworks(&self.repo, chat_id, &[illust_id], first_message_id).await;
     }
 
-    fn pending_retry_state(
-        latest_illust_id: u64,
-        pending: &PendingIllust,
-        retry_count: u8,
-    ) -> AuthorState {
-        Self::author_state(
-            latest_illust_id,
-            Some(PendingIllust {
-                illust_id: pending.illust_id,
-                sent_pages: pending.sent_pages.clone(),
-                total_pages: pending.total_pages,
-                retry_count,
-            }),
-        )
-    }
-
-    fn partial_push_state(
-        latest_illust_id: u64,
+    /// Hand an unfinished push to the RetryEngine.
+    /// Returns false when retries are disabled (max_retry_count <= 0).
+    async fn enqueue_retry(
+        &self,
+        ctx: &AuthorContext<'_>,
         illust_id: u64,
         sent_pages: Vec<usize>,
         total_pages: usize,
-        retry_count: u8,
-    ) -> AuthorState {
-        Self::author_state(
-            latest_illust_id,
-            Some(PendingIllust {
+        spoiler: bool,
+    ) -> bool {
+        // The retry queue resends to the subscription's own chat, not to mirror targets
+        if self.max_retry_count <= 0 || ctx.mirror_target {
+            return false;
+        }
+
+        let next_attempt_at = (Utc::now() + retry_delay(0)).naive_utc();
+        match self
+            .repo
+            .enqueue_push_retry(
+                ctx.subscription.id,
+                ctx.subscription.chat_id,
                 illust_id,
                 sent_pages,
                 total_pages,
-                retry_count,
-            }),
-        )
+                spoiler,
+                next_attempt_at,
+            )
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                error!(
+                    "Failed to queue retry of illust {} for subscription {}: {:#}",
+                    illust_id, ctx.subscription.id, e
+                );
+                false
+            }
+        }
     }
 
-    // ==================== Dispatcher ====================
+    // ==================== Workers ====================
 
-    /// Dispatcher: Decide between pending retry or new illust processing
-    /// Returns Some(new_state) if state changed, None if no change
-    async fn process_single_author_sub(
+    /// Decide what this subscription's turn pushes, without sending anything
+    ///
+    /// Shared by the poll and `/simulate`, so a dry run follows the same filters.
+    async fn plan_turn<'a>(&self, ctx: &AuthorContext<'_>, illusts: &'a [Illust]) -> TurnPlan<'a> {
+        let chat_id = ChatId(ctx.subscription.chat_id);
+        let last_illust_id = ctx.subscription_state.as_ref().map(|s| s.latest_illust_id);
+
+        // Find new illusts for this subscription
+        let new_illusts: Vec<&Illust> = if let Some(last_id) = last_illust_id {
+            illusts.iter().take_while(|i| i.id > last_id).collect()
+        } else {
+            // First run: only send the latest one
+            illusts.iter().take(1).collect()
+        };
+
+        let action = self.plan_action(ctx, chat_id, &new_illusts).await;
+        TurnPlan {
+            new_illusts,
+            action,
+        }
+    }
+
+    async fn plan_action<'a>(
         &self,
         ctx: &AuthorContext<'_>,
-        illusts: &[Illust],
-    ) -> Result<Option<AuthorState>> {
-        // Check if there's a pending illust to resume
-        if let Some(ref state) = ctx.subscription_state {
-            if let Some(ref pending) = state.pending_illust {
-                // Handle pending first (retry incomplete push)
-                return self.handle_existing_pending(ctx, illusts, pending).await;
+        chat_id: ChatId,
+        new_illusts: &[&'a Illust],
+    ) -> TurnAction<'a> {
+        let Some(newest_illust) = new_illusts.first() else {
+            return TurnAction::NothingNew;
+        };
+
+        // Apply tag filters
+        let filtered_illusts =
+            apply_subscription_tag_filter(ctx.subscription, &ctx.chat, new_illusts.iter().copied());
+
+        // If all filtered out, the cursor skips past them
+        if filtered_illusts.is_empty() {
+            return TurnAction::AllFiltered {
+                newest_illust_id: newest_illust.id,
+            };
+        }
+
+        if ctx.subscription.push_mode == PushMode::Album {
+            let oldest_first: Vec<&Illust> = filtered_illusts.iter().rev().copied().collect();
+            let ids: Vec<u64> = oldest_first.iter().map(|i| i.id).collect();
+            let sent = already_sent_illust_ids(&self.repo, chat_id, &ids).await;
+            let album = Self::album_illusts(&oldest_first, &sent);
+            // A lone work goes through the regular push to keep all of its pages
+            if album.len() > 1 {
+                return TurnAction::Album(album);
             }
         }
 
-        // No pending, process new illusts
-        self.handle_new_illusts(ctx, illusts).await
-    }
+        // *** KEY CHANGE: Only process the OLDEST new illust (last in the filtered list) ***
+        let illust = *filtered_illusts
+            .last()
+            .expect("filtered_illusts is not empty");
 
-    // ==================== Workers ====================
+        // Already delivered by another subscription of this chat (e.g. a ranking)
+        if !already_sent_illust_ids(&self.repo, chat_id, &[illust.id])
+            .await
+            .is_empty()
+        {
+            return TurnAction::AlreadySent {
+                illust_id: illust.id,
+            };
+        }
 
-    /// Worker: Handle retry of existing pending illust
-    /// Returns Some(new_state) if state changed, None if no change
-    async fn handle_existing_pending(
+        TurnAction::Single(illust)
+    }
+
+    /// Worker: Push the oldest new illust, or an album of them in album mode
+    /// Returns the new state if it changed, and whether anything was sent
+    async fn handle_new_illusts(
         &self,
         ctx: &AuthorContext<'_>,
         illusts: &[Illust],
-        pending: &PendingIllust,
-    ) -> Result<Option<AuthorState>> {
+    ) -> Result<SubscriptionTurn> {
         let chat_id = ChatId(ctx.subscription.chat_id);
-        let state = ctx
-            .subscription_state
-            .as_ref()
-            .context("Missing subscription state for pending illust")?;
-
-        // Check retry limit
-        if self.max_retry_count <= 0 {
-            // Retry disabled, abandon immediately
-            warn!(
-                "Retry disabled (max_retry_count={}), abandoning pending illust {} for chat {}",
-                self.max_retry_count, pending.illust_id, chat_id
+        let plan = self.plan_turn(ctx, illusts).await;
+
+        if !plan.new_illusts.is_empty() {
+            info!(
+                "Found {} new illusts for subscription {} (chat {}): {:?}",
+                plan.new_illusts.len(),
+                ctx.subscription.id,
+                chat_id,
+                plan.new_illusts.iter().map(|i| i.id).collect::<Vec<_>>()
             );
-            return Ok(Some(Self::clear_pending_state(state.latest_illust_id)));
         }
 
-        // Compare retry_count (u8) with max_retry_count (i32) safely
-        if (pending.retry_count as i32) >= self.max_retry_count {
-            // Max retries reached, abandon
-            warn!(
-                "Max retry count reached ({}/{}), abandoning pending illust {} for chat {}",
-                pending.retry_count, self.max_retry_count, pending.illust_id, chat_id
-            );
-            return Ok(Some(Self::clear_pending_state(state.latest_illust_id)));
+        match plan.action {
+            TurnAction::NothingNew => Ok(SubscriptionTurn::idle(None)),
+            TurnAction::AllFiltered { newest_illust_id } => Ok(SubscriptionTurn::idle(Some(
+                Self::clear_pending_state(newest_illust_id),
+            ))),
+            TurnAction::AlreadySent { illust_id } => {
+                info!(
+                    "Illust {} was already sent to chat {}, skipping",
+                    illust_id, chat_id
+                );
+                Ok(SubscriptionTurn::idle(Some(Self::clear_pending_state(
+                    illust_id,
+                ))))
+            }
+            TurnAction::Album(album) => {
+                let previous_illust_id = ctx
+                    .subscription_state
+                    .as_ref()
+                    .map_or(0, |s| s.latest_illust_id);
+                self.push_album(ctx, &album, previous_illust_id)
+                    .await
+                    .map(SubscriptionTurn::pushed)
+            }
+            TurnAction::Single(illust) => self.push_single(ctx, illust).await,
         }
+    }
 
-        // Find the illust in API response
-        let Some(illust) = illusts.iter().find(|i| i.id == pending.illust_id) else {
-            // Pending illust not found (deleted or too old), abandon it
-            warn!(
-                "Pending illust {} not found in API response, abandoning",
-                pending.illust_id
-            );
-            return Ok(Some(Self::clear_pending_state(state.latest_illust_id)));
-        };
-
-        info!(
-            "Resuming pending illust {} ({}/{} pages sent, retry {}/{})",
-            pending.illust_id,
-            pending.sent_pages.len(),
-            pending.total_pages,
-            pending.retry_count,
-            self.max_retry_count
-        );
-
-        // Calculate remaining pages
-        let total_pages = illust.get_all_image_urls_with_size(self.image_size).len();
-        let remaining_pages: Vec<usize> = (0..total_pages)
-            .filter(|i| !pending.sent_pages.contains(i))
-            .collect();
-
-        if remaining_pages.is_empty() {
-            // All pages already sent, mark as complete
-            return Ok(Some(Self::clear_pending_state(pending.illust_id)));
-        }
+    /// Worker: Push one illust with all of its pages
+    async fn push_single(
+        &self,
+        ctx: &AuthorContext<'_>,
+        illust: &Illust,
+    ) -> Result<SubscriptionTurn> {
+        let chat_id = ChatId(ctx.subscription.chat_id);
 
-        // Send remaining pages
+        // Push this single illust
+        let has_spoiler = illust_spoiler(ctx, illust, false);
         let push_result = process_illust_push(
             &self.notifier,
             &self.pixiv_client,
             ctx,
             illust,
-            &pending.sent_pages,
+            &Vec::new(),
             self.image_size,
+            false,
         )
         .await?;
 
+        let delivered = !matches!(push_result, PushResult::Failure { .. });
+        log_push(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            usize::from(delivered),
+            delivered,
+        )
+        .await;
+
         // Calculate new state based on result
         let new_state = match push_result {
             PushResult::Success {
@@ -380,7 +886,7 @@ impl AuthorEngine {
                 first_message_id,
             } => {
                 info!(
-                    "✅ Completed pending illust {} for chat {}",
+                    "✅ Successfully sent illust {} to chat {}",
                     illust_id, chat_id
                 );
                 self.save_push_message_record(
@@ -411,177 +917,176 @@ impl AuthorEngine {
                     first_message_id,
                 )
                 .await;
-                Self::partial_push_state(
-                    state.latest_illust_id,
-                    illust_id,
-                    sent_pages,
-                    total_pages,
-                    pending.retry_count.saturating_add(1),
-                )
+                // The rest is resent by the RetryEngine (or given up when retries are off)
+                self.enqueue_retry(ctx, illust_id, sent_pages, total_pages, has_spoiler)
+                    .await;
+                Self::clear_pending_state(illust_id)
             }
             PushResult::Failure { illust_id } => {
-                // Use saturating_add to prevent u8 overflow
-                let new_retry_count = pending.retry_count.saturating_add(1);
-                // Check if we should give up after this failure (compare u8 with i32 safely)
-                if self.max_retry_count > 0 && (new_retry_count as i32) >= self.max_retry_count {
-                    error!(
-                        "❌ Failed to send pending illust {} to chat {}, max retries reached ({}/{}), abandoning",
-                        illust_id, chat_id, new_retry_count, self.max_retry_count
-                    );
-                    Self::clear_pending_state(state.latest_illust_id)
-                } else {
+                let total_pages = illust.get_all_image_urls_with_size(self.image_size).len();
+                if !self
+                    .enqueue_retry(ctx, illust_id, Vec::new(), total_pages, has_spoiler)
+                    .await
+                {
                     error!(
-                        "❌ Failed to send pending illust {} to chat {}, will retry (attempt {}/{})",
-                        illust_id, chat_id, new_retry_count, self.max_retry_count
+                        "❌ Failed to send illust {} to chat {}, will retry next poll",
+                        illust_id, chat_id
                     );
-                    // Increment retry count and keep pending state
-                    Self::pending_retry_state(state.latest_illust_id, pending, new_retry_count)
+                    // Don't update state, retry next tick
+                    return Ok(SubscriptionTurn::pushed(None));
                 }
+                error!(
+                    "❌ Failed to send illust {} to chat {}, queued for retry",
+                    illust_id, chat_id
+                );
+                Self::clear_pending_state(illust_id)
             }
         };
 
-        Ok(Some(new_state))
+        Ok(SubscriptionTurn::pushed(Some(new_state)))
+    }
+
+    /// Works for one album: the oldest unsent ones, up to a media group,
+    /// stopping before a restricted work that has to be pushed on its own
+    fn album_illusts<'a>(oldest_first: &[&'a Illust], sent: &HashSet<u64>) -> Vec<&'a Illust> {
+        oldest_first
+            .iter()
+            .copied()
+            .filter(|illust| !sent.contains(&illust.id))
+            .take_while(|illust| illust.access_restriction().is_none())
+            .take(caption::MAX_PER_GROUP)
+            .collect()
     }
 
-    /// Worker: Select and push the oldest new illust
+    /// Cursor after an album push: the newest work before the first one that failed.
+    /// Works sent after a failure are recorded as sent, so the retry skips them.
+    fn album_latest_illust_id(album_ids: &[u64], failed_indices: &[usize], previous: u64) -> u64 {
+        let sent_prefix = failed_indices
+            .iter()
+            .min()
+            .copied()
+            .unwrap_or(album_ids.len());
+        sent_prefix
+            .checked_sub(1)
+            .and_then(|i| album_ids.get(i))
+            .copied()
+            .unwrap_or(previous)
+    }
+
+    /// First page of each work of an album, in the subscription's image size
+    fn album_image_urls(&self, ctx: &AuthorContext<'_>, album: &[&Illust]) -> Vec<String> {
+        let image_size = ctx.subscription.image_size.resolve(self.image_size);
+        album
+            .iter()
+            .map(|illust| {
+                illust
+                    .get_all_image_urls_with_size(image_size)
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| illust.image_urls.large.clone())
+            })
+            .collect()
+    }
+
+    /// Worker: Push several new illusts (oldest first) as one media group, first page of each
     /// Returns Some(new_state) if state changed, None if no change
-    async fn handle_new_illusts(
+    async fn push_album(
         &self,
         ctx: &AuthorContext<'_>,
-        illusts: &[Illust],
+        album: &[&Illust],
+        previous_illust_id: u64,
     ) -> Result<Option<AuthorState>> {
         let chat_id = ChatId(ctx.subscription.chat_id);
-        let last_illust_id = ctx.subscription_state.as_ref().map(|s| s.latest_illust_id);
-
-        // Find new illusts for this subscription
-        let new_illusts: Vec<_> = if let Some(last_id) = last_illust_id {
-            illusts.iter().take_while(|i| i.id > last_id).collect()
-        } else {
-            // First run: only send the latest one
-            illusts.iter().take(1).collect()
-        };
+        let album_ids: Vec<u64> = album.iter().map(|illust| illust.id).collect();
+
+        let image_urls = self.album_image_urls(ctx, album);
+        let album_caption = caption::build_album_caption(album);
+        let has_spoiler = album
+            .iter()
+            .any(|illust| illust_spoiler(ctx, illust, false));
+
+        let send_result = self
+            .notifier
+            .for_subscription(ctx.subscription)
+            .notify_with_images(chat_id, &image_urls, Some(&album_caption), has_spoiler)
+            .await;
+
+        let delivered: Vec<u64> = send_result
+            .succeeded_indices
+            .iter()
+            .filter_map(|&i| album_ids.get(i).copied())
+            .collect();
+        log_push(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            delivered.len(),
+            !delivered.is_empty(),
+        )
+        .await;
 
-        if new_illusts.is_empty() {
+        if send_result.is_complete_failure() {
+            error!(
+                "❌ Failed to send album {:?} to chat {}, will retry next poll",
+                album_ids, chat_id
+            );
             return Ok(None);
         }
 
-        info!(
-            "Found {} new illusts for subscription {} (chat {}): {:?}",
-            new_illusts.len(),
-            ctx.subscription.id,
-            chat_id,
-            new_illusts.iter().map(|i| i.id).collect::<Vec<_>>()
-        );
-
-        let newest_illust_id = new_illusts.first().map(|i| i.id);
-
-        // Apply tag filters
-        let filtered_illusts =
-            apply_subscription_tag_filter(ctx.subscription, &ctx.chat, new_illusts.iter().copied());
-
-        // If all filtered out, update cursor and return
-        if filtered_illusts.is_empty() {
-            return Ok(newest_illust_id.map(Self::clear_pending_state));
+        if send_result.is_complete_success() {
+            info!(
+                "✅ Successfully sent album {:?} to chat {}",
+                album_ids, chat_id
+            );
+        } else {
+            warn!(
+                "⚠️  Partially sent album {:?} to chat {} ({}/{} works)",
+                album_ids,
+                chat_id,
+                delivered.len(),
+                album_ids.len()
+            );
         }
 
-        // *** KEY CHANGE: Only process the OLDEST new illust (last in the filtered list) ***
-        let illust = filtered_illusts
-            .last()
-            .expect("filtered_illusts is not empty");
-
-        // Push this single illust
-        let push_result = process_illust_push(
-            &self.notifier,
-            &self.pixiv_client,
-            ctx,
-            illust,
-            &Vec::new(),
-            self.image_size,
+        save_first_message_record(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            send_result.first_message_id,
+            Some(album_ids[0] as i64),
         )
-        .await?;
-
-        // Calculate new state based on result
-        let new_state = match push_result {
-            PushResult::Success {
-                illust_id,
-                first_message_id,
-            } => {
-                info!(
-                    "✅ Successfully sent illust {} to chat {}",
-                    illust_id, chat_id
-                );
-                self.save_push_message_record(
-                    chat_id,
-                    ctx.subscription.id,
-                    illust_id,
-                    first_message_id,
-                )
-                .await;
-                Self::clear_pending_state(illust_id)
-            }
-            PushResult::Partial {
-                illust_id,
-                sent_pages,
-                total_pages,
-                first_message_id,
-            } => {
-                warn!(
-                    "⚠️  Partially sent illust {} ({}/{} pages)",
-                    illust_id,
-                    sent_pages.len(),
-                    total_pages
-                );
-                self.save_push_message_record(
-                    chat_id,
-                    ctx.subscription.id,
-                    illust_id,
-                    first_message_id,
-                )
-                .await;
-                Self::partial_push_state(
-                    last_illust_id.unwrap_or(0),
-                    illust_id,
-                    sent_pages,
-                    total_pages,
-                    0,
-                )
-            }
-            PushResult::Failure { illust_id } => {
-                error!(
-                    "❌ Failed to send illust {} to chat {}, will retry next poll",
-                    illust_id, chat_id
-                );
-                // Don't update state, retry next tick
-                return Ok(None);
-            }
-        };
-
-        Ok(Some(new_state))
+        .await;
+        record_sent_batch(&self.repo, chat_id, &album_ids, &send_result).await;
+
+        // One state update covers the whole album
+        Ok(Some(Self::clear_pending_state(
+            Self::album_latest_illust_id(
+                &album_ids,
+                &send_result.failed_indices,
+                previous_illust_id,
+            ),
+        )))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::AuthorEngine;
-    use crate::db::types::{AuthorState, PendingIllust};
+    use crate::db::types::AuthorState;
 
     #[test]
-    fn author_state_keeps_latest_id_and_pending_payload() {
-        let pending = PendingIllust {
-            illust_id: 123,
-            sent_pages: vec![0, 2],
-            total_pages: 4,
-            retry_count: 1,
-        };
-
-        let state = AuthorEngine::author_state(999, Some(pending.clone()));
-
+    fn poll_interval_range_prefers_task_override() {
         assert_eq!(
-            state,
-            AuthorState {
-                latest_illust_id: 999,
-                pending_illust: Some(pending),
-            }
+            AuthorEngine::poll_interval_range(Some(30), 7200, 10800),
+            (1620, 1980)
+        );
+        assert_eq!(
+            AuthorEngine::poll_interval_range(None, 7200, 10800),
+            (7200, 10800)
+        );
+        assert_eq!(
+            AuthorEngine::poll_interval_range(Some(0), 7200, 10800),
+            (7200, 10800)
         );
     }
 
@@ -599,46 +1104,11 @@ mod tests {
     }
 
     #[test]
-    fn pending_retry_state_preserves_progress_and_updates_retry_count() {
-        let pending = PendingIllust {
-            illust_id: 321,
-            sent_pages: vec![0, 1],
-            total_pages: 5,
-            retry_count: 0,
-        };
-
-        let state = AuthorEngine::pending_retry_state(654, &pending, 2);
+    fn album_latest_illust_id_stops_before_first_failure() {
+        let album = [10, 11, 12, 13];
 
-        assert_eq!(
-            state,
-            AuthorState {
-                latest_illust_id: 654,
-                pending_illust: Some(PendingIllust {
-                    illust_id: 321,
-                    sent_pages: vec![0, 1],
-                    total_pages: 5,
-                    retry_count: 2,
-                }),
-            }
-        );
-        assert_eq!(pending.retry_count, 0);
-    }
-
-    #[test]
-    fn partial_push_state_starts_new_pending_retry_from_partial_send() {
-        let state = AuthorEngine::partial_push_state(777, 888, vec![0, 3], 6, 0);
-
-        assert_eq!(
-            state,
-            AuthorState {
-                latest_illust_id: 777,
-                pending_illust: Some(PendingIllust {
-                    illust_id: 888,
-                    sent_pages: vec![0, 3],
-                    total_pages: 6,
-                    retry_count: 0,
-                }),
-            }
-        );
+        assert_eq!(AuthorEngine::album_latest_illust_id(&album, &[], 5), 13);
+        assert_eq!(AuthorEngine::album_latest_illust_id(&album, &[3, 2], 5), 11);
+        assert_eq!(AuthorEngine::album_latest_illust_id(&album, &[0], 5), 5);
     }
 }
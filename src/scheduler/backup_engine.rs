@@ -0,0 +1,283 @@
+use crate::bot::notifier::Notifier;
+use crate::config::SubscriptionBackupConfig;
+use crate::db::entities::{chats, subscriptions, tasks};
+use crate::db::repo::Repo;
+use crate::scheduler::digest_engine::next_digest_time;
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use teloxide::types::ChatId;
+use teloxide::utils::markdown;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+
+/// Bumped when the snapshot layout changes incompatibly
+const SNAPSHOT_VERSION: u32 = 1;
+
+const SNAPSHOT_PREFIX: &str = "subscriptions_";
+const SNAPSHOT_SUFFIX: &str = ".json";
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    version: u32,
+    generated_at: NaiveDateTime,
+    chats: Vec<ChatSnapshot<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChatSnapshot<'a> {
+    chat: &'a chats::Model,
+    subscriptions: Vec<SubscriptionSnapshot<'a>>,
+}
+
+#[derive(Serialize)]
+struct SubscriptionSnapshot<'a> {
+    subscription: &'a subscriptions::Model,
+    task: &'a tasks::Model,
+}
+
+/// Engine that takes a weekly JSON snapshot of every chat's settings and subscriptions
+///
+/// The snapshot is sent to the owner and/or written to a directory that keeps the
+/// newest `keep` files, so a lost database can be rebuilt without manual exports.
+pub struct SubscriptionBackupEngine {
+    repo: Arc<Repo>,
+    notifier: Notifier,
+    owner_id: Option<i64>,
+    weekday: Weekday,
+    time: NaiveTime,
+    send_to_owner: bool,
+    dir: Option<PathBuf>,
+    keep: usize,
+}
+
+impl SubscriptionBackupEngine {
+    pub fn new(
+        repo: Arc<Repo>,
+        notifier: Notifier,
+        owner_id: Option<i64>,
+        config: &SubscriptionBackupConfig,
+    ) -> Result<Self> {
+        let weekday = config
+            .weekday
+            .parse::<Weekday>()
+            .map_err(|_| anyhow::anyhow!("Invalid backup weekday '{}'", config.weekday))?;
+        let time = NaiveTime::parse_from_str(&config.time, "%H:%M")
+            .context("Invalid backup time format (expected HH:MM)")?;
+        let dir = config
+            .dir
+            .as_deref()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
+        if dir.is_none() && !config.send_to_owner {
+            anyhow::bail!("Subscription backup has neither a dir nor send_to_owner");
+        }
+        Ok(Self {
+            repo,
+            notifier,
+            owner_id,
+            weekday,
+            time,
+            send_to_owner: config.send_to_owner,
+            dir,
+            keep: config.keep,
+        })
+    }
+
+    /// Main scheduler loop - runs indefinitely at the configured weekday and time
+    pub async fn run(&self) {
+        info!(
+            "🚀 Subscription backup engine started (every {} at {})",
+            self.weekday,
+            self.time.format("%H:%M")
+        );
+
+        loop {
+            let now = Local::now();
+            let next_naive = next_digest_time(now.naive_local(), self.weekday, self.time);
+            let Some(next_execution) = Local.from_local_datetime(&next_naive).earliest() else {
+                error!(
+                    "Backup time {} does not exist in local time zone",
+                    next_naive
+                );
+                sleep(Duration::from_secs(3600)).await;
+                continue;
+            };
+            let duration_until_execution = (next_execution - now).to_std().unwrap_or_default();
+
+            info!(
+                "⏰ Next subscription backup at: {} (in {} seconds)",
+                next_execution.format("%Y-%m-%d %H:%M:%S"),
+                duration_until_execution.as_secs()
+            );
+
+            sleep(duration_until_execution).await;
+
+            if let Err(e) = self.backup().await {
+                error!("Subscription backup error: {:#}", e);
+            }
+
+            // Sleep a bit to avoid executing twice in the same minute
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+
+    async fn backup(&self) -> Result<()> {
+        let chats = self.repo.list_chats().await?;
+        let subscriptions = self.repo.list_all_subscriptions().await?;
+        let now = Utc::now().naive_utc();
+        let json = build_snapshot(&chats, &subscriptions, now)?;
+
+        let filename = format!(
+            "{}{}{}",
+            SNAPSHOT_PREFIX,
+            now.format("%Y%m%d_%H%M%S"),
+            SNAPSHOT_SUFFIX
+        );
+
+        // Without a backup dir the file only lives long enough to be uploaded
+        let temp_dir;
+        let dir = match &self.dir {
+            Some(dir) => dir.as_path(),
+            None => {
+                temp_dir = tempfile::tempdir().context("Failed to create temp dir")?;
+                temp_dir.path()
+            }
+        };
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create backup dir {}", dir.display()))?;
+        let path = dir.join(&filename);
+        tokio::fs::write(&path, json)
+            .await
+            .with_context(|| format!("Failed to write backup {}", path.display()))?;
+        info!(
+            "✅ Backed up {} subscription(s) of {} chat(s) to {}",
+            subscriptions.len(),
+            chats.len(),
+            path.display()
+        );
+
+        if self.dir.is_some() {
+            match rotate_snapshots(dir, self.keep) {
+                Ok(0) => {}
+                Ok(removed) => info!("Removed {} old subscription backup(s)", removed),
+                Err(e) => warn!("Failed to rotate subscription backups: {:#}", e),
+            }
+        }
+
+        if self.send_to_owner {
+            let Some(owner_id) = self.owner_id else {
+                warn!("Subscription backup send_to_owner enabled but no owner_id configured");
+                return Ok(());
+            };
+            let caption = format!(
+                "🗄 订阅备份：{} 个聊天，{} 条订阅",
+                chats.len(),
+                subscriptions.len()
+            );
+            self.notifier
+                .send_document(
+                    ChatId(owner_id),
+                    &path,
+                    &filename,
+                    &markdown::escape(&caption),
+                )
+                .await
+                .context("Failed to send subscription backup to owner")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Snapshot JSON with the chats in ID order, each followed by its subscriptions
+fn build_snapshot(
+    chats: &[chats::Model],
+    subscriptions: &[(subscriptions::Model, tasks::Model)],
+    generated_at: NaiveDateTime,
+) -> Result<String> {
+    let chats = chats
+        .iter()
+        .map(|chat| ChatSnapshot {
+            chat,
+            subscriptions: subscriptions
+                .iter()
+                .filter(|(sub, _)| sub.chat_id == chat.id)
+                .map(|(subscription, task)| SubscriptionSnapshot { subscription, task })
+                .collect(),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&Snapshot {
+        version: SNAPSHOT_VERSION,
+        generated_at,
+        chats,
+    })
+    .context("Failed to serialize subscription backup")
+}
+
+/// Delete all but the newest `keep` snapshots in `dir` (0 keeps everything)
+fn rotate_snapshots(dir: &Path, keep: usize) -> Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read backup dir {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(SNAPSHOT_SUFFIX)
+                })
+        })
+        .collect();
+    // Timestamped names sort oldest first
+    snapshots.sort();
+
+    let excess = snapshots.len().saturating_sub(keep);
+    for path in &snapshots[..excess] {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove old backup {}", path.display()))?;
+    }
+    Ok(excess)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_snapshots_keeps_newest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for stamp in ["20260104_040000", "20260111_040000", "20260118_040000"] {
+            std::fs::write(
+                dir.path().join(format!("subscriptions_{}.json", stamp)),
+                "{}",
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+
+        assert_eq!(rotate_snapshots(dir.path(), 2).unwrap(), 1);
+        assert_eq!(rotate_snapshots(dir.path(), 0).unwrap(), 0);
+
+        let mut left: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "notes.txt",
+                "subscriptions_20260111_040000.json",
+                "subscriptions_20260118_040000.json"
+            ]
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use crate::bot::notifier::Notifier;
+use crate::config::{ImageSizePolicy, SharedConfig};
+use crate::db::entities::{chats, subscriptions, tasks};
+use crate::db::repo::Repo;
+use crate::db::types::{BookmarksState, SubscriptionState, TaskType};
+use crate::pixiv::client::PixivClient;
+use crate::scheduler::helpers::{
+    already_sent_illust_ids, apply_subscription_tag_filter, bookmarks_subscription_state,
+    get_chat_if_should_notify, illust_spoiler, log_push, process_illust_push, record_sent_artworks,
+    record_task_result, save_first_message_record, save_subscription_state, AuthorContext,
+    PushResult, SubscriptionDelay,
+};
+use crate::scheduler::retry_engine::retry_delay;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use pixiv_client::Illust;
+use rand::RngExt;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Bookmarks fetched per poll, one page of the Pixiv API
+const BOOKMARKS_PER_POLL: usize = 30;
+
+/// Bookmark tasks executed per tick
+const TASKS_PER_TICK: u64 = 5;
+
+/// Works pushed to one subscription per poll; the rest wait for the next poll
+const MAX_PUSHES_PER_POLL: usize = 5;
+
+/// Handled works remembered per subscription, a few pages of bookmarks
+const PUSHED_IDS_CAP: usize = 300;
+
+/// Engine for `/subbookmarks`: pushes works a Pixiv user newly bookmarked publicly
+pub struct BookmarkEngine {
+    repo: Arc<Repo>,
+    pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
+    notifier: Notifier,
+    /// Poll intervals are read from here so config reloads apply to the next poll
+    config: SharedConfig,
+    tick_interval_sec: u64,
+    max_retry_count: i32,
+    image_size: pixiv_client::ImageSize,
+    subscription_delay: SubscriptionDelay,
+}
+
+impl BookmarkEngine {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: Arc<Repo>,
+        pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
+        notifier: Notifier,
+        config: SharedConfig,
+        tick_interval_sec: u64,
+        max_retry_count: i32,
+        image_sizes: ImageSizePolicy,
+        subscription_delay: SubscriptionDelay,
+    ) -> Self {
+        Self {
+            repo,
+            pixiv_client,
+            notifier,
+            config,
+            tick_interval_sec,
+            max_retry_count,
+            image_size: image_sizes.push,
+            subscription_delay,
+        }
+    }
+
+    /// Main scheduler loop - runs indefinitely
+    pub async fn run(&self) {
+        info!("🚀 Bookmark engine started");
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.tick_interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.tick().await {
+                error!("Bookmark engine tick error: {:#}", e);
+            }
+        }
+    }
+
+    async fn tick(&self) -> Result<()> {
+        // Pause polling while the token refresher is failing
+        if !self.pixiv_client.read().await.is_authenticated() {
+            debug!("Pixiv is not authenticated, skipping bookmark tick");
+            return Ok(());
+        }
+
+        let tasks = self
+            .repo
+            .get_pending_tasks_by_types(&[TaskType::Bookmarks], TASKS_PER_TICK)
+            .await?;
+
+        for task in tasks {
+            debug!("⚙️  Executing bookmark task [{}] {}", task.id, task.value);
+
+            let result = self.execute_task(&task).await;
+            record_task_result(&self.repo, task.id, &result).await;
+            if let Err(e) = result {
+                error!("Bookmark task [{}] execution failed: {:#}", task.id, e);
+                // On error, still update the poll time to avoid immediate retry
+                self.schedule_next_poll(&task).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the user's latest bookmarks once and push the new ones to every subscription
+    async fn execute_task(&self, task: &tasks::Model) -> Result<()> {
+        let user_id: u64 = task.value.parse()?;
+
+        let pixiv = self.pixiv_client.read().await;
+        let illusts = pixiv
+            .get_user_bookmarks(user_id, BOOKMARKS_PER_POLL)
+            .await?;
+        drop(pixiv);
+
+        let subscriptions = self.repo.list_active_subscriptions_by_task(task.id).await?;
+
+        for subscription in subscriptions {
+            let chat = match get_chat_if_should_notify(&self.repo, subscription.chat_id).await {
+                Ok(Some(chat)) => chat,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Failed to process chat {}: {:#}", subscription.chat_id, e);
+                    continue;
+                }
+            };
+
+            // Hold the chat's turn for the whole push so other pushes can't interleave
+            let _turn = self.notifier.begin_push(ChatId(subscription.chat_id)).await;
+
+            let pushed = match self
+                .push_new_bookmarks(&subscription, chat, &illusts)
+                .await
+                .with_context(|| format!("Failed to process subscription {}", subscription.id))
+            {
+                Ok(pushed) => pushed,
+                Err(e) => {
+                    error!("{:#}", e);
+                    // The error may have come mid-push
+                    true
+                }
+            };
+
+            // Jittered delay between subscriptions, only when something was sent
+            if pushed {
+                self.subscription_delay.wait().await;
+            }
+        }
+
+        self.schedule_next_poll(task).await
+    }
+
+    /// Push a subscription's new bookmarks, oldest first, and remember them as handled
+    ///
+    /// Returns whether anything was sent to the chat.
+    async fn push_new_bookmarks(
+        &self,
+        subscription: &subscriptions::Model,
+        chat: chats::Model,
+        illusts: &[Illust],
+    ) -> Result<bool> {
+        let chat_id = ChatId(subscription.chat_id);
+        let state = bookmarks_subscription_state(subscription);
+        let new_illusts = new_bookmarks(state.as_ref(), illusts);
+
+        if new_illusts.is_empty() && state.is_some() {
+            return Ok(false);
+        }
+
+        let ids: Vec<u64> = new_illusts.iter().map(|illust| illust.id).collect();
+        let sent = already_sent_illust_ids(&self.repo, chat_id, &ids).await;
+        let ctx = AuthorContext {
+            subscription,
+            chat,
+            subscription_state: None,
+            mirror_target: false,
+        };
+
+        let mut handled = Vec::new();
+        let mut pushes = 0;
+        for illust in new_illusts {
+            if apply_subscription_tag_filter(subscription, &ctx.chat, [illust]).is_empty()
+                || sent.contains(&illust.id)
+            {
+                handled.push(illust.id);
+                continue;
+            }
+            if pushes == MAX_PUSHES_PER_POLL {
+                break;
+            }
+            pushes += 1;
+
+            if !self.push_illust(&ctx, illust).await? {
+                // Retried on the next poll, keep the later works behind it
+                break;
+            }
+            handled.push(illust.id);
+        }
+
+        let mut pushed_ids = match state {
+            Some(state) => state.pushed_ids,
+            // First run: everything bookmarked so far counts as seen
+            None => illusts.iter().rev().map(|illust| illust.id).collect(),
+        };
+        for id in handled {
+            if !pushed_ids.contains(&id) {
+                pushed_ids.push(id);
+            }
+        }
+        let mut new_state = BookmarksState { pushed_ids };
+        new_state.trim_pushed(PUSHED_IDS_CAP);
+        save_subscription_state(
+            &self.repo,
+            subscription,
+            SubscriptionState::Bookmarks(new_state),
+        )
+        .await?;
+
+        Ok(pushes > 0)
+    }
+
+    /// Push one work with all of its pages
+    ///
+    /// Returns false when it failed and could not be handed to the retry queue.
+    async fn push_illust(&self, ctx: &AuthorContext<'_>, illust: &Illust) -> Result<bool> {
+        let chat_id = ChatId(ctx.subscription.chat_id);
+        let has_spoiler = illust_spoiler(ctx, illust, false);
+        let push_result = process_illust_push(
+            &self.notifier,
+            &self.pixiv_client,
+            ctx,
+            illust,
+            &[],
+            self.image_size,
+            false,
+        )
+        .await?;
+
+        let delivered = !matches!(push_result, PushResult::Failure { .. });
+        log_push(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            usize::from(delivered),
+            delivered,
+        )
+        .await;
+
+        match push_result {
+            PushResult::Success {
+                illust_id,
+                first_message_id,
+            } => {
+                info!(
+                    "✅ Successfully sent bookmarked illust {} to chat {}",
+                    illust_id, chat_id
+                );
+                self.save_push_message_record(ctx, illust_id, first_message_id)
+                    .await;
+                Ok(true)
+            }
+            PushResult::Partial {
+                illust_id,
+                sent_pages,
+                total_pages,
+                first_message_id,
+            } => {
+                warn!(
+                    "⚠️  Partially sent bookmarked illust {} ({}/{} pages)",
+                    illust_id,
+                    sent_pages.len(),
+                    total_pages
+                );
+                self.save_push_message_record(ctx, illust_id, first_message_id)
+                    .await;
+                self.enqueue_retry(ctx, illust_id, sent_pages, total_pages, has_spoiler)
+                    .await;
+                Ok(true)
+            }
+            PushResult::Failure { illust_id } => {
+                let total_pages = illust.get_all_image_urls_with_size(self.image_size).len();
+                let queued = self
+                    .enqueue_retry(ctx, illust_id, Vec::new(), total_pages, has_spoiler)
+                    .await;
+                error!(
+                    "❌ Failed to send bookmarked illust {} to chat {}, {}",
+                    illust_id,
+                    chat_id,
+                    if queued {
+                        "queued for retry"
+                    } else {
+                        "will retry next poll"
+                    }
+                );
+                Ok(queued)
+            }
+        }
+    }
+
+    async fn save_push_message_record(
+        &self,
+        ctx: &AuthorContext<'_>,
+        illust_id: u64,
+        first_message_id: Option<i32>,
+    ) {
+        let chat_id = ChatId(ctx.subscription.chat_id);
+        save_first_message_record(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            first_message_id,
+            Some(illust_id as i64),
+        )
+        .await;
+        record_sent_artworks(&self.repo, chat_id, &[illust_id], first_message_id).await;
+    }
+
+    /// Hand an unfinished push to the RetryEngine.
+    /// Returns false when retries are disabled (max_retry_count <= 0).
+    async fn enqueue_retry(
+        &self,
+        ctx: &AuthorContext<'_>,
+        illust_id: u64,
+        sent_pages: Vec<usize>,
+        total_pages: usize,
+        spoiler: bool,
+    ) -> bool {
+        if self.max_retry_count <= 0 {
+            return false;
+        }
+
+        let next_attempt_at = (Utc::now() + retry_delay(0)).naive_utc();
+        match self
+            .repo
+            .enqueue_push_retry(
+                ctx.subscription.id,
+                ctx.subscription.chat_id,
+                illust_id,
+                sent_pages,
+                total_pages,
+                spoiler,
+                next_attempt_at,
+            )
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                error!(
+                    "Failed to queue retry of illust {} for subscription {}: {:#}",
+                    illust_id, ctx.subscription.id, e
+                );
+                false
+            }
+        }
+    }
+
+    /// Schedule next poll with randomized interval, honoring the task's own override
+    async fn schedule_next_poll(&self, task: &tasks::Model) -> Result<()> {
+        let (min_sec, max_sec) = match task.poll_interval_min {
+            Some(minutes) if minutes > 0 => {
+                let base_sec = minutes as u64 * 60;
+                (base_sec - base_sec / 10, base_sec + base_sec / 10)
+            }
+            _ => {
+                let config = self.config.load();
+                (
+                    config.scheduler.min_task_interval_sec,
+                    config.scheduler.max_task_interval_sec,
+                )
+            }
+        };
+        let random_interval_sec = rand::rng().random_range(min_sec..=max_sec);
+        let next_poll = Utc::now() + chrono::Duration::seconds(random_interval_sec as i64);
+        self.repo.update_task_after_poll(task.id, next_poll).await?;
+        Ok(())
+    }
+}
+
+/// Works bookmarked since the subscription last looked, oldest bookmark first
+///
+/// Bookmarks are listed newest-bookmarked first. On the first poll only the most
+/// recent bookmark is pushed, like a new author subscription.
+fn new_bookmarks<'a>(state: Option<&BookmarksState>, illusts: &'a [Illust]) -> Vec<&'a Illust> {
+    match state {
+        Some(state) => illusts
+            .iter()
+            .rev()
+            .filter(|illust| !state.pushed_ids.contains(&illust.id))
+            .collect(),
+        None => illusts.iter().take(1).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn illust(id: u64) -> Illust {
+        serde_json::from_value(json!({
+            "id": id,
+            "title": format!("illust-{id}"),
+            "type": "illust",
+            "image_urls": {
+                "square_medium": "square",
+                "medium": "medium",
+                "large": "large",
+                "original": "original"
+            },
+            "caption": "",
+            "restrict": 0,
+            "user": {
+                "id": 67890,
+                "name": "Author",
+                "account": "author"
+            },
+            "tags": [],
+            "create_date": "2026-01-01T00:00:00+00:00",
+            "page_count": 1,
+            "width": 100,
+            "height": 100,
+            "sanity_level": 2,
+            "x_restrict": 0,
+            "series": null,
+            "meta_single_page": { "original_image_url": "original" },
+            "meta_pages": [],
+            "total_view": 1,
+            "total_bookmarks": 2,
+            "is_bookmarked": false,
+            "visible": true,
+            "is_muted": false,
+            "total_comments": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn new_bookmarks_are_unseen_works_oldest_first() {
+        // Newest bookmark first, illust IDs are not in bookmark order
+        let illusts = vec![illust(5), illust(90), illust(7), illust(3)];
+        let ids = |works: Vec<&Illust>| works.iter().map(|w| w.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(new_bookmarks(None, &illusts)), vec![5]);
+
+        let state = BookmarksState {
+            pushed_ids: vec![3, 7],
+        };
+        assert_eq!(ids(new_bookmarks(Some(&state), &illusts)), vec![90, 5]);
+    }
+}
@@ -6,13 +6,15 @@ use crate::db::types::{
     BooruFilter, BooruRankingMode, BooruRankingState, BooruTagState, BooruTaskKey, HotPost,
     OrderbyKind, PopularScale, QueuedBooruPost, SubscriptionState, TaskType,
 };
+use crate::scheduler::fair_queue::{FairQueue, FAIR_QUEUE_WINDOW};
 use crate::scheduler::helpers::{
     booru_ranking_subscription_state, booru_tag_subscription_state, get_chat_if_should_notify,
-    save_first_message_record, INTER_SUBSCRIPTION_DELAY_MS,
+    log_push, record_task_result, save_first_message_record, save_subscription_state,
+    INTER_SUBSCRIPTION_DELAY_MS,
 };
 use crate::utils::{caption, duration::parse_duration_key, sensitive};
 use anyhow::{Context, Result};
-use chrono::{Local, Utc};
+use chrono::Utc;
 use rand::RngExt;
 use std::borrow::Cow;
 use std::collections::HashSet;
@@ -43,6 +45,9 @@ const MAX_GRACE_PUSH_PER_TICK: usize = 5;
 // in the ranking (filtered by `!state.pushed_ids.contains`).
 const MAX_RANKING_PUSH_PER_TICK: usize = 5;
 
+// Booru tasks executed per tick, shared fairly between tag and ranking tasks
+const TASKS_PER_TICK: usize = 2;
+
 fn booru_post_image_urls(post: &booru_client::BooruPost) -> Vec<Cow<'_, str>> {
     // Real-time (user-triggered) sends prefer the original file; sample_url
     // is a downscaled variant and preview_url only a thumbnail.
@@ -79,6 +84,7 @@ pub struct BooruEngine {
     max_retry_count: i32,
     registry: Arc<BooruSiteRegistry>,
     booru_config: Arc<BooruConfig>,
+    fair_queue: FairQueue,
 }
 
 impl BooruEngine {
@@ -99,6 +105,7 @@ impl BooruEngine {
             max_retry_count: max_retry_count.min(255),
             registry,
             booru_config,
+            fair_queue: FairQueue::new(&[]),
         }
     }
 
@@ -124,36 +131,39 @@ impl BooruEngine {
     }
 
     async fn tick(&self) -> Result<()> {
-        let tag_task = self
-            .repo
-            .get_pending_tasks_by_type(TaskType::BooruTag, 1)
-            .await?
-            .into_iter()
-            .next();
-        let ranking_task = self
+        let candidates = self
             .repo
-            .get_pending_tasks_by_type(TaskType::BooruRanking, 1)
-            .await?
-            .into_iter()
-            .next();
-
-        if let Some(task) = tag_task {
-            debug!("⚙️  Executing booru tag task [{}] {}", task.id, task.value);
-            if let Err(e) = self.execute_booru_tag_task(&task).await {
-                error!("Booru tag task execution failed: {:#}", e);
-                self.handle_tag_task_error(&task).await?;
-            }
-        }
-
-        if let Some(task) = ranking_task {
-            debug!(
-                "⚙️  Executing booru ranking task [{}] {}",
-                task.id, task.value
-            );
-            if let Err(e) = self.execute_booru_ranking_task(&task).await {
-                error!("Booru ranking task execution failed: {:#}", e);
-                let backoff = Local::now() + chrono::Duration::hours(1);
-                self.repo.update_task_after_poll(task.id, backoff).await?;
+            .get_pending_tasks_by_types(
+                &[TaskType::BooruTag, TaskType::BooruRanking],
+                TASKS_PER_TICK as u64 * FAIR_QUEUE_WINDOW,
+            )
+            .await?;
+
+        for task in self.fair_queue.select(candidates, TASKS_PER_TICK) {
+            match task.r#type {
+                TaskType::BooruTag => {
+                    debug!("⚙️  Executing booru tag task [{}] {}", task.id, task.value);
+                    let result = self.execute_booru_tag_task(&task).await;
+                    record_task_result(&self.repo, task.id, &result).await;
+                    if let Err(e) = result {
+                        error!("Booru tag task execution failed: {:#}", e);
+                        self.handle_tag_task_error(&task).await?;
+                    }
+                }
+                // Only booru ranking tasks are left among the candidates
+                _ => {
+                    debug!(
+                        "⚙️  Executing booru ranking task [{}] {}",
+                        task.id, task.value
+                    );
+                    let result = self.execute_booru_ranking_task(&task).await;
+                    record_task_result(&self.repo, task.id, &result).await;
+                    if let Err(e) = result {
+                        error!("Booru ranking task execution failed: {:#}", e);
+                        let backoff = Utc::now() + chrono::Duration::hours(1);
+                        self.repo.update_task_after_poll(task.id, backoff).await?;
+                    }
+                }
             }
         }
 
@@ -182,7 +192,7 @@ impl BooruEngine {
                 "Task [{}] refers to unknown site '{}', scheduling backoff",
                 task.id, task.value
             );
-            let backoff = Local::now() + chrono::Duration::hours(1);
+            let backoff = Utc::now() + chrono::Duration::hours(1);
             self.repo.update_task_after_poll(task.id, backoff).await?;
         }
         Ok(())
@@ -195,7 +205,7 @@ impl BooruEngine {
             .site_for_task_value(&task.value)
             .ok_or_else(|| anyhow::anyhow!("Unknown booru site: {}", site_name))?;
 
-        let subscriptions = self.repo.list_subscriptions_by_task(task.id).await?;
+        let subscriptions = self.repo.list_active_subscriptions_by_task(task.id).await?;
         if subscriptions.is_empty() {
             self.schedule_next_poll(task.id, &site_ctx.config).await?;
             return Ok(());
@@ -286,13 +296,12 @@ impl BooruEngine {
                     if !new_state.pending_queue.is_empty() {
                         has_pending_queue = true;
                     }
-                    if let Err(e) = self
-                        .repo
-                        .update_subscription_latest_data(
-                            subscription.id,
-                            Some(SubscriptionState::BooruTag(new_state)),
-                        )
-                        .await
+                    if let Err(e) = save_subscription_state(
+                        &self.repo,
+                        subscription,
+                        SubscriptionState::BooruTag(new_state),
+                    )
+                    .await
                     {
                         error!(
                             "Failed to update subscription {} state: {:#}",
@@ -335,7 +344,7 @@ impl BooruEngine {
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Ranking task missing mode: {}", task.value))?;
 
-        let subscriptions = self.repo.list_subscriptions_by_task(task.id).await?;
+        let subscriptions = self.repo.list_active_subscriptions_by_task(task.id).await?;
         if subscriptions.is_empty() {
             self.schedule_ranking_next_poll(task.id, &mode).await?;
             return Ok(());
@@ -493,13 +502,9 @@ impl BooruEngine {
             gc_failed_attempts(&mut new_state.failed_attempts, &current_ids);
 
             new_state.trim_pushed(self.booru_config.ranking_pushed_cap);
-            if let Err(e) = self
-                .repo
-                .update_subscription_latest_data(
-                    sub.id,
-                    Some(SubscriptionState::BooruRanking(new_state)),
-                )
-                .await
+            if let Err(e) =
+                save_subscription_state(&self.repo, sub, SubscriptionState::BooruRanking(new_state))
+                    .await
             {
                 error!("update sub state failed: {:#}", e);
             }
@@ -550,7 +555,7 @@ impl BooruEngine {
                 parsed.clamp(min_interval, max_interval)
             }
         };
-        let next = Local::now() + interval;
+        let next = Utc::now() + interval;
         self.repo.update_task_after_poll(task_id, next).await?;
         Ok(())
     }
@@ -958,6 +963,7 @@ impl BooruEngine {
         let queued_rating = booru_client::BooruRating::from_short_str(&first.rating);
         let has_spoiler = sensitive::should_blur_booru(chat, &first.tags, queued_rating);
 
+        let _turn = self.notifier.begin_push(chat_id).await;
         let mut successful_send = None;
         for url in image_urls {
             let image_url = url.into_owned();
@@ -968,7 +974,9 @@ impl BooruEngine {
                     &[image_url],
                     Some(&caption_text),
                     has_spoiler,
-                    &DownloadButtonConfig::for_booru_chat(site_name, first.id, chat),
+                    &DownloadButtonConfig::for_booru_chat(site_name, first.id, chat)
+                        .with_source_url(caption::booru_post_url(base_url, engine_type, first.id))
+                        .with_unblur_button(),
                 )
                 .await;
             if send_result.is_complete_success() {
@@ -977,6 +985,15 @@ impl BooruEngine {
             }
         }
 
+        log_push(
+            &self.repo,
+            chat_id,
+            subscription.id,
+            usize::from(successful_send.is_some()),
+            successful_send.is_some(),
+        )
+        .await;
+
         if let Some(send_result) = successful_send {
             save_first_message_record(
                 &self.repo,
@@ -1014,6 +1031,7 @@ impl BooruEngine {
         let caption_text = caption::build_booru_caption(post, site_name, base_url, engine_type);
         let has_spoiler = sensitive::should_blur_booru(chat, &post.tags, post.rating);
 
+        let _turn = self.notifier.begin_push(chat_id).await;
         let mut successful_send = None;
         for url in image_urls {
             let image_url = url.into_owned();
@@ -1024,7 +1042,9 @@ impl BooruEngine {
                     &[image_url],
                     Some(&caption_text),
                     has_spoiler,
-                    &DownloadButtonConfig::for_booru_chat(site_name, post.id, chat),
+                    &DownloadButtonConfig::for_booru_chat(site_name, post.id, chat)
+                        .with_source_url(caption::booru_post_url(base_url, engine_type, post.id))
+                        .with_unblur_button(),
                 )
                 .await;
             if send_result.is_complete_success() {
@@ -1033,6 +1053,15 @@ impl BooruEngine {
             }
         }
 
+        log_push(
+            &self.repo,
+            chat_id,
+            subscription_id,
+            usize::from(successful_send.is_some()),
+            successful_send.is_some(),
+        )
+        .await;
+
         if let Some(send_result) = successful_send {
             save_first_message_record(
                 &self.repo,
@@ -1153,13 +1182,13 @@ impl BooruEngine {
         let min = site_config.min_interval_sec;
         let max = site_config.max_interval_sec.max(min);
         let random_interval_sec = rand::rng().random_range(min..=max);
-        let next_poll = Local::now() + chrono::Duration::seconds(random_interval_sec as i64);
+        let next_poll = Utc::now() + chrono::Duration::seconds(random_interval_sec as i64);
         self.repo.update_task_after_poll(task_id, next_poll).await?;
         Ok(())
     }
 
     async fn schedule_drain_poll(&self, task_id: i32) -> Result<()> {
-        let next_poll = Local::now() + chrono::Duration::seconds(DRAIN_POLL_INTERVAL_SEC as i64);
+        let next_poll = Utc::now() + chrono::Duration::seconds(DRAIN_POLL_INTERVAL_SEC as i64);
         self.repo.update_task_after_poll(task_id, next_poll).await?;
         Ok(())
     }
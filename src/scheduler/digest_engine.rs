@@ -0,0 +1,315 @@
+use crate::bot::notifier::Notifier;
+use crate::db::entities::{subscriptions, tasks};
+use crate::db::repo::Repo;
+use crate::db::types::TaskType;
+use crate::utils::time::to_local;
+use anyhow::{Context, Result};
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::types::ChatId;
+use teloxide::utils::markdown;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+
+/// Period covered by each digest
+const DIGEST_PERIOD_DAYS: i64 = 7;
+
+/// Subscriptions listed per chat before the rest are summarized as a count
+const MAX_DIGEST_ENTRIES: usize = 40;
+
+/// Engine that posts a weekly digest of new subscriptions to group chats
+///
+/// Lists every subscription a group gained in the past week together with the member
+/// who added it and its filters, so admins can keep the feed curated.
+pub struct SubscriptionDigestEngine {
+    repo: Arc<Repo>,
+    notifier: Notifier,
+    weekday: Weekday,
+    time: NaiveTime,
+}
+
+impl SubscriptionDigestEngine {
+    pub fn new(repo: Arc<Repo>, notifier: Notifier, weekday: &str, time: &str) -> Result<Self> {
+        let weekday = weekday
+            .parse::<Weekday>()
+            .map_err(|_| anyhow::anyhow!("Invalid digest weekday '{}'", weekday))?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .context("Invalid digest time format (expected HH:MM)")?;
+        Ok(Self {
+            repo,
+            notifier,
+            weekday,
+            time,
+        })
+    }
+
+    /// Main scheduler loop - runs indefinitely at the configured weekday and time
+    pub async fn run(&self) {
+        info!(
+            "🚀 Subscription digest engine started (every {} at {})",
+            self.weekday,
+            self.time.format("%H:%M")
+        );
+
+        loop {
+            let now = Local::now();
+            let next_naive = next_digest_time(now.naive_local(), self.weekday, self.time);
+            let Some(next_execution) = Local.from_local_datetime(&next_naive).earliest() else {
+                error!(
+                    "Digest time {} does not exist in local time zone",
+                    next_naive
+                );
+                sleep(Duration::from_secs(3600)).await;
+                continue;
+            };
+            let duration_until_execution = (next_execution - now).to_std().unwrap_or_default();
+
+            info!(
+                "⏰ Next subscription digest at: {} (in {} seconds)",
+                next_execution.format("%Y-%m-%d %H:%M:%S"),
+                duration_until_execution.as_secs()
+            );
+
+            sleep(duration_until_execution).await;
+
+            if let Err(e) = self.send_digests().await {
+                error!("Subscription digest error: {:#}", e);
+            }
+
+            // Sleep a bit to avoid executing twice in the same minute
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+
+    async fn send_digests(&self) -> Result<()> {
+        let since = Utc::now().naive_utc() - chrono::Duration::days(DIGEST_PERIOD_DAYS);
+        let subscriptions = self
+            .repo
+            .list_group_subscriptions_created_since(since)
+            .await?;
+
+        if subscriptions.is_empty() {
+            info!("No new group subscriptions for the digest");
+            return Ok(());
+        }
+
+        let users = self.creator_names(&subscriptions).await;
+
+        let mut sent = 0;
+        for entries in subscriptions.chunk_by(|(a, _), (b, _)| a.chat_id == b.chat_id) {
+            let chat_id = entries[0].0.chat_id;
+            let text = build_digest_message(entries, &users);
+            match self.notifier.send_text(ChatId(chat_id), &text, true).await {
+                Ok(_) => sent += 1,
+                Err(e) => warn!("Failed to send subscription digest to {}: {:#}", chat_id, e),
+            }
+        }
+
+        info!("✅ Subscription digest sent to {} group(s)", sent);
+        Ok(())
+    }
+
+    /// Usernames of the members who added the subscriptions, keyed by user ID
+    async fn creator_names(
+        &self,
+        subscriptions: &[(subscriptions::Model, tasks::Model)],
+    ) -> HashMap<i64, String> {
+        let mut names = HashMap::new();
+        for user_id in subscriptions.iter().filter_map(|(sub, _)| sub.created_by) {
+            if names.contains_key(&user_id) {
+                continue;
+            }
+            match self.repo.get_user(user_id).await {
+                Ok(Some(user)) => {
+                    if let Some(username) = user.username {
+                        names.insert(user_id, username);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to get user {} for digest: {:#}", user_id, e),
+            }
+        }
+        names
+    }
+}
+
+/// First occurrence of `weekday` at `time` strictly after `now`
+pub(super) fn next_digest_time(
+    now: NaiveDateTime,
+    weekday: Weekday,
+    time: NaiveTime,
+) -> NaiveDateTime {
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - now.weekday().num_days_from_monday()) % 7;
+    let candidate = (now.date() + chrono::Duration::days(days_ahead as i64)).and_time(time);
+    if candidate > now {
+        candidate
+    } else {
+        candidate + chrono::Duration::days(7)
+    }
+}
+
+/// Digest text for one chat (MarkdownV2)
+fn build_digest_message(
+    entries: &[(subscriptions::Model, tasks::Model)],
+    users: &HashMap<i64, String>,
+) -> String {
+    let mut message = format!("🗞 *本周新增订阅* \\(共 {} 条\\)\n\n", entries.len());
+
+    for (sub, task) in entries.iter().take(MAX_DIGEST_ENTRIES) {
+        message.push_str(&format!("{}\n", task_display(task)));
+
+        let creator = match sub.created_by {
+            Some(user_id) => match users.get(&user_id) {
+                Some(username) => format!("@{}", markdown::escape(username)),
+                None => format!("ID: `{}`", user_id),
+            },
+            None => "未知".to_string(),
+        };
+        message.push_str(&format!(
+            "  👤 {} · {}\n",
+            creator,
+            markdown::escape(&to_local(sub.created_at).format("%m-%d %H:%M").to_string())
+        ));
+
+        if !sub.filter_tags.is_empty() {
+            message.push_str(&format!("  🏷 {}\n", sub.filter_tags.format_for_display()));
+        }
+        if let Some(filter) = sub.booru_filter.as_ref().filter(|f| !f.is_empty()) {
+            message.push_str(&format!(
+                "  🔍 {}\n",
+                markdown::escape(&filter.format_for_display())
+            ));
+        }
+        if let Some(filter) = sub.eh_filter.as_ref().filter(|f| !f.is_empty()) {
+            message.push_str(&format!(
+                "  🔍 {}\n",
+                markdown::escape(&filter.format_for_display())
+            ));
+        }
+    }
+
+    if entries.len() > MAX_DIGEST_ENTRIES {
+        message.push_str(&format!(
+            "\n…以及另外 {} 条\n",
+            entries.len() - MAX_DIGEST_ENTRIES
+        ));
+    }
+
+    message.push_str("\n💡 使用 /list 查看全部订阅");
+    message
+}
+
+fn task_display(task: &tasks::Model) -> String {
+    let type_emoji = match task.r#type {
+        TaskType::Author => "🎨",
+        TaskType::Ranking => "📊",
+        TaskType::BooruTag => "🏷",
+        TaskType::BooruPool => "📦",
+        TaskType::BooruRanking => "🏆",
+        TaskType::Ehentai => "📖",
+        TaskType::Bookmarks => "🔖",
+    };
+
+    let display_info = match (task.r#type, task.author_name.as_deref()) {
+        (TaskType::Ranking, _) => format!("排行榜 `{}`", markdown::escape_code(&task.value)),
+        (_, Some(name)) => format!(
+            "{} \\| `{}`",
+            markdown::escape(name),
+            markdown::escape_code(&task.value)
+        ),
+        (_, None) => format!("`{}`", markdown::escape_code(&task.value)),
+    };
+
+    format!("{} {}", type_emoji, display_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::types::TagFilter;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn next_digest_time_picks_next_matching_weekday() {
+        let ten = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+
+        // 2026-10-12 is a Monday
+        assert_eq!(
+            next_digest_time(at("2026-10-12 09:00"), Weekday::Mon, ten),
+            at("2026-10-12 10:00")
+        );
+        assert_eq!(
+            next_digest_time(at("2026-10-12 10:00"), Weekday::Mon, ten),
+            at("2026-10-19 10:00")
+        );
+        assert_eq!(
+            next_digest_time(at("2026-10-16 23:00"), Weekday::Sun, ten),
+            at("2026-10-18 10:00")
+        );
+    }
+
+    #[test]
+    fn build_digest_message_lists_creator_and_filters() {
+        let created_at = at("2026-10-14 20:30");
+        let sub = |id: i32, created_by: Option<i64>, filter_tags: TagFilter| subscriptions::Model {
+            id,
+            chat_id: -100,
+            task_id: id,
+            filter_tags,
+            booru_filter: None,
+            eh_filter: None,
+            latest_data: None,
+            created_at,
+            version: 0,
+            created_by,
+            spoiler_mode: Default::default(),
+            enabled: true,
+            push_mode: Default::default(),
+            silent: false,
+            ranking_date_offset: 0,
+            message_thread_id: None,
+            ai_filter: Default::default(),
+            image_size: Default::default(),
+        };
+        let task =
+            |id: i32, r#type: TaskType, value: &str, author_name: Option<&str>| tasks::Model {
+                id,
+                r#type,
+                value: value.to_string(),
+                next_poll_at: created_at,
+                last_polled_at: None,
+                author_name: author_name.map(str::to_string),
+                poll_interval_min: None,
+                last_success_at: None,
+                last_error: None,
+                last_error_at: None,
+                failure_count: 0,
+                dead_at: None,
+            };
+        let entries = vec![
+            (
+                sub(1, Some(42), TagFilter::parse_from_args(&["+genshin"])),
+                task(1, TaskType::Author, "123", Some("artist")),
+            ),
+            (
+                sub(2, None, TagFilter::default()),
+                task(2, TaskType::Ranking, "daily", None),
+            ),
+        ];
+        let users = HashMap::from([(42, "some_member".to_string())]);
+
+        let message = build_digest_message(&entries, &users);
+
+        assert!(message.contains("共 2 条"));
+        assert!(message.contains("🎨 artist \\| `123`"));
+        assert!(message.contains("@some\\_member"));
+        assert!(message.contains("+genshin"));
+        assert!(message.contains("📊 排行榜 `daily`"));
+        assert!(message.contains("👤 未知"));
+    }
+}
@@ -5,15 +5,20 @@ use crate::db::repo::Repo;
 use crate::db::types::{
     EhFilter, EhPendingGallery, EhTagState, EhTaskKey, SubscriptionState, TaskType,
 };
-use crate::scheduler::helpers::{eh_tag_subscription_state, get_chat_if_should_notify};
+use crate::scheduler::helpers::{
+    eh_tag_subscription_state, get_chat_if_should_notify, record_task_result,
+    save_subscription_state,
+};
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::Utc;
 use eh_client::{
     parser::DownloadCost, rewrite_ipfs_gateway_nodes, ArchiveArtifacts, ArchiveDownloadOptions,
     EhClient, EhGallery, ImageUploadInput, ImageUploader, IpfS3PreviewRewriteConfig,
-    TelegraphClient, TelegraphImageUrlPair, TelegraphRewriteData, ZipArchiveUploadInput,
+    TagTranslationDb, TagTranslations, TelegraphClient, TelegraphImageUrlPair,
+    TelegraphRewriteData, ZipArchiveUploadInput,
 };
 use rand::RngExt;
+use std::borrow::Cow;
 use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 use tokio::sync::{mpsc, Mutex};
@@ -73,6 +78,18 @@ fn should_schedule_background_download(failures: i32, bytes_delta: u64, elapsed:
 }
 
 /// Convert a byte count to whole MiB, rounding up so partial MiB is not under-reported.
+/// When E-Hentai's temporary IP ban ends, if `err` was caused by one.
+///
+/// Callers wrap client errors with `.context()`, so the whole chain is searched.
+fn eh_ban_expiry(err: &anyhow::Error) -> Option<chrono::DateTime<Utc>> {
+    err.chain()
+        .find_map(|c| c.downcast_ref::<eh_client::Error>())
+        .and_then(|client_err| match client_err {
+            eh_client::Error::TemporarilyBanned { until } => Some(*until),
+            _ => None,
+        })
+}
+
 fn format_mib(bytes: u64) -> u64 {
     bytes.div_ceil(1024 * 1024)
 }
@@ -502,6 +519,45 @@ async fn drain_background_download_tasks(tasks: &mut JoinSet<Result<()>>) -> Res
         Ok(())
     }
 }
+/// Number of gallery tags listed in a preview caption
+const PREVIEW_CAPTION_MAX_TAGS: usize = 12;
+
+fn format_preview_caption(
+    base_url: &str,
+    gallery: &EhGallery,
+    translations: Option<&TagTranslations>,
+) -> String {
+    use teloxide::utils::markdown::{escape, escape_link_url};
+
+    let gallery_url = format!(
+        "{}/g/{}/{}",
+        base_url.trim_end_matches('/'),
+        gallery.gid,
+        gallery.token
+    );
+    let mut caption = format!(
+        "📖 *{}*\n📂 {} \\| 📄 {} 页 \\| ⭐ {}",
+        escape(&gallery.title),
+        escape(&gallery.category),
+        gallery.filecount,
+        escape(&format!("{:.2}", gallery.rating))
+    );
+    if !gallery.tags.is_empty() {
+        let tags = match translations {
+            Some(translations) => gallery.translated_tags(translations),
+            None => gallery.tags.clone(),
+        };
+        let tags: Vec<String> = tags
+            .iter()
+            .take(PREVIEW_CAPTION_MAX_TAGS)
+            .map(|tag| escape(tag))
+            .collect();
+        caption.push_str(&format!("\n🏷 {}", tags.join(", ")));
+    }
+    caption.push_str(&format!("\n\n🔗 [来源]({})", escape_link_url(&gallery_url)));
+    caption
+}
+
 // ============================================================================
 // Stage 1: EhEngine — Collect (search → metadata → filter → enqueue downloads)
 // ============================================================================
@@ -509,15 +565,18 @@ async fn drain_background_download_tasks(tasks: &mut JoinSet<Result<()>>) -> Res
 pub struct EhEngine {
     repo: Arc<Repo>,
     client: Arc<EhClient>,
+    notifier: Notifier,
     config: Arc<EhentaiConfig>,
     telegraph_available: bool,
     tick_interval_sec: u64,
+    tag_translations: Option<TagTranslationDb>,
 }
 
 impl EhEngine {
     pub fn new(
         repo: Arc<Repo>,
         client: Arc<EhClient>,
+        notifier: Notifier,
         config: Arc<EhentaiConfig>,
         telegraph_available: bool,
         tick_interval_sec: u64,
@@ -525,12 +584,20 @@ impl EhEngine {
         Self {
             repo,
             client,
+            notifier,
             config,
             telegraph_available,
             tick_interval_sec,
+            tag_translations: None,
         }
     }
 
+    /// Translate gallery tags in preview captions with the given database
+    pub fn with_tag_translations(mut self, tag_translations: TagTranslationDb) -> Self {
+        self.tag_translations = Some(tag_translations);
+        self
+    }
+
     pub async fn run(self) {
         let mut interval =
             tokio::time::interval(tokio::time::Duration::from_secs(self.tick_interval_sec));
@@ -552,9 +619,13 @@ impl EhEngine {
             .context("Failed to fetch pending eh tasks")?;
 
         if let Some(task) = tasks.into_iter().next() {
-            if let Err(e) = self.execute_eh_task(&task).await {
+            let result = self.execute_eh_task(&task).await;
+            record_task_result(&self.repo, task.id, &result).await;
+            if let Err(e) = result {
                 error!("Failed to execute eh task {}: {:#}", task.id, e);
-                let backoff = Local::now() + chrono::Duration::hours(1);
+                // Poll again once the IP ban has expired
+                let backoff =
+                    eh_ban_expiry(&e).unwrap_or_else(|| Utc::now() + chrono::Duration::hours(1));
                 if let Err(e2) = self.repo.update_task_after_poll(task.id, backoff).await {
                     error!("Failed to backoff eh task {}: {:#}", task.id, e2);
                 }
@@ -569,7 +640,7 @@ impl EhEngine {
 
         let subs = self
             .repo
-            .list_subscriptions_by_task(task.id)
+            .list_active_subscriptions_by_task(task.id)
             .await
             .context("Failed to list eh subscriptions")?;
 
@@ -653,11 +724,19 @@ impl EhEngine {
         }
 
         // Filter by real posted timestamp + aggregate filter
-        let now_ts = Local::now().timestamp();
+        let now_ts = Utc::now().timestamp();
         let scan_cutoff = now_ts - (self.config.scan_window_hours as i64 * 3600);
 
+        // Expunged galleries can't be downloaded; drop them from the per-sub state too
+        let expunged_gids: Vec<u64> = all_metadata
+            .iter()
+            .filter(|g| g.expunged)
+            .map(|g| g.gid)
+            .collect();
+
         let filtered: Vec<EhGallery> = all_metadata
             .into_iter()
+            .filter(|g| !g.expunged)
             .filter(|g| {
                 if oldest_ts > 0 && g.posted <= oldest_ts {
                     return false;
@@ -680,7 +759,7 @@ impl EhEngine {
 
         // Process each subscription
         for (sub, remaining_slots) in &prepared_subs {
-            self.process_eh_sub_with_slots(sub, &filtered, *remaining_slots)
+            self.process_eh_sub_with_slots(sub, &filtered, &expunged_gids, *remaining_slots)
                 .await?;
         }
 
@@ -768,15 +847,7 @@ impl EhEngine {
                 continue;
             }
             if let Err(e) = self
-                .repo
-                .enqueue_eh_subscription_download(
-                    sub.chat_id,
-                    sub.id,
-                    pending.gid as i64,
-                    &pending.token,
-                    &pending.title,
-                    telegraph_default,
-                )
+                .deliver_gallery(sub, &pending, None, telegraph_default)
                 .await
             {
                 if !self.repo.subscription_exists(sub.id).await? {
@@ -794,8 +865,7 @@ impl EhEngine {
                 still_pending.extend(backlog_iter);
                 state.pending_galleries = still_pending;
                 state.trim_pushed(self.config.pushed_cap);
-                self.repo
-                    .update_subscription_latest_data(sub.id, Some(SubscriptionState::EhTag(state)))
+                save_subscription_state(&self.repo, sub, SubscriptionState::EhTag(state))
                     .await
                     .context("Failed to persist eh pending backlog after enqueue failure")?;
                 return Err(e)
@@ -827,11 +897,10 @@ impl EhEngine {
                 .await?;
             return Ok((sub.clone(), state, 0));
         }
-        let updated_sub = self
-            .repo
-            .update_subscription_latest_data(sub.id, Some(SubscriptionState::EhTag(state.clone())))
-            .await
-            .context("Failed to update eh subscription state")?;
+        let updated_sub =
+            save_subscription_state(&self.repo, sub, SubscriptionState::EhTag(state.clone()))
+                .await
+                .context("Failed to update eh subscription state")?;
         Ok((updated_sub, state, remaining_slots))
     }
 
@@ -839,6 +908,7 @@ impl EhEngine {
         &self,
         sub: &crate::db::entities::subscriptions::Model,
         galleries: &[EhGallery],
+        expunged_gids: &[u64],
         max_push: usize,
     ) -> Result<()> {
         if !self.repo.subscription_exists(sub.id).await? {
@@ -846,16 +916,20 @@ impl EhEngine {
             return Ok(());
         }
         let mut state = eh_tag_subscription_state(sub).unwrap_or_else(EhTagState::cleared);
+        state.expunge_gids(expunged_gids);
 
         let sub_filter = sub.eh_filter.as_ref();
         let mut remaining_slots = max_push;
         let telegraph_default = self.telegraph_default(sub_filter);
 
         // Step 1: Consume pending backlog first (galleries from previous overflow).
+        // Track the latest persisted row so later writes use its current version
+        let mut current_sub = Cow::Borrowed(sub);
         if !state.pending_galleries.is_empty() {
-            let (_updated_sub, updated_state, remaining) = self
+            let (updated_sub, updated_state, remaining) = self
                 .drain_pending_backlog(sub, state, remaining_slots, telegraph_default)
                 .await?;
+            current_sub = Cow::Owned(updated_sub);
             state = updated_state;
             remaining_slots = remaining;
             if !state.pending_galleries.is_empty() || remaining_slots == 0 {
@@ -864,9 +938,12 @@ impl EhEngine {
         }
 
         // Step 2: Pending backlog drained. Now process new filtered galleries.
+        // Rating-filtered subs rescan a window and may push older galleries late,
+        // so only the others skip everything at or below the newest pushed GID.
+        let require_newer = !sub_filter.is_some_and(|f| f.has_rating_filter());
         let eligible: Vec<EhPendingGallery> = galleries
             .iter()
-            .filter(|g| !state.pushed_gids.contains(&g.gid))
+            .filter(|g| state.is_unseen(g.gid, require_newer))
             .filter(|g| sub_filter.map(|f| f.matches(g)).unwrap_or(true))
             .map(|g| EhPendingGallery {
                 gid: g.gid,
@@ -900,16 +977,9 @@ impl EhEngine {
                 );
                 continue;
             }
+            let metadata = galleries.iter().find(|g| g.gid == gallery.gid);
             if let Err(e) = self
-                .repo
-                .enqueue_eh_subscription_download(
-                    sub.chat_id,
-                    sub.id,
-                    gallery.gid as i64,
-                    &gallery.token,
-                    &gallery.title,
-                    telegraph_default,
-                )
+                .deliver_gallery(sub, &gallery, metadata, telegraph_default)
                 .await
             {
                 if !self.repo.subscription_exists(sub.id).await? {
@@ -926,8 +996,7 @@ impl EhEngine {
                 state.pending_galleries.push(gallery);
                 state.pending_galleries.extend(eligible_iter);
                 state.trim_pushed(self.config.pushed_cap);
-                self.repo
-                    .update_subscription_latest_data(sub.id, Some(SubscriptionState::EhTag(state)))
+                save_subscription_state(&self.repo, &current_sub, SubscriptionState::EhTag(state))
                     .await
                     .context("Failed to persist eh collect state after enqueue failure")?;
                 return Err(e).with_context(|| {
@@ -966,14 +1035,119 @@ impl EhEngine {
             return Ok(());
         }
 
-        self.repo
-            .update_subscription_latest_data(sub.id, Some(SubscriptionState::EhTag(state)))
+        save_subscription_state(&self.repo, &current_sub, SubscriptionState::EhTag(state))
             .await
             .context("Failed to update eh subscription state")?;
 
         Ok(())
     }
 
+    /// Hand a collected gallery to its subscription: subscriptions with `pages=N`
+    /// get a preview sent right away, everything else is queued for archive download.
+    async fn deliver_gallery(
+        &self,
+        sub: &subscriptions::Model,
+        pending: &EhPendingGallery,
+        metadata: Option<&EhGallery>,
+        telegraph_default: bool,
+    ) -> Result<()> {
+        if let Some(preview_pages) = sub.eh_filter.as_ref().and_then(|f| f.preview_pages) {
+            return self
+                .send_gallery_preview(sub, pending, metadata, preview_pages)
+                .await;
+        }
+
+        self.repo
+            .enqueue_eh_subscription_download(
+                sub.chat_id,
+                sub.id,
+                pending.gid as i64,
+                &pending.token,
+                &pending.title,
+                telegraph_default,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Send the cover (`preview_pages == 0`) or the first sample pages as an album,
+    /// captioned with the gallery metadata.
+    async fn send_gallery_preview(
+        &self,
+        sub: &subscriptions::Model,
+        pending: &EhPendingGallery,
+        metadata: Option<&EhGallery>,
+        preview_pages: u32,
+    ) -> Result<()> {
+        let Some(chat) = get_chat_if_should_notify(&self.repo, sub.chat_id).await? else {
+            return Ok(());
+        };
+
+        // Backlog entries only keep gid/token/title, so look the metadata up again
+        let fetched;
+        let gallery = match metadata {
+            Some(gallery) => gallery,
+            None => {
+                fetched = self
+                    .client
+                    .get_metadata(&[(pending.gid, pending.token.as_str())])
+                    .await
+                    .with_context(|| {
+                        format!("Failed to fetch metadata for gallery {}", pending.gid)
+                    })?;
+                fetched
+                    .first()
+                    .with_context(|| format!("No metadata returned for gallery {}", pending.gid))?
+            }
+        };
+
+        let mut image_urls = Vec::new();
+        if preview_pages > 0 {
+            match self
+                .client
+                .get_gallery_sample_image_urls(gallery.gid, &gallery.token, preview_pages as usize)
+                .await
+            {
+                Ok(urls) => image_urls = urls,
+                Err(e) => warn!(
+                    "Failed to resolve sample pages for gallery {}, sending cover instead: {:#}",
+                    gallery.gid, e
+                ),
+            }
+        }
+        if image_urls.is_empty() {
+            image_urls.push(gallery.thumb.clone());
+        }
+
+        let translations = self.tag_translations.as_ref().map(|db| db.snapshot());
+        let caption =
+            format_preview_caption(self.client.base_url(), gallery, translations.as_deref());
+        let _turn = self
+            .notifier
+            .begin_push(teloxide::types::ChatId(chat.id))
+            .await;
+        let result = self
+            .notifier
+            .notify_with_images(
+                teloxide::types::ChatId(chat.id),
+                &image_urls,
+                Some(&caption),
+                chat.blur_sensitive_tags,
+            )
+            .await;
+
+        if result.is_complete_failure() {
+            anyhow::bail!("Failed to send preview for gallery {}", gallery.gid);
+        }
+        if !result.is_complete_success() {
+            warn!(
+                "Preview for gallery {} partially failed in chat {}: {:?}",
+                gallery.gid, chat.id, result.failed_indices
+            );
+        }
+        Ok(())
+    }
+
     /// Update state when no new galleries were found.
     async fn update_sub_state_no_new(
         &self,
@@ -994,10 +1168,8 @@ impl EhEngine {
             pending_galleries: state.pending_galleries,
             pending_high_water_ts: state.pending_high_water_ts,
         };
-        if let Err(e) = self
-            .repo
-            .update_subscription_latest_data(sub.id, Some(SubscriptionState::EhTag(new_state)))
-            .await
+        if let Err(e) =
+            save_subscription_state(&self.repo, sub, SubscriptionState::EhTag(new_state)).await
         {
             warn!("Failed to update eh sub state: {:#}", e);
         }
@@ -1011,7 +1183,7 @@ impl EhEngine {
         } else {
             max
         };
-        let next = Local::now() + chrono::Duration::seconds(delay as i64);
+        let next = Utc::now() + chrono::Duration::seconds(delay as i64);
         if let Err(e) = self.repo.update_task_after_poll(task_id, next).await {
             error!("Failed to schedule next eh poll: {:#}", e);
         }
@@ -1087,6 +1259,15 @@ impl EhDownloadWorker {
                 return Err(e);
             }
 
+            // An IP ban is not the entry's fault: wait it out without counting a retry
+            if let Some(until) = eh_ban_expiry(&e) {
+                let delay_secs = (until - Utc::now()).num_seconds().max(1);
+                self.repo
+                    .defer_eh_download(entry.id, STATUS_PENDING, delay_secs)
+                    .await?;
+                return Ok(());
+            }
+
             // process() wraps errors with .context(); downcast_ref only checks the
             // outermost layer. Must traverse the error chain to find eh_client::Error.
             let download_progress = e
@@ -1808,6 +1989,8 @@ impl EhPublishWorker {
         }
         let chat_id = teloxide::types::ChatId(entry.chat_id);
 
+        // The archive and its Telegraph link go out back to back in this chat
+        let _turn = self.notifier.begin_push(chat_id).await;
         let _publish_cancel_guard = EH_PUBLISH_CANCEL_LOCK.lock().await;
 
         if !self.ensure_entry_active(entry).await? {
@@ -2075,6 +2258,42 @@ mod tests {
         assert_eq!(Repo::backoff_delay_secs(99), 3600);
     }
 
+    #[test]
+    fn test_format_preview_caption_escapes_metadata() {
+        let gallery = EhGallery {
+            gid: 123,
+            token: "abcdef0123".to_string(),
+            title: "[Circle] Title (Vol.1)".to_string(),
+            title_jpn: None,
+            category: "Non-H".to_string(),
+            thumb: "https://ehgt.org/t/cover.jpg".to_string(),
+            uploader: "someone".to_string(),
+            posted: 0,
+            filecount: 24,
+            filesize: 0,
+            expunged: false,
+            rating: 4.5,
+            tags: vec!["female:glasses".to_string()],
+        };
+
+        assert_eq!(
+            format_preview_caption("https://e-hentai.org/", &gallery, None),
+            "📖 *\\[Circle\\] Title \\(Vol\\.1\\)*\n📂 Non\\-H \\| 📄 24 页 \\| ⭐ 4\\.50\n🏷 female:glasses\n\n🔗 [来源](https://e-hentai.org/g/123/abcdef0123)"
+        );
+
+        let translations = TagTranslations::from_json(
+            r#"{"data": [
+                {"namespace": "rows", "data": {"female": {"name": "女性"}}},
+                {"namespace": "female", "data": {"glasses": {"name": "眼镜"}}}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(
+            format_preview_caption("https://e-hentai.org/", &gallery, Some(&translations))
+                .contains("🏷 女性:眼镜\n")
+        );
+    }
+
     #[test]
     fn test_should_schedule_background_download_after_slow_repeated_resume_attempts() {
         assert!(should_schedule_background_download(
@@ -2668,7 +2887,7 @@ mod integration_tests {
         zip_path: Option<&str>,
         telegraph_url: Option<&str>,
     ) -> eh_download_queue::Model {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let active = eh_download_queue::ActiveModel {
             chat_id: Set(chat_id),
             gid: Set(gid),
@@ -2704,7 +2923,7 @@ mod integration_tests {
         zip_path: Option<&str>,
         telegraph_url: Option<&str>,
     ) -> eh_download_queue::Model {
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let active = eh_download_queue::ActiveModel {
             chat_id: Set(chat_id),
             gid: Set(gid),
@@ -2756,8 +2975,7 @@ mod integration_tests {
         // Make the task immediately available (get_or_create_task sets next_poll_at 60s in future)
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -2765,7 +2983,7 @@ mod integration_tests {
             .unwrap();
 
         let eh_server = MockServer::start().await;
-        let _tg_server = MockServer::start().await;
+        let tg_server = MockServer::start().await;
 
         mock_eh_search_with_four_galleries(&eh_server).await;
         mock_eh_metadata_for_four_galleries(&eh_server).await;
@@ -2774,6 +2992,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&tg_server),
             Arc::clone(&config),
             true,
             60,
@@ -2795,8 +3014,7 @@ mod integration_tests {
             .unwrap()
             .unwrap();
         let mut active: tasks::ActiveModel = task_model.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         engine.tick().await.unwrap();
@@ -2836,8 +3054,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -2851,6 +3068,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -2888,8 +3106,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(
@@ -2914,6 +3131,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(config),
             true,
             60,
@@ -2949,8 +3167,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(
@@ -2975,6 +3192,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(config),
             false,
             60,
@@ -3015,8 +3233,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -3030,6 +3247,7 @@ mod integration_tests {
             .unwrap();
         repo.update_subscription_latest_data(
             sub.id,
+            sub.version,
             Some(SubscriptionState::EhTag(EhTagState {
                 pushed_gids: Vec::new(),
                 latest_posted_ts: 0,
@@ -3055,6 +3273,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -3092,8 +3311,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -3107,6 +3325,7 @@ mod integration_tests {
             .unwrap();
         repo.update_subscription_latest_data(
             sub.id,
+            sub.version,
             Some(SubscriptionState::EhTag(EhTagState {
                 pushed_gids: Vec::new(),
                 latest_posted_ts: 0,
@@ -3132,6 +3351,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -3153,7 +3373,7 @@ mod integration_tests {
             .await
             .unwrap()
             .unwrap();
-        assert!(task.next_poll_at > chrono::Local::now().naive_local());
+        assert!(task.next_poll_at > chrono::Utc::now().naive_utc());
     }
 
     #[tokio::test]
@@ -3175,8 +3395,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -3189,6 +3408,7 @@ mod integration_tests {
         let existing = subs.iter().find(|s| s.chat_id == -100).unwrap();
         repo.update_subscription_latest_data(
             existing.id,
+            existing.version,
             Some(SubscriptionState::EhTag(EhTagState {
                 pushed_gids: vec![999],
                 latest_posted_ts: 500,
@@ -3209,6 +3429,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -3259,8 +3480,7 @@ mod integration_tests {
             .unwrap();
         let task_id = task.id;
         let mut active: tasks::ActiveModel = task.into();
-        active.next_poll_at =
-            Set(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+        active.next_poll_at = Set(chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1));
         active.update(repo.db()).await.unwrap();
 
         repo.upsert_eh_subscription(-100, task_id, crate::db::types::TagFilter::default(), None)
@@ -3274,6 +3494,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&eh_server),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -3299,7 +3520,7 @@ mod integration_tests {
             .await
             .unwrap()
             .unwrap();
-        assert!(task.next_poll_at > chrono::Local::now().naive_local());
+        assert!(task.next_poll_at > chrono::Utc::now().naive_utc());
     }
 
     #[tokio::test]
@@ -3325,6 +3546,7 @@ mod integration_tests {
             .unwrap();
         repo.update_subscription_latest_data(
             sub.id,
+            sub.version,
             Some(SubscriptionState::EhTag(EhTagState {
                 pushed_gids: vec![1],
                 latest_posted_ts: 500,
@@ -3343,6 +3565,7 @@ mod integration_tests {
         let engine = EhEngine::new(
             Arc::clone(&repo),
             make_eh_client(&MockServer::start().await),
+            make_notifier(&MockServer::start().await),
             Arc::new(make_config()),
             true,
             60,
@@ -3416,6 +3639,17 @@ mod integration_tests {
         );
     }
 
+    #[test]
+    fn eh_ban_expiry_found_through_anyhow_context() {
+        let until = Utc::now() + chrono::Duration::hours(2);
+        let result: eh_client::Result<()> = Err(eh_client::Error::TemporarilyBanned { until });
+        let wrapped: anyhow::Error = result.context("Failed to search eh galleries").unwrap_err();
+        assert_eq!(eh_ban_expiry(&wrapped), Some(until));
+
+        let other = anyhow::anyhow!("gallery page returned 500");
+        assert_eq!(eh_ban_expiry(&other), None);
+    }
+
     // === Download Worker Tests ===
 
     #[tokio::test]
@@ -3539,7 +3773,7 @@ mod integration_tests {
         setup_chat(&repo, -100, true).await;
 
         // Pre-fill a done entry to hit rate limit
-        let now = Local::now().naive_local();
+        let now = Utc::now().naive_utc();
         let big = eh_download_queue::ActiveModel {
             chat_id: Set(-100),
             gid: Set(999999),
@@ -4790,7 +5024,7 @@ mod integration_tests {
         eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::ArchiveSentAt,
-                Expr::value(Some(Local::now().naive_local())),
+                Expr::value(Some(Utc::now().naive_utc())),
             )
             .filter(eh_download_queue::Column::Id.eq(entry.id))
             .exec(repo.db())
@@ -5154,11 +5388,11 @@ mod integration_tests {
         eh_download_queue::Entity::update_many()
             .col_expr(
                 eh_download_queue::Column::ArchiveSentAt,
-                Expr::value(Some(Local::now().naive_local())),
+                Expr::value(Some(Utc::now().naive_utc())),
             )
             .col_expr(
                 eh_download_queue::Column::TelegraphSentAt,
-                Expr::value(Some(Local::now().naive_local())),
+                Expr::value(Some(Utc::now().naive_utc())),
             )
             .filter(eh_download_queue::Column::Id.eq(entry.id))
             .exec(repo.db())
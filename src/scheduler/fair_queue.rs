@@ -0,0 +1,172 @@
+//! Fair selection of due tasks for the polling engines.
+//!
+//! Engines fetch a window of due tasks and let [`FairQueue`] pick the ones to
+//! run this tick: task types take turns by smooth weighted round-robin, and
+//! within a type the task served longest ago goes first. Each author has a
+//! single task, so a busy author or a large backlog of one type can no longer
+//! take every slot tick after tick.
+
+use crate::db::entities::tasks;
+use crate::db::types::TaskType;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Due tasks fetched per slot, so there is something to choose from
+pub const FAIR_QUEUE_WINDOW: u64 = 8;
+
+/// Serve records older than this many selections are forgotten
+const STALE_ROUNDS: u64 = 1000;
+
+#[derive(Default)]
+struct FairQueueState {
+    /// Smooth weighted round-robin credit per task type
+    credit: HashMap<TaskType, i64>,
+    /// Selection round in which each task was last picked
+    last_served: HashMap<i32, u64>,
+    round: u64,
+}
+
+/// Weighted fair scheduling policy shared by the clones of one engine
+#[derive(Clone)]
+pub struct FairQueue {
+    weights: Arc<HashMap<TaskType, i64>>,
+    state: Arc<Mutex<FairQueueState>>,
+}
+
+impl FairQueue {
+    /// Types missing from `weights` get weight 1
+    pub fn new(weights: &[(TaskType, u32)]) -> Self {
+        Self {
+            weights: Arc::new(
+                weights
+                    .iter()
+                    .map(|&(task_type, weight)| (task_type, i64::from(weight.max(1))))
+                    .collect(),
+            ),
+            state: Arc::new(Mutex::new(FairQueueState::default())),
+        }
+    }
+
+    fn weight(&self, task_type: TaskType) -> i64 {
+        self.weights.get(&task_type).copied().unwrap_or(1)
+    }
+
+    /// Pick up to `limit` of the due `candidates` (ordered by `next_poll_at`)
+    pub fn select(&self, candidates: Vec<tasks::Model>, limit: usize) -> Vec<tasks::Model> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.round += 1;
+        let round = state.round;
+
+        // Per type, least recently served first; the stable sort keeps poll order for ties
+        let mut queues: HashMap<TaskType, Vec<tasks::Model>> = HashMap::new();
+        for task in candidates {
+            queues.entry(task.r#type).or_default().push(task);
+        }
+        // Types without due work don't bank credit for later
+        state
+            .credit
+            .retain(|task_type, _| queues.contains_key(task_type));
+        for queue in queues.values_mut() {
+            queue.sort_by_key(|task| state.last_served.get(&task.id).copied().unwrap_or(0));
+            queue.reverse();
+        }
+
+        let mut selected = Vec::with_capacity(limit);
+        while selected.len() < limit {
+            let mut active: Vec<TaskType> = queues
+                .iter()
+                .filter(|(_, queue)| !queue.is_empty())
+                .map(|(&task_type, _)| task_type)
+                .collect();
+            if active.is_empty() {
+                break;
+            }
+            active.sort_by_cached_key(|task_type| task_type.to_string());
+
+            let total: i64 = active.iter().map(|&t| self.weight(t)).sum();
+            for &task_type in &active {
+                *state.credit.entry(task_type).or_default() += self.weight(task_type);
+            }
+            let Some(&picked) = active
+                .iter()
+                .max_by_key(|task_type| state.credit.get(*task_type).copied().unwrap_or(0))
+            else {
+                break;
+            };
+            *state.credit.entry(picked).or_default() -= total;
+
+            let task = queues
+                .get_mut(&picked)
+                .and_then(Vec::pop)
+                .expect("active queues are not empty");
+            state.last_served.insert(task.id, round);
+            selected.push(task);
+        }
+
+        state
+            .last_served
+            .retain(|_, served| round - *served < STALE_ROUNDS);
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn task(id: i32, r#type: TaskType) -> tasks::Model {
+        tasks::Model {
+            id,
+            r#type,
+            value: id.to_string(),
+            next_poll_at: NaiveDateTime::default(),
+            last_polled_at: None,
+            author_name: None,
+            poll_interval_min: None,
+            last_success_at: None,
+            last_error: None,
+            last_error_at: None,
+            failure_count: 0,
+            dead_at: None,
+        }
+    }
+
+    fn ids(tasks: &[tasks::Model]) -> Vec<i32> {
+        tasks.iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn select_rotates_through_tasks_of_one_type() {
+        let queue = FairQueue::new(&[]);
+        let candidates = || (1..=3).map(|id| task(id, TaskType::Author)).collect();
+
+        assert_eq!(ids(&queue.select(candidates(), 2)), [1, 2]);
+        // Task 1 is still first by poll time but was served more recently than 3
+        assert_eq!(ids(&queue.select(candidates(), 2)), [3, 1]);
+        assert_eq!(ids(&queue.select(candidates(), 1)), [2]);
+    }
+
+    #[test]
+    fn select_interleaves_types_by_weight() {
+        let queue = FairQueue::new(&[(TaskType::BooruTag, 2)]);
+        let mut candidates: Vec<_> = (1..=6).map(|id| task(id, TaskType::BooruTag)).collect();
+        candidates.extend((7..=9).map(|id| task(id, TaskType::BooruRanking)));
+
+        let types: Vec<TaskType> = queue
+            .select(candidates, 6)
+            .iter()
+            .map(|task| task.r#type)
+            .collect();
+        let tags = types.iter().filter(|&&t| t == TaskType::BooruTag).count();
+        assert_eq!(tags, 4);
+        assert_ne!(
+            types[0], types[1],
+            "types must alternate, not drain one backlog"
+        );
+
+        // Work-conserving: a lone type still fills every slot
+        let only_tags = (1..=3).map(|id| task(id, TaskType::BooruTag)).collect();
+        assert_eq!(queue.select(only_tags, 3).len(), 3);
+    }
+}
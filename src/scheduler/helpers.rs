@@ -2,22 +2,64 @@ use crate::bot::notifier::{
     BatchSendResult, ContinuationNumbering, DownloadButtonConfig, Notifier,
 };
 use crate::db::entities::{chats, subscriptions};
-use crate::db::repo::Repo;
+use crate::db::repo::{is_version_conflict, Repo};
 use crate::db::types::{
-    AuthorState, BooruRankingState, BooruTagState, EhTagState, RankingState, SubscriptionState,
-    TagFilter,
+    AiFilter, AuthorState, BookmarksState, BooruRankingState, BooruTagState, EhTagState,
+    RankingState, SubscriptionState, TagFilter,
 };
 use crate::pixiv::client::PixivClient;
 use crate::utils::{caption, sensitive};
 use anyhow::{Context, Result};
-use pixiv_client::Illust;
+use pixiv_client::{AccessRestriction, Illust};
+use rand::RngExt;
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 use teloxide::prelude::*;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 
 pub const INTER_SUBSCRIPTION_DELAY_MS: u64 = 2000;
 
+/// Jittered pause between subscriptions of one task, so pushes don't go out
+/// in a fixed rhythm.
+///
+/// Half of the average is always waited, the other half is exponentially
+/// distributed, and a single pause never exceeds `max`.
+#[derive(Debug, Clone, Copy)]
+pub struct SubscriptionDelay {
+    average: Duration,
+    max: Duration,
+}
+
+impl SubscriptionDelay {
+    pub fn new(average_ms: u64, max_ms: u64) -> Self {
+        Self {
+            average: Duration::from_millis(average_ms),
+            max: Duration::from_millis(max_ms.max(average_ms)),
+        }
+    }
+
+    /// Pause length for a uniform sample `u` in `[0, 1)`
+    fn delay_for(&self, u: f64) -> Duration {
+        let half = self.average / 2;
+        let jitter = half.mul_f64(-(1.0 - u).ln());
+        (half + jitter).min(self.max)
+    }
+
+    /// Sleep before the next subscription
+    pub async fn wait(&self) {
+        if self.average.is_zero() {
+            return;
+        }
+        let u: f64 = rand::rng().random();
+        tokio::time::sleep(self.delay_for(u)).await;
+    }
+}
+
+/// How many times a state write is attempted when racing concurrent writers
+const MAX_STATE_SAVE_ATTEMPTS: usize = 3;
+
 /// Result of processing a single illust push
 #[derive(Debug)]
 pub enum PushResult {
@@ -42,6 +84,8 @@ pub struct AuthorContext<'a> {
     pub subscription: &'a crate::db::entities::subscriptions::Model,
     pub chat: crate::db::entities::chats::Model,
     pub subscription_state: Option<crate::db::types::AuthorState>,
+    /// Pushing to a mirror target (`subscription_targets`) instead of the subscription's own chat
+    pub mirror_target: bool,
 }
 
 /// Context for processing a single ranking subscription
@@ -88,14 +132,106 @@ pub fn eh_tag_subscription_state(subscription: &subscriptions::Model) -> Option<
     }
 }
 
+pub fn bookmarks_subscription_state(subscription: &subscriptions::Model) -> Option<BookmarksState> {
+    match &subscription.latest_data {
+        Some(SubscriptionState::Bookmarks(state)) => Some(state.clone()),
+        _ => None,
+    }
+}
+
 pub fn apply_subscription_tag_filter<'a>(
     subscription: &subscriptions::Model,
     chat: &chats::Model,
     illusts: impl IntoIterator<Item = &'a Illust>,
+) -> Vec<&'a Illust> {
+    filter_illusts(
+        &subscription.filter_tags,
+        subscription.ai_filter,
+        chat,
+        illusts,
+    )
+}
+
+/// Apply a subscription tag filter merged with the chat's excluded tags,
+/// dropping works of blocked authors, R-18 works when the chat has them disabled
+/// and AI works according to the chat's default AI filter
+///
+/// This is the same filter pipeline the engines use before pushing, exposed so
+/// `/preview` can show exactly what a subscription would deliver.
+pub fn filter_illusts_for_chat<'a>(
+    filter: &TagFilter,
+    chat: &chats::Model,
+    illusts: impl IntoIterator<Item = &'a Illust>,
+) -> Vec<&'a Illust> {
+    filter_illusts(filter, AiFilter::Auto, chat, illusts)
+}
+
+fn filter_illusts<'a>(
+    filter: &TagFilter,
+    ai_filter: AiFilter,
+    chat: &chats::Model,
+    illusts: impl IntoIterator<Item = &'a Illust>,
 ) -> Vec<&'a Illust> {
     let chat_filter = TagFilter::from_excluded_tags(&chat.excluded_tags);
-    let combined_filter = subscription.filter_tags.merged(&chat_filter);
-    combined_filter.filter(illusts)
+    illusts
+        .into_iter()
+        .filter(|illust| filter_rejection(filter, ai_filter, &chat_filter, chat, illust).is_none())
+        .collect()
+}
+
+/// Why [`filter_illusts_for_chat`] drops a work
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterRejection {
+    /// Carries a tag excluded by the chat
+    ChatExcludedTag,
+    /// Misses the subscription's include tags or carries one of its exclude tags
+    SubscriptionTags,
+    /// Drawn by an author the chat blocked
+    BlockedAuthor,
+    /// R-18 work in a chat with R-18 disabled
+    R18Blocked,
+    /// AI work excluded, or non-AI work when only AI works are wanted
+    AiFiltered,
+}
+
+/// First filter of the pipeline that drops `illust`, `None` when it is delivered
+///
+/// `ai_filter` is the subscription's override, `Auto` for the chat's default.
+pub fn illust_filter_rejection(
+    filter: &TagFilter,
+    ai_filter: AiFilter,
+    chat: &chats::Model,
+    illust: &Illust,
+) -> Option<FilterRejection> {
+    let chat_filter = TagFilter::from_excluded_tags(&chat.excluded_tags);
+    filter_rejection(filter, ai_filter, &chat_filter, chat, illust)
+}
+
+fn filter_rejection(
+    filter: &TagFilter,
+    ai_filter: AiFilter,
+    chat_filter: &TagFilter,
+    chat: &chats::Model,
+    illust: &Illust,
+) -> Option<FilterRejection> {
+    // Chat excludes only add exclude tags, so checking both filters in turn
+    // is the same as checking the merged filter
+    if !chat_filter.matches(illust) {
+        Some(FilterRejection::ChatExcludedTag)
+    } else if !filter.matches(illust) {
+        Some(FilterRejection::SubscriptionTags)
+    } else if chat.blocked_authors.contains(&illust.user.id) {
+        Some(FilterRejection::BlockedAuthor)
+    } else if sensitive::is_r18_blocked(chat, illust) {
+        Some(FilterRejection::R18Blocked)
+    } else if !ai_filter
+        .resolve(chat.ai_filter)
+        .allows(illust.is_ai_generated())
+    {
+        Some(FilterRejection::AiFiltered)
+    } else {
+        None
+    }
 }
 
 pub async fn save_first_message_record(
@@ -117,6 +253,130 @@ pub async fn save_first_message_record(
     }
 }
 
+/// Append a push attempt to the push log used by /stats
+pub async fn log_push(
+    repo: &Repo,
+    chat_id: ChatId,
+    subscription_id: i32,
+    items: usize,
+    success: bool,
+) {
+    if let Err(e) = repo
+        .record_push(chat_id.0, subscription_id, items, success)
+        .await
+    {
+        warn!("Failed to record push log: {:#}", e);
+    }
+}
+
+/// Record whether a task run succeeded, for the failing task report of /health
+pub async fn record_task_result<T>(repo: &Repo, task_id: i32, result: &Result<T>) {
+    let recorded = match result {
+        Ok(_) => repo.record_task_success(task_id).await,
+        Err(e) => repo.record_task_failure(task_id, &format!("{:#}", e)).await,
+    };
+    if let Err(e) = recorded {
+        warn!("Failed to record result of task {}: {:#}", task_id, e);
+    }
+}
+
+/// Remember which artworks a chat has received so other subscriptions skip them
+pub async fn record_sent_artworks(
+    repo: &Repo,
+    chat_id: ChatId,
+    illust_ids: &[u64],
+    first_message_id: Option<i32>,
+) {
+    let Some(msg_id) = first_message_id else {
+        return;
+    };
+
+    for &illust_id in illust_ids {
+        if let Err(e) = repo
+            .record_sent_artwork(chat_id.0, illust_id as i64, msg_id)
+            .await
+        {
+            warn!("Failed to record sent artwork {}: {:#}", illust_id, e);
+        }
+    }
+}
+
+/// Record the works of an album or ranking batch, each with the message it went out in
+///
+/// `illust_ids` are the works in the order their images were sent. Every work maps
+/// to its own message so `/resend` copies the right image.
+pub async fn record_sent_batch(
+    repo: &Repo,
+    chat_id: ChatId,
+    illust_ids: &[u64],
+    result: &BatchSendResult,
+) {
+    for &(index, message_id) in &result.message_ids {
+        if let Some(&illust_id) = illust_ids.get(index) {
+            record_sent_artworks(repo, chat_id, &[illust_id], Some(message_id)).await;
+        }
+    }
+}
+
+/// Subset of `illust_ids` already delivered to the chat.
+///
+/// Lookup failures are logged and treated as "nothing sent" so pushes are not blocked.
+pub async fn already_sent_illust_ids(
+    repo: &Repo,
+    chat_id: ChatId,
+    illust_ids: &[u64],
+) -> HashSet<u64> {
+    repo.get_sent_illust_ids(chat_id.0, illust_ids)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to query sent artworks for chat {}: {:#}",
+                chat_id, e
+            );
+            HashSet::new()
+        })
+}
+
+/// Persist a subscription state with optimistic locking.
+///
+/// Uses the version the subscription was loaded with. On conflict the row is
+/// reloaded, our state is merged into the concurrently written one and the
+/// write is retried, so pending/pushed progress from either side is not lost.
+pub async fn save_subscription_state(
+    repo: &Repo,
+    subscription: &subscriptions::Model,
+    state: SubscriptionState,
+) -> Result<subscriptions::Model> {
+    let mut expected_version = subscription.version;
+    let mut state = state;
+    let mut attempt = 1;
+
+    loop {
+        match repo
+            .update_subscription_latest_data(subscription.id, expected_version, Some(state.clone()))
+            .await
+        {
+            Ok(updated) => return Ok(updated),
+            Err(e) if is_version_conflict(&e) && attempt < MAX_STATE_SAVE_ATTEMPTS => {
+                warn!(
+                    "State conflict on subscription {} (attempt {}), merging and retrying",
+                    subscription.id, attempt
+                );
+                let current = repo
+                    .get_subscription(subscription.id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Subscription {} not found", subscription.id))?;
+                if let Some(current_state) = current.latest_data.as_ref() {
+                    state = state.merge_concurrent(current_state);
+                }
+                expected_version = current.version;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 /// Get chat and check if should notify (enabled or admin)
 pub async fn get_chat_if_should_notify(
     repo: &Repo,
@@ -143,7 +403,23 @@ pub async fn get_chat_if_should_notify(
     }
 }
 
+/// Decide whether an illust push should be spoilered
+///
+/// The subscription's `spoiler_mode` overrides the chat's sensitive tag decision.
+/// `force_spoiler` carries the decision made for earlier batches of the same work.
+pub fn illust_spoiler(ctx: &AuthorContext<'_>, illust: &Illust, force_spoiler: bool) -> bool {
+    force_spoiler
+        || ctx
+            .subscription
+            .spoiler_mode
+            .resolve(sensitive::should_blur(&ctx.chat, illust))
+}
+
 /// Generic push executor: Send specific illust pages (excluding already sent pages)
+///
+/// Silent subscriptions are sent without a notification, and into the forum
+/// topic when the subscription has one. `image_size` is the global push size,
+/// the subscription's `size=` overrides it.
 pub async fn process_illust_push(
     notifier: &Notifier,
     pixiv: &Arc<RwLock<PixivClient>>,
@@ -151,13 +427,23 @@ pub async fn process_illust_push(
     illust: &Illust,
     already_sent_pages: &[usize],
     image_size: pixiv_client::ImageSize,
+    force_spoiler: bool,
 ) -> Result<PushResult> {
+    let notifier = &*notifier.for_subscription(ctx.subscription);
+
+    // Restricted works only come with placeholder images, push them as text instead
+    if let Some(restriction) = illust.access_restriction() {
+        return process_restricted_push(notifier, ctx, illust, restriction, already_sent_pages)
+            .await;
+    }
+
     // For ugoira works, delegate to the specialized handler
     if illust.is_ugoira() {
         return process_ugoira_push(notifier, pixiv, ctx, illust).await;
     }
 
     let chat_id = ChatId(ctx.subscription.chat_id);
+    let image_size = ctx.subscription.image_size.resolve(image_size);
     let all_urls = illust.get_all_image_urls_with_size(image_size);
     let total_pages = all_urls.len();
 
@@ -184,13 +470,15 @@ pub async fn process_illust_push(
     } else {
         caption::build_continuation_caption(illust, already_sent_pages.len(), total_pages)
     };
+    let caption = notifier.process_caption(caption, illust, &ctx.chat);
 
-    // Check spoiler setting
-    let has_spoiler = sensitive::should_blur(&ctx.chat, illust);
+    // Check spoiler setting (continuation batches may inherit an earlier spoiler)
+    let has_spoiler = illust_spoiler(ctx, illust, force_spoiler);
 
     // Build download button config
     // Skip download button for channel chats (channels don't support inline buttons)
-    let download_config = DownloadButtonConfig::for_pixiv_chat(illust.id, &ctx.chat);
+    let download_config =
+        DownloadButtonConfig::for_pixiv_chat(illust.id, &ctx.chat).with_unblur_button();
 
     // Send images with download button
     let continuation_numbering = (!already_sent_pages.is_empty()).then(|| {
@@ -222,9 +510,49 @@ pub async fn process_illust_push(
         total_pages,
     );
 
+    if ctx.chat.send_original && total_pages == 1 && already_sent_pages.is_empty() {
+        if let PushResult::Success {
+            first_message_id, ..
+        } = &result
+        {
+            send_original_file(notifier, chat_id, illust, *first_message_id).await;
+        }
+    }
+
     Ok(result)
 }
 
+/// Follow a delivered single-page work with its original file (best effort)
+async fn send_original_file(
+    notifier: &Notifier,
+    chat_id: ChatId,
+    illust: &Illust,
+    reply_to: Option<i32>,
+) {
+    let Some(original_url) = illust
+        .get_all_image_urls_with_size(pixiv_client::ImageSize::Original)
+        .into_iter()
+        .next()
+    else {
+        return;
+    };
+
+    match notifier
+        .send_original_document(chat_id, &original_url, reply_to)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => info!(
+            "Original of illust {} exceeds the size limit, not sent to chat {}",
+            illust.id, chat_id
+        ),
+        Err(e) => warn!(
+            "Failed to send original of illust {} to chat {}: {:#}",
+            illust.id, chat_id, e
+        ),
+    }
+}
+
 /// Map BatchSendResult to PushResult
 fn map_send_result_to_push_result(
     illust_id: u64,
@@ -279,6 +607,53 @@ fn map_send_result_to_push_result(
 }
 
 /// Push a ugoira (animated) illust as an MP4 animation
+/// Push a work the Pixiv account cannot see as a text notice.
+///
+/// Its images will not become available on retry, so only a failed text message is
+/// reported as a failure.
+async fn process_restricted_push(
+    notifier: &Notifier,
+    ctx: &AuthorContext<'_>,
+    illust: &Illust,
+    restriction: AccessRestriction,
+    already_sent_pages: &[usize],
+) -> Result<PushResult> {
+    let chat_id = ChatId(ctx.subscription.chat_id);
+    info!(
+        "Illust {} is restricted ({:?}), sending text notice to chat {}",
+        illust.id, restriction, chat_id
+    );
+
+    // Pages sent before the work became restricted are all this chat will get
+    if !already_sent_pages.is_empty() {
+        return Ok(PushResult::Success {
+            illust_id: illust.id,
+            first_message_id: None,
+        });
+    }
+
+    let caption = notifier.process_caption(
+        caption::build_restricted_caption(illust, restriction),
+        illust,
+        &ctx.chat,
+    );
+    match notifier.send_text(chat_id, &caption, false).await {
+        Ok(message_id) => Ok(PushResult::Success {
+            illust_id: illust.id,
+            first_message_id: Some(message_id),
+        }),
+        Err(e) => {
+            warn!(
+                "Failed to send restricted illust {} to chat {}: {:#}",
+                illust.id, chat_id, e
+            );
+            Ok(PushResult::Failure {
+                illust_id: illust.id,
+            })
+        }
+    }
+}
+
 async fn process_ugoira_push(
     notifier: &Notifier,
     pixiv: &Arc<RwLock<PixivClient>>,
@@ -296,13 +671,15 @@ async fn process_ugoira_push(
     drop(pixiv_guard);
 
     // Prepare caption (same format as regular illusts, with 🎞️ indicator)
-    let caption = caption::build_ugoira_caption(illust);
+    let caption =
+        notifier.process_caption(caption::build_ugoira_caption(illust), illust, &ctx.chat);
 
     // Check spoiler setting
-    let has_spoiler = sensitive::should_blur(&ctx.chat, illust);
+    let has_spoiler = illust_spoiler(ctx, illust, false);
 
     // Build download button config
-    let download_config = DownloadButtonConfig::for_pixiv_chat(illust.id, &ctx.chat);
+    let download_config =
+        DownloadButtonConfig::for_pixiv_chat(illust.id, &ctx.chat).with_unblur_button();
 
     // Send ugoira as MP4 animation
     let send_result = notifier
@@ -333,14 +710,17 @@ async fn process_ugoira_push(
 mod tests {
     use super::{
         apply_subscription_tag_filter, author_subscription_state, booru_ranking_subscription_state,
-        ranking_subscription_state, INTER_SUBSCRIPTION_DELAY_MS,
+        filter_illusts_for_chat, illust_filter_rejection, ranking_subscription_state,
+        FilterRejection, SubscriptionDelay, INTER_SUBSCRIPTION_DELAY_MS,
     };
     use crate::db::entities::{chats, subscriptions};
     use crate::db::types::{
-        AuthorState, BooruRankingState, RankingState, SubscriptionState, TagFilter, Tags,
+        AiFilter, AuthorIds, AuthorState, BooruRankingState, RankingState, SubscriptionState,
+        TagFilter, Tags,
     };
     use pixiv_client::Illust;
     use serde_json::json;
+    use std::time::Duration;
 
     fn make_chat(excluded_tags: &[&str]) -> chats::Model {
         chats::Model {
@@ -353,6 +733,15 @@ mod tests {
             sensitive_tags: Tags::default(),
             created_at: chrono::Utc::now().naive_utc(),
             allow_without_mention: false,
+            allow_r18: false,
+            send_original: false,
+            bot_id: None,
+            link_preview: true,
+            blocked_authors: AuthorIds::default(),
+            slow_mode_delay: None,
+            ai_filter: Default::default(),
+            default_filter: Default::default(),
+            notify_failures: false,
         }
     }
 
@@ -369,6 +758,16 @@ mod tests {
             eh_filter: None,
             latest_data,
             created_at: chrono::Utc::now().naive_utc(),
+            version: 0,
+            created_by: None,
+            spoiler_mode: Default::default(),
+            enabled: true,
+            push_mode: Default::default(),
+            silent: false,
+            ranking_date_offset: 0,
+            message_thread_id: None,
+            ai_filter: Default::default(),
+            image_size: Default::default(),
         }
     }
 
@@ -495,8 +894,100 @@ mod tests {
         assert_eq!(filtered[0].id, keep.id);
     }
 
+    #[test]
+    fn filter_illusts_for_chat_drops_r18_unless_allowed() {
+        let mut chat = make_chat(&[]);
+        let safe = make_illust(1, &["cat"]);
+        let mut r18 = make_illust(2, &["cat"]);
+        r18.x_restrict = 1;
+
+        let filtered = filter_illusts_for_chat(&TagFilter::default(), &chat, [&safe, &r18]);
+        assert_eq!(
+            filtered.iter().map(|i| i.id).collect::<Vec<_>>(),
+            vec![safe.id]
+        );
+
+        chat.allow_r18 = true;
+        let filtered = filter_illusts_for_chat(&TagFilter::default(), &chat, [&safe, &r18]);
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn filter_illusts_for_chat_drops_blocked_authors() {
+        let mut chat = make_chat(&[]);
+        let illust = make_illust(1, &["cat"]);
+
+        chat.blocked_authors = AuthorIds(vec![illust.user.id]);
+        assert!(filter_illusts_for_chat(&TagFilter::default(), &chat, [&illust]).is_empty());
+
+        chat.blocked_authors = AuthorIds(vec![illust.user.id + 1]);
+        assert_eq!(
+            filter_illusts_for_chat(&TagFilter::default(), &chat, [&illust]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn illust_filter_rejection_names_the_first_failing_filter() {
+        let filter = TagFilter::parse_from_args(&["+cat"]);
+        let mut chat = make_chat(&["R-18"]);
+        let mut illust = make_illust(1, &["cat", "R-18"]);
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Auto, &chat, &illust),
+            Some(FilterRejection::ChatExcludedTag)
+        );
+
+        illust = make_illust(1, &["dog"]);
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Auto, &chat, &illust),
+            Some(FilterRejection::SubscriptionTags)
+        );
+
+        illust = make_illust(1, &["cat"]);
+        illust.x_restrict = 1;
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Auto, &chat, &illust),
+            Some(FilterRejection::R18Blocked)
+        );
+
+        chat.allow_r18 = true;
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Auto, &chat, &illust),
+            None
+        );
+
+        illust.illust_ai_type = 2;
+        chat.ai_filter = AiFilter::Exclude;
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Auto, &chat, &illust),
+            Some(FilterRejection::AiFiltered)
+        );
+        // The subscription's own setting wins over the chat default
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::All, &chat, &illust),
+            None
+        );
+        illust.illust_ai_type = 1;
+        assert_eq!(
+            illust_filter_rejection(&filter, AiFilter::Only, &chat, &illust),
+            Some(FilterRejection::AiFiltered)
+        );
+    }
+
     #[test]
     fn inter_subscription_delay_constant_stays_two_seconds() {
         assert_eq!(INTER_SUBSCRIPTION_DELAY_MS, 2000);
     }
+
+    #[test]
+    fn subscription_delay_keeps_floor_and_cap() {
+        let delay = SubscriptionDelay::new(2000, 6000);
+        assert_eq!(delay.delay_for(0.0), Duration::from_millis(1000));
+        assert!(delay.delay_for(0.5) > Duration::from_millis(1000));
+        assert_eq!(delay.delay_for(0.9999999), Duration::from_millis(6000));
+
+        // A cap below the average is raised to it
+        let delay = SubscriptionDelay::new(2000, 500);
+        assert_eq!(delay.delay_for(0.9999999), Duration::from_millis(2000));
+    }
 }
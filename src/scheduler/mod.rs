@@ -1,15 +1,33 @@
 mod author_engine;
+mod backup_engine;
+mod bookmark_engine;
 mod booru_engine;
+mod digest_engine;
 mod eh_engine;
+mod fair_queue;
 mod helpers;
 mod name_update_engine;
 mod ranking_engine;
+mod retry_engine;
+mod self_test;
+mod update_checker;
 
-pub use author_engine::AuthorEngine;
+pub use author_engine::{
+    AuthorEngine, AuthorSimulation, SimulatedOutcome, SimulatedPush, SimulatedPushKind,
+};
+pub use backup_engine::SubscriptionBackupEngine;
+pub use bookmark_engine::BookmarkEngine;
 pub use booru_engine::BooruEngine;
+pub use digest_engine::SubscriptionDigestEngine;
 pub use eh_engine::{
     EhBackgroundDownloadWorker, EhDownloadWorker, EhEngine, EhPublishWorker,
     EhTelegraphRewriteWorker, EhUploadWorker,
 };
+pub use helpers::{
+    author_subscription_state, filter_illusts_for_chat, FilterRejection, SubscriptionDelay,
+};
 pub use name_update_engine::NameUpdateEngine;
-pub use ranking_engine::RankingEngine;
+pub use ranking_engine::{RankingEngine, RankingPushOutcome, TopPushOutcome};
+pub use retry_engine::RetryEngine;
+pub use self_test::SelfTest;
+pub use update_checker::{LatestRelease, UpdateChecker};
@@ -1,17 +1,31 @@
+use crate::db::entities::tasks;
 use crate::db::repo::Repo;
 use crate::db::types::TaskType;
-use crate::pixiv::client::PixivClient;
+use crate::pixiv::client::{wait_until_authenticated, PixivClient};
 use anyhow::{Context, Result};
 use chrono::{Local, NaiveTime, TimeZone, Timelike};
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 use tracing::{error, info, warn};
 
+/// Number of nameless author tasks loaded per backfill batch
+const BACKFILL_BATCH_SIZE: u64 = 50;
+
+/// Result of a backfill run over tasks without an author name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NameBackfillSummary {
+    pub updated: usize,
+    pub failed: usize,
+}
+
 /// Engine responsible for daily author name updates
 pub struct NameUpdateEngine {
     repo: Arc<Repo>,
     pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
     execution_time: String,
+    /// Keeps the daily update and backfill runs from overlapping
+    run_lock: Mutex<()>,
 }
 
 impl NameUpdateEngine {
@@ -24,6 +38,7 @@ impl NameUpdateEngine {
             repo,
             pixiv_client,
             execution_time,
+            run_lock: Mutex::new(()),
         }
     }
 
@@ -34,6 +49,12 @@ impl NameUpdateEngine {
             self.execution_time
         );
 
+        // Tasks created before names were stored still show raw IDs, fill them in right away
+        wait_until_authenticated(&self.pixiv_client).await;
+        if let Err(e) = self.backfill_missing_names().await {
+            error!("Author name backfill error: {:#}", e);
+        }
+
         loop {
             // Calculate next execution time
             let next_execution = match self.calculate_next_execution_time() {
@@ -57,6 +78,8 @@ impl NameUpdateEngine {
             // Wait until execution time
             sleep(duration_until_execution).await;
 
+            wait_until_authenticated(&self.pixiv_client).await;
+
             // Execute author name updates
             if let Err(e) = self.update_all_author_names().await {
                 error!("Author name update error: {:#}", e);
@@ -94,12 +117,78 @@ impl NameUpdateEngine {
         Ok((time.hour(), time.minute()))
     }
 
+    /// Resolve names for author tasks that have none stored, in batches
+    pub async fn backfill_missing_names(&self) -> Result<NameBackfillSummary> {
+        let _guard = self.run_lock.lock().await;
+        info!("🔄 Starting author name backfill...");
+
+        let mut summary = NameBackfillSummary::default();
+        // Failed tasks keep a NULL name, so page by id instead of re-querying from the start
+        let mut after_id = 0;
+
+        loop {
+            let batch = self
+                .repo
+                .get_author_tasks_without_name(after_id, BACKFILL_BATCH_SIZE)
+                .await?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after_id = last.id;
+
+            for task in batch {
+                match self.fetch_author_name(&task).await {
+                    Ok(name) => match self
+                        .repo
+                        .update_task_author_name(task.id, Some(name.clone()))
+                        .await
+                    {
+                        Ok(_) => {
+                            info!("Backfilled author name: {} (ID: {})", name, task.value);
+                            summary.updated += 1;
+                        }
+                        Err(e) => {
+                            error!("Failed to update author name for task {}: {:#}", task.id, e);
+                            summary.failed += 1;
+                        }
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Failed to resolve author name for task {} ({}): {:#}",
+                            task.id, task.value, e
+                        );
+                        summary.failed += 1;
+                    }
+                }
+
+                // Small delay between API calls to avoid rate limiting
+                sleep(Duration::from_millis(500)).await;
+            }
+        }
+
+        info!(
+            "✅ Author name backfill completed: {} updated, {} failed",
+            summary.updated, summary.failed
+        );
+
+        Ok(summary)
+    }
+
+    async fn fetch_author_name(&self, task: &tasks::Model) -> Result<String> {
+        let author_id: u64 = task.value.parse().context("Invalid author ID")?;
+        let pixiv = self.pixiv_client.read().await;
+        let user = pixiv.get_user_detail(author_id).await?;
+        Ok(user.name)
+    }
+
     /// Update all author names by fetching latest from Pixiv API
     async fn update_all_author_names(&self) -> Result<()> {
+        let _guard = self.run_lock.lock().await;
         info!("🔄 Starting author name update...");
 
-        // Get all author tasks
-        let tasks = self.repo.get_all_tasks_by_type(TaskType::Author).await?;
+        // Get all author tasks, skipping authors that no longer exist
+        let mut tasks = self.repo.get_all_tasks_by_type(TaskType::Author).await?;
+        tasks.retain(|task| task.dead_at.is_none());
 
         if tasks.is_empty() {
             info!("No author tasks to update");
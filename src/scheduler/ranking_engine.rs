@@ -1,19 +1,59 @@
 use crate::bot::notifier::{BatchSendResult, DownloadButtonConfig, Notifier};
+use crate::config::ImageSizePolicy;
+use crate::db::entities::{chats, subscriptions, tasks};
 use crate::db::repo::Repo;
-use crate::db::types::{SubscriptionState, TaskType};
-use crate::pixiv::client::PixivClient;
+use crate::db::types::{SubscriptionState, TagFilter, TaskType};
+use crate::pixiv::client::{wait_until_authenticated, PixivClient};
 use crate::scheduler::helpers::{
-    apply_subscription_tag_filter, get_chat_if_should_notify, ranking_subscription_state,
-    save_first_message_record, RankingContext, INTER_SUBSCRIPTION_DELAY_MS,
+    already_sent_illust_ids, apply_subscription_tag_filter, filter_illusts_for_chat,
+    get_chat_if_should_notify, log_push, ranking_subscription_state, record_sent_batch,
+    record_task_result, save_first_message_record, save_subscription_state, RankingContext,
+    SubscriptionDelay,
+};
+use crate::utils::caption::{
+    build_ranking_caption, build_ranking_title, build_top_ranking_title, format_rank,
 };
-use crate::utils::caption::{build_ranking_caption, build_ranking_title};
 use anyhow::{Context, Result};
-use chrono::{Local, NaiveTime, TimeZone, Timelike};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
 use pixiv_client::Illust;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use teloxide::prelude::*;
 use tokio::time::{sleep, Duration};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Delay before re-running failed ranking tasks, multiplied by the attempt number
+const RANKING_RETRY_DELAY_SECS: u64 = 300;
+
+/// Result of an on-demand ranking push
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingPushOutcome {
+    /// New ranking works were processed for the subscription
+    Pushed,
+    /// Every work in today's ranking was already pushed
+    NothingNew,
+    /// The chat is disabled or otherwise not accepting pushes
+    ChatInactive,
+}
+
+/// Result of a `/top` ranking lookup
+#[derive(Debug)]
+pub enum TopPushOutcome {
+    /// Pixiv returned no works for the mode and date
+    Empty,
+    /// Every fetched work was removed by the chat's filters
+    AllFiltered,
+    /// The remaining works were sent
+    Sent(BatchSendResult),
+}
+
+/// Ranking works fetched for a mode on a given day, shared by all subscriptions of that mode
+#[derive(Debug, Clone)]
+struct RankingSnapshot {
+    date: NaiveDate,
+    illust_ids: Vec<u64>,
+}
 
 pub struct RankingEngine {
     repo: Arc<Repo>,
@@ -21,30 +61,45 @@ pub struct RankingEngine {
     notifier: Notifier,
     execution_time: String,
     image_size: pixiv_client::ImageSize,
+    /// Same-day re-runs of tasks whose push failed
+    max_retry_count: u8,
+    /// Show medals instead of `#1`-`#3` for the top 3 works
+    medals: bool,
+    subscription_delay: SubscriptionDelay,
+    /// Today's ranking per mode, used to seed subscriptions created after the daily push
+    snapshots: Mutex<HashMap<String, RankingSnapshot>>,
 }
 
 impl RankingEngine {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         repo: Arc<Repo>,
         pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
         notifier: Notifier,
         execution_time: String,
-        image_size: pixiv_client::ImageSize,
+        image_sizes: ImageSizePolicy,
+        max_retry_count: u8,
+        medals: bool,
+        subscription_delay: SubscriptionDelay,
     ) -> Self {
         Self {
             repo,
             pixiv_client,
             notifier,
             execution_time,
-            image_size,
+            image_size: image_sizes.push,
+            max_retry_count,
+            medals,
+            subscription_delay,
+            snapshots: Mutex::new(HashMap::new()),
         }
     }
 
     /// Main scheduler loop - runs indefinitely at specified time daily
     pub async fn run(&self) {
         info!(
-            "🚀 Ranking engine started (execution time: {})",
-            self.execution_time
+            "🚀 Ranking engine started (execution time: {}, max retries: {})",
+            self.execution_time, self.max_retry_count
         );
 
         loop {
@@ -70,6 +125,8 @@ impl RankingEngine {
             // Wait until execution time
             sleep(duration_until_execution).await;
 
+            wait_until_authenticated(&self.pixiv_client).await;
+
             // Execute all ranking tasks
             if let Err(e) = self.execute_all_ranking_tasks().await {
                 error!("Ranking engine execution error: {:#}", e);
@@ -121,25 +178,68 @@ impl RankingEngine {
 
         info!("Found {} ranking tasks to execute", tasks.len());
 
+        let mut failed = self.execute_ranking_tasks(tasks).await;
+
+        // Re-run failed tasks the same day; subscriptions that already got today's
+        // ranking have nothing new and are skipped
+        for attempt in 1..=self.max_retry_count {
+            if failed.is_empty() {
+                break;
+            }
+            let delay = Duration::from_secs(RANKING_RETRY_DELAY_SECS * u64::from(attempt));
+            warn!(
+                "{} ranking task(s) failed, retrying in {}s (attempt {}/{})",
+                failed.len(),
+                delay.as_secs(),
+                attempt,
+                self.max_retry_count
+            );
+            sleep(delay).await;
+            failed = self.execute_ranking_tasks(failed).await;
+        }
+
+        if !failed.is_empty() {
+            error!(
+                "❌ {} ranking task(s) still failing, waiting for the next execution",
+                failed.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Execute ranking tasks one by one, returning those that should be retried
+    async fn execute_ranking_tasks(&self, tasks: Vec<tasks::Model>) -> Vec<tasks::Model> {
+        let mut failed = Vec::new();
+
         for task in tasks {
             debug!(
                 "⚙️  Executing ranking task [{}] {} {}",
                 task.id, task.r#type, task.value
             );
 
-            if let Err(e) = self.execute_ranking_task(&task).await {
-                error!("Failed to execute ranking task [{}]: {:#}", task.id, e);
+            let result = self.execute_ranking_task(&task).await;
+            record_task_result(&self.repo, task.id, &result).await;
+            match result {
+                Ok(true) => {}
+                Ok(false) => failed.push(task),
+                Err(e) => {
+                    error!("Failed to execute ranking task [{}]: {:#}", task.id, e);
+                    failed.push(task);
+                }
             }
 
             // Small delay between tasks
             sleep(Duration::from_secs(2)).await;
         }
 
-        Ok(())
+        failed
     }
 
     /// Execute ranking subscription task (Orchestrator)
-    async fn execute_ranking_task(&self, task: &crate::db::entities::tasks::Model) -> Result<()> {
+    ///
+    /// Returns false if any subscription could not be pushed.
+    async fn execute_ranking_task(&self, task: &tasks::Model) -> Result<bool> {
         let mode = &task.value;
 
         // Get ranking illusts from Pixiv API
@@ -150,20 +250,26 @@ impl RankingEngine {
         if illusts.is_empty() {
             info!("No ranking illusts found for mode {}", mode);
             self.schedule_ranking_next_poll(task.id).await?;
-            return Ok(());
+            return Ok(true);
         }
 
         info!("Found {} ranking illusts for mode {}", illusts.len(), mode);
+        self.record_snapshot(mode, &illusts);
 
         // Get all subscriptions for this task
-        let subscriptions = self.repo.list_subscriptions_by_task(task.id).await?;
+        let subscriptions = self.repo.list_active_subscriptions_by_task(task.id).await?;
 
         if subscriptions.is_empty() {
             info!("No subscriptions for ranking task {}", task.id);
             self.schedule_ranking_next_poll(task.id).await?;
-            return Ok(());
+            return Ok(true);
         }
 
+        let mut all_pushed = true;
+        // Rankings of earlier days, fetched once for all subscriptions with that offset
+        let mut earlier_rankings: HashMap<i32, (String, Vec<Illust>)> = HashMap::new();
+        let today = Local::now().date_naive();
+
         // Process each subscription independently (one push per subscription per tick)
         for subscription in subscriptions {
             // Prepare context
@@ -172,10 +278,38 @@ impl RankingEngine {
                 Ok(None) => continue,
                 Err(e) => {
                     error!("Failed to process chat {}: {:#}", subscription.chat_id, e);
+                    all_pushed = false;
                     continue;
                 }
             };
 
+            let (date, ranking) = match ranking_date(subscription.ranking_date_offset, today) {
+                None => (None, &illusts),
+                Some(date) => {
+                    let offset = subscription.ranking_date_offset;
+                    if !earlier_rankings.contains_key(&offset) {
+                        let pixiv = self.pixiv_client.read().await;
+                        let fetched = pixiv.get_ranking(mode, Some(&date), 10).await;
+                        drop(pixiv);
+                        match fetched {
+                            Ok(fetched) => {
+                                earlier_rankings.insert(offset, (date, fetched));
+                            }
+                            Err(e) => {
+                                error!(
+                                    "Failed to get {} ranking of {} for subscription {}: {:#}",
+                                    mode, date, subscription.id, e
+                                );
+                                all_pushed = false;
+                                continue;
+                            }
+                        }
+                    }
+                    let (date, ranking) = &earlier_rankings[&offset];
+                    (Some(date.as_str()), ranking)
+                }
+            };
+
             let subscription_state = ranking_subscription_state(&subscription);
 
             let ctx = RankingContext {
@@ -185,30 +319,194 @@ impl RankingEngine {
             };
 
             // Delegate to dispatcher
-            if let Err(e) = self
-                .process_single_ranking_sub(&ctx, &illusts, mode)
+            let pushed = match self
+                .process_single_ranking_sub(&ctx, ranking, mode, date)
                 .await
                 .context(format!(
                     "Failed to process subscription {}",
                     subscription.id
-                ))
-            {
-                error!("{:#}", e);
-            }
+                )) {
+                Ok(pushed) => pushed,
+                Err(e) => {
+                    error!("{:#}", e);
+                    all_pushed = false;
+                    true
+                }
+            };
 
-            // Small delay between subscriptions
-            sleep(Duration::from_millis(INTER_SUBSCRIPTION_DELAY_MS)).await;
+            // Jittered delay between subscriptions, only when something was sent
+            if pushed {
+                self.subscription_delay.wait().await;
+            }
         }
 
         // Schedule next poll (next day at execution time)
         self.schedule_ranking_next_poll(task.id).await?;
 
-        Ok(())
+        Ok(all_pushed)
+    }
+
+    /// Fetch today's ranking and push it to a single subscription right away.
+    ///
+    /// Used by the "send now" button after `/subrank`; the task's schedule is left untouched.
+    pub async fn push_subscription_now(
+        &self,
+        subscription: &subscriptions::Model,
+    ) -> Result<RankingPushOutcome> {
+        let task = self
+            .repo
+            .get_task(subscription.task_id)
+            .await?
+            .context("Ranking task not found")?;
+        if task.r#type != TaskType::Ranking {
+            anyhow::bail!("Task {} is not a ranking task", task.id);
+        }
+
+        let Some(chat) = get_chat_if_should_notify(&self.repo, subscription.chat_id).await? else {
+            return Ok(RankingPushOutcome::ChatInactive);
+        };
+
+        let mode = &task.value;
+        let date = ranking_date(subscription.ranking_date_offset, Local::now().date_naive());
+        let pixiv = self.pixiv_client.read().await;
+        let illusts = pixiv.get_ranking(mode, date.as_deref(), 10).await?;
+        drop(pixiv);
+
+        let subscription_state = ranking_subscription_state(subscription);
+        let has_new = illusts.iter().any(|illust| {
+            subscription_state
+                .as_ref()
+                .is_none_or(|s| !s.pushed_ids.contains(&illust.id))
+        });
+        if !has_new {
+            return Ok(RankingPushOutcome::NothingNew);
+        }
+
+        let ctx = RankingContext {
+            subscription,
+            chat,
+            subscription_state,
+        };
+        self.process_single_ranking_sub(&ctx, &illusts, mode, date.as_deref())
+            .await
+            .with_context(|| format!("Failed to process subscription {}", subscription.id))?;
+
+        Ok(RankingPushOutcome::Pushed)
+    }
+
+    /// Fetch a ranking and send its top `count` works to a chat, outside any subscription.
+    ///
+    /// Used by `/top`. The chat's excluded tags, blocked authors and R-18 setting apply and
+    /// works keep their position in the full ranking. Nothing is recorded as pushed.
+    pub async fn push_top(
+        &self,
+        chat: &chats::Model,
+        mode: &str,
+        date: Option<&str>,
+        count: usize,
+    ) -> Result<TopPushOutcome> {
+        let pixiv = self.pixiv_client.read().await;
+        let illusts = pixiv.get_ranking(mode, date, count).await?;
+        drop(pixiv);
+
+        if illusts.is_empty() {
+            return Ok(TopPushOutcome::Empty);
+        }
+
+        let filtered = filter_illusts_for_chat(&TagFilter::default(), chat, &illusts);
+        if filtered.is_empty() {
+            return Ok(TopPushOutcome::AllFiltered);
+        }
+
+        let chat_id = ChatId(chat.id);
+        let ranks = ranking_positions(&illusts, &filtered);
+        let title = build_top_ranking_title(mode, date, illusts.len());
+        let _turn = self.notifier.begin_push(chat_id).await;
+        let result = self
+            .send_ranking_illusts(chat_id, &title, chat, &filtered, &ranks, None)
+            .await?;
+        Ok(TopPushOutcome::Sent(result))
+    }
+
+    /// Mark today's ranking as already pushed for a newly created subscription.
+    ///
+    /// Once today's scheduled push has run, a new subscription would otherwise receive
+    /// the whole current top-10 at the next execution, repeating what other chats got today.
+    /// Existing subscriptions and subscriptions created before today's push are left as is.
+    /// Returns whether the subscription was seeded.
+    pub async fn seed_new_subscription(&self, subscription: &subscriptions::Model) -> Result<bool> {
+        if subscription.latest_data.is_some() {
+            return Ok(false);
+        }
+
+        let today = Local::now().date_naive();
+        if self.calculate_next_execution_time()?.date_naive() <= today {
+            // Today's push is still ahead, the subscription will get it with everyone else
+            return Ok(false);
+        }
+
+        let task = self
+            .repo
+            .get_task(subscription.task_id)
+            .await?
+            .context("Ranking task not found")?;
+        let mode = &task.value;
+
+        let illust_ids = match ranking_date(subscription.ranking_date_offset, today) {
+            // Snapshots only keep the latest ranking
+            Some(date) => {
+                let pixiv = self.pixiv_client.read().await;
+                let illusts = pixiv.get_ranking(mode, Some(&date), 10).await?;
+                drop(pixiv);
+                illusts.iter().map(|illust| illust.id).collect()
+            }
+            None => {
+                let cached = {
+                    let snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+                    snapshot_ids_for(&snapshots, mode, today)
+                };
+                match cached {
+                    Some(ids) => ids,
+                    None => {
+                        let pixiv = self.pixiv_client.read().await;
+                        let illusts = pixiv.get_ranking(mode, None, 10).await?;
+                        drop(pixiv);
+                        self.record_snapshot(mode, &illusts);
+                        illusts.iter().map(|illust| illust.id).collect()
+                    }
+                }
+            }
+        };
+
+        if illust_ids.is_empty() {
+            return Ok(false);
+        }
+
+        info!(
+            "Seeding new ranking subscription {} with {} works from today's {} ranking",
+            subscription.id,
+            illust_ids.len(),
+            mode
+        );
+        self.trim_and_update_pushed_ids(subscription, illust_ids)
+            .await?;
+        Ok(true)
+    }
+
+    fn record_snapshot(&self, mode: &str, illusts: &[Illust]) {
+        let snapshot = RankingSnapshot {
+            date: Local::now().date_naive(),
+            illust_ids: illusts.iter().map(|illust| illust.id).collect(),
+        };
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(mode.to_string(), snapshot);
     }
 
     /// Schedule next poll for ranking task (next execution time)
     async fn schedule_ranking_next_poll(&self, task_id: i32) -> Result<()> {
-        let next_poll = self.calculate_next_execution_time()?;
+        let next_poll = self.calculate_next_execution_time()?.with_timezone(&Utc);
         self.repo.update_task_after_poll(task_id, next_poll).await?;
         Ok(())
     }
@@ -216,12 +514,15 @@ impl RankingEngine {
     // ==================== Ranking-Specific Methods ====================
 
     /// Dispatcher: Process single ranking subscription
+    /// `date` is set when the subscription follows an earlier day's ranking.
+    /// Returns whether anything was sent to the chat
     async fn process_single_ranking_sub(
         &self,
         ctx: &RankingContext<'_>,
         illusts: &[Illust],
         mode: &str,
-    ) -> Result<()> {
+        date: Option<&str>,
+    ) -> Result<bool> {
         let chat_id = ChatId(ctx.subscription.chat_id);
 
         // Get previously pushed IDs
@@ -238,7 +539,7 @@ impl RankingEngine {
             .collect();
 
         if new_illusts.is_empty() {
-            return Ok(());
+            return Ok(false);
         }
 
         info!(
@@ -250,18 +551,26 @@ impl RankingEngine {
         );
 
         // Apply tag filters
-        let filtered_illusts =
+        let mut filtered_illusts =
             apply_subscription_tag_filter(ctx.subscription, &ctx.chat, new_illusts.iter().copied());
 
         // Collect all new IDs for tracking
         let all_new_ids: Vec<u64> = new_illusts.iter().map(|i| i.id).collect();
 
+        // Wait for pushes already running in this chat, and keep our turn until the
+        // ranking is recorded so author pushes see what it sent
+        let _turn = self.notifier.begin_push(chat_id).await;
+
+        // Skip works this chat already received from another subscription
+        let already_sent = already_sent_illust_ids(&self.repo, chat_id, &all_new_ids).await;
+        filtered_illusts.retain(|illust| !already_sent.contains(&illust.id));
+
         // If all filtered out, mark as processed and return
         if filtered_illusts.is_empty() {
             info!("No illusts to send to chat {} after filtering", chat_id);
-            self.mark_ranking_illusts_as_pushed(ctx.subscription.id, pushed_ids, all_new_ids)
+            self.mark_ranking_illusts_as_pushed(ctx.subscription, pushed_ids, all_new_ids)
                 .await?;
-            return Ok(());
+            return Ok(false);
         }
 
         // *** Process ALL filtered ranking illusts in batch ***
@@ -275,9 +584,18 @@ impl RankingEngine {
         for illust in &filtered_illusts {
             illust_ids.push(illust.id);
         }
+        let ranks = ranking_positions(illusts, &filtered_illusts);
 
+        let title = build_ranking_title(mode, date, filtered_illusts.len());
         let send_result = self
-            .send_ranking_illusts(chat_id, mode, &ctx.chat, &filtered_illusts)
+            .send_ranking_illusts(
+                chat_id,
+                &title,
+                &ctx.chat,
+                &filtered_illusts,
+                &ranks,
+                Some(ctx.subscription),
+            )
             .await?;
 
         // Collect successfully sent illust IDs
@@ -287,13 +605,18 @@ impl RankingEngine {
             .filter_map(|&idx| illust_ids.get(idx).copied())
             .collect();
 
+        log_push(
+            &self.repo,
+            chat_id,
+            ctx.subscription.id,
+            successfully_sent_ids.len(),
+            !send_result.is_complete_failure(),
+        )
+        .await;
+
         if send_result.is_complete_failure() {
-            error!(
-                "❌ Failed to send ranking to chat {}, will retry next poll",
-                chat_id
-            );
-            // Don't update pushed_ids, retry next tick
-            return Ok(());
+            // Don't update pushed_ids, the works are sent again on retry
+            anyhow::bail!("Failed to send ranking to chat {}", chat_id);
         }
 
         // Save message record for reply-based unsubscribe (use first illust_id)
@@ -305,11 +628,12 @@ impl RankingEngine {
             illust_ids.first().map(|&id| id as i64),
         )
         .await;
+        record_sent_batch(&self.repo, chat_id, &illust_ids, &send_result).await;
 
         // Update pushed_ids with successfully sent illusts
         let mut new_pushed_ids = pushed_ids.clone();
         new_pushed_ids.extend(successfully_sent_ids);
-        self.trim_and_update_pushed_ids(ctx.subscription.id, new_pushed_ids)
+        self.trim_and_update_pushed_ids(ctx.subscription, new_pushed_ids)
             .await?;
 
         if send_result.is_complete_success() {
@@ -327,51 +651,70 @@ impl RankingEngine {
             );
         }
 
-        Ok(())
+        Ok(true)
     }
 
+    /// Send ranking works with the ranking captions, following the silent and topic
+    /// settings of `subscription` when sent for one
     async fn send_ranking_illusts(
         &self,
         chat_id: ChatId,
-        mode: &str,
+        title: &str,
         chat: &crate::db::entities::chats::Model,
         illusts: &[&Illust],
+        ranks: &[usize],
+        subscription: Option<&subscriptions::Model>,
     ) -> Result<BatchSendResult> {
+        let notifier = match subscription {
+            Some(subscription) => self.notifier.for_subscription(subscription),
+            None => Cow::Borrowed(&self.notifier),
+        };
+        let image_size = subscription.map_or(self.image_size, |subscription| {
+            subscription.image_size.resolve(self.image_size)
+        });
         if ranking_requires_individual_send(illusts) {
             info!(
                 "Ranking push for chat {} contains ugoira, sending items individually",
                 chat_id
             );
             return self
-                .send_ranking_illusts_individually(chat_id, mode, chat, illusts)
+                .send_ranking_illusts_individually(
+                    &notifier, chat_id, title, chat, illusts, ranks, image_size,
+                )
                 .await;
         }
 
         Ok(self
-            .send_ranking_illusts_as_batch(chat_id, mode, chat, illusts)
+            .send_ranking_illusts_as_batch(
+                &notifier, chat_id, title, chat, illusts, ranks, image_size,
+            )
             .await)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn send_ranking_illusts_as_batch(
         &self,
+        notifier: &Notifier,
         chat_id: ChatId,
-        mode: &str,
+        title: &str,
         chat: &crate::db::entities::chats::Model,
         illusts: &[&Illust],
+        ranks: &[usize],
+        image_size: pixiv_client::ImageSize,
     ) -> BatchSendResult {
-        let title = build_ranking_title(mode, illusts.len());
-
         let mut image_urls = Vec::new();
         let mut captions = Vec::new();
 
         for (index, illust) in illusts.iter().enumerate() {
             let image_url = illust
-                .get_all_image_urls_with_size(self.image_size)
+                .get_all_image_urls_with_size(image_size)
                 .first()
                 .cloned()
                 .unwrap_or_else(|| illust.image_urls.large.clone());
             image_urls.push(image_url);
-            captions.push(build_ranking_caption(&title, index, illust));
+            let rank = format_rank(ranks[index], self.medals);
+            let caption = build_ranking_caption(title, index, Some(&rank), illust);
+            captions.push(notifier.process_caption(caption, illust, chat));
         }
 
         let sensitive_tags = crate::utils::sensitive::get_chat_sensitive_tags(chat);
@@ -380,26 +723,35 @@ impl RankingEngine {
                 crate::utils::sensitive::contains_sensitive_tags(illust, sensitive_tags)
             });
 
-        self.notifier
+        notifier
             .notify_with_individual_captions(chat_id, &image_urls, &captions, has_spoiler)
             .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn send_ranking_illusts_individually(
         &self,
+        notifier: &Notifier,
         chat_id: ChatId,
-        mode: &str,
+        title: &str,
         chat: &crate::db::entities::chats::Model,
         illusts: &[&Illust],
+        ranks: &[usize],
+        image_size: pixiv_client::ImageSize,
     ) -> Result<BatchSendResult> {
-        let title = build_ranking_title(mode, illusts.len());
         let sensitive_tags = crate::utils::sensitive::get_chat_sensitive_tags(chat);
         let mut succeeded_indices = Vec::new();
         let mut failed_indices = Vec::new();
         let mut first_message_id = None;
+        let mut message_ids = Vec::new();
 
         for (index, illust) in illusts.iter().enumerate() {
-            let caption = build_ranking_caption(&title, index, illust);
+            let rank = format_rank(ranks[index], self.medals);
+            let caption = notifier.process_caption(
+                build_ranking_caption(title, index, Some(&rank), illust),
+                illust,
+                chat,
+            );
             let has_spoiler = chat.blur_sensitive_tags
                 && crate::utils::sensitive::contains_sensitive_tags(illust, sensitive_tags);
 
@@ -410,7 +762,7 @@ impl RankingEngine {
 
                 match metadata_result {
                     Ok(metadata) => {
-                        self.notifier
+                        notifier
                             .notify_ugoira(
                                 chat_id,
                                 &metadata.zip_urls.medium,
@@ -430,17 +782,18 @@ impl RankingEngine {
                             succeeded_indices: Vec::new(),
                             failed_indices: vec![0],
                             first_message_id: None,
+                            message_ids: Vec::new(),
                         }
                     }
                 }
             } else {
                 let image_url = illust
-                    .get_all_image_urls_with_size(self.image_size)
+                    .get_all_image_urls_with_size(image_size)
                     .first()
                     .cloned()
                     .unwrap_or_else(|| illust.image_urls.large.clone());
 
-                self.notifier
+                notifier
                     .notify_with_images(
                         chat_id,
                         std::slice::from_ref(&image_url),
@@ -459,19 +812,23 @@ impl RankingEngine {
             if first_message_id.is_none() {
                 first_message_id = send_result.first_message_id;
             }
+            if let Some(message_id) = send_result.first_message_id {
+                message_ids.push((index, message_id));
+            }
         }
 
         Ok(BatchSendResult {
             succeeded_indices,
             failed_indices,
             first_message_id,
+            message_ids,
         })
     }
 
     /// Helper: Trim pushed_ids to last 200 and update state
     async fn trim_and_update_pushed_ids(
         &self,
-        subscription_id: i32,
+        subscription: &subscriptions::Model,
         mut pushed_ids: Vec<u64>,
     ) -> Result<()> {
         // Keep only the last 200 IDs to prevent unbounded growth
@@ -485,20 +842,16 @@ impl RankingEngine {
             pending_illust: None,
         };
 
-        self.update_ranking_state(subscription_id, new_state).await
+        self.update_ranking_state(subscription, new_state).await
     }
 
     /// Update ranking subscription state in database
     async fn update_ranking_state(
         &self,
-        subscription_id: i32,
+        subscription: &subscriptions::Model,
         state: crate::db::types::RankingState,
     ) -> Result<()> {
-        self.repo
-            .update_subscription_latest_data(
-                subscription_id,
-                Some(SubscriptionState::Ranking(state)),
-            )
+        save_subscription_state(&self.repo, subscription, SubscriptionState::Ranking(state))
             .await?;
         Ok(())
     }
@@ -506,20 +859,54 @@ impl RankingEngine {
     /// Helper: Mark illusts as pushed (when filtered out but should be marked as processed)
     async fn mark_ranking_illusts_as_pushed(
         &self,
-        subscription_id: i32,
+        subscription: &subscriptions::Model,
         mut pushed_ids: Vec<u64>,
         new_ids: Vec<u64>,
     ) -> Result<()> {
         pushed_ids.extend(new_ids);
-        self.trim_and_update_pushed_ids(subscription_id, pushed_ids)
+        self.trim_and_update_pushed_ids(subscription, pushed_ids)
             .await
     }
 }
 
+/// Illust IDs of the snapshot for `mode`, if it was taken on `today`
+fn snapshot_ids_for(
+    snapshots: &HashMap<String, RankingSnapshot>,
+    mode: &str,
+    today: NaiveDate,
+) -> Option<Vec<u64>> {
+    snapshots
+        .get(mode)
+        .filter(|snapshot| snapshot.date == today)
+        .map(|snapshot| snapshot.illust_ids.clone())
+}
+
+/// Date passed to the ranking API for a subscription's day offset, `None` for the latest ranking
+fn ranking_date(offset_days: i32, today: NaiveDate) -> Option<String> {
+    (offset_days > 0).then(|| {
+        (today - chrono::Duration::days(i64::from(offset_days)))
+            .format("%Y-%m-%d")
+            .to_string()
+    })
+}
+
 fn ranking_requires_individual_send(illusts: &[&Illust]) -> bool {
     illusts.iter().any(|illust| illust.is_ugoira())
 }
 
+/// 1-based position in the fetched ranking of each work to send, whatever was filtered out before it
+fn ranking_positions(ranking: &[Illust], selected: &[&Illust]) -> Vec<usize> {
+    selected
+        .iter()
+        .map(|illust| {
+            ranking
+                .iter()
+                .position(|ranked| ranked.id == illust.id)
+                .map_or(0, |position| position + 1)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -573,18 +960,68 @@ mod tests {
         assert!(!ranking_requires_individual_send(&[&still]));
     }
 
+    #[test]
+    fn ranking_positions_keep_ranks_of_filtered_lists() {
+        let ranking: Vec<Illust> = (1..=4)
+            .map(|id| {
+                let mut illust = make_illust("illust", "Work");
+                illust.id = id;
+                illust
+            })
+            .collect();
+        let selected = [&ranking[1], &ranking[3]];
+
+        assert_eq!(ranking_positions(&ranking, &selected), [2, 4]);
+    }
+
     #[test]
     fn build_ranking_caption_marks_ugoira_and_prepends_title_once() {
-        let title = build_ranking_title("day", 2);
+        let title = build_ranking_title("day", None, 2);
         let ugoira = make_illust("ugoira", "Animated");
         let still = make_illust("illust", "Still");
 
-        let first_caption = build_ranking_caption(&title, 0, &ugoira);
-        let second_caption = build_ranking_caption(&title, 1, &still);
+        let first_caption = build_ranking_caption(&title, 0, None, &ugoira);
+        let second_caption = build_ranking_caption(&title, 1, None, &still);
 
         assert!(first_caption.starts_with(&title));
         assert!(first_caption.contains("🎞️ Animated"));
         assert!(!second_caption.starts_with(&title));
         assert!(!second_caption.contains("🎞️ Still"));
     }
+
+    #[test]
+    fn snapshot_ids_for_ignores_other_days_and_modes() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let snapshots = HashMap::from([
+            (
+                "day".to_string(),
+                RankingSnapshot {
+                    date: today,
+                    illust_ids: vec![1, 2, 3],
+                },
+            ),
+            (
+                "week".to_string(),
+                RankingSnapshot {
+                    date: today - chrono::Duration::days(1),
+                    illust_ids: vec![4, 5],
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            snapshot_ids_for(&snapshots, "day", today),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(snapshot_ids_for(&snapshots, "week", today), None);
+        assert_eq!(snapshot_ids_for(&snapshots, "month", today), None);
+    }
+
+    #[test]
+    fn ranking_date_counts_days_back_from_today() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(ranking_date(0, today), None);
+        assert_eq!(ranking_date(1, today).as_deref(), Some("2026-02-28"));
+        assert_eq!(ranking_date(3, today).as_deref(), Some("2026-02-26"));
+    }
 }
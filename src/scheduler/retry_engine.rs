@@ -0,0 +1,375 @@
+use crate::bot::notifier::Notifier;
+use crate::config::ImageSizePolicy;
+use crate::db::entities::{push_queue, subscriptions};
+use crate::db::repo::push_queue::{STATUS_ABANDONED, STATUS_DONE};
+use crate::db::repo::Repo;
+use crate::pixiv::client::{is_not_found, PixivClient};
+use crate::scheduler::helpers::{
+    get_chat_if_should_notify, illust_spoiler, log_push, process_illust_push, record_sent_artworks,
+    save_first_message_record, AuthorContext, PushResult, INTER_SUBSCRIPTION_DELAY_MS,
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::utils::markdown;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Queued retries attempted per tick
+const RETRIES_PER_TICK: u64 = 5;
+
+/// Delay before the first retry, doubled after every failed retry
+const RETRY_BASE_DELAY_SECS: i64 = 5 * 60;
+
+/// Longest delay between two retries
+const RETRY_MAX_DELAY_SECS: i64 = 6 * 60 * 60;
+
+/// Finished and abandoned entries are kept this long for inspection
+const FINISHED_RETENTION_DAYS: i64 = 7;
+
+/// Delay before the next attempt after `attempts` failed retries
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let delay = RETRY_BASE_DELAY_SECS << attempts.clamp(0, 16);
+    chrono::Duration::seconds(delay.min(RETRY_MAX_DELAY_SECS))
+}
+
+/// Engine that drains `push_queue`, resending the missing pages of author
+/// pushes that failed or were only partly sent
+pub struct RetryEngine {
+    repo: Arc<Repo>,
+    pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
+    notifier: Notifier,
+    tick_interval_sec: u64,
+    max_retry_count: i32,
+    image_size: pixiv_client::ImageSize,
+    keep_continuation_spoiler: bool,
+}
+
+impl RetryEngine {
+    pub fn new(
+        repo: Arc<Repo>,
+        pixiv_client: Arc<tokio::sync::RwLock<PixivClient>>,
+        notifier: Notifier,
+        tick_interval_sec: u64,
+        max_retry_count: i32,
+        image_sizes: ImageSizePolicy,
+        keep_continuation_spoiler: bool,
+    ) -> Self {
+        Self {
+            repo,
+            pixiv_client,
+            notifier,
+            tick_interval_sec,
+            max_retry_count,
+            image_size: image_sizes.push,
+            keep_continuation_spoiler,
+        }
+    }
+
+    /// Main scheduler loop - runs indefinitely
+    pub async fn run(&self) {
+        info!("🚀 Retry engine started");
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.tick_interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.tick().await {
+                error!("Retry engine tick error: {:#}", e);
+            }
+        }
+    }
+
+    async fn tick(&self) -> Result<()> {
+        let retention_cutoff =
+            (Utc::now() - chrono::Duration::days(FINISHED_RETENTION_DAYS)).naive_utc();
+        let purged = self
+            .repo
+            .purge_finished_push_retries(retention_cutoff)
+            .await?;
+        if purged > 0 {
+            debug!("Purged {} finished push retries", purged);
+        }
+
+        // Pause retries while the token refresher is failing
+        if !self.pixiv_client.read().await.is_authenticated() {
+            debug!("Pixiv is not authenticated, skipping retry tick");
+            return Ok(());
+        }
+
+        let entries = self.repo.get_due_push_retries(RETRIES_PER_TICK).await?;
+        for entry in entries {
+            let entry_id = entry.id;
+            if let Err(e) = self.retry_entry(entry).await {
+                error!("Push retry {} failed: {:#}", entry_id, e);
+            }
+            sleep(Duration::from_millis(INTER_SUBSCRIPTION_DELAY_MS)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Make one retry attempt and persist its outcome
+    async fn retry_entry(&self, mut entry: push_queue::Model) -> Result<()> {
+        let Some(subscription) = self.repo.get_subscription(entry.subscription_id).await? else {
+            return self
+                .finish(entry, STATUS_ABANDONED, Some("subscription removed"))
+                .await;
+        };
+        // The chat may have been migrated since the entry was queued
+        entry.chat_id = subscription.chat_id;
+
+        // Paused subscriptions keep their retry until they are resumed
+        if !subscription.enabled {
+            entry.next_attempt_at = (Utc::now() + retry_delay(entry.attempts)).naive_utc();
+            return self.repo.save_push_retry(entry).await;
+        }
+
+        let Some(chat) = get_chat_if_should_notify(&self.repo, subscription.chat_id).await? else {
+            return self
+                .finish(entry, STATUS_ABANDONED, Some("chat disabled"))
+                .await;
+        };
+
+        let illust_id = entry.illust_id as u64;
+        let illust = {
+            let pixiv = self.pixiv_client.read().await;
+            pixiv.get_illust_detail(illust_id).await
+        };
+        let illust = match illust {
+            Ok(illust) => illust,
+            Err(e) if is_not_found(&e) => {
+                warn!("Illust {} was deleted, abandoning its retry", illust_id);
+                return self
+                    .finish(entry, STATUS_ABANDONED, Some("illust not found"))
+                    .await;
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                return self.record_failure(&subscription, entry, error).await;
+            }
+        };
+
+        info!(
+            "Retrying illust {} for chat {} ({}/{} pages sent, retry {}/{})",
+            illust_id,
+            subscription.chat_id,
+            entry.sent_pages.len(),
+            entry.total_pages,
+            entry.attempts + 1,
+            self.max_retry_count
+        );
+
+        let chat_id = ChatId(subscription.chat_id);
+        let ctx = AuthorContext {
+            subscription: &subscription,
+            chat,
+            subscription_state: None,
+            mirror_target: false,
+        };
+        // Keep the spoiler of earlier batches if configured
+        let force_spoiler =
+            self.keep_continuation_spoiler && entry.spoiler && !entry.sent_pages.is_empty();
+        let has_spoiler = illust_spoiler(&ctx, &illust, force_spoiler);
+
+        let push_result = {
+            let _turn = self.notifier.begin_push(chat_id).await;
+            process_illust_push(
+                &self.notifier,
+                &self.pixiv_client,
+                &ctx,
+                &illust,
+                &entry.sent_pages,
+                self.image_size,
+                force_spoiler,
+            )
+            .await
+            .context("Failed to push illust")
+        };
+        // Errors count as a failed attempt too, or the entry would be retried every tick
+        let push_result = match push_result {
+            Ok(push_result) => push_result,
+            Err(e) => {
+                log_push(&self.repo, chat_id, subscription.id, 0, false).await;
+                let error = format!("{:#}", e);
+                return self.record_failure(&subscription, entry, error).await;
+            }
+        };
+
+        // Only the first delivery of a work counts as a push, failures are always logged
+        let first_delivery = entry.sent_pages.is_empty();
+        match &push_result {
+            PushResult::Failure { .. } => {
+                log_push(&self.repo, chat_id, subscription.id, 0, false).await
+            }
+            _ if first_delivery => log_push(&self.repo, chat_id, subscription.id, 1, true).await,
+            _ => {}
+        }
+
+        match push_result {
+            PushResult::Success {
+                first_message_id, ..
+            } => {
+                info!(
+                    "✅ Completed retry of illust {} for chat {}",
+                    illust_id, chat_id
+                );
+                self.save_message_record(chat_id, subscription.id, illust_id, first_message_id)
+                    .await;
+                self.finish(entry, STATUS_DONE, None).await
+            }
+            PushResult::Partial {
+                sent_pages,
+                total_pages,
+                first_message_id,
+                ..
+            } => {
+                warn!(
+                    "⚠️  Partially sent illust {} ({}/{} pages)",
+                    illust_id,
+                    sent_pages.len(),
+                    total_pages
+                );
+                self.save_message_record(chat_id, subscription.id, illust_id, first_message_id)
+                    .await;
+                entry.sent_pages = sent_pages.into();
+                entry.total_pages = total_pages as i32;
+                entry.spoiler = has_spoiler;
+                self.record_failure(&subscription, entry, "partially sent".to_string())
+                    .await
+            }
+            PushResult::Failure { .. } => {
+                self.record_failure(&subscription, entry, "send failed".to_string())
+                    .await
+            }
+        }
+    }
+
+    /// Count a failed retry, scheduling the next one or giving up
+    async fn record_failure(
+        &self,
+        subscription: &subscriptions::Model,
+        mut entry: push_queue::Model,
+        error: String,
+    ) -> Result<()> {
+        entry.attempts += 1;
+        entry.last_error = Some(error);
+
+        if entry.attempts >= self.max_retry_count {
+            error!(
+                "❌ Max retries reached ({}/{}), abandoning illust {} for chat {}",
+                entry.attempts, self.max_retry_count, entry.illust_id, entry.chat_id
+            );
+            entry.status = STATUS_ABANDONED.to_string();
+            let illust_id = entry.illust_id as u64;
+            self.repo.save_push_retry(entry).await?;
+            self.notify_abandoned(subscription, illust_id).await;
+            return Ok(());
+        }
+
+        let delay = retry_delay(entry.attempts);
+        warn!(
+            "Retry of illust {} for chat {} failed, next attempt in {} min (attempt {}/{})",
+            entry.illust_id,
+            entry.chat_id,
+            delay.num_minutes(),
+            entry.attempts,
+            self.max_retry_count
+        );
+        entry.next_attempt_at = (Utc::now() + delay).naive_utc();
+        self.repo.save_push_retry(entry).await
+    }
+
+    /// Tell the chat a work was skipped, if it asked for failure notices
+    async fn notify_abandoned(&self, subscription: &subscriptions::Model, illust_id: u64) {
+        let chat_id = ChatId(subscription.chat_id);
+        match self.repo.get_chat(chat_id.0).await {
+            Ok(Some(chat)) if chat.notify_failures => {}
+            Ok(_) => return,
+            Err(e) => {
+                warn!("Failed to get chat {} for failure notice: {:#}", chat_id, e);
+                return;
+            }
+        }
+
+        let notifier = self.notifier.for_subscription(subscription);
+        if let Err(e) = notifier
+            .send_text(chat_id, &abandoned_notice(illust_id), false)
+            .await
+        {
+            warn!(
+                "Failed to send failure notice of illust {} to chat {}: {:#}",
+                illust_id, chat_id, e
+            );
+        }
+    }
+
+    async fn finish(
+        &self,
+        mut entry: push_queue::Model,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        entry.status = status.to_string();
+        if let Some(error) = error {
+            entry.last_error = Some(error.to_string());
+        }
+        self.repo.save_push_retry(entry).await
+    }
+
+    async fn save_message_record(
+        &self,
+        chat_id: ChatId,
+        subscription_id: i32,
+        illust_id: u64,
+        first_message_id: Option<i32>,
+    ) {
+        save_first_message_record(
+            &self.repo,
+            chat_id,
+            subscription_id,
+            first_message_id,
+            Some(illust_id as i64),
+        )
+        .await;
+        record_sent_artworks(&self.repo, chat_id, &[illust_id], first_message_id).await;
+    }
+}
+
+/// MarkdownV2 notice for a work abandoned after its last retry
+fn abandoned_notice(illust_id: u64) -> String {
+    format!(
+        "⚠️ 作品 {} 推送失败已跳过 \\({}\\)",
+        illust_id,
+        markdown::link(
+            &format!("https://www.pixiv.net/artworks/{}", illust_id),
+            "链接"
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{abandoned_notice, retry_delay};
+
+    #[test]
+    fn abandoned_notice_links_the_artwork() {
+        assert_eq!(
+            abandoned_notice(123),
+            "⚠️ 作品 123 推送失败已跳过 \\([链接](https://www.pixiv.net/artworks/123)\\)"
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(0).num_minutes(), 5);
+        assert_eq!(retry_delay(1).num_minutes(), 10);
+        assert_eq!(retry_delay(3).num_minutes(), 40);
+        assert_eq!(retry_delay(10).num_hours(), 6);
+        assert_eq!(retry_delay(i32::MAX).num_hours(), 6);
+        assert_eq!(retry_delay(-1).num_minutes(), 5);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::bot::notifier::Notifier;
+use crate::pixiv::client::PixivClient;
+use crate::utils::caption;
+use anyhow::{Context, Result};
+use pixiv_client::{Illust, ImageSize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::types::ChatId;
+use teloxide::utils::markdown;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+/// Outcome of one pipeline stage
+struct StageReport {
+    name: &'static str,
+    /// None when the stage was skipped after an earlier failure
+    result: Option<(Duration, Result<(), String>)>,
+}
+
+/// Sends the owner a test push on startup and reports the time each stage took
+pub struct SelfTest {
+    pixiv_client: Arc<RwLock<PixivClient>>,
+    notifier: Notifier,
+    owner_id: Option<i64>,
+    illust_id: Option<u64>,
+    image_size: ImageSize,
+}
+
+impl SelfTest {
+    pub fn new(
+        pixiv_client: Arc<RwLock<PixivClient>>,
+        notifier: Notifier,
+        owner_id: Option<i64>,
+        illust_id: Option<u64>,
+        image_size: ImageSize,
+    ) -> Self {
+        Self {
+            pixiv_client,
+            notifier,
+            owner_id,
+            illust_id,
+            image_size,
+        }
+    }
+
+    pub async fn run(self) {
+        let Some(owner_id) = self.owner_id else {
+            warn!("Startup self-test enabled but no owner_id configured, skipping");
+            return;
+        };
+        let chat_id = ChatId(owner_id);
+        info!("🩺 Running startup self-test");
+
+        let mut stages = Vec::new();
+        let illust = record_stage(&mut stages, "Pixiv API", self.fetch_illust()).await;
+        let path = match &illust {
+            Some(illust) => {
+                let url = illust
+                    .get_all_image_urls_with_size(self.image_size)
+                    .into_iter()
+                    .next();
+                record_stage(&mut stages, "下载", self.download(url)).await
+            }
+            None => skip_stage(&mut stages, "下载"),
+        };
+        let caption = match &illust {
+            Some(illust) => {
+                let build = async { Ok::<_, anyhow::Error>(caption::build_illust_caption(illust)) };
+                record_stage(&mut stages, "文案", build).await
+            }
+            None => skip_stage(&mut stages, "文案"),
+        };
+        match (&path, &caption) {
+            (Some(path), Some(caption)) => {
+                let send = self.notifier.send_photo_file(chat_id, path, Some(caption));
+                record_stage(&mut stages, "发送", send).await;
+            }
+            _ => {
+                skip_stage::<()>(&mut stages, "发送");
+            }
+        }
+
+        let passed = stages
+            .iter()
+            .all(|stage| matches!(stage.result, Some((_, Ok(())))));
+        if passed {
+            info!("✅ Startup self-test passed");
+        } else {
+            error!("Startup self-test failed");
+        }
+
+        if let Err(e) = self
+            .notifier
+            .send_text(chat_id, &format_report(&stages), false)
+            .await
+        {
+            error!("Failed to send self-test report to owner: {:#}", e);
+        }
+    }
+
+    async fn fetch_illust(&self) -> Result<Illust> {
+        let pixiv = self.pixiv_client.read().await;
+        match self.illust_id {
+            Some(illust_id) => pixiv.get_illust_detail(illust_id).await,
+            None => pixiv
+                .get_ranking("day", None, 1)
+                .await?
+                .into_iter()
+                .next()
+                .context("Daily ranking is empty"),
+        }
+    }
+
+    async fn download(&self, url: Option<String>) -> Result<PathBuf> {
+        let url = url.context("Illust has no image URL")?;
+        self.notifier.get_downloader().download(&url).await
+    }
+}
+
+/// Run a stage, record its timing and outcome, and pass its value on
+async fn record_stage<T, E>(
+    stages: &mut Vec<StageReport>,
+    name: &'static str,
+    stage: impl Future<Output = Result<T, E>>,
+) -> Option<T>
+where
+    E: Into<anyhow::Error>,
+{
+    let started = Instant::now();
+    let result = stage.await.map_err(Into::into);
+    let elapsed = started.elapsed();
+
+    let (outcome, value) = match result {
+        Ok(value) => (Ok(()), Some(value)),
+        Err(e) => {
+            error!("Self-test stage '{}' failed: {:#}", name, e);
+            (Err(format!("{:#}", e)), None)
+        }
+    };
+    stages.push(StageReport {
+        name,
+        result: Some((elapsed, outcome)),
+    });
+    value
+}
+
+fn skip_stage<T>(stages: &mut Vec<StageReport>, name: &'static str) -> Option<T> {
+    stages.push(StageReport { name, result: None });
+    None
+}
+
+/// MarkdownV2 report with one line per stage
+fn format_report(stages: &[StageReport]) -> String {
+    let passed = stages
+        .iter()
+        .all(|stage| matches!(stage.result, Some((_, Ok(())))));
+    let mut report = if passed {
+        "🩺 *启动自检通过*\n".to_string()
+    } else {
+        "🩺 *启动自检失败*\n".to_string()
+    };
+
+    for stage in stages {
+        let line = match &stage.result {
+            Some((elapsed, Ok(()))) => {
+                format!("✅ {}: `{} ms`", stage.name, elapsed.as_millis())
+            }
+            Some((elapsed, Err(e))) => format!(
+                "❌ {}: `{} ms`\n   {}",
+                stage.name,
+                elapsed.as_millis(),
+                markdown::escape(e)
+            ),
+            None => format!("⏭ {}: 已跳过", stage.name),
+        };
+        report.push('\n');
+        report.push_str(&line);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_report_marks_failed_and_skipped_stages() {
+        let stages = [
+            StageReport {
+                name: "Pixiv API",
+                result: Some((Duration::from_millis(120), Ok(()))),
+            },
+            StageReport {
+                name: "下载",
+                result: Some((Duration::from_millis(3), Err("proxy refused (x.y)".into()))),
+            },
+            StageReport {
+                name: "发送",
+                result: None,
+            },
+        ];
+
+        assert_eq!(
+            format_report(&stages),
+            "🩺 *启动自检失败*\n\n\
+             ✅ Pixiv API: `120 ms`\n\
+             ❌ 下载: `3 ms`\n   proxy refused \\(x\\.y\\)\n\
+             ⏭ 发送: 已跳过"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::bot::notifier::Notifier;
+use crate::utils::version::{self, is_newer_version};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use teloxide::types::ChatId;
+use teloxide::utils::markdown;
+use tokio::time::Duration;
+use tracing::{error, info};
+
+/// Newer release tag found by the update checker, shared with `/version`
+#[derive(Clone, Default)]
+pub struct LatestRelease {
+    tag: Arc<RwLock<Option<String>>>,
+}
+
+impl LatestRelease {
+    pub fn get(&self) -> Option<String> {
+        self.tag.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set(&self, tag: String) {
+        *self.tag.write().unwrap_or_else(|e| e.into_inner()) = Some(tag);
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Periodically checks GitHub releases and notifies the owner about new versions
+pub struct UpdateChecker {
+    http: reqwest::Client,
+    notifier: Notifier,
+    owner_id: Option<i64>,
+    repo: String,
+    interval: Duration,
+    latest: LatestRelease,
+}
+
+impl UpdateChecker {
+    pub fn new(
+        notifier: Notifier,
+        owner_id: Option<i64>,
+        repo: String,
+        interval_hours: u64,
+        latest: LatestRelease,
+    ) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent(format!("pixivbot/{}", version::VERSION))
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("Failed to build update check HTTP client")?;
+        Ok(Self {
+            http,
+            notifier,
+            owner_id,
+            repo,
+            interval: Duration::from_secs(interval_hours.max(1) * 3600),
+            latest,
+        })
+    }
+
+    pub async fn run(self) {
+        info!(
+            "🚀 Update checker started (repo: {}, every {}h)",
+            self.repo,
+            self.interval.as_secs() / 3600
+        );
+
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check().await {
+                error!("Update check failed: {:#}", e);
+            }
+        }
+    }
+
+    async fn check(&self) -> Result<()> {
+        let release: GithubRelease = self
+            .http
+            .get(format!(
+                "https://api.github.com/repos/{}/releases/latest",
+                self.repo
+            ))
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .context("Failed to request latest release")?
+            .error_for_status()
+            .context("Latest release request returned error status")?
+            .json()
+            .await
+            .context("Failed to parse latest release")?;
+
+        if !is_newer_version(&release.tag_name, version::VERSION) {
+            return Ok(());
+        }
+
+        // Only notify once per release tag
+        if self.latest.get().as_deref() == Some(release.tag_name.as_str()) {
+            return Ok(());
+        }
+        info!(
+            "New release available: {} (current {})",
+            release.tag_name,
+            version::VERSION
+        );
+        self.latest.set(release.tag_name.clone());
+
+        let Some(owner_id) = self.owner_id else {
+            return Ok(());
+        };
+        let text = format!(
+            "🆕 *PixivBot 有新版本可用*\n\n当前版本: `{}`\n最新版本: `{}`\n\n[查看发布说明]({})",
+            markdown::escape_code(version::VERSION),
+            markdown::escape_code(&release.tag_name),
+            markdown::escape_link_url(&release.html_url)
+        );
+        self.notifier
+            .send_text(ChatId(owner_id), &text, false)
+            .await
+            .context("Failed to notify owner about new release")?;
+
+        Ok(())
+    }
+}
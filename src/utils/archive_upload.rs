@@ -0,0 +1,88 @@
+//! Expiring links for generated ZIPs too large to send through the Bot API.
+
+use crate::config::ArchiveUploadConfig;
+use anyhow::{Context, Result};
+use eh_client::{ArchiveUploadInput, ImageUploader, S3Uploader};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Longest expiry a SigV4 presigned URL accepts
+const MAX_LINK_EXPIRY_HOURS: u32 = 7 * 24;
+
+/// Picks between sending a ZIP and uploading it, and performs the upload
+#[derive(Clone)]
+pub struct ArchiveUpload {
+    uploader: Arc<dyn ImageUploader>,
+    min_size_bytes: u64,
+    link_expiry_hours: u32,
+}
+
+impl ArchiveUpload {
+    pub fn new(uploader: Arc<dyn ImageUploader>, min_size_mb: u64, link_expiry_hours: u32) -> Self {
+        Self {
+            uploader,
+            min_size_bytes: min_size_mb.saturating_mul(1024 * 1024),
+            link_expiry_hours: link_expiry_hours.clamp(1, MAX_LINK_EXPIRY_HOURS),
+        }
+    }
+
+    /// None when archive upload is disabled
+    pub fn from_config(config: &ArchiveUploadConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let s3 = config
+            .s3
+            .as_ref()
+            .context("archive_upload.s3 is required when archive_upload is enabled")?;
+        let uploader = S3Uploader::for_archives(s3, "archive_upload.s3")
+            .context("Failed to build archive uploader")?;
+        Ok(Some(Self::new(
+            Arc::new(uploader),
+            config.min_size_mb,
+            config.link_expiry_hours,
+        )))
+    }
+
+    /// Whether a ZIP of `size` bytes is uploaded instead of sent as a document
+    pub fn should_upload(&self, size: u64) -> bool {
+        size > self.min_size_bytes
+    }
+
+    pub fn link_expiry_hours(&self) -> u32 {
+        self.link_expiry_hours
+    }
+
+    /// Upload the ZIP at `path` and return its expiring link
+    pub async fn upload(&self, path: &Path, filename: &str) -> Result<String> {
+        let bytes = tokio::fs::read(path)
+            .await
+            .context("Failed to read ZIP file")?;
+        self.uploader
+            .upload_archive_with_expiring_url(
+                ArchiveUploadInput {
+                    filename,
+                    bytes: &bytes,
+                },
+                self.link_expiry_hours * 3600,
+            )
+            .await
+            .context("Failed to upload ZIP file")?
+            .context("Archive uploader does not support expiring links")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eh_client::PixiUploader;
+
+    #[test]
+    fn should_upload_only_above_threshold_and_clamps_expiry() {
+        let upload = ArchiveUpload::new(Arc::new(PixiUploader::new()), 50, 1000);
+
+        assert!(!upload.should_upload(50 * 1024 * 1024));
+        assert!(upload.should_upload(50 * 1024 * 1024 + 1));
+        assert_eq!(upload.link_expiry_hours(), MAX_LINK_EXPIRY_HOURS);
+    }
+}
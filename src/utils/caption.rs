@@ -1,9 +1,15 @@
 use crate::utils::tag;
-use pixiv_client::Illust;
+use pixiv_client::{AccessRestriction, Illust};
+use std::collections::HashMap;
+use std::ops::Range;
+use teloxide::types::{CustomEmojiId, MessageEntity, MessageEntityKind};
 use teloxide::utils::markdown;
 
 pub const MAX_PER_GROUP: usize = 10;
 
+/// Telegram media caption limit, counted in UTF-16 units of the rendered text
+pub const MAX_CAPTION_UTF16_UNITS: usize = 1024;
+
 pub fn build_illust_caption(illust: &Illust) -> String {
     let page_info = if illust.is_multi_page() {
         format!(" \\({} photos\\)", illust.page_count)
@@ -14,6 +20,22 @@ pub fn build_illust_caption(illust: &Illust) -> String {
     build_standard_caption("🎨", illust, &page_info)
 }
 
+/// Caption for a work whose images the Pixiv account cannot see, sent as plain text
+pub fn build_restricted_caption(illust: &Illust, restriction: AccessRestriction) -> String {
+    let notice = match restriction {
+        AccessRestriction::MyPixiv => "🔒 仅好P友可见的作品，无法获取预览",
+        AccessRestriction::Unavailable => "🔒 受限作品（如仅关注者可见），无法获取预览",
+    };
+
+    format!(
+        "{}
+
+{}",
+        build_standard_caption("🎨", illust, ""),
+        markdown::escape(notice)
+    )
+}
+
 pub fn build_ugoira_caption(illust: &Illust) -> String {
     build_standard_caption("🎞️", illust, "")
 }
@@ -38,21 +60,93 @@ pub fn build_continuation_caption(
     )
 }
 
-pub fn build_ranking_title(mode: &str, count: usize) -> String {
+/// Combined caption of an author album: one linked line per work, oldest first
+pub fn build_album_caption(illusts: &[&Illust]) -> String {
+    let author = illusts
+        .first()
+        .map(|illust| illust.user.name.as_str())
+        .unwrap_or_default();
+    let lines: Vec<String> = illusts
+        .iter()
+        .enumerate()
+        .map(|(index, illust)| {
+            let page_info = if illust.is_multi_page() {
+                format!(" \\({} photos\\)", illust.page_count)
+            } else {
+                String::new()
+            };
+            format!(
+                "{}\\. [{}](https://pixiv\\.net/artworks/{}){}",
+                index + 1,
+                markdown::escape(&illust.title),
+                illust.id,
+                page_info
+            )
+        })
+        .collect();
+
     format!(
-        "📊 *{} Ranking* \\- {} new\\!\n\n",
+        "🎨 *{}* \\- {} 个新作品\n\n{}",
+        markdown::escape(author),
+        illusts.len(),
+        lines.join("\n")
+    )
+}
+
+/// Title of a scheduled ranking push, with the ranking date for subscriptions to an earlier day
+pub fn build_ranking_title(mode: &str, date: Option<&str>, count: usize) -> String {
+    format!(
+        "📊 *{} Ranking*{} \\- {} new\\!\n\n",
+        markdown::escape(&mode.replace('_', " ").to_uppercase()),
+        format_ranking_date(date),
+        count
+    )
+}
+
+/// Title of an on-demand `/top` ranking lookup, with the ranking date when one was given
+pub fn build_top_ranking_title(mode: &str, date: Option<&str>, count: usize) -> String {
+    let date = format_ranking_date(date);
+    format!(
+        "📊 *{} Ranking*{} \\- Top {}\n\n",
         markdown::escape(&mode.replace('_', " ").to_uppercase()),
+        date,
         count
     )
 }
 
-pub fn build_ranking_caption(title: &str, index: usize, illust: &Illust) -> String {
+fn format_ranking_date(date: Option<&str>) -> String {
+    date.map(|date| format!(" \\({}\\)", markdown::escape(date)))
+        .unwrap_or_default()
+}
+
+/// Escaped rank label for a ranking caption: `#4`, or a medal for the top 3 when `medals` is set
+pub fn format_rank(rank: usize, medals: bool) -> String {
+    match rank {
+        1 if medals => "🥇".to_string(),
+        2 if medals => "🥈".to_string(),
+        3 if medals => "🥉".to_string(),
+        _ => format!("\\#{}", rank),
+    }
+}
+
+/// Caption of one ranking item; the title is only prepended to the first item.
+///
+/// `rank` is a label from [`format_rank`], shown before the work's title.
+pub fn build_ranking_caption(
+    title: &str,
+    index: usize,
+    rank: Option<&str>,
+    illust: &Illust,
+) -> String {
     let tags = tag::format_tags_escaped(illust);
-    let title_line = if illust.is_ugoira() {
+    let mut title_line = if illust.is_ugoira() {
         format!("🎞️ {}", markdown::escape(&illust.title))
     } else {
         markdown::escape(&illust.title)
     };
+    if let Some(rank) = rank {
+        title_line = format!("{} {}", rank, title_line);
+    }
 
     let base_caption = format!(
         "{}\nby *{}* \\(ID: `{}`\\)\n\n❤️ {} \\| 🔗 [来源](https://pixiv\\.net/artworks/{}){}",
@@ -71,6 +165,19 @@ pub fn build_ranking_caption(title: &str, index: usize, illust: &Illust) -> Stri
     }
 }
 
+/// Public page URL of a booru post
+pub fn booru_post_url(
+    base_url: &str,
+    engine_type: booru_client::BooruEngineType,
+    post_id: u64,
+) -> String {
+    format!(
+        "{}{}",
+        base_url.trim_end_matches('/'),
+        engine_type.post_path(post_id)
+    )
+}
+
 /// Build caption for a booru post (MarkdownV2 format)
 pub fn build_booru_caption(
     post: &booru_client::BooruPost,
@@ -85,8 +192,7 @@ pub fn build_booru_caption(
         booru_client::BooruRating::Explicit => "🔴",
     };
 
-    let clean_base = base_url.trim_end_matches('/');
-    let post_url = format!("{}{}", clean_base, engine_type.post_path(post.id));
+    let post_url = booru_post_url(base_url, engine_type, post.id);
 
     let tag_list: Vec<&str> = post.tags.split_whitespace().take(5).collect();
     let tags_display = if tag_list.is_empty() {
@@ -139,6 +245,354 @@ fn build_standard_caption(prefix: &str, illust: &Illust, title_suffix: &str) ->
     )
 }
 
+/// Caption text with its formatting as Telegram entities, sent with `caption_entities`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityCaption {
+    pub text: String,
+    pub entities: Vec<MessageEntity>,
+}
+
+impl EntityCaption {
+    /// Append one rendered character and add its UTF-16 length to `len`
+    fn push(&mut self, c: char, len: &mut usize) {
+        self.text.push(c);
+        *len += c.len_utf16();
+    }
+}
+
+/// Formatting opened in a MarkdownV2 caption and not closed yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Markup {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    /// `[text](url)`
+    Link,
+    /// `![emoji](tg://emoji?id=...)`
+    CustomEmoji,
+}
+
+/// Convert a MarkdownV2 caption into plain text and entities
+///
+/// Covers the markup captions are built with: escapes, `*bold*`, `_italic_`,
+/// `__underline__`, `~strikethrough~`, `||spoiler||`, inline code and links, with
+/// `![👍](tg://emoji?id=...)` read as a custom emoji. Markup still open at the end
+/// gives no entity.
+pub fn markdown_to_entities(caption: &str) -> EntityCaption {
+    let mut out = EntityCaption::default();
+    // UTF-16 length of `out.text`
+    let mut len = 0;
+    let mut open: Vec<(Markup, usize)> = Vec::new();
+    let mut rest = caption;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let markup = match c {
+            '\\' => {
+                if let Some(escaped) = rest.chars().next() {
+                    out.push(escaped, &mut len);
+                    rest = &rest[escaped.len_utf8()..];
+                }
+                continue;
+            }
+            '`' => {
+                let start = len;
+                while let Some(c) = rest.chars().next() {
+                    rest = &rest[c.len_utf8()..];
+                    match c {
+                        '`' => break,
+                        '\\' => {
+                            if let Some(escaped) = rest.chars().next() {
+                                out.push(escaped, &mut len);
+                                rest = &rest[escaped.len_utf8()..];
+                            }
+                        }
+                        _ => out.push(c, &mut len),
+                    }
+                }
+                if len > start {
+                    out.entities.push(MessageEntity::code(start, len - start));
+                }
+                continue;
+            }
+            ']' if rest.starts_with('(') => {
+                let Some(pos) = open
+                    .iter()
+                    .rposition(|(markup, _)| matches!(markup, Markup::Link | Markup::CustomEmoji))
+                else {
+                    out.push(c, &mut len);
+                    continue;
+                };
+                let (markup, start) = open[pos];
+                // Markup opened inside the link text and never closed is dropped
+                open.truncate(pos);
+
+                let mut url = String::new();
+                rest = &rest[1..];
+                while let Some(c) = rest.chars().next() {
+                    rest = &rest[c.len_utf8()..];
+                    match c {
+                        ')' => break,
+                        '\\' => {
+                            if let Some(escaped) = rest.chars().next() {
+                                url.push(escaped);
+                                rest = &rest[escaped.len_utf8()..];
+                            }
+                        }
+                        _ => url.push(c),
+                    }
+                }
+
+                if len > start {
+                    let entity = match markup {
+                        Markup::CustomEmoji => url.strip_prefix("tg://emoji?id=").map(|id| {
+                            MessageEntity::custom_emoji(
+                                CustomEmojiId(id.to_string()),
+                                start,
+                                len - start,
+                            )
+                        }),
+                        _ => url::Url::parse(&url)
+                            .ok()
+                            .map(|url| MessageEntity::text_link(url, start, len - start)),
+                    };
+                    out.entities.extend(entity);
+                }
+                continue;
+            }
+            '[' => Markup::Link,
+            '!' if rest.starts_with('[') => {
+                rest = &rest[1..];
+                Markup::CustomEmoji
+            }
+            '*' => Markup::Bold,
+            '~' => Markup::Strikethrough,
+            '_' if rest.starts_with('_') => {
+                rest = &rest[1..];
+                Markup::Underline
+            }
+            '_' => Markup::Italic,
+            '|' if rest.starts_with('|') => {
+                rest = &rest[1..];
+                Markup::Spoiler
+            }
+            _ => {
+                out.push(c, &mut len);
+                continue;
+            }
+        };
+
+        if matches!(markup, Markup::Link | Markup::CustomEmoji) {
+            open.push((markup, len));
+            continue;
+        }
+        match open.iter().rposition(|(open, _)| *open == markup) {
+            Some(pos) => {
+                let (_, start) = open.remove(pos);
+                if len > start {
+                    let length = len - start;
+                    out.entities.push(match markup {
+                        Markup::Bold => MessageEntity::bold(start, length),
+                        Markup::Italic => MessageEntity::italic(start, length),
+                        Markup::Underline => MessageEntity::underline(start, length),
+                        Markup::Strikethrough => MessageEntity::strikethrough(start, length),
+                        _ => MessageEntity::spoiler(start, length),
+                    });
+                }
+            }
+            None => open.push((markup, len)),
+        }
+    }
+
+    out.entities.sort_by_key(|entity| entity.offset);
+    out
+}
+
+/// Mark the configured emoji in `caption` as custom emoji entities
+///
+/// Telegram shows no custom emoji inside code or links, so emoji there stay plain.
+/// Entries whose ID is not numeric are skipped.
+pub fn apply_custom_emoji(caption: &mut EntityCaption, emojis: &HashMap<String, String>) {
+    let mut replacements: Vec<(&str, &str)> = emojis
+        .iter()
+        .filter(|(emoji, id)| {
+            !emoji.is_empty() && !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+        })
+        .map(|(emoji, id)| (emoji.as_str(), id.as_str()))
+        .collect();
+    if replacements.is_empty() {
+        return;
+    }
+    // Longest match first so "🎞️" (with variation selector) wins over "🎞"
+    replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+    let excluded: Vec<Range<usize>> = caption
+        .entities
+        .iter()
+        .filter(|entity| {
+            matches!(
+                entity.kind,
+                MessageEntityKind::Code
+                    | MessageEntityKind::Pre { .. }
+                    | MessageEntityKind::TextLink { .. }
+                    | MessageEntityKind::CustomEmoji { .. }
+            )
+        })
+        .map(|entity| entity.offset..entity.offset + entity.length)
+        .collect();
+
+    let mut added = Vec::new();
+    let mut offset = 0;
+    let mut rest = caption.text.as_str();
+    while let Some(c) = rest.chars().next() {
+        if !excluded.iter().any(|range| range.contains(&offset)) {
+            if let Some((emoji, id)) = replacements
+                .iter()
+                .find(|(emoji, _)| rest.starts_with(emoji))
+            {
+                let length = emoji.encode_utf16().count();
+                added.push(MessageEntity::custom_emoji(
+                    CustomEmojiId(id.to_string()),
+                    offset,
+                    length,
+                ));
+                offset += length;
+                rest = &rest[emoji.len()..];
+                continue;
+            }
+        }
+        offset += c.len_utf16();
+        rest = &rest[c.len_utf8()..];
+    }
+
+    caption.entities.extend(added);
+    caption.entities.sort_by_key(|entity| entity.offset);
+}
+
+/// A run of rendered caption text and the URL it links to, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionSpan {
+    pub text: String,
+    pub url: Option<String>,
+}
+
+/// Parse a MarkdownV2 caption into the text Telegram renders
+///
+/// Escapes and formatting markers are dropped and link text keeps its URL. Custom
+/// emoji (`![👍](tg://emoji?id=...)`) render as their fallback emoji without a URL.
+pub fn caption_spans(caption: &str) -> Vec<CaptionSpan> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut link_text: Option<String> = None;
+    let mut link_url: Option<String> = None;
+    let mut in_code = false;
+    let mut chars = caption.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(url) = link_url.as_mut() {
+            match c {
+                '\\' => url.extend(chars.next()),
+                ')' => {
+                    let url = link_url.take().unwrap_or_default();
+                    let linked = link_text.take().unwrap_or_default();
+                    if url.starts_with("tg://") {
+                        text.push_str(&linked);
+                    } else {
+                        if !text.is_empty() {
+                            spans.push(CaptionSpan {
+                                text: std::mem::take(&mut text),
+                                url: None,
+                            });
+                        }
+                        spans.push(CaptionSpan {
+                            text: linked,
+                            url: Some(url),
+                        });
+                    }
+                }
+                _ => url.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '\\' => link_text.as_mut().unwrap_or(&mut text).extend(chars.next()),
+            '`' => in_code = !in_code,
+            _ if in_code => link_text.as_mut().unwrap_or(&mut text).push(c),
+            '[' if link_text.is_none() => link_text = Some(String::new()),
+            ']' if link_text.is_some() && chars.peek() == Some(&'(') => {
+                chars.next();
+                link_url = Some(String::new());
+            }
+            '*' | '_' | '~' | '|' => {}
+            '!' if chars.peek() == Some(&'[') => {}
+            _ => link_text.as_mut().unwrap_or(&mut text).push(c),
+        }
+    }
+
+    // An unterminated link renders its text as is
+    text.push_str(&link_text.unwrap_or_default());
+    if !text.is_empty() {
+        spans.push(CaptionSpan { text, url: None });
+    }
+    spans
+}
+
+/// Rendered length of a MarkdownV2 caption in UTF-16 code units, as Telegram counts it
+pub fn caption_len(caption: &str) -> usize {
+    caption_spans(caption)
+        .iter()
+        .map(|span| span.text.encode_utf16().count())
+        .sum()
+}
+
+/// Shorten a MarkdownV2 caption to at most `max_len` rendered units, then append `suffix`
+///
+/// Cuts only between lines or between hashtags so entities and escapes stay balanced,
+/// which drops trailing tags first. `suffix` is MarkdownV2 and counts toward `max_len`.
+/// Captions that already fit are returned unchanged.
+pub fn truncate_caption(caption: &str, max_len: usize, suffix: &str) -> String {
+    if caption_len(caption) <= max_len {
+        return caption.to_owned();
+    }
+
+    let budget = max_len.saturating_sub(caption_len(suffix));
+    let mut out = String::new();
+    for (index, line) in caption.split('\n').enumerate() {
+        let separator = if index == 0 { "" } else { "\n" };
+        let candidate = format!("{}{}{}", out, separator, line);
+        if caption_len(&candidate) <= budget {
+            out = candidate;
+            continue;
+        }
+
+        let is_hashtag_line = line
+            .split_whitespace()
+            .all(|token| token.starts_with("\\#"));
+        if is_hashtag_line {
+            let mut tags: Vec<&str> = Vec::new();
+            for token in line.split_whitespace() {
+                tags.push(token);
+                let candidate = format!("{}{}{}", out, separator, tags.join("  "));
+                if caption_len(&candidate) > budget {
+                    tags.pop();
+                    break;
+                }
+            }
+            if !tags.is_empty() {
+                out = format!("{}{}{}", out, separator, tags.join("  "));
+            }
+        }
+        break;
+    }
+
+    let mut out = out.trim_end_matches('\n').to_owned();
+    out.push_str(suffix);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +694,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_restricted_caption_appends_notice() {
+        let illust = make_illust("illust", "Hidden", "Author", 1, 123, 45, &[]);
+
+        assert_eq!(
+            build_restricted_caption(&illust, AccessRestriction::MyPixiv),
+            "🎨 Hidden\nby *Author* \\(ID: `67890`\\)\n\n👀 123 \\| ❤️ 45 \\| 🔗 [来源](https://pixiv\\.net/artworks/12345)\n\n🔒 仅好P友可见的作品，无法获取预览"
+        );
+    }
+
     #[test]
     fn build_ugoira_caption_matches_golden_output() {
         let illust = make_illust("ugoira", "Animated", "Author", 1, 123, 45, &[]);
@@ -260,21 +724,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_album_caption_lists_each_work() {
+        let single = make_illust("illust", "One.", "Author", 1, 123, 45, &[]);
+        let multi = make_illust("illust", "Two", "Author", 3, 123, 45, &[]);
+
+        assert_eq!(
+            build_album_caption(&[&single, &multi]),
+            "🎨 *Author* \\- 2 个新作品\n\n1\\. [One\\.](https://pixiv\\.net/artworks/12345)\n2\\. [Two](https://pixiv\\.net/artworks/12345) \\(3 photos\\)"
+        );
+    }
+
     #[test]
     fn build_ranking_title_matches_golden_output() {
         assert_eq!(
-            build_ranking_title("day_ai", 2),
+            build_ranking_title("day_ai", None, 2),
             "📊 *DAY AI Ranking* \\- 2 new\\!\n\n"
         );
+        assert_eq!(
+            build_ranking_title("day", Some("2026-10-15"), 3),
+            "📊 *DAY Ranking* \\(2026\\-10\\-15\\) \\- 3 new\\!\n\n"
+        );
     }
 
     #[test]
     fn build_ranking_caption_for_first_item_prepends_title_once() {
         let illust = make_illust("illust", "Still", "Author", 1, 123, 45, &[]);
-        let title = build_ranking_title("day", 2);
+        let title = build_ranking_title("day", None, 2);
 
         assert_eq!(
-            build_ranking_caption(&title, 0, &illust),
+            build_ranking_caption(&title, 0, None, &illust),
             "📊 *DAY Ranking* \\- 2 new\\!\n\nStill\nby *Author* \\(ID: `67890`\\)\n\n❤️ 45 \\| 🔗 [来源](https://pixiv\\.net/artworks/12345)"
         );
     }
@@ -284,11 +763,25 @@ mod tests {
         let illust = make_illust("ugoira", "Animated", "Author", 1, 123, 45, &[]);
 
         assert_eq!(
-            build_ranking_caption("ignored", 1, &illust),
+            build_ranking_caption("ignored", 1, None, &illust),
             "🎞️ Animated\nby *Author* \\(ID: `67890`\\)\n\n❤️ 45 \\| 🔗 [来源](https://pixiv\\.net/artworks/12345)"
         );
     }
 
+    #[test]
+    fn build_ranking_caption_prefixes_rank_label() {
+        let illust = make_illust("ugoira", "Animated", "Author", 1, 123, 45, &[]);
+
+        assert_eq!(format_rank(1, true), "🥇");
+        assert_eq!(format_rank(3, true), "🥉");
+        assert_eq!(format_rank(1, false), "\\#1");
+        assert_eq!(format_rank(12, true), "\\#12");
+        assert!(
+            build_ranking_caption("ignored", 1, Some(&format_rank(4, true)), &illust)
+                .starts_with("\\#4 🎞️ Animated\nby *Author*")
+        );
+    }
+
     #[test]
     fn caption_builders_escape_markdown_sensitive_text() {
         let illust = make_illust("illust", "_[]()!", "A_B(C)!", 1, 123, 45, &["tag(test)"]);
@@ -411,4 +904,158 @@ mod tests {
         // The `-` in `-5` must be escaped for MarkdownV2
         assert!(caption.contains("\\-5"));
     }
+
+    fn emoji_map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(emoji, id)| (emoji.to_string(), id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn markdown_to_entities_converts_caption_markup() {
+        let caption = "🎨 *a\\.b* \\(ID: `1`\\)\n🔗 [来源](https://pixiv\\.net/artworks/1)";
+
+        let converted = markdown_to_entities(caption);
+        assert_eq!(converted.text, "🎨 a.b (ID: 1)\n🔗 来源");
+        assert_eq!(
+            converted.entities,
+            vec![
+                MessageEntity::bold(3, 3),
+                MessageEntity::code(12, 1),
+                MessageEntity::text_link(
+                    url::Url::parse("https://pixiv.net/artworks/1").unwrap(),
+                    18,
+                    2
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_to_entities_reads_nested_and_paired_markers() {
+        let converted = markdown_to_entities("__u__ _i_ ~s~ ||*p*|| ![🎨](tg://emoji?id=7)");
+
+        assert_eq!(converted.text, "u i s p 🎨");
+        assert_eq!(
+            converted.entities,
+            vec![
+                MessageEntity::underline(0, 1),
+                MessageEntity::italic(2, 1),
+                MessageEntity::strikethrough(4, 1),
+                MessageEntity::bold(6, 1),
+                MessageEntity::spoiler(6, 1),
+                MessageEntity::custom_emoji(CustomEmojiId("7".to_string()), 8, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_to_entities_renders_built_captions() {
+        let illust = make_illust("illust", "a.b (c)", "A_*", 3, 10, 2, &["原神", "R-18"]);
+        let caption = build_illust_caption(&illust);
+
+        let converted = markdown_to_entities(&caption);
+        assert_eq!(converted.text.encode_utf16().count(), caption_len(&caption));
+        assert!(!converted.text.contains('\\'));
+    }
+
+    #[test]
+    fn apply_custom_emoji_marks_plain_emoji() {
+        let emojis = emoji_map(&[("🎨", "111"), ("❤️", "222"), ("#️⃣", "333")]);
+        let mut caption = markdown_to_entities("🎨 *x* \\#️⃣\n❤️ 5");
+
+        apply_custom_emoji(&mut caption, &emojis);
+        assert_eq!(caption.text, "🎨 x #️⃣\n❤️ 5");
+        assert_eq!(
+            caption.entities,
+            vec![
+                MessageEntity::custom_emoji(CustomEmojiId("111".to_string()), 0, 2),
+                MessageEntity::bold(3, 1),
+                MessageEntity::custom_emoji(CustomEmojiId("333".to_string()), 5, 3),
+                MessageEntity::custom_emoji(CustomEmojiId("222".to_string()), 9, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_custom_emoji_skips_code_and_links() {
+        let emojis = emoji_map(&[("🎨", "111")]);
+        let mut caption = markdown_to_entities("`🎨` [🎨 link](https://example\\.com/) 🎨");
+        let plain_entities = caption.entities.clone();
+
+        apply_custom_emoji(&mut caption, &emojis);
+        assert_eq!(caption.entities.len(), plain_entities.len() + 1);
+        assert_eq!(
+            caption.entities.last(),
+            Some(&MessageEntity::custom_emoji(
+                CustomEmojiId("111".to_string()),
+                11,
+                2
+            ))
+        );
+    }
+
+    #[test]
+    fn apply_custom_emoji_prefers_longest_match() {
+        let emojis = emoji_map(&[("🎞", "1"), ("🎞️", "2")]);
+        let mut caption = markdown_to_entities("🎞️ ugoira");
+
+        apply_custom_emoji(&mut caption, &emojis);
+        assert_eq!(
+            caption.entities,
+            vec![MessageEntity::custom_emoji(
+                CustomEmojiId("2".to_string()),
+                0,
+                3
+            )]
+        );
+    }
+
+    #[test]
+    fn apply_custom_emoji_skips_invalid_ids() {
+        let emojis = emoji_map(&[("🎨", "2) x"), ("❤️", "")]);
+        let mut caption = markdown_to_entities("🎨 ❤️");
+
+        apply_custom_emoji(&mut caption, &emojis);
+        assert!(caption.entities.is_empty());
+    }
+
+    #[test]
+    fn caption_spans_render_escapes_links_and_custom_emoji() {
+        let caption =
+            "![🎨](tg://emoji?id=1) *a\\.b* \\(ID: `1`\\)\n🔗 [来源](https://pixiv\\.net/artworks/1)";
+
+        let spans = caption_spans(caption);
+        assert_eq!(
+            spans,
+            vec![
+                CaptionSpan {
+                    text: "🎨 a.b (ID: 1)\n🔗 ".to_string(),
+                    url: None,
+                },
+                CaptionSpan {
+                    text: "来源".to_string(),
+                    url: Some("https://pixiv.net/artworks/1".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            caption_len(caption),
+            "🎨 a.b (ID: 1)\n🔗 来源".encode_utf16().count()
+        );
+    }
+
+    #[test]
+    fn truncate_caption_drops_trailing_tags_first() {
+        let caption = "title\nby *A*\n\n\\#tag1  \\#tag2  \\#tag3";
+        assert_eq!(caption_len(caption), 31);
+        assert_eq!(truncate_caption(caption, 31, "\n…"), caption);
+
+        assert_eq!(
+            truncate_caption(caption, 25, "\n…"),
+            "title\nby *A*\n\n\\#tag1\n…"
+        );
+        assert_eq!(truncate_caption(caption, 15, "\n…"), "title\nby *A*\n…");
+    }
 }
@@ -135,6 +135,13 @@ pub trait BotChannelExt {
     /// Returns `Ok(true)` if the bot is an administrator with posting rights or the owner.
     async fn can_post_to_channel(&self, channel: &ChannelIdentifier) -> Result<bool, String>;
 
+    /// Get a user's membership status in a channel.
+    async fn user_channel_status(
+        &self,
+        channel: &ChannelIdentifier,
+        user_id: UserId,
+    ) -> Result<ChatMemberStatus, String>;
+
     /// Check if a user is an administrator of a channel.
     ///
     /// Returns `Ok(true)` if the user is either the owner or an administrator.
@@ -211,29 +218,14 @@ where
         }
     }
 
-    async fn is_user_channel_admin(
+    async fn user_channel_status(
         &self,
         channel: &ChannelIdentifier,
         user_id: UserId,
-    ) -> Result<bool, String> {
+    ) -> Result<ChatMemberStatus, String> {
         let recipient = channel.to_recipient();
         match self.get_chat_member(recipient, user_id).await {
-            Ok(member) => {
-                let is_admin = matches!(
-                    member.status(),
-                    ChatMemberStatus::Administrator | ChatMemberStatus::Owner
-                );
-
-                info!(
-                    "User {} in channel {:?}: status={:?}, is_admin={}",
-                    user_id,
-                    channel,
-                    member.status(),
-                    is_admin
-                );
-
-                Ok(is_admin)
-            }
+            Ok(member) => Ok(member.status()),
             Err(e) => {
                 tracing::error!(
                     "Failed to get user {} member status in channel {:?}: {:#}",
@@ -246,6 +238,25 @@ where
         }
     }
 
+    async fn is_user_channel_admin(
+        &self,
+        channel: &ChannelIdentifier,
+        user_id: UserId,
+    ) -> Result<bool, String> {
+        let status = self.user_channel_status(channel, user_id).await?;
+        let is_admin = matches!(
+            status,
+            ChatMemberStatus::Administrator | ChatMemberStatus::Owner
+        );
+
+        info!(
+            "User {} in channel {:?}: status={:?}, is_admin={}",
+            user_id, channel, status, is_admin
+        );
+
+        Ok(is_admin)
+    }
+
     async fn resolve_channel_id(&self, channel: &ChannelIdentifier) -> Result<ChatId, String> {
         match channel {
             ChannelIdentifier::Id(id) => Ok(*id),
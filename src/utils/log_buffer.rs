@@ -0,0 +1,155 @@
+//! In-memory copy of recent log lines, read by the owner's `/logs` command.
+
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Lines kept for `/logs`
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
+/// Lines a live tail may fall behind before skipping ahead
+const LIVE_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub text: String,
+}
+
+/// Ring buffer of formatted log lines, also broadcast to live tails.
+///
+/// Installed as a tracing layer next to the stdout and file layers.
+#[derive(Clone)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+    capacity: usize,
+    live: broadcast::Sender<LogLine>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(LOG_BUFFER_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        let (live, _) = broadcast::channel(LIVE_CHANNEL_CAPACITY);
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            live,
+        }
+    }
+
+    fn push(&self, line: LogLine) {
+        {
+            let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+            if lines.len() >= self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.clone());
+        }
+        // No receivers just means nobody is tailing
+        let _ = self.live.send(line);
+    }
+
+    /// The last `count` lines at `min_level` or more severe, oldest first
+    pub fn tail(&self, count: usize, min_level: Level) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tail: Vec<String> = lines
+            .iter()
+            .rev()
+            .filter(|line| line.level <= min_level)
+            .take(count)
+            .map(|line| line.text.clone())
+            .collect();
+        tail.reverse();
+        tail
+    }
+
+    /// Receive lines logged from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.live.subscribe()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = FieldFormatter::default();
+        event.record(&mut fields);
+
+        let text = format!(
+            "{} {:>5} {}: {}{}",
+            Local::now().format("%m-%d %H:%M:%S"),
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.extra
+        );
+        self.push(LogLine {
+            level: *metadata.level(),
+            text,
+        });
+    }
+}
+
+#[derive(Default)]
+struct FieldFormatter {
+    message: String,
+    extra: String,
+}
+
+impl Visit for FieldFormatter {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.extra, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.extra, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn log_buffer_keeps_recent_lines_by_level() {
+        let buffer = LogBuffer::new(3);
+        let subscriber = tracing_subscriber::registry().with(buffer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(chat = 42, "second");
+            tracing::error!("third");
+            tracing::info!("fourth");
+        });
+
+        let all = buffer.tail(10, Level::TRACE);
+        assert_eq!(all.len(), 3);
+        assert!(all[0].ends_with("second chat=42"));
+        assert!(all[2].ends_with("fourth"));
+
+        let warnings = buffer.tail(10, Level::WARN);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("ERROR"));
+        assert!(warnings[1].ends_with("third"));
+
+        assert_eq!(buffer.tail(1, Level::TRACE).len(), 1);
+    }
+}
@@ -1,6 +1,11 @@
+pub mod archive_upload;
 pub mod args;
 pub mod caption;
 pub mod channel;
 pub mod duration;
+pub mod log_buffer;
 pub mod sensitive;
 pub mod tag;
+pub mod time;
+pub mod version;
+pub mod zip_workspace;
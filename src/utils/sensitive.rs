@@ -31,6 +31,16 @@ pub fn should_blur(chat: &chats::Model, illust: &Illust) -> bool {
     chat.blur_sensitive_tags && contains_sensitive_tags(illust, get_chat_sensitive_tags(chat))
 }
 
+/// Whether the work is R-18/R-18G according to Pixiv (`x_restrict`), not tags
+pub fn is_r18(illust: &Illust) -> bool {
+    illust.x_restrict > 0
+}
+
+/// Whether the chat has R-18 disabled and this work must not be delivered
+pub fn is_r18_blocked(chat: &chats::Model, illust: &Illust) -> bool {
+    !chat.allow_r18 && is_r18(illust)
+}
+
 pub fn should_blur_booru(chat: &chats::Model, tags: &str, rating: BooruRating) -> bool {
     if !chat.blur_sensitive_tags {
         return false;
@@ -53,7 +63,7 @@ fn tags_match_sensitive(chat: &chats::Model, tags: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{contains_sensitive_tags, should_blur, should_blur_booru};
+    use super::{contains_sensitive_tags, is_r18_blocked, should_blur, should_blur_booru};
     use crate::db::entities::chats;
     use crate::db::types::Tags;
     use booru_client::BooruRating;
@@ -71,6 +81,15 @@ mod tests {
             sensitive_tags: Tags(sensitive_tags.iter().map(|s| s.to_string()).collect()),
             created_at: chrono::Utc::now().naive_utc(),
             allow_without_mention: false,
+            allow_r18: false,
+            send_original: false,
+            bot_id: None,
+            link_preview: true,
+            blocked_authors: Default::default(),
+            slow_mode_delay: None,
+            ai_filter: Default::default(),
+            default_filter: Default::default(),
+            notify_failures: false,
         }
     }
 
@@ -117,6 +136,19 @@ mod tests {
         .unwrap()
     }
 
+    #[test]
+    fn is_r18_blocked_uses_x_restrict_and_chat_setting() {
+        let mut illust = make_illust(&[]);
+        let mut chat = make_chat(true, &[]);
+        assert!(!is_r18_blocked(&chat, &illust));
+
+        illust.x_restrict = 1;
+        assert!(is_r18_blocked(&chat, &illust));
+
+        chat.allow_r18 = true;
+        assert!(!is_r18_blocked(&chat, &illust));
+    }
+
     #[test]
     fn contains_sensitive_tags_matches_normalized_tags() {
         let illust = make_illust(&["R-18"]);
@@ -1,5 +1,6 @@
 use regex::Regex;
-use std::sync::LazyLock;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
 
 /// Regex pattern to match non-word characters.
 /// The regex crate has Unicode support enabled by default, so \w matches Unicode word characters.
@@ -8,6 +9,10 @@ static NON_WORD_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"[^\w]").expect("BUG: Failed to compile hardcoded regex pattern [^\\w]")
 });
 
+/// Normalized alias -> normalized tag, loaded from the `tag_aliases` table at
+/// startup and replaced whenever `/tagalias` changes it
+static TAG_ALIASES: LazyLock<RwLock<HashMap<String, String>>> = LazyLock::new(Default::default);
+
 /// Remove non-word characters from a tag string using whitelist approach.
 /// Only keeps letters, numbers, underscores, and Unicode characters.
 /// Used by both normalize_tag and format_tags.
@@ -32,6 +37,50 @@ pub fn normalize_tag(tag: &str) -> String {
     remove_special_chars(tag).to_lowercase()
 }
 
+/// Replace the alias table consulted by [`canonical_tag`]
+pub fn set_tag_aliases(aliases: HashMap<String, String>) {
+    *TAG_ALIASES.write().unwrap_or_else(|e| e.into_inner()) = aliases;
+}
+
+/// Normalize a tag and resolve it through the alias table
+///
+/// Every spelling of a tag with aliases (e.g. "原神", "Genshin Impact", "genshin")
+/// yields the same value, so tag filters match any of them.
+pub fn canonical_tag(tag: &str) -> String {
+    let normalized = normalize_tag(tag);
+    TAG_ALIASES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&normalized)
+        .cloned()
+        .unwrap_or(normalized)
+}
+
+/// Parse a tag alias dataset into normalized `(tag, aliases)` groups
+///
+/// One group per line: the tag first, then its aliases, separated by commas or
+/// tabs. Blank lines and lines starting with `#` are skipped.
+///
+/// ```text
+/// 原神,genshin,Genshin Impact,げんしん
+/// ```
+pub fn parse_alias_dataset(text: &str) -> Vec<(String, Vec<String>)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut tags = line
+                .split([',', '\t'])
+                .map(normalize_tag)
+                .filter(|tag| !tag.is_empty());
+            let tag = tags.next()?;
+            let mut aliases: Vec<String> = tags.filter(|alias| *alias != tag).collect();
+            aliases.dedup();
+            (!aliases.is_empty()).then_some((tag, aliases))
+        })
+        .collect()
+}
+
 /// Extract tag names from tags and format for display
 ///
 /// Removes special characters that Telegram doesn't recognize in hashtags.
@@ -176,6 +225,23 @@ mod tests {
         assert_eq!(normalize_tag("r_18"), "r_18");
     }
 
+    #[test]
+    fn test_parse_alias_dataset() {
+        let groups = parse_alias_dataset(
+            "# tag,aliases\n原神,genshin,Genshin Impact\n\nR-18\tr18\tR_18\nlonely\n",
+        );
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "原神".to_string(),
+                    vec!["genshin".to_string(), "genshinimpact".to_string()]
+                ),
+                ("r18".to_string(), vec!["r_18".to_string()]),
+            ]
+        );
+    }
+
     #[test]
     fn test_normalize_tag_japanese_chars() {
         assert_eq!(normalize_tag("「テスト」"), "テスト");
@@ -0,0 +1,24 @@
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+
+/// Convert a stored timestamp (naive UTC) to the server's local time for display.
+pub fn to_local(utc: NaiveDateTime) -> NaiveDateTime {
+    Utc.from_utc_datetime(&utc)
+        .with_timezone(&Local)
+        .naive_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_local_applies_local_offset() {
+        let utc =
+            NaiveDateTime::parse_from_str("2026-01-15 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let offset = Local.offset_from_utc_datetime(&utc);
+        assert_eq!(
+            to_local(utc) - utc,
+            chrono::Duration::seconds(offset.local_minus_utc() as i64)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Local};
+
+/// Crate version from Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git commit hash the binary was built from (set by build.rs)
+pub const GIT_COMMIT: &str = env!("PIXIVBOT_GIT_COMMIT");
+
+const BUILD_TIMESTAMP: &str = env!("PIXIVBOT_BUILD_TIMESTAMP");
+
+/// Build time formatted in local time, or "unknown" if it was not recorded
+pub fn build_date() -> String {
+    BUILD_TIMESTAMP
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    // Ignore pre-release / build metadata suffixes such as `-beta.1`
+    let core = version.split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Whether a release tag (e.g. `v0.4.0`) is newer than `current`
+pub fn is_newer_version(tag: &str, current: &str) -> bool {
+    match (parse_version(tag), parse_version(current)) {
+        (Some(tag), Some(current)) => tag > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_newer_version_compares_numeric_parts() {
+        assert!(is_newer_version("v0.3.1", "0.3.0"));
+        assert!(is_newer_version("v0.10.0", "0.9.9"));
+        assert!(is_newer_version("1.0.0-beta.1", "0.3.0"));
+        assert!(!is_newer_version("v0.3.0", "0.3.0"));
+        assert!(!is_newer_version("v0.2.9", "0.3.0"));
+        assert!(!is_newer_version("nightly", "0.3.0"));
+    }
+}
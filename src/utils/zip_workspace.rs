@@ -0,0 +1,225 @@
+//! Work directory for the ZIPs built by `/download`, with a disk quota.
+//!
+//! ZIPs only live here until they are sent, so ZIPs left over from a previous
+//! run are removed on startup. Only files named like [`ZipWorkspace::zip_path`]
+//! are touched, so the directory may be shared with other data.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// Local file header and central directory entry of one ZIP member, with room for the name
+const ZIP_ENTRY_OVERHEAD: u64 = 512;
+
+/// End of central directory record and slack
+const ZIP_ARCHIVE_OVERHEAD: u64 = 4096;
+
+/// File name prefix of the ZIPs this workspace creates
+const ZIP_PREFIX: &str = "pixivbot_download_";
+
+/// Whether `path` names a ZIP created by [`ZipWorkspace::zip_path`]
+fn is_workspace_zip(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(ZIP_PREFIX) && name.ends_with(".zip"))
+}
+
+/// Estimated size of a ZIP of files with the given sizes
+///
+/// Images barely shrink under deflate, so the sum of the inputs is a close upper bound.
+pub fn estimate_zip_size(sizes: &[u64]) -> u64 {
+    sizes.iter().sum::<u64>() + ZIP_ENTRY_OVERHEAD * sizes.len() as u64 + ZIP_ARCHIVE_OVERHEAD
+}
+
+/// A ZIP was refused because the work directory would go over its quota
+#[derive(Debug)]
+pub struct WorkspaceFull {
+    pub needed: u64,
+    pub available: u64,
+}
+
+impl std::fmt::Display for WorkspaceFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ZIP work directory quota exceeded: {} bytes needed, {} bytes available",
+            self.needed, self.available
+        )
+    }
+}
+
+impl std::error::Error for WorkspaceFull {}
+
+pub struct ZipWorkspace {
+    dir: PathBuf,
+    /// 0 for no quota
+    quota_bytes: u64,
+    /// Bytes promised to ZIPs still being written
+    reserved: Arc<Mutex<u64>>,
+    next_id: AtomicU64,
+}
+
+/// Space held for a ZIP being written, released on drop
+#[derive(Debug)]
+pub struct ZipReservation {
+    reserved: Arc<Mutex<u64>>,
+    bytes: u64,
+}
+
+impl Drop for ZipReservation {
+    fn drop(&mut self) {
+        let mut reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+        *reserved = reserved.saturating_sub(self.bytes);
+    }
+}
+
+impl ZipWorkspace {
+    /// Create the work directory and clear ZIPs left over from a previous run
+    pub fn new(dir: impl Into<PathBuf>, quota_mb: u64) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create ZIP work directory {:?}", dir))?;
+
+        let mut removed = 0;
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read ZIP work directory {:?}", dir))?
+            .flatten()
+        {
+            let path = entry.path();
+            if !path.is_file() || !is_workspace_zip(&path) {
+                continue;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to remove leftover ZIP {:?}: {:#}", path, e),
+            }
+        }
+        if removed > 0 {
+            info!("Removed {} leftover ZIPs from {:?}", removed, dir);
+        }
+
+        Ok(Self {
+            dir,
+            quota_bytes: quota_mb.saturating_mul(1024 * 1024),
+            reserved: Arc::new(Mutex::new(0)),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// A path for a new ZIP, unique within this run
+    pub fn zip_path(&self) -> PathBuf {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.dir.join(format!(
+            "{}{}_{}.zip",
+            ZIP_PREFIX,
+            Local::now().format("%Y%m%d_%H%M%S"),
+            id
+        ))
+    }
+
+    /// Hold `bytes` of the quota for a ZIP about to be written
+    ///
+    /// Fails with [`WorkspaceFull`] when the ZIPs already in the directory plus
+    /// other pending ZIPs leave too little room.
+    pub async fn reserve(&self, bytes: u64) -> Result<ZipReservation> {
+        if self.quota_bytes > 0 {
+            let used = dir_size(&self.dir).await?;
+            let mut reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+            let available = self.quota_bytes.saturating_sub(used + *reserved);
+            if bytes > available {
+                return Err(WorkspaceFull {
+                    needed: bytes,
+                    available,
+                }
+                .into());
+            }
+            *reserved += bytes;
+        }
+        Ok(ZipReservation {
+            reserved: self.reserved.clone(),
+            bytes: if self.quota_bytes > 0 { bytes } else { 0 },
+        })
+    }
+}
+
+/// Total size of the workspace ZIPs directly inside `dir`
+async fn dir_size(dir: &Path) -> Result<u64> {
+    let mut total = 0;
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read ZIP work directory {:?}", dir))?;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .context("Failed to read ZIP work directory entry")?
+    {
+        if !is_workspace_zip(&entry.path()) {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata().await {
+            if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reserve_respects_quota_and_releases_on_drop() {
+        let temp = tempfile::tempdir().unwrap();
+        let leftover = temp.path().join("pixivbot_download_20260101_000000_0.zip");
+        std::fs::write(&leftover, b"old").unwrap();
+
+        let workspace = ZipWorkspace::new(temp.path(), 1).unwrap();
+        assert!(!leftover.exists());
+
+        let first = workspace.reserve(600 * 1024).await.unwrap();
+        let error = workspace.reserve(600 * 1024).await.unwrap_err();
+        let full = error.downcast_ref::<WorkspaceFull>().unwrap();
+        assert_eq!(full.available, 424 * 1024);
+
+        drop(first);
+        assert!(workspace.reserve(600 * 1024).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn startup_keeps_files_it_did_not_create() {
+        let temp = tempfile::tempdir().unwrap();
+        let other_zip = temp.path().join("backup.zip");
+        let database = temp.path().join("pixivbot.db");
+        std::fs::write(&other_zip, vec![0; 600 * 1024]).unwrap();
+        std::fs::write(&database, b"data").unwrap();
+
+        let workspace = ZipWorkspace::new(temp.path(), 1).unwrap();
+        assert!(other_zip.exists());
+        assert!(database.exists());
+
+        // Files of others don't count against the quota either
+        assert!(workspace.reserve(600 * 1024).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reserve_is_unlimited_without_quota() {
+        let temp = tempfile::tempdir().unwrap();
+        let workspace = ZipWorkspace::new(temp.path(), 0).unwrap();
+        assert!(workspace.reserve(u64::MAX / 2).await.is_ok());
+        assert_ne!(workspace.zip_path(), workspace.zip_path());
+    }
+
+    #[test]
+    fn estimate_zip_size_adds_overhead_per_entry() {
+        assert_eq!(estimate_zip_size(&[]), ZIP_ARCHIVE_OVERHEAD);
+        assert_eq!(
+            estimate_zip_size(&[100, 200]),
+            300 + 2 * ZIP_ENTRY_OVERHEAD + ZIP_ARCHIVE_OVERHEAD
+        );
+    }
+}